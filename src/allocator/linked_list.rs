@@ -0,0 +1,210 @@
+use alloc::alloc::{GlobalAlloc, Layout};
+use core::mem;
+use core::ptr;
+use super::Locked;
+
+/// Header written at the start of every free block. Free blocks form a list
+/// sorted by address, so a freed block can be merged with both neighbours.
+struct FreeBlock {
+    size: usize,
+    next: *mut FreeBlock,
+}
+
+/// Every block, free or allocated, is a multiple of this and at least this big,
+/// so any block can hold a `FreeBlock` once it is freed.
+const BLOCK_ALIGN: usize = mem::size_of::<FreeBlock>();
+
+/// A first-fit free-list allocator over the kernel heap. Freed blocks are
+/// merged with adjacent free blocks, and when no block fits the heap grows
+/// by mapping more pages (see `super::map_heap_pages`), up to `HEAP_MAX_SIZE`.
+pub struct LinkedListAllocator {
+    head: *mut FreeBlock,
+    heap_start: usize,
+    /// End of the mapped part of the heap.
+    heap_end: usize,
+    /// Bytes currently handed out, block rounding included.
+    used: usize,
+    /// Bytes handed out and given back since boot.
+    allocated_total: u64,
+    freed_total: u64,
+}
+
+// The raw list pointers are only touched under the `Locked` mutex
+unsafe impl Send for LinkedListAllocator {}
+
+impl LinkedListAllocator {
+    /// Creates an allocator with no memory; `init` hands it the heap.
+    pub const fn new() -> Self {
+        LinkedListAllocator {
+            head: ptr::null_mut(),
+            heap_start: 0,
+            heap_end: 0,
+            used: 0,
+            allocated_total: 0,
+            freed_total: 0,
+        }
+    }
+
+    /// Initializes the allocator with the given (mapped, unused) heap bounds.
+    ///
+    /// This method is unsafe because the caller must ensure that the given
+    /// memory range is unused. Also, this method must be called only once.
+    pub unsafe fn init(&mut self, heap_start: usize, heap_size: usize) {
+        self.heap_start = heap_start;
+        self.heap_end = heap_start + heap_size;
+        self.free_region(heap_start, heap_size);
+    }
+
+    /// Bytes currently allocated.
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Bytes allocated and freed since boot: (allocated, freed).
+    pub fn totals(&self) -> (u64, u64) {
+        (self.allocated_total, self.freed_total)
+    }
+
+    /// Bytes of heap mapped so far.
+    pub fn size(&self) -> usize {
+        self.heap_end - self.heap_start
+    }
+
+    /// Number of free blocks and the largest of them, for fragmentation checks.
+    pub fn free_blocks(&self) -> (usize, usize) {
+        let (mut count, mut largest) = (0, 0);
+        let mut block = self.head;
+        while !block.is_null() {
+            unsafe {
+                count += 1;
+                largest = largest.max((*block).size);
+                block = (*block).next;
+            }
+        }
+        (count, largest)
+    }
+
+    /// Block size used for `layout`: the same on alloc and dealloc.
+    fn block_size(layout: &Layout) -> usize {
+        align_up(layout.size().max(BLOCK_ALIGN), BLOCK_ALIGN)
+    }
+
+    /// Put `[addr, addr + size)` on the free list, merging it with the free
+    /// blocks directly before and after it.
+    unsafe fn free_region(&mut self, addr: usize, size: usize) {
+        // Find the last free block below `addr`
+        let mut prev: *mut FreeBlock = ptr::null_mut();
+        let mut next = self.head;
+        while !next.is_null() && (next as usize) < addr {
+            prev = next;
+            next = (*next).next;
+        }
+
+        let block = addr as *mut FreeBlock;
+        block.write(FreeBlock { size, next });
+        if !next.is_null() && addr + size == next as usize {
+            (*block).size += (*next).size;
+            (*block).next = (*next).next;
+        }
+
+        if prev.is_null() {
+            self.head = block;
+        } else if prev as usize + (*prev).size == addr {
+            (*prev).size += (*block).size;
+            (*prev).next = (*block).next;
+        } else {
+            (*prev).next = block;
+        }
+    }
+
+    /// Carve `size` bytes aligned to `align` out of the first free block that
+    /// has room. A gap left in front or behind goes back on the list, so it
+    /// must either be empty or big enough to hold a `FreeBlock`.
+    unsafe fn take_block(&mut self, size: usize, align: usize) -> Option<usize> {
+        let mut prev: *mut FreeBlock = ptr::null_mut();
+        let mut block = self.head;
+        while !block.is_null() {
+            let start = block as usize;
+            let end = start + (*block).size;
+            let mut alloc_start = align_up(start, align);
+            if alloc_start != start && alloc_start - start < BLOCK_ALIGN {
+                alloc_start = align_up(start + BLOCK_ALIGN, align);
+            }
+            let alloc_end = alloc_start.checked_add(size)?;
+            let tail = end.saturating_sub(alloc_end);
+
+            if alloc_end <= end && (tail == 0 || tail >= BLOCK_ALIGN) {
+                // Unlink, then return the unused front and tail
+                let next = (*block).next;
+                if prev.is_null() {
+                    self.head = next;
+                } else {
+                    (*prev).next = next;
+                }
+                if alloc_start > start {
+                    self.free_region(start, alloc_start - start);
+                }
+                if tail > 0 {
+                    self.free_region(alloc_end, tail);
+                }
+                return Some(alloc_start);
+            }
+            prev = block;
+            block = (*block).next;
+        }
+        None
+    }
+
+    /// Map new pages at the end of the heap, enough to fit `size` bytes at
+    /// `align` if possible. Returns false if nothing could be mapped: the
+    /// heap is at `HEAP_MAX_SIZE` or physical frames ran out.
+    unsafe fn grow(&mut self, size: usize, align: usize) -> bool {
+        let wanted = align_up(size + align, 4096).max(super::HEAP_GROW_STEP);
+        let room = super::HEAP_START + super::HEAP_MAX_SIZE - self.heap_end;
+        let mapped = super::map_heap_pages(self.heap_end, wanted.min(room));
+        if mapped == 0 {
+            return false;
+        }
+        let start = self.heap_end;
+        self.heap_end += mapped;
+        self.free_region(start, mapped);
+        true
+    }
+}
+
+unsafe impl GlobalAlloc for Locked<LinkedListAllocator> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let size = LinkedListAllocator::block_size(&layout);
+        let align = layout.align().max(BLOCK_ALIGN);
+        // A task switch while the lock is held would leave every other task spinning
+        x86_64::instructions::interrupts::without_interrupts(|| {
+            let mut heap = self.lock();
+            let addr = match heap.take_block(size, align) {
+                Some(addr) => addr,
+                None if heap.grow(size, align) => match heap.take_block(size, align) {
+                    Some(addr) => addr,
+                    None => return ptr::null_mut(),
+                },
+                None => return ptr::null_mut(),
+            };
+            heap.used += size;
+            heap.allocated_total += size as u64;
+            addr as *mut u8
+        })
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let size = LinkedListAllocator::block_size(&layout);
+        x86_64::instructions::interrupts::without_interrupts(|| {
+            let mut heap = self.lock();
+            heap.free_region(ptr as usize, size);
+            heap.used -= size;
+            heap.freed_total += size as u64;
+        });
+    }
+}
+
+/// Align a given address upwards to alignment `align`.
+fn align_up(addr: usize, align: usize) -> usize {
+    (addr + align - 1) & !(align - 1)
+}
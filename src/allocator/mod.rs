@@ -1,4 +1,5 @@
-pub mod bump;
+pub mod linked_list;
+pub mod slab;
 
 use x86_64::{
     structures::paging::{
@@ -7,21 +8,44 @@ use x86_64::{
     VirtAddr,
 };
 
-use bump::{BumpAllocator, Locked};
+use linked_list::LinkedListAllocator;
 
 #[global_allocator]
-static ALLOCATOR: Locked<BumpAllocator> = Locked::new(BumpAllocator::new());
+static ALLOCATOR: Locked<LinkedListAllocator> = Locked::new(LinkedListAllocator::new());
 
+/// The kernel heap lives in `[HEAP_START, HEAP_START + HEAP_MAX_SIZE)`.
+/// `HEAP_SIZE` bytes are mapped at boot; the rest is mapped on demand, at
+/// least `HEAP_GROW_STEP` at a time, as the allocator runs out of room.
+/// The whole range sits in one P4 slot that every address space shares,
+/// so pages mapped later are visible in every process too.
 pub const HEAP_START: usize = 0x_4444_4444_0000;
 pub const HEAP_SIZE: usize = 100 * 1024; // 100 KiB
+pub const HEAP_MAX_SIZE: usize = 16 * 1024 * 1024; // 16 MiB
+const HEAP_GROW_STEP: usize = 64 * 1024;
 
 pub fn init_heap(
     mapper: &mut impl Mapper<Size4KiB>,
     frame_allocator: &mut impl FrameAllocator<Size4KiB>,
+) -> Result<(), MapToError<Size4KiB>> {
+    map_pages(mapper, frame_allocator, HEAP_START, HEAP_SIZE)?;
+
+    unsafe {
+        ALLOCATOR.lock().init(HEAP_START, HEAP_SIZE);
+    }
+
+    Ok(())
+}
+
+/// Map `[start, start + size)` to fresh frames, kernel-only and writable.
+fn map_pages(
+    mapper: &mut impl Mapper<Size4KiB>,
+    frame_allocator: &mut impl FrameAllocator<Size4KiB>,
+    start: usize,
+    size: usize,
 ) -> Result<(), MapToError<Size4KiB>> {
     let page_range = {
-        let heap_start = VirtAddr::new(HEAP_START as u64);
-        let heap_end = heap_start + HEAP_SIZE - 1u64;
+        let heap_start = VirtAddr::new(start as u64);
+        let heap_end = heap_start + size - 1u64;
         let heap_start_page = Page::containing_address(heap_start);
         let heap_end_page = Page::containing_address(heap_end);
         Page::range_inclusive(heap_start_page, heap_end_page)
@@ -36,12 +60,67 @@ pub fn init_heap(
             mapper.map_to(page, frame, flags, frame_allocator)?.flush();
         }
     }
+    Ok(())
+}
 
-    unsafe {
-        ALLOCATOR.lock().init(HEAP_START, HEAP_SIZE);
+/// Grow the heap: map up to `size` more bytes at `start`, the current end
+/// of the heap, and return how many were mapped. Called by the allocator
+/// with its lock held. The frame allocator is also only held with
+/// interrupts off, so no switched-out task can be sitting on it: a busy
+/// lock here means this very context allocated while holding it, which
+/// would deadlock on `lock()`, so it is only tried and nothing is mapped.
+fn map_heap_pages(start: usize, size: usize) -> usize {
+    let Some(mut frame_allocator) = crate::memory::FRAME_ALLOCATOR.try_lock() else {
+        return 0;
+    };
+    let mut mapper = unsafe { crate::memory::paging::init_paging(VirtAddr::new(0)) };
+    // Page by page, so a shortage of frames still leaves a consistent heap end
+    let mut mapped = 0;
+    while mapped < size && map_pages(&mut mapper, &mut *frame_allocator, start + mapped, 4096).is_ok() {
+        mapped += 4096;
     }
+    mapped
+}
 
-    Ok(())
+/// Returns (bytes used, bytes mapped) of the kernel heap.
+pub fn heap_stats() -> (usize, usize) {
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        let heap = ALLOCATOR.lock();
+        (heap.used(), heap.size())
+    })
+}
+
+/// Whether some code is inside the heap lock right now. The lock is only
+/// ever taken with interrupts disabled, so from an exception handler a held
+/// lock belongs to the code that was interrupted.
+pub fn heap_locked() -> bool {
+    ALLOCATOR.inner.is_locked()
+}
+
+/// Returns (bytes allocated, bytes freed) on the kernel heap since boot.
+pub fn heap_totals() -> (u64, u64) {
+    x86_64::instructions::interrupts::without_interrupts(|| ALLOCATOR.lock().totals())
+}
+
+/// Returns (number of free blocks, largest free block) of the kernel heap.
+pub fn heap_fragments() -> (usize, usize) {
+    x86_64::instructions::interrupts::without_interrupts(|| ALLOCATOR.lock().free_blocks())
+}
+
+pub struct Locked<A> {
+    inner: spin::Mutex<A>,
+}
+
+impl<A> Locked<A> {
+    pub const fn new(inner: A) -> Self {
+        Locked {
+            inner: spin::Mutex::new(inner),
+        }
+    }
+
+    pub fn lock(&self) -> spin::MutexGuard<'_, A> {
+        self.inner.lock()
+    }
 }
 
 #[alloc_error_handler]
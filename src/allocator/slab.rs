@@ -0,0 +1,200 @@
+use alloc::alloc::{alloc, handle_alloc_error, Layout};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr::{self, NonNull};
+use spin::Mutex;
+
+/// Target size of one slab. Objects larger than this get a slab each.
+const SLAB_BYTES: usize = 4096;
+
+/// One object slot: holds either a live `T` or the link to the next free slot.
+union Slot<T> {
+    _value: ManuallyDrop<T>,
+    next: *mut Slot<T>,
+}
+
+struct CacheInner<T> {
+    /// Head of the intrusive free list.
+    free: *mut Slot<T>,
+    slabs: usize,
+    in_use: usize,
+    free_count: usize,
+}
+
+/// A cache of fixed-size `T` objects. Slabs are carved from the kernel heap
+/// and never returned to it; freed objects go on a free list and are handed
+/// out again before a new slab is allocated, which keeps hot objects off the
+/// heap's free list.
+///
+/// Declare caches as statics; objects are `SlabBox`es that return their slot
+/// when dropped:
+///
+/// ```ignore
+/// static PIPE_BUFFERS: SlabCache<[u8; PIPE_BUFFER_SIZE]> = SlabCache::new("pipe_buffer");
+/// let buffer = unsafe { PIPE_BUFFERS.alloc_zeroed() };
+/// ```
+pub struct SlabCache<T> {
+    name: &'static str,
+    inner: Mutex<CacheInner<T>>,
+    _marker: PhantomData<T>,
+}
+
+// The raw free-list pointers are only touched under the mutex
+unsafe impl<T: Send> Send for SlabCache<T> {}
+unsafe impl<T: Send> Sync for SlabCache<T> {}
+
+/// Per-cache counters, as shown by `meminfo`.
+#[derive(Debug, Clone, Copy)]
+pub struct SlabStats {
+    pub name: &'static str,
+    pub object_size: usize,
+    pub slabs: usize,
+    pub in_use: usize,
+    pub free: usize,
+}
+
+trait CacheStats: Sync {
+    fn stats(&self) -> SlabStats;
+}
+
+/// Every cache that has allocated at least one slab, in first-use order.
+static CACHES: Mutex<Vec<&'static dyn CacheStats>> = Mutex::new(Vec::new());
+
+/// Counters for every cache in use.
+pub fn slab_stats() -> Vec<SlabStats> {
+    CACHES.lock().iter().map(|c| c.stats()).collect()
+}
+
+impl<T: Send + 'static> SlabCache<T> {
+    pub const fn new(name: &'static str) -> Self {
+        SlabCache {
+            name,
+            inner: Mutex::new(CacheInner {
+                free: ptr::null_mut(),
+                slabs: 0,
+                in_use: 0,
+                free_count: 0,
+            }),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Objects carved from each slab.
+    pub const fn objects_per_slab() -> usize {
+        let per = SLAB_BYTES / core::mem::size_of::<Slot<T>>();
+        if per == 0 { 1 } else { per }
+    }
+
+    /// Move `value` into a cached slot.
+    pub fn alloc(&'static self, value: T) -> SlabBox<T> {
+        let slot = self.take_slot(false);
+        unsafe { ptr::write(slot as *mut T, value) };
+        SlabBox { ptr: unsafe { NonNull::new_unchecked(slot as *mut T) }, cache: self }
+    }
+
+    /// A slot with every byte zero, built in place (for objects too big to
+    /// construct on a kernel stack first).
+    ///
+    /// # Safety
+    /// The all-zero bit pattern must be a valid `T`.
+    pub unsafe fn alloc_zeroed(&'static self) -> SlabBox<T> {
+        let slot = self.take_slot(true);
+        SlabBox { ptr: NonNull::new_unchecked(slot as *mut T), cache: self }
+    }
+
+    fn take_slot(&'static self, zeroed: bool) -> *mut Slot<T> {
+        let mut inner = self.inner.lock();
+        let first_slab = inner.free.is_null() && Self::grow(&mut inner);
+
+        let slot = inner.free;
+        inner.free = unsafe { (*slot).next };
+        inner.free_count -= 1;
+        inner.in_use += 1;
+        drop(inner);
+
+        // Outside our lock: slab_stats() takes CACHES first, then each cache
+        if first_slab {
+            CACHES.lock().push(self);
+        }
+
+        if zeroed {
+            unsafe { ptr::write_bytes(slot as *mut u8, 0, core::mem::size_of::<Slot<T>>()) };
+        }
+        slot
+    }
+
+    /// Allocate a new slab and thread its slots onto the free list.
+    /// Returns true for the cache's first slab.
+    fn grow(inner: &mut CacheInner<T>) -> bool {
+        let count = Self::objects_per_slab();
+        let layout = Layout::array::<Slot<T>>(count).expect("slab layout overflow");
+        let base = unsafe { alloc(layout) } as *mut Slot<T>;
+        if base.is_null() {
+            handle_alloc_error(layout);
+        }
+
+        for i in (0..count).rev() {
+            unsafe {
+                let slot = base.add(i);
+                (*slot).next = inner.free;
+                inner.free = slot;
+            }
+        }
+        inner.slabs += 1;
+        inner.free_count += count;
+        inner.slabs == 1
+    }
+
+    fn release(&self, slot: *mut Slot<T>) {
+        let mut inner = self.inner.lock();
+        unsafe { (*slot).next = inner.free };
+        inner.free = slot;
+        inner.in_use -= 1;
+        inner.free_count += 1;
+    }
+}
+
+impl<T: Send + 'static> CacheStats for SlabCache<T> {
+    fn stats(&self) -> SlabStats {
+        let inner = self.inner.lock();
+        SlabStats {
+            name: self.name,
+            object_size: core::mem::size_of::<T>(),
+            slabs: inner.slabs,
+            in_use: inner.in_use,
+            free: inner.free_count,
+        }
+    }
+}
+
+/// An owned object living in a `SlabCache`, like a `Box` whose memory goes
+/// back to the cache on drop. The object never moves while the box lives.
+pub struct SlabBox<T: Send + 'static> {
+    ptr: NonNull<T>,
+    cache: &'static SlabCache<T>,
+}
+
+unsafe impl<T: Send + 'static> Send for SlabBox<T> {}
+unsafe impl<T: Send + Sync + 'static> Sync for SlabBox<T> {}
+
+impl<T: Send + 'static> Deref for SlabBox<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T: Send + 'static> DerefMut for SlabBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T: Send + 'static> Drop for SlabBox<T> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.ptr.as_ptr()) };
+        self.cache.release(self.ptr.as_ptr() as *mut Slot<T>);
+    }
+}
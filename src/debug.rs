@@ -0,0 +1,109 @@
+use core::fmt::{self, Write};
+use crate::vga::EmergencyWriter;
+
+/// Deepest call chain `kassert!` prints.
+const MAX_FRAMES: usize = 16;
+
+/// Halt with a diagnostic if `cond` is false: the message, the current PID,
+/// a register snapshot and a frame-pointer backtrace go to both VGA and
+/// serial. Unlike `assert!`, the report needs no locks, so it also works
+/// while the scheduler, VGA or serial lock is held.
+///
+/// ```ignore
+/// kassert!(frame.is_some(), "fork: no frame for the child's P4");
+/// ```
+#[macro_export]
+macro_rules! kassert {
+    ($cond:expr $(,)?) => {
+        $crate::kassert!($cond, "assertion failed")
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::debug::assert_failed(stringify!($cond), file!(), line!(), format_args!($($arg)+));
+        }
+    };
+}
+
+/// `kassert!(false, ...)` as an expression of type `!`, for `let ... else`
+/// and match arms that can only be reached through a kernel bug.
+#[macro_export]
+macro_rules! kpanic {
+    ($($arg:tt)+) => {
+        $crate::debug::assert_failed("unreachable", file!(), line!(), format_args!($($arg)+))
+    };
+}
+
+/// Writes to the emergency VGA console and COM1 at once.
+struct Report(EmergencyWriter);
+
+impl Write for Report {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let _ = self.0.write_str(s);
+        crate::serial::emergency_print(format_args!("{}", s));
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub fn assert_failed(cond: &str, file: &str, line: u32, msg: fmt::Arguments) -> ! {
+    halt_with_report(format_args!("KERNEL ASSERTION FAILED: {}\n  at {}:{}: {}", msg, file, line, cond))
+}
+
+/// Print `title` with the current PID, registers and backtrace to VGA and
+/// serial, then halt for good. Takes no locks: the failing code may hold them.
+#[inline(never)]
+pub fn halt_with_report(title: fmt::Arguments) -> ! {
+    x86_64::instructions::interrupts::disable();
+
+    let (rsp, rbp): (u64, u64);
+    unsafe {
+        core::arch::asm!("mov {}, rsp", out(reg) rsp, options(nomem, nostack, preserves_flags));
+        core::arch::asm!("mov {}, rbp", out(reg) rbp, options(nomem, nostack, preserves_flags));
+    }
+
+    let mut out = Report(unsafe { EmergencyWriter::take_over() });
+    let _ = writeln!(out, "{}", title);
+    write_state(&mut out, rsp, rbp);
+
+    loop {
+        x86_64::instructions::hlt();
+    }
+}
+
+fn write_state(out: &mut impl Write, rsp: u64, rbp: u64) {
+    use x86_64::registers::control::{Cr0, Cr2, Cr3, Cr4};
+
+    let _ = writeln!(out, "  PID {}", crate::scheduler::current_pid().0);
+    let _ = writeln!(out, "  RSP={:#018x} RBP={:#018x} RFLAGS={:#010x}",
+        rsp, rbp, x86_64::registers::rflags::read_raw());
+    let _ = writeln!(out, "  CR0={:#010x} CR2={:#018x} CR3={:#018x} CR4={:#010x}",
+        Cr0::read_raw(), Cr2::read().as_u64(), Cr3::read().0.start_address().as_u64(), Cr4::read_raw());
+
+    // Needs frame pointers, which target.json forces on
+    let _ = writeln!(out, "Backtrace:");
+    let text = crate::memory::layout::sections().into_iter().find(|r| r.name == ".text").unwrap();
+    let mut rbp = rbp;
+    for depth in 0..MAX_FRAMES {
+        // Only follow frames on a kernel stack: a corrupt RBP must not fault here
+        if rbp & 7 != 0 || !on_kernel_stack(rbp) || !on_kernel_stack(rbp + 8) {
+            break;
+        }
+        let (next, ret) = unsafe { (*(rbp as *const u64), *((rbp + 8) as *const u64)) };
+        if ret < text.start || ret >= text.end {
+            break;
+        }
+        let _ = writeln!(out, "  #{:<2} {:#018x}", depth, ret);
+        // Callers' frames sit higher on the same stack
+        if next <= rbp {
+            break;
+        }
+        rbp = next;
+    }
+}
+
+/// Kernel stacks are the boot stack and the task stacks.
+fn on_kernel_stack(addr: u64) -> bool {
+    use crate::scheduler::stack::is_stack_memory;
+    let boot = crate::memory::layout::boot_stack();
+    (addr >= boot.start && addr + 8 <= boot.end) || (is_stack_memory(addr) && is_stack_memory(addr + 7))
+}
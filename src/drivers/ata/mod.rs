@@ -1,26 +1,65 @@
 pub mod pio;
 
-use pio::AtaDevice;
+use pio::{AtaDevice, DeviceClass};
 use spin::Mutex;
 use lazy_static::lazy_static;
-use x86_64::instructions::port::Port;
 
 lazy_static! {
     pub static ref PRIMARY_ATA: Mutex<AtaDevice> = Mutex::new(AtaDevice::new(0x1F0, 0x3F6, true));
+    pub static ref PRIMARY_SLAVE: Mutex<AtaDevice> = Mutex::new(AtaDevice::new(0x1F0, 0x3F6, false));
+    pub static ref SECONDARY_MASTER: Mutex<AtaDevice> = Mutex::new(AtaDevice::new(0x170, 0x376, true));
+    pub static ref SECONDARY_SLAVE: Mutex<AtaDevice> = Mutex::new(AtaDevice::new(0x170, 0x376, false));
+}
+
+/// The device table: every bus position with its conventional name, in probe
+/// order. QEMU puts `-hda` on the primary master and `-cdrom` on the secondary master.
+pub fn devices() -> [(&'static str, &'static Mutex<AtaDevice>); 4] {
+    [
+        ("hda", &PRIMARY_ATA),
+        ("hdb", &PRIMARY_SLAVE),
+        ("hdc", &SECONDARY_MASTER),
+        ("hdd", &SECONDARY_SLAVE),
+    ]
+}
+
+/// The first ATAPI (CD/DVD) drive found by `init`, if any.
+pub fn first_atapi() -> Option<&'static Mutex<AtaDevice>> {
+    devices().into_iter()
+        .map(|(_, dev)| dev)
+        .find(|dev| dev.lock().class == DeviceClass::Atapi)
 }
 
 pub fn init() {
-    // Disable ATA interrupts (nIEN bit) on both primary and secondary
-    // bus BEFORE doing any commands — prevents unhandled IRQ 14/15 double faults
-    unsafe {
-        Port::<u8>::new(0x3F6).write(0x02); // Primary control: nIEN = 1
-        Port::<u8>::new(0x376).write(0x02); // Secondary control: nIEN = 1
+    // Reset both buses with ATA interrupts disabled (nIEN bit) BEFORE doing
+    // any commands — prevents unhandled IRQ 14/15 double faults, and leaves
+    // each drive's signature in place for identify
+    let buses: [&Mutex<AtaDevice>; 2] = [&PRIMARY_ATA, &SECONDARY_MASTER];
+    for bus in buses {
+        let bus = bus.lock();
+        if bus.soft_reset().is_err() {
+            crate::log_info!("ATA PIO: bus {:#x} empty or not responding", bus.io_base());
+        }
+    }
+
+    for (name, dev) in devices() {
+        let mut dev = dev.lock();
+        if dev.identify().is_err() {
+            crate::log_info!("ATA PIO: {}: no device", name);
+            continue;
+        }
+        match (&dev.atapi, &dev.disk) {
+            (Some(info), _) => {
+                crate::log_info!("ATA PIO: {}: ATAPI device type {:#04x}, '{}'", name, info.device_type, info.model);
+            }
+            (None, Some(disk)) => {
+                crate::log_info!("ATA PIO: {}: ATA disk '{}', {} sectors ({} MiB){}", name, disk.model,
+                    disk.sectors(), disk.capacity() / (1024 * 1024), if disk.lba48 { ", LBA48" } else { "" });
+            }
+            (None, None) => { crate::log_info!("ATA PIO: {}: ATA disk detected.", name); }
+        }
     }
 
-    let mut dev = PRIMARY_ATA.lock();
-    if dev.identify().is_ok() {
-        crate::log_info!("ATA PIO: Primary master disk detected.");
-    } else {
+    if !PRIMARY_ATA.lock().detected {
         crate::log_warn!("ATA PIO: No disk detected.");
     }
 }
@@ -1,3 +1,4 @@
+use alloc::string::String;
 use x86_64::instructions::port::Port;
 use core::fmt;
 
@@ -14,6 +15,10 @@ const LBA_HIGH: u16        = 5;
 const DRIVE_HEAD: u16      = 6;
 const CMD_STATUS: u16      = 7; // R: status / W: command
 
+// Device control register bits
+const CTRL_NIEN: u8 = 0x02; // no interrupts
+const CTRL_SRST: u8 = 0x04; // software reset of both drives on the bus
+
 // Status register bits
 const STATUS_BSY: u8  = 0x80;
 const STATUS_DRQ: u8  = 0x08;
@@ -22,9 +27,43 @@ const STATUS_DF: u8   = 0x20;
 
 // ATA commands
 const CMD_IDENTIFY: u8      = 0xEC;
+const CMD_IDENTIFY_PACKET: u8 = 0xA1;
+const CMD_PACKET: u8        = 0xA0;
+
+// SCSI commands sent in an ATAPI packet
+const SCSI_READ_10: u8 = 0x28;
+
+/// Block size of CD/DVD media (and of ISO9660).
+pub const ATAPI_BLOCK_SIZE: usize = 2048;
 const CMD_READ_SECTORS: u8  = 0x20;
 const CMD_WRITE_SECTORS: u8 = 0x30;
+const CMD_READ_SECTORS_EXT: u8  = 0x24;
+const CMD_WRITE_SECTORS_EXT: u8 = 0x34;
 const CMD_CACHE_FLUSH: u8   = 0xE7;
+const CMD_CACHE_FLUSH_EXT: u8 = 0xEA;
+
+/// LBA mid/high left by a packet (ATAPI) device that aborts IDENTIFY.
+const ATAPI_SIGNATURE: (u8, u8) = (0x14, 0xEB);
+/// The same for a SATA packet device behind an IDE-compatible controller.
+const SATAPI_SIGNATURE: (u8, u8) = (0x69, 0x96);
+
+/// Status polls before an ordinary command is declared stuck.
+const BUSY_POLLS: u32 = 100_000;
+/// Status polls allowed while probing a bus position. A present drive is
+/// ready long before this; an empty one must not hold up boot.
+const PROBE_POLLS: u32 = 10_000;
+
+/// Largest transfer issued as one LBA28 READ/WRITE SECTORS command
+/// (the 8-bit count register; 0 would mean 256 and is avoided).
+pub const MAX_SECTORS_PER_CMD: usize = 255;
+/// Largest transfer issued as one LBA48 READ/WRITE SECTORS EXT command
+/// (the 16-bit count register; 0 would mean 65536 and is avoided).
+pub const MAX_SECTORS_PER_CMD_EXT: usize = 65535;
+
+/// First sector LBA28 cannot address (128 GiB).
+pub const LBA28_LIMIT: u64 = 1 << 28;
+/// First sector LBA48 cannot address.
+pub const LBA48_LIMIT: u64 = 1 << 48;
 
 // ──────────────────────────────────────────────────────────────
 //  Error type
@@ -37,6 +76,10 @@ pub enum AtaError {
     BusyTimeout,
     DrqTimeout,
     IoError,
+    /// The transfer runs past the last sector IDENTIFY reported.
+    OutOfRange,
+    /// The command set needed (LBA48) is not supported by the drive.
+    Unsupported,
 }
 
 impl fmt::Display for AtaError {
@@ -47,12 +90,122 @@ impl fmt::Display for AtaError {
             AtaError::BusyTimeout    => write!(f, "BSY timeout"),
             AtaError::DrqTimeout     => write!(f, "DRQ timeout"),
             AtaError::IoError        => write!(f, "I/O error"),
+            AtaError::OutOfRange     => write!(f, "Sector out of range"),
+            AtaError::Unsupported    => write!(f, "Not supported by drive"),
         }
     }
 }
 
 pub type AtaResult<T> = Result<T, AtaError>;
 
+// ──────────────────────────────────────────────────────────────
+//  Device class / ATAPI parameters
+// ──────────────────────────────────────────────────────────────
+
+/// What answered at a bus position, as found by `identify`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceClass {
+    /// Nothing there (or not probed yet).
+    None,
+    /// A hard disk, addressed in 512-byte sectors.
+    Ata,
+    /// A packet device (CD/DVD drive), driven with SCSI commands.
+    Atapi,
+}
+
+impl fmt::Display for DeviceClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            DeviceClass::None  => "none",
+            DeviceClass::Ata   => "ATA",
+            DeviceClass::Atapi => "ATAPI",
+        })
+    }
+}
+
+/// Parameters from IDENTIFY PACKET DEVICE.
+#[derive(Debug, Clone)]
+pub struct AtapiInfo {
+    /// SCSI peripheral device type (word 0, bits 12:8); 0x05 is CD/DVD.
+    pub device_type: u8,
+    /// Command packet length in bytes: 12, or 16 (word 0, bits 1:0).
+    pub packet_size: usize,
+    pub removable: bool,
+    pub model: String,
+}
+
+impl AtapiInfo {
+    /// SCSI peripheral device type of CD/DVD drives.
+    pub const TYPE_CDROM: u8 = 0x05;
+
+    fn parse(words: &[u16; 256]) -> Self {
+        AtapiInfo {
+            device_type: ((words[0] >> 8) & 0x1F) as u8,
+            packet_size: if words[0] & 0x3 == 1 { 16 } else { 12 },
+            removable: words[0] & (1 << 7) != 0,
+            model: identify_string(&words[27..47]),
+        }
+    }
+}
+
+/// Parameters from IDENTIFY DEVICE, for an ATA disk.
+#[derive(Debug, Clone)]
+pub struct AtaInfo {
+    /// Words 27-46.
+    pub model: String,
+    /// Words 10-19.
+    pub serial: String,
+    /// Firmware revision, words 23-26.
+    pub firmware: String,
+    /// Sectors addressable with LBA28 commands (words 60-61).
+    pub lba28_sectors: u32,
+    /// Sectors addressable with LBA48 commands (words 100-103); 0 without LBA48.
+    pub lba48_sectors: u64,
+    /// The drive accepts the 48-bit commands (word 83, bit 10).
+    pub lba48: bool,
+}
+
+impl AtaInfo {
+    fn parse(words: &[u16; 256]) -> Self {
+        let lba48 = words[83] & (1 << 10) != 0;
+        let lba48_sectors = if lba48 {
+            words[100..104].iter().rev().fold(0u64, |acc, &w| acc << 16 | w as u64)
+        } else {
+            0
+        };
+        AtaInfo {
+            model: identify_string(&words[27..47]),
+            serial: identify_string(&words[10..20]),
+            firmware: identify_string(&words[23..27]),
+            lba28_sectors: words[60] as u32 | (words[61] as u32) << 16,
+            lba48_sectors,
+            lba48,
+        }
+    }
+
+    /// Total addressable sectors, by whichever command set reaches further.
+    pub fn sectors(&self) -> u64 {
+        self.lba48_sectors.max(self.lba28_sectors as u64)
+    }
+
+    /// Capacity in bytes.
+    pub fn capacity(&self) -> u64 {
+        self.sectors() * 512
+    }
+}
+
+/// Decode an IDENTIFY string field: two ASCII characters per word, high
+/// byte first, padded with spaces.
+pub fn identify_string(words: &[u16]) -> String {
+    let mut s = String::with_capacity(words.len() * 2);
+    for w in words {
+        for b in w.to_be_bytes() {
+            s.push(if b.is_ascii_graphic() || b == b' ' { b as char } else { '?' });
+        }
+    }
+    String::from(s.trim())
+}
+
 // ──────────────────────────────────────────────────────────────
 //  ATA Device
 // ──────────────────────────────────────────────────────────────
@@ -61,7 +214,14 @@ pub struct AtaDevice {
     io_base: u16,
     ctrl_base: u16,
     is_master: bool,
+    /// An ATA disk answered IDENTIFY, so sector reads and writes may be issued.
+    /// Stays false for ATAPI devices, which reject ATA sector commands.
     pub detected: bool,
+    pub class: DeviceClass,
+    /// Set when `class` is `Atapi`.
+    pub atapi: Option<AtapiInfo>,
+    /// Set when `class` is `Ata`.
+    pub disk: Option<AtaInfo>,
 }
 
 impl AtaDevice {
@@ -71,9 +231,17 @@ impl AtaDevice {
             ctrl_base,
             is_master,
             detected: false,
+            class: DeviceClass::None,
+            atapi: None,
+            disk: None,
         }
     }
 
+    /// Command block base port of the bus.
+    pub fn io_base(&self) -> u16 {
+        self.io_base
+    }
+
     // ── Port I/O helpers ─────────────────────────────────────
 
     fn read_port(&self, offset: u16) -> u8 {
@@ -101,11 +269,21 @@ impl AtaDevice {
         unsafe { port.read() }
     }
 
+    fn write_ctrl(&self, val: u8) {
+        let mut port = Port::<u8>::new(self.ctrl_base);
+        unsafe { port.write(val) }
+    }
+
     // ── Status polling ───────────────────────────────────────
 
     /// Wait until BSY bit clears. Returns Err on timeout.
     fn wait_bsy(&self) -> AtaResult<()> {
-        for _ in 0..100_000 {
+        self.wait_bsy_polls(BUSY_POLLS)
+    }
+
+    /// `wait_bsy` with a caller-chosen number of status polls.
+    fn wait_bsy_polls(&self, polls: u32) -> AtaResult<()> {
+        for _ in 0..polls {
             let status = self.read_port(CMD_STATUS);
             if status == 0xFF {
                 return Err(AtaError::DeviceNotFound); // floating bus
@@ -150,52 +328,112 @@ impl AtaDevice {
         self.delay_400ns();
     }
 
-    // ── IDENTIFY ─────────────────────────────────────────────
+    // ── RESET / IDENTIFY ─────────────────────────────────────
+
+    /// Software-reset both drives on this device's bus, leaving interrupts
+    /// disabled (nIEN). Afterwards each drive holds its signature in LBA
+    /// mid/high until a command changes them, which is what `identify`
+    /// classifies by. A floating bus (status 0xFF) or one that stays busy
+    /// gives `DeviceNotFound` after a bounded wait.
+    pub fn soft_reset(&self) -> AtaResult<()> {
+        self.write_ctrl(CTRL_SRST | CTRL_NIEN);
+        // SRST must be held for at least 5 µs
+        for _ in 0..16 {
+            self.delay_400ns();
+        }
+        self.write_ctrl(CTRL_NIEN);
+        self.delay_400ns();
 
-    /// Identify the disk. Sets `detected` to true on success.
-    pub fn identify(&mut self) -> AtaResult<()> {
-        // Check for floating bus first (no device at all)
-        let initial = self.read_port(CMD_STATUS);
-        if initial == 0xFF {
+        if self.read_ctrl() == 0xFF {
             return Err(AtaError::DeviceNotFound);
         }
+        self.wait_bsy_polls(PROBE_POLLS).map_err(|_| AtaError::DeviceNotFound)
+    }
 
-        self.select_drive();
+    /// Identify the device. Sets `class`, and `detected` for an ATA disk;
+    /// a packet device is identified with IDENTIFY PACKET DEVICE instead.
+    /// Meant to run after `soft_reset` of the bus and before any other
+    /// command on it: the drive is classified by its reset signature in LBA
+    /// mid/high (0x0000 ATA, 0x14EB ATAPI). An empty position gives
+    /// `DeviceNotFound` without waiting out the full command timeout.
+    pub fn identify(&mut self) -> AtaResult<()> {
+        self.detected = false;
+        self.class = DeviceClass::None;
+        self.atapi = None;
+        self.disk = None;
 
-        // Clear sector count / LBA for IDENTIFY
-        self.write_port(SECTOR_COUNT, 0);
-        self.write_port(LBA_LOW, 0);
-        self.write_port(LBA_MID, 0);
-        self.write_port(LBA_HIGH, 0);
+        self.select_drive();
 
-        // Send IDENTIFY command
-        self.write_port(CMD_STATUS, CMD_IDENTIFY);
+        // A floating bus (no drive on it at all) reads all ones
+        if self.read_ctrl() == 0xFF {
+            return Err(AtaError::DeviceNotFound);
+        }
+        self.wait_bsy_polls(PROBE_POLLS).map_err(|_| AtaError::DeviceNotFound)?;
 
-        // Read status — if 0, no device
-        let status = self.read_port(CMD_STATUS);
-        if status == 0 {
+        // Packet devices keep DRDY clear until IDENTIFY PACKET DEVICE, so
+        // their signature is checked before the status
+        let signature = (self.read_port(LBA_MID), self.read_port(LBA_HIGH));
+        if signature == ATAPI_SIGNATURE || signature == SATAPI_SIGNATURE {
+            return self.identify_packet();
+        }
+        if signature != (0, 0) {
+            return Err(AtaError::DeviceNotFound);
+        }
+        // Nothing at this position while the other drive on the bus answers
+        if self.read_port(CMD_STATUS) == 0 {
             return Err(AtaError::DeviceNotFound);
         }
 
-        // Wait for BSY to clear
-        self.wait_bsy()?;
+        self.write_port(CMD_STATUS, CMD_IDENTIFY);
+        self.delay_400ns();
+        if self.read_port(CMD_STATUS) == 0 {
+            return Err(AtaError::DeviceNotFound);
+        }
+        self.wait_bsy_polls(PROBE_POLLS).map_err(|_| AtaError::DeviceNotFound)?;
 
-        // Check LBA mid/high — if non-zero, it's ATAPI, not ATA
-        let mid = self.read_port(LBA_MID);
-        let high = self.read_port(LBA_HIGH);
-        if mid != 0 || high != 0 {
+        // Check LBA mid/high — a packet device aborts IDENTIFY and leaves
+        // its signature there; anything else non-zero is not a device we know
+        let signature = (self.read_port(LBA_MID), self.read_port(LBA_HIGH));
+        if signature == ATAPI_SIGNATURE || signature == SATAPI_SIGNATURE {
+            return self.identify_packet();
+        }
+        if signature != (0, 0) {
             return Err(AtaError::DeviceNotFound);
         }
 
         // Wait for DRQ or ERR
         self.wait_drq()?;
 
-        // Read 256 words of identify data (discard for now)
-        for _ in 0..256 {
-            let _ = self.read_data16();
+        let mut words = [0u16; 256];
+        for w in words.iter_mut() {
+            *w = self.read_data16();
         }
 
         self.detected = true;
+        self.class = DeviceClass::Ata;
+        self.disk = Some(AtaInfo::parse(&words));
+        Ok(())
+    }
+
+    /// IDENTIFY PACKET DEVICE, for a drive that showed the ATAPI signature.
+    fn identify_packet(&mut self) -> AtaResult<()> {
+        self.write_port(CMD_STATUS, CMD_IDENTIFY_PACKET);
+        self.delay_400ns();
+        self.wait_bsy()?;
+        self.wait_drq()?;
+
+        let mut words = [0u16; 256];
+        for w in words.iter_mut() {
+            *w = self.read_data16();
+        }
+
+        // Word 0 bits 15:14 = 10b mark an ATAPI identify block
+        if words[0] >> 14 != 0b10 {
+            return Err(AtaError::IoError);
+        }
+
+        self.class = DeviceClass::Atapi;
+        self.atapi = Some(AtapiInfo::parse(&words));
         Ok(())
     }
 
@@ -233,6 +471,163 @@ impl AtaDevice {
         Ok(())
     }
 
+    // ── MULTI-SECTOR READ / WRITE (LBA28 / LBA48) ───────────
+
+    /// Read `buf.len() / 512` consecutive sectors starting at `lba` with a
+    /// single command. `buf` must be a whole number of sectors. Transfers
+    /// that fit below `LBA28_LIMIT` in at most `MAX_SECTORS_PER_CMD` sectors
+    /// use READ SECTORS; anything else uses READ SECTORS EXT.
+    pub fn read_sectors(&self, lba: u64, buf: &mut [u8]) -> AtaResult<()> {
+        let ext = Self::needs_lba48(lba, buf.len());
+        self.read_sectors_with(lba, buf, ext)
+    }
+
+    /// Like `read_sectors`, but always issues READ SECTORS EXT (LBA48),
+    /// even for an address LBA28 could reach.
+    pub fn read_sectors_ext(&self, lba: u64, buf: &mut [u8]) -> AtaResult<()> {
+        self.read_sectors_with(lba, buf, true)
+    }
+
+    /// Write `data.len() / 512` consecutive sectors starting at `lba` with a
+    /// single command, then flush the drive cache. The command set is chosen
+    /// as in `read_sectors`.
+    pub fn write_sectors(&self, lba: u64, data: &[u8]) -> AtaResult<()> {
+        let ext = Self::needs_lba48(lba, data.len());
+        self.write_sectors_with(lba, data, ext)
+    }
+
+    /// Like `write_sectors`, but always issues WRITE SECTORS EXT (LBA48).
+    pub fn write_sectors_ext(&self, lba: u64, data: &[u8]) -> AtaResult<()> {
+        self.write_sectors_with(lba, data, true)
+    }
+
+    fn read_sectors_with(&self, lba: u64, buf: &mut [u8], ext: bool) -> AtaResult<()> {
+        let count = Self::sector_count(lba, buf.len(), ext)?;
+        self.check_range(lba, count, ext)?;
+
+        self.wait_bsy()?;
+        if ext {
+            self.setup_lba48(lba, count);
+            self.write_port(CMD_STATUS, CMD_READ_SECTORS_EXT);
+        } else {
+            self.setup_lba28(lba as u32, count as u8);
+            self.write_port(CMD_STATUS, CMD_READ_SECTORS);
+        }
+
+        for sector in buf.chunks_exact_mut(512) {
+            // The drive raises DRQ once per sector
+            self.wait_drq()?;
+            for i in 0..256 {
+                let word = self.read_data16();
+                sector[i * 2]     = (word & 0xFF) as u8;
+                sector[i * 2 + 1] = (word >> 8) as u8;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_sectors_with(&self, lba: u64, data: &[u8], ext: bool) -> AtaResult<()> {
+        let count = Self::sector_count(lba, data.len(), ext)?;
+        self.check_range(lba, count, ext)?;
+
+        self.wait_bsy()?;
+        if ext {
+            self.setup_lba48(lba, count);
+            self.write_port(CMD_STATUS, CMD_WRITE_SECTORS_EXT);
+        } else {
+            self.setup_lba28(lba as u32, count as u8);
+            self.write_port(CMD_STATUS, CMD_WRITE_SECTORS);
+        }
+
+        for sector in data.chunks_exact(512) {
+            self.wait_drq()?;
+            for i in 0..256 {
+                let word = (sector[i * 2] as u16) | ((sector[i * 2 + 1] as u16) << 8);
+                self.write_data16(word);
+            }
+        }
+
+        self.write_port(CMD_STATUS, if ext { CMD_CACHE_FLUSH_EXT } else { CMD_CACHE_FLUSH });
+        self.wait_bsy()?;
+
+        Ok(())
+    }
+
+    /// Check a transfer against what IDENTIFY reported: the disk must be
+    /// present, support LBA48 if `ext`, and hold every sector of the range.
+    fn check_range(&self, lba: u64, count: u16, ext: bool) -> AtaResult<()> {
+        let disk = match (self.detected, &self.disk) {
+            (true, Some(disk)) => disk,
+            _ => return Err(AtaError::DeviceNotFound),
+        };
+        if ext && !disk.lba48 {
+            return Err(AtaError::Unsupported);
+        }
+        if lba + count as u64 > disk.sectors() {
+            return Err(AtaError::OutOfRange);
+        }
+        Ok(())
+    }
+
+    /// True if a transfer of `len` bytes at `lba` is out of LBA28's reach,
+    /// by address or by sector count.
+    fn needs_lba48(lba: u64, len: usize) -> bool {
+        let count = (len / 512) as u64;
+        count > MAX_SECTORS_PER_CMD as u64 || lba.saturating_add(count) > LBA28_LIMIT
+    }
+
+    /// Validate a multi-sector buffer length and return its sector count.
+    /// The whole range must be addressable with the chosen command set.
+    fn sector_count(lba: u64, len: usize, ext: bool) -> AtaResult<u16> {
+        let (max_count, limit) = if ext {
+            (MAX_SECTORS_PER_CMD_EXT, LBA48_LIMIT)
+        } else {
+            (MAX_SECTORS_PER_CMD, LBA28_LIMIT)
+        };
+        if len == 0 || len % 512 != 0 || len / 512 > max_count {
+            return Err(AtaError::IoError);
+        }
+        let count = (len / 512) as u16;
+        if lba.checked_add(count as u64).map_or(true, |end| end > limit) {
+            return Err(AtaError::IoError);
+        }
+        Ok(count)
+    }
+
+    /// Program drive/head, sector count and LBA registers for an LBA28 transfer.
+    fn setup_lba28(&self, lba: u32, count: u8) {
+        let head = if self.is_master { 0xE0 } else { 0xF0 };
+        self.write_port(DRIVE_HEAD, head | ((lba >> 24) as u8 & 0x0F));
+        self.delay_400ns();
+
+        self.write_port(ERROR_REG, 0);
+        self.write_port(SECTOR_COUNT, count);
+        self.write_port(LBA_LOW, lba as u8);
+        self.write_port(LBA_MID, (lba >> 8) as u8);
+        self.write_port(LBA_HIGH, (lba >> 16) as u8);
+    }
+
+    /// Program the registers for an LBA48 transfer. Each register is a
+    /// two-byte FIFO: the high-order bytes go in first, then the low-order.
+    fn setup_lba48(&self, lba: u64, count: u16) {
+        let head = if self.is_master { 0x40 } else { 0x50 };
+        self.write_port(DRIVE_HEAD, head);
+        self.delay_400ns();
+
+        self.write_port(ERROR_REG, 0);
+        self.write_port(SECTOR_COUNT, (count >> 8) as u8);
+        self.write_port(LBA_LOW, (lba >> 24) as u8);
+        self.write_port(LBA_MID, (lba >> 32) as u8);
+        self.write_port(LBA_HIGH, (lba >> 40) as u8);
+
+        self.write_port(ERROR_REG, 0);
+        self.write_port(SECTOR_COUNT, count as u8);
+        self.write_port(LBA_LOW, lba as u8);
+        self.write_port(LBA_MID, (lba >> 8) as u8);
+        self.write_port(LBA_HIGH, (lba >> 16) as u8);
+    }
+
     // ── WRITE SECTOR (LBA28) ────────────────────────────────
 
     /// Write one 512-byte sector at the given LBA.
@@ -268,4 +663,75 @@ impl AtaDevice {
 
         Ok(())
     }
+
+    // ── ATAPI READ (10) ─────────────────────────────────────
+
+    /// Read one 2048-byte block from an ATAPI drive (PIO, no interrupts).
+    pub fn read_atapi_block(&self, lba: u32, buf: &mut [u8; ATAPI_BLOCK_SIZE]) -> AtaResult<()> {
+        let packet_size = match (&self.class, &self.atapi) {
+            (DeviceClass::Atapi, Some(info)) => info.packet_size,
+            _ => return Err(AtaError::DeviceNotFound),
+        };
+
+        self.wait_bsy()?;
+        self.select_drive();
+
+        // Features = 0 (PIO); LBA mid/high = most bytes to move per DRQ block
+        self.write_port(ERROR_REG, 0);
+        self.write_port(LBA_MID, ATAPI_BLOCK_SIZE as u8);
+        self.write_port(LBA_HIGH, (ATAPI_BLOCK_SIZE >> 8) as u8);
+        self.write_port(CMD_STATUS, CMD_PACKET);
+        self.delay_400ns();
+        self.wait_bsy()?;
+        self.wait_drq()?;
+
+        // READ(10): big-endian LBA in bytes 2..6, block count in 7..9
+        let mut packet = [0u8; 16];
+        packet[0] = SCSI_READ_10;
+        packet[2..6].copy_from_slice(&lba.to_be_bytes());
+        packet[7..9].copy_from_slice(&1u16.to_be_bytes());
+        for pair in packet[..packet_size].chunks_exact(2) {
+            self.write_data16(u16::from_le_bytes([pair[0], pair[1]]));
+        }
+        self.delay_400ns();
+        self.wait_bsy()?;
+        self.wait_drq()?;
+
+        // The drive reports how many bytes this DRQ block holds
+        let len = self.read_port(LBA_MID) as usize | (self.read_port(LBA_HIGH) as usize) << 8;
+        if len != ATAPI_BLOCK_SIZE {
+            return Err(AtaError::IoError);
+        }
+        for i in 0..ATAPI_BLOCK_SIZE / 2 {
+            let word = self.read_data16();
+            buf[i * 2]     = (word & 0xFF) as u8;
+            buf[i * 2 + 1] = (word >> 8) as u8;
+        }
+
+        // Status phase: BSY drops once the command completes
+        self.wait_bsy()?;
+        if self.read_port(CMD_STATUS) & (STATUS_ERR | STATUS_DF) != 0 {
+            return Err(AtaError::DeviceFault);
+        }
+        Ok(())
+    }
+
+    // ── CACHE FLUSH ─────────────────────────────────────────
+
+    /// Ask the drive to commit its write cache to the medium.
+    pub fn flush_cache(&self) -> AtaResult<()> {
+        if !self.detected {
+            return Err(AtaError::DeviceNotFound);
+        }
+
+        self.wait_bsy()?;
+        let head = if self.is_master { 0xE0 } else { 0xF0 };
+        self.write_port(DRIVE_HEAD, head);
+        self.delay_400ns();
+
+        self.write_port(CMD_STATUS, CMD_CACHE_FLUSH);
+        self.wait_bsy()?;
+
+        Ok(())
+    }
 }
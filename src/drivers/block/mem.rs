@@ -0,0 +1,71 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use spin::Mutex;
+use super::{BlockDevice, BlockError, BlockResult};
+
+/// A block device held in memory, for exercising filesystems without a disk.
+pub struct MemBlockDevice {
+    block_size: usize,
+    data: Mutex<Vec<u8>>,
+}
+
+impl MemBlockDevice {
+    /// `blocks` zeroed blocks of `block_size` bytes.
+    pub fn new(block_size: usize, blocks: u64) -> Self {
+        Self::from_vec(block_size, vec![0; block_size * blocks as usize])
+    }
+
+    /// A device over `data`, whose length must be a whole number of blocks
+    /// (a partial last block is dropped).
+    pub fn from_vec(block_size: usize, mut data: Vec<u8>) -> Self {
+        data.truncate(data.len() / block_size * block_size);
+        MemBlockDevice { block_size, data: Mutex::new(data) }
+    }
+
+    /// Byte range of `len` bytes from block `lba`, if within the device.
+    fn range(&self, len: usize, lba: u64) -> BlockResult<core::ops::Range<usize>> {
+        if len % self.block_size != 0 {
+            return Err(BlockError::OutOfRange);
+        }
+        let start = usize::try_from(lba).ok()
+            .and_then(|lba| lba.checked_mul(self.block_size))
+            .ok_or(BlockError::OutOfRange)?;
+        let end = start.checked_add(len).ok_or(BlockError::OutOfRange)?;
+        if end > self.data.lock().len() {
+            return Err(BlockError::OutOfRange);
+        }
+        Ok(start..end)
+    }
+}
+
+impl BlockDevice for MemBlockDevice {
+    fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    fn block_count(&self) -> u64 {
+        (self.data.lock().len() / self.block_size) as u64
+    }
+
+    fn read_block(&self, lba: u64, buf: &mut [u8]) -> BlockResult<()> {
+        if buf.len() != self.block_size {
+            return Err(BlockError::OutOfRange);
+        }
+        self.read_blocks(lba, buf)
+    }
+
+    fn write_block(&self, lba: u64, buf: &[u8]) -> BlockResult<()> {
+        if buf.len() != self.block_size {
+            return Err(BlockError::OutOfRange);
+        }
+        let range = self.range(buf.len(), lba)?;
+        self.data.lock()[range].copy_from_slice(buf);
+        Ok(())
+    }
+
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> BlockResult<()> {
+        let range = self.range(buf.len(), lba)?;
+        buf.copy_from_slice(&self.data.lock()[range]);
+        Ok(())
+    }
+}
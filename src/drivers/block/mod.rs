@@ -0,0 +1,138 @@
+// Block devices: storage addressed in fixed-size blocks by LBA, so a
+// filesystem doesn't care whether it sits on an ATA drive or in memory.
+
+pub mod mem;
+
+use core::fmt;
+use spin::Mutex;
+use crate::drivers::ata::pio::{AtaDevice, AtaError};
+
+pub use mem::MemBlockDevice;
+
+/// Block device error types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockError {
+    /// The transfer runs past the last block, or the buffer is not a whole
+    /// number of blocks.
+    OutOfRange,
+    /// The device failed or is missing.
+    IoError,
+}
+
+impl fmt::Display for BlockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlockError::OutOfRange => write!(f, "Block out of range"),
+            BlockError::IoError => write!(f, "I/O error"),
+        }
+    }
+}
+
+pub type BlockResult<T> = Result<T, BlockError>;
+
+/// A device read and written in whole blocks of `block_size()` bytes.
+/// Methods take `&self`: a device shared between tasks does its own locking
+/// (see the `Mutex` implementation below).
+pub trait BlockDevice: Send + Sync {
+    /// Bytes per block.
+    fn block_size(&self) -> usize;
+
+    /// Number of blocks, numbered from 0.
+    fn block_count(&self) -> u64;
+
+    /// Read block `lba` into `buf`, which is `block_size()` bytes long.
+    fn read_block(&self, lba: u64, buf: &mut [u8]) -> BlockResult<()>;
+
+    /// Write `buf`, `block_size()` bytes long, to block `lba`.
+    fn write_block(&self, lba: u64, buf: &[u8]) -> BlockResult<()>;
+
+    /// Read `buf.len() / block_size()` consecutive blocks starting at `lba`.
+    /// Devices that can move several blocks per command override this.
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> BlockResult<()> {
+        let size = self.block_size();
+        if buf.len() % size != 0 {
+            return Err(BlockError::OutOfRange);
+        }
+        for (i, block) in buf.chunks_exact_mut(size).enumerate() {
+            self.read_block(lba + i as u64, block)?;
+        }
+        Ok(())
+    }
+
+    /// Commit anything the device buffers to the medium.
+    fn flush(&self) -> BlockResult<()> {
+        Ok(())
+    }
+}
+
+impl From<AtaError> for BlockError {
+    fn from(e: AtaError) -> Self {
+        match e {
+            AtaError::OutOfRange => BlockError::OutOfRange,
+            _ => BlockError::IoError,
+        }
+    }
+}
+
+/// An ATA disk in 512-byte sectors.
+impl BlockDevice for AtaDevice {
+    fn block_size(&self) -> usize {
+        512
+    }
+
+    fn block_count(&self) -> u64 {
+        self.disk.as_ref().map_or(0, |disk| disk.sectors())
+    }
+
+    fn read_block(&self, lba: u64, buf: &mut [u8]) -> BlockResult<()> {
+        self.read_blocks(lba, buf)
+    }
+
+    /// Switches to LBA48 past the LBA28 limit, as `read_blocks` does.
+    fn write_block(&self, lba: u64, buf: &[u8]) -> BlockResult<()> {
+        if buf.len() != 512 {
+            return Err(BlockError::OutOfRange);
+        }
+        Ok(self.write_sectors(lba, buf)?)
+    }
+
+    /// One multi-sector ATA command.
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> BlockResult<()> {
+        if buf.len() % 512 != 0 {
+            return Err(BlockError::OutOfRange);
+        }
+        Ok(self.read_sectors(lba, buf)?)
+    }
+
+    fn flush(&self) -> BlockResult<()> {
+        Ok(self.flush_cache()?)
+    }
+}
+
+/// A device shared between tasks: every call holds the lock for its whole
+/// transfer, so the ATA command sequences of two tasks never interleave.
+impl<T: BlockDevice> BlockDevice for Mutex<T> {
+    fn block_size(&self) -> usize {
+        self.lock().block_size()
+    }
+
+    fn block_count(&self) -> u64 {
+        self.lock().block_count()
+    }
+
+    fn read_block(&self, lba: u64, buf: &mut [u8]) -> BlockResult<()> {
+        self.lock().read_block(lba, buf)
+    }
+
+    fn write_block(&self, lba: u64, buf: &[u8]) -> BlockResult<()> {
+        self.lock().write_block(lba, buf)
+    }
+
+    fn read_blocks(&self, lba: u64, buf: &mut [u8]) -> BlockResult<()> {
+        self.lock().read_blocks(lba, buf)
+    }
+
+    fn flush(&self) -> BlockResult<()> {
+        self.lock().flush()
+    }
+}
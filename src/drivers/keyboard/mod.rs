@@ -2,77 +2,88 @@ pub mod scancodes;
 
 use spin::Mutex;
 use lazy_static::lazy_static;
-use core::sync::atomic::{AtomicUsize, Ordering};
 use scancodes::{KeyCode, KeyboardState};
-use x86_64::instructions::port::Port;
+use super::ps2;
+use crate::scheduler::wait::WaitQueue;
+use crate::util::spsc::SpscRing;
 
 const BUFFER_SIZE: usize = 256;
 
-pub struct KeyboardBuffer {
-    buffer: [KeyCode; BUFFER_SIZE],
-    head: AtomicUsize,
-    tail: AtomicUsize,
+/// Keys queued by the keyboard interrupt for `read_char`.
+pub type KeyboardBuffer = SpscRing<KeyCode, BUFFER_SIZE>;
+
+pub static KEYBOARD_BUFFER: KeyboardBuffer = KeyboardBuffer::new(KeyCode::Unknown);
+
+lazy_static! {
+    pub static ref KEYBOARD_STATE: Mutex<KeyboardState> = Mutex::new(KeyboardState::new());
 }
 
-impl KeyboardBuffer {
-    pub const fn new() -> Self {
-        Self {
-            // Arrays of non-Copy enum requiring explicit loop initialization normally,
-            // but for a strict `const` contexts under `no_std`, we force repeated items
-            buffer: [KeyCode::Unknown; BUFFER_SIZE],
-            head: AtomicUsize::new(0),
-            tail: AtomicUsize::new(0),
-        }
+/// Tasks blocked in `read_char`.
+static KEY_READY: WaitQueue = WaitQueue::new();
+
+/// Keyboard command: reset and run the basic assurance test.
+const KBD_RESET: u8 = 0xFF;
+/// Keyboard replies.
+const KBD_ACK: u8 = 0xFA;
+const KBD_SELF_TEST_OK: u8 = 0xAA;
+/// The keyboard's self-test can take several hundred milliseconds.
+const KBD_RESET_POLLS: u32 = ps2::TIMEOUT_POLLS * 10;
+
+/// Bring the 8042 controller to a known state and reset the keyboard.
+/// Every step is bounded, so a missing or wedged controller costs a few
+/// timeouts and a logged warning rather than a hung boot. The mouse port
+/// is left disabled for `mouse::init` to bring up.
+pub fn init() {
+    // Quiesce both ports and drop whatever the firmware left behind
+    if !ps2::write_command(ps2::CMD_DISABLE_PORT1) || !ps2::write_command(ps2::CMD_DISABLE_PORT2) {
+        crate::log_warn!("PS/2: controller not responding, keyboard unavailable");
+        return;
     }
-
-    pub fn push(&self, key: KeyCode) -> Result<(), ()> {
-        let head = self.head.load(Ordering::Acquire);
-        let next_head = (head + 1) % BUFFER_SIZE;
-        
-        if next_head == self.tail.load(Ordering::Acquire) {
-            return Err(()); // Buffer Full
+    let stale = ps2::flush();
+
+    // IRQs off while we poll replies; keep scancode translation as is
+    let config = match ps2::read_config() {
+        Some(c) => c & !(ps2::CONFIG_PORT1_IRQ | ps2::CONFIG_PORT2_IRQ),
+        None => {
+            crate::log_warn!("PS/2: no reply to config read, keyboard unavailable");
+            return;
         }
+    };
+    ps2::write_config(config);
 
-        // To mutate within an atomic-tracked struct without `&mut self` or Cell wrapper in interrupts:
-        // We cast the reference natively relying on the atomic offsets preventing race overlaps between producer/consumer.
-        // In this no_std minimal driver without alloc, we use unsafe pointer cast to bypass borrow checker just for the array slot.
-        unsafe {
-            let slot = self.buffer.as_ptr().add(head) as *mut KeyCode;
-            *slot = key;
-        }
+    match ps2::command_reply(ps2::CMD_SELF_TEST) {
+        Some(0x55) => {}
+        reply => { crate::log_warn!("PS/2: controller self-test failed ({:x?})", reply); }
+    }
+    // The self-test resets the configuration on some controllers
+    ps2::write_config(config);
 
-        self.head.store(next_head, Ordering::Release);
-        Ok(())
+    match ps2::command_reply(ps2::CMD_TEST_PORT1) {
+        Some(0x00) => {}
+        reply => { crate::log_warn!("PS/2: keyboard port test failed ({:x?})", reply); }
     }
 
-    pub fn pop(&self) -> Option<KeyCode> {
-        let tail = self.tail.load(Ordering::Acquire);
-        
-        if tail == self.head.load(Ordering::Acquire) {
-            return None; // Buffer Empty
+    ps2::write_command(ps2::CMD_ENABLE_PORT1);
+
+    // Reset: the keyboard ACKs, then reports its self-test result
+    let mut ack = false;
+    let mut passed = false;
+    if ps2::write_data(KBD_RESET) {
+        for _ in 0..2 {
+            match ps2::read_data_within(KBD_RESET_POLLS) {
+                Some(KBD_ACK) => ack = true,
+                Some(KBD_SELF_TEST_OK) => passed = true,
+                _ => break,
+            }
         }
-
-        let key = unsafe {
-            let slot = self.buffer.as_ptr().add(tail) as *mut KeyCode;
-            *slot
-        };
-
-        self.tail.store((tail + 1) % BUFFER_SIZE, Ordering::Release);
-        Some(key)
     }
-}
-
-lazy_static! {
-    pub static ref KEYBOARD_BUFFER: KeyboardBuffer = KeyboardBuffer::new();
-    pub static ref KEYBOARD_STATE: Mutex<KeyboardState> = Mutex::new(KeyboardState::new());
-}
+    if !(ack && passed) {
+        crate::log_warn!("PS/2: keyboard reset incomplete (ack={}, self-test={})", ack, passed);
+    }
 
-pub fn init() {
-    // Escoar teclado inicial
-    let mut port: Port<u8> = Port::new(0x60);
-    // Lê o scancode residual se existir na inicialização da controladora 8042
-    let _scancode = unsafe { port.read() };
-    crate::log_info!("PS/2 Keyboard driver initialized.");
+    ps2::flush();
+    ps2::write_config(config | ps2::CONFIG_PORT1_IRQ);
+    crate::log_info!("PS/2 Keyboard driver initialized ({} stale byte(s) flushed).", stale);
 }
 
 pub fn push_scancode(scancode: u8) {
@@ -83,21 +94,42 @@ pub fn push_scancode(scancode: u8) {
     if let KeyCode::Unknown = keycode {
         return;
     }
+    drop(state);
+    push_key(keycode);
+}
+
+/// Queue a decoded key, from this driver or another input source (the
+/// serial console). Called from interrupt handlers. Ctrl-C goes to the
+/// foreground task as SIGINT instead when there is one.
+pub fn push_key(keycode: KeyCode) {
+    // Ctrl-C interrupts whatever the shell is running in the foreground
+    if keycode == KeyCode::Ctrl('c') && crate::scheduler::signal::interrupt_foreground() {
+        return;
+    }
+
+    // A task blocked reading the console takes the key first
+    if crate::drivers::tty::console::offer(keycode) {
+        return;
+    }
 
     // Try to enqueue
-    let _ = KEYBOARD_BUFFER.push(keycode);
+    if KEYBOARD_BUFFER.push(keycode).is_ok() {
+        KEY_READY.wake_all();
+    }
 }
 
 pub fn try_read_char() -> Option<KeyCode> {
     KEYBOARD_BUFFER.pop()
 }
 
+/// Block until a key is available. Printable keys, space included, arrive
+/// as `KeyCode::Char`; everything else has its own variant. The caller
+/// sleeps until the keyboard interrupt queues a key.
 pub fn read_char() -> KeyCode {
     loop {
         if let Some(key) = try_read_char() {
             return key;
         }
-        crate::scheduler::yield_now();
-        x86_64::instructions::interrupts::enable_and_hlt();
+        KEY_READY.wait_until(|| !KEYBOARD_BUFFER.is_empty());
     }
 }
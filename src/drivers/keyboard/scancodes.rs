@@ -3,11 +3,14 @@ pub enum KeyCode {
     Char(char),
     Enter,
     Backspace,
-    Space,
     ArrowUp,
     ArrowDown,
     ArrowLeft,
     ArrowRight,
+    PageUp,
+    PageDown,
+    /// A letter typed with Ctrl held, as its lowercase letter (Ctrl-D is `Ctrl('d')`).
+    Ctrl(char),
     F(u8),
     Unknown,
 }
@@ -48,6 +51,8 @@ impl KeyboardState {
                 0x4B => KeyCode::ArrowLeft,
                 0x4D => KeyCode::ArrowRight,
                 0x50 => KeyCode::ArrowDown,
+                0x49 => KeyCode::PageUp,
+                0x51 => KeyCode::PageDown,
                 
                 // Right Ctrl
                 0x1D => { self.ctrl_pressed = true; KeyCode::Unknown }
@@ -135,9 +140,13 @@ impl KeyboardState {
             0xB8 => { self.alt_pressed = false; KeyCode::Unknown }, // LAlt
 
             // Control Keys
-            0x39 => KeyCode::Space,
+            0x39 => KeyCode::Char(' '), // Space is an ordinary printable key
             0x1C => KeyCode::Enter,
             0x0E => KeyCode::Backspace,
+
+            // Keypad 9/3 (PgUp/PgDn with Num Lock off, which is all we support)
+            0x49 => KeyCode::PageUp,
+            0x51 => KeyCode::PageDown,
             
             // Function Keys
             0x3B => KeyCode::F(1),
@@ -160,6 +169,9 @@ impl KeyboardState {
 
     fn char_with_shift(&self, lower: char, upper: char) -> KeyCode {
         let is_letter = lower.is_ascii_lowercase();
+        if is_letter && self.ctrl_pressed {
+            return KeyCode::Ctrl(lower);
+        }
         
         let shift_active = if is_letter && self.caps_lock {
             !self.shift_pressed
@@ -2,11 +2,15 @@ pub mod keyboard;
 pub mod mouse;
 pub mod tty;
 pub mod ata;
+pub mod block;
+pub mod ps2;
+pub mod rtc;
 
 pub fn init() {
     keyboard::init();
     mouse::init();
     tty::init();
     ata::init();
+    rtc::init();
     crate::log_info!("Drivers subsystem initialized.");
 }
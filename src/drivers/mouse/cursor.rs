@@ -0,0 +1,108 @@
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+use super::MouseEvent;
+
+/// Text mode size the cursor is clamped to.
+const COLUMNS: i32 = 80;
+const ROWS: i32 = 25;
+/// Mouse movement counts per text cell: cells are 8x16 pixels on a 640x400 screen.
+const COUNTS_PER_COL: i32 = 8;
+const COUNTS_PER_ROW: i32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+    Middle,
+}
+
+/// A button press and the cell the cursor was on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Click {
+    pub button: Button,
+    pub row: usize,
+    pub col: usize,
+}
+
+/// Absolute position of the text-mode mouse cursor, built up from the
+/// relative movements in `MouseEvent`s.
+struct CursorState {
+    /// Position in movement counts, so slow movements still add up to a cell.
+    x: i32,
+    y: i32,
+    /// Buttons held at the last event, to log presses rather than every packet.
+    buttons: [bool; 3],
+    last_click: Option<Click>,
+    /// Drawn from the first mouse event on, until `hide`.
+    visible: bool,
+}
+
+impl CursorState {
+    const fn new() -> Self {
+        // Start in the middle of the screen
+        CursorState {
+            x: COLUMNS / 2 * COUNTS_PER_COL,
+            y: ROWS / 2 * COUNTS_PER_ROW,
+            buttons: [false; 3],
+            last_click: None,
+            visible: false,
+        }
+    }
+
+    fn cell(&self) -> (usize, usize) {
+        ((self.y / COUNTS_PER_ROW) as usize, (self.x / COUNTS_PER_COL) as usize)
+    }
+
+    fn apply(&mut self, event: &MouseEvent) {
+        self.visible = true;
+        self.x = (self.x + event.x_movement as i32).clamp(0, COLUMNS * COUNTS_PER_COL - 1);
+        self.y = (self.y + event.y_movement as i32).clamp(0, ROWS * COUNTS_PER_ROW - 1);
+
+        let buttons = [event.left_button, event.right_button, event.middle_button];
+        let names = [Button::Left, Button::Right, Button::Middle];
+        for i in 0..3 {
+            if buttons[i] && !self.buttons[i] {
+                let (row, col) = self.cell();
+                crate::log_info!("Mouse: {:?} click at row {}, column {}", names[i], row, col);
+                self.last_click = Some(Click { button: names[i], row, col });
+            }
+        }
+        self.buttons = buttons;
+    }
+}
+
+static CURSOR: Mutex<CursorState> = Mutex::new(CursorState::new());
+
+/// Apply every queued mouse event to the cursor and draw it where it ended
+/// up. Runs on every timer tick; if the cursor state is busy the events wait
+/// for the next tick, and so does the drawing if the screen is busy.
+pub fn pump() {
+    interrupts::without_interrupts(|| {
+        let Some(mut cursor) = CURSOR.try_lock() else { return };
+        while let Some(event) = super::try_read_event() {
+            cursor.apply(&event);
+        }
+        if cursor.visible {
+            let (row, col) = cursor.cell();
+            crate::vga::try_show_mouse(row, col);
+        }
+    });
+}
+
+/// Take the cursor off the screen until the mouse moves again.
+pub fn hide() {
+    interrupts::without_interrupts(|| {
+        CURSOR.lock().visible = false;
+        crate::vga::hide_mouse();
+    });
+}
+
+/// The cell the cursor is on, as (row, column).
+pub fn position() -> (usize, usize) {
+    interrupts::without_interrupts(|| CURSOR.lock().cell())
+}
+
+/// The most recent button press.
+pub fn last_click() -> Option<Click> {
+    interrupts::without_interrupts(|| CURSOR.lock().last_click)
+}
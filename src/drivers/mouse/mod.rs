@@ -1,10 +1,22 @@
-use core::sync::atomic::{AtomicUsize, Ordering};
+pub mod cursor;
+
 use spin::Mutex;
-use x86_64::instructions::port::Port;
+use super::ps2;
+use crate::util::spsc::SpscRing;
 use lazy_static::lazy_static;
 
 const BUFFER_SIZE: usize = 256;
 
+/// Mouse commands (sent through the controller's second port) and replies.
+const MOUSE_GET_DEVICE_ID: u8 = 0xF2;
+const MOUSE_SET_SAMPLE_RATE: u8 = 0xF3;
+const MOUSE_ENABLE_STREAMING: u8 = 0xF4;
+const MOUSE_ACK: u8 = 0xFA;
+/// Device ID of an IntelliMouse: 4-byte packets with a scroll wheel.
+const DEVICE_ID_WHEEL: u8 = 3;
+/// The "magic knock" of sample rates that unlocks the wheel.
+const WHEEL_SAMPLE_RATES: [u8; 3] = [200, 100, 80];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MouseEvent {
     pub left_button: bool,
@@ -12,76 +24,48 @@ pub struct MouseEvent {
     pub middle_button: bool,
     pub x_movement: i16,
     pub y_movement: i16,
+    /// Scroll wheel: negative is up (away from the user), 0 without a wheel.
+    pub z_movement: i8,
 }
 
 impl MouseEvent {
     pub const fn empty() -> Self {
-        Self { left_button: false, right_button: false, middle_button: false, x_movement: 0, y_movement: 0 }
+        Self { left_button: false, right_button: false, middle_button: false, x_movement: 0, y_movement: 0, z_movement: 0 }
     }
 }
 
-pub struct MouseBuffer {
-    buffer: [MouseEvent; BUFFER_SIZE],
-    head: AtomicUsize,
-    tail: AtomicUsize,
-}
-
-impl MouseBuffer {
-    pub const fn new() -> Self {
-        Self {
-            buffer: [MouseEvent::empty(); BUFFER_SIZE],
-            head: AtomicUsize::new(0),
-            tail: AtomicUsize::new(0),
-        }
-    }
-
-    pub fn push(&self, event: MouseEvent) -> Result<(), ()> {
-        let head = self.head.load(Ordering::Acquire);
-        let next_head = (head + 1) % BUFFER_SIZE;
-        
-        if next_head == self.tail.load(Ordering::Acquire) {
-            return Err(()); // Buffer Full
-        }
-
-        unsafe {
-            let slot = self.buffer.as_ptr().add(head) as *mut MouseEvent;
-            *slot = event;
-        }
-
-        self.head.store(next_head, Ordering::Release);
-        Ok(())
-    }
-
-    pub fn pop(&self) -> Option<MouseEvent> {
-        let tail = self.tail.load(Ordering::Acquire);
-        
-        if tail == self.head.load(Ordering::Acquire) {
-            return None; // Buffer Empty
-        }
+/// Events decoded from mouse packets, queued by IRQ12 for the cursor.
+pub type MouseBuffer = SpscRing<MouseEvent, BUFFER_SIZE>;
 
-        let event = unsafe {
-            let slot = self.buffer.as_ptr().add(tail) as *mut MouseEvent;
-            *slot
-        };
-
-        self.tail.store((tail + 1) % BUFFER_SIZE, Ordering::Release);
-        Some(event)
-    }
-}
+pub static MOUSE_BUFFER: MouseBuffer = MouseBuffer::new(MouseEvent::empty());
 
 pub struct MouseState {
-    packet: [u8; 3],
+    packet: [u8; 4],
     bytes_received: usize,
+    /// 3 bytes per packet, or 4 once the scroll wheel is enabled.
+    packet_len: usize,
 }
 
 impl MouseState {
     pub const fn new() -> Self {
         Self {
-            packet: [0; 3],
+            packet: [0; 4],
             bytes_received: 0,
+            packet_len: 3,
         }
     }
 
+    /// Switch between 3-byte packets and 4-byte wheel packets, dropping any
+    /// partial packet.
+    pub fn set_wheel(&mut self, wheel: bool) {
+        self.packet_len = if wheel { 4 } else { 3 };
+        self.bytes_received = 0;
+    }
+
+    pub fn has_wheel(&self) -> bool {
+        self.packet_len == 4
+    }
+
     pub fn process_byte(&mut self, byte: u8) -> Option<MouseEvent> {
         // PS/2 limits packet alignment with bit 3 of the 1st byte always being 1
         if self.bytes_received == 0 && (byte & 0x08) == 0 {
@@ -91,7 +75,7 @@ impl MouseState {
         self.packet[self.bytes_received] = byte;
         self.bytes_received += 1;
 
-        if self.bytes_received == 3 {
+        if self.bytes_received == self.packet_len {
             self.bytes_received = 0;
             return Some(self.parse_packet());
         }
@@ -118,75 +102,77 @@ impl MouseState {
         // Y overflow/sign (also PS/2 Y is bottom-left, typically screens are top-left so we invert later)
         let y_final = if y_sign { y_mov - 256 } else { y_mov };
 
+        // Z is a 4-bit two's complement value in the low nibble of byte 4
+        let z_final = if self.has_wheel() { ((self.packet[3] << 4) as i8) >> 4 } else { 0 };
+
         MouseEvent {
             left_button: left,
             right_button: right,
             middle_button: middle,
             x_movement: x_final,
             y_movement: -y_final, // Inverted for top-left 0,0 mapping
+            z_movement: z_final,
         }
     }
 }
 
 lazy_static! {
-    pub static ref MOUSE_BUFFER: MouseBuffer = MouseBuffer::new();
     pub static ref MOUSE_STATE: Mutex<MouseState> = Mutex::new(MouseState::new());
 }
 
-fn wait_write_ready() {
-    let mut status_port: Port<u8> = Port::new(0x64);
-    // Wait until bit 1 is clear (input buffer empty)
-    while unsafe { status_port.read() } & 0x02 != 0 {}
-}
-
-fn wait_read_ready() {
-    let mut status_port: Port<u8> = Port::new(0x64);
-    // Wait until bit 0 is set (output buffer full)
-    while unsafe { status_port.read() } & 0x01 == 0 {}
-}
-
-fn write_command(cmd: u8) {
-    wait_write_ready();
-    let mut cmd_port: Port<u8> = Port::new(0x64);
-    unsafe { cmd_port.write(cmd) };
-}
-
-fn write_data(data: u8) {
-    wait_write_ready();
-    let mut data_port: Port<u8> = Port::new(0x60);
-    unsafe { data_port.write(data) };
-}
-
-fn read_data() -> u8 {
-    wait_read_ready();
-    let mut data_port: Port<u8> = Port::new(0x60);
-    unsafe { data_port.read() }
-}
-
 pub fn init() {
     // Enable Aux Port on Controller
-    write_command(0xA8); 
+    ps2::write_command(ps2::CMD_ENABLE_PORT2);
 
     // Retrieve Compaq Status Byte
-    write_command(0x20); 
-    let mut status = read_data();
+    let status = match ps2::read_config() {
+        Some(status) => status,
+        None => {
+            crate::log_warn!("PS/2: no reply to config read, mouse unavailable");
+            return;
+        }
+    };
+
+    // Enable the clock line, but keep IRQ12 off while we poll for replies
+    let status = status & !ps2::CONFIG_PORT2_CLOCK_OFF;
+    ps2::write_config(status & !ps2::CONFIG_PORT2_IRQ);
+
+    let wheel = enable_scroll_wheel();
+    MOUSE_STATE.lock().set_wheel(wheel);
 
-    // Enable IRQ12 and disable clock line
-    status |= 0b0000_0010; 
-    status &= 0b1101_1111;
+    if !mouse_command(MOUSE_ENABLE_STREAMING) {
+        crate::log_warn!("PS/2: mouse did not acknowledge streaming mode");
+    }
 
-    // Write back Compaq Status Byte
-    write_command(0x60);
-    write_data(status);
+    // Packets arrive by IRQ12 from now on
+    ps2::write_config(status | ps2::CONFIG_PORT2_IRQ);
 
-    // Send the Enable Packet Streaming command to the mouse
-    write_command(0xD4); // Tell controller to talk to mouse
-    write_data(0xF4);    // Enable packet streaming
+    if wheel {
+        crate::log_info!("PS/2 Mouse driver initialized (IntelliMouse, scroll wheel).");
+    } else {
+        crate::log_info!("PS/2 Mouse driver initialized.");
+    }
+}
 
-    // Read the ACK from the mouse (should be 0xFA)
-    let _ack = read_data();
-    
-    crate::log_info!("PS/2 Mouse driver initialized.");
+/// Send one byte to the mouse and wait for its acknowledgement.
+fn mouse_command(byte: u8) -> bool {
+    ps2::write_command(ps2::CMD_WRITE_PORT2) && ps2::write_data(byte) && ps2::read_data() == Some(MOUSE_ACK)
+}
+
+/// The IntelliMouse handshake: set the sample rate to 200, 100 and 80, then
+/// read the device ID again. A wheel mouse now reports ID 3 and sends 4-byte
+/// packets; anything else (including no reply) keeps the 3-byte protocol.
+fn enable_scroll_wheel() -> bool {
+    for rate in WHEEL_SAMPLE_RATES {
+        if !mouse_command(MOUSE_SET_SAMPLE_RATE) || !mouse_command(rate) {
+            crate::log_warn!("PS/2: mouse rejected sample rate {}, no scroll wheel", rate);
+            return false;
+        }
+    }
+    if !mouse_command(MOUSE_GET_DEVICE_ID) {
+        return false;
+    }
+    ps2::read_data() == Some(DEVICE_ID_WHEEL)
 }
 
 pub fn push_byte(byte: u8) {
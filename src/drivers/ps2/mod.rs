@@ -0,0 +1,107 @@
+use x86_64::instructions::port::Port;
+
+/// 8042 data port (device bytes and command parameters).
+const DATA_PORT: u16 = 0x60;
+/// 8042 status (read) / command (write) port.
+const CMD_PORT: u16 = 0x64;
+
+/// Status bit 0: output buffer full (a byte is waiting at 0x60).
+const STATUS_OUTPUT_FULL: u8 = 0x01;
+/// Status bit 1: input buffer full (the controller hasn't taken our last byte).
+const STATUS_INPUT_FULL: u8 = 0x02;
+
+/// Status polls before giving up on the controller (each port access takes
+/// about a microsecond, so roughly 100 ms).
+pub const TIMEOUT_POLLS: u32 = 100_000;
+
+/// Controller commands.
+pub const CMD_READ_CONFIG: u8 = 0x20;
+pub const CMD_WRITE_CONFIG: u8 = 0x60;
+pub const CMD_DISABLE_PORT2: u8 = 0xA7;
+pub const CMD_ENABLE_PORT2: u8 = 0xA8;
+pub const CMD_SELF_TEST: u8 = 0xAA;
+pub const CMD_TEST_PORT1: u8 = 0xAB;
+pub const CMD_DISABLE_PORT1: u8 = 0xAD;
+pub const CMD_ENABLE_PORT1: u8 = 0xAE;
+pub const CMD_WRITE_PORT2: u8 = 0xD4;
+
+/// Configuration byte bits.
+pub const CONFIG_PORT1_IRQ: u8 = 0x01;
+pub const CONFIG_PORT2_IRQ: u8 = 0x02;
+pub const CONFIG_PORT2_CLOCK_OFF: u8 = 0x20;
+
+fn status() -> u8 {
+    let mut port: Port<u8> = Port::new(CMD_PORT);
+    unsafe { port.read() }
+}
+
+/// Spin until the controller can accept a byte. False on timeout.
+fn wait_write_ready() -> bool {
+    (0..TIMEOUT_POLLS).any(|_| status() & STATUS_INPUT_FULL == 0)
+}
+
+/// Spin until a byte is waiting, for at most `polls` status reads.
+fn wait_read_ready(polls: u32) -> bool {
+    (0..polls).any(|_| status() & STATUS_OUTPUT_FULL != 0)
+}
+
+/// Send a controller command. False if the controller never accepted it.
+pub fn write_command(cmd: u8) -> bool {
+    if !wait_write_ready() {
+        return false;
+    }
+    let mut port: Port<u8> = Port::new(CMD_PORT);
+    unsafe { port.write(cmd) };
+    true
+}
+
+/// Send a byte to the data port (command parameter or device byte).
+pub fn write_data(data: u8) -> bool {
+    if !wait_write_ready() {
+        return false;
+    }
+    let mut port: Port<u8> = Port::new(DATA_PORT);
+    unsafe { port.write(data) };
+    true
+}
+
+/// Read the next byte from the data port, or None after TIMEOUT_POLLS.
+pub fn read_data() -> Option<u8> {
+    read_data_within(TIMEOUT_POLLS)
+}
+
+/// Read the next byte from the data port, waiting at most `polls` status reads.
+pub fn read_data_within(polls: u32) -> Option<u8> {
+    if !wait_read_ready(polls) {
+        return None;
+    }
+    let mut port: Port<u8> = Port::new(DATA_PORT);
+    Some(unsafe { port.read() })
+}
+
+/// Discard stale bytes left in the output buffer. Returns how many were dropped.
+pub fn flush() -> usize {
+    let mut port: Port<u8> = Port::new(DATA_PORT);
+    // A present controller empties after a handful of reads; a missing one
+    // floats the bus at 0xFF, so bound the loop
+    (0..16)
+        .take_while(|_| status() & STATUS_OUTPUT_FULL != 0)
+        .map(|_| unsafe { port.read() })
+        .count()
+}
+
+/// Send a controller command and read its one-byte reply.
+pub fn command_reply(cmd: u8) -> Option<u8> {
+    if !write_command(cmd) {
+        return None;
+    }
+    read_data()
+}
+
+pub fn read_config() -> Option<u8> {
+    command_reply(CMD_READ_CONFIG)
+}
+
+pub fn write_config(config: u8) -> bool {
+    write_command(CMD_WRITE_CONFIG) && write_data(config)
+}
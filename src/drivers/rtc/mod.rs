@@ -0,0 +1,183 @@
+use core::fmt;
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+use x86_64::instructions::port::Port;
+
+const CMOS_ADDRESS: u16 = 0x70;
+const CMOS_DATA: u16 = 0x71;
+
+const REG_SECONDS: u8 = 0x00;
+const REG_MINUTES: u8 = 0x02;
+const REG_HOURS: u8 = 0x04;
+const REG_DAY: u8 = 0x07;
+const REG_MONTH: u8 = 0x08;
+const REG_YEAR: u8 = 0x09;
+/// Where PCs (and QEMU) keep the century. ACPI's FADT names the register,
+/// but without an ACPI parser the conventional one is assumed and checked.
+const REG_CENTURY: u8 = 0x32;
+const REG_STATUS_A: u8 = 0x0A;
+const REG_STATUS_B: u8 = 0x0B;
+
+/// Status A: the clock is updating its registers; reads may be torn.
+const STATUS_A_UPDATING: u8 = 1 << 7;
+/// Status B: hours are 0-23 rather than 1-12 with a PM flag.
+pub const STATUS_B_24_HOUR: u8 = 1 << 1;
+/// Status B: registers hold binary values rather than BCD.
+pub const STATUS_B_BINARY: u8 = 1 << 2;
+/// In 12-hour mode, set in the hours register for PM.
+const HOUR_PM: u8 = 1 << 7;
+
+/// Selecting a register and reading it are two port accesses; keep them together.
+static CMOS: Mutex<()> = Mutex::new(());
+
+/// Wall-clock time as kept by the RTC (UTC on QEMU and most setups).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Seconds since 1970-01-01 00:00:00 UTC.
+    pub fn unix_time(&self) -> u64 {
+        // Days from the civil date (Howard Hinnant's days_from_civil, March-based years)
+        let y = if self.month <= 2 { self.year as i64 - 1 } else { self.year as i64 };
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let m = self.month as i64;
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146097 + doe - 719468;
+
+        (days * 86400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64).max(0) as u64
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second)
+    }
+}
+
+/// The time and date registers as read, still in the clock's own encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawTime {
+    pub second: u8,
+    pub minute: u8,
+    pub hour: u8,
+    pub day: u8,
+    pub month: u8,
+    pub year: u8,
+    pub century: u8,
+}
+
+impl RawTime {
+    /// Decode the registers according to status register B: BCD or binary,
+    /// and 12-hour (PM in bit 7 of the hours) or 24-hour mode.
+    pub fn decode(&self, status_b: u8) -> DateTime {
+        let binary = status_b & STATUS_B_BINARY != 0;
+        let value = |v: u8| if binary { v } else { bcd_to_dec(v) };
+
+        let mut hour = value(self.hour & !HOUR_PM);
+        if status_b & STATUS_B_24_HOUR == 0 {
+            // 12 AM is midnight, 12 PM is noon
+            hour %= 12;
+            if self.hour & HOUR_PM != 0 {
+                hour += 12;
+            }
+        }
+
+        // A century register that is missing reads as garbage: assume 20xx then
+        let century = match value(self.century) {
+            c @ 19..=99 => c as u16,
+            _ => 20,
+        };
+
+        DateTime {
+            year: century * 100 + value(self.year) as u16,
+            month: value(self.month),
+            day: value(self.day),
+            hour,
+            minute: value(self.minute),
+            second: value(self.second),
+        }
+    }
+}
+
+/// Read a single CMOS register. The caller holds `CMOS` with interrupts off.
+fn read_cmos(reg: u8) -> u8 {
+    let mut addr: Port<u8> = Port::new(CMOS_ADDRESS);
+    let mut data: Port<u8> = Port::new(CMOS_DATA);
+    unsafe {
+        addr.write(reg);
+        data.read()
+    }
+}
+
+fn updating() -> bool {
+    read_cmos(REG_STATUS_A) & STATUS_A_UPDATING != 0
+}
+
+fn read_raw() -> RawTime {
+    RawTime {
+        second: read_cmos(REG_SECONDS),
+        minute: read_cmos(REG_MINUTES),
+        hour: read_cmos(REG_HOURS),
+        day: read_cmos(REG_DAY),
+        month: read_cmos(REG_MONTH),
+        year: read_cmos(REG_YEAR),
+        century: read_cmos(REG_CENTURY),
+    }
+}
+
+/// Convert BCD-encoded byte to decimal.
+fn bcd_to_dec(bcd: u8) -> u8 {
+    (bcd & 0x0F) + ((bcd >> 4) * 10)
+}
+
+/// Registers and status register B, read consistently: after waiting out
+/// an update in progress, the registers are read until two passes agree,
+/// so a rollover between two reads (59 -> 00) can't mix old and new values.
+pub fn read_raw_time() -> (RawTime, u8) {
+    interrupts::without_interrupts(|| {
+        let _cmos = CMOS.lock();
+        while updating() {
+            core::hint::spin_loop();
+        }
+        let mut last = read_raw();
+        loop {
+            while updating() {
+                core::hint::spin_loop();
+            }
+            let now = read_raw();
+            if now == last {
+                break;
+            }
+            last = now;
+        }
+        (last, read_cmos(REG_STATUS_B))
+    })
+}
+
+/// Current wall-clock time.
+pub fn read_datetime() -> DateTime {
+    let (raw, status_b) = read_raw_time();
+    raw.decode(status_b)
+}
+
+/// Current RTC time as seconds since 1970-01-01 00:00:00 UTC.
+pub fn unix_time() -> u64 {
+    read_datetime().unix_time()
+}
+
+pub fn init() {
+    let (raw, status_b) = read_raw_time();
+    crate::log_info!("RTC: {} UTC ({}, {}-hour)", raw.decode(status_b),
+        if status_b & STATUS_B_BINARY != 0 { "binary" } else { "BCD" },
+        if status_b & STATUS_B_24_HOUR != 0 { 24 } else { 12 });
+}
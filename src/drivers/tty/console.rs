@@ -0,0 +1,143 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+use crate::drivers::keyboard::{self, scancodes::KeyCode};
+use crate::scheduler::wait::WaitQueue;
+
+/// Longest line a console read can collect, newline included.
+pub const LINE_MAX: usize = 256;
+
+/// Kernel-side line editing for console (fd 0) reads. While a task is
+/// reading, keys go here instead of to the shell: printable characters are
+/// echoed, Backspace edits the line, and Enter completes it. Ctrl-D hands
+/// over the line typed so far without a newline, so on an empty line the
+/// read returns 0 (end of file). Fixed-size so the keyboard interrupt
+/// never allocates.
+struct LineBuffer {
+    buf: [u8; LINE_MAX],
+    len: usize,
+    /// Enter or Ctrl-D was pressed; `buf[..len]` is ready to read.
+    complete: bool,
+    /// Bytes of a complete line already handed to readers.
+    consumed: usize,
+}
+
+impl LineBuffer {
+    const fn new() -> Self {
+        LineBuffer { buf: [0; LINE_MAX], len: 0, complete: false, consumed: 0 }
+    }
+
+    /// Apply one key to the line being edited.
+    fn edit(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char(c) if c.is_ascii() => {
+                // Keep the last slot for the newline
+                if self.len < LINE_MAX - 1 {
+                    self.buf[self.len] = c as u8;
+                    self.len += 1;
+                    crate::print!("{}", c);
+                }
+            }
+            KeyCode::Backspace => {
+                if self.len > 0 {
+                    self.len -= 1;
+                    crate::vga::backspace();
+                    crate::serial::console_backspace();
+                }
+            }
+            KeyCode::PageUp => crate::vga::scroll_up(super::SCROLL_STEP),
+            KeyCode::PageDown => crate::vga::scroll_down(super::SCROLL_STEP),
+            KeyCode::Enter => {
+                self.buf[self.len] = b'\n';
+                self.len += 1;
+                self.complete = true;
+                crate::println!();
+            }
+            KeyCode::Ctrl('d') => self.complete = true,
+            _ => {}
+        }
+    }
+
+    /// Copy as much of the completed line as fits into `out`. What doesn't
+    /// fit stays for the next read.
+    fn take(&mut self, out: &mut [u8]) -> usize {
+        // Ctrl-D on an empty line: nothing to copy, and the read reports EOF
+        let n = out.len().min(self.len - self.consumed);
+        out[..n].copy_from_slice(&self.buf[self.consumed..self.consumed + n]);
+        self.consumed += n;
+        if self.consumed == self.len {
+            *self = LineBuffer::new();
+        }
+        n
+    }
+}
+
+static LINE: Mutex<LineBuffer> = Mutex::new(LineBuffer::new());
+/// Tasks blocked in `read_line`.
+static LINE_READY: WaitQueue = WaitQueue::new();
+/// How many tasks are inside `read_line`; keys are only diverted while > 0.
+static READERS: AtomicUsize = AtomicUsize::new(0);
+
+/// Called by the keyboard interrupt for every key. Returns true if a
+/// console reader took the key, false if it belongs in the shell's buffer.
+pub fn offer(key: KeyCode) -> bool {
+    if READERS.load(Ordering::Acquire) == 0 {
+        return false;
+    }
+    let completed = interrupts::without_interrupts(|| {
+        let mut line = LINE.lock();
+        // A finished line waits for its reader; later keys queue up as type-ahead
+        if line.complete {
+            return None;
+        }
+        line.edit(key);
+        Some(line.complete)
+    });
+    match completed {
+        Some(true) => {
+            LINE_READY.wake_all();
+            true
+        }
+        Some(false) => true,
+        None => false,
+    }
+}
+
+/// True if a console read would find input waiting: a completed line, or
+/// keys typed while nobody was reading.
+pub fn input_ready() -> bool {
+    interrupts::without_interrupts(|| LINE.lock().complete) || !keyboard::KEYBOARD_BUFFER.is_empty()
+}
+
+/// Read one line from the keyboard into `buf`, blocking until Enter is
+/// pressed. The line includes its trailing '\n'; a line longer than `buf`
+/// is returned over several calls. Keys typed before the call are used first.
+/// Returns 0 if Ctrl-D was pressed on an empty line, or if a signal
+/// arrived first (see `scheduler::signal::pending`).
+pub fn read_line(buf: &mut [u8]) -> usize {
+    if buf.is_empty() {
+        return 0;
+    }
+    READERS.fetch_add(1, Ordering::AcqRel);
+
+    // Type-ahead: keys that reached the shell's buffer while nobody was reading
+    interrupts::without_interrupts(|| {
+        let mut line = LINE.lock();
+        while !line.complete {
+            match keyboard::try_read_char() {
+                Some(key) => line.edit(key),
+                None => break,
+            }
+        }
+    });
+
+    // A signal ends the read early; the line stays for the next reader
+    let n = if LINE_READY.wait_until(|| LINE.lock().complete) {
+        interrupts::without_interrupts(|| LINE.lock().take(buf))
+    } else {
+        0
+    };
+
+    READERS.fetch_sub(1, Ordering::AcqRel);
+    n
+}
@@ -1,19 +1,32 @@
+pub mod console;
+
 use crate::{print, println};
 use crate::drivers::keyboard;
 use crate::drivers::keyboard::scancodes::KeyCode;
 use alloc::string::String;
 
+/// Lines moved per PageUp/PageDown: half a screen, so some context stays in view.
+pub const SCROLL_STEP: usize = 12;
+
 pub fn init() {
     crate::log_info!("Virtual TTY System initialized.");
     print_prompt();
 }
 
+/// Print the prompt; Backspace won't erase it, even once a long command
+/// line wraps or the screen scrolls.
 pub fn print_prompt() {
-    let cwd = crate::shell::state::CWD.lock().clone();
+    let cwd = crate::shell::state::cwd();
     let display = if cwd == "/" { "~".into() } else { cwd };
-    print!("root@atomicos:{}$ ", display);
+    // Bold green user@host, bold blue directory (bright colors on VGA)
+    print!("\x1b[1;32m{}@{}\x1b[0m:\x1b[1;34m{}\x1b[0m$ ",
+        crate::system_info::current_user(), crate::system_info::hostname(), display);
+    crate::vga::set_backspace_limit(true);
 }
 
+/// Line editing for the shell. Every key from `keyboard::read_char` is
+/// dispatched here; keys typed while a task reads the console go to
+/// `console` instead.
 pub fn process_input_loop() -> ! {
     x86_64::instructions::interrupts::enable();
     let mut command_buffer = String::new();
@@ -26,11 +39,8 @@ pub fn process_input_loop() -> ! {
                 print!("{}", c);
                 command_buffer.push(c);
             }
-            KeyCode::Space => {
-                print!(" ");
-                command_buffer.push(' ');
-            }
             KeyCode::Enter => {
+                crate::vga::set_backspace_limit(false);
                 println!();
                 // Dispatch to shell command system
                 crate::shell::exec_command(&command_buffer);
@@ -40,22 +50,19 @@ pub fn process_input_loop() -> ! {
             KeyCode::Backspace => {
                 if !command_buffer.is_empty() {
                     command_buffer.pop();
-                    crate::vga::WRITER.lock().backspace();
+                    crate::vga::backspace();
+                    crate::serial::console_backspace();
                 }
             },
             KeyCode::ArrowUp => {},
             KeyCode::ArrowDown => {},
             KeyCode::ArrowLeft => {},
             KeyCode::ArrowRight => {},
+            KeyCode::PageUp => crate::vga::scroll_up(SCROLL_STEP),
+            KeyCode::PageDown => crate::vga::scroll_down(SCROLL_STEP),
+            KeyCode::Ctrl(_) => {},
             KeyCode::F(_) => {},
             KeyCode::Unknown => {}
         }
-
-        // Non-blocking mouse event check
-        if let Some(mouse_event) = crate::drivers::mouse::try_read_event() {
-            if mouse_event.left_button {
-                crate::log_info!("Mouse Click at X:{}, Y:{}", mouse_event.x_movement, mouse_event.y_movement);
-            }
-        }
     }
 }
@@ -0,0 +1,96 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::dentry::DirEntry;
+use super::error::{FsError, FsResult};
+use super::inode::{FileType, Inode};
+use super::mount::FileSystem;
+
+/// Inode id of the devfs root directory.
+const ROOT_ID: u64 = 0;
+/// Inode id of `/dev/kmsg`.
+const KMSG_ID: u64 = 1;
+
+/// Pseudo-filesystem exposing kernel state as read-only files.
+/// Contents are generated on every read; nothing is stored.
+pub struct DevFs;
+
+/// The single devfs instance, mounted at /dev.
+pub static DEVFS_INSTANCE: DevFs = DevFs;
+
+impl DevFs {
+    fn kmsg_inode() -> Inode {
+        Inode { id: KMSG_ID, file_type: FileType::File, size: kmsg_len(), mode: 0o444 }
+    }
+}
+
+/// Visit the kernel log as text, one `"<entry>\n"` line at a time.
+fn for_each_kmsg_line(mut f: impl FnMut(&str) -> bool) {
+    use core::fmt::Write;
+    let (first, next) = crate::klog::seq_range();
+    let mut line = String::new();
+    for seq in first..next {
+        if let Some(entry) = crate::klog::get(seq) {
+            line.clear();
+            let _ = writeln!(line, "{}", entry);
+            if !f(&line) { break; }
+        }
+    }
+}
+
+fn kmsg_len() -> usize {
+    let mut len = 0;
+    for_each_kmsg_line(|line| { len += line.len(); true });
+    len
+}
+
+impl FileSystem for DevFs {
+    fn name(&self) -> &str {
+        "devfs"
+    }
+
+    fn lookup(&self, path: &str) -> FsResult<Inode> {
+        match path.trim_matches('/') {
+            "" => Ok(Inode { id: ROOT_ID, file_type: FileType::Directory, size: 1, mode: 0o555 }),
+            "kmsg" => Ok(Self::kmsg_inode()),
+            _ => Err(FsError::NotFound),
+        }
+    }
+
+    fn read(&self, path: &str, offset: usize, buf: &mut [u8]) -> FsResult<usize> {
+        match path.trim_matches('/') {
+            "" => return Err(FsError::IsADirectory),
+            "kmsg" => {}
+            _ => return Err(FsError::NotFound),
+        }
+
+        // Skip whole lines before `offset`, then copy until `buf` is full
+        let mut pos = 0;
+        let mut written = 0;
+        for_each_kmsg_line(|line| {
+            let bytes = line.as_bytes();
+            let line_end = pos + bytes.len();
+            if line_end > offset {
+                let from = offset.saturating_sub(pos);
+                let n = (bytes.len() - from).min(buf.len() - written);
+                buf[written..written + n].copy_from_slice(&bytes[from..from + n]);
+                written += n;
+            }
+            pos = line_end;
+            written < buf.len()
+        });
+        Ok(written)
+    }
+
+    fn readdir(&self, path: &str) -> FsResult<Vec<DirEntry>> {
+        match path.trim_matches('/') {
+            "" => Ok(alloc::vec![DirEntry { name: String::from("kmsg"), inode: Self::kmsg_inode() }]),
+            "kmsg" => Err(FsError::NotADirectory),
+            _ => Err(FsError::NotFound),
+        }
+    }
+
+    fn sync(&self) -> FsResult<()> {
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use alloc::string::String;
 use core::fmt;
 
 /// Filesystem error types.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FsError {
     NotFound,
     AlreadyExists,
@@ -12,6 +12,13 @@ pub enum FsError {
     IoError,
     NoSpace,
     NotMounted,
+    NotSupported,
+    TooManyLinks,
+    ReadOnly,
+    /// Rename between two different mounts.
+    CrossDevice,
+    /// The entry's permission bits forbid the access.
+    PermissionDenied,
 }
 
 impl fmt::Display for FsError {
@@ -25,6 +32,11 @@ impl fmt::Display for FsError {
             FsError::IoError => write!(f, "I/O error"),
             FsError::NoSpace => write!(f, "No space left"),
             FsError::NotMounted => write!(f, "No filesystem mounted at path"),
+            FsError::NotSupported => write!(f, "Operation not supported"),
+            FsError::TooManyLinks => write!(f, "Too many levels of symbolic links"),
+            FsError::ReadOnly => write!(f, "Read-only file system"),
+            FsError::CrossDevice => write!(f, "Invalid cross-device link"),
+            FsError::PermissionDenied => write!(f, "Permission denied"),
         }
     }
 }
@@ -1,12 +1,14 @@
 use alloc::string::String;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
 use spin::Mutex;
 
-use crate::drivers::ata::PRIMARY_ATA;
+use crate::drivers::block::BlockDevice;
+use crate::drivers::rtc::DateTime;
 use crate::fs::dentry::DirEntry as VfsDirEntry;
 use crate::fs::error::{FsError, FsResult};
-use crate::fs::inode::{FileType, Inode};
+use crate::fs::inode::{FileType, Inode, DEFAULT_DIR_MODE, DEFAULT_FILE_MODE, MODE_WRITE};
 use crate::fs::mount::FileSystem;
 
 // ══════════════════════════════════════════════════════════════
@@ -14,15 +16,18 @@ use crate::fs::mount::FileSystem;
 // ══════════════════════════════════════════════════════════════
 
 const SECTOR_SIZE: usize = 512;
+/// FAT32 entries per FAT sector.
+const FAT_ENTRIES_PER_SECTOR: u64 = (SECTOR_SIZE / 4) as u64;
 const DIR_ENTRY_SIZE: usize = 32;
 const ENTRIES_PER_SECTOR: usize = SECTOR_SIZE / DIR_ENTRY_SIZE;
 
 // FAT32 special cluster values
 const FAT_EOC: u32   = 0x0FFF_FFF8; // end-of-chain marker (>= this)
+const FAT_BAD: u32   = 0x0FFF_FFF7; // bad-cluster marker
 const FAT_FREE: u32  = 0x0000_0000;
 
 // Directory entry attribute bits
-// const ATTR_READ_ONLY: u8 = 0x01;
+const ATTR_READ_ONLY: u8 = 0x01;
 // const ATTR_HIDDEN: u8    = 0x02;
 // const ATTR_SYSTEM: u8    = 0x04;
 const ATTR_VOLUME_ID: u8 = 0x08;
@@ -30,6 +35,11 @@ const ATTR_DIRECTORY: u8 = 0x10;
 const ATTR_ARCHIVE: u8   = 0x20;
 const ATTR_LFN: u8       = 0x0F;
 
+/// 8.3 name of the entry pointing back at a directory's parent.
+const DOTDOT_NAME: [u8; 11] = *b"..         ";
+/// Deepest directory nesting followed when walking up through "..".
+const MAX_DIR_DEPTH: usize = 256;
+
 // ══════════════════════════════════════════════════════════════
 //  BPB — BIOS Parameter Block (parsed from boot sector)
 // ══════════════════════════════════════════════════════════════
@@ -43,9 +53,12 @@ struct Bpb {
     total_sectors: u32,
     fat_size: u32,         // sectors per FAT
     root_cluster: u32,
+    oem_name: String,      // bytes 3..11, informational only
+    bpb_label: String,     // extended BPB volume label (bytes 71..82)
     // Computed
-    fat_start: u32,        // first sector of FAT
-    data_start: u32,       // first sector of data area
+    fat_start: u64,        // first sector of FAT
+    data_start: u64,       // first sector of data area
+    total_clusters: u32,   // data clusters, numbered 2..total_clusters+2
 }
 
 impl Bpb {
@@ -72,8 +85,46 @@ impl Bpb {
 
         let root_cluster = u32::from_le_bytes([sector[44], sector[45], sector[46], sector[47]]);
 
-        let fat_start = reserved_sectors as u32;
-        let data_start = fat_start + (num_fats as u32) * fat_size;
+        let oem_name = trim_padded(&sector[3..11]);
+        // The label is only valid when the extended boot signature (0x29) is present
+        let bpb_label = if sector[66] == 0x29 { trim_padded(&sector[71..82]) } else { String::new() };
+
+        // Sanity-check the geometry before any of it is used to compute an LBA
+        let reject = |why: &str| {
+            crate::log_warn!("FAT32: rejecting BPB: {}", why);
+            Err(FsError::InvalidPath)
+        };
+        if bytes_per_sector as usize != SECTOR_SIZE {
+            return reject("bytes per sector is not 512");
+        }
+        if !sectors_per_cluster.is_power_of_two() {
+            return reject("sectors per cluster is not a power of two");
+        }
+        if reserved_sectors == 0 || num_fats == 0 || fat_size == 0 {
+            return reject("zero reserved sectors, FAT count or FAT size");
+        }
+        let data_start = reserved_sectors as u64 + num_fats as u64 * fat_size as u64;
+        if data_start >= total_sectors as u64 {
+            return reject("data area starts beyond the end of the volume");
+        }
+        let total_clusters = (total_sectors as u64 - data_start) / sectors_per_cluster as u64;
+        if total_clusters == 0 {
+            return reject("no data clusters");
+        }
+        if total_clusters + 2 > FAT_BAD as u64 {
+            return reject("more clusters than FAT32 can number");
+        }
+        if fat_size as u64 * FAT_ENTRIES_PER_SECTOR < total_clusters + 2 {
+            return reject("FAT too small for the data area");
+        }
+        if root_cluster < 2 || root_cluster as u64 >= total_clusters + 2 {
+            return reject("root cluster out of range");
+        }
+
+        // Sectors are LBAs of the whole disk, so they stay u64; cluster
+        // numbers were just bounded below the FAT32 markers
+        let fat_start = reserved_sectors as u64;
+        let total_clusters = total_clusters as u32;
 
         Ok(Bpb {
             bytes_per_sector,
@@ -83,17 +134,86 @@ impl Bpb {
             total_sectors,
             fat_size,
             root_cluster,
+            oem_name,
+            bpb_label,
             fat_start,
             data_start,
+            total_clusters,
         })
     }
 
+    /// True if `cluster` names a cluster in the data area.
+    fn is_data_cluster(&self, cluster: u32) -> bool {
+        cluster >= 2 && cluster - 2 < self.total_clusters
+    }
+
+    /// LBA of sector `index` of FAT copy `copy` (0 = primary).
+    fn fat_sector(&self, copy: u32, index: u32) -> u64 {
+        self.fat_start + copy as u64 * self.fat_size as u64 + index as u64
+    }
+
     /// Convert a cluster number to its first sector in the data area.
-    fn cluster_to_sector(&self, cluster: u32) -> u32 {
-        self.data_start + (cluster - 2) * self.sectors_per_cluster as u32
+    /// A cluster number outside the volume (corrupt FAT or directory entry)
+    /// is an I/O error rather than a wild LBA.
+    fn cluster_to_sector(&self, cluster: u32) -> FsResult<u64> {
+        if !self.is_data_cluster(cluster) {
+            crate::log_warn!("FAT32: cluster {:#x} is outside the volume", cluster);
+            return Err(FsError::IoError);
+        }
+        Ok(self.data_start + (cluster as u64 - 2) * self.sectors_per_cluster as u64)
     }
 }
 
+// ══════════════════════════════════════════════════════════════
+//  DOS timestamps
+// ══════════════════════════════════════════════════════════════
+
+/// Year 0 of DOS dates. The 7-bit year field reaches 2107.
+const DOS_EPOCH_YEAR: u16 = 1980;
+const DOS_LAST_YEAR: u16 = DOS_EPOCH_YEAR + 127;
+
+/// Encode `dt` as a DOS (date, time, tenth) triple. The date packs
+/// year-1980, month and day as 7:4:5 bits, the time hours, minutes and
+/// seconds/2 as 5:6:5 bits; the odd second goes in the creation-only tenth
+/// field (units of 10 ms). Dates outside 1980-2107 are clamped to the range.
+pub fn dos_timestamp(dt: &DateTime) -> (u16, u16, u8) {
+    if dt.year < DOS_EPOCH_YEAR {
+        return ((1 << 5) | 1, 0, 0);
+    }
+    if dt.year > DOS_LAST_YEAR {
+        return ((127 << 9) | (12 << 5) | 31, (23 << 11) | (59 << 5) | 29, 100);
+    }
+    let date = ((dt.year - DOS_EPOCH_YEAR) << 9) | ((dt.month as u16) << 5) | dt.day as u16;
+    let time = ((dt.hour as u16) << 11) | ((dt.minute as u16) << 5) | (dt.second as u16 / 2);
+    (date, time, (dt.second % 2) * 100)
+}
+
+/// Decode a DOS date, time and tenth field (pass 0 for fields without one).
+pub fn dos_datetime(date: u16, time: u16, tenth: u8) -> DateTime {
+    DateTime {
+        year: DOS_EPOCH_YEAR + (date >> 9),
+        month: ((date >> 5) & 0x0F) as u8,
+        day: (date & 0x1F) as u8,
+        hour: (time >> 11) as u8,
+        minute: ((time >> 5) & 0x3F) as u8,
+        second: ((time & 0x1F) * 2) as u8 + tenth / 100,
+    }
+}
+
+/// Timestamps of a directory entry, as `Fat32Fs::times` reports them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileTimes {
+    pub created: DateTime,
+    pub modified: DateTime,
+    /// Date only; the time of day is always midnight.
+    pub accessed: DateTime,
+}
+
+/// Validate a boot sector's BPB exactly as mounting would, without mounting.
+pub fn check_boot_sector(sector: &[u8; 512]) -> FsResult<()> {
+    Bpb::parse(sector).map(|_| ())
+}
+
 // ══════════════════════════════════════════════════════════════
 //  Raw FAT32 directory entry (32 bytes)
 // ══════════════════════════════════════════════════════════════
@@ -102,13 +222,68 @@ impl Bpb {
 struct RawDirEntry {
     name: [u8; 11],    // 8.3 name
     attr: u8,
+    /// Byte 12, reserved for Windows NT (name case flags); kept as found.
+    nt_reserved: u8,
+    /// Creation time in 10 ms units (0-199) on top of `create_time`'s 2 seconds.
+    create_time_tenth: u8,
+    create_time: u16,
+    create_date: u16,
+    /// Last access: date only.
+    access_date: u16,
     cluster_hi: u16,
+    write_time: u16,
+    write_date: u16,
     cluster_lo: u16,
     file_size: u32,
+    /// VFAT long name from the LFN entries in front of this one. Not part
+    /// of the 32 on-disk bytes; only `read_dir_entries` fills it in.
+    long_name: Option<String>,
 }
 
 impl RawDirEntry {
+    /// An entry with all timestamps zero ("no time recorded").
+    fn blank(name: [u8; 11], attr: u8, cluster: u32) -> Self {
+        RawDirEntry {
+            name,
+            attr,
+            nt_reserved: 0,
+            create_time_tenth: 0,
+            create_time: 0,
+            create_date: 0,
+            access_date: 0,
+            cluster_hi: (cluster >> 16) as u16,
+            write_time: 0,
+            write_date: 0,
+            cluster_lo: cluster as u16,
+            file_size: 0,
+            long_name: None,
+        }
+    }
+
+    /// A new, empty entry created, written and accessed now (RTC time).
+    fn new(name: [u8; 11], attr: u8, cluster: u32) -> Self {
+        let (date, time, tenth) = dos_timestamp(&crate::drivers::rtc::read_datetime());
+        RawDirEntry {
+            create_time_tenth: tenth,
+            create_time: time,
+            create_date: date,
+            access_date: date,
+            write_time: time,
+            write_date: date,
+            ..RawDirEntry::blank(name, attr, cluster)
+        }
+    }
+
+    /// Record a change to the file's data now: write time and access date.
+    fn touch_write(&mut self) {
+        let (date, time, _) = dos_timestamp(&crate::drivers::rtc::read_datetime());
+        self.write_date = date;
+        self.write_time = time;
+        self.access_date = date;
+    }
+
     fn from_bytes(data: &[u8]) -> Self {
+        let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
         RawDirEntry {
             name: {
                 let mut n = [0u8; 11];
@@ -116,9 +291,17 @@ impl RawDirEntry {
                 n
             },
             attr: data[11],
-            cluster_hi: u16::from_le_bytes([data[20], data[21]]),
-            cluster_lo: u16::from_le_bytes([data[26], data[27]]),
+            nt_reserved: data[12],
+            create_time_tenth: data[13],
+            create_time: u16_at(14),
+            create_date: u16_at(16),
+            access_date: u16_at(18),
+            cluster_hi: u16_at(20),
+            write_time: u16_at(22),
+            write_date: u16_at(24),
+            cluster_lo: u16_at(26),
             file_size: u32::from_le_bytes([data[28], data[29], data[30], data[31]]),
+            long_name: None,
         }
     }
 
@@ -126,10 +309,15 @@ impl RawDirEntry {
         let mut buf = [0u8; 32];
         buf[0..11].copy_from_slice(&self.name);
         buf[11] = self.attr;
-        buf[20] = self.cluster_hi as u8;
-        buf[21] = (self.cluster_hi >> 8) as u8;
-        buf[26] = self.cluster_lo as u8;
-        buf[27] = (self.cluster_lo >> 8) as u8;
+        buf[12] = self.nt_reserved;
+        buf[13] = self.create_time_tenth;
+        buf[14..16].copy_from_slice(&self.create_time.to_le_bytes());
+        buf[16..18].copy_from_slice(&self.create_date.to_le_bytes());
+        buf[18..20].copy_from_slice(&self.access_date.to_le_bytes());
+        buf[20..22].copy_from_slice(&self.cluster_hi.to_le_bytes());
+        buf[22..24].copy_from_slice(&self.write_time.to_le_bytes());
+        buf[24..26].copy_from_slice(&self.write_date.to_le_bytes());
+        buf[26..28].copy_from_slice(&self.cluster_lo.to_le_bytes());
         buf[28..32].copy_from_slice(&self.file_size.to_le_bytes());
         buf
     }
@@ -158,8 +346,18 @@ impl RawDirEntry {
         self.attr & ATTR_VOLUME_ID != 0
     }
 
-    /// Convert the 8.3 name to a human-readable string.
+    /// Permission bits. FAT only records read-only (plus hidden and system,
+    /// which don't map to any), so that bit alone clears the write bits.
+    fn mode(&self) -> u16 {
+        let mode = if self.is_dir() { DEFAULT_DIR_MODE } else { DEFAULT_FILE_MODE };
+        if self.attr & ATTR_READ_ONLY != 0 { mode & !0o222 } else { mode }
+    }
+
+    /// The long name if the entry has one, else the 8.3 name as `BASE.EXT`.
     fn display_name(&self) -> String {
+        if let Some(long) = &self.long_name {
+            return long.clone();
+        }
         let base = core::str::from_utf8(&self.name[0..8]).unwrap_or("").trim();
         let ext = core::str::from_utf8(&self.name[8..11]).unwrap_or("").trim();
         if ext.is_empty() {
@@ -168,16 +366,196 @@ impl RawDirEntry {
             alloc::format!("{}.{}", base, ext)
         }
     }
+
+    /// True if path component `segment` names this entry: its long name
+    /// (case-insensitively) or its 8.3 name.
+    fn matches(&self, segment: &str) -> bool {
+        if self.long_name.as_deref().is_some_and(|long| long.eq_ignore_ascii_case(segment)) {
+            return true;
+        }
+        encode_83_name(segment) == Some(self.name)
+    }
+}
+
+// ══════════════════════════════════════════════════════════════
+//  VFAT long file names
+// ══════════════════════════════════════════════════════════════
+
+/// Set in the sequence byte of the LFN entry holding the end of the name
+/// (stored first on disk).
+const LFN_LAST: u8 = 0x40;
+/// VFAT names are at most 255 characters: 20 entries of 13.
+const LFN_MAX_CHARS: usize = 255;
+const LFN_MAX_ENTRIES: usize = 20;
+const LFN_CHARS_PER_ENTRY: usize = 13;
+/// Byte offsets of the 13 UTF-16LE code units inside an LFN entry.
+const LFN_CHAR_OFFSETS: [usize; LFN_CHARS_PER_ENTRY] = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+
+/// Checksum of an 8.3 name, stored in each of its LFN entries so a stale
+/// long name (left by a driver that doesn't know about them) is detected.
+pub fn lfn_checksum(name: &[u8; 11]) -> u8 {
+    name.iter().fold(0u8, |sum, &c| ((sum & 1) << 7).wrapping_add(sum >> 1).wrapping_add(c))
+}
+
+/// The LFN entries giving the short entry `alias` the long name `name`, in
+/// on-disk order (the `LFN_LAST` one first). The name is NUL-terminated and
+/// padded with 0xFFFF, unless it fills the last entry exactly.
+pub fn lfn_entries(name: &str, alias: &[u8; 11]) -> FsResult<Vec<[u8; DIR_ENTRY_SIZE]>> {
+    let mut units: Vec<u16> = name.encode_utf16().collect();
+    let count = units.len().div_ceil(LFN_CHARS_PER_ENTRY);
+    if count == 0 || count > LFN_MAX_ENTRIES {
+        return Err(FsError::InvalidPath);
+    }
+    if units.len() % LFN_CHARS_PER_ENTRY != 0 {
+        units.push(0);
+    }
+    units.resize(count * LFN_CHARS_PER_ENTRY, 0xFFFF);
+
+    let checksum = lfn_checksum(alias);
+    Ok((1..=count).rev().map(|seq| {
+        let mut raw = [0u8; DIR_ENTRY_SIZE];
+        raw[0] = seq as u8 | if seq == count { LFN_LAST } else { 0 };
+        raw[11] = ATTR_LFN;
+        raw[13] = checksum;
+        let base = (seq - 1) * LFN_CHARS_PER_ENTRY;
+        for (i, &off) in LFN_CHAR_OFFSETS.iter().enumerate() {
+            raw[off..off + 2].copy_from_slice(&units[base + i].to_le_bytes());
+        }
+        raw
+    }).collect())
+}
+
+/// The on-disk entries for a new entry named `name`: its LFN entries, when
+/// `name` isn't a valid 8.3 name, followed by `entry` itself.
+fn entries_for(name: &str, entry: &RawDirEntry) -> FsResult<Vec<[u8; DIR_ENTRY_SIZE]>> {
+    let mut raw = if encode_83_name(name).is_some() {
+        Vec::new()
+    } else {
+        lfn_entries(name.trim(), &entry.name)?
+    };
+    raw.push(entry.to_bytes());
+    Ok(raw)
+}
+
+/// Collects the LFN entries in front of a short entry. They come in reverse
+/// order: the entry flagged `LFN_LAST` with sequence number N first, down to
+/// sequence 1 right before the short entry. Any break in that sequence drops
+/// the partial name.
+pub struct LongNameBuilder {
+    units: [u16; LFN_MAX_ENTRIES * LFN_CHARS_PER_ENTRY],
+    /// Sequence number of the `LFN_LAST` entry; 0 when no name is in progress.
+    total: u8,
+    /// Sequence number the next entry must carry.
+    expected: u8,
+    checksum: u8,
+}
+
+impl LongNameBuilder {
+    pub const fn new() -> Self {
+        LongNameBuilder {
+            units: [0; LFN_MAX_ENTRIES * LFN_CHARS_PER_ENTRY],
+            total: 0,
+            expected: 0,
+            checksum: 0,
+        }
+    }
+
+    /// Forget any partial name.
+    pub fn reset(&mut self) {
+        self.total = 0;
+        self.expected = 0;
+    }
+
+    /// Add one 32-byte LFN entry.
+    pub fn push(&mut self, raw: &[u8]) {
+        let seq = raw[0] & 0x1F;
+        let checksum = raw[13];
+        if raw[0] & LFN_LAST != 0 {
+            if seq == 0 || seq as usize > LFN_MAX_ENTRIES {
+                self.reset();
+                return;
+            }
+            self.total = seq;
+            self.checksum = checksum;
+        } else if self.total == 0 || seq == 0 || seq != self.expected || checksum != self.checksum {
+            self.reset();
+            return;
+        }
+
+        let base = (seq as usize - 1) * LFN_CHARS_PER_ENTRY;
+        for (i, &off) in LFN_CHAR_OFFSETS.iter().enumerate() {
+            self.units[base + i] = u16::from_le_bytes([raw[off], raw[off + 1]]);
+        }
+        self.expected = seq - 1;
+    }
+
+    /// The long name for the short entry `name`, if a complete sequence with
+    /// a matching checksum precedes it. Starts over for the next entry.
+    pub fn finish(&mut self, name: &[u8; 11]) -> Option<String> {
+        let complete = self.total != 0 && self.expected == 0 && self.checksum == lfn_checksum(name);
+        let len = self.total as usize * LFN_CHARS_PER_ENTRY;
+        self.reset();
+        if !complete {
+            return None;
+        }
+
+        // The name ends at a 0x0000 unit unless it fills the last entry exactly
+        let units = &self.units[..len];
+        let end = units.iter().position(|&u| u == 0).unwrap_or(len);
+        let long: String = char::decode_utf16(units[..end].iter().copied())
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect();
+        if long.is_empty() { None } else { Some(long) }
+    }
 }
 
-/// Encode a filename into 8.3 format. Returns None if invalid.
-fn encode_83_name(name: &str) -> Option<[u8; 11]> {
+/// Decode a space/NUL padded ASCII field, dropping the padding.
+fn trim_padded(bytes: &[u8]) -> String {
+    let text: String = bytes.iter()
+        .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { ' ' })
+        .collect();
+    String::from(text.trim())
+}
+
+/// Bytes that may never appear in an 8.3 short name (besides control characters).
+const ILLEGAL_83_CHARS: &[u8] = b"\"*+,/:;<=>?[\\]|";
+
+/// DOS device names, reserved whatever the extension.
+const RESERVED_83_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// True if `c` may appear in a short name.
+fn is_legal_83_char(c: u8) -> bool {
+    c.is_ascii() && c >= 0x20 && c != 0x7F && !ILLEGAL_83_CHARS.contains(&c)
+}
+
+/// True if `name` (before its first dot) is a reserved device name.
+fn is_reserved_83_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or("");
+    RESERVED_83_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem))
+}
+
+/// Encode a filename into 8.3 format. Returns None if it is not a valid short
+/// name: too long, more than one dot, illegal characters or a reserved name.
+pub fn encode_83_name(name: &str) -> Option<[u8; 11]> {
     let name = name.trim();
+    let mut result = [0x20u8; 11]; // space-padded
+
+    // The directory self/parent links
+    if name == "." || name == ".." {
+        result[..name.len()].copy_from_slice(name.as_bytes());
+        return Some(result);
+    }
+
     if name.is_empty() || name.len() > 12 {
         return None;
     }
-
-    let mut result = [0x20u8; 11]; // space-padded
+    if !name.bytes().all(is_legal_83_char) || is_reserved_83_name(name) {
+        return None;
+    }
 
     let (base, ext) = if let Some(dot_pos) = name.rfind('.') {
         (&name[..dot_pos], &name[dot_pos + 1..])
@@ -185,28 +563,410 @@ fn encode_83_name(name: &str) -> Option<[u8; 11]> {
         (name, "")
     };
 
-    if base.len() > 8 || ext.len() > 3 {
+    if base.is_empty() || base.contains('.') || base.len() > 8 || ext.len() > 3 {
         return None;
     }
 
-    for (i, c) in base.chars().enumerate() {
-        if i >= 8 { break; }
-        result[i] = (c as u8).to_ascii_uppercase();
+    for (i, c) in base.bytes().enumerate() {
+        result[i] = c.to_ascii_uppercase();
     }
-    for (i, c) in ext.chars().enumerate() {
-        if i >= 3 { break; }
-        result[8 + i] = (c as u8).to_ascii_uppercase();
+    for (i, c) in ext.bytes().enumerate() {
+        result[8 + i] = c.to_ascii_uppercase();
     }
 
     Some(result)
 }
 
+/// Choose the short name for a new entry `name` in a directory whose entries
+/// are named `existing`.
+///
+/// Valid 8.3 names are used as-is (`AlreadyExists` if taken). Anything longer
+/// is mangled the DOS way into `BASE~N.EXT` with the lowest free `N`, so two
+/// long names sharing a prefix get distinct aliases; the entry then needs
+/// LFN entries for its real name (see `lfn_entries`). Illegal characters,
+/// reserved device names and names over 255 characters are rejected with
+/// `InvalidPath`.
+pub fn short_name_for(name: &str, existing: &[[u8; 11]]) -> FsResult<[u8; 11]> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(FsError::InvalidPath);
+    }
+    if !name.bytes().all(is_legal_83_char) || is_reserved_83_name(name) {
+        return Err(FsError::InvalidPath);
+    }
+    // Longer names don't fit in LFN entries
+    if name.len() > LFN_MAX_CHARS {
+        return Err(FsError::InvalidPath);
+    }
+
+    if let Some(exact) = encode_83_name(name) {
+        if existing.contains(&exact) {
+            return Err(FsError::AlreadyExists);
+        }
+        return Ok(exact);
+    }
+
+    // Mangle: drop spaces and leading dots, split at the last dot
+    let stripped: String = name.trim_start_matches('.').chars().filter(|&c| c != ' ').collect();
+    let (base, ext) = match stripped.rfind('.') {
+        Some(dot_pos) => (&stripped[..dot_pos], &stripped[dot_pos + 1..]),
+        None => (stripped.as_str(), ""),
+    };
+    let base: Vec<u8> = base.bytes().filter(|&c| c != b'.').map(|c| c.to_ascii_uppercase()).collect();
+    if base.is_empty() {
+        return Err(FsError::InvalidPath);
+    }
+
+    let mut alias = [0x20u8; 11];
+    for (i, c) in ext.bytes().take(3).enumerate() {
+        alias[8 + i] = c.to_ascii_uppercase();
+    }
+
+    for n in 1..=999_999u32 {
+        let tail = alloc::format!("~{}", n);
+        let keep = base.len().min(8 - tail.len());
+        let mut candidate = alias;
+        candidate[..8].fill(0x20);
+        candidate[..keep].copy_from_slice(&base[..keep]);
+        candidate[keep..keep + tail.len()].copy_from_slice(tail.as_bytes());
+        if !existing.contains(&candidate) {
+            return Ok(candidate);
+        }
+    }
+    Err(FsError::NoSpace)
+}
+
+// ══════════════════════════════════════════════════════════════
+//  ChainWalker — cluster chain validation
+// ══════════════════════════════════════════════════════════════
+
+/// Follows one cluster chain link by link, refusing what a sound FAT cannot
+/// contain: the bad-cluster marker, a link outside the data area, or a
+/// cluster already visited (a cycle). Each is an I/O error, so a corrupt
+/// volume fails the operation instead of hanging it.
+struct ChainWalker {
+    total_clusters: u32,
+    /// One bit per data cluster visited, grown up to the highest one seen.
+    visited: Vec<u64>,
+}
+
+impl ChainWalker {
+    /// Start a walk at `first`, which must be a data cluster.
+    fn new(total_clusters: u32, first: u32) -> FsResult<Self> {
+        let mut walker = ChainWalker { total_clusters, visited: Vec::new() };
+        walker.visit(first)?;
+        Ok(walker)
+    }
+
+    /// Take the link `value` read from the FAT for the current cluster:
+    /// `None` at the end of the chain, otherwise the next cluster.
+    fn follow(&mut self, value: u32) -> FsResult<Option<u32>> {
+        if value >= FAT_EOC {
+            return Ok(None);
+        }
+        if value == FAT_BAD {
+            crate::log_warn!("FAT32: chain runs into a bad cluster");
+            return Err(FsError::IoError);
+        }
+        self.visit(value)?;
+        Ok(Some(value))
+    }
+
+    fn visit(&mut self, cluster: u32) -> FsResult<()> {
+        if cluster < 2 || cluster - 2 >= self.total_clusters {
+            crate::log_warn!("FAT32: chain links to cluster {:#x}, outside the data area", cluster);
+            return Err(FsError::IoError);
+        }
+        let bit = (cluster - 2) as usize;
+        let (word, mask) = (bit / 64, 1u64 << (bit % 64));
+        if word >= self.visited.len() {
+            self.visited.resize(word + 1, 0);
+        }
+        if self.visited[word] & mask != 0 {
+            crate::log_warn!("FAT32: chain loops back to cluster {}", cluster);
+            return Err(FsError::IoError);
+        }
+        self.visited[word] |= mask;
+        Ok(())
+    }
+}
+
+/// Follow the chain from `first` through an in-memory FAT (entries
+/// 0..total_clusters+2) exactly as the driver follows chains on disk.
+/// Returns the clusters in order, or `IoError` for a cycle, a bad-cluster
+/// marker or a link outside the data area.
+pub fn fat_chain(fat: &[u32], first: u32) -> FsResult<Vec<u32>> {
+    let mut walker = ChainWalker::new(fat.len().saturating_sub(2) as u32, first)?;
+    let mut chain = vec![first];
+    let mut cluster = first;
+    while let Some(next) = walker.follow(fat[cluster as usize])? {
+        chain.push(next);
+        cluster = next;
+    }
+    Ok(chain)
+}
+
+// ══════════════════════════════════════════════════════════════
+//  FatBatch — coalesced FAT updates
+// ══════════════════════════════════════════════════════════════
+
+/// FAT sectors touched by one chain operation. Updates are applied in
+/// memory and each dirty sector is written once, to every FAT copy, by
+/// `flush`; reads go through the batch so the operation sees its own
+/// pending links. Dropping a batch without flushing discards its updates.
+/// Only dirty sectors are kept, plus the last clean one read, so a long
+/// chain walk or free-cluster scan doesn't pile up FAT sectors in memory.
+struct FatBatch<'a> {
+    vol: &'a Volume,
+    /// Modified sectors: (sector index within one FAT, contents).
+    dirty: Vec<(u32, [u8; 512])>,
+    /// The last unmodified sector read (from the primary copy).
+    clean: Option<(u32, [u8; 512])>,
+    /// Where the next free-cluster search starts.
+    next_free: u32,
+}
+
+impl<'a> FatBatch<'a> {
+    fn new(vol: &'a Volume) -> Self {
+        FatBatch { vol, dirty: Vec::new(), clean: None, next_free: 2 }
+    }
+
+    /// (sector index within the FAT, byte offset) of `cluster`'s entry.
+    fn locate(&self, cluster: u32) -> FsResult<(u32, usize)> {
+        if !self.vol.is_data_cluster(cluster) {
+            return Err(FsError::IoError);
+        }
+        let fat_offset = cluster * 4;
+        Ok((fat_offset / SECTOR_SIZE as u32, (fat_offset % SECTOR_SIZE as u32) as usize))
+    }
+
+    fn sector(&mut self, index: u32) -> FsResult<&[u8; 512]> {
+        if let Some(i) = self.dirty.iter().position(|(idx, _)| *idx == index) {
+            return Ok(&self.dirty[i].1);
+        }
+        if self.clean.as_ref().map_or(true, |(idx, _)| *idx != index) {
+            self.clean = Some((index, Fat32Fs::read_sector_raw(self.vol, self.vol.fat_sector(0, index))?));
+        }
+        Ok(&self.clean.as_ref().unwrap().1)
+    }
+
+    fn sector_mut(&mut self, index: u32) -> FsResult<&mut [u8; 512]> {
+        let i = match self.dirty.iter().position(|(idx, _)| *idx == index) {
+            Some(i) => i,
+            None => {
+                let data = *self.sector(index)?;
+                self.dirty.push((index, data));
+                self.dirty.len() - 1
+            }
+        };
+        Ok(&mut self.dirty[i].1)
+    }
+
+    fn read(&mut self, cluster: u32) -> FsResult<u32> {
+        let (index, off) = self.locate(cluster)?;
+        let sector = self.sector(index)?;
+        let raw = u32::from_le_bytes([sector[off], sector[off + 1], sector[off + 2], sector[off + 3]]);
+        Ok(raw & 0x0FFF_FFFF)
+    }
+
+    fn write(&mut self, cluster: u32, value: u32) -> FsResult<()> {
+        let (index, off) = self.locate(cluster)?;
+        let sector = self.sector_mut(index)?;
+        // Preserve top 4 bits
+        let existing = u32::from_le_bytes([sector[off], sector[off + 1], sector[off + 2], sector[off + 3]]);
+        let new_val = (existing & 0xF000_0000) | (value & 0x0FFF_FFFF);
+        sector[off..off + 4].copy_from_slice(&new_val.to_le_bytes());
+        Ok(())
+    }
+
+    /// Claim a free cluster, mark it end-of-chain and link `prev` to it.
+    /// The cluster's contents are left as they are on disk.
+    fn alloc(&mut self, prev: Option<u32>) -> FsResult<u32> {
+        let end = self.vol.total_clusters + 2;
+        let new = (self.next_free..end)
+            .chain(2..self.next_free)
+            .find_map(|c| match self.read(c) {
+                Ok(FAT_FREE) => Some(Ok(c)),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            })
+            .ok_or(FsError::NoSpace)??;
+        self.write(new, 0x0FFF_FFFF)?; // mark as end-of-chain
+        if let Some(p) = prev {
+            self.write(p, new)?; // link previous to new
+        }
+        self.next_free = new + 1;
+        Ok(new)
+    }
+
+    /// Write every dirty sector to each FAT copy.
+    fn flush(mut self) -> FsResult<()> {
+        self.dirty.sort_unstable_by_key(|(index, _)| *index);
+        for (index, sector) in &self.dirty {
+            for copy in 0..self.vol.num_fats as u32 {
+                Fat32Fs::write_sector_raw(self.vol, self.vol.fat_sector(copy, *index), sector)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 // ══════════════════════════════════════════════════════════════
 //  Fat32Fs — main filesystem struct
 // ══════════════════════════════════════════════════════════════
 
-struct Fat32Inner {
+/// Sectors the FAT32 driver read from or wrote to the disk since boot (for
+/// measuring I/O cost); sectors served or absorbed by the cache don't count.
+static SECTOR_READS: AtomicU64 = AtomicU64::new(0);
+static SECTOR_WRITES: AtomicU64 = AtomicU64::new(0);
+
+pub fn sector_reads() -> u64 {
+    SECTOR_READS.load(Ordering::Relaxed)
+}
+
+pub fn sector_writes() -> u64 {
+    SECTOR_WRITES.load(Ordering::Relaxed)
+}
+
+// ── Sector cache ────────────────────────────────────────
+
+/// Sectors held by a volume's `SectorCache`.
+const CACHE_SECTORS: usize = 16;
+
+#[derive(Clone, Copy)]
+struct CachedSector {
+    lba: u64,
+    data: [u8; 512],
+    /// Written since it was read or last flushed.
+    dirty: bool,
+    /// `SectorCache::clock` at the last access, for LRU eviction.
+    last_used: u64,
+}
+
+/// Write-back LRU cache of a volume's sectors. Every single-sector read
+/// and write of the driver goes through it, so FAT and directory sectors,
+/// read over and over by chain walks and path lookups, are mostly served
+/// from memory, and repeated updates to one sector reach the disk once.
+/// Each FAT copy's sectors are entries of their own: `FatBatch::flush`
+/// writes every copy, so a flush brings all mirrors up to date.
+struct SectorCache {
+    entries: [Option<CachedSector>; CACHE_SECTORS],
+    clock: u64,
+}
+
+impl SectorCache {
+    const fn new() -> Self {
+        SectorCache { entries: [None; CACHE_SECTORS], clock: 0 }
+    }
+
+    fn lookup(&mut self, lba: u64) -> Option<&mut CachedSector> {
+        self.clock += 1;
+        let clock = self.clock;
+        let entry = self.entries.iter_mut().flatten().find(|e| e.lba == lba)?;
+        entry.last_used = clock;
+        Some(entry)
+    }
+
+    fn read(&mut self, dev: &dyn BlockDevice, lba: u64) -> FsResult<[u8; 512]> {
+        if let Some(entry) = self.lookup(lba) {
+            return Ok(entry.data);
+        }
+        let mut data = [0u8; 512];
+        disk_read_sectors(dev, lba, &mut data)?;
+        self.insert(dev, lba, data, false)?;
+        Ok(data)
+    }
+
+    fn write(&mut self, dev: &dyn BlockDevice, lba: u64, buf: &[u8; 512]) -> FsResult<()> {
+        match self.lookup(lba) {
+            Some(entry) => {
+                entry.data = *buf;
+                entry.dirty = true;
+                Ok(())
+            }
+            None => self.insert(dev, lba, *buf, true),
+        }
+    }
+
+    /// Add a sector not yet cached, evicting the least recently used one
+    /// (written back first if dirty) when full.
+    fn insert(&mut self, dev: &dyn BlockDevice, lba: u64, data: [u8; 512], dirty: bool) -> FsResult<()> {
+        let slot = match self.entries.iter().position(Option::is_none) {
+            Some(free) => free,
+            None => {
+                let (lru, victim) = self.entries.iter().enumerate()
+                    .filter_map(|(i, e)| e.as_ref().map(|e| (i, e)))
+                    .min_by_key(|(_, e)| e.last_used)
+                    .expect("cache is full");
+                if victim.dirty {
+                    disk_write_sector(dev, victim.lba, &victim.data)?;
+                }
+                lru
+            }
+        };
+        self.entries[slot] = Some(CachedSector { lba, data, dirty, last_used: self.clock });
+        Ok(())
+    }
+
+    /// Copy the cached sectors within `buf`, which holds the disk contents
+    /// of consecutive sectors from `lba`.
+    fn overlay(&self, lba: u64, buf: &mut [u8]) {
+        let count = (buf.len() / SECTOR_SIZE) as u64;
+        for entry in self.entries.iter().flatten() {
+            if entry.lba >= lba && entry.lba - lba < count {
+                let off = (entry.lba - lba) as usize * SECTOR_SIZE;
+                buf[off..off + SECTOR_SIZE].copy_from_slice(&entry.data);
+            }
+        }
+    }
+
+    /// Write every dirty sector to disk, in LBA order.
+    fn flush(&mut self, dev: &dyn BlockDevice) -> FsResult<()> {
+        let mut dirty: Vec<&mut CachedSector> = self.entries.iter_mut().flatten().filter(|e| e.dirty).collect();
+        dirty.sort_unstable_by_key(|e| e.lba);
+        for entry in dirty {
+            disk_write_sector(dev, entry.lba, &entry.data)?;
+            entry.dirty = false;
+        }
+        Ok(())
+    }
+
+    /// Drop every entry. Only safe once flushed.
+    fn clear(&mut self) {
+        self.entries = [None; CACHE_SECTORS];
+    }
+}
+
+fn disk_read_sectors(dev: &dyn BlockDevice, lba: u64, buf: &mut [u8]) -> FsResult<()> {
+    SECTOR_READS.fetch_add((buf.len() / SECTOR_SIZE) as u64, Ordering::Relaxed);
+    dev.read_blocks(lba as u64, buf).map_err(|_| FsError::IoError)
+}
+
+fn disk_write_sector(dev: &dyn BlockDevice, lba: u64, buf: &[u8; 512]) -> FsResult<()> {
+    SECTOR_WRITES.fetch_add(1, Ordering::Relaxed);
+    dev.write_block(lba as u64, buf).map_err(|_| FsError::IoError)
+}
+
+// ── Volume ──────────────────────────────────────────────
+
+/// A mounted volume: its geometry, the device it lives on and the cache in
+/// front of that device. Derefs to the `Bpb` for the geometry.
+struct Volume {
     bpb: Bpb,
+    dev: &'static dyn BlockDevice,
+    cache: Mutex<SectorCache>,
+}
+
+impl core::ops::Deref for Volume {
+    type Target = Bpb;
+
+    fn deref(&self) -> &Bpb {
+        &self.bpb
+    }
+}
+
+struct Fat32Inner {
+    vol: Volume,
 }
 
 pub struct Fat32Fs {
@@ -214,49 +974,77 @@ pub struct Fat32Fs {
 }
 
 impl Fat32Fs {
-    /// Create and initialize a Fat32Fs by reading the BPB from disk.
-    pub fn init() -> FsResult<Self> {
-        let mut sector = [0u8; 512];
-        {
-            let ata = PRIMARY_ATA.lock();
-            ata.read_sector(0, &mut sector).map_err(|_| FsError::IoError)?;
+    /// Create and initialize a Fat32Fs on `dev` by reading the BPB from its
+    /// first sector.
+    pub fn init(dev: &'static dyn BlockDevice) -> FsResult<Self> {
+        if dev.block_size() != SECTOR_SIZE {
+            crate::log_warn!("FAT32: {}-byte blocks are not supported", dev.block_size());
+            return Err(FsError::NotSupported);
         }
+        let mut sector = [0u8; 512];
+        dev.read_block(0, &mut sector).map_err(|_| FsError::IoError)?;
 
         let bpb = Bpb::parse(&sector)?;
+        // A volume larger than the disk would send reads past its end
+        if bpb.total_sectors as u64 > dev.block_count() {
+            crate::log_warn!("FAT32: volume has {} sectors but the disk only {}", bpb.total_sectors, dev.block_count());
+            return Err(FsError::InvalidPath);
+        }
 
-        crate::log_info!("FAT32: BPS={} SPC={} FATs={} FATsz={} root_clus={} data_start={}",
-            bpb.bytes_per_sector, bpb.sectors_per_cluster,
+        crate::log_info!("FAT32: OEM='{}' BPS={} SPC={} FATs={} FATsz={} root_clus={} data_start={}",
+            bpb.oem_name, bpb.bytes_per_sector, bpb.sectors_per_cluster,
             bpb.num_fats, bpb.fat_size, bpb.root_cluster, bpb.data_start);
 
+        let vol = Volume { bpb, dev, cache: Mutex::new(SectorCache::new()) };
         Ok(Fat32Fs {
-            inner: Mutex::new(Fat32Inner { bpb }),
+            inner: Mutex::new(Fat32Inner { vol }),
         })
     }
 
     // ── Low-level disk I/O helpers ──────────────────────────
 
-    fn read_sector_raw(lba: u32) -> FsResult<[u8; 512]> {
-        let mut buf = [0u8; 512];
-        let ata = PRIMARY_ATA.lock();
-        ata.read_sector(lba, &mut buf).map_err(|_| FsError::IoError)?;
-        Ok(buf)
+    /// Read a sector through the volume's sector cache.
+    fn read_sector_raw(vol: &Volume, lba: u64) -> FsResult<[u8; 512]> {
+        vol.cache.lock().read(vol.dev, lba)
     }
 
-    fn write_sector_raw(lba: u32, buf: &[u8; 512]) -> FsResult<()> {
-        let ata = PRIMARY_ATA.lock();
-        ata.write_sector(lba, buf).map_err(|_| FsError::IoError)?;
+    /// Read `buf.len() / 512` consecutive sectors in one device request.
+    /// Cached sectors in the range replace what the disk returned, since
+    /// they may hold writes not yet flushed.
+    fn read_sectors_raw(vol: &Volume, lba: u64, buf: &mut [u8]) -> FsResult<()> {
+        // Held across the read, so nothing is evicted to disk in between
+        let cache = vol.cache.lock();
+        disk_read_sectors(vol.dev, lba, buf)?;
+        cache.overlay(lba, buf);
         Ok(())
     }
 
+    /// Write a sector into the volume's sector cache; it reaches the disk
+    /// when evicted or on `sync`.
+    fn write_sector_raw(vol: &Volume, lba: u64, buf: &[u8; 512]) -> FsResult<()> {
+        vol.cache.lock().write(vol.dev, lba, buf)
+    }
+
     // ── FAT operations ──────────────────────────────────────
 
-    /// Read the next cluster from the FAT.
-    fn fat_read(bpb: &Bpb, cluster: u32) -> FsResult<u32> {
+    /// Read the next cluster from the FAT (primary copy).
+    fn fat_read(vol: &Volume, cluster: u32) -> FsResult<u32> {
+        Self::fat_read_copy(vol, 0, cluster)
+    }
+
+    /// Read the next cluster from a specific FAT copy (0 = primary).
+    fn fat_read_copy(vol: &Volume, copy: u32, cluster: u32) -> FsResult<u32> {
+        if copy >= vol.num_fats as u32 {
+            return Err(FsError::InvalidPath);
+        }
+        if !vol.is_data_cluster(cluster) {
+            return Err(FsError::IoError);
+        }
         let fat_offset = cluster * 4;
-        let fat_sector = bpb.fat_start + (fat_offset / SECTOR_SIZE as u32);
+        let fat_sector = vol.fat_sector(copy, fat_offset / SECTOR_SIZE as u32);
         let offset_in_sector = (fat_offset % SECTOR_SIZE as u32) as usize;
 
-        let sector = Self::read_sector_raw(fat_sector)?;
+        let sector = Self::read_sector_raw(vol, fat_sector)?;
         let val = u32::from_le_bytes([
             sector[offset_in_sector],
             sector[offset_in_sector + 1],
@@ -267,97 +1055,103 @@ impl Fat32Fs {
         Ok(val)
     }
 
-    /// Write a value to the FAT (both copies).
-    fn fat_write(bpb: &Bpb, cluster: u32, value: u32) -> FsResult<()> {
-        let fat_offset = cluster * 4;
-        let fat_sector_offset = fat_offset / SECTOR_SIZE as u32;
-        let offset_in_sector = (fat_offset % SECTOR_SIZE as u32) as usize;
-
-        // Update each FAT copy
-        for fat_idx in 0..bpb.num_fats as u32 {
-            let sector_lba = bpb.fat_start + fat_idx * bpb.fat_size + fat_sector_offset;
-            let mut sector = Self::read_sector_raw(sector_lba)?;
-
-            // Preserve top 4 bits
-            let existing = u32::from_le_bytes([
-                sector[offset_in_sector],
-                sector[offset_in_sector + 1],
-                sector[offset_in_sector + 2],
-                sector[offset_in_sector + 3],
-            ]);
-            let new_val = (existing & 0xF000_0000) | (value & 0x0FFF_FFFF);
-            let bytes = new_val.to_le_bytes();
-            sector[offset_in_sector..offset_in_sector + 4].copy_from_slice(&bytes);
-
-            Self::write_sector_raw(sector_lba, &sector)?;
-        }
-        Ok(())
-    }
-
-    /// Find a free cluster in the FAT.
-    fn fat_alloc(bpb: &Bpb) -> FsResult<u32> {
-        // Total data clusters
-        let total_clusters = (bpb.total_sectors - bpb.data_start) / bpb.sectors_per_cluster as u32;
-        for cluster in 2..total_clusters + 2 {
-            let val = Self::fat_read(bpb, cluster)?;
-            if val == FAT_FREE {
-                return Ok(cluster);
-            }
-        }
-        Err(FsError::NoSpace)
-    }
-
-    /// Allocate a new cluster, mark as EOC, optionally chain from `prev`.
-    fn alloc_cluster(bpb: &Bpb, prev: Option<u32>) -> FsResult<u32> {
-        let new = Self::fat_alloc(bpb)?;
-        Self::fat_write(bpb, new, 0x0FFF_FFFF)?; // mark as end-of-chain
-        if let Some(p) = prev {
-            Self::fat_write(bpb, p, new)?; // link previous to new
-        }
+    /// Allocate a new zeroed cluster, mark as EOC, optionally chain from `prev`.
+    fn alloc_cluster(vol: &Volume, prev: Option<u32>) -> FsResult<u32> {
+        let mut batch = FatBatch::new(vol);
+        let new = batch.alloc(prev)?;
+        batch.flush()?;
         // Zero the cluster
-        let start_sector = bpb.cluster_to_sector(new);
+        let start_sector = vol.cluster_to_sector(new)?;
         let zero = [0u8; 512];
-        for s in 0..bpb.sectors_per_cluster as u32 {
-            Self::write_sector_raw(start_sector + s, &zero)?;
+        for s in 0..vol.sectors_per_cluster as u64 {
+            Self::write_sector_raw(vol, start_sector + s, &zero)?;
         }
         Ok(new)
     }
 
     // ── Cluster chain reading ───────────────────────────────
 
-    /// Read all data from a cluster chain into a Vec.
-    fn read_chain(bpb: &Bpb, start_cluster: u32) -> FsResult<Vec<u8>> {
+    /// Read all data from a cluster chain into a Vec, one command per cluster.
+    fn read_chain(vol: &Volume, start_cluster: u32) -> FsResult<Vec<u8>> {
         let mut data = Vec::new();
-        let cluster_bytes = bpb.sectors_per_cluster as usize * SECTOR_SIZE;
+        if start_cluster < 2 {
+            return Ok(data);
+        }
+        let cluster_bytes = vol.sectors_per_cluster as usize * SECTOR_SIZE;
+        let mut walker = ChainWalker::new(vol.total_clusters, start_cluster)?;
         let mut cluster = start_cluster;
 
         loop {
-            if cluster < 2 { break; }
-            let sector = bpb.cluster_to_sector(cluster);
-            for s in 0..bpb.sectors_per_cluster as u32 {
-                let buf = Self::read_sector_raw(sector + s)?;
-                data.extend_from_slice(&buf);
-            }
-            let next = Self::fat_read(bpb, cluster)?;
-            if next >= FAT_EOC { break; }
-            cluster = next;
-            // Safety: prevent infinite loops
-            if data.len() > 16 * 1024 * 1024 { break; }
+            let sector = vol.cluster_to_sector(cluster)?;
+            let start = data.len();
+            data.resize(start + cluster_bytes, 0);
+            Self::read_sectors_raw(vol, sector, &mut data[start..])?;
+            match walker.follow(Self::fat_read(vol, cluster)?)? {
+                Some(next) => cluster = next,
+                None => break,
+            }
         }
 
         Ok(data)
     }
 
+    /// Read up to `buf.len()` bytes of a chain, starting `offset` bytes in.
+    /// Only the FAT links up to `offset` and the sectors copied are read.
+    /// Returns the number of bytes read, short if the chain ends first.
+    fn read_chain_at(vol: &Volume, start_cluster: u32, offset: usize, buf: &mut [u8]) -> FsResult<usize> {
+        if start_cluster < 2 {
+            return Ok(0);
+        }
+        let cluster_bytes = vol.sectors_per_cluster as usize * SECTOR_SIZE;
+        let mut fat = FatBatch::new(vol);
+        let mut walker = ChainWalker::new(vol.total_clusters, start_cluster)?;
+        let mut cluster = start_cluster;
+
+        // Skip whole clusters before `offset`
+        for _ in 0..offset / cluster_bytes {
+            match walker.follow(fat.read(cluster)?)? {
+                Some(next) => cluster = next,
+                None => return Ok(0),
+            }
+        }
+
+        let mut pos = offset % cluster_bytes;
+        let mut done = 0;
+        while done < buf.len() {
+            let first_sector = vol.cluster_to_sector(cluster)?;
+            while pos < cluster_bytes && done < buf.len() {
+                let sector = Self::read_sector_raw(vol, first_sector + (pos / SECTOR_SIZE) as u64)?;
+                let from = pos % SECTOR_SIZE;
+                let n = (SECTOR_SIZE - from).min(buf.len() - done);
+                buf[done..done + n].copy_from_slice(&sector[from..from + n]);
+                done += n;
+                pos += n;
+            }
+            if done < buf.len() {
+                match walker.follow(fat.read(cluster)?)? {
+                    Some(next) => cluster = next,
+                    None => break,
+                }
+                pos = 0;
+            }
+        }
+
+        Ok(done)
+    }
+
     /// Write data to a cluster chain, allocating new clusters as needed.
-    fn write_chain(bpb: &Bpb, start_cluster: u32, data: &[u8]) -> FsResult<u32> {
-        let cluster_bytes = bpb.sectors_per_cluster as usize * SECTOR_SIZE;
+    /// FAT updates are batched and written once per sector at the end; new
+    /// clusters aren't zeroed first since every sector of them is written.
+    fn write_chain(vol: &Volume, start_cluster: u32, data: &[u8]) -> FsResult<u32> {
+        let mut fat = FatBatch::new(vol);
+        let mut walker = ChainWalker::new(vol.total_clusters, start_cluster)?;
         let mut cluster = start_cluster;
         let mut offset = 0usize;
 
         loop {
             // Write data to current cluster
-            let sector = bpb.cluster_to_sector(cluster);
-            for s in 0..bpb.sectors_per_cluster as u32 {
+            let sector = vol.cluster_to_sector(cluster)?;
+            for s in 0..vol.sectors_per_cluster as u64 {
                 let mut buf = [0u8; 512];
                 let start = offset;
                 let end = (offset + SECTOR_SIZE).min(data.len());
@@ -365,219 +1159,680 @@ impl Fat32Fs {
                     let len = end - start;
                     buf[..len].copy_from_slice(&data[start..end]);
                 }
-                Self::write_sector_raw(sector + s, &buf)?;
+                Self::write_sector_raw(vol, sector + s, &buf)?;
                 offset += SECTOR_SIZE;
             }
 
-            if offset >= data.len() {
-                // Mark this as end of chain
-                Self::fat_write(bpb, cluster, 0x0FFF_FFFF)?;
-                break;
+            if offset >= data.len() {
+                // Mark this as end of chain
+                fat.write(cluster, 0x0FFF_FFFF)?;
+                break;
+            }
+
+            // Need more clusters
+            cluster = match walker.follow(fat.read(cluster)?)? {
+                Some(next) => next,
+                None => {
+                    let new = fat.alloc(Some(cluster))?;
+                    walker.visit(new)?;
+                    new
+                }
+            };
+        }
+
+        fat.flush()?;
+        Ok(start_cluster)
+    }
+
+    // ── Directory operations ────────────────────────────────
+
+    /// Read all directory entries from a directory cluster chain, with the
+    /// long name of each short entry attached when its LFN entries are intact.
+    fn read_dir_entries(vol: &Volume, dir_cluster: u32) -> FsResult<Vec<(RawDirEntry, u64, usize)>> {
+        // Returns (entry, sector_lba, offset_in_sector) for each valid entry
+        let mut entries = Vec::new();
+        let mut lfn = LongNameBuilder::new();
+        if dir_cluster < 2 {
+            return Ok(entries);
+        }
+        let mut walker = ChainWalker::new(vol.total_clusters, dir_cluster)?;
+        let mut cluster = dir_cluster;
+
+        loop {
+            let base_sector = vol.cluster_to_sector(cluster)?;
+
+            for s in 0..vol.sectors_per_cluster as u64 {
+                let sector_lba = base_sector + s;
+                let sector = Self::read_sector_raw(vol, sector_lba)?;
+
+                for i in 0..ENTRIES_PER_SECTOR {
+                    let off = i * DIR_ENTRY_SIZE;
+                    let mut entry = RawDirEntry::from_bytes(&sector[off..off + DIR_ENTRY_SIZE]);
+
+                    if entry.is_free() {
+                        return Ok(entries); // no more entries
+                    }
+                    if entry.is_deleted() || (entry.is_volume_id() && !entry.is_lfn()) {
+                        lfn.reset();
+                        continue;
+                    }
+                    if entry.is_lfn() {
+                        lfn.push(&sector[off..off + DIR_ENTRY_SIZE]);
+                        continue;
+                    }
+
+                    entry.long_name = lfn.finish(&entry.name);
+                    entries.push((entry, sector_lba, off));
+                }
+            }
+
+            match walker.follow(Self::fat_read(vol, cluster)?)? {
+                Some(next) => cluster = next,
+                None => break,
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Resolve a path to the target directory entry.
+    /// Returns (entry, parent_cluster).
+    fn resolve_path_entry(vol: &Volume, path: &str) -> FsResult<(RawDirEntry, u32)> {
+        let path = path.trim_start_matches('/');
+        if path.is_empty() {
+            // Root directory — synthesize entry
+            let mut entry = RawDirEntry::blank([0x20; 11], ATTR_DIRECTORY, vol.root_cluster);
+            entry.name[0] = b'/';
+            return Ok((entry, 0));
+        }
+
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let mut current_cluster = vol.root_cluster;
+
+        for (idx, segment) in segments.iter().enumerate() {
+            let entries = Self::read_dir_entries(vol, current_cluster)?;
+
+            let mut found = false;
+            for (entry, _, _) in &entries {
+                if entry.matches(segment) {
+                    if idx == segments.len() - 1 {
+                        // Final segment — return this entry
+                        return Ok((entry.clone(), current_cluster));
+                    }
+                    if !entry.is_dir() {
+                        return Err(FsError::NotADirectory);
+                    }
+                    current_cluster = entry.first_cluster();
+                    found = true;
+                    break;
+                }
+            }
+            if !found {
+                return Err(FsError::NotFound);
+            }
+        }
+
+        Err(FsError::NotFound)
+    }
+
+    /// Resolve a path to the parent directory cluster and child name.
+    fn resolve_parent_and_name(vol: &Volume, path: &str) -> FsResult<(u32, String)> {
+        let path = path.trim_start_matches('/');
+        if path.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        if segments.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+
+        let child_name = String::from(*segments.last().unwrap());
+        let mut parent_cluster = vol.root_cluster;
+
+        // Navigate to parent directory
+        for segment in &segments[..segments.len() - 1] {
+            let entries = Self::read_dir_entries(vol, parent_cluster)?;
+            let mut found = false;
+            for (entry, _, _) in &entries {
+                if entry.matches(segment) && entry.is_dir() {
+                    parent_cluster = entry.first_cluster();
+                    found = true;
+                    break;
+                }
+            }
+            if !found {
+                return Err(FsError::NotFound);
+            }
+        }
+
+        Ok((parent_cluster, child_name))
+    }
+
+    /// Add a new entry to a directory.
+    fn add_dir_entry(vol: &Volume, dir_cluster: u32, entry: &RawDirEntry) -> FsResult<()> {
+        Self::add_dir_entries(vol, dir_cluster, &[entry.to_bytes()])
+    }
+
+    /// Add `entries` to a directory in consecutive slots, as a short entry's
+    /// LFN entries must be. The run may cross sectors and clusters; the
+    /// directory grows if it has no room.
+    fn add_dir_entries(vol: &Volume, dir_cluster: u32, entries: &[[u8; DIR_ENTRY_SIZE]]) -> FsResult<()> {
+        let mut walker = ChainWalker::new(vol.total_clusters, dir_cluster)?;
+        let mut cluster = dir_cluster;
+        // (sector, offset) of the free slots in a row so far
+        let mut run: Vec<(u64, usize)> = Vec::new();
+
+        loop {
+            let base_sector = vol.cluster_to_sector(cluster)?;
+
+            for s in 0..vol.sectors_per_cluster as u64 {
+                let sector_lba = base_sector + s;
+                let sector = Self::read_sector_raw(vol, sector_lba)?;
+
+                for i in 0..ENTRIES_PER_SECTOR {
+                    let off = i * DIR_ENTRY_SIZE;
+                    if sector[off] != 0x00 && sector[off] != 0xE5 {
+                        run.clear();
+                        continue;
+                    }
+                    run.push((sector_lba, off));
+                    if run.len() == entries.len() {
+                        return Self::write_dir_slots(vol, &run, entries);
+                    }
+                }
+            }
+
+            cluster = match walker.follow(Self::fat_read(vol, cluster)?)? {
+                Some(next) => next,
+                None => {
+                    // Allocate new cluster for directory; the run carries on into it
+                    let new_cluster = Self::alloc_cluster(vol, Some(cluster))?;
+                    walker.visit(new_cluster)?;
+                    new_cluster
+                }
+            };
+        }
+    }
+
+    /// Write `entries` into the directory slots `slots`, one sector at a time.
+    fn write_dir_slots(vol: &Volume, slots: &[(u64, usize)], entries: &[[u8; DIR_ENTRY_SIZE]]) -> FsResult<()> {
+        let mut i = 0;
+        while i < slots.len() {
+            let lba = slots[i].0;
+            let mut sector = Self::read_sector_raw(vol, lba)?;
+            while i < slots.len() && slots[i].0 == lba {
+                let off = slots[i].1;
+                sector[off..off + DIR_ENTRY_SIZE].copy_from_slice(&entries[i]);
+                i += 1;
+            }
+            Self::write_sector_raw(vol, lba, &sector)?;
+        }
+        Ok(())
+    }
+
+    /// Update an existing directory entry (find by name in parent cluster).
+    fn update_dir_entry(vol: &Volume, parent_cluster: u32, name: &[u8; 11], new_entry: &RawDirEntry) -> FsResult<()> {
+        if parent_cluster < 2 {
+            return Err(FsError::NotFound);
+        }
+        let mut walker = ChainWalker::new(vol.total_clusters, parent_cluster)?;
+        let mut cluster = parent_cluster;
+
+        loop {
+            let base_sector = vol.cluster_to_sector(cluster)?;
+
+            for s in 0..vol.sectors_per_cluster as u64 {
+                let sector_lba = base_sector + s;
+                let mut sector = Self::read_sector_raw(vol, sector_lba)?;
+
+                for i in 0..ENTRIES_PER_SECTOR {
+                    let off = i * DIR_ENTRY_SIZE;
+                    if sector[off] == 0x00 { return Err(FsError::NotFound); }
+                    if sector[off] == 0xE5 { continue; }
+
+                    let entry = RawDirEntry::from_bytes(&sector[off..off + DIR_ENTRY_SIZE]);
+                    if !entry.is_lfn() && entry.name == *name {
+                        let bytes = new_entry.to_bytes();
+                        sector[off..off + DIR_ENTRY_SIZE].copy_from_slice(&bytes);
+                        Self::write_sector_raw(vol, sector_lba, &sector)?;
+                        return Ok(());
+                    }
+                }
+            }
+
+            match walker.follow(Self::fat_read(vol, cluster)?)? {
+                Some(next) => cluster = next,
+                None => break,
+            }
+        }
+
+        Err(FsError::NotFound)
+    }
+
+    /// Mark the entry named `name` in the directory deleted, along with the
+    /// LFN entries in front of it. Its clusters are left alone.
+    fn remove_dir_entry(vol: &Volume, parent_cluster: u32, name: &[u8; 11]) -> FsResult<()> {
+        if parent_cluster < 2 {
+            return Err(FsError::NotFound);
+        }
+        let mut walker = ChainWalker::new(vol.total_clusters, parent_cluster)?;
+        let mut cluster = parent_cluster;
+        let mut lfn_run: Vec<(u64, usize)> = Vec::new();
+
+        'outer: loop {
+            let base_sector = vol.cluster_to_sector(cluster)?;
+
+            for s in 0..vol.sectors_per_cluster as u64 {
+                let sector_lba = base_sector + s;
+                let mut sector = Self::read_sector_raw(vol, sector_lba)?;
+
+                for i in 0..ENTRIES_PER_SECTOR {
+                    let off = i * DIR_ENTRY_SIZE;
+                    if sector[off] == 0x00 { break 'outer; }
+                    if sector[off] == 0xE5 {
+                        lfn_run.clear();
+                        continue;
+                    }
+
+                    let e = RawDirEntry::from_bytes(&sector[off..off + DIR_ENTRY_SIZE]);
+                    if e.is_lfn() {
+                        lfn_run.push((sector_lba, off));
+                        continue;
+                    }
+                    if e.name == *name {
+                        sector[off] = 0xE5; // mark as deleted
+                        // The run may start in an earlier sector, even an earlier cluster
+                        for &(lba, lfn_off) in &lfn_run {
+                            if lba == sector_lba {
+                                sector[lfn_off] = 0xE5;
+                            } else {
+                                let mut other = Self::read_sector_raw(vol, lba)?;
+                                other[lfn_off] = 0xE5;
+                                Self::write_sector_raw(vol, lba, &other)?;
+                            }
+                        }
+                        Self::write_sector_raw(vol, sector_lba, &sector)?;
+                        return Ok(());
+                    }
+                    lfn_run.clear();
+                }
+            }
+
+            match walker.follow(Self::fat_read(vol, cluster)?)? {
+                Some(next) => cluster = next,
+                None => break,
             }
+        }
 
-            // Need more clusters
-            let next = Self::fat_read(bpb, cluster)?;
-            if next >= FAT_EOC || next < 2 {
-                // Allocate new cluster
-                let new_cluster = Self::alloc_cluster(bpb, Some(cluster))?;
-                cluster = new_cluster;
-            } else {
-                cluster = next;
+        Err(FsError::NotFound)
+    }
+
+    /// Return every cluster of the chain starting at `first` to the free pool.
+    /// Nothing is freed if the chain turns out to be corrupt.
+    fn free_chain(vol: &Volume, first: u32) -> FsResult<()> {
+        if !vol.is_data_cluster(first) {
+            return Ok(());
+        }
+        let mut fat = FatBatch::new(vol);
+        let mut walker = ChainWalker::new(vol.total_clusters, first)?;
+        let mut c = first;
+        loop {
+            let next = walker.follow(fat.read(c)?)?;
+            fat.write(c, FAT_FREE)?;
+            match next {
+                Some(n) => c = n,
+                None => break,
             }
         }
+        fat.flush()
+    }
 
-        Ok(start_cluster)
+    /// True if the directory holds nothing besides "." and "..".
+    fn dir_is_empty(vol: &Volume, dir_cluster: u32) -> FsResult<bool> {
+        let children = Self::read_dir_entries(vol, dir_cluster)?;
+        Ok(children.iter().all(|(e, _, _)| {
+            let n = e.display_name();
+            n == "." || n == ".."
+        }))
     }
 
-    // ── Directory operations ────────────────────────────────
+    /// True if directory `dir` is `ancestor` or lies somewhere below it,
+    /// found by following ".." up to the root.
+    fn is_within(vol: &Volume, mut dir: u32, ancestor: u32) -> FsResult<bool> {
+        for _ in 0..MAX_DIR_DEPTH {
+            if dir == ancestor {
+                return Ok(true);
+            }
+            // ".." of a top-level directory is 0 on volumes formatted elsewhere
+            if dir < 2 || dir == vol.root_cluster {
+                return Ok(false);
+            }
+            let parent = Self::read_dir_entries(vol, dir)?.into_iter()
+                .find(|(e, _, _)| e.name == DOTDOT_NAME)
+                .map(|(e, _, _)| e.first_cluster());
+            match parent {
+                Some(parent) => dir = parent,
+                None => return Ok(false),
+            }
+        }
+        // Deeper than any sane tree: a loop of ".." entries
+        Err(FsError::IoError)
+    }
+}
 
-    /// Read all directory entries from a directory cluster chain.
-    fn read_dir_entries(bpb: &Bpb, dir_cluster: u32) -> FsResult<Vec<(RawDirEntry, u32, usize)>> {
-        // Returns (entry, sector_lba, offset_in_sector) for each valid entry
-        let mut entries = Vec::new();
-        let mut cluster = dir_cluster;
+// ══════════════════════════════════════════════════════════════
+//  Volume information
+// ══════════════════════════════════════════════════════════════
 
-        loop {
-            if cluster < 2 { break; }
-            let base_sector = bpb.cluster_to_sector(cluster);
+/// Identity and geometry of the mounted volume, for `diskinfo`.
+pub struct VolumeInfo {
+    pub oem_name: String,
+    /// None when the volume has no label (or the "NO NAME" placeholder).
+    pub label: Option<String>,
+    pub bytes_per_sector: u16,
+    pub sectors_per_cluster: u8,
+    pub num_fats: u8,
+    pub total_sectors: u32,
+    pub total_clusters: u32,
+    pub free_clusters: u32,
+}
 
-            for s in 0..bpb.sectors_per_cluster as u32 {
-                let sector_lba = base_sector + s;
-                let sector = Self::read_sector_raw(sector_lba)?;
+impl Fat32Fs {
+    /// Creation, modification and access times of the entry at `path`.
+    pub fn times(&self, path: &str) -> FsResult<FileTimes> {
+        let inner = self.inner.lock();
+        let (entry, _) = Self::resolve_path_entry(&inner.vol, path)?;
+        Ok(FileTimes {
+            created: dos_datetime(entry.create_date, entry.create_time, entry.create_time_tenth),
+            modified: dos_datetime(entry.write_date, entry.write_time, 0),
+            accessed: dos_datetime(entry.access_date, 0, 0),
+        })
+    }
+
+    /// Gather OEM name, label and capacity. Counting free clusters scans the whole FAT.
+    pub fn volume_info(&self) -> FsResult<VolumeInfo> {
+        let inner = self.inner.lock();
+        let vol = &inner.vol;
+
+        let total_clusters = vol.total_clusters;
+        let fat = Self::load_fat_copy(vol, 0, total_clusters)?;
+        let free_clusters = fat.iter().skip(2).filter(|&&v| v == FAT_FREE).count() as u32;
+
+        Ok(VolumeInfo {
+            oem_name: vol.oem_name.clone(),
+            label: Self::volume_label(vol)?,
+            bytes_per_sector: vol.bytes_per_sector,
+            sectors_per_cluster: vol.sectors_per_cluster,
+            num_fats: vol.num_fats,
+            total_sectors: vol.total_sectors,
+            total_clusters,
+            free_clusters,
+        })
+    }
+
+    /// Find the volume label: the root directory's volume-ID entry wins,
+    /// the extended BPB field is the fallback.
+    fn volume_label(vol: &Volume) -> FsResult<Option<String>> {
+        let mut walker = ChainWalker::new(vol.total_clusters, vol.root_cluster)?;
+        let mut cluster = vol.root_cluster;
 
+        'chain: loop {
+            let base_sector = vol.cluster_to_sector(cluster)?;
+
+            for s in 0..vol.sectors_per_cluster as u64 {
+                let sector = Self::read_sector_raw(vol, base_sector + s)?;
                 for i in 0..ENTRIES_PER_SECTOR {
                     let off = i * DIR_ENTRY_SIZE;
                     let entry = RawDirEntry::from_bytes(&sector[off..off + DIR_ENTRY_SIZE]);
-
-                    if entry.is_free() {
-                        return Ok(entries); // no more entries
+                    if entry.is_free() { break 'chain; }
+                    if entry.is_deleted() || entry.is_lfn() { continue; }
+                    if entry.is_volume_id() {
+                        return Ok(normalize_label(trim_padded(&entry.name)));
                     }
-                    if entry.is_deleted() || entry.is_lfn() || entry.is_volume_id() {
-                        continue;
-                    }
-
-                    entries.push((entry, sector_lba, off));
                 }
             }
 
-            let next = Self::fat_read(bpb, cluster)?;
-            if next >= FAT_EOC { break; }
-            cluster = next;
+            match walker.follow(Self::fat_read(vol, cluster)?)? {
+                Some(next) => cluster = next,
+                None => break,
+            }
         }
 
-        Ok(entries)
+        Ok(normalize_label(vol.bpb_label.clone()))
     }
+}
 
-    /// Resolve a path to the target directory entry.
-    /// Returns (entry, parent_cluster).
-    fn resolve_path_entry(bpb: &Bpb, path: &str) -> FsResult<(RawDirEntry, u32)> {
-        let path = path.trim_start_matches('/');
-        if path.is_empty() {
-            // Root directory — synthesize entry
-            let mut entry = RawDirEntry {
-                name: [0x20; 11],
-                attr: ATTR_DIRECTORY,
-                cluster_hi: (bpb.root_cluster >> 16) as u16,
-                cluster_lo: bpb.root_cluster as u16,
-                file_size: 0,
-            };
-            entry.name[0] = b'/';
-            return Ok((entry, 0));
-        }
+/// Blank labels and the mkfs "NO NAME" placeholder mean "unlabelled".
+fn normalize_label(label: String) -> Option<String> {
+    if label.is_empty() || label == "NO NAME" {
+        None
+    } else {
+        Some(label)
+    }
+}
 
-        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
-        let mut current_cluster = bpb.root_cluster;
+// ══════════════════════════════════════════════════════════════
+//  Consistency check (fsck)
+// ══════════════════════════════════════════════════════════════
 
-        for (idx, segment) in segments.iter().enumerate() {
-            let entries = Self::read_dir_entries(bpb, current_cluster)?;
-            let target_name = encode_83_name(segment).ok_or(FsError::InvalidPath)?;
+/// Maximum number of individual problems kept in a report.
+const FSCK_MAX_MESSAGES: usize = 32;
+
+/// Result of a read-only consistency check of the mounted volume.
+pub struct FsckReport {
+    pub num_fats: u8,
+    pub total_clusters: u32,
+    /// Clusters whose entry differs between the primary FAT and a mirror.
+    pub fat_mismatches: u32,
+    /// Chains that loop back onto themselves.
+    pub loops: u32,
+    /// Chains pointing at free, reserved or out-of-range clusters.
+    pub bad_links: u32,
+    /// Clusters claimed by more than one chain.
+    pub cross_links: u32,
+    /// Files whose size does not match their chain length.
+    pub size_mismatches: u32,
+    /// Allocated clusters not reachable from any directory entry.
+    pub orphaned_clusters: u32,
+    pub used_clusters: u32,
+    pub files: u32,
+    pub directories: u32,
+    pub messages: Vec<String>,
+}
 
-            let mut found = false;
-            for (entry, _, _) in &entries {
-                if entry.name == target_name {
-                    if idx == segments.len() - 1 {
-                        // Final segment — return this entry
-                        return Ok((entry.clone(), current_cluster));
-                    }
-                    if !entry.is_dir() {
-                        return Err(FsError::NotADirectory);
-                    }
-                    current_cluster = entry.first_cluster();
-                    found = true;
-                    break;
+impl FsckReport {
+    pub fn is_clean(&self) -> bool {
+        self.fat_mismatches == 0 && self.loops == 0 && self.bad_links == 0
+            && self.cross_links == 0 && self.size_mismatches == 0 && self.orphaned_clusters == 0
+    }
+
+    fn note(&mut self, msg: String) {
+        if self.messages.len() < FSCK_MAX_MESSAGES {
+            self.messages.push(msg);
+        }
+    }
+}
+
+impl Fat32Fs {
+    /// Check the volume without modifying it: compare every FAT copy against the
+    /// primary, walk all chains reachable from the root directory and count
+    /// allocated clusters that nothing references.
+    pub fn fsck(&self) -> FsResult<FsckReport> {
+        let inner = self.inner.lock();
+        let vol = &inner.vol;
+
+        let total_clusters = vol.total_clusters;
+        let mut report = FsckReport {
+            num_fats: vol.num_fats,
+            total_clusters,
+            fat_mismatches: 0,
+            loops: 0,
+            bad_links: 0,
+            cross_links: 0,
+            size_mismatches: 0,
+            orphaned_clusters: 0,
+            used_clusters: 0,
+            files: 0,
+            directories: 0,
+            messages: Vec::new(),
+        };
+
+        // 1. Load the primary FAT and compare each mirror against it
+        let fat = Self::load_fat_copy(vol, 0, total_clusters)?;
+        for copy in 1..vol.num_fats as u32 {
+            let mirror = Self::load_fat_copy(vol, copy, total_clusters)?;
+            for cluster in 2..fat.len() {
+                if fat[cluster] != mirror[cluster] {
+                    report.fat_mismatches += 1;
+                    report.note(alloc::format!("FAT{} differs at cluster {}: {:#x} vs {:#x}",
+                        copy + 1, cluster, fat[cluster], mirror[cluster]));
                 }
             }
-            if !found {
-                return Err(FsError::NotFound);
-            }
         }
 
-        Err(FsError::NotFound)
-    }
+        // 2. Walk the directory tree, validating every chain against the primary FAT
+        let mut owner: Vec<bool> = vec![false; fat.len()];
+        let mut pending: Vec<(String, u32)> = vec![(String::from("/"), vol.root_cluster)];
+        let cluster_bytes = vol.sectors_per_cluster as usize * SECTOR_SIZE;
 
-    /// Resolve a path to the parent directory cluster and child name.
-    fn resolve_parent_and_name(bpb: &Bpb, path: &str) -> FsResult<(u32, String)> {
-        let path = path.trim_start_matches('/');
-        if path.is_empty() {
-            return Err(FsError::InvalidPath);
+        while let Some((dir_path, dir_cluster)) = pending.pop() {
+            report.directories += 1;
+            let chain = Self::check_chain(&fat, &mut owner, dir_cluster, &dir_path, &mut report);
+
+            for entry in Self::read_dir_chain(vol, &chain)? {
+                let name = entry.display_name();
+                if name == "." || name == ".." {
+                    continue;
+                }
+                let path = if dir_path == "/" {
+                    alloc::format!("/{}", name)
+                } else {
+                    alloc::format!("{}/{}", dir_path, name)
+                };
+
+                if entry.is_dir() {
+                    pending.push((path, entry.first_cluster()));
+                    continue;
+                }
+
+                report.files += 1;
+                if entry.first_cluster() < 2 {
+                    if entry.file_size != 0 {
+                        report.size_mismatches += 1;
+                        report.note(alloc::format!("{}: {} bytes but no clusters", path, entry.file_size));
+                    }
+                    continue;
+                }
+                let chain = Self::check_chain(&fat, &mut owner, entry.first_cluster(), &path, &mut report);
+                let expected = (entry.file_size as usize + cluster_bytes - 1) / cluster_bytes;
+                if chain.len() != expected.max(1) {
+                    report.size_mismatches += 1;
+                    report.note(alloc::format!("{}: size {} needs {} clusters, chain has {}",
+                        path, entry.file_size, expected, chain.len()));
+                }
+            }
         }
 
-        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
-        if segments.is_empty() {
-            return Err(FsError::InvalidPath);
+        // 3. Anything allocated but never reached is orphaned
+        for cluster in 2..fat.len() {
+            let value = fat[cluster];
+            if value == FAT_FREE || value == FAT_BAD {
+                continue;
+            }
+            report.used_clusters += 1;
+            if !owner[cluster] {
+                report.orphaned_clusters += 1;
+            }
         }
 
-        let child_name = String::from(*segments.last().unwrap());
-        let mut parent_cluster = bpb.root_cluster;
+        Ok(report)
+    }
 
-        // Navigate to parent directory
-        for segment in &segments[..segments.len() - 1] {
-            let entries = Self::read_dir_entries(bpb, parent_cluster)?;
-            let target = encode_83_name(segment).ok_or(FsError::InvalidPath)?;
-            let mut found = false;
-            for (entry, _, _) in &entries {
-                if entry.name == target && entry.is_dir() {
-                    parent_cluster = entry.first_cluster();
-                    found = true;
-                    break;
-                }
-            }
-            if !found {
-                return Err(FsError::NotFound);
+    /// Read one FAT copy into memory (entries 0..total_clusters+2).
+    fn load_fat_copy(vol: &Volume, copy: u32, total_clusters: u32) -> FsResult<Vec<u32>> {
+        let entries = (total_clusters + 2) as usize;
+        let mut fat = Vec::with_capacity(entries);
+        let mut sector_idx = 0;
+
+        while fat.len() < entries && sector_idx < vol.fat_size {
+            let lba = vol.fat_sector(copy, sector_idx);
+            let sector = Self::read_sector_raw(vol, lba)?;
+            for chunk in sector.chunks_exact(4) {
+                if fat.len() == entries { break; }
+                fat.push(u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) & 0x0FFF_FFFF);
             }
+            sector_idx += 1;
         }
 
-        Ok((parent_cluster, child_name))
+        Ok(fat)
     }
 
-    /// Add a new entry to a directory.
-    fn add_dir_entry(bpb: &Bpb, dir_cluster: u32, entry: &RawDirEntry) -> FsResult<()> {
-        let mut cluster = dir_cluster;
+    /// Follow a chain in the in-memory FAT, recording problems.
+    /// Returns the valid prefix of the chain.
+    fn check_chain(fat: &[u32], owner: &mut [bool], start: u32, path: &str, report: &mut FsckReport) -> Vec<u32> {
+        let mut chain = Vec::new();
+        let mut cluster = start;
 
         loop {
-            if cluster < 2 { return Err(FsError::IoError); }
-            let base_sector = bpb.cluster_to_sector(cluster);
+            if cluster < 2 || cluster as usize >= fat.len() {
+                report.bad_links += 1;
+                report.note(alloc::format!("{}: chain points to invalid cluster {:#x}", path, cluster));
+                break;
+            }
+            if chain.contains(&cluster) {
+                report.loops += 1;
+                report.note(alloc::format!("{}: chain loops back to cluster {}", path, cluster));
+                break;
+            }
+            if owner[cluster as usize] {
+                report.cross_links += 1;
+                report.note(alloc::format!("{}: cluster {} is cross-linked", path, cluster));
+                break;
+            }
 
-            for s in 0..bpb.sectors_per_cluster as u32 {
-                let sector_lba = base_sector + s;
-                let mut sector = Self::read_sector_raw(sector_lba)?;
+            owner[cluster as usize] = true;
+            chain.push(cluster);
 
-                for i in 0..ENTRIES_PER_SECTOR {
-                    let off = i * DIR_ENTRY_SIZE;
-                    if sector[off] == 0x00 || sector[off] == 0xE5 {
-                        // Found a free slot
-                        let bytes = entry.to_bytes();
-                        sector[off..off + DIR_ENTRY_SIZE].copy_from_slice(&bytes);
-                        Self::write_sector_raw(sector_lba, &sector)?;
-                        return Ok(());
-                    }
-                }
+            let next = fat[cluster as usize];
+            if next >= FAT_EOC {
+                break;
             }
-
-            let next = Self::fat_read(bpb, cluster)?;
-            if next >= FAT_EOC || next < 2 {
-                // Allocate new cluster for directory
-                let new_cluster = Self::alloc_cluster(bpb, Some(cluster))?;
-                cluster = new_cluster;
-            } else {
-                cluster = next;
+            if next == FAT_FREE || next == FAT_BAD {
+                report.bad_links += 1;
+                report.note(alloc::format!("{}: cluster {} links to {} cluster",
+                    path, cluster, if next == FAT_FREE { "a free" } else { "a bad" }));
+                break;
             }
+            cluster = next;
         }
-    }
-
-    /// Update an existing directory entry (find by name in parent cluster).
-    fn update_dir_entry(bpb: &Bpb, parent_cluster: u32, name: &[u8; 11], new_entry: &RawDirEntry) -> FsResult<()> {
-        let mut cluster = parent_cluster;
 
-        loop {
-            if cluster < 2 { return Err(FsError::NotFound); }
-            let base_sector = bpb.cluster_to_sector(cluster);
+        chain
+    }
 
-            for s in 0..bpb.sectors_per_cluster as u32 {
-                let sector_lba = base_sector + s;
-                let mut sector = Self::read_sector_raw(sector_lba)?;
+    /// Parse directory entries from an already-validated list of clusters.
+    fn read_dir_chain(vol: &Volume, chain: &[u32]) -> FsResult<Vec<RawDirEntry>> {
+        let mut entries = Vec::new();
 
+        for &cluster in chain {
+            let base_sector = vol.cluster_to_sector(cluster)?;
+            for s in 0..vol.sectors_per_cluster as u64 {
+                let sector = Self::read_sector_raw(vol, base_sector + s)?;
                 for i in 0..ENTRIES_PER_SECTOR {
                     let off = i * DIR_ENTRY_SIZE;
-                    if sector[off] == 0x00 { return Err(FsError::NotFound); }
-                    if sector[off] == 0xE5 { continue; }
-
                     let entry = RawDirEntry::from_bytes(&sector[off..off + DIR_ENTRY_SIZE]);
-                    if entry.name == *name {
-                        let bytes = new_entry.to_bytes();
-                        sector[off..off + DIR_ENTRY_SIZE].copy_from_slice(&bytes);
-                        Self::write_sector_raw(sector_lba, &sector)?;
-                        return Ok(());
+                    if entry.is_free() {
+                        return Ok(entries);
+                    }
+                    if entry.is_deleted() || entry.is_lfn() || entry.is_volume_id() {
+                        continue;
                     }
+                    entries.push(entry);
                 }
             }
-
-            let next = Self::fat_read(bpb, cluster)?;
-            if next >= FAT_EOC { break; }
-            cluster = next;
         }
 
-        Err(FsError::NotFound)
+        Ok(entries)
     }
 }
 
@@ -592,121 +1847,97 @@ impl FileSystem for Fat32Fs {
 
     fn create(&self, path: &str) -> FsResult<Inode> {
         let inner = self.inner.lock();
-        let bpb = &inner.bpb;
+        let vol = &inner.vol;
 
-        let (parent_cluster, child_name) = Self::resolve_parent_and_name(bpb, path)?;
-        let name83 = encode_83_name(&child_name).ok_or(FsError::InvalidPath)?;
+        let (parent_cluster, child_name) = Self::resolve_parent_and_name(vol, path)?;
 
-        // Check for duplicates
-        let entries = Self::read_dir_entries(bpb, parent_cluster)?;
-        for (e, _, _) in &entries {
-            if e.name == name83 {
-                return Err(FsError::AlreadyExists);
-            }
+        // Pick a free short name (rejects duplicates and bad names)
+        let entries = Self::read_dir_entries(vol, parent_cluster)?;
+        if entries.iter().any(|(e, _, _)| e.long_name.is_some() && e.matches(&child_name)) {
+            return Err(FsError::AlreadyExists);
         }
+        let taken: Vec<[u8; 11]> = entries.iter().map(|(e, _, _)| e.name).collect();
+        let name83 = short_name_for(&child_name, &taken)?;
 
         // Allocate a cluster for the file
-        let cluster = Self::alloc_cluster(bpb, None)?;
+        let cluster = Self::alloc_cluster(vol, None)?;
 
-        let entry = RawDirEntry {
-            name: name83,
-            attr: ATTR_ARCHIVE,
-            cluster_hi: (cluster >> 16) as u16,
-            cluster_lo: cluster as u16,
-            file_size: 0,
-        };
+        let entry = RawDirEntry::new(name83, ATTR_ARCHIVE, cluster);
 
-        Self::add_dir_entry(bpb, parent_cluster, &entry)?;
+        Self::add_dir_entries(vol, parent_cluster, &entries_for(&child_name, &entry)?)?;
 
         Ok(Inode {
             id: cluster as u64,
             file_type: FileType::File,
             size: 0,
+            mode: entry.mode(),
         })
     }
 
     fn mkdir(&self, path: &str) -> FsResult<Inode> {
         let inner = self.inner.lock();
-        let bpb = &inner.bpb;
+        let vol = &inner.vol;
 
-        let (parent_cluster, child_name) = Self::resolve_parent_and_name(bpb, path)?;
-        let name83 = encode_83_name(&child_name).ok_or(FsError::InvalidPath)?;
+        let (parent_cluster, child_name) = Self::resolve_parent_and_name(vol, path)?;
 
-        // Check duplicates
-        let entries = Self::read_dir_entries(bpb, parent_cluster)?;
-        for (e, _, _) in &entries {
-            if e.name == name83 {
-                return Err(FsError::AlreadyExists);
-            }
+        // Pick a free short name (rejects duplicates and bad names)
+        let entries = Self::read_dir_entries(vol, parent_cluster)?;
+        if entries.iter().any(|(e, _, _)| e.long_name.is_some() && e.matches(&child_name)) {
+            return Err(FsError::AlreadyExists);
         }
+        let taken: Vec<[u8; 11]> = entries.iter().map(|(e, _, _)| e.name).collect();
+        let name83 = short_name_for(&child_name, &taken)?;
 
         // Allocate cluster for new directory
-        let cluster = Self::alloc_cluster(bpb, None)?;
+        let cluster = Self::alloc_cluster(vol, None)?;
 
-        // Create . and .. entries
+        // Create . and .. entries, stamped like the directory itself
+        let dir_entry = RawDirEntry::new(name83, ATTR_DIRECTORY, cluster);
         let dot_entry = RawDirEntry {
-            name: {
-                let mut n = [0x20u8; 11];
-                n[0] = b'.';
-                n
-            },
-            attr: ATTR_DIRECTORY,
-            cluster_hi: (cluster >> 16) as u16,
-            cluster_lo: cluster as u16,
-            file_size: 0,
+            name: *b".          ",
+            ..dir_entry.clone()
         };
         let dotdot_entry = RawDirEntry {
-            name: {
-                let mut n = [0x20u8; 11];
-                n[0] = b'.';
-                n[1] = b'.';
-                n
-            },
-            attr: ATTR_DIRECTORY,
+            name: DOTDOT_NAME,
             cluster_hi: (parent_cluster >> 16) as u16,
             cluster_lo: parent_cluster as u16,
-            file_size: 0,
+            ..dir_entry.clone()
         };
 
-        Self::add_dir_entry(bpb, cluster, &dot_entry)?;
-        Self::add_dir_entry(bpb, cluster, &dotdot_entry)?;
+        Self::add_dir_entry(vol, cluster, &dot_entry)?;
+        Self::add_dir_entry(vol, cluster, &dotdot_entry)?;
 
         // Add entry in parent
-        let dir_entry = RawDirEntry {
-            name: name83,
-            attr: ATTR_DIRECTORY,
-            cluster_hi: (cluster >> 16) as u16,
-            cluster_lo: cluster as u16,
-            file_size: 0,
-        };
-        Self::add_dir_entry(bpb, parent_cluster, &dir_entry)?;
+        Self::add_dir_entries(vol, parent_cluster, &entries_for(&child_name, &dir_entry)?)?;
 
         Ok(Inode {
             id: cluster as u64,
             file_type: FileType::Directory,
             size: 0,
+            mode: dir_entry.mode(),
         })
     }
 
     fn lookup(&self, path: &str) -> FsResult<Inode> {
         let inner = self.inner.lock();
-        let bpb = &inner.bpb;
+        let vol = &inner.vol;
 
-        let (entry, _) = Self::resolve_path_entry(bpb, path)?;
+        let (entry, _) = Self::resolve_path_entry(vol, path)?;
         let ft = if entry.is_dir() { FileType::Directory } else { FileType::File };
 
         Ok(Inode {
             id: entry.first_cluster() as u64,
             file_type: ft,
             size: entry.file_size as usize,
+            mode: entry.mode(),
         })
     }
 
     fn read(&self, path: &str, offset: usize, buf: &mut [u8]) -> FsResult<usize> {
         let inner = self.inner.lock();
-        let bpb = &inner.bpb;
+        let vol = &inner.vol;
 
-        let (entry, _) = Self::resolve_path_entry(bpb, path)?;
+        let (entry, _) = Self::resolve_path_entry(vol, path)?;
         if entry.is_dir() {
             return Err(FsError::IsADirectory);
         }
@@ -716,19 +1947,15 @@ impl FileSystem for Fat32Fs {
             return Ok(0);
         }
 
-        let data = Self::read_chain(bpb, entry.first_cluster())?;
-        let available = &data[offset..file_size.min(data.len())];
-        let to_read = buf.len().min(available.len());
-        buf[..to_read].copy_from_slice(&available[..to_read]);
-
-        Ok(to_read)
+        let to_read = buf.len().min(file_size - offset);
+        Self::read_chain_at(vol, entry.first_cluster(), offset, &mut buf[..to_read])
     }
 
     fn write(&self, path: &str, offset: usize, data: &[u8]) -> FsResult<usize> {
         let inner = self.inner.lock();
-        let bpb = &inner.bpb;
+        let vol = &inner.vol;
 
-        let (entry, parent_cluster) = Self::resolve_path_entry(bpb, path)?;
+        let (entry, parent_cluster) = Self::resolve_path_entry(vol, path)?;
         if entry.is_dir() {
             return Err(FsError::IsADirectory);
         }
@@ -736,7 +1963,7 @@ impl FileSystem for Fat32Fs {
         // Read existing data
         let cluster = entry.first_cluster();
         let mut file_data = if entry.file_size > 0 {
-            let d = Self::read_chain(bpb, cluster)?;
+            let d = Self::read_chain(vol, cluster)?;
             d[..entry.file_size as usize].to_vec()
         } else {
             Vec::new()
@@ -750,31 +1977,85 @@ impl FileSystem for Fat32Fs {
         file_data[offset..end].copy_from_slice(data);
 
         // Write back
-        Self::write_chain(bpb, cluster, &file_data)?;
+        Self::write_chain(vol, cluster, &file_data)?;
 
-        // Update directory entry with new size
+        // Update directory entry with new size and write time
         let mut updated = entry.clone();
         updated.file_size = file_data.len() as u32;
-        Self::update_dir_entry(bpb, parent_cluster, &entry.name, &updated)?;
+        updated.touch_write();
+        Self::update_dir_entry(vol, parent_cluster, &entry.name, &updated)?;
 
         Ok(data.len())
     }
 
+    /// Shrinking cuts the chain after the cluster holding byte `len - 1`
+    /// and frees the rest; the first cluster is kept even at length 0, as
+    /// `create` allocates one. Growing goes through `write`, which zero-fills
+    /// from the old end, including any stale bytes left in its last cluster.
+    fn truncate(&self, path: &str, len: usize) -> FsResult<()> {
+        let inner = self.inner.lock();
+        let vol = &inner.vol;
+
+        let (entry, parent_cluster) = Self::resolve_path_entry(vol, path)?;
+        if entry.is_dir() {
+            return Err(FsError::IsADirectory);
+        }
+        let size = entry.file_size as usize;
+        if len > size {
+            drop(inner);
+            return self.write(path, size, &alloc::vec![0u8; len - size]).map(|_| ());
+        }
+        if len == size {
+            return Ok(());
+        }
+
+        let cluster_bytes = SECTOR_SIZE * vol.sectors_per_cluster as usize;
+        let keep = len.div_ceil(cluster_bytes).max(1);
+        let mut fat = FatBatch::new(vol);
+        let mut last = entry.first_cluster();
+        if vol.is_data_cluster(last) {
+            let mut walker = ChainWalker::new(vol.total_clusters, last)?;
+            for _ in 1..keep {
+                match walker.follow(fat.read(last)?)? {
+                    Some(next) => last = next,
+                    None => break,
+                }
+            }
+            if let Some(mut c) = walker.follow(fat.read(last)?)? {
+                fat.write(last, 0x0FFF_FFFF)?; // new end of chain
+                loop {
+                    let next = walker.follow(fat.read(c)?)?;
+                    fat.write(c, FAT_FREE)?;
+                    match next {
+                        Some(n) => c = n,
+                        None => break,
+                    }
+                }
+            }
+        }
+        fat.flush()?;
+
+        let mut updated = entry.clone();
+        updated.file_size = len as u32;
+        updated.touch_write();
+        Self::update_dir_entry(vol, parent_cluster, &entry.name, &updated)
+    }
+
     fn readdir(&self, path: &str) -> FsResult<Vec<VfsDirEntry>> {
         let inner = self.inner.lock();
-        let bpb = &inner.bpb;
+        let vol = &inner.vol;
 
         let dir_cluster = if path.trim_start_matches('/').is_empty() {
-            bpb.root_cluster
+            vol.root_cluster
         } else {
-            let (entry, _) = Self::resolve_path_entry(bpb, path)?;
+            let (entry, _) = Self::resolve_path_entry(vol, path)?;
             if !entry.is_dir() {
                 return Err(FsError::NotADirectory);
             }
             entry.first_cluster()
         };
 
-        let entries = Self::read_dir_entries(bpb, dir_cluster)?;
+        let entries = Self::read_dir_entries(vol, dir_cluster)?;
         let mut result = Vec::new();
 
         for (e, _, _) in &entries {
@@ -785,11 +2066,13 @@ impl FileSystem for Fat32Fs {
             }
             let ft = if e.is_dir() { FileType::Directory } else { FileType::File };
             result.push(VfsDirEntry {
-                name: name.to_lowercase(),
+                // Long names keep their case; bare 8.3 names read better lowercased
+                name: if e.long_name.is_some() { name } else { name.to_lowercase() },
                 inode: Inode {
                     id: e.first_cluster() as u64,
                     file_type: ft,
                     size: e.file_size as usize,
+                    mode: e.mode(),
                 },
             });
         }
@@ -799,65 +2082,137 @@ impl FileSystem for Fat32Fs {
 
     fn unlink(&self, path: &str) -> FsResult<()> {
         let inner = self.inner.lock();
-        let bpb = &inner.bpb;
+        let vol = &inner.vol;
 
-        let (entry, parent_cluster) = Self::resolve_path_entry(bpb, path)?;
+        let (entry, parent_cluster) = Self::resolve_path_entry(vol, path)?;
 
         // Don't delete non-empty directories
-        if entry.is_dir() {
-            let children = Self::read_dir_entries(bpb, entry.first_cluster())?;
-            let real_children: Vec<_> = children.iter()
-                .filter(|(e, _, _)| {
-                    let n = e.display_name();
-                    n != "." && n != ".."
-                })
-                .collect();
-            if !real_children.is_empty() {
-                return Err(FsError::IsADirectory);
-            }
+        if entry.is_dir() && !Self::dir_is_empty(vol, entry.first_cluster())? {
+            return Err(FsError::IsADirectory);
         }
 
-        // Mark directory entry as deleted
-        let mut cluster = parent_cluster;
-        let name83 = entry.name;
-
-        'outer: loop {
-            if cluster < 2 { break; }
-            let base_sector = bpb.cluster_to_sector(cluster);
+        Self::remove_dir_entry(vol, parent_cluster, &entry.name)?;
+        Self::free_chain(vol, entry.first_cluster())
+    }
 
-            for s in 0..bpb.sectors_per_cluster as u32 {
-                let sector_lba = base_sector + s;
-                let mut sector = Self::read_sector_raw(sector_lba)?;
+    /// Move the entry to its new directory and name. The cluster chain stays
+    /// where it is: only directory entries are written (and a moved
+    /// directory's ".."). The new entry is added before the old one is
+    /// removed, so an interrupted rename leaves two names, never none.
+    fn rename(&self, from: &str, to: &str) -> FsResult<()> {
+        let inner = self.inner.lock();
+        let vol = &inner.vol;
 
-                for i in 0..ENTRIES_PER_SECTOR {
-                    let off = i * DIR_ENTRY_SIZE;
-                    if sector[off] == 0x00 { break 'outer; }
-                    if sector[off] == 0xE5 { continue; }
+        let (entry, from_parent) = Self::resolve_path_entry(vol, from)?;
+        if from_parent == 0 {
+            return Err(FsError::InvalidPath); // the root directory
+        }
+        let (to_parent, to_name) = Self::resolve_parent_and_name(vol, to)?;
 
-                    let e = RawDirEntry::from_bytes(&sector[off..off + DIR_ENTRY_SIZE]);
-                    if e.name == name83 {
-                        sector[off] = 0xE5; // mark as deleted
-                        Self::write_sector_raw(sector_lba, &sector)?;
-
-                        // Free the cluster chain
-                        let mut c = entry.first_cluster();
-                        while c >= 2 && c < FAT_EOC {
-                            let next = Self::fat_read(bpb, c)?;
-                            Self::fat_write(bpb, c, FAT_FREE)?;
-                            if next >= FAT_EOC { break; }
-                            c = next;
-                        }
+        // A directory can't become its own descendant
+        if entry.is_dir() && Self::is_within(vol, to_parent, entry.first_cluster())? {
+            return Err(FsError::InvalidPath);
+        }
 
-                        return Ok(());
-                    }
+        let entries = Self::read_dir_entries(vol, to_parent)?;
+        let existing = entries.iter().map(|(e, _, _)| e).find(|e| e.matches(&to_name));
+        if let Some(existing) = existing {
+            if to_parent == from_parent && existing.name == entry.name {
+                return Ok(()); // renamed onto itself
+            }
+            match (entry.is_dir(), existing.is_dir()) {
+                (true, false) => return Err(FsError::NotADirectory),
+                (false, true) => return Err(FsError::IsADirectory),
+                (true, true) if !Self::dir_is_empty(vol, existing.first_cluster())? => {
+                    return Err(FsError::IsADirectory);
                 }
+                _ => {}
             }
+        }
 
-            let next = Self::fat_read(bpb, cluster)?;
-            if next >= FAT_EOC { break; }
-            cluster = next;
+        // Settle the new name before anything is overwritten
+        let taken: Vec<[u8; 11]> = entries.iter()
+            .map(|(e, _, _)| e.name)
+            .filter(|name| existing.is_none_or(|e| e.name != *name))
+            .collect();
+        let name83 = short_name_for(&to_name, &taken)?;
+
+        if let Some(existing) = existing {
+            Self::remove_dir_entry(vol, to_parent, &existing.name)?;
+            Self::free_chain(vol, existing.first_cluster())?;
         }
 
-        Err(FsError::NotFound)
+        let moved = RawDirEntry { name: name83, long_name: None, ..entry.clone() };
+        Self::add_dir_entries(vol, to_parent, &entries_for(&to_name, &moved)?)?;
+        Self::remove_dir_entry(vol, from_parent, &entry.name)?;
+
+        if entry.is_dir() && to_parent != from_parent {
+            let dir = entry.first_cluster();
+            let dotdot = Self::read_dir_entries(vol, dir)?.into_iter()
+                .map(|(e, _, _)| e)
+                .find(|e| e.name == DOTDOT_NAME);
+            if let Some(dotdot) = dotdot {
+                let updated = RawDirEntry {
+                    cluster_hi: (to_parent >> 16) as u16,
+                    cluster_lo: to_parent as u16,
+                    ..dotdot
+                };
+                Self::update_dir_entry(vol, dir, &DOTDOT_NAME, &updated)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn modified(&self, path: &str) -> FsResult<DateTime> {
+        Ok(self.times(path)?.modified)
+    }
+
+    fn touch(&self, path: &str) -> FsResult<()> {
+        let inner = self.inner.lock();
+        let vol = &inner.vol;
+
+        let (entry, parent_cluster) = Self::resolve_path_entry(vol, path)?;
+        if parent_cluster == 0 {
+            // The root directory has no entry to hold times
+            return Err(FsError::NotSupported);
+        }
+        let mut updated = entry.clone();
+        updated.touch_write();
+        Self::update_dir_entry(vol, parent_cluster, &entry.name, &updated)
+    }
+
+    /// Only the owner write bit is stored, as ATTR_READ_ONLY; the other
+    /// bits always read back as the defaults.
+    fn set_mode(&self, path: &str, mode: u16) -> FsResult<()> {
+        let inner = self.inner.lock();
+        let vol = &inner.vol;
+
+        let (entry, parent_cluster) = Self::resolve_path_entry(vol, path)?;
+        if parent_cluster == 0 {
+            // The root directory has no entry to hold attributes
+            return Err(FsError::NotSupported);
+        }
+        let mut updated = entry.clone();
+        if mode & MODE_WRITE == 0 {
+            updated.attr |= ATTR_READ_ONLY;
+        } else {
+            updated.attr &= !ATTR_READ_ONLY;
+        }
+        if updated.attr == entry.attr {
+            return Ok(());
+        }
+        Self::update_dir_entry(vol, parent_cluster, &entry.name, &updated)
+    }
+
+    fn sync(&self) -> FsResult<()> {
+        // Write back the sector cache, then make sure the device's own cache
+        // hits the platter. The cache is emptied too, so a volume unmounted
+        // and changed behind the driver's back isn't read stale later.
+        let inner = self.inner.lock();
+        let vol = &inner.vol;
+        let mut cache = vol.cache.lock();
+        cache.flush(vol.dev)?;
+        cache.clear();
+        vol.dev.flush().map_err(|_| FsError::IoError)
     }
 }
@@ -0,0 +1,97 @@
+// mkfs for FAT32: lay out an empty volume on a block device.
+
+use crate::drivers::block::{BlockDevice, MemBlockDevice};
+use crate::fs::error::{FsError, FsResult};
+
+const SECTOR_SIZE: usize = 512;
+/// Reserved sectors before the first FAT (boot sector, FSInfo, backup boot sector).
+const RESERVED_SECTORS: u32 = 32;
+const NUM_FATS: u32 = 2;
+const FSINFO_SECTOR: u32 = 1;
+const BACKUP_BOOT_SECTOR: u32 = 6;
+const ROOT_CLUSTER: u32 = 2;
+/// "Fixed disk" media descriptor, repeated in the low byte of FAT entry 0.
+const MEDIA_FIXED: u8 = 0xF8;
+const FAT_EOC: u32 = 0x0FFF_FFFF;
+
+/// Format `dev` as an empty FAT32 volume with `sectors_per_cluster`
+/// (a power of two) and `label` (up to 11 characters, padded with spaces).
+/// Writes the boot sector and its backup, the FSInfo sector, both FATs
+/// (cleared, with the reserved entries and the root directory's chain set)
+/// and a zeroed root directory cluster.
+pub fn format(dev: &dyn BlockDevice, sectors_per_cluster: u8, label: &str) -> FsResult<()> {
+    if dev.block_size() != SECTOR_SIZE || !sectors_per_cluster.is_power_of_two() || label.len() > 11 {
+        return Err(FsError::InvalidPath);
+    }
+    let total_sectors = u32::try_from(dev.block_count()).map_err(|_| FsError::InvalidPath)?;
+    let spc = sectors_per_cluster as u32;
+
+    // Size the FATs for every sector past the reserved area being data;
+    // the few clusters the FATs themselves take make that an overestimate
+    let clusters = total_sectors.saturating_sub(RESERVED_SECTORS) / spc;
+    let fat_size = ((clusters + 2) * 4).div_ceil(SECTOR_SIZE as u32);
+    let data_start = RESERVED_SECTORS + NUM_FATS * fat_size;
+    if data_start + spc > total_sectors {
+        return Err(FsError::NoSpace);
+    }
+
+    let mut boot = [0u8; SECTOR_SIZE];
+    boot[0..3].copy_from_slice(&[0xEB, 0x58, 0x90]); // jmp short over the BPB; nop
+    boot[3..11].copy_from_slice(b"ATOMICOS");
+    boot[11..13].copy_from_slice(&(SECTOR_SIZE as u16).to_le_bytes());
+    boot[13] = sectors_per_cluster;
+    boot[14..16].copy_from_slice(&(RESERVED_SECTORS as u16).to_le_bytes());
+    boot[16] = NUM_FATS as u8;
+    boot[21] = MEDIA_FIXED;
+    boot[32..36].copy_from_slice(&total_sectors.to_le_bytes());
+    boot[36..40].copy_from_slice(&fat_size.to_le_bytes());
+    boot[44..48].copy_from_slice(&ROOT_CLUSTER.to_le_bytes());
+    boot[48..50].copy_from_slice(&(FSINFO_SECTOR as u16).to_le_bytes());
+    boot[50..52].copy_from_slice(&(BACKUP_BOOT_SECTOR as u16).to_le_bytes());
+    boot[64] = 0x80; // drive number
+    boot[66] = 0x29; // extended boot signature: serial, label and type follow
+    boot[67..71].copy_from_slice(&0x2026_1016u32.to_le_bytes());
+    let mut padded_label = *b"NO NAME    ";
+    if !label.is_empty() {
+        padded_label = *b"           ";
+        padded_label[..label.len()].copy_from_slice(label.as_bytes());
+    }
+    boot[71..82].copy_from_slice(&padded_label);
+    boot[82..90].copy_from_slice(b"FAT32   ");
+    boot[510] = 0x55;
+    boot[511] = 0xAA;
+
+    // Free count and next free cluster unknown: drivers recompute them
+    let mut fsinfo = [0u8; SECTOR_SIZE];
+    fsinfo[0..4].copy_from_slice(&0x4161_5252u32.to_le_bytes());
+    fsinfo[484..488].copy_from_slice(&0x6141_7272u32.to_le_bytes());
+    fsinfo[488..496].fill(0xFF);
+    fsinfo[508..512].copy_from_slice(&0xAA55_0000u32.to_le_bytes());
+
+    let mut first_fat_sector = [0u8; SECTOR_SIZE];
+    first_fat_sector[0..4].copy_from_slice(&(0x0FFF_FF00 | MEDIA_FIXED as u32).to_le_bytes());
+    first_fat_sector[4..8].copy_from_slice(&FAT_EOC.to_le_bytes());
+    first_fat_sector[8..12].copy_from_slice(&FAT_EOC.to_le_bytes()); // the root directory
+    let zero = [0u8; SECTOR_SIZE];
+
+    let write = |lba: u32, sector: &[u8; SECTOR_SIZE]| {
+        dev.write_block(lba as u64, sector).map_err(|_| FsError::IoError)
+    };
+    for lba in 0..data_start + spc {
+        let sector = match lba {
+            0 | BACKUP_BOOT_SECTOR => &boot,
+            FSINFO_SECTOR => &fsinfo,
+            _ if lba >= RESERVED_SECTORS && lba < data_start && (lba - RESERVED_SECTORS) % fat_size == 0 => &first_fat_sector,
+            _ => &zero,
+        };
+        write(lba, sector)?;
+    }
+    dev.flush().map_err(|_| FsError::IoError)
+}
+
+/// A `sectors`-sector in-memory device holding a freshly formatted volume.
+pub fn mem_volume(sectors: u64, sectors_per_cluster: u8, label: &str) -> FsResult<MemBlockDevice> {
+    let dev = MemBlockDevice::new(SECTOR_SIZE, sectors);
+    format(&dev, sectors_per_cluster, label)?;
+    Ok(dev)
+}
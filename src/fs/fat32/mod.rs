@@ -1,3 +1,4 @@
 pub mod fat32;
+pub mod format;
 
 pub use fat32::Fat32Fs;
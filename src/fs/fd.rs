@@ -1,13 +1,57 @@
 use alloc::sync::Arc;
 use spin::Mutex;
 use alloc::format;
-use crate::fs::pipe::PipeInner;
+use crate::fs::error::{FsError, FsResult};
+use crate::fs::pipe::Pipe;
+
+/// `whence` values for SYS_LSEEK (Linux values).
+pub const SEEK_SET: u64 = 0;
+pub const SEEK_CUR: u64 = 1;
+pub const SEEK_END: u64 = 2;
+
+/// Access modes and flags for SYS_OPEN (Linux values).
+pub const O_RDONLY: u64  = 0;
+pub const O_WRONLY: u64  = 1;
+pub const O_RDWR: u64    = 2;
+pub const O_ACCMODE: u64 = 3;
+pub const O_CREAT: u64   = 0x40;
+pub const O_TRUNC: u64   = 0x200;
+pub const O_APPEND: u64  = 0x400;
+
+/// Where `File::seek` measures from.
+#[derive(Debug, Clone, Copy)]
+pub enum SeekFrom {
+    Start(u64),
+    Current(i64),
+    End(i64),
+}
+
+impl SeekFrom {
+    /// Decode an lseek `(offset, whence)` pair.
+    pub fn from_whence(offset: i64, whence: u64) -> Option<Self> {
+        match whence {
+            SEEK_SET => u64::try_from(offset).ok().map(SeekFrom::Start),
+            SEEK_CUR => Some(SeekFrom::Current(offset)),
+            SEEK_END => Some(SeekFrom::End(offset)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeekError {
+    /// Pipes and the console have no position (ESPIPE).
+    NotSeekable,
+    /// The new offset would be negative or overflow (EINVAL).
+    InvalidOffset,
+    Fs(FsError),
+}
 
 pub enum FileType {
     Regular,
     Directory,
-    PipeRead(Arc<Mutex<PipeInner>>),
-    PipeWrite(Arc<Mutex<PipeInner>>),
+    PipeRead(Arc<Pipe>),
+    PipeWrite(Arc<Pipe>),
     Console,
 }
 
@@ -17,6 +61,8 @@ pub struct File {
     pub offset: u64,
     pub readable: bool,
     pub writable: bool,
+    /// Every write goes to the current end of file (O_APPEND).
+    pub append: bool,
 }
 
 impl File {
@@ -27,6 +73,7 @@ impl File {
             offset: 0,
             readable: true,
             writable: true,
+            append: false,
         }))
     }
 
@@ -37,8 +84,142 @@ impl File {
             offset: 0,
             readable,
             writable,
+            append: false,
+        }))
+    }
+
+    /// Both ends of a new pipe: (read end, write end).
+    pub fn new_pipe() -> (Arc<Mutex<Self>>, Arc<Mutex<Self>>) {
+        let inner = Pipe::new();
+
+        // Pipe initially has 1 reader and 1 writer
+        inner.lock().add_reader();
+        inner.lock().add_writer();
+
+        let read_file = Arc::new(Mutex::new(File {
+            file_type: FileType::PipeRead(inner.clone()),
+            path: alloc::string::String::from("pipe"),
+            offset: 0,
+            readable: true,
+            writable: false,
+            append: false,
+        }));
+        let write_file = Arc::new(Mutex::new(File {
+            file_type: FileType::PipeWrite(inner),
+            path: alloc::string::String::from("pipe"),
+            offset: 0,
+            readable: false,
+            writable: true,
+            append: false,
+        }));
+        (read_file, write_file)
+    }
+
+    /// Directory handles are read-only; `offset` is the index of the next
+    /// entry handed out by `SYS_GETDENTS`.
+    pub fn new_directory(path: &str) -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(File {
+            file_type: FileType::Directory,
+            path: alloc::string::String::from(path),
+            offset: 0,
+            readable: true,
+            writable: false,
+            append: false,
         }))
     }
+
+    /// Open `path` according to the SYS_OPEN `flags`. With O_CREAT a missing
+    /// file is created; O_TRUNC empties a file opened for writing; O_APPEND
+    /// starts at the end of file and keeps every write there. Directories
+    /// can only be opened read-only. The access asked for must be allowed
+    /// by the file's permission bits (`PermissionDenied` otherwise).
+    pub fn open(path: &str, flags: u64) -> FsResult<Arc<Mutex<Self>>> {
+        let (readable, writable) = match flags & O_ACCMODE {
+            O_RDONLY => (true, false),
+            O_WRONLY => (false, true),
+            O_RDWR => (true, true),
+            _ => return Err(FsError::InvalidPath),
+        };
+
+        let mut vfs = crate::fs::VFS.lock();
+        let mut inode = match vfs.lookup(path) {
+            Ok(inode) => inode,
+            Err(FsError::NotFound) if flags & O_CREAT != 0 => vfs.create(path)?,
+            Err(e) => return Err(e),
+        };
+
+        if (readable && !inode.readable()) || (writable && !inode.writable()) {
+            return Err(FsError::PermissionDenied);
+        }
+
+        if inode.file_type == crate::fs::inode::FileType::Directory {
+            if writable {
+                return Err(FsError::IsADirectory);
+            }
+            return Ok(Self::new_directory(path));
+        }
+
+        if flags & O_TRUNC != 0 && writable && inode.size > 0 {
+            vfs.truncate(path, 0)?;
+            inode.size = 0;
+        }
+
+        let append = flags & O_APPEND != 0;
+        Ok(Arc::new(Mutex::new(File {
+            file_type: FileType::Regular,
+            path: alloc::string::String::from(path),
+            offset: if append { inode.size as u64 } else { 0 },
+            readable,
+            writable,
+            append,
+        })))
+    }
+
+    /// Read from a regular file at `offset`, advancing it past the bytes
+    /// read. Returns 0 at or beyond end of file.
+    pub fn read(&mut self, buf: &mut [u8]) -> FsResult<usize> {
+        if !matches!(self.file_type, FileType::Regular) {
+            return Err(FsError::NotSupported);
+        }
+        let n = crate::fs::VFS.lock().read_file(&self.path, self.offset as usize, buf)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+
+    /// Write to a regular file at `offset`, advancing it past the bytes
+    /// written. After a seek beyond the end, the gap reads back as zeros.
+    /// In append mode the offset first moves to the end of file.
+    pub fn write(&mut self, data: &[u8]) -> FsResult<usize> {
+        if !matches!(self.file_type, FileType::Regular) {
+            return Err(FsError::NotSupported);
+        }
+        let mut vfs = crate::fs::VFS.lock();
+        if self.append {
+            self.offset = vfs.lookup(&self.path)?.size as u64;
+        }
+        let n = vfs.write_at(&self.path, self.offset as usize, data)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+
+    /// Move `offset` and return its new value. Seeking past the end is
+    /// allowed. For directories the offset counts entries, so `End` is invalid.
+    pub fn seek(&mut self, pos: SeekFrom) -> Result<u64, SeekError> {
+        let new = match (&self.file_type, pos) {
+            (FileType::Regular | FileType::Directory, SeekFrom::Start(n)) => Some(n),
+            (FileType::Regular | FileType::Directory, SeekFrom::Current(d)) => self.offset.checked_add_signed(d),
+            (FileType::Regular, SeekFrom::End(d)) => {
+                let size = crate::fs::VFS.lock().lookup(&self.path).map_err(SeekError::Fs)?.size;
+                (size as u64).checked_add_signed(d)
+            }
+            (FileType::Directory, SeekFrom::End(_)) => None,
+            _ => return Err(SeekError::NotSeekable),
+        };
+        // Offsets are handed to filesystems as usize and must stay positive as i64
+        let new = new.filter(|&n| n <= i64::MAX as u64).ok_or(SeekError::InvalidOffset)?;
+        self.offset = new;
+        Ok(new)
+    }
 }
 
 impl Drop for File {
@@ -47,12 +228,8 @@ impl Drop for File {
         // We must decrement the inner pipe's writer/reader counts to notify the
         // other side of the Pipe that this endpoint is closed!
         match &self.file_type {
-            FileType::PipeRead(inner) => {
-                inner.lock().drop_reader();
-            },
-            FileType::PipeWrite(inner) => {
-                inner.lock().drop_writer();
-            },
+            FileType::PipeRead(inner) => inner.close_reader(),
+            FileType::PipeWrite(inner) => inner.close_writer(),
             _ => {}
         }
     }
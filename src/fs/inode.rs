@@ -1,9 +1,11 @@
-/// Inode represents a filesystem node (file or directory).
+/// Inode represents a filesystem node (file, directory or symbolic link).
 #[derive(Debug, Clone)]
 pub struct Inode {
     pub id: u64,
     pub file_type: FileType,
     pub size: usize,
+    /// Permission bits, `rwxrwxrwx` as in Unix (e.g. 0o644).
+    pub mode: u16,
 }
 
 /// Type of filesystem node.
@@ -11,4 +13,30 @@ pub struct Inode {
 pub enum FileType {
     File,
     Directory,
+    Symlink,
+}
+
+/// Permission bits of a newly created file.
+pub const DEFAULT_FILE_MODE: u16 = 0o644;
+/// Permission bits of a newly created directory.
+pub const DEFAULT_DIR_MODE: u16 = 0o755;
+/// Permission bits of a symbolic link; its target's are the ones checked.
+pub const SYMLINK_MODE: u16 = 0o777;
+/// All the permission bits `chmod` can set.
+pub const MODE_MASK: u16 = 0o777;
+
+/// Owner read permission. There is a single user for now, so the owner
+/// bits are the ones enforced.
+pub const MODE_READ: u16 = 0o400;
+/// Owner write permission.
+pub const MODE_WRITE: u16 = 0o200;
+
+impl Inode {
+    pub fn readable(&self) -> bool {
+        self.mode & MODE_READ != 0
+    }
+
+    pub fn writable(&self) -> bool {
+        self.mode & MODE_WRITE != 0
+    }
 }
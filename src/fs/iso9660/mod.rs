@@ -0,0 +1,323 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use spin::Mutex;
+
+use crate::drivers::ata::pio::{AtaDevice, DeviceClass, ATAPI_BLOCK_SIZE};
+use super::dentry::DirEntry;
+use super::error::{FsError, FsResult};
+use super::inode::{FileType, Inode};
+use super::mount::FileSystem;
+
+// ══════════════════════════════════════════════════════════════
+//  ISO9660 (ECMA-119) — read-only CD filesystem
+// ══════════════════════════════════════════════════════════════
+
+/// Logical block size. ECMA-119 allows others, but every CD uses 2048.
+pub const BLOCK_SIZE: usize = ATAPI_BLOCK_SIZE;
+
+/// The first volume descriptor follows a 16-block system area.
+const FIRST_DESCRIPTOR_LBA: u32 = 16;
+/// Give up looking for the primary descriptor after this many.
+const MAX_DESCRIPTORS: u32 = 32;
+
+const DESC_PRIMARY: u8 = 1;
+const DESC_TERMINATOR: u8 = 255;
+const STANDARD_ID: &[u8; 5] = b"CD001";
+
+/// Directory record flag: the entry is a directory.
+const FLAG_DIRECTORY: u8 = 0x02;
+/// Rock Ridge NM flag: the name continues in the next NM entry.
+const NM_CONTINUE: u8 = 0x01;
+
+// ── Block sources ───────────────────────────────────────────
+
+/// Where an image's 2048-byte blocks come from.
+pub trait BlockSource: Send + Sync {
+    fn read_block(&self, lba: u32, buf: &mut [u8; BLOCK_SIZE]) -> FsResult<()>;
+}
+
+/// An image on an ATA bus device: read natively from an ATAPI drive, or as
+/// four 512-byte sectors from an ATA disk holding a raw ISO image.
+pub struct DriveSource(pub &'static Mutex<AtaDevice>);
+
+impl BlockSource for DriveSource {
+    fn read_block(&self, lba: u32, buf: &mut [u8; BLOCK_SIZE]) -> FsResult<()> {
+        let dev = self.0.lock();
+        match dev.class {
+            DeviceClass::Atapi => dev.read_atapi_block(lba, buf).map_err(|_| FsError::IoError),
+            DeviceClass::Ata => {
+                let sector = lba.checked_mul((BLOCK_SIZE / 512) as u32).ok_or(FsError::IoError)?;
+                dev.read_sectors(sector as u64, buf).map_err(|_| FsError::IoError)
+            }
+            DeviceClass::None => Err(FsError::IoError),
+        }
+    }
+}
+
+/// An image held in memory. Only the blocks that were set are stored;
+/// the rest read as zeros, so the empty system area costs nothing.
+pub struct MemImage {
+    blocks: Vec<(u32, Box<[u8; BLOCK_SIZE]>)>,
+}
+
+impl MemImage {
+    pub fn new() -> Self {
+        MemImage { blocks: Vec::new() }
+    }
+
+    pub fn set_block(&mut self, lba: u32, data: &[u8]) {
+        let mut block = Box::new([0u8; BLOCK_SIZE]);
+        let n = data.len().min(BLOCK_SIZE);
+        block[..n].copy_from_slice(&data[..n]);
+        match self.blocks.iter_mut().find(|(l, _)| *l == lba) {
+            Some((_, b)) => *b = block,
+            None => self.blocks.push((lba, block)),
+        }
+    }
+}
+
+impl BlockSource for MemImage {
+    fn read_block(&self, lba: u32, buf: &mut [u8; BLOCK_SIZE]) -> FsResult<()> {
+        match self.blocks.iter().find(|(l, _)| *l == lba) {
+            Some((_, b)) => buf.copy_from_slice(&b[..]),
+            None => buf.fill(0),
+        }
+        Ok(())
+    }
+}
+
+// ── Directory records ───────────────────────────────────────
+
+#[derive(Debug, Clone)]
+struct DirRecord {
+    extent: u32,
+    size: u32,
+    is_dir: bool,
+    /// Name with the ";1" version and any trailing '.' removed, or the
+    /// Rock Ridge name if the record carries one.
+    name: String,
+}
+
+impl DirRecord {
+    /// Parse the record at the start of `rec` (whose length is `rec[0]`).
+    /// Returns None for "." / ".." and malformed records.
+    fn parse(rec: &[u8]) -> Option<Self> {
+        let len = *rec.first()? as usize;
+        if len < 34 || len > rec.len() {
+            return None;
+        }
+        let name_len = rec[32] as usize;
+        if 33 + name_len > len {
+            return None;
+        }
+        let raw_name = &rec[33..33 + name_len];
+        // Self and parent entries are a single 0x00 / 0x01 byte
+        if raw_name == [0] || raw_name == [1] {
+            return None;
+        }
+
+        let is_dir = rec[25] & FLAG_DIRECTORY != 0;
+        // System use area (Rock Ridge) starts after the name, padded to even
+        let su_start = 33 + name_len + (1 - name_len % 2);
+        let name = match rec.get(su_start..len).and_then(rock_ridge_name) {
+            Some(n) => n,
+            None => iso_name(raw_name, is_dir),
+        };
+        if name.is_empty() {
+            return None;
+        }
+
+        Some(DirRecord {
+            extent: u32::from_le_bytes([rec[2], rec[3], rec[4], rec[5]]),
+            size: u32::from_le_bytes([rec[10], rec[11], rec[12], rec[13]]),
+            is_dir,
+            name,
+        })
+    }
+
+    fn inode(&self) -> Inode {
+        Inode {
+            id: self.extent as u64,
+            file_type: if self.is_dir { FileType::Directory } else { FileType::File },
+            size: self.size as usize,
+            // Read-only medium
+            mode: if self.is_dir { 0o555 } else { 0o444 },
+        }
+    }
+
+    /// Case-insensitive, as on FAT: plain ISO names are upper-case
+    /// d-characters, and Rock Ridge names are compared the same way.
+    fn matches(&self, component: &str) -> bool {
+        self.name.eq_ignore_ascii_case(component)
+    }
+}
+
+/// "README.TXT;1" -> "README.TXT", "NOEXT.;1" -> "NOEXT".
+fn iso_name(raw: &[u8], is_dir: bool) -> String {
+    let mut name: String = raw.iter().map(|&b| b as char).collect();
+    if !is_dir {
+        if let Some(semi) = name.rfind(';') {
+            name.truncate(semi);
+        }
+        while name.ends_with('.') {
+            name.pop();
+        }
+    }
+    name
+}
+
+/// The alternate name from Rock Ridge "NM" entries in a system use area.
+fn rock_ridge_name(su: &[u8]) -> Option<String> {
+    let mut name = String::new();
+    let mut off = 0;
+    while off + 4 <= su.len() {
+        let len = su[off + 2] as usize;
+        if len < 4 || off + len > su.len() {
+            break;
+        }
+        if &su[off..off + 2] == b"NM" && len >= 5 {
+            let flags = su[off + 4];
+            name.extend(su[off + 5..off + len].iter().map(|&b| b as char));
+            if flags & NM_CONTINUE == 0 {
+                return Some(name);
+            }
+        }
+        off += len;
+    }
+    None
+}
+
+// ── Filesystem ──────────────────────────────────────────────
+
+/// A mounted ISO9660 volume. Reads only: the image is never modified.
+pub struct Iso9660Fs<S: BlockSource> {
+    source: S,
+    root: DirRecord,
+    volume_id: String,
+}
+
+impl<S: BlockSource> Iso9660Fs<S> {
+    /// Find the primary volume descriptor and its root directory.
+    pub fn new(source: S) -> FsResult<Self> {
+        let mut block = [0u8; BLOCK_SIZE];
+        for lba in FIRST_DESCRIPTOR_LBA..FIRST_DESCRIPTOR_LBA + MAX_DESCRIPTORS {
+            source.read_block(lba, &mut block)?;
+            if &block[1..6] != STANDARD_ID {
+                break;
+            }
+            match block[0] {
+                DESC_PRIMARY => {
+                    let block_size = u16::from_le_bytes([block[128], block[129]]) as usize;
+                    if block_size != BLOCK_SIZE {
+                        crate::log_warn!("ISO9660: unsupported logical block size {}", block_size);
+                        return Err(FsError::InvalidPath);
+                    }
+                    // The root's record is embedded at offset 156; it is "."
+                    // so parse its fields directly
+                    let rec = &block[156..190];
+                    let root = DirRecord {
+                        extent: u32::from_le_bytes([rec[2], rec[3], rec[4], rec[5]]),
+                        size: u32::from_le_bytes([rec[10], rec[11], rec[12], rec[13]]),
+                        is_dir: true,
+                        name: String::new(),
+                    };
+                    let volume_id = String::from(iso_name(&block[40..72], true).trim_end());
+                    return Ok(Iso9660Fs { source, root, volume_id });
+                }
+                DESC_TERMINATOR => break,
+                _ => {}
+            }
+        }
+        Err(FsError::NotFound)
+    }
+
+    pub fn volume_id(&self) -> &str {
+        &self.volume_id
+    }
+
+    /// Every record in directory `dir`. Records never span blocks; the rest
+    /// of a block after a zero length byte is padding.
+    fn read_dir(&self, dir: &DirRecord) -> FsResult<Vec<DirRecord>> {
+        let mut entries = Vec::new();
+        let mut block = [0u8; BLOCK_SIZE];
+        let blocks = (dir.size as usize).div_ceil(BLOCK_SIZE) as u32;
+        for i in 0..blocks {
+            self.source.read_block(dir.extent + i, &mut block)?;
+            let mut off = 0;
+            while off < BLOCK_SIZE && block[off] != 0 {
+                let len = block[off] as usize;
+                if let Some(rec) = DirRecord::parse(&block[off..]) {
+                    entries.push(rec);
+                }
+                off += len;
+            }
+        }
+        Ok(entries)
+    }
+
+    fn resolve(&self, path: &str) -> FsResult<DirRecord> {
+        let mut current = self.root.clone();
+        for component in path.split('/').filter(|c| !c.is_empty() && *c != ".") {
+            if !current.is_dir {
+                return Err(FsError::NotADirectory);
+            }
+            current = self.read_dir(&current)?
+                .into_iter()
+                .find(|r| r.matches(component))
+                .ok_or(FsError::NotFound)?;
+        }
+        Ok(current)
+    }
+}
+
+impl<S: BlockSource> FileSystem for Iso9660Fs<S> {
+    fn name(&self) -> &str {
+        "iso9660"
+    }
+
+    fn lookup(&self, path: &str) -> FsResult<Inode> {
+        Ok(self.resolve(path)?.inode())
+    }
+
+    fn read(&self, path: &str, offset: usize, buf: &mut [u8]) -> FsResult<usize> {
+        let rec = self.resolve(path)?;
+        if rec.is_dir {
+            return Err(FsError::IsADirectory);
+        }
+
+        let size = rec.size as usize;
+        if offset >= size {
+            return Ok(0);
+        }
+        let to_read = buf.len().min(size - offset);
+
+        // File data is one contiguous extent: start at the block holding `offset`
+        let mut block = [0u8; BLOCK_SIZE];
+        let mut done = 0;
+        while done < to_read {
+            let pos = offset + done;
+            self.source.read_block(rec.extent + (pos / BLOCK_SIZE) as u32, &mut block)?;
+            let from = pos % BLOCK_SIZE;
+            let n = (BLOCK_SIZE - from).min(to_read - done);
+            buf[done..done + n].copy_from_slice(&block[from..from + n]);
+            done += n;
+        }
+        Ok(done)
+    }
+
+    fn readdir(&self, path: &str) -> FsResult<Vec<DirEntry>> {
+        let dir = self.resolve(path)?;
+        if !dir.is_dir {
+            return Err(FsError::NotADirectory);
+        }
+        Ok(self.read_dir(&dir)?
+            .into_iter()
+            .map(|r| DirEntry { inode: r.inode(), name: r.name })
+            .collect())
+    }
+
+    fn sync(&self) -> FsResult<()> {
+        Ok(())
+    }
+}
@@ -8,17 +8,21 @@ pub mod pipe;
 pub mod fd;
 pub mod ramfs;
 pub mod fat32;
+pub mod devfs;
+pub mod iso9660;
 
-use spin::Mutex;
+use crate::scheduler::lock::InheritMutex;
 use lazy_static::lazy_static;
 use vfs::Vfs;
 
 lazy_static! {
-    pub static ref VFS: Mutex<Vfs> = Mutex::new(Vfs::new());
+    pub static ref VFS: InheritMutex<Vfs> = InheritMutex::new(Vfs::new());
 }
 
 // Static holder for the FAT32 filesystem instance (initialized at runtime)
 static mut FAT32_FS: Option<fat32::Fat32Fs> = None;
+// Same for the boot CD, if an ATAPI drive has a disc in it
+static mut CDROM_FS: Option<iso9660::Iso9660Fs<iso9660::DriveSource>> = None;
 
 /// Initialize the VFS with RAMFS at root.
 pub fn init() {
@@ -35,12 +39,15 @@ pub fn init() {
     drop(vfs);
     seed_default_files();
 
-    crate::log_info!("VFS initialized: ramfs at /, tmpfs at /tmp.");
+    // Mounted over the seeded /dev directory
+    VFS.lock().mount("/dev", &devfs::DEVFS_INSTANCE);
+
+    crate::log_info!("VFS initialized: ramfs at /, tmpfs at /tmp, devfs at /dev.");
 }
 
 /// Mount FAT32 from ATA disk. Must be called AFTER drivers::ata::init().
 pub fn mount_fat32() {
-    match fat32::Fat32Fs::init() {
+    match fat32::Fat32Fs::init(&*crate::drivers::ata::PRIMARY_ATA) {
         Ok(fs) => {
             unsafe {
                 FAT32_FS = Some(fs);
@@ -58,12 +65,91 @@ pub fn mount_fat32() {
     }
 }
 
+/// Mount the first ATAPI drive's ISO9660 disc at /cdrom. Must be called
+/// AFTER drivers::ata::init().
+pub fn mount_cdrom() {
+    let Some(drive) = crate::drivers::ata::first_atapi() else {
+        crate::log_info!("No ATAPI drive — /cdrom unavailable.");
+        return;
+    };
+    match iso9660::Iso9660Fs::new(iso9660::DriveSource(drive)) {
+        Ok(fs) => {
+            unsafe {
+                CDROM_FS = Some(fs);
+                if let Some(ref iso) = *core::ptr::addr_of!(CDROM_FS) {
+                    VFS.lock().mount_with("/cdrom", iso, true);
+                    crate::log_info!("ISO9660 volume '{}' mounted at /cdrom.", iso.volume_id());
+                }
+            }
+        }
+        Err(e) => {
+            crate::log_warn!("ISO9660 mount failed: {} — /cdrom unavailable.", e);
+        }
+    }
+}
+
+/// Flush all mounted filesystems to their devices. Call before anything
+/// that may cut power or reset the machine.
+pub fn sync_all() -> error::FsResult<()> {
+    VFS.lock().sync_all()
+}
+
+/// Flush all filesystems and unmount everything but the root, before the
+/// machine goes away.
+pub fn unmount_all() -> error::FsResult<()> {
+    VFS.lock().unmount_all()
+}
+
+/// The filesystem instance that boot mounts at `path`, for re-attaching it
+/// after `umount`. The root RAMFS is never unmounted, so it is not listed.
+pub fn standard_filesystem(path: &str) -> Option<&'static dyn mount::FileSystem> {
+    match path {
+        "/tmp" => Some(&*ramfs::TMPFS_INSTANCE),
+        "/dev" => Some(&devfs::DEVFS_INSTANCE),
+        "/disk" => fat32().map(|fs| fs as &'static dyn mount::FileSystem),
+        "/cdrom" => unsafe { (*core::ptr::addr_of!(CDROM_FS)).as_ref() }
+            .map(|fs| fs as &'static dyn mount::FileSystem),
+        _ => None,
+    }
+}
+
+/// Make `path` absolute against the working directory `cwd` (itself
+/// absolute) and normalize it: empty and `.` components are dropped, `..`
+/// removes the previous one and stops at `/`. Symbolic links are left alone.
+pub fn absolute_path(cwd: &str, path: &str) -> alloc::string::String {
+    let mut parts: alloc::vec::Vec<&str> = alloc::vec::Vec::new();
+    let base = if path.starts_with('/') { "" } else { cwd };
+    for segment in base.split('/').chain(path.split('/')) {
+        match segment {
+            "" | "." => {}
+            ".." => { parts.pop(); } // popping past root is a no-op
+            s => parts.push(s),
+        }
+    }
+
+    if parts.is_empty() {
+        return alloc::string::String::from("/");
+    }
+    let mut result = alloc::string::String::new();
+    for p in parts {
+        result.push('/');
+        result.push_str(p);
+    }
+    result
+}
+
+/// The mounted FAT32 volume, if `mount_fat32` succeeded.
+pub fn fat32() -> Option<&'static fat32::Fat32Fs> {
+    unsafe { (*core::ptr::addr_of!(FAT32_FS)).as_ref() }
+}
+
 fn seed_default_files() {
     use crate::fs::VFS;
     let mut vfs = VFS.lock();
     let _ = vfs.mkdir("/boot");
     let _ = vfs.mkdir("/etc");
     let _ = vfs.mkdir("/home");
+    let _ = vfs.mkdir("/dev");
     let _ = vfs.create("/README.md");
     let _ = vfs.write_file("/README.md", b"# AtomicOS\nA hobby x86_64 kernel written in Rust.\n");
     let _ = vfs.create("/BUILD.md");
@@ -1,20 +1,28 @@
 use alloc::string::String;
 use alloc::vec::Vec;
 use super::dentry::DirEntry;
-use super::error::FsResult;
+use super::error::{FsError, FsResult};
 use super::inode::Inode;
+use crate::drivers::rtc::DateTime;
 
 /// The FileSystem trait — every concrete filesystem must implement this.
 /// All paths passed to these methods are relative to the mount point.
+///
+/// Methods that modify the tree default to `FsError::NotSupported`, so a
+/// read-only filesystem only implements the read side.
 pub trait FileSystem: Send + Sync {
     /// Name of this filesystem (e.g. "ramfs", "fat32").
     fn name(&self) -> &str;
 
     /// Create a new regular file at `path`.
-    fn create(&self, path: &str) -> FsResult<Inode>;
+    fn create(&self, _path: &str) -> FsResult<Inode> {
+        Err(FsError::NotSupported)
+    }
 
     /// Create a new directory at `path`.
-    fn mkdir(&self, path: &str) -> FsResult<Inode>;
+    fn mkdir(&self, _path: &str) -> FsResult<Inode> {
+        Err(FsError::NotSupported)
+    }
 
     /// Look up an inode by path.
     fn lookup(&self, path: &str) -> FsResult<Inode>;
@@ -25,11 +33,62 @@ pub trait FileSystem: Send + Sync {
 
     /// Write `data` to file at `path`, starting at `offset`.
     /// Returns number of bytes written.
-    fn write(&self, path: &str, offset: usize, data: &[u8]) -> FsResult<usize>;
+    fn write(&self, _path: &str, _offset: usize, _data: &[u8]) -> FsResult<usize> {
+        Err(FsError::NotSupported)
+    }
 
     /// List entries in directory at `path`.
     fn readdir(&self, path: &str) -> FsResult<Vec<DirEntry>>;
 
     /// Remove a file or empty directory at `path`.
-    fn unlink(&self, path: &str) -> FsResult<()>;
+    fn unlink(&self, _path: &str) -> FsResult<()> {
+        Err(FsError::NotSupported)
+    }
+
+    /// Move the entry at `from` to `to`, both on this filesystem.
+    fn rename(&self, _from: &str, _to: &str) -> FsResult<()> {
+        Err(FsError::NotSupported)
+    }
+
+    /// Shrink or zero-extend the file at `path` to `len` bytes.
+    fn truncate(&self, _path: &str, _len: usize) -> FsResult<()> {
+        Err(FsError::NotSupported)
+    }
+
+    /// Create a symbolic link at `path` pointing to `target`.
+    /// The target is stored verbatim and is not required to exist.
+    fn symlink(&self, _target: &str, _path: &str) -> FsResult<Inode> {
+        Err(FsError::NotSupported)
+    }
+
+    /// Return the target of the symbolic link at `path`.
+    fn readlink(&self, _path: &str) -> FsResult<String> {
+        Err(FsError::NotSupported)
+    }
+
+    /// Permission bits of the entry at `path`.
+    fn get_mode(&self, path: &str) -> FsResult<u16> {
+        self.lookup(path).map(|inode| inode.mode)
+    }
+
+    /// Change the permission bits of the entry at `path`. Filesystems that
+    /// store fewer bits keep what they can.
+    fn set_mode(&self, _path: &str, _mode: u16) -> FsResult<()> {
+        Err(FsError::NotSupported)
+    }
+
+    /// Last modification time of the entry at `path`, on filesystems that
+    /// record one.
+    fn modified(&self, _path: &str) -> FsResult<DateTime> {
+        Err(FsError::NotSupported)
+    }
+
+    /// Record a change to the entry at `path` now, as `touch` does, on
+    /// filesystems that record modification times.
+    fn touch(&self, _path: &str) -> FsResult<()> {
+        Err(FsError::NotSupported)
+    }
+
+    /// Write any buffered data through to the backing device.
+    fn sync(&self) -> FsResult<()>;
 }
@@ -1,31 +1,135 @@
 use alloc::sync::Arc;
-use alloc::vec;
-use alloc::vec::Vec;
-use spin::Mutex;
-use crate::scheduler::ProcessId;
+use spin::{Mutex, MutexGuard};
+use crate::allocator::slab::{SlabBox, SlabCache};
+use crate::scheduler::wait::WaitQueue;
 
 const PIPE_BUFFER_SIZE: usize = 4096;
 
-/// Shared internal state of a Pipe representing the data conduit between processes.
+/// Ring buffers of closed pipes are reused by the next `pipe()`.
+static PIPE_BUFFERS: SlabCache<[u8; PIPE_BUFFER_SIZE]> = SlabCache::new("pipe_buffer");
+
+/// Buffer and end counts of a `Pipe`, behind its lock.
 pub struct PipeInner {
-    buffer: Vec<u8>,
+    buffer: SlabBox<[u8; PIPE_BUFFER_SIZE]>,
     read_pos: usize,
     write_pos: usize,
     readers: usize,
     writers: usize,
 }
 
-impl PipeInner {
-    pub fn new() -> Arc<Mutex<Self>> {
-        Arc::new(Mutex::new(PipeInner {
-            buffer: vec![0; PIPE_BUFFER_SIZE],
-            read_pos: 0,
-            write_pos: 0,
-            readers: 0,
-            writers: 0,
-        }))
+/// Why a blocking pipe transfer gave up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipeError {
+    /// Write with no reader left (EPIPE).
+    Broken,
+    /// A signal arrived while waiting (EINTR).
+    Interrupted,
+}
+
+/// A pipe shared by its read and write ends. Each side waits on its own
+/// queue, so a transfer wakes only the tasks blocked on the other end of
+/// this pipe rather than every blocked task. A task leaves the queue when
+/// its wait ends, including when a signal (such as the one killing it)
+/// cuts the wait short.
+pub struct Pipe {
+    inner: Mutex<PipeInner>,
+    /// Readers waiting for data or for the last writer to close.
+    readers_waiting: WaitQueue,
+    /// Writers waiting for space or for the last reader to close.
+    writers_waiting: WaitQueue,
+}
+
+impl Pipe {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Pipe {
+            inner: Mutex::new(PipeInner {
+                buffer: unsafe { PIPE_BUFFERS.alloc_zeroed() },
+                read_pos: 0,
+                write_pos: 0,
+                readers: 0,
+                writers: 0,
+            }),
+            readers_waiting: WaitQueue::new(),
+            writers_waiting: WaitQueue::new(),
+        })
     }
 
+    pub fn lock(&self) -> MutexGuard<'_, PipeInner> {
+        self.inner.lock()
+    }
+
+    /// Read up to `buf.len()` bytes, blocking while the pipe is empty and
+    /// a writer is left. Returns 0 at end of file.
+    pub fn read(&self, buf: &mut [u8]) -> Result<usize, PipeError> {
+        loop {
+            {
+                let mut inner = self.inner.lock();
+                if !inner.is_empty() {
+                    let n = inner.read(buf);
+                    drop(inner);
+                    self.writers_waiting.wake_all();
+                    return Ok(n);
+                }
+                if inner.active_writers() == 0 {
+                    return Ok(0);
+                }
+            }
+            // The pipe lock may belong to a preempted task: if it's busy,
+            // go round again rather than spin with interrupts off
+            let woken = self.readers_waiting.wait_until(|| {
+                self.inner.try_lock().map_or(true, |p| !p.is_empty() || p.active_writers() == 0)
+            });
+            if !woken {
+                return Err(PipeError::Interrupted);
+            }
+        }
+    }
+
+    /// Write up to `buf.len()` bytes, blocking while the pipe is full and
+    /// a reader is left. Returns how many bytes fit.
+    pub fn write(&self, buf: &[u8]) -> Result<usize, PipeError> {
+        loop {
+            {
+                let mut inner = self.inner.lock();
+                if inner.active_readers() == 0 {
+                    return Err(PipeError::Broken);
+                }
+                if !inner.is_full() {
+                    let n = inner.write(buf);
+                    drop(inner);
+                    self.readers_waiting.wake_all();
+                    return Ok(n);
+                }
+            }
+            let woken = self.writers_waiting.wait_until(|| {
+                self.inner.try_lock().map_or(true, |p| !p.is_full() || p.active_readers() == 0)
+            });
+            if !woken {
+                return Err(PipeError::Interrupted);
+            }
+        }
+    }
+
+    /// Tasks blocked reading this pipe.
+    pub fn waiting_readers(&self) -> usize {
+        self.readers_waiting.waiting()
+    }
+
+    /// A read end closed; with the last one gone, blocked writers fail.
+    pub fn close_reader(&self) {
+        self.inner.lock().drop_reader();
+        self.writers_waiting.wake_all();
+    }
+
+    /// A write end closed; with the last one gone, blocked readers see EOF.
+    pub fn close_writer(&self) {
+        self.inner.lock().drop_writer();
+        self.readers_waiting.wake_all();
+    }
+}
+
+impl PipeInner {
+
     pub fn add_reader(&mut self) {
         self.readers += 1;
     }
@@ -5,7 +5,7 @@ use lazy_static::lazy_static;
 
 use super::dentry::DirEntry;
 use super::error::{FsError, FsResult};
-use super::inode::{FileType, Inode};
+use super::inode::{FileType, Inode, DEFAULT_DIR_MODE, DEFAULT_FILE_MODE, SYMLINK_MODE};
 use super::mount::FileSystem;
 
 // ──────────────────────────────────────────────────────────────
@@ -19,13 +19,14 @@ struct RamNode {
     file_type: FileType,
     parent: Option<u64>,       // inode id of parent (None for root)
     children: Vec<u64>,        // inode ids of children (dirs only)
-    data: Vec<u8>,             // file content (files only)
+    data: Vec<u8>,             // file content (files) or link target (symlinks)
+    mode: u16,                 // permission bits
 }
 
 impl RamNode {
     fn size(&self) -> usize {
         match self.file_type {
-            FileType::File => self.data.len(),
+            FileType::File | FileType::Symlink => self.data.len(),
             FileType::Directory => self.children.len(),
         }
     }
@@ -35,6 +36,7 @@ impl RamNode {
             id: self.id,
             file_type: self.file_type,
             size: self.size(),
+            mode: self.mode,
         }
     }
 }
@@ -59,6 +61,7 @@ impl RamFsInner {
             parent: None,
             children: Vec::new(),
             data: Vec::new(),
+            mode: DEFAULT_DIR_MODE,
         };
         RamFsInner {
             nodes: alloc::vec![root],
@@ -150,6 +153,77 @@ impl RamFsInner {
         Ok((parent_id, String::from(child_name)))
     }
 
+    /// Find the child of directory `parent_id` called `name`.
+    fn find_child(&self, parent_id: u64, name: &str) -> Option<u64> {
+        let pidx = self.find_by_id(parent_id)?;
+        self.nodes[pidx].children.iter().copied().find(|&cid| {
+            self.find_by_id(cid).is_some_and(|ci| self.nodes[ci].name == name)
+        })
+    }
+
+    /// Relink node `from` under `to`, replacing a file or empty directory
+    /// already there. Nothing is copied: the node keeps its id and contents,
+    /// and a directory takes its children along.
+    fn rename(&mut self, from: &str, to: &str) -> FsResult<()> {
+        let id = self.resolve_path(from)?;
+        if id == 0 {
+            return Err(FsError::InvalidPath);
+        }
+
+        let to = to.trim_end_matches('/');
+        let last_slash = to.rfind('/').ok_or(FsError::InvalidPath)?;
+        let parent_path = if last_slash == 0 { "/" } else { &to[..last_slash] };
+        let name = &to[last_slash + 1..];
+        if name.is_empty() || name == "." || name == ".." {
+            return Err(FsError::InvalidPath);
+        }
+        let new_parent = self.resolve_path(parent_path)?;
+        let pidx = self.find_by_id(new_parent).ok_or(FsError::NotFound)?;
+        if self.nodes[pidx].file_type != FileType::Directory {
+            return Err(FsError::NotADirectory);
+        }
+
+        // A directory can't become its own descendant
+        let mut ancestor = Some(new_parent);
+        while let Some(a) = ancestor {
+            if a == id {
+                return Err(FsError::InvalidPath);
+            }
+            ancestor = self.find_by_id(a).and_then(|i| self.nodes[i].parent);
+        }
+
+        let idx = self.find_by_id(id).ok_or(FsError::NotFound)?;
+        if let Some(existing) = self.find_child(new_parent, name) {
+            if existing == id {
+                return Ok(());
+            }
+            let eidx = self.find_by_id(existing).ok_or(FsError::NotFound)?;
+            let is_dir = self.nodes[idx].file_type == FileType::Directory;
+            let target = &self.nodes[eidx];
+            match (is_dir, target.file_type == FileType::Directory) {
+                (true, false) => return Err(FsError::NotADirectory),
+                (false, true) => return Err(FsError::IsADirectory),
+                (true, true) if !target.children.is_empty() => return Err(FsError::IsADirectory),
+                _ => {}
+            }
+            self.nodes[pidx].children.retain(|&c| c != existing);
+            self.nodes.remove(eidx);
+        }
+
+        // Indices may have shifted with the removal
+        let idx = self.find_by_id(id).ok_or(FsError::NotFound)?;
+        let old_parent = self.nodes[idx].parent.ok_or(FsError::InvalidPath)?;
+        let opidx = self.find_by_id(old_parent).ok_or(FsError::NotFound)?;
+        self.nodes[opidx].children.retain(|&c| c != id);
+        let pidx = self.find_by_id(new_parent).ok_or(FsError::NotFound)?;
+        self.nodes[pidx].children.push(id);
+
+        let node = &mut self.nodes[idx];
+        node.parent = Some(new_parent);
+        node.name = String::from(name);
+        Ok(())
+    }
+
     /// Insert a new node as a child of parent_id.
     fn insert_node(&mut self, parent_id: u64, name: String, ft: FileType) -> FsResult<Inode> {
         let id = self.alloc_id();
@@ -160,6 +234,11 @@ impl RamFsInner {
             parent: Some(parent_id),
             children: Vec::new(),
             data: Vec::new(),
+            mode: match ft {
+                FileType::File => DEFAULT_FILE_MODE,
+                FileType::Directory => DEFAULT_DIR_MODE,
+                FileType::Symlink => SYMLINK_MODE,
+            },
         };
         let inode = node.to_inode();
         self.nodes.push(node);
@@ -277,6 +356,24 @@ impl FileSystem for RamFs {
         Ok(data.len())
     }
 
+    fn truncate(&self, path: &str, len: usize) -> FsResult<()> {
+        let path = Self::normalize(path);
+        let mut inner = self.inner.lock();
+        let id = inner.resolve_path(&path)?;
+        let idx = inner.find_by_id(id).ok_or(FsError::NotFound)?;
+        let node = &mut inner.nodes[idx];
+
+        if node.file_type == FileType::Directory {
+            return Err(FsError::IsADirectory);
+        }
+        node.data.resize(len, 0);
+        // Give the memory back when a file shrinks a lot
+        if node.data.capacity() > 2 * len.max(64) {
+            node.data.shrink_to_fit();
+        }
+        Ok(())
+    }
+
     fn readdir(&self, path: &str) -> FsResult<Vec<DirEntry>> {
         let path = Self::normalize(path);
         let inner = self.inner.lock();
@@ -330,6 +427,53 @@ impl FileSystem for RamFs {
 
         Ok(())
     }
+
+    fn rename(&self, from: &str, to: &str) -> FsResult<()> {
+        let from = Self::normalize(from);
+        let to = Self::normalize(to);
+        self.inner.lock().rename(&from, &to)
+    }
+
+    fn symlink(&self, target: &str, path: &str) -> FsResult<Inode> {
+        if target.is_empty() {
+            return Err(FsError::InvalidPath);
+        }
+        let path = Self::normalize(path);
+        let mut inner = self.inner.lock();
+        let (parent_id, name) = inner.resolve_parent(&path)?;
+        let inode = inner.insert_node(parent_id, name, FileType::Symlink)?;
+
+        let idx = inner.find_by_id(inode.id).ok_or(FsError::NotFound)?;
+        inner.nodes[idx].data.extend_from_slice(target.as_bytes());
+        Ok(inner.nodes[idx].to_inode())
+    }
+
+    fn readlink(&self, path: &str) -> FsResult<String> {
+        let path = Self::normalize(path);
+        let inner = self.inner.lock();
+        let id = inner.resolve_path(&path)?;
+        let idx = inner.find_by_id(id).ok_or(FsError::NotFound)?;
+        let node = &inner.nodes[idx];
+
+        if node.file_type != FileType::Symlink {
+            return Err(FsError::InvalidPath);
+        }
+        Ok(String::from_utf8_lossy(&node.data).into_owned())
+    }
+
+    fn set_mode(&self, path: &str, mode: u16) -> FsResult<()> {
+        let path = Self::normalize(path);
+        let mut inner = self.inner.lock();
+        let id = inner.resolve_path(&path)?;
+        let idx = inner.find_by_id(id).ok_or(FsError::NotFound)?;
+        inner.nodes[idx].mode = mode;
+        Ok(())
+    }
+
+    fn sync(&self) -> FsResult<()> {
+        // Everything lives in memory already
+        Ok(())
+    }
 }
 
 // ──────────────────────────────────────────────────────────────
@@ -2,13 +2,26 @@ use alloc::string::String;
 use alloc::vec::Vec;
 use super::dentry::DirEntry;
 use super::error::{FsError, FsResult};
-use super::inode::Inode;
+use super::inode::{FileType, Inode};
 use super::mount::FileSystem;
 
+/// Maximum number of symbolic links followed while resolving one path.
+const MAX_SYMLINK_HOPS: usize = 40;
+
 /// A mount point associates a path prefix with a concrete filesystem.
 struct MountPoint {
     path: String,
     fs: &'static dyn FileSystem,
+    /// Reject every modifying call before it reaches `fs`.
+    read_only: bool,
+}
+
+/// A mount as listed by `Vfs::mounts`.
+#[derive(Debug, Clone)]
+pub struct MountInfo {
+    pub path: String,
+    pub fs_name: &'static str,
+    pub read_only: bool,
 }
 
 /// The Virtual File System — resolves paths to mount points and delegates.
@@ -21,19 +34,94 @@ impl Vfs {
         Vfs { mounts: Vec::new() }
     }
 
-    /// Mount a filesystem at the given path.
+    /// Mount a filesystem read-write at the given path.
     pub fn mount(&mut self, path: &str, fs: &'static dyn FileSystem) {
+        self.mount_with(path, fs, false);
+    }
+
+    /// Mount a filesystem at the given path, read-only if `read_only`.
+    pub fn mount_with(&mut self, path: &str, fs: &'static dyn FileSystem, read_only: bool) {
         self.mounts.push(MountPoint {
             path: String::from(path),
             fs,
+            read_only,
         });
         // Sort by path length descending so longer prefixes match first
         self.mounts.sort_by(|a, b| b.path.len().cmp(&a.path.len()));
     }
 
+    /// Flush and detach the filesystem mounted exactly at `path`.
+    /// The root mount cannot be removed.
+    pub fn unmount(&mut self, path: &str) -> FsResult<()> {
+        if path == "/" {
+            return Err(FsError::InvalidPath);
+        }
+        let idx = self.mounts.iter().position(|mp| mp.path == path).ok_or(FsError::NotMounted)?;
+        self.mounts[idx].fs.sync()?;
+        self.mounts.remove(idx);
+        Ok(())
+    }
+
+    /// Flush every filesystem and detach all but the root mount, for
+    /// shutdown. Mounts are detached even if their flush fails; the first
+    /// error is reported.
+    pub fn unmount_all(&mut self) -> FsResult<()> {
+        let result = self.sync_all();
+        self.mounts.retain(|mp| mp.path == "/");
+        result
+    }
+
+    /// Change the read-only flag of the mount exactly at `path`. Data already
+    /// buffered is flushed before the mount goes read-only.
+    pub fn remount(&mut self, path: &str, read_only: bool) -> FsResult<()> {
+        let mp = self.mounts.iter_mut().find(|mp| mp.path == path).ok_or(FsError::NotMounted)?;
+        if read_only && !mp.read_only {
+            mp.fs.sync()?;
+        }
+        mp.read_only = read_only;
+        Ok(())
+    }
+
+    /// Flush every mounted filesystem. Keeps going past failures and
+    /// reports the first error.
+    pub fn sync_all(&self) -> FsResult<()> {
+        let mut result = Ok(());
+        for mp in &self.mounts {
+            if let Err(e) = mp.fs.sync() {
+                crate::log_error!("sync: {} at {} failed: {}", mp.fs.name(), mp.path, e);
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Mount points and the filesystem serving each, longest path first.
+    pub fn mounts(&self) -> Vec<MountInfo> {
+        self.mounts.iter()
+            .map(|mp| MountInfo { path: mp.path.clone(), fs_name: mp.fs.name(), read_only: mp.read_only })
+            .collect()
+    }
+
     /// Resolve which mount point handles a given absolute path.
     /// Returns (filesystem, path relative to mount point).
     fn resolve(&self, abs_path: &str) -> FsResult<(&dyn FileSystem, String)> {
+        let (mp, relative) = self.resolve_mount(abs_path)?;
+        Ok((mp.fs, relative))
+    }
+
+    /// Like `resolve`, for operations that modify the filesystem: fails with
+    /// `ReadOnly` on a read-only mount without calling into it.
+    fn resolve_writable(&self, abs_path: &str) -> FsResult<(&dyn FileSystem, String)> {
+        let (mp, relative) = self.resolve_mount(abs_path)?;
+        if mp.read_only {
+            return Err(FsError::ReadOnly);
+        }
+        Ok((mp.fs, relative))
+    }
+
+    fn resolve_mount(&self, abs_path: &str) -> FsResult<(&MountPoint, String)> {
         for mp in &self.mounts {
             if abs_path == mp.path || abs_path.starts_with(&alloc::format!("{}/", mp.path.trim_end_matches('/'))) || mp.path == "/" {
                 let relative = if mp.path == "/" {
@@ -46,49 +134,189 @@ impl Vfs {
                         String::from(stripped)
                     }
                 };
-                return Ok((mp.fs, relative));
+                return Ok((mp, relative));
             }
         }
         Err(FsError::NotMounted)
     }
 
+    /// Walk `path` component by component, substituting symbolic link targets.
+    /// Returns an absolute path containing no symlinks, except for the final
+    /// component when `follow_final` is false. The final component need not exist.
+    fn walk(&self, path: &str, follow_final: bool) -> FsResult<String> {
+        // Components still to visit, stored in reverse so `pop` yields the next one
+        let mut pending: Vec<String> = path.split('/')
+            .filter(|c| !c.is_empty())
+            .rev()
+            .map(String::from)
+            .collect();
+        let mut resolved: Vec<String> = Vec::new();
+        let mut hops = 0;
+
+        while let Some(comp) = pending.pop() {
+            match comp.as_str() {
+                "." => continue,
+                ".." => { resolved.pop(); continue; }
+                _ => resolved.push(comp),
+            }
+
+            let is_last = pending.is_empty();
+            if is_last && !follow_final {
+                break;
+            }
+
+            let current = join_components(&resolved);
+            let (fs, rel) = self.resolve(&current)?;
+            match fs.lookup(&rel) {
+                Ok(inode) if inode.file_type == FileType::Symlink => {
+                    hops += 1;
+                    if hops > MAX_SYMLINK_HOPS {
+                        return Err(FsError::TooManyLinks);
+                    }
+                    let target = fs.readlink(&rel)?;
+
+                    // Relative targets are interpreted from the link's directory
+                    resolved.pop();
+                    if target.starts_with('/') {
+                        resolved.clear();
+                    }
+                    pending.extend(target.split('/').filter(|c| !c.is_empty()).rev().map(String::from));
+                }
+                Ok(_) => {}
+                Err(FsError::NotFound) if is_last => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(join_components(&resolved))
+    }
+
     // ---- VFS public API (delegates to resolved filesystem) ----
 
     pub fn create(&mut self, path: &str) -> FsResult<Inode> {
-        let (fs, rel) = self.resolve(path)?;
+        let path = self.walk(path, false)?;
+        let (fs, rel) = self.resolve_writable(&path)?;
         fs.create(&rel)
     }
 
     pub fn mkdir(&mut self, path: &str) -> FsResult<Inode> {
-        let (fs, rel) = self.resolve(path)?;
+        let path = self.walk(path, false)?;
+        let (fs, rel) = self.resolve_writable(&path)?;
         fs.mkdir(&rel)
     }
 
+    /// Look up an inode, following symbolic links (including the final component).
     pub fn lookup(&self, path: &str) -> FsResult<Inode> {
-        let (fs, rel) = self.resolve(path)?;
+        let path = self.walk(path, true)?;
+        let (fs, rel) = self.resolve(&path)?;
+        fs.lookup(&rel)
+    }
+
+    /// Look up an inode without following a symbolic link in the final component.
+    pub fn lookup_nofollow(&self, path: &str) -> FsResult<Inode> {
+        let path = self.walk(path, false)?;
+        let (fs, rel) = self.resolve(&path)?;
         fs.lookup(&rel)
     }
 
     pub fn read_file(&self, path: &str, offset: usize, buf: &mut [u8]) -> FsResult<usize> {
-        let (fs, rel) = self.resolve(path)?;
+        let path = self.walk(path, true)?;
+        let (fs, rel) = self.resolve(&path)?;
         fs.read(&rel, offset, buf)
     }
 
     pub fn write_file(&mut self, path: &str, data: &[u8]) -> FsResult<usize> {
-        let (fs, rel) = self.resolve(path)?;
-        fs.write(&rel, 0, data)
+        self.write_at(path, 0, data)
+    }
+
+    /// Write `data` at byte `offset`. Writing past the end fills the gap with zeros.
+    pub fn write_at(&mut self, path: &str, offset: usize, data: &[u8]) -> FsResult<usize> {
+        let path = self.walk(path, true)?;
+        let (fs, rel) = self.resolve_writable(&path)?;
+        fs.write(&rel, offset, data)
+    }
+
+    /// Last modification time, of a symbolic link itself rather than its target.
+    /// `NotSupported` on filesystems that don't record one.
+    pub fn modified(&self, path: &str) -> FsResult<crate::drivers::rtc::DateTime> {
+        let path = self.walk(path, false)?;
+        let (fs, rel) = self.resolve(&path)?;
+        fs.modified(&rel)
     }
 
     pub fn readdir(&self, path: &str) -> FsResult<Vec<DirEntry>> {
-        let (fs, rel) = self.resolve(path)?;
+        let path = self.walk(path, true)?;
+        let (fs, rel) = self.resolve(&path)?;
         fs.readdir(&rel)
     }
 
+    /// Remove a directory entry. A symlink in the final component is removed itself.
     pub fn unlink(&mut self, path: &str) -> FsResult<()> {
-        let (fs, rel) = self.resolve(path)?;
+        let path = self.walk(path, false)?;
+        let (fs, rel) = self.resolve_writable(&path)?;
         fs.unlink(&rel)
     }
 
+    /// Create a symbolic link at `linkpath` pointing to `target`.
+    pub fn symlink(&mut self, target: &str, linkpath: &str) -> FsResult<Inode> {
+        let path = self.walk(linkpath, false)?;
+        let (fs, rel) = self.resolve_writable(&path)?;
+        fs.symlink(target, &rel)
+    }
+
+    /// Move `from` to `to`, replacing a file or empty directory at `to`.
+    /// Both must be on the same mount (`CrossDevice` otherwise); a symlink
+    /// in the final component of either is renamed itself.
+    pub fn rename(&mut self, from: &str, to: &str) -> FsResult<()> {
+        let from = self.walk(from, false)?;
+        let to = self.walk(to, false)?;
+        let (from_mp, from_rel) = self.resolve_mount(&from)?;
+        let (to_mp, to_rel) = self.resolve_mount(&to)?;
+        if !core::ptr::eq(from_mp, to_mp) {
+            return Err(FsError::CrossDevice);
+        }
+        if from_mp.read_only {
+            return Err(FsError::ReadOnly);
+        }
+        from_mp.fs.rename(&from_rel, &to_rel)
+    }
+
+    /// Set the length of the file at `path` to `len` bytes.
+    pub fn truncate(&mut self, path: &str, len: usize) -> FsResult<()> {
+        let path = self.walk(path, true)?;
+        let (fs, rel) = self.resolve_writable(&path)?;
+        fs.truncate(&rel, len)
+    }
+
+    /// Set the modification time of `path` to now, following symbolic links.
+    /// `NotSupported` on filesystems that don't record one.
+    pub fn touch(&mut self, path: &str) -> FsResult<()> {
+        let path = self.walk(path, true)?;
+        let (fs, rel) = self.resolve_writable(&path)?;
+        fs.touch(&rel)
+    }
+
+    /// Permission bits of `path`, following symbolic links.
+    pub fn mode(&self, path: &str) -> FsResult<u16> {
+        let path = self.walk(path, true)?;
+        let (fs, rel) = self.resolve(&path)?;
+        fs.get_mode(&rel)
+    }
+
+    /// Set the permission bits of `path`, following symbolic links.
+    pub fn chmod(&mut self, path: &str, mode: u16) -> FsResult<()> {
+        let path = self.walk(path, true)?;
+        let (fs, rel) = self.resolve_writable(&path)?;
+        fs.set_mode(&rel, mode & crate::fs::inode::MODE_MASK)
+    }
+
+    /// Read the target of the symbolic link at `path`.
+    pub fn readlink(&self, path: &str) -> FsResult<String> {
+        let path = self.walk(path, false)?;
+        let (fs, rel) = self.resolve(&path)?;
+        fs.readlink(&rel)
+    }
+
     /// Check if path exists.
     pub fn exists(&self, path: &str) -> bool {
         self.lookup(path).is_ok()
@@ -97,7 +325,20 @@ impl Vfs {
     /// Check if path is a directory.
     pub fn is_dir(&self, path: &str) -> bool {
         self.lookup(path)
-            .map(|inode| inode.file_type == super::inode::FileType::Directory)
+            .map(|inode| inode.file_type == FileType::Directory)
             .unwrap_or(false)
     }
 }
+
+/// Build an absolute path from resolved components.
+fn join_components(parts: &[String]) -> String {
+    if parts.is_empty() {
+        return String::from("/");
+    }
+    let mut path = String::new();
+    for p in parts {
+        path.push('/');
+        path.push_str(p);
+    }
+    path
+}
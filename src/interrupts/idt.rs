@@ -8,6 +8,16 @@ use spin::Mutex;
 pub const PIC_1_OFFSET: u8 = 32;
 pub const PIC_2_OFFSET: u8 = PIC_1_OFFSET + 8;
 
+/// Exit status the shell reports for a user process killed by a breakpoint
+/// or debug trap (128 + SIGTRAP).
+pub const SIGTRAP_EXIT_STATUS: u64 = 128 + crate::scheduler::signal::SIGTRAP as u64;
+/// Exit status the shell reports for a user process killed by a bad memory
+/// access (128 + SIGSEGV).
+pub const SIGSEGV_EXIT_STATUS: u64 = 128 + crate::scheduler::signal::SIGSEGV as u64;
+
+/// RFLAGS interrupt enable bit, as saved in an interrupt stack frame.
+const RFLAGS_IF: u64 = 1 << 9;
+
 pub static PICS: Mutex<ChainedPics> = Mutex::new(unsafe { ChainedPics::new(PIC_1_OFFSET, PIC_2_OFFSET) });
 
 #[derive(Debug, Clone, Copy)]
@@ -15,6 +25,7 @@ pub static PICS: Mutex<ChainedPics> = Mutex::new(unsafe { ChainedPics::new(PIC_1
 pub enum InterruptIndex {
     Timer = PIC_1_OFFSET,
     Keyboard = PIC_1_OFFSET + 1,
+    Serial = PIC_1_OFFSET + 4,
     Mouse = PIC_1_OFFSET + 12,
 }
 
@@ -31,7 +42,10 @@ impl InterruptIndex {
 lazy_static! {
     static ref IDT: InterruptDescriptorTable = {
         let mut idt = InterruptDescriptorTable::new();
-        idt.breakpoint.set_handler_fn(breakpoint_handler);
+        // DPL=3 so a user `int3` raises #BP instead of a #GP on the gate
+        idt.breakpoint.set_handler_fn(breakpoint_handler)
+            .set_privilege_level(x86_64::PrivilegeLevel::Ring3);
+        idt.debug.set_handler_fn(debug_handler);
         unsafe {
             idt.double_fault.set_handler_fn(double_fault_handler)
                 .set_stack_index(gdt::DOUBLE_FAULT_IST_INDEX);
@@ -42,6 +56,8 @@ lazy_static! {
             .set_handler_fn(timer_interrupt_handler);
         idt[InterruptIndex::Keyboard.as_usize()]
             .set_handler_fn(keyboard_interrupt_handler);
+        idt[InterruptIndex::Serial.as_usize()]
+            .set_handler_fn(serial_interrupt_handler);
         idt[InterruptIndex::Mouse.as_usize()]
             .set_handler_fn(mouse_interrupt_handler);
 
@@ -62,15 +78,61 @@ pub fn init() {
     IDT.load();
 }
 
+/// True if the exception interrupted Ring 3 code.
+fn from_user_mode(stack_frame: &InterruptStackFrame) -> bool {
+    stack_frame.code_segment & 3 == 3
+}
+
+/// Deliver SIGTRAP to the current user process. There are no signal
+/// handlers yet, so the default action applies: terminate it.
+fn user_trap(what: &str, stack_frame: &InterruptStackFrame) -> ! {
+    crate::log_warn!("{} in user process PID {} at {:#x}: SIGTRAP",
+        what, crate::scheduler::current_pid().0, stack_frame.instruction_pointer.as_u64());
+    crate::println!("Trace/breakpoint trap");
+    crate::scheduler::kill_current(crate::scheduler::signal::SIGTRAP);
+    unreachable!();
+}
+
+/// Deliver SIGSEGV to the current user process: terminate it and switch to
+/// the next task. The exception frame lives on the process's own kernel
+/// stack, which is only freed once the zombie is reaped, and the process
+/// holds no kernel locks while in Ring 3, so `exit_current` is safe here.
+fn user_segfault() -> ! {
+    crate::println!("Segmentation fault");
+    crate::scheduler::kill_current(crate::scheduler::signal::SIGSEGV);
+    unreachable!();
+}
+
 extern "x86-interrupt" fn breakpoint_handler(
     stack_frame: InterruptStackFrame)
 {
+    if from_user_mode(&stack_frame) {
+        user_trap("breakpoint", &stack_frame);
+    }
     println!("EXCEPTION: BREAKPOINT\n{:#?}", stack_frame);
 }
 
+extern "x86-interrupt" fn debug_handler(
+    stack_frame: InterruptStackFrame)
+{
+    if from_user_mode(&stack_frame) {
+        user_trap("debug exception", &stack_frame);
+    }
+    // Kernel single-step / hardware breakpoint: report and carry on
+    let dr6: u64;
+    unsafe { core::arch::asm!("mov {}, dr6", out(reg) dr6, options(nomem, nostack, preserves_flags)); }
+    println!("EXCEPTION: DEBUG (DR6={:#x})\n{:#?}", dr6, stack_frame);
+}
+
 extern "x86-interrupt" fn double_fault_handler(
     stack_frame: InterruptStackFrame, _error_code: u64) -> !
 {
+    use x86_64::registers::control::Cr2;
+    // A task that runs off its kernel stack faults on the guard page, and
+    // the page fault cannot be delivered on that stack either
+    if crate::scheduler::stack::is_guard_page(Cr2::read().as_u64()) {
+        crate::scheduler::stack::overflowed(&stack_frame);
+    }
     panic!("EXCEPTION: DOUBLE FAULT\n{:#?}", stack_frame);
 }
 
@@ -79,38 +141,62 @@ extern "x86-interrupt" fn page_fault_handler(
 {
     use x86_64::registers::control::Cr2;
     let accessed_address = Cr2::read();
-    
-    // Check if the page fault originated from User Mode (Ring 3)
-    if error_code.contains(PageFaultErrorCode::USER_MODE) {
-        crate::log_error!("SEGMENTATION FAULT in User Process!");
-        crate::log_error!("Accessed Address: {:?}", accessed_address);
-        crate::log_error!("Error Code: {:?}", error_code);
-        crate::log_error!("{:#?}", stack_frame);
-        
-        crate::println!("Segmentation Fault");
-        
-        // Kill the offending process gracefully instead of panicking the whole kernel
-        crate::scheduler::exit_current(139); // 139 is standard Unix code for segfault (128 + 11)
-    } else {
-        log_error!("KERNEL PANIC: PAGE FAULT");
-        log_error!("Accessed Address: {:?}", accessed_address);
-        log_error!("Error Code: {:?}", error_code);
-        panic!("EXCEPTION: PAGE FAULT\n{:#?}", stack_frame);
+    let user = error_code.contains(PageFaultErrorCode::USER_MODE) || from_user_mode(&stack_frame);
+
+    // A program image page not loaded yet: load it and retry the access
+    if !error_code.contains(PageFaultErrorCode::PROTECTION_VIOLATION) {
+        let addr = accessed_address.as_u64();
+        // Ring 3 holds no kernel locks, and neither does a syscall running
+        // with interrupts on (see `load_page`), so the handler may wait for
+        // them. A syscall touching a user buffer that was never loaded with
+        // interrupts off only gets the page if the locks are free.
+        let loaded = if user || stack_frame.cpu_flags & RFLAGS_IF != 0 {
+            x86_64::instructions::interrupts::enable();
+            let loaded = crate::memory::demand::load_page(addr, true);
+            x86_64::instructions::interrupts::disable();
+            loaded
+        } else {
+            crate::memory::demand::load_page(addr, false)
+        };
+        if loaded {
+            return;
+        }
+    }
+
+    // A fault in Ring 3 only takes down the offending process
+    if user {
+        crate::log_warn!("page fault in user process PID {} at {:#x}: {:?} accessing {:?}: SIGSEGV",
+            crate::scheduler::current_pid().0, stack_frame.instruction_pointer.as_u64(),
+            error_code, accessed_address);
+        user_segfault();
     }
+
+    log_error!("KERNEL PANIC: PAGE FAULT");
+    log_error!("Accessed Address: {:?}", accessed_address);
+    log_error!("Error Code: {:?}", error_code);
+    panic!("EXCEPTION: PAGE FAULT\n{:#?}", stack_frame);
 }
 
 extern "x86-interrupt" fn timer_interrupt_handler(
-    _stack_frame: InterruptStackFrame)
+    stack_frame: InterruptStackFrame)
 {
     crate::shell::commands::uptime::tick();
+    crate::scheduler::account_tick();
+    crate::drivers::mouse::cursor::pump();
 
     unsafe {
         PICS.lock()
             .notify_end_of_interrupt(InterruptIndex::Timer.as_u8());
     }
     
-    // Enable Preemptive Multitasking!
-    crate::scheduler::try_yield_now();
+    // Charge this quantum to the running task (may terminate a spinning user task)
+    crate::scheduler::watchdog::tick(stack_frame.code_segment);
+
+    // A signalled task caught in user mode dies here rather than at its next syscall
+    crate::scheduler::signal::deliver_on_tick(stack_frame.code_segment);
+    
+    // Round-robin: switch tasks once the running one has used its quantum
+    crate::scheduler::preempt();
 }
 
 extern "x86-interrupt" fn keyboard_interrupt_handler(
@@ -128,6 +214,17 @@ extern "x86-interrupt" fn keyboard_interrupt_handler(
     }
 }
 
+extern "x86-interrupt" fn serial_interrupt_handler(
+    _stack_frame: InterruptStackFrame)
+{
+    crate::serial::receive_interrupt();
+
+    unsafe {
+        PICS.lock()
+            .notify_end_of_interrupt(InterruptIndex::Serial.as_u8());
+    }
+}
+
 extern "x86-interrupt" fn mouse_interrupt_handler(
     _stack_frame: InterruptStackFrame)
 {
@@ -146,5 +243,11 @@ extern "x86-interrupt" fn mouse_interrupt_handler(
 extern "x86-interrupt" fn general_protection_fault_handler(
     stack_frame: InterruptStackFrame, error_code: u64)
 {
+    // e.g. a non-canonical pointer dereferenced in Ring 3
+    if from_user_mode(&stack_frame) {
+        crate::log_warn!("general protection fault in user process PID {} at {:#x} (error code {}): SIGSEGV",
+            crate::scheduler::current_pid().0, stack_frame.instruction_pointer.as_u64(), error_code);
+        user_segfault();
+    }
     panic!("EXCEPTION: GENERAL PROTECTION FAULT\nError Code: {error_code}\n{:#?}", stack_frame);
 }
@@ -1,9 +1,17 @@
 pub mod gdt;
 pub mod idt;
+pub mod pit;
 pub mod usermode;
 
 pub fn init() {
     gdt::init();
     idt::init();
-    unsafe { idt::PICS.lock().initialize() };
+    unsafe {
+        let mut pics = idt::PICS.lock();
+        pics.initialize();
+        // Firmware may leave COM1's line masked; it carries serial console input
+        let [master, slave] = pics.read_masks();
+        pics.write_masks(master & !(1 << 4), slave);
+    }
+    pit::set_timer_hz(pit::DEFAULT_HZ);
 }
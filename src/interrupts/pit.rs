@@ -0,0 +1,60 @@
+// Programmable Interval Timer (8253/8254), channel 0: the timer interrupt
+// (IRQ 0) that drives the tick counter, preemption and sleeps.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+use x86_64::instructions::{interrupts, port::Port};
+
+/// Input clock of the PIT.
+pub const PIT_BASE_HZ: u32 = 1_193_182;
+/// Rate programmed at boot.
+pub const DEFAULT_HZ: u32 = 100;
+/// Lowest rate: the divisor must fit in 16 bits (65536 is written as 0).
+pub const MIN_HZ: u32 = 19;
+/// Highest rate accepted; faster, the CPU would spend its time in the handler.
+pub const MAX_HZ: u32 = 10_000;
+
+const COMMAND_PORT: u16 = 0x43;
+const CHANNEL0_PORT: u16 = 0x40;
+/// Channel 0, low then high divisor byte, mode 3 (square wave), binary.
+const CHANNEL0_SQUARE_WAVE: u8 = 0x36;
+
+/// Current channel-0 divisor; the power-on default until `set_timer_hz`.
+static DIVISOR: AtomicU32 = AtomicU32::new(65_536);
+
+/// Program the timer as close to `hz` as a whole divisor allows, clamped
+/// to `MIN_HZ..=MAX_HZ`. Returns the rate actually set (rounded to Hz).
+pub fn set_timer_hz(hz: u32) -> u32 {
+    let hz = hz.clamp(MIN_HZ, MAX_HZ);
+    // Nearest divisor, not the one below: 1000 Hz gets 1193 (1000.15 Hz)
+    let divisor = (PIT_BASE_HZ + hz / 2) / hz;
+    interrupts::without_interrupts(|| {
+        // SAFETY: the PIT ports only set the timer rate
+        unsafe {
+            Port::<u8>::new(COMMAND_PORT).write(CHANNEL0_SQUARE_WAVE);
+            let mut data = Port::<u8>::new(CHANNEL0_PORT);
+            data.write(divisor as u8);
+            data.write((divisor >> 8) as u8);
+        }
+        DIVISOR.store(divisor, Ordering::Relaxed);
+    });
+    timer_hz()
+}
+
+/// Timer interrupts per second, rounded to the nearest Hz.
+pub fn timer_hz() -> u32 {
+    let divisor = DIVISOR.load(Ordering::Relaxed);
+    (PIT_BASE_HZ + divisor / 2) / divisor
+}
+
+/// Length of one tick at the current rate, in nanoseconds.
+pub fn nanos_per_tick() -> u64 {
+    let divisor = DIVISOR.load(Ordering::Relaxed) as u64;
+    (divisor * 1_000_000_000 + PIT_BASE_HZ as u64 / 2) / PIT_BASE_HZ as u64
+}
+
+/// Ticks covering `ms` milliseconds at the current rate, rounded up so a
+/// sleep never ends early.
+pub fn ms_to_ticks(ms: u64) -> u64 {
+    let ticks = (ms as u128 * 1_000_000).div_ceil(nanos_per_tick() as u128);
+    ticks.min(u64::MAX as u128) as u64
+}
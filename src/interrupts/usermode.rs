@@ -1,12 +1,38 @@
 /// Usermode support — int 0x80 syscall handler and Ring 3 transition.
 
 use core::arch::naked_asm;
+use crate::scheduler::TrapFrame;
+use crate::scheduler::fpu::FpuState;
+
+/// General-purpose registers pushed by `syscall_handler_asm` (all but RAX and RSP).
+pub const SAVED_GPRS: usize = 14;
+/// QWORDs the CPU pushes when entering from Ring 3: SS, RSP, RFLAGS, CS, RIP.
+pub const CPU_FRAME_QWORDS: usize = 5;
+/// Size of the `TrapFrame` sitting at the top of the kernel stack during a syscall.
+pub const TRAP_FRAME_SIZE: usize = (SAVED_GPRS + CPU_FRAME_QWORDS) * 8;
+/// Size of the FXSAVE area reserved below the `TrapFrame`.
+const FPU_AREA_SIZE: usize = core::mem::size_of::<FpuState>();
+
+/// The FXSAVE area `syscall_handler_asm` fills below `frame`: the user's
+/// FPU/SSE registers as they were at the int 0x80, restored before `iretq`.
+pub fn saved_fpu(frame: *const TrapFrame) -> *const FpuState {
+    ((frame as usize - FPU_AREA_SIZE) & !15) as *const FpuState
+}
 
 /// The int 0x80 handler — entered from Ring 3.
 /// Saves user registers, calls Rust syscall dispatcher, restores and iretq back.
 ///
 /// Convention: RAX=syscall number, RDI=arg0, RSI=arg1, RDX=arg2
 /// Returns: RAX=result
+///
+/// The registers pushed here, together with the CPU frame, form a
+/// `scheduler::TrapFrame`; its address is handed to `dispatch` so `fork`
+/// can copy it without guessing where it lives.
+///
+/// The kernel is built with SSE, so the user's FPU/SSE state is saved
+/// (FXSAVE, into a 16-byte aligned area below the frame — see `saved_fpu`)
+/// before any Rust runs and restored just before `iretq`. Context switches
+/// only preserve whatever the kernel left in the registers.
 #[unsafe(naked)]
 pub extern "C" fn syscall_handler_asm() {
     naked_asm!(
@@ -26,13 +52,20 @@ pub extern "C" fn syscall_handler_asm() {
         "push rbx",
         "push rcx",
 
-        // Align the stack strictly to 16-bytes as required by System V AMD64 ABI
-        // CPU pushes 5 QWORDS (40 bytes), we push 14 QWORDS (112 bytes) = 152 bytes total.
-        // 152 is not divisible by 16! It's off by 8 bytes.
-        "sub rsp, 8",
+        // RSP now points at the complete TrapFrame (5th param); RBP (saved
+        // above, callee-saved) keeps it across the calls below
+        "mov r8, rsp",
+        "mov rbp, rsp",
+
+        // Save the user's FPU/SSE state below the frame. The area is
+        // 16-byte aligned, which also aligns the stack for the calls
+        // as required by System V AMD64 ABI
+        "sub rsp, {fpu_area}",
+        "and rsp, -16",
+        "fxsave64 [rsp]",
 
-        // Call Rust dispatcher: dispatch(rax, rdi, rsi, rdx)
-        // System V ABI: arg0=rdi, arg1=rsi, arg2=rdx, arg3=rcx
+        // Call Rust dispatcher: dispatch(rax, rdi, rsi, rdx, frame)
+        // System V ABI: arg0=rdi, arg1=rsi, arg2=rdx, arg3=rcx, arg4=r8
         // We need: rdi=number(was rax), rsi=arg0(was rdi), rdx=arg1(was rsi), rcx=arg2(was rdx)
         "mov rcx, rdx",   // arg2 → rcx (4th param)
         "mov rdx, rsi",   // arg1 → rdx (3rd param)
@@ -40,8 +73,18 @@ pub extern "C" fn syscall_handler_asm() {
         "mov rdi, rax",   // number → rdi (1st param)
         "call {dispatch}",
 
-        // Un-align stack before resuming context POP routines
+        // Deliver pending signals on the way out; RAX (the result) is kept.
+        // RDI = the TrapFrame; a SIGTERM rewrites it so iretq enters its handler
+        "push rax",
+        "sub rsp, 8",
+        "mov rdi, rbp",
+        "call {signals}",
         "add rsp, 8",
+        "pop rax",
+
+        // Put the user's FPU/SSE state back and drop the area
+        "fxrstor64 [rsp]",
+        "mov rsp, rbp",
 
         // Return value is in RAX — it'll be restored to user's RAX
 
@@ -63,6 +106,8 @@ pub extern "C" fn syscall_handler_asm() {
 
         "iretq",
         dispatch = sym crate::syscalls::dispatch,
+        signals = sym crate::scheduler::signal::deliver_on_syscall_return,
+        fpu_area = const FPU_AREA_SIZE,
     );
 }
 
@@ -0,0 +1,150 @@
+// Kernel message ring.
+//
+// Every `log_info!`/`log_warn!`/`log_error!` goes to the serial port and is
+// also recorded here with its level and the tick it was logged at, so the
+// history can be read back later through `log` or `/dev/kmsg`.
+//
+// Logging happens in IRQ context, so recording never allocates: messages are
+// formatted into a fixed on-stack buffer and copied into a static ring while
+// interrupts are disabled (an interrupt can never find the ring lock held).
+
+use core::fmt;
+use spin::Mutex;
+
+/// Entries kept before the oldest is overwritten.
+pub const KLOG_CAPACITY: usize = 512;
+/// Message bytes stored per entry; longer messages are truncated.
+pub const MSG_MAX: usize = 120;
+
+/// Severity of a log entry, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    /// Tag printed in front of the message ("INFO", "WARN", "ERROR").
+    pub fn tag(self) -> &'static str {
+        match self {
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        }
+    }
+
+    /// Parse a level name as used by `log <level>`.
+    pub fn parse(s: &str) -> Option<Level> {
+        match s {
+            "info" => Some(Level::Info),
+            "warn" => Some(Level::Warn),
+            "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+}
+
+/// One recorded message.
+#[derive(Clone, Copy)]
+pub struct Entry {
+    pub ticks: u64,
+    pub level: Level,
+    len: u8,
+    msg: [u8; MSG_MAX],
+}
+
+impl Entry {
+    const EMPTY: Entry = Entry { ticks: 0, level: Level::Info, len: 0, msg: [0; MSG_MAX] };
+
+    pub fn message(&self) -> &str {
+        // Truncation only ever happens on a char boundary (see `MsgBuf`)
+        core::str::from_utf8(&self.msg[..self.len as usize]).unwrap_or("")
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{:>8}] [{}] {}", self.ticks, self.level.tag(), self.message())
+    }
+}
+
+struct Ring {
+    entries: [Entry; KLOG_CAPACITY],
+    /// Sequence number of the next entry to be written; entry `seq` lives
+    /// at `seq % KLOG_CAPACITY` until it is overwritten.
+    next_seq: u64,
+}
+
+static RING: Mutex<Ring> = Mutex::new(Ring {
+    entries: [Entry::EMPTY; KLOG_CAPACITY],
+    next_seq: 0,
+});
+
+/// Fixed-capacity `fmt::Write` sink that silently truncates.
+struct MsgBuf {
+    buf: [u8; MSG_MAX],
+    len: usize,
+}
+
+impl fmt::Write for MsgBuf {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            let mut tmp = [0u8; 4];
+            let bytes = c.encode_utf8(&mut tmp).as_bytes();
+            if self.len + bytes.len() > MSG_MAX {
+                break;
+            }
+            self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+            self.len += bytes.len();
+        }
+        Ok(())
+    }
+}
+
+/// Append a message to the ring.
+pub fn record(level: Level, args: fmt::Arguments) {
+    use fmt::Write;
+    let mut msg = MsgBuf { buf: [0; MSG_MAX], len: 0 };
+    let _ = msg.write_fmt(args);
+
+    let entry = Entry {
+        ticks: crate::shell::commands::uptime::TICKS.load(core::sync::atomic::Ordering::Relaxed),
+        level,
+        len: msg.len as u8,
+        msg: msg.buf,
+    };
+
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        let mut ring = RING.lock();
+        let slot = (ring.next_seq % KLOG_CAPACITY as u64) as usize;
+        ring.entries[slot] = entry;
+        ring.next_seq += 1;
+    });
+}
+
+/// Sequence numbers `[first, next)` currently held by the ring.
+pub fn seq_range() -> (u64, u64) {
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        let ring = RING.lock();
+        (ring.next_seq.saturating_sub(KLOG_CAPACITY as u64), ring.next_seq)
+    })
+}
+
+/// Copy out entry `seq`, or None if it has not been written yet or was overwritten.
+pub fn get(seq: u64) -> Option<Entry> {
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        let ring = RING.lock();
+        if seq >= ring.next_seq || seq + (KLOG_CAPACITY as u64) < ring.next_seq {
+            return None;
+        }
+        Some(ring.entries[(seq % KLOG_CAPACITY as u64) as usize])
+    })
+}
+
+/// Backend of the `log_*!` macros: echo to serial and record in the ring.
+#[doc(hidden)]
+pub fn _log(level: Level, args: fmt::Arguments) {
+    crate::serial::_print(format_args!("[{}] {}\n", level.tag(), args));
+    record(level, args);
+}
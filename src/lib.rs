@@ -4,6 +4,7 @@
 
 pub mod vga;
 pub mod serial;
+pub mod klog;
 pub mod allocator;
 
 extern crate alloc;
@@ -15,6 +16,10 @@ pub mod fs;
 pub mod drivers;
 pub mod loader;
 pub mod shell;
+pub mod system_info;
+pub mod power;
+pub mod util;
+pub mod debug;
 
 use core::panic::PanicInfo;
 
@@ -33,6 +38,7 @@ pub extern "C" fn _start(multiboot_info_addr: usize) -> ! {
     fs::init();
     drivers::init();
     fs::mount_fat32(); // ATA is now available
+    fs::mount_cdrom();
     println!("AtomicOS is successfully running!");
 
 
@@ -48,9 +54,6 @@ pub extern "C" fn _start(multiboot_info_addr: usize) -> ! {
 
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
-    println!("{}", info);
-    log_error!("{}", info);
-    loop {
-        x86_64::instructions::hlt();
-    }
+    // Never touch WRITER/SERIAL1 here: the panicking code may be holding them.
+    debug::halt_with_report(format_args!("KERNEL PANIC: {}", info))
 }
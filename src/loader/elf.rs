@@ -1,7 +1,9 @@
 use alloc::string::String;
 use alloc::vec;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::fmt;
+use crate::memory::demand::{LazyImage, Segment};
 
 // ══════════════════════════════════════════════════════════════
 //  ELF64 constants
@@ -79,6 +81,7 @@ pub enum ExecError {
     UnsupportedType,
     MemoryError,
     ReadError,
+    ArgListTooLong,
 }
 
 impl fmt::Display for ExecError {
@@ -90,6 +93,7 @@ impl fmt::Display for ExecError {
             ExecError::UnsupportedType => write!(f, "Unsupported ELF type (need ET_EXEC)"),
             ExecError::MemoryError     => write!(f, "Memory allocation error"),
             ExecError::ReadError       => write!(f, "File read error"),
+            ExecError::ArgListTooLong  => write!(f, "Argument list too long"),
         }
     }
 }
@@ -99,8 +103,11 @@ impl fmt::Display for ExecError {
 // ══════════════════════════════════════════════════════════════
 
 /// Trampoline function — runs as a kernel task starting point for Ring 3 processes.
-/// Since it's a raw entry, we receive the target entry and stack via registers R12 and R13
-/// which we will craft manually in the `Context` builder.
+/// Since it's a raw entry, we receive the target entry and stack via registers R12 and R13,
+/// and argc/argv via R14 and R15, which we will craft manually in the `Context` builder.
+/// The user RSP points at argc with argv and envp above it (System V); argc,
+/// argv and envp are also passed in RDI, RSI and RDX for programs that
+/// start straight in a C-style `main`.
 #[unsafe(naked)]
 pub extern "C" fn usermode_trampoline() {
     unsafe {
@@ -108,6 +115,7 @@ pub extern "C" fn usermode_trampoline() {
             "
             // R12 = user_entry
             // R13 = user_stack_top
+            // R14 = argc, R15 = argv (user pointer)
             
             // Log entry
             // (Skipped complex logging in naked assembly for stability)
@@ -121,6 +129,12 @@ pub extern "C" fn usermode_trampoline() {
             mov fs, ax
             mov gs, ax
 
+            // main(argc, argv, envp) per System V: rdi, rsi, rdx
+            // envp follows argv's NULL terminator
+            mov rdi, r14
+            mov rsi, r15
+            lea rdx, [r15 + r14 * 8 + 8]
+
             // IRETQ Frame construction on the Kernel Stack
             push 0x1B         // SS
             push r13          // RSP
@@ -141,9 +155,23 @@ pub extern "C" fn usermode_trampoline() {
 /// Stack size for user programs (16 KiB).
 const USER_STACK_SIZE: usize = 4096 * 4;
 
+/// Upper bound for the argv and envp strings + pointer arrays copied onto the
+/// user stack. Half the stack, so a program with maximal arguments still has
+/// 8 KiB to run in.
+pub const MAX_ARG_BYTES: usize = USER_STACK_SIZE / 2;
+
+const _: () = assert!(MAX_ARG_BYTES > crate::syscalls::PATH_MAX);
+
 /// Load an ELF64 binary and create a Ring 3 task (Legacy boot support API).
 pub fn load(path: &str) -> Result<u64, ExecError> {
-    let params = parse_and_map_elf(path)?;
+    spawn(path, &[path], &[], None).map(|pid| pid.0)
+}
+
+/// Load an ELF64 binary into a new Ring 3 process with the given argv and
+/// environment (`NAME=value` strings).
+/// `parent` becomes the process' parent so it can `sys_wait` for it.
+pub fn spawn(path: &str, argv: &[&str], envp: &[&str], parent: Option<crate::scheduler::ProcessId>) -> Result<crate::scheduler::ProcessId, ExecError> {
+    let params = parse_and_map_elf(path, argv, envp)?;
 
     // 8. Spawn process using Phase 5.3 Custom Scheduler Builder
     let task_name = extract_filename(path);
@@ -154,19 +182,42 @@ pub fn load(path: &str) -> Result<u64, ExecError> {
         usermode_trampoline as *const () as u64, // The Ring 0 kernel entry of this new process
         params.user_stack_top,
         params.allocations,
+        crate::scheduler::PRIORITY_NORMAL,
     );
     
-    // Inject R12 and R13 into the freshly spawned process Context to feed the trampoline
+    // Inject R12-R15 into the freshly spawned process Context to feed the trampoline
     {
         let mut sched = crate::scheduler::SCHEDULER.lock();
+        // The child starts with the parent's descriptors, as after fork+exec
+        let parent_fds = parent.and_then(|parent_pid| {
+            let cur = sched.current.as_ref().filter(|p| p.pid == parent_pid);
+            cur.or_else(|| sched.ready_queue.iter().find(|p| p.pid == parent_pid))
+                .map(|p| p.fd_table.clone())
+        });
         if let Some(proc) = sched.ready_queue.iter_mut().find(|p| p.pid == task_id) {
+            if let Some(fds) = parent_fds {
+                proc.fd_table = fds;
+            }
             proc.context.r12 = params.entry;
             proc.context.r13 = params.user_stack_top;
+            proc.context.r14 = params.argc;
+            proc.context.r15 = params.argv;
+            proc.heap_start = params.heap_start;
+            proc.heap_end = params.heap_start;
+            proc.image = Some(params.image);
+            proc.parent_pid = parent;
+        }
+        if let Some(parent_pid) = parent {
+            if let Some(cur) = sched.current.as_mut().filter(|p| p.pid == parent_pid) {
+                cur.children.push(task_id);
+            } else if let Some(p) = sched.ready_queue.iter_mut().find(|p| p.pid == parent_pid) {
+                p.children.push(task_id);
+            }
         }
     }
 
     crate::log_info!("ELF: spawned process '{}' (PID {})", task_name, task_id.0);
-    Ok(task_id.0)
+    Ok(task_id)
 }
 
 /// Represents the extracted core parameters of an ELF binary 
@@ -174,26 +225,50 @@ pub fn load(path: &str) -> Result<u64, ExecError> {
 pub struct ElfExecParams {
     pub page_table: u64,
     pub entry: u64,
+    /// Initial RSP, pointing at argc.
     pub user_stack_top: u64,
+    /// First byte after the user stack; the program break starts here.
+    pub heap_start: u64,
+    pub argc: u64,
+    /// User address of the NULL-terminated argv pointer array.
+    pub argv: u64,
     pub allocations: alloc::vec::Vec<(u64, u64)>,
+    /// The program's segments, loaded into `page_table` as they are touched.
+    pub image: Arc<LazyImage>,
 }
 
 /// Parse and map an ELF into a brand new isolated Address Space.
-/// Returns the mapping parameters without modifying the scheduler.
-pub fn parse_and_map_elf(path: &str) -> Result<ElfExecParams, ExecError> {
+/// `argv` and `envp` are copied onto the new user stack. The segments are
+/// not copied in: their pages stay unmapped until first touched, when the
+/// page fault handler loads them (see `memory::demand`). Returns the mapping
+/// parameters without modifying the scheduler.
+pub fn parse_and_map_elf(path: &str, argv: &[&str], envp: &[&str]) -> Result<ElfExecParams, ExecError> {
+    if arg_bytes(argv) + arg_bytes(envp) > MAX_ARG_BYTES {
+        return Err(ExecError::ArgListTooLong);
+    }
+
     let file_data = read_file_all(path)?;
     let ehdr = Elf64Ehdr::parse(&file_data)?;
 
     let mut load_base: u64 = u64::MAX;
     let mut load_end: u64 = 0;
+    let mut segments = Vec::new();
 
     for i in 0..ehdr.e_phnum as usize {
         let off = ehdr.e_phoff as usize + i * ehdr.e_phentsize as usize;
-        let phdr = Elf64Phdr::parse(&file_data[off..])?;
+        let phdr = Elf64Phdr::parse(file_data.get(off..).ok_or(ExecError::InvalidFormat)?)?;
         if phdr.p_type != PT_LOAD { continue; }
+        let seg_end = phdr.p_vaddr.checked_add(phdr.p_memsz)
+            .filter(|&end| end <= crate::memory::paging::USER_SPACE_END)
+            .ok_or(ExecError::InvalidFormat)?;
         if phdr.p_vaddr < load_base { load_base = phdr.p_vaddr; }
-        let seg_end = phdr.p_vaddr + phdr.p_memsz;
         if seg_end > load_end { load_end = seg_end; }
+        segments.push(Segment {
+            vaddr: phdr.p_vaddr,
+            memsz: phdr.p_memsz,
+            offset: phdr.p_offset,
+            filesz: phdr.p_filesz.min(phdr.p_memsz),
+        });
     }
 
     if load_base == u64::MAX { return Err(ExecError::InvalidFormat); }
@@ -216,53 +291,76 @@ pub fn parse_and_map_elf(path: &str) -> Result<ElfExecParams, ExecError> {
     let phys_mem_offset = x86_64::VirtAddr::new(0);
     let mut mapper = unsafe { crate::memory::paging::init_paging(phys_mem_offset) };
 
-    let image_size = (load_end - load_base) as u64;
-    if !crate::memory::paging::allocate_process_memory(&mut mapper, x86_64::VirtAddr::new(load_base), image_size) {
-        unsafe { Cr3::write(old_p4, flags); }
-        return Err(ExecError::MemoryError);
-    }
+    // The image is only reserved: recorded so exit and fork see its pages once loaded
+    let image_size = load_end - load_base;
     mapped_allocations.push((load_base, image_size));
 
     if !crate::memory::paging::allocate_process_memory(&mut mapper, x86_64::VirtAddr::new(user_stack_base), USER_STACK_SIZE as u64) {
         unsafe { Cr3::write(old_p4, flags); }
+        // Whatever part of the stack got mapped goes with the tables
+        crate::memory::paging::discard_address_space(new_p4_phys, &[(user_stack_base, USER_STACK_SIZE as u64)]);
         return Err(ExecError::MemoryError);
     }
     mapped_allocations.push((user_stack_base, USER_STACK_SIZE as u64));
 
-    for i in 0..ehdr.e_phnum as usize {
-        let off = ehdr.e_phoff as usize + i * ehdr.e_phentsize as usize;
-        let phdr = Elf64Phdr::parse(&file_data[off..])?;
-        if phdr.p_type != PT_LOAD { continue; }
-
-        let dest_ptr = phdr.p_vaddr as *mut u8;
-        let file_offset = phdr.p_offset as usize;
-        let file_size = phdr.p_filesz as usize;
-
-        if file_offset + file_size <= file_data.len() {
-            unsafe {
-                core::ptr::copy_nonoverlapping(file_data[file_offset..].as_ptr(), dest_ptr, file_size);
-            }
-        }
-
-        if phdr.p_memsz > phdr.p_filesz {
-            let bss_size = (phdr.p_memsz - phdr.p_filesz) as usize;
-            unsafe { core::ptr::write_bytes(dest_ptr.add(file_size), 0, bss_size); }
-        }
-    }
+    // Still on the new address space: lay out the arguments at the top of the user stack
+    let (initial_rsp, argv_ptr) = unsafe { push_args(user_stack_top, argv, envp) };
 
     unsafe { Cr3::write(old_p4, flags); }
 
     let real_entry = ehdr.e_entry;
-    crate::log_info!("ELF Parsed: mapped at {:#x}, entry={:#x} stack_top={:#x} (Isolated P4 at {:#x})", load_base, real_entry, user_stack_top, new_p4_phys.as_u64());
+    crate::log_info!("ELF Parsed: image reserved at {:#x}, entry={:#x} stack_top={:#x} (Isolated P4 at {:#x})", load_base, real_entry, user_stack_top, new_p4_phys.as_u64());
 
     Ok(ElfExecParams {
         page_table: new_p4_phys.as_u64(),
         entry: real_entry,
-        user_stack_top,
+        user_stack_top: initial_rsp,
+        heap_start: user_stack_top,
+        argc: argv.len() as u64,
+        argv: argv_ptr,
         allocations: mapped_allocations,
+        image: Arc::new(LazyImage::new(file_data.into_boxed_slice(), segments)),
     })
 }
 
+/// Stack bytes taken by one string vector: the strings with their NULs and
+/// a pointer to each, plus the terminating NULL pointer.
+fn arg_bytes(strings: &[&str]) -> usize {
+    strings.iter().map(|a| a.len() + 1).sum::<usize>() + (strings.len() + 1) * 8
+}
+
+/// Lay out the initial process stack below `stack_top`, System V style.
+/// Growing down: argv and envp strings, padding, then from the new rsp up:
+/// argc, argv[0..argc], NULL, envp[..], NULL. The rsp is 16-byte aligned
+/// and points at argc. Returns (initial rsp, argv pointer).
+///
+/// # Safety
+/// The target address space must be active and the stack mapped.
+unsafe fn push_args(stack_top: u64, argv: &[&str], envp: &[&str]) -> (u64, u64) {
+    let mut sp = stack_top;
+    let mut words: Vec<u64> = Vec::with_capacity(argv.len() + envp.len() + 3);
+    words.push(argv.len() as u64);
+
+    for strings in [argv, envp] {
+        for s in strings {
+            sp -= s.len() as u64 + 1;
+            core::ptr::copy_nonoverlapping(s.as_ptr(), sp as *mut u8, s.len());
+            *((sp + s.len() as u64) as *mut u8) = 0;
+            words.push(sp);
+        }
+        words.push(0);
+    }
+
+    sp &= !0xF;
+    // An odd number of words would leave argc 8 bytes off the boundary
+    if words.len() % 2 == 1 {
+        sp -= 8;
+    }
+    sp -= (words.len() * 8) as u64;
+    core::ptr::copy_nonoverlapping(words.as_ptr(), sp as *mut u64, words.len());
+    (sp, sp + 8)
+}
+
 fn read_file_all(path: &str) -> Result<Vec<u8>, ExecError> {
     let vfs = crate::fs::VFS.lock();
     let inode = vfs.lookup(path).map_err(|_| ExecError::FileNotFound)?;
@@ -0,0 +1,132 @@
+// Demand paging for program images. `exec` records where the ELF's
+// loadable segments go instead of copying them in; each page gets a frame
+// and its contents on first access, from the page fault handler.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use x86_64::structures::paging::{FrameAllocator, FrameDeallocator, Mapper, Page, PageTableFlags, Size4KiB};
+use x86_64::VirtAddr;
+
+const PAGE_SIZE: u64 = 4096;
+
+/// Pages loaded on demand since boot.
+static PAGES_LOADED: AtomicU64 = AtomicU64::new(0);
+
+/// A loadable segment: `filesz` bytes from `offset` in the file go to
+/// `vaddr`, and the rest up to `memsz` is zero (BSS).
+#[derive(Debug, Clone, Copy)]
+pub struct Segment {
+    pub vaddr: u64,
+    pub memsz: u64,
+    pub offset: u64,
+    pub filesz: u64,
+}
+
+/// A program's file contents and its segments. The bytes stay on the heap
+/// while the program runs; only the pages it touches take frames. Shared by
+/// a process and its forked children, whose untouched pages load from it too.
+pub struct LazyImage {
+    data: Box<[u8]>,
+    segments: Vec<Segment>,
+}
+
+impl LazyImage {
+    pub fn new(data: Box<[u8]>, segments: Vec<Segment>) -> Self {
+        LazyImage { data, segments }
+    }
+
+    /// Whether the page starting at `page` overlaps a segment.
+    pub fn covers(&self, page: u64) -> bool {
+        self.segments.iter().any(|s| page < s.vaddr + s.memsz && s.vaddr < page + PAGE_SIZE)
+    }
+
+    /// Contents of the page starting at `page`: file bytes where a segment
+    /// has them, zeros elsewhere. A page may hold the end of one segment's
+    /// file data and its BSS, or parts of two segments.
+    fn fill(&self, page: u64, dst: &mut [u8]) {
+        dst.fill(0);
+        for s in &self.segments {
+            let start = s.vaddr.max(page);
+            let end = (s.vaddr + s.filesz).min(page + PAGE_SIZE);
+            if start >= end {
+                continue;
+            }
+            let file_at = (s.offset + (start - s.vaddr)) as usize;
+            let len = (end - start) as usize;
+            // File data past the end of a truncated file reads as zeros
+            if let Some(src) = self.data.get(file_at..file_at + len) {
+                dst[(start - page) as usize..][..len].copy_from_slice(src);
+            }
+        }
+    }
+}
+
+/// Give the running process's page containing `addr` a frame and its
+/// contents, if it belongs to the program image and is not loaded yet.
+/// Returns false otherwise, or if memory ran out.
+///
+/// With `blocking`, waits for the scheduler and frame allocator locks
+/// instead of giving up. Only safe where the faulting code cannot hold them:
+/// in Ring 3, or in a syscall with interrupts enabled. Syscalls must not
+/// touch user memory while holding the scheduler lock for this reason; the
+/// frame allocator is only ever held with interrupts off.
+pub fn load_page(addr: u64, blocking: bool) -> bool {
+    use crate::scheduler::SCHEDULER;
+    use crate::memory::FRAME_ALLOCATOR;
+
+    if addr >= crate::memory::paging::USER_SPACE_END {
+        return false;
+    }
+    let page_addr = addr & !(PAGE_SIZE - 1);
+
+    let image = {
+        let sched = if blocking { Some(SCHEDULER.lock()) } else { SCHEDULER.try_lock() };
+        match sched.and_then(|s| s.current.as_ref().and_then(|p| p.image.clone())) {
+            Some(image) => image,
+            None => return false,
+        }
+    };
+    if !image.covers(page_addr) {
+        return false;
+    }
+
+    let take = || if blocking { Some(FRAME_ALLOCATOR.lock()) } else { FRAME_ALLOCATOR.try_lock() };
+    let mut mapper = unsafe { crate::memory::paging::init_paging(VirtAddr::new(0)) };
+    let page = Page::<Size4KiB>::containing_address(VirtAddr::new(page_addr));
+    if mapper.translate_page(page).is_ok() {
+        // Already loaded: the fault was about something else
+        return false;
+    }
+    let Some(frame) = take().and_then(|mut frames| frames.allocate_frame()) else {
+        return false;
+    };
+
+    // Filled through the identity map before the page becomes visible, and
+    // without the frame allocator so interrupts stay on for the copy
+    let dst = unsafe {
+        core::slice::from_raw_parts_mut(frame.start_address().as_u64() as *mut u8, PAGE_SIZE as usize)
+    };
+    image.fill(page_addr, dst);
+
+    let Some(mut frames) = take() else {
+        // Not reached: without `blocking` interrupts are off, so nothing
+        // took the lock since the frame was allocated
+        return false;
+    };
+    let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::USER_ACCESSIBLE;
+    match unsafe { mapper.map_to(page, frame, flags, &mut *frames) } {
+        Ok(flush) => flush.flush(),
+        Err(_) => {
+            unsafe { frames.deallocate_frame(frame) };
+            return false;
+        }
+    }
+    PAGES_LOADED.fetch_add(1, Ordering::Relaxed);
+    true
+}
+
+/// Pages loaded on demand since boot.
+pub fn pages_loaded() -> u64 {
+    PAGES_LOADED.load(Ordering::Relaxed)
+}
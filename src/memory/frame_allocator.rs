@@ -1,13 +1,34 @@
 use x86_64::{
-    structures::paging::{FrameAllocator, PhysFrame, Size4KiB},
+    structures::paging::{FrameAllocator, FrameDeallocator, PhysFrame, Size4KiB},
     PhysAddr,
 };
 use multiboot2::{MemoryArea, MemoryAreaType};
 
-/// A simple bump allocator for physical memory frames.
+/// Written into the second word of every frame on the free list, so a double
+/// free can be spotted without walking the whole list every time.
+const FREE_MAGIC: u64 = 0xF4EE_F4A3_E5F4_EE00;
+
+/// Physical frame allocator: hands out frames freed with `deallocate_frame`
+/// first, then bumps through the memory map.
+///
+/// Freed frames form an intrusive stack: each one stores the address of the
+/// next in its first word (through the identity map), so recycling never
+/// touches the heap and works before the heap exists. A recycled frame is
+/// zeroed as it leaves the stack, since it may be mapped into user space.
 pub struct BumpFrameAllocator {
     memory_areas: Option<&'static [MemoryArea]>,
     next_free_frame: usize,
+    total_frames: usize,
+    /// Top of the free stack, most recently freed first.
+    free_list: Option<PhysFrame>,
+    /// Frames currently on the free stack.
+    free_listed: usize,
+    /// Bytes in the memory map's usable areas, summed at `init`.
+    usable_bytes: u64,
+    /// Frames handed out since boot, recycled ones included.
+    handed_out: u64,
+    /// Allocations left before one is made to fail (see `fail_after`).
+    fail_in: Option<usize>,
 }
 
 impl BumpFrameAllocator {
@@ -16,18 +37,32 @@ impl BumpFrameAllocator {
         BumpFrameAllocator {
             memory_areas: None,
             next_free_frame: 0,
+            total_frames: 0,
+            free_list: None,
+            free_listed: 0,
+            usable_bytes: 0,
+            handed_out: 0,
+            fail_in: None,
         }
     }
 
     /// Initialize the allocator with the multiboot memory map.
     pub unsafe fn init(&mut self, memory_areas: &'static [MemoryArea]) {
         self.memory_areas = Some(memory_areas);
+        self.total_frames = self.usable_frames().count();
+        self.usable_bytes = memory_areas.iter()
+            .filter(|r| r.typ() == MemoryAreaType::Available)
+            .map(|r| r.end_address() - r.start_address())
+            .sum();
     }
     
     /// Returns an iterator over the usable memory areas specified in the memory map.
     fn usable_frames(&self) -> impl Iterator<Item = PhysFrame> {
         // get usable areas from memory map
-        let regions = self.memory_areas.unwrap().iter();
+        let Some(areas) = self.memory_areas else {
+            crate::kpanic!("frame allocator used before init");
+        };
+        let regions = areas.iter();
         let usable_regions = regions.filter(|r| r.typ() == MemoryAreaType::Available);
         
         // map each region to its address range
@@ -39,12 +74,112 @@ impl BumpFrameAllocator {
         // Return valid physical frames
         frame_addresses.map(|addr| PhysFrame::containing_address(PhysAddr::new(addr)))
     }
+
+    /// Number of frames currently handed out.
+    pub fn used_frames(&self) -> usize {
+        self.next_free_frame - self.free_listed
+    }
+
+    /// Total number of usable frames in the memory map.
+    pub fn total_frames(&self) -> usize {
+        self.total_frames
+    }
+
+    /// Number of frames that can still be handed out.
+    pub fn free_frames(&self) -> usize {
+        self.total_frames - self.used_frames()
+    }
+
+    /// Bytes of usable physical memory in the memory map (0 before `init`).
+    pub fn usable_bytes(&self) -> u64 {
+        self.usable_bytes
+    }
+
+    /// Frames handed out since boot, including ones handed out again.
+    pub fn frames_handed_out(&self) -> u64 {
+        self.handed_out
+    }
+
+    /// Frames waiting on the free list to be handed out again.
+    pub fn recycled_frames(&self) -> usize {
+        self.free_listed
+    }
+
+    /// Make the allocation `n` allocations from now fail, once, so error
+    /// paths that hold several frames can be tested. `None` disarms it.
+    pub fn fail_after(&mut self, n: Option<usize>) {
+        self.fail_in = n;
+    }
+
+    /// Whether `frame` is on the free list (walks the whole list).
+    fn is_free_listed(&self, frame: PhysFrame) -> bool {
+        let mut cursor = self.free_list;
+        while let Some(f) = cursor {
+            if f == frame {
+                return true;
+            }
+            cursor = unsafe { read_link(f) };
+        }
+        false
+    }
+}
+
+/// The two words a free frame holds: the next free frame (0 = none) and `FREE_MAGIC`.
+fn link_words(frame: PhysFrame) -> *mut u64 {
+    // Physical memory is identity-mapped
+    frame.start_address().as_u64() as *mut u64
+}
+
+unsafe fn read_link(frame: PhysFrame) -> Option<PhysFrame> {
+    match link_words(frame).read() {
+        0 => None,
+        addr => Some(PhysFrame::containing_address(PhysAddr::new(addr))),
+    }
 }
 
 unsafe impl FrameAllocator<Size4KiB> for BumpFrameAllocator {
     fn allocate_frame(&mut self) -> Option<PhysFrame> {
-        let frame = self.usable_frames().nth(self.next_free_frame);
+        if self.memory_areas.is_none() {
+            return None;
+        }
+        match self.fail_in {
+            Some(0) => {
+                self.fail_in = None;
+                return None;
+            }
+            Some(n) => self.fail_in = Some(n - 1),
+            None => {}
+        }
+        if let Some(frame) = self.free_list {
+            unsafe {
+                self.free_list = read_link(frame);
+                // Clears the links and whatever the last owner left behind
+                core::ptr::write_bytes(link_words(frame), 0, 4096 / 8);
+            }
+            self.free_listed -= 1;
+            self.handed_out += 1;
+            return Some(frame);
+        }
+        // Only advance on success, so exhaustion does not push the counter past the end
+        let frame = self.usable_frames().nth(self.next_free_frame)?;
         self.next_free_frame += 1;
-        frame
+        self.handed_out += 1;
+        Some(frame)
+    }
+}
+
+impl FrameDeallocator<Size4KiB> for BumpFrameAllocator {
+    /// Put `frame` on the free list for `allocate_frame` to hand out again.
+    /// Debug builds panic if it is already there.
+    unsafe fn deallocate_frame(&mut self, frame: PhysFrame) {
+        let words = link_words(frame);
+        // The magic alone could be left-over data; only then pay for the walk
+        if cfg!(debug_assertions) && words.add(1).read() == FREE_MAGIC && self.is_free_listed(frame) {
+            crate::kpanic!("frame {:#x} freed twice", frame.start_address().as_u64());
+        }
+        words.write(self.free_list.map_or(0, |f| f.start_address().as_u64()));
+        words.add(1).write(FREE_MAGIC);
+        self.free_list = Some(frame);
+        self.free_listed += 1;
     }
 }
@@ -0,0 +1,92 @@
+// Kernel image layout, read from symbols defined in linker.ld and boot.asm.
+// Only the *addresses* of these symbols are meaningful; they must never be read.
+
+extern "C" {
+    static __kernel_start: u8;
+    static __kernel_end: u8;
+    static __boot_start: u8;
+    static __boot_end: u8;
+    static __text_start: u8;
+    static __text_end: u8;
+    static __rodata_start: u8;
+    static __rodata_end: u8;
+    static __data_start: u8;
+    static __data_end: u8;
+    static __bss_start: u8;
+    static __bss_end: u8;
+
+    // Boot stack reserved in boot.asm (.bss), used by the kernel main thread
+    static stack_bottom: u8;
+    static stack_top: u8;
+}
+
+/// A half-open virtual address range `[start, end)` of the running kernel.
+#[derive(Debug, Clone, Copy)]
+pub struct Region {
+    pub name: &'static str,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Region {
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+}
+
+macro_rules! symbol_addr {
+    ($sym:ident) => {
+        core::ptr::addr_of!($sym) as u64
+    };
+}
+
+/// The kernel image sections, in link order.
+pub fn sections() -> [Region; 5] {
+    [
+        Region { name: ".boot",   start: symbol_addr!(__boot_start),   end: symbol_addr!(__boot_end) },
+        Region { name: ".text",   start: symbol_addr!(__text_start),   end: symbol_addr!(__text_end) },
+        Region { name: ".rodata", start: symbol_addr!(__rodata_start), end: symbol_addr!(__rodata_end) },
+        Region { name: ".data",   start: symbol_addr!(__data_start),   end: symbol_addr!(__data_end) },
+        Region { name: ".bss",    start: symbol_addr!(__bss_start),    end: symbol_addr!(__bss_end) },
+    ]
+}
+
+/// The whole loaded image, from the first section to the end of `.bss`.
+pub fn kernel_image() -> Region {
+    Region { name: "kernel", start: symbol_addr!(__kernel_start), end: symbol_addr!(__kernel_end) }
+}
+
+/// The boot stack the kernel main thread (PID 0) runs on.
+pub fn boot_stack() -> Region {
+    Region { name: "stack", start: symbol_addr!(stack_bottom), end: symbol_addr!(stack_top) }
+}
+
+/// The virtual range reserved for the kernel heap. Only its start is mapped
+/// at boot; the allocator maps the rest as it grows (see `allocator::heap_stats`).
+pub fn heap() -> Region {
+    let start = crate::allocator::HEAP_START as u64;
+    Region { name: "heap", start, end: start + crate::allocator::HEAP_MAX_SIZE as u64 }
+}
+
+/// The virtual range holding kernel task stacks and their guard pages,
+/// mapped a stack at a time (see `scheduler::stack`).
+pub fn task_stacks() -> Region {
+    use crate::scheduler::stack::{STACK_AREA_SIZE, STACK_AREA_START};
+    Region { name: "kstacks", start: STACK_AREA_START, end: STACK_AREA_START + STACK_AREA_SIZE }
+}
+
+/// Physical memory boot.asm identity-maps with 512 2 MiB pages. The kernel
+/// image, the boot stack and every page table and frame the kernel touches
+/// through physical addresses must lie inside it.
+pub const IDENTITY_MAP_SIZE: u64 = 1 << 30;
+
+/// The identity-mapped low memory.
+pub fn identity_map() -> Region {
+    Region { name: "identity", start: 0, end: IDENTITY_MAP_SIZE }
+}
+
+/// Everything the kernel needs mapped in every address space, user ones
+/// included: syscalls and interrupts run on the process's page table.
+pub fn kernel_regions() -> [Region; 5] {
+    [identity_map(), kernel_image(), boot_stack(), heap(), task_stacks()]
+}
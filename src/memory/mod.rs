@@ -1,12 +1,65 @@
 pub mod paging;
 pub mod frame_allocator;
+pub mod shared;
+pub mod layout;
+pub mod demand;
 
 use frame_allocator::BumpFrameAllocator;
-use spin::Mutex;
+use crate::util::irq_mutex::IrqMutex;
 use lazy_static::lazy_static;
 
 lazy_static! {
-    pub static ref FRAME_ALLOCATOR: Mutex<BumpFrameAllocator> = Mutex::new(BumpFrameAllocator::new());
+    /// Held with interrupts off: heap growth and the page fault handler may
+    /// need it from any context, so it must never be left held by a task
+    /// the timer switched out.
+    pub static ref FRAME_ALLOCATOR: IrqMutex<BumpFrameAllocator> = IrqMutex::new(BumpFrameAllocator::new());
+}
+
+/// Physical memory and kernel heap usage, as reported by `free` and `neofetch`.
+/// Everything is zero until the frame allocator and heap are initialized.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemStats {
+    /// Usable physical memory in the Multiboot2 memory map, in bytes.
+    pub total_bytes: u64,
+    pub frames_total: usize,
+    /// Frames currently handed out.
+    pub frames_used: usize,
+    /// Frames handed out since boot, recycled ones included.
+    pub frames_handed_out: u64,
+    /// Heap bytes currently allocated.
+    pub heap_used: usize,
+    /// Heap bytes mapped so far.
+    pub heap_mapped: usize,
+    /// Heap bytes allocated and freed since boot.
+    pub heap_allocated: u64,
+    pub heap_freed: u64,
+}
+
+impl MemStats {
+    pub fn used_bytes(&self) -> u64 {
+        self.frames_used as u64 * 4096
+    }
+
+    pub fn free_bytes(&self) -> u64 {
+        self.total_bytes.saturating_sub(self.used_bytes())
+    }
+}
+
+/// Snapshot of memory usage.
+pub fn stats() -> MemStats {
+    let mut stats = {
+        let frames = FRAME_ALLOCATOR.lock();
+        MemStats {
+            total_bytes: frames.usable_bytes(),
+            frames_total: frames.total_frames(),
+            frames_used: frames.used_frames(),
+            frames_handed_out: frames.frames_handed_out(),
+            ..Default::default()
+        }
+    };
+    (stats.heap_used, stats.heap_mapped) = crate::allocator::heap_stats();
+    (stats.heap_allocated, stats.heap_freed) = crate::allocator::heap_totals();
+    stats
 }
 
 pub fn init(multiboot_info_addr: usize) {
@@ -30,15 +83,18 @@ pub fn init(multiboot_info_addr: usize) {
     
     // Test native single frame allocation visually
     use x86_64::structures::paging::FrameAllocator;
-    let _first_frame = allocator.allocate_frame().unwrap();
+    if allocator.allocate_frame().is_none() {
+        crate::log_error!("Frame allocator: no usable physical memory in the Multiboot2 map!");
+    }
 
-    crate::log_info!("Physical Memory Frame Allocator initialized using Multiboot2 Map.");
+    crate::log_info!("Physical Memory Frame Allocator initialized using Multiboot2 Map ({} frames).", allocator.total_frames());
 
     // Setup Paging
     // In our architecture, the bootloader (boot.asm) identity maps the first 1GB of memory.
     // This allows us to use physical address 0 as virtual address 0.
     use x86_64::VirtAddr;
     let phys_mem_offset = VirtAddr::new(0); // For identity mapping
+    paging::record_kernel_page_table();
     let mut mapper = unsafe { paging::init_paging(phys_mem_offset) };
     crate::log_info!("Virtual Memory Paging subsystem initialized.");
 
@@ -1,10 +1,25 @@
 use x86_64::{
     structures::paging::{
-        FrameAllocator, Mapper, OffsetPageTable, Page, PageTable, PageTableFlags, PhysFrame, Size4KiB
+        FrameAllocator, FrameDeallocator, Mapper, OffsetPageTable, Page, PageTable, PageTableFlags, PhysFrame, Size4KiB
     },
     PhysAddr, VirtAddr,
 };
 
+/// Physical address of the boot page table, which kernel tasks run on.
+static KERNEL_P4: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(0);
+
+/// Remember the active (boot) page table as the kernel's. Called once from `memory::init`.
+pub fn record_kernel_page_table() {
+    use x86_64::registers::control::Cr3;
+    let (p4, _) = Cr3::read();
+    KERNEL_P4.store(p4.start_address().as_u64(), core::sync::atomic::Ordering::Relaxed);
+}
+
+/// The kernel's own page table, which kernel tasks run on.
+pub fn kernel_page_table() -> PhysAddr {
+    PhysAddr::new(KERNEL_P4.load(core::sync::atomic::Ordering::Relaxed))
+}
+
 /// Initialize a new OffsetPageTable.
 pub unsafe fn init_paging(physical_memory_offset: VirtAddr) -> OffsetPageTable<'static> {
     let level_4_table = active_level_4_table(physical_memory_offset);
@@ -24,18 +39,54 @@ unsafe fn active_level_4_table(physical_memory_offset: VirtAddr) -> &'static mut
 }
 
 /// Map a specific virtual page to a physical frame.
+/// Returns false if the page is already mapped or a page table could not be allocated.
 pub fn create_mapping(
     page: Page,
     frame: PhysFrame,
     mapper: &mut impl Mapper<Size4KiB>,
     frame_allocator: &mut impl FrameAllocator<Size4KiB>,
-) {
+) -> bool {
     let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE;
     // Map the page and trigger internal memory allocation tables using the Frame Allocator if missing
     let map_to_result = unsafe {
         mapper.map_to(page, frame, flags, frame_allocator)
     };
-    map_to_result.expect("Map to failed").flush();
+    match map_to_result {
+        Ok(flush) => { flush.flush(); true }
+        Err(_) => false,
+    }
+}
+
+/// Map `[start, start + size_bytes)` of kernel space to fresh frames,
+/// kernel-only and writable, skipping pages that are already mapped. Meant
+/// for areas in a P4 slot every address space shares (see
+/// `layout::kernel_regions`), so the pages appear in every process at once.
+/// Returns false if frames ran out; pages mapped so far stay mapped.
+pub fn map_kernel_memory(start: VirtAddr, size_bytes: u64) -> bool {
+    let mut frame_allocator = crate::memory::FRAME_ALLOCATOR.lock();
+    let mut mapper = unsafe { init_paging(VirtAddr::new(0)) };
+    let first = Page::<Size4KiB>::containing_address(start);
+    let last = Page::containing_address(start + size_bytes - 1u64);
+    for page in Page::range_inclusive(first, last) {
+        if mapper.translate_page(page).is_ok() {
+            continue;
+        }
+        let Some(frame) = frame_allocator.allocate_frame() else {
+            return false;
+        };
+        if !create_mapping(page, frame, &mut mapper, &mut *frame_allocator) {
+            unsafe { frame_allocator.deallocate_frame(frame) };
+            return false;
+        }
+    }
+    true
+}
+
+/// Worst-case number of frames needed to map `size_bytes` of fresh memory:
+/// one per page, plus the P1 tables covering them and a new P2/P3 on each side.
+pub fn frames_needed(size_bytes: u64) -> usize {
+    let pages = size_bytes.div_ceil(4096) as usize;
+    pages + pages.div_ceil(512) + 1 + 2
 }
 
 /// Allocate and map memory for a user program at a specific virtual address.
@@ -47,6 +98,8 @@ pub fn allocate_user_memory(start_addr: VirtAddr, size_bytes: u64) -> bool {
     let phys_mem_offset = VirtAddr::new(0);
     let mut mapper = unsafe { init_paging(phys_mem_offset) };
     let mut frame_allocator = crate::memory::FRAME_ALLOCATOR.lock();
+    // Refuse up front rather than leave a half-mapped region behind
+    if frame_allocator.free_frames() < frames_needed(size_bytes) { return false; }
 
     let start_page = Page::<Size4KiB>::containing_address(start_addr);
     let end_page = Page::<Size4KiB>::containing_address(start_addr + size_bytes - 1u64);
@@ -71,15 +124,55 @@ pub fn allocate_user_memory(start_addr: VirtAddr, size_bytes: u64) -> bool {
     true
 }
 
+/// Index into the P4 table for `addr`.
+fn p4_index(addr: u64) -> usize {
+    ((addr >> 39) & 0x1FF) as usize
+}
+
+/// Index into the P3 table for `addr`.
+fn p3_index(addr: u64) -> usize {
+    ((addr >> 30) & 0x1FF) as usize
+}
+
+/// P4 slots every address space shares outright, and the 1 GiB P3 slots
+/// shared inside P4[0]. User programs also live in P4[0], so that slot gets
+/// a private P3 and is never shared itself.
+fn kernel_slots() -> ([bool; 512], [bool; 512]) {
+    use crate::memory::layout;
+
+    let mut shared_p4 = [false; 512];
+    let mut shared_p3 = [false; 512];
+    shared_p4[256..].fill(true);
+    for region in layout::kernel_regions() {
+        if region.size() == 0 {
+            continue;
+        }
+        let last = region.end - 1;
+        for i in p4_index(region.start)..=p4_index(last) {
+            shared_p4[i] = true;
+        }
+        if p4_index(region.start) == 0 {
+            let last_p3 = if p4_index(last) == 0 { p3_index(last) } else { 511 };
+            shared_p3[p3_index(region.start)..=last_p3].fill(true);
+        }
+    }
+    shared_p4[0] = false;
+    (shared_p4, shared_p3)
+}
+
 /// Create a new isolated Page Table (P4) for a new Process.
-/// It clones the Kernel's higher-half mappings into the new P4, leaving user-space empty.
+/// It shares the kernel's mappings (`layout::kernel_regions` and the higher
+/// half) with the active P4, leaving user space empty.
 pub fn create_new_page_table() -> Option<PhysAddr> {
+    use crate::memory::layout;
+
     let mut frame_allocator = crate::memory::FRAME_ALLOCATOR.lock();
     
     // Allocate a new physical frame for the P4 table
     let p4_frame = frame_allocator.allocate_frame()?;
     
     let phys_mem_offset = VirtAddr::new(0);
+    let (shared_p4, shared_p3) = kernel_slots();
     
     unsafe {
         // Zero out the new P4
@@ -94,23 +187,22 @@ pub fn create_new_page_table() -> Option<PhysAddr> {
         let new_p4 = &mut *p4_virt.as_mut_ptr::<PageTable>();
         let active_p4 = &*active_p4_virt.as_ptr::<PageTable>();
         
-        // We only copy indices that correspond to Kernel space (higher half).
-        // Since we are identity mapping for now (lower half = kernel), we'll do an exact copy
-        // for indices EXCEPT the ones where the Elf loader places User code (e.g., 0x80000000 -> index 1)
-        // For simplicity in Phase 5.3, we clone *everything* except explicitly cleared user regions.
-        for i in 256..512 {
+        for i in (0..512).filter(|&i| shared_p4[i]) {
             new_p4[i] = active_p4[i].clone();
         }
         
-        // Also clone Kernel Heap Mapping (which AtomicOS placed at index 136)
-        new_p4[136] = active_p4[136].clone();
-        
-        // Hack for Phase 5.3 Identity Mapping preservation:
-        // We cannot just clone `active_p4[0]` because that shares the P3 table.
-        // User space loads at `0x80000000` (P3 index 2). If we share P3, we break isolation!
-        // Instead, we allocate a NEW P3 table, and copy only the kernel's portion (P3 index 0).
-        if active_p4[0].flags().contains(x86_64::structures::paging::PageTableFlags::PRESENT) {
-            let p3_frame = frame_allocator.allocate_frame()?;
+        // We cannot just clone `active_p4[0]`: sharing its P3 would make user
+        // mappings (ELF segments, stack, mmap) visible in every address space.
+        // Allocate a NEW P3 holding only the kernel's slots.
+        if active_p4[0].flags().contains(PageTableFlags::PRESENT) {
+            let p3_frame = match frame_allocator.allocate_frame() {
+                Some(f) => f,
+                None => {
+                    // The half-built P4 is not referenced anywhere yet
+                    frame_allocator.deallocate_frame(p4_frame);
+                    return None;
+                }
+            };
             let p3_virt = phys_mem_offset + p3_frame.start_address().as_u64();
             core::ptr::write_bytes(p3_virt.as_mut_ptr::<u8>(), 0, 4096);
             let new_p3 = &mut *p3_virt.as_mut_ptr::<PageTable>();
@@ -118,16 +210,30 @@ pub fn create_new_page_table() -> Option<PhysAddr> {
             let active_p3_virt = phys_mem_offset + active_p4[0].addr().as_u64();
             let active_p3 = &*active_p3_virt.as_ptr::<PageTable>();
 
-            // Copy only the first 2 GB (P3 index 0 and 1) representing the kernel's lower mapping
-            new_p3[0] = active_p3[0].clone();
-            new_p3[1] = active_p3[1].clone();
+            for i in (0..512).filter(|&i| shared_p3[i]) {
+                new_p3[i] = active_p3[i].clone();
+            }
             
             // Set P4[0] to point to the isolated P3. MUST include USER_ACCESSIBLE because
             // User Space lives in P4[0] -> P3[2] -> P2 etc.
             let mut flags = active_p4[0].flags();
-            flags.insert(x86_64::structures::paging::PageTableFlags::USER_ACCESSIBLE);
+            flags.insert(PageTableFlags::USER_ACCESSIBLE);
             new_p4[0].set_addr(p3_frame.start_address(), flags);
         }
+
+        // The new table must reach the kernel exactly as the active one does,
+        // or the first syscall or interrupt in the new process faults
+        use x86_64::structures::paging::Translate;
+        let active = OffsetPageTable::new(&mut *active_p4_virt.as_mut_ptr::<PageTable>(), phys_mem_offset);
+        let cloned = OffsetPageTable::new(new_p4, phys_mem_offset);
+        let heap_base = VirtAddr::new(layout::heap().start);
+        let kernel_code = VirtAddr::new(create_new_page_table as *const () as u64);
+        for addr in [heap_base, kernel_code] {
+            crate::kassert!(
+                cloned.translate_addr(addr).is_some() && cloned.translate_addr(addr) == active.translate_addr(addr),
+                "new page table maps kernel address {:#x} differently", addr.as_u64()
+            );
+        }
     }
     
     Some(p4_frame.start_address())
@@ -139,6 +245,8 @@ pub fn allocate_process_memory(mapper: &mut OffsetPageTable, start_addr: VirtAdd
     if size_bytes == 0 { return true; }
 
     let mut frame_allocator = crate::memory::FRAME_ALLOCATOR.lock();
+    // Refuse up front rather than leave a half-mapped region behind
+    if frame_allocator.free_frames() < frames_needed(size_bytes) { return false; }
 
     let start_page = Page::<Size4KiB>::containing_address(start_addr);
     let end_page = Page::<Size4KiB>::containing_address(start_addr + size_bytes - 1u64);
@@ -155,13 +263,27 @@ pub fn allocate_process_memory(mapper: &mut OffsetPageTable, start_addr: VirtAdd
         unsafe {
             match mapper.map_to(page, frame, flags, &mut *frame_allocator) {
                 Ok(flush) => flush.flush(),
-                Err(_) => return false,
+                Err(_) => {
+                    // Not mapped, so nobody else will free it
+                    frame_allocator.deallocate_frame(frame);
+                    return false;
+                }
             }
         }
     }
     true
 }
 
+/// Allocate and map memory into the address space rooted at `p4_addr` (used by `brk`).
+pub fn map_user_memory(p4_addr: PhysAddr, start_addr: VirtAddr, size_bytes: u64) -> bool {
+    let phys_mem_offset = VirtAddr::new(0);
+    let mut mapper = unsafe {
+        let p4 = &mut *(phys_mem_offset + p4_addr.as_u64()).as_mut_ptr::<PageTable>();
+        OffsetPageTable::new(p4, phys_mem_offset)
+    };
+    allocate_process_memory(&mut mapper, start_addr, size_bytes)
+}
+
 /// Free virtual user memory space back into the void (Cleanup for Exit).
 pub fn free_user_memory(start_addr: VirtAddr, size_bytes: u64) {
     use x86_64::structures::paging::{Page, Mapper};
@@ -172,76 +294,308 @@ pub fn free_user_memory(start_addr: VirtAddr, size_bytes: u64) {
     let start_page = Page::<Size4KiB>::containing_address(start_addr);
     let end_page = Page::<Size4KiB>::containing_address(start_addr + size_bytes - 1u64);
     
+    let mut frame_allocator = crate::memory::FRAME_ALLOCATOR.lock();
     for page in Page::range_inclusive(start_page, end_page) {
-        if let Ok((_frame, flush)) = mapper.unmap(page) {
+        if let Ok((frame, flush)) = mapper.unmap(page) {
             flush.flush();
-            // Note: In a real system, we'd also flag the _frame as free in the Bitmap Allocator.
-            // Since we're using a Bump Allocator, physical frame recycling isn't fully supported yet,
-            // but the Virtual Memory space is correctly unmapped and TLB flushed!
+            // Private pages have exactly one owner: the frame is free now
+            unsafe { frame_allocator.deallocate_frame(frame); }
         }
     }
 }
 
-/// Helper for `fork` syscall: Clones memory blocks mapped in the Parent's P4 into a brand new Child P4.
-pub fn deep_clone_process_memory(
-    child_p4_addr: PhysAddr,
-    allocations: &alloc::vec::Vec<(u64, u64)>
-) -> bool {
+/// Return a discarded address space's page tables to the frame allocator:
+/// the P4, its private P3 and every P3/P2/P1 table under the user slots.
+/// The user pages must already be unmapped (`free_user_memory`,
+/// `free_shared_memory`), and the table must not be loaded in CR3. The
+/// kernel's table, which kernel tasks run on, is never freed.
+pub fn free_page_table(p4_addr: PhysAddr) {
     use x86_64::registers::control::Cr3;
-    use x86_64::structures::paging::{PageTableFlags, Page, Mapper, Translate};
 
-    let phys_mem_offset = VirtAddr::new(0);
-    // Active mapper (Parent)
-    let mut parent_mapper = unsafe { init_paging(phys_mem_offset) };
-    
-    // Switch to child temporarily to allocate frames
-    let (old_p4, flags) = Cr3::read();
-    unsafe {
-        Cr3::write(PhysFrame::containing_address(child_p4_addr), flags);
+    if p4_addr == kernel_page_table() {
+        return;
     }
-    
-    let mut child_mapper = unsafe { init_paging(phys_mem_offset) };
-    
-    for (start_vaddr, size) in allocations {
-        if !allocate_process_memory(&mut child_mapper, VirtAddr::new(*start_vaddr), *size) {
-            unsafe { Cr3::write(old_p4, flags); }
-            return false;
+    let (active, _) = Cr3::read();
+    if active.start_address() == p4_addr {
+        crate::log_error!("free_page_table: {:#x} is the active page table", p4_addr.as_u64());
+        return;
+    }
+
+    let (shared_p4, shared_p3) = kernel_slots();
+    let table_at = |addr: PhysAddr| unsafe { &*(addr.as_u64() as *const PageTable) };
+    let owned = |flags: PageTableFlags| flags.contains(PageTableFlags::PRESENT) && !flags.contains(PageTableFlags::HUGE_PAGE);
+    let mut frame_allocator = crate::memory::FRAME_ALLOCATOR.lock();
+    let mut free = |addr: PhysAddr| unsafe {
+        frame_allocator.deallocate_frame(PhysFrame::containing_address(addr));
+    };
+
+    let p4 = table_at(p4_addr);
+    for i in (0..512).filter(|&i| !shared_p4[i]) {
+        if !owned(p4[i].flags()) { continue; }
+        let p3 = table_at(p4[i].addr());
+        // Only P4[0] holds kernel P3 slots, and those stay
+        for j in (0..512).filter(|&j| i != 0 || !shared_p3[j]) {
+            if !owned(p3[j].flags()) { continue; }
+            let p2 = table_at(p3[j].addr());
+            for entry in p2.iter().filter(|e| owned(e.flags())) {
+                free(entry.addr());
+            }
+            free(p3[j].addr());
         }
+        free(p4[i].addr());
     }
-    
-    // Switch back to parent to read from User Space
-    unsafe {
-        Cr3::write(old_p4, flags);
+    free(p4_addr);
+}
+
+/// Throw away an address space that never ran, after a failed fork or
+/// exec: free the frames mapped in its `private` regions, then its page
+/// tables. MAP_SHARED frames mapped into it are left alone, since no
+/// references were taken on them. It must not be loaded in CR3.
+pub fn discard_address_space(p4_addr: PhysAddr, private: &[(u64, u64)]) {
+    use x86_64::structures::paging::{Page, Mapper};
+
+    let phys_mem_offset = VirtAddr::new(0);
+    let mut mapper = unsafe {
+        let p4 = &mut *(phys_mem_offset + p4_addr.as_u64()).as_mut_ptr::<PageTable>();
+        OffsetPageTable::new(p4, phys_mem_offset)
+    };
+    let mut frame_allocator = crate::memory::FRAME_ALLOCATOR.lock();
+    for &(start, size) in private.iter().filter(|(_, size)| *size > 0) {
+        let start_page = Page::<Size4KiB>::containing_address(VirtAddr::new(start));
+        let end_page = Page::<Size4KiB>::containing_address(VirtAddr::new(start + size - 1));
+        for page in Page::range_inclusive(start_page, end_page) {
+            if let Ok((frame, flush)) = mapper.unmap(page) {
+                // Not the active table: nothing of it is in the TLB
+                flush.ignore();
+                unsafe { frame_allocator.deallocate_frame(frame); }
+            }
+        }
     }
-    
-    // Now, for every allocated page, we must copy data from parent virtual address
-    // to child's physical frame. Since identity mapping covers all physical memory (0 offset)
-    // we can write directly to the physical frames mapped by the child!
+    drop(frame_allocator);
+    free_page_table(p4_addr);
+}
+
+/// Allocate zeroed, user-writable memory in the active address space whose
+/// frames are refcounted so a later `fork` can share them (MAP_SHARED).
+pub fn allocate_shared_memory(start_addr: VirtAddr, size_bytes: u64) -> bool {
+    use x86_64::structures::paging::{PageTableFlags, Page, Mapper};
+    if size_bytes == 0 { return true; }
+
+    let phys_mem_offset = VirtAddr::new(0);
+    let mut mapper = unsafe { init_paging(phys_mem_offset) };
+    let mut frame_allocator = crate::memory::FRAME_ALLOCATOR.lock();
+    // Refuse up front rather than leave a half-mapped region behind
+    if frame_allocator.free_frames() < frames_needed(size_bytes) { return false; }
+
+    let start_page = Page::<Size4KiB>::containing_address(start_addr);
+    let end_page = Page::<Size4KiB>::containing_address(start_addr + size_bytes - 1u64);
+
+    let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::USER_ACCESSIBLE;
+
+    for page in Page::range_inclusive(start_page, end_page) {
+        let frame = match frame_allocator.allocate_frame() {
+            Some(f) => f,
+            None => return false,
+        };
+
+        unsafe {
+            // Frames come straight from the bump allocator: scrub them before user code sees them
+            core::ptr::write_bytes((phys_mem_offset + frame.start_address().as_u64()).as_mut_ptr::<u8>(), 0, 4096);
+            match mapper.map_to(page, frame, flags, &mut *frame_allocator) {
+                Ok(flush) => flush.flush(),
+                Err(_) => {
+                    frame_allocator.deallocate_frame(frame);
+                    return false;
+                }
+            }
+        }
+        crate::memory::shared::register(frame);
+    }
+    true
+}
+
+/// Helper for `fork` syscall: map the parent's MAP_SHARED regions into the child's P4
+/// using the *same* physical frames, bumping each frame's refcount instead of copying.
+pub fn share_process_memory(
+    child_p4_addr: PhysAddr,
+    regions: &alloc::vec::Vec<(u64, u64)>
+) -> bool {
+    use x86_64::structures::paging::{PageTableFlags, Page, Mapper};
+
+    let phys_mem_offset = VirtAddr::new(0);
+    // Active mapper (Parent)
+    let parent_mapper = unsafe { init_paging(phys_mem_offset) };
+    // Sized before taking the frame allocator: growing the heap needs it
+    let pages: usize = regions.iter().map(|(_, size)| size.div_ceil(4096) as usize + 1).sum();
+    let mut shared_frames = alloc::vec::Vec::with_capacity(pages);
+    let mut frame_allocator = crate::memory::FRAME_ALLOCATOR.lock();
+
+    let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::USER_ACCESSIBLE;
+
     unsafe {
-        // Build child mapper again (virtually but pointing to child's P4 physical address manually)
         let child_p4_virt = phys_mem_offset + child_p4_addr.as_u64();
         let child_page_table = &mut *child_p4_virt.as_mut_ptr::<PageTable>();
-        let child_mapper_offset = OffsetPageTable::new(child_page_table, phys_mem_offset);
+        let mut child_mapper = OffsetPageTable::new(child_page_table, phys_mem_offset);
 
-        for (start_vaddr, size) in allocations {
+        for (start_vaddr, size) in regions {
             let start_page = Page::<Size4KiB>::containing_address(VirtAddr::new(*start_vaddr));
             let end_page = Page::<Size4KiB>::containing_address(VirtAddr::new(*start_vaddr + *size - 1));
-            
+
             for page in Page::range_inclusive(start_page, end_page) {
-                // Address in Parent Space (Source)
-                let parent_ptr = page.start_address().as_ptr::<u8>();
-                
-                // Get the physical frame the Child allocated for this page
-                if let Ok(child_phys_frame) = child_mapper_offset.translate_page(page) {
-                    // The identity map gives us direct access to any physical memory.
-                    let target_ptr = (phys_mem_offset + child_phys_frame.start_address().as_u64()).as_mut_ptr::<u8>();
-                    
-                    // Deep copy 4096 bytes
-                    core::ptr::copy_nonoverlapping(parent_ptr, target_ptr, 4096);
+                let frame = match parent_mapper.translate_page(page) {
+                    Ok(f) => f,
+                    Err(_) => return false,
+                };
+                // The child's P4 is not active: no TLB entry to flush
+                match child_mapper.map_to(page, frame, flags, &mut *frame_allocator) {
+                    Ok(flush) => flush.ignore(),
+                    Err(_) => return false,
                 }
+                shared_frames.push(frame);
             }
         }
     }
 
+    // Only take the references once everything is mapped, so a failed fork
+    // does not leave the counts inflated
+    for frame in shared_frames {
+        crate::memory::shared::acquire(frame);
+    }
     true
 }
+
+/// Unmap a MAP_SHARED region from the active address space, dropping one
+/// reference per frame. Frames are only released once their last user is gone.
+pub fn free_shared_memory(start_addr: VirtAddr, size_bytes: u64) {
+    use x86_64::structures::paging::{Page, Mapper};
+    let phys_mem_offset = VirtAddr::new(0);
+    let mut mapper = unsafe { init_paging(phys_mem_offset) };
+
+    let start_page = Page::<Size4KiB>::containing_address(start_addr);
+    let end_page = Page::<Size4KiB>::containing_address(start_addr + size_bytes - 1u64);
+
+    for page in Page::range_inclusive(start_page, end_page) {
+        if let Ok((frame, flush)) = mapper.unmap(page) {
+            flush.flush();
+            if crate::memory::shared::release(frame) {
+                unsafe { crate::memory::FRAME_ALLOCATOR.lock().deallocate_frame(frame); }
+            }
+        }
+    }
+}
+
+/// Helper for `fork` syscall: Clones memory blocks mapped in the Parent's P4 into a brand new Child P4.
+/// Only pages present in the parent are copied: program image pages it
+/// never touched stay unloaded in the child too, which loads them from the
+/// same image on demand (see `demand`).
+pub fn deep_clone_process_memory(
+    child_p4_addr: PhysAddr,
+    allocations: &alloc::vec::Vec<(u64, u64)>
+) -> bool {
+    use x86_64::structures::paging::{PageTableFlags, Page, Mapper};
+
+    // The identity map gives us direct access to any physical memory, so the
+    // child's tables and frames can be filled without switching to it
+    let phys_mem_offset = VirtAddr::new(0);
+    let parent_mapper = unsafe { init_paging(phys_mem_offset) };
+    let mut child_mapper = unsafe {
+        let child_p4 = &mut *(phys_mem_offset + child_p4_addr.as_u64()).as_mut_ptr::<PageTable>();
+        OffsetPageTable::new(child_p4, phys_mem_offset)
+    };
+    let mut frame_allocator = crate::memory::FRAME_ALLOCATOR.lock();
+    let flags = PageTableFlags::PRESENT | PageTableFlags::WRITABLE | PageTableFlags::USER_ACCESSIBLE;
+
+    for (start_vaddr, size) in allocations {
+        let start_page = Page::<Size4KiB>::containing_address(VirtAddr::new(*start_vaddr));
+        let end_page = Page::<Size4KiB>::containing_address(VirtAddr::new(*start_vaddr + *size - 1));
+
+        for page in Page::range_inclusive(start_page, end_page) {
+            if parent_mapper.translate_page(page).is_err() {
+                continue;
+            }
+            let Some(frame) = frame_allocator.allocate_frame() else {
+                return false;
+            };
+            unsafe {
+                match child_mapper.map_to(page, frame, flags, &mut *frame_allocator) {
+                    // Not the active table: nothing of it is in the TLB
+                    Ok(flush) => flush.ignore(),
+                    Err(_) => {
+                        // Not mapped, so `discard_address_space` won't find it
+                        frame_allocator.deallocate_frame(frame);
+                        return false;
+                    }
+                }
+                // Deep copy 4096 bytes from the parent's page into the child's frame
+                let target_ptr = (phys_mem_offset + frame.start_address().as_u64()).as_mut_ptr::<u8>();
+                core::ptr::copy_nonoverlapping(page.start_address().as_ptr::<u8>(), target_ptr, 4096);
+            }
+        }
+    }
+
+    true
+}
+
+/// Highest canonical lower-half address + 1; user pointers must stay below it.
+pub const USER_SPACE_END: u64 = 0x0000_8000_0000_0000;
+
+/// Check that every page of `[start, start + len)` is present and reachable
+/// from Ring 3 in the active address space (and writable, if `write`).
+/// Walks the tables by hand so huge pages and per-level USER/WRITABLE bits
+/// are honoured exactly as the CPU would. Pages of the program image that
+/// were never touched are loaded first, as the CPU's fault would (see `demand`).
+pub fn user_range_accessible(start: u64, len: u64, write: bool) -> bool {
+    use x86_64::registers::control::Cr3;
+
+    if len == 0 { return true; }
+    let end = match start.checked_add(len) {
+        Some(e) if e <= USER_SPACE_END => e,
+        _ => return false,
+    };
+
+    let mut required = PageTableFlags::PRESENT | PageTableFlags::USER_ACCESSIBLE;
+    if write { required |= PageTableFlags::WRITABLE; }
+    let (p4_frame, _) = Cr3::read();
+    // With interrupts on, whoever holds the locks `load_page` needs can run and release them
+    let blocking = x86_64::instructions::interrupts::are_enabled();
+
+    let mut addr = start & !0xFFF;
+    while addr < end {
+        let page_size = match page_access(p4_frame.start_address(), addr, required) {
+            Some(size) => size,
+            None if crate::memory::demand::load_page(addr, blocking) => 4096,
+            None => return false,
+        };
+        addr = (addr & !(page_size - 1)) + page_size;
+    }
+    true
+}
+
+/// Size of the page mapping `addr` in the tables at `p4`, if every level
+/// grants `required`.
+fn page_access(p4: PhysAddr, addr: u64, required: PageTableFlags) -> Option<u64> {
+    use x86_64::structures::paging::PageTableIndex;
+
+    let phys_mem_offset = VirtAddr::new(0);
+    let table_at = |addr: PhysAddr| unsafe { &*(phys_mem_offset + addr.as_u64()).as_ptr::<PageTable>() };
+    let virt = VirtAddr::new(addr);
+    let indices: [PageTableIndex; 4] = [virt.p4_index(), virt.p3_index(), virt.p2_index(), virt.p1_index()];
+    let mut table = table_at(p4);
+
+    for (level, index) in indices.iter().enumerate() {
+        let entry = &table[*index];
+        if !entry.flags().contains(required) { return None; }
+        // A huge entry at P3 (1 GiB) or P2 (2 MiB) is the leaf
+        if entry.flags().contains(PageTableFlags::HUGE_PAGE) {
+            return match level {
+                1 => Some(1 << 30),
+                2 => Some(1 << 21),
+                _ => None,
+            };
+        }
+        if level < 3 {
+            table = table_at(entry.addr());
+        }
+    }
+    Some(4096)
+}
@@ -0,0 +1,41 @@
+use alloc::collections::BTreeMap;
+use spin::Mutex;
+use lazy_static::lazy_static;
+use x86_64::structures::paging::{PhysFrame, Size4KiB};
+
+lazy_static! {
+    /// Number of address spaces mapping each MAP_SHARED frame, keyed by physical address.
+    /// Private frames are never listed here: they always have exactly one owner.
+    static ref SHARED_FRAMES: Mutex<BTreeMap<u64, usize>> = Mutex::new(BTreeMap::new());
+}
+
+/// Start tracking a freshly allocated shared frame with a single user.
+pub fn register(frame: PhysFrame<Size4KiB>) {
+    SHARED_FRAMES.lock().insert(frame.start_address().as_u64(), 1);
+}
+
+/// Another address space maps `frame` (fork). Returns the new count.
+pub fn acquire(frame: PhysFrame<Size4KiB>) -> usize {
+    let mut frames = SHARED_FRAMES.lock();
+    let count = frames.entry(frame.start_address().as_u64()).or_insert(0);
+    *count += 1;
+    *count
+}
+
+/// An address space dropped its mapping of `frame`.
+/// Returns true if that was the last user and the frame may be freed.
+pub fn release(frame: PhysFrame<Size4KiB>) -> bool {
+    let mut frames = SHARED_FRAMES.lock();
+    let addr = frame.start_address().as_u64();
+    match frames.get_mut(&addr) {
+        Some(count) if *count > 1 => {
+            *count -= 1;
+            false
+        }
+        Some(_) => {
+            frames.remove(&addr);
+            true
+        }
+        None => true,
+    }
+}
@@ -0,0 +1,66 @@
+use x86_64::instructions::port::Port;
+
+/// Hypervisor power-off ports and the value that triggers them: QEMU (q35
+/// and newer PIIX setups), Bochs and older QEMU, VirtualBox.
+const POWEROFF_PORTS: [(u16, u16); 3] = [(0x604, 0x2000), (0xB004, 0x2000), (0x4004, 0x3400)];
+
+/// 8042 keyboard controller command/status port, and its input-buffer-full bit.
+const KBC_PORT: u16 = 0x64;
+const KBC_INPUT_FULL: u8 = 0x02;
+/// Polls of the 8042 status before giving up waiting for it to take a command.
+const KBC_WAIT_POLLS: u32 = 100_000;
+
+/// Flush and unmount filesystems before the machine goes away. Errors are
+/// logged but do not stop the shutdown: there is nothing better to do with
+/// them at this point.
+fn prepare() {
+    if let Err(e) = crate::fs::unmount_all() {
+        crate::log_error!("power: sync failed: {}", e);
+    }
+}
+
+/// Flush all filesystems and reset the machine through the 8042 keyboard
+/// controller, or halt if it doesn't respond.
+pub fn reboot() -> ! {
+    prepare();
+    crate::log_info!("Rebooting...");
+    x86_64::instructions::interrupts::disable();
+    unsafe {
+        let mut status = Port::<u8>::new(KBC_PORT);
+        for _ in 0..KBC_WAIT_POLLS {
+            if status.read() & KBC_INPUT_FULL == 0 {
+                break;
+            }
+        }
+        // Pulse the CPU reset line
+        Port::<u8>::new(KBC_PORT).write(0xFE);
+    }
+    crate::log_warn!("Reset did not happen; halting instead.");
+    halt()
+}
+
+/// Flush all filesystems and power off through the first hypervisor port
+/// that works; on real hardware (or an unknown emulator) just halt.
+pub fn poweroff() -> ! {
+    prepare();
+    crate::log_info!("Powering off...");
+    x86_64::instructions::interrupts::disable();
+    for (port, value) in POWEROFF_PORTS {
+        unsafe { Port::<u16>::new(port).write(value) };
+    }
+    crate::log_warn!("No power-off port available; halting instead.");
+    halt()
+}
+
+/// Flush all filesystems and stop the CPU, leaving the machine on.
+pub fn shutdown_halt() -> ! {
+    prepare();
+    crate::log_info!("System halted.");
+    halt()
+}
+
+/// Stop the CPU for good (interrupts off).
+pub fn halt() -> ! {
+    x86_64::instructions::interrupts::disable();
+    loop { x86_64::instructions::hlt(); }
+}
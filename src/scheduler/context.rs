@@ -1,4 +1,5 @@
 use core::arch::naked_asm;
+use super::fpu::FpuState;
 
 /// CPU register context saved/restored during context switches.
 /// All callee-saved registers on x86_64 System V ABI.
@@ -47,13 +48,20 @@ impl Context {
 }
 
 /// Switch context from `old` to `new`.
-/// Saves callee-saved registers into `old`, restores from `new`.
+/// Saves callee-saved registers into `old`, restores from `new`. The whole
+/// FPU/SSE state is saved eagerly into `old_fpu` and reloaded from `new_fpu`,
+/// since none of it is preserved by the calling convention for another task.
 ///
 /// # Safety
-/// Both pointers must be valid Context structs with valid stack pointers.
+/// Both pointers must be valid Context structs with valid stack pointers;
+/// both FPU areas must be valid and 16-byte aligned.
 #[unsafe(naked)]
-pub unsafe extern "C" fn switch_context(old: *mut Context, new: *const Context) {
+pub unsafe extern "C" fn switch_context(old: *mut Context, new: *const Context, old_fpu: *mut FpuState, new_fpu: *const FpuState) {
     naked_asm!(
+        // Swap FPU/SSE state (rdx = old_fpu, rcx = new_fpu)
+        "fxsave64 [rdx]",
+        "fxrstor64 [rcx]",
+
         // Save callee-saved registers into `old` (rdi = old ptr)
         "mov [rdi + 0x00], rsp",
         "mov [rdi + 0x08], rbp",
@@ -87,10 +95,12 @@ pub unsafe extern "C" fn switch_context(old: *mut Context, new: *const Context)
 /// Restore context without saving (used when current task is dead).
 ///
 /// # Safety
-/// The context pointer must be valid.
+/// The context pointer must be valid; `new_fpu` must be valid and 16-byte aligned.
 #[unsafe(naked)]
-pub unsafe extern "C" fn restore_context(new: *const Context) {
+pub unsafe extern "C" fn restore_context(new: *const Context, new_fpu: *const FpuState) {
     naked_asm!(
+        // rsi = new FPU area
+        "fxrstor64 [rsi]",
         // rdi = new context ptr
         "mov rsp, [rdi + 0x00]",
         "mov rbp, [rdi + 0x08]",
@@ -0,0 +1,54 @@
+use x86_64::registers::control::{Cr0, Cr0Flags, Cr4, Cr4Flags};
+
+/// x87 control word after FNINIT: all exceptions masked, 64-bit precision.
+const DEFAULT_FCW: u16 = 0x037F;
+/// MXCSR at reset: all SSE exceptions masked, round to nearest.
+pub const DEFAULT_MXCSR: u32 = 0x1F80;
+
+/// FXSAVE/FXRSTOR image of the x87, MMX and SSE registers (512 bytes,
+/// 16-byte aligned as the instructions require). Each process owns one,
+/// saved on switch-out and restored on switch-in by `context::switch_context`.
+/// The syscall entry keeps its own copy of the user's registers on the
+/// kernel stack (`interrupts::usermode::saved_fpu`).
+#[repr(C, align(16))]
+#[derive(Clone)]
+pub struct FpuState([u8; 512]);
+
+const _: () = assert!(core::mem::size_of::<FpuState>() == 512);
+
+impl FpuState {
+    /// A freshly initialized FPU: as after FNINIT, with the default MXCSR
+    /// and all XMM registers zero.
+    pub fn new() -> Self {
+        let mut area = [0u8; 512];
+        area[0..2].copy_from_slice(&DEFAULT_FCW.to_le_bytes());
+        area[24..28].copy_from_slice(&DEFAULT_MXCSR.to_le_bytes());
+        FpuState(area)
+    }
+}
+
+impl Default for FpuState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Make x87/SSE usable at CPL 0 and 3 with exceptions reported natively.
+/// The boot code already sets most of this; doing it here keeps the
+/// requirements of `FpuState` in one place. Call once, before the first task switch.
+pub fn init() {
+    unsafe {
+        let mut cr0 = Cr0::read();
+        cr0.remove(Cr0Flags::EMULATE_COPROCESSOR | Cr0Flags::TASK_SWITCHED);
+        cr0.insert(Cr0Flags::MONITOR_COPROCESSOR | Cr0Flags::NUMERIC_ERROR);
+        Cr0::write(cr0);
+
+        let mut cr4 = Cr4::read();
+        cr4.insert(Cr4Flags::OSFXSR | Cr4Flags::OSXMMEXCPT_ENABLE);
+        Cr4::write(cr4);
+
+        core::arch::asm!("fninit", options(nomem, nostack));
+        let mxcsr = DEFAULT_MXCSR;
+        core::arch::asm!("ldmxcsr [{}]", in(reg) &mxcsr, options(nostack, readonly));
+    }
+}
@@ -0,0 +1,115 @@
+use core::fmt;
+use x86_64::instructions::interrupts;
+use super::{ProcessId, ProcessState, PRIORITY_NORMAL, SCHEDULER};
+
+/// Why `join` or `detach` refused a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinError {
+    /// No task with that PID (or it was already joined and reaped).
+    NoSuchTask,
+    /// The task was not spawned with `spawn_joinable`, or has been detached.
+    NotJoinable,
+    /// Another task is already joining it.
+    AlreadyJoined,
+    /// A task cannot join itself.
+    Deadlock,
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JoinError::NoSuchTask    => write!(f, "no such task"),
+            JoinError::NotJoinable   => write!(f, "task is not joinable"),
+            JoinError::AlreadyJoined => write!(f, "task is already being joined"),
+            JoinError::Deadlock      => write!(f, "task cannot join itself"),
+        }
+    }
+}
+
+/// Spawn a kernel task at normal priority that any other task can `join`.
+/// Unlike `spawn`, its zombie stays around until joined or `detach`ed.
+pub fn spawn_joinable(entry: fn(), name: &str) -> ProcessId {
+    // Marked under the same lock, so it cannot end (and be reaped) before
+    let mut sched = SCHEDULER.lock();
+    let id = sched.spawn(entry, name, PRIORITY_NORMAL);
+    if let Some(task) = sched.ready_queue.iter_mut().find(|p| p.pid == id) {
+        task.joinable = true;
+    }
+    id
+}
+
+/// Block until joinable task `pid` has ended, reap it and return its wait
+/// status (see `syscalls::wait`). Returns at once if it has already ended.
+///
+/// The first task to join claims the target: any other `join` on it fails
+/// with `AlreadyJoined`, so only the claimant ever reaps it.
+pub fn join(pid: ProcessId) -> Result<u64, JoinError> {
+    loop {
+        let mut guard = SCHEDULER.lock();
+        let sched = &mut *guard;
+        let me = sched.current.as_ref().map_or(ProcessId(0), |p| p.pid);
+        if pid == me {
+            return Err(JoinError::Deadlock);
+        }
+
+        let i = sched.ready_queue.iter().position(|p| p.pid == pid).ok_or(JoinError::NoSuchTask)?;
+        let target = &mut sched.ready_queue[i];
+        if !target.joinable {
+            return Err(JoinError::NotJoinable);
+        }
+        match target.joined_by {
+            Some(joiner) if joiner != me => return Err(JoinError::AlreadyJoined),
+            _ => target.joined_by = Some(me),
+        }
+
+        if target.state == ProcessState::Zombie {
+            let status = target.exit_status.unwrap_or(0);
+            if let Some(zombie) = sched.ready_queue.remove(i) {
+                crate::memory::paging::free_page_table(x86_64::PhysAddr::new(zombie.page_table));
+            }
+            return Ok(status);
+        }
+
+        // Still running: sleep until it ends and wakes us (see `terminate_current`)
+        if let Some(current) = sched.current.as_mut() {
+            current.state = ProcessState::Blocked;
+        }
+        drop(guard);
+
+        interrupts::enable();
+        x86_64::instructions::hlt();
+        super::yield_now();
+    }
+}
+
+/// Let joinable task `pid` be reaped by init when it ends, like a task from
+/// `spawn`. A task that already ended is handed over right away.
+pub fn detach(pid: ProcessId) -> Result<(), JoinError> {
+    let init_pid = super::reaper::INIT_PID;
+    interrupts::without_interrupts(|| {
+        let mut guard = SCHEDULER.lock();
+        let sched = &mut *guard;
+        let target = sched.ready_queue.iter_mut()
+            .find(|p| p.pid == pid)
+            .ok_or(JoinError::NoSuchTask)?;
+        if !target.joinable {
+            return Err(JoinError::NotJoinable);
+        }
+        if target.joined_by.is_some() {
+            return Err(JoinError::AlreadyJoined);
+        }
+
+        target.joinable = false;
+        if target.state != ProcessState::Zombie {
+            return Ok(());
+        }
+        target.parent_pid = Some(init_pid);
+        if let Some(init) = sched.ready_queue.iter_mut().find(|p| p.pid == init_pid) {
+            init.children.push(pid);
+            if init.state == ProcessState::Blocked {
+                init.state = ProcessState::Ready;
+            }
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,89 @@
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicU64, Ordering};
+use super::ProcessId;
+
+/// Owner value while the lock is free.
+const NO_OWNER: u64 = u64::MAX;
+
+/// A spin mutex that remembers which task holds it.
+///
+/// Plain `spin::Mutex` is unsafe to hold across a preemption: if the timer
+/// switches away from the holder, every other task that wants the lock burns
+/// its whole quantum spinning while the holder waits at the back of the ready
+/// queue. When an `InheritMutex` is contended, the waiter instead donates its
+/// CPU time to the holder (`yield_to`), so the holder runs at the waiter's
+/// precedence until it releases — cooperative priority inheritance.
+pub struct InheritMutex<T> {
+    inner: spin::Mutex<T>,
+    owner: AtomicU64,
+}
+
+pub struct InheritMutexGuard<'a, T> {
+    guard: spin::MutexGuard<'a, T>,
+    owner: &'a AtomicU64,
+}
+
+impl<T> InheritMutex<T> {
+    pub const fn new(value: T) -> Self {
+        InheritMutex {
+            inner: spin::Mutex::new(value),
+            owner: AtomicU64::new(NO_OWNER),
+        }
+    }
+
+    /// Acquire the lock, handing the CPU to the holder while it is contended.
+    pub fn lock(&self) -> InheritMutexGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_lock() {
+                return guard;
+            }
+
+            // Interrupt handlers run with IF clear and must never switch tasks;
+            // a task re-locking its own mutex can't be helped by yielding either.
+            let owner = self.owner.load(Ordering::Acquire);
+            if owner != NO_OWNER
+                && owner != super::current_pid().0
+                && x86_64::instructions::interrupts::are_enabled()
+            {
+                super::yield_to(ProcessId(owner));
+            } else {
+                core::hint::spin_loop();
+            }
+        }
+    }
+
+    /// Acquire the lock only if it is free right now.
+    pub fn try_lock(&self) -> Option<InheritMutexGuard<'_, T>> {
+        let guard = self.inner.try_lock()?;
+        self.owner.store(super::current_pid().0, Ordering::Release);
+        Some(InheritMutexGuard { guard, owner: &self.owner })
+    }
+
+    /// The task currently holding the lock, if any.
+    pub fn owner(&self) -> Option<ProcessId> {
+        match self.owner.load(Ordering::Acquire) {
+            NO_OWNER => None,
+            pid => Some(ProcessId(pid)),
+        }
+    }
+}
+
+impl<T> Deref for InheritMutexGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for InheritMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for InheritMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        // Cleared before `guard` unlocks, so a new owner is never overwritten
+        self.owner.store(NO_OWNER, Ordering::Release);
+    }
+}
@@ -1,23 +1,59 @@
 pub mod task;
 pub mod context;
+pub mod watchdog;
+pub mod reaper;
+pub mod lock;
+pub mod fpu;
+pub mod wait;
+pub mod signal;
+pub mod join;
+pub mod stack;
 
 use alloc::collections::VecDeque;
-use alloc::boxed::Box;
 use alloc::vec;
 use spin::Mutex;
+use core::sync::atomic::{AtomicU64, Ordering};
 use lazy_static::lazy_static;
-pub use task::{Process, ProcessId, ProcessState};
+pub use stack::KernelStack;
+pub use task::{Process, ProcessId, ProcessState, PRIORITY_HIGH, PRIORITY_LOW, PRIORITY_NORMAL};
 use context::Context;
+use fpu::FpuState;
+use crate::allocator::slab::{SlabBox, SlabCache};
 
 /// Size of each task's kernel stack (16 KiB).
 const TASK_STACK_SIZE: usize = 4096 * 4;
 
+/// Timer ticks a task runs before `preempt` switches away from it
+/// (50 ms at the default 100 Hz PIT rate).
+pub const QUANTUM_TICKS: u64 = 5;
+
+/// Times a Ready task can be passed over before its effective priority goes
+/// up a level, so even a low-priority task runs now and then next to busy
+/// higher ones.
+pub const AGING_PICKS: u32 = 4;
+
+/// Ticks the running task has used of its current quantum.
+static SLICE_TICKS: AtomicU64 = AtomicU64::new(0);
+
+/// FPU areas of reaped tasks, recycled by spawn and fork.
+static FPU_STATES: SlabCache<FpuState> = SlabCache::new("fpu_state");
+
+fn new_fpu_state() -> SlabBox<FpuState> {
+    FPU_STATES.alloc(FpuState::new())
+}
+
 /// The global scheduler state.
 pub struct Scheduler {
     /// Currently running process (if any).
     pub current: Option<Process>,
-    /// Ready queue of processes waiting to run.
+    /// Every process but the running one, in round-robin order: the task
+    /// that ran last goes to the back. Blocked tasks and zombies stay here
+    /// too and are skipped. All priority levels share the queue; each level
+    /// is served in queue order (see `schedule_next`).
     pub ready_queue: VecDeque<Process>,
+    /// Task `schedule_next` picks ahead of everything else, if it can run
+    /// (see `yield_to`).
+    run_next: Option<ProcessId>,
     /// Next process ID to assign.
     next_id: u64,
     /// Whether the scheduler is active (context switches enabled).
@@ -29,29 +65,32 @@ impl Scheduler {
         Scheduler {
             current: None,
             ready_queue: VecDeque::new(),
+            run_next: None,
             next_id: 1,
             active: false,
         }
     }
 
-    /// Spawn a new kernel process with the given entry point and name.
-    pub fn spawn(&mut self, entry: fn(), name: &str) -> ProcessId {
+    /// Working directory new processes inherit: the running process's,
+    /// or "/" if there is none.
+    fn current_cwd(&self) -> alloc::string::String {
+        self.current.as_ref().map_or_else(|| alloc::string::String::from("/"), |p| p.cwd.clone())
+    }
+
+    /// Spawn a new kernel process with the given entry point, name and priority.
+    pub fn spawn(&mut self, entry: fn(), name: &str, priority: u8) -> ProcessId {
         let id = ProcessId(self.next_id);
         self.next_id += 1;
 
         // Allocate a kernel stack for the new process
-        let stack = vec![0u8; TASK_STACK_SIZE].into_boxed_slice();
-        let mut stack_top = stack.as_ptr() as usize + TASK_STACK_SIZE;
-        stack_top &= !0xF; // STRICT 16-byte alignment
+        let stack = KernelStack::new();
 
         // Build the initial context: RIP = entry, RSP = stack_top
-        let ctx = Context::new(entry as u64, stack_top as u64);
+        let ctx = Context::new(entry as u64, stack.top());
         
-        // Kernel processes (like Init/Shell/Threads) just use the current kernel P4 root
-        // For real userspace isolation this will be customized later.
-        use x86_64::registers::control::Cr3;
-        let (current_p4, _) = Cr3::read();
-        let current_p4_addr = current_p4.start_address().as_u64();
+        // Kernel processes (like Init/Shell/Threads) run on the kernel's P4,
+        // even when spawned while a user address space is loaded
+        let current_p4_addr = crate::memory::paging::kernel_page_table().as_u64();
 
         let process = Process {
             pid: id,
@@ -61,30 +100,63 @@ impl Scheduler {
             exit_status: None,
             children: alloc::vec::Vec::new(),
             context: ctx,
+            fpu: new_fpu_state(),
             page_table: current_p4_addr,
-            _kernel_stack: stack,
+            _kernel_stack: Some(stack),
             user_allocations: alloc::vec::Vec::new(),
+            shared_allocations: alloc::vec::Vec::new(),
+            mmap_next: MMAP_BASE,
             heap_start: 0,
             heap_end: 0,
             fd_table: create_default_fd_table(),
-            _image: None,
+            cwd: self.current_cwd(),
+            watchdog_quanta: 0,
+            cpu_ticks: 0,
+            switches: 0,
+            wake_at: None,
+            pending_signals: 0,
+            joinable: false,
+            joined_by: None,
+            priority,
+            passed_over: 0,
+            image: None,
         };
 
         self.ready_queue.push_back(process);
         id
     }
 
-    /// Pick the next ready process. Returns None if queue is empty.
+    /// Take the next task to run out of the ready queue: the first runnable
+    /// one at the highest effective priority, so each level runs round-robin
+    /// and a lower level only runs when no higher one is ready. Every
+    /// runnable task passed over ages (see `AGING_PICKS`). Returns None if
+    /// nothing can run.
     pub fn schedule_next(&mut self) -> Option<Process> {
-        // Find next process that is not blocked/zombie
-        // For now pop_front assumes all in ready_queue are ready/running.
-        // We will refine this.
-        self.ready_queue.pop_front()
+        let hinted = self.run_next.take()
+            .and_then(|pid| self.ready_queue.iter().position(|p| p.pid == pid && p.is_runnable()));
+        let idx = hinted.or_else(|| {
+            let mut best: Option<(usize, u8)> = None;
+            for (i, p) in self.ready_queue.iter().enumerate() {
+                let prio = p.effective_priority();
+                if p.is_runnable() && best.map_or(true, |(_, b)| prio > b) {
+                    best = Some((i, prio));
+                }
+            }
+            best.map(|(i, _)| i)
+        })?;
+
+        let mut next = self.ready_queue.remove(idx)?;
+        for p in self.ready_queue.iter_mut().filter(|p| p.is_runnable()) {
+            p.passed_over = p.passed_over.saturating_add(1);
+        }
+        next.passed_over = 0;
+        Some(next)
     }
 
     /// Wakes up all processes that are currently in the Blocked state.
-    /// This is used heavily by the Pipe IPC mechanism so readers/writers 
-    /// retry their data transfer conditions.
+    /// Only the fallback for wake-ups deferred while this lock was held
+    /// (see `wait::WAKE_PENDING`); pipes and wait queues wake just their
+    /// own waiters.
     pub fn wake_all_blocked(&mut self) {
         let mut any_woken = false;
         for process in self.ready_queue.iter_mut() {
@@ -106,14 +178,60 @@ impl Scheduler {
             // crate::log_info!("scheduler: wake_all_blocked activated sleeping processes");
         }
     }
+
+    /// Make `pid` runnable again if it is Blocked.
+    pub fn wake(&mut self, pid: ProcessId) {
+        let process = self.current.iter_mut()
+            .chain(self.ready_queue.iter_mut())
+            .find(|p| p.pid == pid);
+        if let Some(p) = process {
+            if p.state == ProcessState::Blocked {
+                p.state = ProcessState::Ready;
+            }
+        }
+    }
+
+    /// Apply wake-ups that interrupt handlers had to defer because this
+    /// lock was held at the time, including sleep deadlines that passed.
+    fn apply_deferred_wakes(&mut self) {
+        if wait::WAKE_PENDING.swap(false, Ordering::Acquire) {
+            self.wake_all_blocked();
+        }
+        signal::apply_deferred_interrupt(self);
+        self.wake_sleepers(crate::shell::commands::uptime::TICKS.load(Ordering::Relaxed));
+    }
+
+    /// Make every task whose `sleep_ticks` deadline is at or before `now` Ready.
+    fn wake_sleepers(&mut self, now: u64) {
+        for p in self.current.iter_mut().chain(self.ready_queue.iter_mut()) {
+            if p.wake_at.is_some_and(|deadline| deadline_passed(now, deadline)) {
+                p.wake_at = None;
+                if p.state == ProcessState::Blocked {
+                    p.state = ProcessState::Ready;
+                }
+            }
+        }
+    }
 }
 
 lazy_static! {
     pub static ref SCHEDULER: Mutex<Scheduler> = Mutex::new(Scheduler::new());
 }
 
+/// PID of the running task, readable without taking `SCHEDULER`
+/// (lock owners are recorded from paths that may already hold it).
+static CURRENT_PID: AtomicU64 = AtomicU64::new(0);
+
+/// The PID of the task currently on the CPU.
+pub fn current_pid() -> ProcessId {
+    ProcessId(CURRENT_PID.load(Ordering::Relaxed))
+}
+
 /// Initialize the scheduler. Create Process 0 (kernel/shell) as the current process.
 pub fn init() {
+    // Every task switch saves and restores the FPU/SSE registers
+    fpu::init();
+
     let mut sched = SCHEDULER.lock();
     
     use x86_64::registers::control::Cr3;
@@ -129,45 +247,86 @@ pub fn init() {
         exit_status: None,
         children: alloc::vec::Vec::new(),
         context: Context::empty(),
+        fpu: new_fpu_state(),
         page_table: current_p4_addr,
-        _kernel_stack: Box::new([]),
+        _kernel_stack: None,
         user_allocations: alloc::vec::Vec::new(),
+        shared_allocations: alloc::vec::Vec::new(),
+        mmap_next: MMAP_BASE,
         heap_start: 0,
         heap_end: 0,
         fd_table: create_default_fd_table(),
-        _image: None,
+        cwd: alloc::string::String::from("/"),
+        watchdog_quanta: 0,
+        cpu_ticks: 0,
+        switches: 0,
+        wake_at: None,
+        pending_signals: 0,
+        joinable: false,
+        joined_by: None,
+        // The shell: stays responsive next to busy background tasks
+        priority: PRIORITY_HIGH,
+        passed_over: 0,
+        image: None,
     };
     sched.current = Some(kernel_process);
+
+    // PID 1 = init: adopts orphaned processes and reaps their zombies
+    let init_pid = sched.spawn(reaper::init_task, "init", PRIORITY_NORMAL);
+    debug_assert_eq!(init_pid, reaper::INIT_PID);
+
     sched.active = true;
     drop(sched);
 
     crate::log_info!("Scheduler initialized with cooperative multitasking.");
 }
 
-/// Spawn a new kernel process from anywhere in the kernel.
+/// Spawn a new kernel process from anywhere in the kernel, at normal priority.
 pub fn spawn(entry: fn(), name: &str) -> ProcessId {
+    spawn_with_priority(entry, name, PRIORITY_NORMAL)
+}
+
+/// Spawn a new kernel process at one of the `PRIORITY_*` levels.
+pub fn spawn_with_priority(entry: fn(), name: &str, priority: u8) -> ProcessId {
     let mut sched = SCHEDULER.lock();
-    let id = sched.spawn(entry, name);
+    let id = sched.spawn(entry, name, priority.min(PRIORITY_HIGH));
     // crate::log_info!("Spawned process '{}' with PID {}", name, id.0);
     id
 }
 
+/// Spawn a kernel process as a child of the running one, at normal
+/// priority. It starts with the parent's descriptors and working directory,
+/// and the parent reaps it with `sys_wait`.
+pub fn spawn_child(entry: fn(), name: &str) -> ProcessId {
+    let mut sched = SCHEDULER.lock();
+    let id = sched.spawn(entry, name, PRIORITY_NORMAL);
+    let parent = sched.current.as_mut().map(|p| {
+        p.children.push(id);
+        (p.pid, p.fd_table.clone())
+    });
+    if let Some((parent_pid, fds)) = parent {
+        if let Some(child) = sched.ready_queue.iter_mut().find(|p| p.pid == id) {
+            child.parent_pid = Some(parent_pid);
+            child.fd_table = fds;
+        }
+    }
+    id
+}
+
 /// Spawn a completely customized process (Used by ELF loader / Fork).
-/// It allows specifying a custom Page Table (CR3) and initial context.
-pub fn spawn_process(name: &str, page_table: u64, entry: u64, _user_stack_top: u64, allocations: alloc::vec::Vec<(u64, u64)>) -> ProcessId {
+/// It allows specifying a custom Page Table (CR3), initial context and priority.
+pub fn spawn_process(name: &str, page_table: u64, entry: u64, _user_stack_top: u64, allocations: alloc::vec::Vec<(u64, u64)>, priority: u8) -> ProcessId {
     let mut sched = SCHEDULER.lock();
     
     let id = ProcessId(sched.next_id);
     sched.next_id += 1;
 
     // Allocate a separate KERNEL stack for the process (needed for Ring 3 -> Ring 0 transitions)
-    let kernel_stack = vec![0u8; TASK_STACK_SIZE].into_boxed_slice();
-    let mut kernel_stack_top = kernel_stack.as_ptr() as usize + TASK_STACK_SIZE;
-    kernel_stack_top &= !0xF; // Enforce 16-byte hardware alignment
+    let kernel_stack = KernelStack::new();
 
     // Build the initial context: RIP = trampoline or entry, but since this is 
     // for Ring 3, the jump must happen inside the trampoline.
-    let ctx = Context::new(entry, kernel_stack_top as u64);
+    let ctx = Context::new(entry, kernel_stack.top());
 
     let process = Process {
         pid: id,
@@ -177,13 +336,26 @@ pub fn spawn_process(name: &str, page_table: u64, entry: u64, _user_stack_top: u
         exit_status: None,
         children: alloc::vec::Vec::new(),
         context: ctx,
+        fpu: new_fpu_state(),
         page_table,
-        _kernel_stack: kernel_stack,
+        _kernel_stack: Some(kernel_stack),
         user_allocations: allocations,
+        shared_allocations: alloc::vec::Vec::new(),
+        mmap_next: MMAP_BASE,
         heap_start: 0,  // Will be set during sys_exec
         heap_end: 0,
         fd_table: create_default_fd_table(),
-        _image: None,
+        cwd: sched.current_cwd(),
+        watchdog_quanta: 0,
+        cpu_ticks: 0,
+        switches: 0,
+        wake_at: None,
+        pending_signals: 0,
+        joinable: false,
+        joined_by: None,
+        priority,
+        passed_over: 0,
+        image: None,
     };
 
     sched.ready_queue.push_back(process);
@@ -200,25 +372,14 @@ pub fn try_yield_now() {
             Some(lock) => lock,
             None => return, // Don't yield if scheduler is busy! (e.g. inside a syscall setup)
         };
+        sched.apply_deferred_wakes();
         
         if !sched.active || sched.ready_queue.is_empty() {
             return;
         }
 
         if let Some(mut current) = sched.current.take() {
-            let queue_len = sched.ready_queue.len();
-            let mut found = None;
-            for _ in 0..queue_len {
-                if let Some(n) = sched.ready_queue.pop_front() {
-                    if n.state == ProcessState::Ready || n.state == ProcessState::Running {
-                        found = Some(n);
-                        break;
-                    } else {
-                        sched.ready_queue.push_back(n);
-                    }
-                }
-            }
-            let mut next = match found {
+            let mut next = match sched.schedule_next() {
                 Some(n) => n,
                 None => {
                     // No runnable task found, put current back and return
@@ -229,16 +390,22 @@ pub fn try_yield_now() {
 
             current.state = ProcessState::Ready;
             next.state = ProcessState::Running;
+            next.switches += 1;
+            watchdog::clear();
 
-            let mut next_stack_top = next._kernel_stack.as_ptr() as u64 + TASK_STACK_SIZE as u64;
-            next_stack_top &= !0xF;
-            crate::interrupts::gdt::set_tss_rsp0(next_stack_top);
+            if let Some(next_stack_top) = next.kernel_stack_top() {
+                crate::interrupts::gdt::set_tss_rsp0(next_stack_top);
+            }
             sched.ready_queue.reserve(1);
             sched.ready_queue.push_back(current);
+            CURRENT_PID.store(next.pid.0, Ordering::Relaxed);
+            SLICE_TICKS.store(0, Ordering::Relaxed);
             sched.current = Some(next);
 
             let current_ctx_ptr = &mut sched.ready_queue.back_mut().unwrap().context as *mut Context;
             let next_ctx_ptr = &sched.current.as_ref().unwrap().context as *const Context;
+            let current_fpu_ptr = &mut *sched.ready_queue.back_mut().unwrap().fpu as *mut FpuState;
+            let next_fpu_ptr = &*sched.current.as_ref().unwrap().fpu as *const FpuState;
 
             unsafe {
                 let cr3_val = sched.current.as_ref().unwrap().page_table;
@@ -248,51 +415,180 @@ pub fn try_yield_now() {
             let target_pid = sched.current.as_ref().unwrap().pid.0;
             drop(sched);
 
-            unsafe { context::switch_context(current_ctx_ptr, next_ctx_ptr); }
+            unsafe { context::switch_context(current_ctx_ptr, next_ctx_ptr, current_fpu_ptr, next_fpu_ptr); }
         }
     });
 }
 
+/// Timer-driven preemption, called on every tick. Once the running task has
+/// used `QUANTUM_TICKS`, switch to the next ready task.
+///
+/// Switching from inside the interrupt handler keeps the full register set:
+/// the CPU pushed the interrupted RIP/RSP/RFLAGS (and the user stack for
+/// Ring 3) on this task's kernel stack, the `x86-interrupt` prologue saved
+/// every scratch register below it, and `switch_context` saves the rest
+/// plus the FPU state. When the task is picked again, `switch_context`
+/// returns into the handler, whose epilogue and `iretq` resume it exactly
+/// where it was interrupted.
+pub fn preempt() {
+    // Sleepers whose deadline passed become Ready on this tick. If a syscall
+    // holds the lock, the next reschedule catches them instead.
+    if let Some(mut sched) = SCHEDULER.try_lock() {
+        sched.wake_sleepers(crate::shell::commands::uptime::TICKS.load(Ordering::Relaxed));
+    }
+    if SLICE_TICKS.fetch_add(1, Ordering::Relaxed) + 1 >= QUANTUM_TICKS {
+        // Reset here too: if the scheduler is busy the task keeps the CPU
+        // for another full quantum rather than retrying every tick
+        SLICE_TICKS.store(0, Ordering::Relaxed);
+        try_yield_now();
+    }
+}
+
+/// Hand the CPU to `target` ahead of the rest of the ready queue.
+/// Used by `lock::InheritMutex` to let a descheduled lock holder finish its
+/// critical section on the waiter's time. Falls back to spinning when the
+/// scheduler is busy or `target` can't run.
+pub fn yield_to(target: ProcessId) {
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        {
+            let mut sched = match SCHEDULER.try_lock() {
+                Some(lock) => lock,
+                None => return,
+            };
+            if !sched.ready_queue.iter().any(|p| p.pid == target && p.is_runnable()) {
+                return;
+            }
+            sched.run_next = Some(target);
+        }
+        // The holder is now first in line, whatever its priority
+        try_yield_now();
+    });
+}
+
+/// Block the calling task for at least `ticks` timer ticks.
+/// The task is Blocked with a deadline and uses no CPU until the timer
+/// makes it Ready again; when nothing else can run the CPU halts. A zero
+/// sleep just yields. Returns early if a signal is posted to the task.
+pub fn sleep_ticks(ticks: u64) {
+    if ticks == 0 {
+        yield_now();
+        return;
+    }
+    let timer = &crate::shell::commands::uptime::TICKS;
+    // The deadline may wrap past u64::MAX; `deadline_passed` compares modulo
+    // 2^64, so clamp to half the range to keep the order unambiguous
+    let deadline = timer.load(Ordering::Relaxed).wrapping_add(ticks.min(i64::MAX as u64));
+
+    loop {
+        let done = x86_64::instructions::interrupts::without_interrupts(|| {
+            let mut sched = SCHEDULER.lock();
+            let Some(current) = sched.current.as_mut() else {
+                return true;
+            };
+            // A pending signal cuts the sleep short so it can be delivered
+            if deadline_passed(timer.load(Ordering::Relaxed), deadline) || current.pending_signals != 0 {
+                current.wake_at = None;
+                current.state = ProcessState::Running;
+                return true;
+            }
+            current.wake_at = Some(deadline);
+            current.state = ProcessState::Blocked;
+            false
+        });
+        if done {
+            return;
+        }
+        block_current();
+    }
+}
+
+/// Block the calling task until something makes it Ready (a pipe or wait
+/// queue wake-up, a signal) or tick `deadline` passes, whichever comes
+/// first. Callers re-check their condition afterwards, as the wake-up may
+/// have been for someone else.
+pub fn block_until(deadline: u64) {
+    let timer = &crate::shell::commands::uptime::TICKS;
+    let blocked = x86_64::instructions::interrupts::without_interrupts(|| {
+        let mut sched = SCHEDULER.lock();
+        let Some(current) = sched.current.as_mut() else {
+            return false;
+        };
+        if deadline_passed(timer.load(Ordering::Relaxed), deadline) || current.pending_signals != 0 {
+            return false;
+        }
+        current.wake_at = Some(deadline);
+        current.state = ProcessState::Blocked;
+        true
+    });
+    if blocked {
+        block_current();
+    }
+    // Woken before the deadline: don't leave it to wake a later wait
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        if let Some(current) = SCHEDULER.lock().current.as_mut() {
+            current.wake_at = None;
+            current.state = ProcessState::Running;
+        }
+    });
+}
+
+/// Whether tick `now` is at or after `deadline`, allowing for the counter
+/// wrapping between the two.
+fn deadline_passed(now: u64, deadline: u64) -> bool {
+    now.wrapping_sub(deadline) as i64 >= 0
+}
+
 /// Cooperatively yield the CPU to the next ready task.
 pub fn yield_now() {
+    reschedule(false);
+}
+
+/// Give up the CPU until a `wait::WaitQueue` wakes the calling task. Unlike
+/// `yield_now`, a Blocked task stays Blocked and is skipped by the scheduler.
+/// When nothing else can run, halts until the next interrupt and returns so
+/// the caller can re-check its condition.
+pub fn block_current() {
+    if !reschedule(true) {
+        x86_64::instructions::interrupts::enable_and_hlt();
+    }
+}
+
+/// Switch to the next runnable task. The current task goes back in the queue
+/// as Ready, or keeps its Blocked state if `keep_blocked`. Returns false if
+/// there was nothing to switch to.
+fn reschedule(keep_blocked: bool) -> bool {
     // Disable interrupts during context switch for safety
     x86_64::instructions::interrupts::without_interrupts(|| {
         let mut sched = SCHEDULER.lock();
+        sched.apply_deferred_wakes();
         if !sched.active || sched.ready_queue.is_empty() {
-            return;
+            return false;
         }
 
         // Take the current process out
         if let Some(mut current) = sched.current.take() {
             // Get next process (skipping Blocked/Zombie)
-            let queue_len = sched.ready_queue.len();
-            let mut found = None;
-            for _ in 0..queue_len {
-                if let Some(n) = sched.ready_queue.pop_front() {
-                    if n.state == ProcessState::Ready || n.state == ProcessState::Running {
-                        found = Some(n);
-                        break;
-                    } else {
-                        sched.ready_queue.push_back(n);
-                    }
-                }
-            }
-            let mut next = match found {
+            let mut next = match sched.schedule_next() {
                 Some(n) => n,
                 None => {
                     // No runnable task found, put current back and return
                     sched.current = Some(current);
-                    return;
+                    return false;
                 }
             };
 
-            current.state = ProcessState::Ready;
+            if !(keep_blocked && current.state == ProcessState::Blocked) {
+                current.state = ProcessState::Ready;
+            }
+            current.watchdog_quanta = 0; // Voluntary yield: the task is not spinning
             next.state = ProcessState::Running;
+            next.switches += 1;
+            watchdog::clear();
 
             // Calculate next kernel stack top
-            let mut next_stack_top = next._kernel_stack.as_ptr() as u64 + TASK_STACK_SIZE as u64;
-            next_stack_top &= !0xF;
-            crate::interrupts::gdt::set_tss_rsp0(next_stack_top);
+            if let Some(next_stack_top) = next.kernel_stack_top() {
+                crate::interrupts::gdt::set_tss_rsp0(next_stack_top);
+            }
 
             // Reserve capacity to guarantee `push_back` will NOT reallocate and move structures!
             sched.ready_queue.reserve(1);
@@ -300,11 +596,15 @@ pub fn yield_now() {
             // Put current back in queue, set next as current
             // MOVES HAPPEN HERE: We must do this BEFORE taking pointers!
             sched.ready_queue.push_back(current);
+            CURRENT_PID.store(next.pid.0, Ordering::Relaxed);
+            SLICE_TICKS.store(0, Ordering::Relaxed);
             sched.current = Some(next);
 
             // NOW grab the valid pointers from their permanent heap locations within the guaranteed-stable VecDeque buffer
             let current_ctx_ptr = &mut sched.ready_queue.back_mut().unwrap().context as *mut Context;
             let next_ctx_ptr = &sched.current.as_ref().unwrap().context as *const Context;
+            let current_fpu_ptr = &mut *sched.ready_queue.back_mut().unwrap().fpu as *mut FpuState;
+            let next_fpu_ptr = &*sched.current.as_ref().unwrap().fpu as *const FpuState;
 
             // Load the new process's Page Table (CR3)
             unsafe {
@@ -322,42 +622,93 @@ pub fn yield_now() {
             // crate::log_info!("yield_now: switching CPU to PID {}", target_pid);
 
             // Perform the actual context switch via assembly
-            unsafe { context::switch_context(current_ctx_ptr, next_ctx_ptr); }
+            unsafe { context::switch_context(current_ctx_ptr, next_ctx_ptr, current_fpu_ptr, next_fpu_ptr); }
+            true
+        } else {
+            false
         }
-    });
+    })
 }
 
-/// Terminate the current process and switch to the next one.
+/// Terminate the current process with `exit_code` and switch to the next one.
 pub fn exit_current(exit_code: u64) {
+    terminate_current(crate::syscalls::wait::exited_status(exit_code));
+}
+
+/// Terminate the current process as killed by signal `sig` and switch to
+/// the next one.
+pub fn kill_current(sig: u32) {
+    terminate_current(crate::syscalls::wait::signaled_status(sig));
+}
+
+/// End the current process, leaving wait status `status` for its parent.
+fn terminate_current(status: u64) {
+    // Phase 5.4: Drop all file descriptors first, before becoming a Zombie, so
+    // no FD leaks and readers see EOF. Not under the scheduler lock: closing a
+    // pipe end wakes the tasks waiting on its other side.
+    let fds = x86_64::instructions::interrupts::without_interrupts(|| {
+        SCHEDULER.lock().current.as_mut().map(|p| core::mem::take(&mut p.fd_table))
+    });
+    drop(fds);
+
     // Disable interrupts to ensure atomicity
     x86_64::instructions::interrupts::without_interrupts(|| {
         let mut sched = SCHEDULER.lock();
 
         // 1. Remove the current process, transform to Zombie, free User allocations
-        let mut finished = sched.current.take().expect("exit_current called without an active process");
+        let Some(mut finished) = sched.current.take() else {
+            crate::kpanic!("terminate_current called without an active process");
+        };
         
-        // crate::log_info!("Process '{}' (PID {}) exiting with code {}.", finished.name, finished.pid.0, exit_code);
+        // crate::log_info!("Process '{}' (PID {}) exiting with status {:#x}.", finished.name, finished.pid.0, status);
         
         finished.state = ProcessState::Zombie;
-        finished.exit_status = Some(exit_code);
+        finished.exit_status = Some(status);
         
         // Free user allocations!
         for (vaddr, size) in &finished.user_allocations {
             crate::memory::paging::free_user_memory(x86_64::VirtAddr::new(*vaddr), *size);
         }
         finished.user_allocations.clear();
-        
-        // Phase 5.4: Drop all file descriptors immediately!
-        // This drops the Arc Rc. If Rc == 0, the underlying Pipe/File is cleaned up.
-        // Doing this before becoming a Zombie ensures we don't leak FDs and signal EOF to readers.
-        for slot in finished.fd_table.iter_mut() {
-            *slot = None;
+        for (vaddr, size) in &finished.shared_allocations {
+            crate::memory::paging::free_shared_memory(x86_64::VirtAddr::new(*vaddr), *size);
         }
+        finished.shared_allocations.clear();
         
-        // Wake up Parent if it was waiting
-        if let Some(parent_pid) = finished.parent_pid {
+        // Hand our children (living or zombie) over to init so they can still be reaped
+        let init_pid = reaper::INIT_PID;
+        let mut adopted = alloc::vec::Vec::new();
+        let mut adopted_zombie = false;
+        for proc in sched.ready_queue.iter_mut() {
+            if proc.parent_pid == Some(finished.pid) {
+                proc.parent_pid = Some(init_pid);
+                adopted.push(proc.pid);
+                adopted_zombie |= proc.state == ProcessState::Zombie;
+            }
+        }
+        finished.children.clear();
+
+        // Processes nobody will wait for (no parent) are reaped by init as well,
+        // unless they are kept for `join`
+        if finished.parent_pid.is_none() && finished.pid != init_pid && !finished.joinable {
+            finished.parent_pid = Some(init_pid);
+            adopted.push(finished.pid);
+            adopted_zombie = true;
+        }
+
+        if !adopted.is_empty() {
+            if let Some(init) = sched.ready_queue.iter_mut().find(|p| p.pid == init_pid) {
+                init.children.extend_from_slice(&adopted);
+                if adopted_zombie && init.state == ProcessState::Blocked {
+                    init.state = ProcessState::Ready;
+                }
+            }
+        }
+
+        // Wake up Parent (or the task joining us) if it was waiting
+        for waiter in [finished.parent_pid, finished.joined_by].into_iter().flatten() {
             for proc in sched.ready_queue.iter_mut() {
-                if proc.pid == parent_pid && proc.state == ProcessState::Blocked {
+                if proc.pid == waiter && proc.state == ProcessState::Blocked {
                     proc.state = ProcessState::Ready;
                     break;
                 }
@@ -370,31 +721,42 @@ pub fn exit_current(exit_code: u64) {
         // 2. We MUST switch to the next task now
         // Get next process (skipping Blocked/Zombie)
         let mut next = loop {
-            if let Some(n) = sched.ready_queue.pop_front() {
-                if n.state == ProcessState::Ready || n.state == ProcessState::Running {
-                    break n;
-                } else {
-                    sched.ready_queue.push_back(n);
-                }
-            } else {
+            if let Some(n) = sched.schedule_next() {
+                break n;
+            }
+            if sched.ready_queue.iter().all(|p| p.state == ProcessState::Zombie) {
                 // No tasks left at all (not even the shell).
                 // crate::log_info!("All tasks finished. System halted.");
                 drop(sched);
+                if let Err(e) = crate::fs::sync_all() {
+                    crate::log_error!("sync before halt failed: {}", e);
+                }
                 loop { x86_64::instructions::interrupts::enable_and_hlt(); }
             }
+            // Everyone is Blocked: idle until an interrupt wakes someone
+            drop(sched);
+            x86_64::instructions::interrupts::enable_and_hlt();
+            x86_64::instructions::interrupts::disable();
+            sched = SCHEDULER.lock();
+            sched.apply_deferred_wakes();
         };
 
         next.state = ProcessState::Running;
+        next.switches += 1;
+        watchdog::clear();
             
-        let mut next_stack_top = next._kernel_stack.as_ptr() as u64 + TASK_STACK_SIZE as u64;
-        next_stack_top &= !0xF;
-        crate::interrupts::gdt::set_tss_rsp0(next_stack_top);
+        if let Some(next_stack_top) = next.kernel_stack_top() {
+            crate::interrupts::gdt::set_tss_rsp0(next_stack_top);
+        }
             
         // We must place it in `sched.current` before getting its context pointer.
+        CURRENT_PID.store(next.pid.0, Ordering::Relaxed);
+        SLICE_TICKS.store(0, Ordering::Relaxed);
         sched.current = Some(next);
             
         // Get the raw pointer to the next context IN its new memory location.
         let next_ctx_ptr = &sched.current.as_ref().unwrap().context as *const Context;
+        let next_fpu_ptr = &*sched.current.as_ref().unwrap().fpu as *const FpuState;
             
         // Load the new process's Page Table (CR3)
         unsafe {
@@ -410,37 +772,102 @@ pub fn exit_current(exit_code: u64) {
 
         // 3. Jump to the next task without saving the current state
         unsafe {
-            context::restore_context(next_ctx_ptr);
+            context::restore_context(next_ctx_ptr, next_fpu_ptr);
         }
     });
 
-    unreachable!("exit_current should never return");
+    crate::kpanic!("terminate_current returned from restore_context");
 }
 
-/// Get a snapshot of all processes for display purposes (used by `ps` command).
-pub fn list_tasks() -> alloc::vec::Vec<(u64, alloc::string::String, alloc::string::String)> {
+/// Get a snapshot of all processes for display purposes (used by `ps` and `top`):
+/// (pid, name, state, cpu_ticks).
+pub fn list_tasks() -> alloc::vec::Vec<(u64, alloc::string::String, alloc::string::String, u64)> {
+    task_snapshot().into_iter().map(|t| {
+        let state = if t.running { "running".into() } else { alloc::format!("{:?}", t.state) };
+        (t.pid, t.name, state, t.cpu_ticks)
+    }).collect()
+}
+
+/// One process as seen by `task_snapshot`.
+pub struct TaskInfo {
+    pub pid: u64,
+    pub parent_pid: Option<u64>,
+    pub name: alloc::string::String,
+    pub state: ProcessState,
+    /// The process that called `task_snapshot`.
+    pub running: bool,
+    pub priority: u8,
+    pub cpu_ticks: u64,
+    pub switches: u64,
+    /// Descriptors in use in its `fd_table`.
+    pub open_fds: usize,
+}
+
+/// Every process, the caller first and the rest in scheduling order.
+pub fn task_snapshot() -> alloc::vec::Vec<TaskInfo> {
     let sched = SCHEDULER.lock();
-    let mut result = alloc::vec::Vec::new();
+    sched.current.iter().map(|p| (p, true))
+        .chain(sched.ready_queue.iter().map(|p| (p, false)))
+        .map(|(p, running)| TaskInfo {
+            pid: p.pid.0,
+            parent_pid: p.parent_pid.map(|pid| pid.0),
+            name: p.name.clone(),
+            state: p.state,
+            running,
+            priority: p.priority,
+            cpu_ticks: p.cpu_ticks,
+            switches: p.switches,
+            open_fds: p.fd_table.iter().filter(|fd| fd.is_some()).count(),
+        })
+        .collect()
+}
 
-    if let Some(ref current) = sched.current {
-        result.push((current.pid.0, current.name.clone(), alloc::string::String::from("running")));
-    }
-    for proc in &sched.ready_queue {
-        result.push((proc.pid.0, proc.name.clone(), alloc::format!("{:?}", proc.state)));
+/// Working directory of the running process.
+pub fn current_cwd() -> alloc::string::String {
+    SCHEDULER.lock().current_cwd()
+}
+
+/// Change the running process's working directory. `path` must already be
+/// absolute and normalized, and name a directory.
+pub fn set_current_cwd(path: alloc::string::String) {
+    if let Some(current) = SCHEDULER.lock().current.as_mut() {
+        current.cwd = path;
     }
+}
+
+/// The running process's descriptor `fd`, if open.
+pub fn current_fd(fd: usize) -> Option<alloc::sync::Arc<spin::Mutex<crate::fs::fd::File>>> {
+    SCHEDULER.lock().current.as_ref()?.fd_table.get(fd)?.clone()
+}
 
-    result
+/// Install `file` as descriptor `fd` of the running process and return the
+/// descriptor it replaces.
+pub fn replace_current_fd(fd: usize, file: Option<alloc::sync::Arc<spin::Mutex<crate::fs::fd::File>>>)
+    -> Option<alloc::sync::Arc<spin::Mutex<crate::fs::fd::File>>> {
+    let mut sched = SCHEDULER.lock();
+    let slot = sched.current.as_mut()?.fd_table.get_mut(fd)?;
+    core::mem::replace(slot, file)
+}
+
+/// Charge the tick that just elapsed to the running task.
+/// Called from the timer interrupt; the tick goes uncounted if the
+/// scheduler lock is held at that moment.
+pub fn account_tick() {
+    if let Some(mut sched) = SCHEDULER.try_lock() {
+        if let Some(current) = sched.current.as_mut() {
+            current.cpu_ticks += 1;
+        }
+    }
 }
 
 /// Syscall fork: Duplicate the current process (parent) into a new running process (child).
+/// `parent_frame` is the parent's syscall `TrapFrame` as passed to `dispatch`.
 /// Returns Child PID to Parent, 0 to Child.
-pub fn sys_fork() -> u64 {
+pub fn sys_fork(parent_frame: *const TrapFrame) -> u64 {
     let mut sched = SCHEDULER.lock();
     
     // Extract everything we need from current to drop the borrow
-        let parent_heap_start = current_proc.heap_start;
-        let parent_heap_end = current_proc.heap_end;
-    let (parent_pid, parent_name, child_allocations, parent_stack_ptr, parent_image, parent_fd_table) = {
+    let (parent_pid, parent_name, parent_priority, child_allocations, parent_shared, parent_mmap_next, parent_heap_start, parent_heap_end, parent_image, parent_fd_table) = {
         let current_proc = match sched.current.as_ref() {
             Some(p) => p,
             None => return u64::MAX,
@@ -448,14 +875,26 @@ pub fn sys_fork() -> u64 {
         (
             current_proc.pid,
             current_proc.name.clone(),
+            current_proc.priority,
             current_proc.user_allocations.clone(),
-            current_proc._kernel_stack.as_ptr(),
-            None, // Phase 5.3 memory mapping isolates physical frames manually, no need to clone the legacy image!
+            current_proc.shared_allocations.clone(),
+            current_proc.mmap_next,
+            current_proc.heap_start,
+            current_proc.heap_end,
+            // Pages the parent never touched load from the same image
+            current_proc.image.clone(),
             current_proc.fd_table.clone()
         )
     };
     
-    // crate::log_info!("sys_fork: allocating P4 phys...");
+    // Cheap early-out: don't start building a child we cannot finish
+    let needed: usize = 2 + child_allocations.iter().chain(parent_shared.iter())
+        .map(|(_, size)| crate::memory::paging::frames_needed(*size))
+        .sum::<usize>();
+    if crate::memory::FRAME_ALLOCATOR.lock().free_frames() < needed {
+        crate::log_warn!("sys_fork: out of physical frames ({} needed)", needed);
+        return u64::MAX;
+    }
     
     // 2. Clone the User Page Table and Allocations
     let child_p4_phys = match crate::memory::paging::create_new_page_table() {
@@ -468,31 +907,43 @@ pub fn sys_fork() -> u64 {
     // Execute Deep Copy of physical Memory Frames!
     if !crate::memory::paging::deep_clone_process_memory(child_p4_phys, &child_allocations) {
         crate::log_error!("sys_fork: Failed to deep copy memory frames!");
+        crate::memory::paging::discard_address_space(child_p4_phys, &child_allocations);
+        return u64::MAX;
+    }
+    
+    // MAP_SHARED regions are not copied: the child maps the very same frames
+    if !crate::memory::paging::share_process_memory(child_p4_phys, &parent_shared) {
+        crate::log_error!("sys_fork: Failed to share MAP_SHARED frames!");
+        crate::memory::paging::discard_address_space(child_p4_phys, &child_allocations);
         return u64::MAX;
     }
     
     // crate::log_info!("sys_fork: P4 clone finished! Allocating child kernel stack...");
     
     // 3. Allocate a fresh independent Kernel Stack for the child
-    let child_kernel_stack = vec![0u8; TASK_STACK_SIZE].into_boxed_slice();
-    let mut child_stack_top = child_kernel_stack.as_ptr() as u64 + TASK_STACK_SIZE as u64;
-    child_stack_top &= !0xF; // Strict 16-byte boundary
-
-    // 4. Copy the User Context (TrapFrame) exactly
-    // Subtract 152 bytes (19 * 8 bytes) to match exactly what is pushed by the CPU + syscall handler!
-    let mut parent_stack_top = parent_stack_ptr as u64 + TASK_STACK_SIZE as u64;
-    parent_stack_top &= !0xF;
-    
-    let trap_frame_ptr = (parent_stack_top - 152) as *const TrapFrame;
-    let trap_frame = unsafe { *trap_frame_ptr };
+    let child_kernel_stack = KernelStack::new();
+    let child_stack_top = child_kernel_stack.top();
+
+    // 4. Copy the User Context (TrapFrame) saved by the syscall entry, placing it
+    // where the CPU would have left it at the top of the child's kernel stack
+    let mut trap_frame = unsafe { *parent_frame };
+    // A kernel task (the shell) forks from Ring 0, where iretq keeps the
+    // stack: the child runs on the top of its own kernel stack, as if called
+    if trap_frame.cs & 3 == 0 {
+        trap_frame.rsp = child_stack_top - 8;
+    }
     
-    let child_trap_frame_ptr = (child_stack_top - 152) as *mut TrapFrame;
-    unsafe { *child_trap_frame_ptr = trap_frame; }
+    let child_frame_addr = child_stack_top - crate::interrupts::usermode::TRAP_FRAME_SIZE as u64;
+    unsafe { *(child_frame_addr as *mut TrapFrame) = trap_frame; }
     
     // Set child's Context to resume at `fork_trampoline` with RSP pointing at the TrapFrame
     let mut child_context = Context::empty();
-    child_context.rsp = child_stack_top - 152;
+    child_context.rsp = child_frame_addr;
     child_context.rip = fork_trampoline as *const () as u64;
+
+    // The child resumes with the parent's FPU/SSE registers as they were at
+    // the int 0x80, which the syscall entry saved below the TrapFrame
+    let child_fpu = FPU_STATES.alloc(unsafe { (*crate::interrupts::usermode::saved_fpu(parent_frame)).clone() });
     
     // 5. Construct Process
     let child_pid = ProcessId(sched.next_id);
@@ -508,17 +959,32 @@ pub fn sys_fork() -> u64 {
         exit_status: None,
         children: alloc::vec::Vec::new(),
         context: child_context,
+        fpu: child_fpu,
         page_table: child_p4_phys.as_u64(),
-        _kernel_stack: child_kernel_stack,
+        _kernel_stack: Some(child_kernel_stack),
         user_allocations: child_allocations,
+        shared_allocations: parent_shared,
+        mmap_next: parent_mmap_next,
         heap_start: parent_heap_start,
         heap_end: parent_heap_end,
         fd_table: parent_fd_table, // Exact clone()! Bumps Arc ref counts seamlessly!
-        _image: parent_image,
+        cwd: sched.current_cwd(),
+        watchdog_quanta: 0,
+        cpu_ticks: 0,
+        switches: 0,
+        wake_at: None,
+        pending_signals: 0,
+        joinable: false,
+        joined_by: None,
+        priority: parent_priority,
+        passed_over: 0,
+        image: parent_image,
     };
     
     // 6. Push Child to Parent list and scheduler
-    let current_proc_mut = sched.current.as_mut().unwrap();
+    let Some(current_proc_mut) = sched.current.as_mut() else {
+        crate::kpanic!("sys_fork: parent {} vanished while forking", parent_pid.0);
+    };
     current_proc_mut.children.push(child_pid);
     
     sched.ready_queue.push_back(child_process);
@@ -531,32 +997,63 @@ pub fn sys_fork() -> u64 {
 /// Syscall exec: Replace the current process with a new ELF binary.
 /// On success it NEVER returns here, it jumps manually into the new program.
 /// Returns only if there was an error loading the file.
-pub fn sys_exec(path: &str) -> Result<(), crate::loader::elf::ExecError> {
-    // CRITICAL: Copy path into kernel-owned memory BEFORE we free user pages!
-    // `path` is a &str pointing into user-space memory which will be unmapped below.
+/// An empty `argv` defaults to `[path]`.
+pub fn sys_exec(path: &str, argv: &[&str], envp: &[&str]) -> Result<(), crate::loader::elf::ExecError> {
+    // CRITICAL: Copy path, argv and envp into kernel-owned memory BEFORE we free user pages!
+    // They point into user-space memory which will be unmapped below.
     let owned_path = alloc::string::String::from(path);
-    
+    let owned_argv: alloc::vec::Vec<alloc::string::String> = if argv.is_empty() {
+        vec![owned_path.clone()]
+    } else {
+        argv.iter().map(|a| alloc::string::String::from(*a)).collect()
+    };
+    let owned_envp: alloc::vec::Vec<alloc::string::String> = envp.iter().map(|e| alloc::string::String::from(*e)).collect();
+    exec_owned(owned_path, owned_argv, owned_envp).map_err(|(e, _)| e)
+}
+
+/// `sys_exec` on kernel-owned strings. They are consumed: on success
+/// nothing returns here to drop them, so they are freed before the jump.
+/// On failure the path is handed back for the caller's error message.
+pub fn exec_owned(
+    owned_path: alloc::string::String,
+    owned_argv: alloc::vec::Vec<alloc::string::String>,
+    owned_envp: alloc::vec::Vec<alloc::string::String>,
+) -> Result<(), (crate::loader::elf::ExecError, alloc::string::String)> {
     // 1. Construct the new User Image Memory Map
-    let params = match crate::loader::elf::parse_and_map_elf(&owned_path) {
-        Ok(p) => p,
-        Err(e) => return Err(e),
+    let params = {
+        let argv_refs: alloc::vec::Vec<&str> = owned_argv.iter().map(|a| a.as_str()).collect();
+        let envp_refs: alloc::vec::Vec<&str> = owned_envp.iter().map(|e| e.as_str()).collect();
+        match crate::loader::elf::parse_and_map_elf(&owned_path, &argv_refs, &envp_refs) {
+            Ok(p) => p,
+            Err(e) => return Err((e, owned_path)),
+        }
     };
+    drop(owned_argv);
+    drop(owned_envp);
 
     // crate::log_info!("sys_exec: replacing current process with '{}'", owned_path);
 
     x86_64::instructions::interrupts::without_interrupts(|| {
         let mut sched = SCHEDULER.lock();
 
-        let current = sched.current.as_mut().expect("sys_exec called without active process!");
+        let Some(current) = sched.current.as_mut() else {
+            crate::kpanic!("sys_exec called without an active process");
+        };
         
         // 2. Free old virtual memory allocations
         for (vaddr, size) in &current.user_allocations {
             crate::memory::paging::free_user_memory(x86_64::VirtAddr::new(*vaddr), *size);
         }
+        for (vaddr, size) in &current.shared_allocations {
+            crate::memory::paging::free_shared_memory(x86_64::VirtAddr::new(*vaddr), *size);
+        }
+        current.shared_allocations.clear();
+        current.mmap_next = MMAP_BASE;
 
         // 3. Swap in new Page Table and Allocations
-        current.page_table = params.page_table;
+        let old_page_table = core::mem::replace(&mut current.page_table, params.page_table);
         current.user_allocations = params.allocations;
+        current.image = Some(params.image);
         current.name = owned_path;
         current.heap_start = params.heap_start;
         current.heap_end = params.heap_start; // Initially empty heap
@@ -564,15 +1061,21 @@ pub fn sys_exec(path: &str) -> Result<(), crate::loader::elf::ExecError> {
         // 4. Reset the Kernel Stack to a clean slate over the current frame!
         // We reset `current.context.rsp` to the top of the kernel stack where a fresh
         // Ring 3 trampoline will be orchestrated. 
-        let mut kernel_stack_top = current._kernel_stack.as_ptr() as u64 + TASK_STACK_SIZE as u64;
-        kernel_stack_top &= !0xF;
+        let Some(kernel_stack_top) = current.kernel_stack_top() else {
+            crate::kpanic!("sys_exec: PID {} has no kernel stack to exec on", current.pid.0);
+        };
         
         // We use the `Context` struct purely to point to the trampoline inside ring 0!
         current.context = Context::new(crate::loader::elf::usermode_trampoline as *const () as u64, kernel_stack_top);
         
-        // Inject R12 and R13 for trampoline usage
+        // Inject R12-R15 for trampoline usage
         current.context.r12 = params.entry;
         current.context.r13 = params.user_stack_top;
+        current.context.r14 = params.argc;
+        current.context.r15 = params.argv;
+
+        // The new image starts with a clean FPU, as after FNINIT
+        *current.fpu = FpuState::new();
         
         // Securely prepare CPU for context replacement
         crate::interrupts::gdt::set_tss_rsp0(kernel_stack_top);
@@ -584,19 +1087,24 @@ pub fn sys_exec(path: &str) -> Result<(), crate::loader::elf::ExecError> {
                 in(reg) current.page_table
             );
         }
+        // Its user pages were freed above; now the tables can go too
+        crate::memory::paging::free_page_table(x86_64::PhysAddr::new(old_page_table));
 
         let next_ctx_ptr = &current.context as *const Context;
+        let next_fpu_ptr = &*current.fpu as *const FpuState;
         
         // 6. Jump linearly into the trampoline (Wipes out old Syscall state!)
         drop(sched);
         unsafe {
-            crate::scheduler::context::restore_context(next_ctx_ptr);
+            crate::scheduler::context::restore_context(next_ctx_ptr, next_fpu_ptr);
         }
     });
 
-    unreachable!("sys_exec should never return on success");
+    crate::kpanic!("sys_exec returned from restore_context");
 }
 
+/// User registers saved on the kernel stack by `syscall_handler_asm`,
+/// lowest address first, followed by the CPU's interrupt frame.
 #[repr(C, packed)]
 #[derive(Clone, Copy)]
 pub struct TrapFrame {
@@ -621,6 +1129,9 @@ pub struct TrapFrame {
     pub ss: u64,
 }
 
+// The syscall entry builds this frame push by push; the two must stay in sync.
+const _: () = assert!(core::mem::size_of::<TrapFrame>() == crate::interrupts::usermode::TRAP_FRAME_SIZE);
+
 #[unsafe(naked)]
 pub extern "C" fn fork_trampoline() {
     unsafe {
@@ -645,13 +1156,36 @@ pub extern "C" fn fork_trampoline() {
     }
 }
 
+/// Why `sys_wait` returned without reaping a child.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitError {
+    /// No child matches the PID asked for.
+    NoChild,
+    /// A signal arrived while waiting.
+    Interrupted,
+}
+
+impl core::fmt::Display for WaitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            WaitError::NoChild     => write!(f, "no child processes"),
+            WaitError::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
 /// Syscall wait: Wait for a child process to change state to Zombie, then reap it.
 /// If `target_pid` is u64::MAX (-1), wait for ANY child.
-/// Returns the Exit Status of the child, or u64::MAX if no children exist.
-pub fn sys_wait(target_pid: u64) -> u64 {
+/// Returns the reaped child's PID and wait status (see `syscalls::wait`).
+/// With `WNOHANG` in `options`, returns `Ok(None)` at once if no matching
+/// child has exited yet.
+pub fn sys_wait(target_pid: u64, options: u64) -> Result<Option<(ProcessId, u64)>, WaitError> {
     loop {
         let mut sched = SCHEDULER.lock();
         let current_pid = sched.current.as_ref().map(|p| p.pid).unwrap_or(ProcessId(0));
+        if sched.current.as_ref().is_some_and(|p| p.pending_signals != 0) {
+            return Err(WaitError::Interrupted);
+        }
         
         let mut child_found = false;
         let mut reaped_pid = None;
@@ -676,7 +1210,11 @@ pub fn sys_wait(target_pid: u64) -> u64 {
 
         if let Some(pid) = reaped_pid {
             // A Zombie was found! We must reap it (Remove it entirely from scheduler)
-            sched.ready_queue.retain(|p| p.pid != pid);
+            if let Some(i) = sched.ready_queue.iter().position(|p| p.pid == pid) {
+                if let Some(zombie) = sched.ready_queue.remove(i) {
+                    crate::memory::paging::free_page_table(x86_64::PhysAddr::new(zombie.page_table));
+                }
+            }
             
             // Remove it from current process's children tracking list
             if let Some(current) = sched.current.as_mut() {
@@ -684,12 +1222,15 @@ pub fn sys_wait(target_pid: u64) -> u64 {
             }
             
             // crate::log_info!("sys_wait: Process {} reaped Zombie child {}", current_pid.0, pid.0);
-            return reaped_status;
+            return Ok(Some((pid, reaped_status)));
         }
 
         if !child_found {
             // No matching children exist computationally. Return error.
-            return u64::MAX;
+            return Err(WaitError::NoChild);
+        }
+        if options & crate::syscalls::wait::WNOHANG != 0 {
+            return Ok(None);
         }
 
         // 2. Child exists but is still Running/Ready. We must BLOCK and yield!
@@ -724,15 +1265,6 @@ fn create_default_fd_table() -> alloc::vec::Vec<Option<alloc::sync::Arc<spin::Mu
     table
 }
 
-/// Global wrapper to wake up all blocked tasks (e.g., when pipe data arrives or space frees).
-pub fn wake_all_blocked() {
-    // try_lock used because this is often called mid-syscall when the lock might already
-    // be taken, or just before another lock sequence.
-    if let Some(mut sched) = SCHEDULER.try_lock() {
-        sched.wake_all_blocked();
-    }
-}
-
 /// Syscall brk: Sets the end of the data segment (heap).
 /// Returns the new program break, or the old one if it failed or if `addr` is 0.
 pub fn sys_brk(addr: u64) -> u64 {
@@ -777,3 +1309,39 @@ pub fn sys_brk(addr: u64) -> u64 {
     current.heap_end = addr;
     addr
 }
+
+/// Base of the anonymous mmap area (4 GiB), well above the ELF image, stack and brk heap.
+pub const MMAP_BASE: u64 = 0x1_0000_0000;
+
+/// Syscall mmap: map `len` bytes of zeroed anonymous memory into the current process.
+/// `shared` regions keep their frames across `fork` (MAP_SHARED); private ones are
+/// deep-copied like the rest of the address space. Returns the start address, or None
+/// if the frames could not be allocated.
+pub fn sys_mmap(len: u64, shared: bool) -> Option<u64> {
+    let mut sched = SCHEDULER.lock();
+    let current = sched.current.as_mut()?;
+
+    let size = (len + 4095) & !4095;
+    let addr = current.mmap_next;
+    let vaddr = x86_64::VirtAddr::new(addr);
+
+    if shared {
+        if !crate::memory::paging::allocate_shared_memory(vaddr, size) {
+            crate::log_error!("sys_mmap failed allocating {} shared bytes", size);
+            return None;
+        }
+        current.shared_allocations.push((addr, size));
+    } else {
+        if !crate::memory::paging::allocate_user_memory(vaddr, size) {
+            crate::log_error!("sys_mmap failed allocating {} bytes", size);
+            return None;
+        }
+        // Only recycled frames come zeroed; fresh ones hold whatever the
+        // memory map left there
+        unsafe { core::ptr::write_bytes(addr as *mut u8, 0, size as usize); }
+        current.user_allocations.push((addr, size));
+    }
+
+    current.mmap_next = addr + size;
+    Some(addr)
+}
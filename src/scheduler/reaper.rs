@@ -0,0 +1,42 @@
+use super::{ProcessId, ProcessState, SCHEDULER};
+
+/// PID of the init process, which adopts orphaned children and reaps them.
+pub const INIT_PID: ProcessId = ProcessId(1);
+
+/// Entry point of the init kernel task (PID 1).
+/// Reaps adopted zombies forever, sleeping between timer ticks.
+pub fn init_task() {
+    loop {
+        reap_orphans();
+
+        // Kernel tasks start with IF=0 (switched in from `without_interrupts`)
+        x86_64::instructions::interrupts::enable();
+        x86_64::instructions::hlt();
+        super::yield_now();
+    }
+}
+
+/// Remove every zombie whose parent is init. Returns how many were reaped.
+pub fn reap_orphans() -> usize {
+    let mut sched = SCHEDULER.lock();
+
+    let before = sched.ready_queue.len();
+    let mut reaped = alloc::vec::Vec::new();
+    sched.ready_queue.retain(|p| {
+        if p.parent_pid == Some(INIT_PID) && p.state == ProcessState::Zombie {
+            reaped.push(p.pid);
+            crate::memory::paging::free_page_table(x86_64::PhysAddr::new(p.page_table));
+            false
+        } else {
+            true
+        }
+    });
+
+    if let Some(current) = sched.current.as_mut() {
+        if current.pid == INIT_PID {
+            current.children.retain(|c| !reaped.contains(c));
+        }
+    }
+
+    before - sched.ready_queue.len()
+}
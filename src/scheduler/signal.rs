@@ -0,0 +1,204 @@
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use x86_64::instructions::interrupts;
+use super::{ProcessId, ProcessState, Scheduler, TrapFrame, SCHEDULER};
+
+/// Interrupt from the keyboard (Ctrl-C).
+pub const SIGINT: u32 = 2;
+/// Terminate at once; cannot be caught.
+pub const SIGKILL: u32 = 9;
+/// Polite termination request.
+pub const SIGTERM: u32 = 15;
+
+/// Breakpoint or debug trap in user mode. Raised by the kernel, not sendable.
+pub const SIGTRAP: u32 = 5;
+/// Bad memory access in user mode. Raised by the kernel, not sendable.
+pub const SIGSEGV: u32 = 11;
+
+/// Why `send` refused a signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalError {
+    /// Not a signal this kernel knows.
+    InvalidSignal,
+    /// No task with that PID (zombies still count as existing).
+    NoSuchProcess,
+    /// The kernel task, init, or another kernel task: none of them ever
+    /// return to user mode, where signals are delivered.
+    NotPermitted,
+}
+
+impl fmt::Display for SignalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SignalError::InvalidSignal => write!(f, "invalid signal"),
+            SignalError::NoSuchProcess => write!(f, "no such process"),
+            SignalError::NotPermitted  => write!(f, "operation not permitted"),
+        }
+    }
+}
+
+/// Post `sig` to `pid`. Signal 0 only checks that the task exists.
+///
+/// The signal is recorded in the target's `pending_signals` and acted on the
+/// next time it heads back to user mode: on return from a syscall, or at a
+/// timer tick that interrupted it in Ring 3. A Blocked target is woken so it
+/// gets there: the wait loops give up early while a signal is pending.
+pub fn send(pid: ProcessId, sig: u32) -> Result<(), SignalError> {
+    if !matches!(sig, 0 | SIGINT | SIGKILL | SIGTERM) {
+        return Err(SignalError::InvalidSignal);
+    }
+    if pid == ProcessId(0) || pid == super::reaper::INIT_PID {
+        return Err(SignalError::NotPermitted);
+    }
+
+    let kernel_p4 = crate::memory::paging::kernel_page_table().as_u64();
+    interrupts::without_interrupts(|| {
+        let mut guard = SCHEDULER.lock();
+        let sched = &mut *guard;
+        let target = sched.current.iter_mut()
+            .chain(sched.ready_queue.iter_mut())
+            .find(|p| p.pid == pid)
+            .ok_or(SignalError::NoSuchProcess)?;
+
+        // Already dead: nothing left to do, but the PID is still taken
+        if target.state == ProcessState::Zombie {
+            return Ok(());
+        }
+        if target.page_table == kernel_p4 {
+            return Err(SignalError::NotPermitted);
+        }
+        if sig == 0 {
+            return Ok(());
+        }
+
+        target.pending_signals |= 1 << sig;
+        if target.state == ProcessState::Blocked {
+            target.wake_at = None;
+            target.state = ProcessState::Ready;
+        }
+        Ok(())
+    })
+}
+
+/// True if the running task has a signal waiting. Blocking loops check this
+/// so a signalled task gets back to user mode, where the signal is delivered.
+pub fn pending() -> bool {
+    SCHEDULER.lock().current.as_ref().is_some_and(|p| p.pending_signals != 0)
+}
+
+/// `FOREGROUND` while no task is in the foreground.
+const NO_FOREGROUND: u64 = u64::MAX;
+
+/// The task the shell is running in the foreground, which Ctrl-C interrupts.
+static FOREGROUND: AtomicU64 = AtomicU64::new(NO_FOREGROUND);
+
+/// Ctrl-C came in while the scheduler was locked; the scheduler posts the
+/// SIGINT on its next pass.
+static INTERRUPT_DEFERRED: AtomicBool = AtomicBool::new(false);
+
+/// Make `pid` the task Ctrl-C interrupts, or nobody.
+pub fn set_foreground(pid: Option<ProcessId>) {
+    FOREGROUND.store(pid.map_or(NO_FOREGROUND, |p| p.0), Ordering::Release);
+}
+
+/// Called by the keyboard driver on Ctrl-C: post SIGINT to the foreground
+/// task. Unlike `send`, this reaches kernel tasks too, so a shell builtin in
+/// the foreground sees it through `pending`. Returns false if nothing is in
+/// the foreground, leaving the key to be read as usual.
+pub fn interrupt_foreground() -> bool {
+    if FOREGROUND.load(Ordering::Acquire) == NO_FOREGROUND {
+        return false;
+    }
+    interrupts::without_interrupts(|| match SCHEDULER.try_lock() {
+        Some(mut sched) => post_interrupt(&mut sched),
+        None => INTERRUPT_DEFERRED.store(true, Ordering::Release),
+    });
+    true
+}
+
+/// Post a deferred Ctrl-C. Called by the scheduler with its lock held.
+pub(super) fn apply_deferred_interrupt(sched: &mut Scheduler) {
+    if INTERRUPT_DEFERRED.swap(false, Ordering::Acquire) {
+        post_interrupt(sched);
+    }
+}
+
+fn post_interrupt(sched: &mut Scheduler) {
+    let pid = ProcessId(FOREGROUND.load(Ordering::Acquire));
+    let target = sched.current.iter_mut()
+        .chain(sched.ready_queue.iter_mut())
+        .find(|p| p.pid == pid && p.state != ProcessState::Zombie);
+    if let Some(target) = target {
+        target.pending_signals |= 1 << SIGINT;
+        if target.state == ProcessState::Blocked {
+            target.wake_at = None;
+            target.state = ProcessState::Ready;
+        }
+    }
+}
+
+/// Clear a SIGINT posted to the running task and report whether there was
+/// one. For kernel tasks, which never reach user mode to have it delivered.
+pub fn take_interrupt() -> bool {
+    interrupts::without_interrupts(|| {
+        let mut sched = SCHEDULER.lock();
+        let Some(current) = sched.current.as_mut() else { return false };
+        let posted = current.pending_signals & (1 << SIGINT) != 0;
+        current.pending_signals &= !(1 << SIGINT);
+        posted
+    })
+}
+
+/// Remove and return the most urgent pending signal of the running task.
+fn take_pending() -> Option<u32> {
+    interrupts::without_interrupts(|| {
+        let mut sched = SCHEDULER.try_lock()?;
+        let current = sched.current.as_mut()?;
+        let sig = [SIGKILL, SIGTERM, SIGINT].into_iter().find(|&s| current.pending_signals & (1 << s) != 0)?;
+        current.pending_signals &= !(1 << sig);
+        Some(sig)
+    })
+}
+
+/// Called by the syscall entry stub after `dispatch`, just before `iretq`
+/// resumes user code. SIGKILL ends the task right here. For SIGTERM the
+/// return is redirected: `frame` is rewritten so `iretq` enters
+/// `default_handler` in Ring 0 on the top of this kernel stack, with the
+/// signal number in RDI, instead of going back to the user's RIP.
+pub extern "C" fn deliver_on_syscall_return(frame: *mut TrapFrame) {
+    let Some(sig) = take_pending() else { return };
+    if sig == SIGKILL {
+        super::kill_current(sig);
+        unreachable!();
+    }
+
+    let selectors = &crate::interrupts::gdt::GDT.1;
+    let stack_top = frame as u64 + crate::interrupts::usermode::TRAP_FRAME_SIZE as u64;
+    // SAFETY: `frame` is this task's live TrapFrame, built by the entry stub
+    let frame = unsafe { &mut *frame };
+    frame.rip = default_handler as *const () as u64;
+    frame.cs = selectors.kernel_code.0 as u64;
+    frame.ss = selectors.kernel_data.0 as u64;
+    // As if called: the return address slot leaves RSP 8 off 16-byte alignment
+    frame.rsp = stack_top - 8;
+    frame.rdi = sig as u64;
+}
+
+/// Called from the timer interrupt with the interrupted code segment. A task
+/// caught in Ring 3 holds no kernel locks, so a pending signal's default
+/// action (termination) is carried out on the spot, as the watchdog does.
+pub fn deliver_on_tick(interrupted_cs: u64) {
+    if interrupted_cs & 3 != 3 {
+        return;
+    }
+    if let Some(sig) = take_pending() {
+        super::kill_current(sig);
+    }
+}
+
+/// Default action of a terminating signal. Entered by `iretq` from the
+/// syscall return path (see `deliver_on_syscall_return`); never returns.
+extern "C" fn default_handler(sig: u64) -> ! {
+    super::kill_current(sig as u32);
+    unreachable!();
+}
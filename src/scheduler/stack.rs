@@ -0,0 +1,131 @@
+// Kernel task stacks. Each one sits in its own slot of a dedicated area,
+// with an unmapped guard page below it: running off the bottom faults
+// instead of overwriting whatever lies next to it.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+use x86_64::structures::idt::InterruptStackFrame;
+use x86_64::VirtAddr;
+use super::TASK_STACK_SIZE;
+
+const PAGE_SIZE: u64 = 4096;
+/// A guard page and the stack above it.
+const SLOT_SIZE: u64 = PAGE_SIZE + TASK_STACK_SIZE as u64;
+/// Most kernel stacks that can exist at once.
+pub const MAX_STACKS: usize = 256;
+
+/// Start of the stack area: right after the heap's range, in the same P4
+/// slot, which every address space shares. Stacks mapped later are thus
+/// visible in every process.
+pub const STACK_AREA_START: u64 = (crate::allocator::HEAP_START + crate::allocator::HEAP_MAX_SIZE) as u64;
+pub const STACK_AREA_SIZE: u64 = MAX_STACKS as u64 * SLOT_SIZE;
+
+/// Slots of stacks that were freed; still mapped, handed out first.
+static FREE_SLOTS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+/// Slots below this one have been mapped. Only grows.
+static MAPPED_SLOTS: AtomicUsize = AtomicUsize::new(0);
+
+/// A task's kernel stack (the RSP0 target for Ring 3 -> Ring 0 transitions):
+/// `TASK_STACK_SIZE` bytes with an unmapped guard page below. A dropped
+/// stack keeps its frames and goes back to the pool.
+pub struct KernelStack {
+    slot: usize,
+}
+
+impl KernelStack {
+    /// A zeroed stack, reusing a freed one if there is any. Panics when all
+    /// `MAX_STACKS` are in use or frames run out, as the heap does when it
+    /// cannot grow.
+    pub fn new() -> KernelStack {
+        let slot = interrupts::without_interrupts(|| {
+            let mut free = FREE_SLOTS.lock();
+            if let Some(slot) = free.pop() {
+                return slot;
+            }
+            // Mapped under the lock, so MAPPED_SLOTS never covers a slot
+            // that is still being mapped
+            let slot = MAPPED_SLOTS.load(Ordering::Relaxed);
+            if slot == MAX_STACKS {
+                crate::kpanic!("out of kernel stacks ({} in use)", MAX_STACKS);
+            }
+            let bottom = slot_bottom(slot);
+            if !crate::memory::paging::map_kernel_memory(VirtAddr::new(bottom), TASK_STACK_SIZE as u64) {
+                crate::kpanic!("out of memory for a kernel stack");
+            }
+            MAPPED_SLOTS.store(slot + 1, Ordering::Release);
+            slot
+        });
+
+        let stack = KernelStack { slot };
+        unsafe { core::ptr::write_bytes(stack.bottom() as *mut u8, 0, TASK_STACK_SIZE) };
+        stack
+    }
+
+    /// Lowest usable address; the guard page ends here.
+    pub fn bottom(&self) -> u64 {
+        slot_bottom(self.slot)
+    }
+
+    /// Initial stack pointer: one past the highest byte, 16-byte aligned.
+    pub fn top(&self) -> u64 {
+        self.bottom() + TASK_STACK_SIZE as u64
+    }
+}
+
+impl Drop for KernelStack {
+    fn drop(&mut self) {
+        interrupts::without_interrupts(|| FREE_SLOTS.lock().push(self.slot));
+    }
+}
+
+fn slot_bottom(slot: usize) -> u64 {
+    STACK_AREA_START + slot as u64 * SLOT_SIZE + PAGE_SIZE
+}
+
+/// Whether `addr` is in the guard page below some kernel stack.
+pub fn is_guard_page(addr: u64) -> bool {
+    addr >= STACK_AREA_START && addr < STACK_AREA_START + STACK_AREA_SIZE
+        && (addr - STACK_AREA_START) % SLOT_SIZE < PAGE_SIZE
+}
+
+/// Whether `addr` is on a mapped kernel stack, in use or not. Takes no
+/// locks, for the panic path's backtrace.
+pub fn is_stack_memory(addr: u64) -> bool {
+    let end = STACK_AREA_START + MAPPED_SLOTS.load(Ordering::Acquire) as u64 * SLOT_SIZE;
+    addr >= STACK_AREA_START && addr < end && !is_guard_page(addr)
+}
+
+/// Called by the double fault handler when the running task ran into its
+/// guard page: the page fault could not be delivered on the exhausted stack.
+/// Ends the task with SIGSEGV, as a user process would be. If the scheduler
+/// was locked at the time, no other task can be switched to, so panic.
+///
+/// The task never unwinds, so any lock it held stays held. The VFS, serial,
+/// heap and frame allocator locks would hang the rest of the system, so an
+/// overflow holding any of them panics too; other locks are left locked.
+/// The report skips `SERIAL1` and the log for the same reason.
+pub fn overflowed(stack_frame: &InterruptStackFrame) -> ! {
+    let rip = stack_frame.instruction_pointer.as_u64();
+    let pid = super::SCHEDULER.try_lock().and_then(|s| s.current.as_ref().map(|p| p.pid.0));
+    let Some(pid) = pid else {
+        crate::kpanic!("kernel stack overflow at {:#x} with the scheduler locked", rip);
+    };
+    if crate::fs::VFS.owner() == Some(super::ProcessId(pid)) {
+        crate::kpanic!("stack overflow in PID {} at {:#x} holding the VFS lock", pid, rip);
+    }
+    if crate::serial::SERIAL1.owner() == Some(super::ProcessId(pid)) {
+        crate::kpanic!("stack overflow in PID {} at {:#x} holding the serial port", pid, rip);
+    }
+    // Both are only taken with interrupts off: held now means held by the task
+    if crate::allocator::heap_locked() {
+        crate::kpanic!("stack overflow in PID {} at {:#x} holding the heap lock", pid, rip);
+    }
+    if crate::memory::FRAME_ALLOCATOR.is_locked() {
+        crate::kpanic!("stack overflow in PID {} at {:#x} holding the frame allocator", pid, rip);
+    }
+    crate::serial::emergency_print(format_args!("[ERROR] stack overflow in PID {} at {:#x}\n", pid, rip));
+    super::kill_current(super::signal::SIGSEGV);
+    unreachable!();
+}
@@ -1,7 +1,9 @@
-use alloc::boxed::Box;
 use alloc::string::String;
 use alloc::vec::Vec;
+use crate::allocator::slab::SlabBox;
 use super::context::Context;
+use super::fpu::FpuState;
+use super::stack::KernelStack;
 
 /// Unique process identifier (PID).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -16,30 +18,89 @@ pub enum ProcessState {
     Zombie,
 }
 
+/// Scheduling priorities. The scheduler always runs a task from the highest
+/// level that has one ready (see `Scheduler::schedule_next`).
+pub const PRIORITY_LOW: u8 = 0;
+pub const PRIORITY_NORMAL: u8 = 1;
+pub const PRIORITY_HIGH: u8 = 2;
+
 /// A single process unit.
 pub struct Process {
     pub pid: ProcessId,
     pub parent_pid: Option<ProcessId>,
     pub name: String,
     pub state: ProcessState,
+    /// Wait status once the task has ended (see `syscalls::wait`).
     pub exit_status: Option<u64>,
     pub children: Vec<ProcessId>,
     pub context: Context,
+    /// Saved FPU/SSE registers, swapped alongside `context`. Kept out of line
+    /// so the area keeps its alignment and address while the Process moves around.
+    pub fpu: SlabBox<FpuState>,
     
     // Address Space Root Table PTR (CR3) for this process
     pub page_table: u64,
     
     /// Owned kernel stack memory — kept alive as long as the process exists.
-    pub _kernel_stack: Box<[u8]>,
+    /// None for the kernel process, which runs on the boot stack.
+    pub _kernel_stack: Option<KernelStack>,
     
     // Virtual Memory Blocks dynamically allocated to User (Tracked for cleanup)
     pub user_allocations: Vec<(u64, u64)>, // (VirtAddr_Start, Size)
+    /// MAP_SHARED regions: their frames are refcounted and mapped as-is into forked children.
+    pub shared_allocations: Vec<(u64, u64)>, // (VirtAddr_Start, Size)
+    /// Next free address in the anonymous mmap area.
+    pub mmap_next: u64,
 
     /// Process File Descriptor Table
     pub heap_start: u64,
     pub heap_end: u64,
     pub fd_table: Vec<Option<alloc::sync::Arc<spin::Mutex<crate::fs::fd::File>>>>,
+    /// Absolute, normalized working directory. Inherited by spawned and
+    /// forked children, kept across exec.
+    pub cwd: String,
+
+    /// Timer quanta consumed since this process last yielded or made a syscall (see `watchdog`).
+    pub watchdog_quanta: u64,
+    /// Timer ticks this process has been running for, in total (see `account_tick`).
+    pub cpu_ticks: u64,
+    /// Times the scheduler has switched to this process.
+    pub switches: u64,
+    /// Tick at which a task blocked in `sleep_ticks` becomes Ready again.
+    pub wake_at: Option<u64>,
+    /// Signals posted but not yet delivered, one bit per signal number (see `signal`).
+    pub pending_signals: u32,
+    /// Left as a zombie for `join` when it ends, rather than handed to init
+    /// (see `join`).
+    pub joinable: bool,
+    /// The task that called `join` on this one; no other task may join it.
+    pub joined_by: Option<ProcessId>,
+    /// One of the `PRIORITY_*` levels. Inherited by forked children.
+    pub priority: u8,
+    /// Times another task was picked to run while this one was Ready.
+    /// Raises its effective priority (see `AGING_PICKS`); reset when it runs.
+    pub passed_over: u32,
+
+    /// The program a user process runs, loaded page by page as it is
+    /// touched (see `memory::demand`). None for kernel tasks.
+    pub image: Option<alloc::sync::Arc<crate::memory::demand::LazyImage>>,
+}
+
+impl Process {
+    /// Ready to be picked by the scheduler.
+    pub fn is_runnable(&self) -> bool {
+        self.state == ProcessState::Ready || self.state == ProcessState::Running
+    }
+
+    /// `priority` raised one level for every `AGING_PICKS` times the task
+    /// was passed over, up to `PRIORITY_HIGH`.
+    pub fn effective_priority(&self) -> u8 {
+        let boost = (self.passed_over / super::AGING_PICKS).min(PRIORITY_HIGH as u32) as u8;
+        self.priority.saturating_add(boost).min(PRIORITY_HIGH)
+    }
 
-    /// Optional program image memory (For legacy compatibility before full VFS elf parsing is moved to Page Mapping)
-    pub _image: Option<Box<[u8]>>,
+    /// Top of this process's kernel stack, if it has its own.
+    pub fn kernel_stack_top(&self) -> Option<u64> {
+        self._kernel_stack.as_ref().map(|s| s.top())
+    }
 }
@@ -0,0 +1,90 @@
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicBool, Ordering};
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+use super::{ProcessId, ProcessState, SCHEDULER};
+
+/// Set when a wake-up found `SCHEDULER` locked (an interrupt arrived while a
+/// syscall held it). The next reschedule wakes every blocked task instead;
+/// waiters re-check their condition, so the extra wake-ups are harmless.
+pub(super) static WAKE_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Tasks blocked until some event, such as a completed console line.
+/// `wake_all` is safe to call from interrupt handlers: it neither allocates
+/// nor waits for the scheduler lock.
+///
+/// ```ignore
+/// static LINE_READY: WaitQueue = WaitQueue::new();
+/// LINE_READY.wait_until(|| line_is_complete());   // reader
+/// LINE_READY.wake_all();                           // keyboard IRQ
+/// ```
+pub struct WaitQueue {
+    waiters: Mutex<Vec<ProcessId>>,
+}
+
+impl WaitQueue {
+    pub const fn new() -> Self {
+        WaitQueue { waiters: Mutex::new(Vec::new()) }
+    }
+
+    /// Block the calling task until `ready` returns true. `ready` runs with
+    /// interrupts disabled, so a wake-up from an interrupt handler can't slip
+    /// in between the check and going to sleep; it must not block.
+    /// Returns false, with `ready` still false, if a signal is posted to the
+    /// task first.
+    pub fn wait_until(&self, mut ready: impl FnMut() -> bool) -> bool {
+        let pid = super::current_pid();
+        loop {
+            let done = interrupts::without_interrupts(|| {
+                let mut waiters = self.waiters.lock();
+                let mut sched = SCHEDULER.lock();
+                let Some(current) = sched.current.as_mut() else {
+                    return Some(true);
+                };
+                let outcome = if ready() {
+                    true
+                } else if current.pending_signals != 0 {
+                    false
+                } else {
+                    if !waiters.contains(&pid) {
+                        waiters.push(pid);
+                    }
+                    current.state = ProcessState::Blocked;
+                    return None;
+                };
+                waiters.retain(|&p| p != pid);
+                current.state = ProcessState::Running;
+                Some(outcome)
+            });
+            if let Some(ready) = done {
+                return ready;
+            }
+            super::block_current();
+        }
+    }
+
+    /// Tasks waiting right now.
+    pub fn waiting(&self) -> usize {
+        self.waiters.lock().len()
+    }
+
+    /// Make every waiting task runnable again.
+    pub fn wake_all(&self) {
+        interrupts::without_interrupts(|| {
+            let mut waiters = self.waiters.lock();
+            if waiters.is_empty() {
+                return;
+            }
+            match SCHEDULER.try_lock() {
+                Some(mut sched) => {
+                    for &pid in waiters.iter() {
+                        sched.wake(pid);
+                    }
+                }
+                None => WAKE_PENDING.store(true, Ordering::Release),
+            }
+            // clear() keeps the allocation: nothing is freed in interrupt context
+            waiters.clear();
+        });
+    }
+}
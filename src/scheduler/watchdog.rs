@@ -0,0 +1,68 @@
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use super::{ProcessId, SCHEDULER};
+use crate::interrupts::pit::DEFAULT_HZ;
+
+/// Quanta (timer ticks) a task may run without yielding before a warning is logged.
+/// Default 5 seconds at the default PIT rate.
+pub static WARN_QUANTA: AtomicU64 = AtomicU64::new(5 * DEFAULT_HZ as u64);
+
+/// Quanta after which a spinning user task is force-terminated. 0 disables killing.
+/// Default 15 seconds at the default PIT rate.
+pub static KILL_QUANTA: AtomicU64 = AtomicU64::new(15 * DEFAULT_HZ as u64);
+
+/// Set whenever the running task makes forward progress (enters a syscall).
+/// Consumed by the next timer tick.
+static PETTED: AtomicBool = AtomicBool::new(false);
+
+/// Called on syscall entry: the current task is alive and talking to the kernel.
+pub fn pet() {
+    PETTED.store(true, Ordering::Relaxed);
+}
+
+/// Forget a pending pet when the CPU is handed to another task, so the
+/// next task is not credited with the previous one's syscall.
+pub fn clear() {
+    PETTED.store(false, Ordering::Relaxed);
+}
+
+/// Called from the timer interrupt (after EOI) with the interrupted code segment.
+/// Charges one quantum to the running task and enforces the warn/kill limits.
+pub fn tick(interrupted_cs: u64) {
+    let mut sched = match SCHEDULER.try_lock() {
+        Some(lock) => lock,
+        None => return, // Scheduler busy — the task is inside the kernel anyway
+    };
+
+    let current = match sched.current.as_mut() {
+        Some(p) => p,
+        None => return,
+    };
+
+    // PID 0 is the kernel/shell idle loop and is never watched
+    if current.pid == ProcessId(0) {
+        return;
+    }
+
+    if PETTED.swap(false, Ordering::Relaxed) {
+        current.watchdog_quanta = 0;
+        return;
+    }
+
+    current.watchdog_quanta += 1;
+    let quanta = current.watchdog_quanta;
+    let pid = current.pid.0;
+
+    let warn = WARN_QUANTA.load(Ordering::Relaxed);
+    if warn != 0 && quanta == warn {
+        crate::log_warn!("watchdog: PID {} ('{}') has run {} quanta without yielding", pid, current.name, quanta);
+    }
+
+    // Only kill tasks interrupted in Ring 3: a kernel path may be holding locks
+    // that would never be released if we tore it down here.
+    let kill = KILL_QUANTA.load(Ordering::Relaxed);
+    if kill != 0 && quanta >= kill && interrupted_cs & 3 == 3 {
+        crate::log_error!("watchdog: killing PID {} ('{}') after {} quanta", pid, current.name, quanta);
+        drop(sched);
+        super::kill_current(super::signal::SIGKILL);
+    }
+}
@@ -1,7 +1,17 @@
+use core::sync::atomic::{AtomicBool, Ordering};
 use lazy_static::lazy_static;
 use spin::Mutex;
+use crate::drivers::keyboard::scancodes::KeyCode;
+use crate::scheduler::lock::InheritMutex;
 use x86_64::instructions::port::Port;
 
+/// I/O base of COM1.
+const COM1: u16 = 0x3F8;
+/// Line status: a received byte is waiting in the data register.
+const LINE_DATA_READY: u8 = 0x01;
+/// Line status: the transmit holding register is empty.
+const LINE_TX_EMPTY: u8 = 0x20;
+
 pub struct SerialPort {
     data: Port<u8>,
     int_en: Port<u8>,
@@ -32,13 +42,25 @@ impl SerialPort {
             self.line_ctrl.write(0x03);
             self.fifo_ctrl.write(0xC7);
             self.modem_ctrl.write(0x0B);
+            // Interrupt on received data only (IRQ 4, see `receive_interrupt`)
             self.int_en.write(0x01);
         }
     }
 
     fn wait_for_tx_empty(&mut self) {
         unsafe {
-            while (self.line_sts.read() & 0x20) == 0 {}
+            while (self.line_sts.read() & LINE_TX_EMPTY) == 0 {}
+        }
+    }
+
+    /// The next received byte, if one is waiting.
+    pub fn recv(&mut self) -> Option<u8> {
+        unsafe {
+            if self.line_sts.read() & LINE_DATA_READY != 0 {
+                Some(self.data.read())
+            } else {
+                None
+            }
         }
     }
 
@@ -60,10 +82,10 @@ impl core::fmt::Write for SerialPort {
 }
 
 lazy_static! {
-    pub static ref SERIAL1: Mutex<SerialPort> = {
-        let mut serial_port = unsafe { SerialPort::new(0x3F8) };
+    pub static ref SERIAL1: InheritMutex<SerialPort> = {
+        let mut serial_port = unsafe { SerialPort::new(COM1) };
         serial_port.init();
-        Mutex::new(serial_port)
+        InheritMutex::new(serial_port)
     };
 }
 
@@ -76,30 +98,191 @@ pub fn _print(args: ::core::fmt::Arguments) {
 #[macro_export]
 macro_rules! log_info {
     ($($arg:tt)*) => {
-        $crate::serial::_print(format_args!("[INFO] "));
-        $crate::serial::_print(format_args!($($arg)*));
-        $crate::serial::_print(format_args!("\n"));
+        $crate::klog::_log($crate::klog::Level::Info, format_args!($($arg)*));
     };
 }
 
 #[macro_export]
 macro_rules! log_warn {
     ($($arg:tt)*) => {
-        $crate::serial::_print(format_args!("[WARN] "));
-        $crate::serial::_print(format_args!($($arg)*));
-        $crate::serial::_print(format_args!("\n"));
+        $crate::klog::_log($crate::klog::Level::Warn, format_args!($($arg)*));
     };
 }
 
 #[macro_export]
 macro_rules! log_error {
     ($($arg:tt)*) => {
-        $crate::serial::_print(format_args!("[ERROR] "));
-        $crate::serial::_print(format_args!($($arg)*));
-        $crate::serial::_print(format_args!("\n"));
+        $crate::klog::_log($crate::klog::Level::Error, format_args!($($arg)*));
     };
 }
 
 pub fn init() {
-    let _ = SERIAL1.lock();
+    let mut port = SERIAL1.lock();
+    // Drop whatever arrived before we were listening
+    while port.recv().is_some() {}
+}
+
+/// Turns the bytes a terminal emulator sends into keys. CR, LF and CR LF
+/// each end a line; DEL and BS are Backspace; Ctrl-letters arrive as C0
+/// controls; `ESC [` sequences give the arrows and PageUp/PageDown, and
+/// `ESC O P`..`S` give F1-F4. Other escape sequences are consumed and dropped.
+pub struct SerialDecoder {
+    state: DecodeState,
+}
+
+#[derive(Clone, Copy)]
+enum DecodeState {
+    Ground,
+    /// Just saw CR: an LF right after it belongs to the same Enter.
+    AfterCr,
+    /// Just saw ESC.
+    Escape,
+    /// Inside `ESC [`, with the numeric parameter read so far.
+    Csi(u8),
+    /// Just saw `ESC O`; one more byte follows.
+    Ss3,
+}
+
+impl SerialDecoder {
+    pub const fn new() -> Self {
+        SerialDecoder { state: DecodeState::Ground }
+    }
+
+    /// Feed one received byte; returns the key it completes, if any.
+    pub fn feed(&mut self, byte: u8) -> Option<KeyCode> {
+        match self.state {
+            DecodeState::Escape => {
+                self.state = match byte {
+                    b'[' => DecodeState::Csi(0),
+                    b'O' => DecodeState::Ss3,
+                    _ => DecodeState::Ground,
+                };
+                return None;
+            }
+            DecodeState::Ss3 => {
+                self.state = DecodeState::Ground;
+                return match byte {
+                    b'P'..=b'S' => Some(KeyCode::F(byte - b'P' + 1)),
+                    _ => None,
+                };
+            }
+            DecodeState::Csi(param) => {
+                return match byte {
+                    b'0'..=b'9' => {
+                        self.state = DecodeState::Csi(param.saturating_mul(10).saturating_add(byte - b'0'));
+                        None
+                    }
+                    // Final byte ends the sequence
+                    0x40..=0x7E => {
+                        self.state = DecodeState::Ground;
+                        match (byte, param) {
+                            (b'A', _) => Some(KeyCode::ArrowUp),
+                            (b'B', _) => Some(KeyCode::ArrowDown),
+                            (b'C', _) => Some(KeyCode::ArrowRight),
+                            (b'D', _) => Some(KeyCode::ArrowLeft),
+                            (b'~', 5) => Some(KeyCode::PageUp),
+                            (b'~', 6) => Some(KeyCode::PageDown),
+                            _ => None,
+                        }
+                    }
+                    // Separators and intermediate bytes
+                    _ => None,
+                };
+            }
+            _ => {}
+        }
+
+        let after_cr = matches!(self.state, DecodeState::AfterCr);
+        self.state = DecodeState::Ground;
+        match byte {
+            b'\r' => {
+                self.state = DecodeState::AfterCr;
+                Some(KeyCode::Enter)
+            }
+            b'\n' if after_cr => None,
+            b'\n' => Some(KeyCode::Enter),
+            0x08 | 0x7F => Some(KeyCode::Backspace),
+            0x1B => {
+                self.state = DecodeState::Escape;
+                None
+            }
+            0x01..=0x1A => Some(KeyCode::Ctrl((b'a' + byte - 1) as char)),
+            0x20..=0x7E => Some(KeyCode::Char(byte as char)),
+            _ => None,
+        }
+    }
+}
+
+/// Decoder for COM1; only `receive_interrupt` uses it.
+static DECODER: Mutex<SerialDecoder> = Mutex::new(SerialDecoder::new());
+/// Set once anything has been typed on COM1; console output is then echoed
+/// there too (see `console_print`).
+static CONSOLE_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// IRQ 4: hand every byte COM1 has received to the keyboard queue as keys,
+/// so the shell and console readers take input from either device. Uses
+/// its own port handle, as the interrupted code may hold `SERIAL1`.
+pub fn receive_interrupt() {
+    let mut port = unsafe { SerialPort::new(COM1) };
+    let mut decoder = DECODER.lock();
+    while let Some(byte) = port.recv() {
+        CONSOLE_ACTIVE.store(true, Ordering::Relaxed);
+        if let Some(key) = decoder.feed(byte) {
+            crate::drivers::keyboard::push_key(key);
+        }
+    }
+}
+
+/// Whether someone is typing on COM1, e.g. QEMU run with `-nographic`.
+pub fn console_active() -> bool {
+    CONSOLE_ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Run `f` on COM1. Interrupt handlers (console readers echo from the
+/// keyboard IRQ) get a fresh handle rather than waiting on `SERIAL1`, which
+/// the interrupted code may hold.
+fn with_console_port(f: impl FnOnce(&mut SerialPort)) {
+    if x86_64::instructions::interrupts::are_enabled() {
+        f(&mut SERIAL1.lock());
+    } else {
+        f(&mut unsafe { SerialPort::new(COM1) });
+    }
+}
+
+/// Echo console output to COM1 once it is in use as a console, with LF
+/// sent as CR LF for the terminal on the other end.
+pub fn console_print(args: ::core::fmt::Arguments) {
+    use core::fmt::Write;
+    struct Crlf<'a>(&'a mut SerialPort);
+    impl Write for Crlf<'_> {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            for byte in s.bytes() {
+                if byte == b'\n' {
+                    self.0.send(b'\r');
+                }
+                self.0.send(byte);
+            }
+            Ok(())
+        }
+    }
+    if console_active() {
+        with_console_port(|port| { let _ = Crlf(port).write_fmt(args); });
+    }
+}
+
+/// Erase the character before the cursor on a serial console.
+pub fn console_backspace() {
+    use core::fmt::Write;
+    if console_active() {
+        with_console_port(|port| { let _ = port.write_str("\x08 \x08"); });
+    }
+}
+
+/// Write to COM1 without taking `SERIAL1`'s lock — for the panic path and
+/// fatal faults only, where the lock may be held by the code that failed. The port was already
+/// configured by `init`, so a fresh handle can transmit directly.
+pub fn emergency_print(args: ::core::fmt::Arguments) {
+    use core::fmt::Write;
+    let mut port = unsafe { SerialPort::new(COM1) };
+    let _ = port.write_fmt(args);
 }
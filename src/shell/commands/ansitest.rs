@@ -0,0 +1,123 @@
+use crate::vga::{self, AnsiAction, AnsiParser};
+use crate::shell::commands::testutil::test_log;
+
+/// Columns on the text screen.
+const SCREEN_COLUMNS: usize = 80;
+
+/// ansitest — ANSI escape sequences in the VGA writer: the CSI parser on its
+/// own, then colors, cursor movement, erasing and backspace (across a wrapped
+/// line, stopping at the prompt limit) on a cleared screen. The
+/// screen is cleared again before the results are printed.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+    let mut results: alloc::vec::Vec<(&str, bool)> = alloc::vec::Vec::new();
+
+    macro_rules! check {
+        ($desc:expr, $cond:expr) => {
+            results.push(($desc, $cond))
+        }
+    }
+
+    // Parser
+    let csi = last_action(&mut AnsiParser::new(), b"\x1b[1;31m");
+    check!("SGR parsed with two parameters",
+        matches!(csi, AnsiAction::Csi(c) if c.final_byte == b'm' && c.params() == [1, 31]));
+    let mut split = AnsiParser::new();
+    let _ = last_action(&mut split, b"\x1b[1");
+    check!("sequence continues across feeds",
+        matches!(last_action(&mut split, b"2;4H"), AnsiAction::Csi(c) if c.params() == [12, 4]));
+    check!("empty parameter takes the default",
+        matches!(last_action(&mut AnsiParser::new(), b"\x1b[;5H"), AnsiAction::Csi(c) if c.param(0, 1) == 1 && c.param(1, 1) == 5));
+    check!("private marker recorded",
+        matches!(last_action(&mut AnsiParser::new(), b"\x1b[?25l"), AnsiAction::Csi(c) if c.private));
+    check!("extra parameters dropped",
+        matches!(last_action(&mut AnsiParser::new(), b"\x1b[1;2;3;4;5;6;7;8;9;10m"), AnsiAction::Csi(c) if c.params().len() == 8));
+    let mut other = AnsiParser::new();
+    check!("non-CSI escape swallowed",
+        other.feed(0x1b) == AnsiAction::Pending && other.feed(b'c') == AnsiAction::Pending
+            && other.feed(b'x') == AnsiAction::Print(b'x'));
+
+    // Screen
+    vga::clear_screen();
+    vga::write_str("\x1b[5;10H");
+    check!("ESC[5;10H moves to row 4, column 9", vga::cursor() == (4, 9));
+    vga::write_str("\x1b[2A\x1b[3C");
+    check!("ESC[2A / ESC[3C move up and right", vga::cursor() == (2, 12));
+    vga::write_str("\x1b[D\x1b[B");
+    check!("ESC[D / ESC[B default to one cell", vga::cursor() == (3, 11));
+    vga::write_str("\x1b[31mR\x1b[0m");
+    check!("ESC[31m draws red on black", vga::read_row(3)[11] == b'R' && vga::attribute_at(3, 11) == 0x04);
+    vga::write_str("\x1b[1;32mG");
+    check!("bold green is light green", vga::attribute_at(3, 12) == 0x0A);
+    vga::write_str("\x1b[0;37;44mB\x1b[m");
+    check!("ESC[37;44m sets both colors", vga::attribute_at(3, 13) == 0x17);
+    vga::write_str("W");
+    check!("ESC[m resets to white on black", vga::attribute_at(3, 14) == 0x0F);
+
+    vga::write_str("\x1b[");
+    vga::write_str("1;1H");
+    check!("split sequence applied without garbage", vga::cursor() == (0, 0) && vga::read_row(0)[0] == b' ');
+    vga::write_str("\x1b[?25lX");
+    check!("unsupported sequence swallowed", vga::read_row(0)[..2] == *b"X ");
+
+    vga::write_str("\x1b[7;1Habcdef\x1b[7;3H\x1b[K");
+    check!("ESC[K erases to the end of the line", vga::read_row(6)[..6] == *b"ab    ");
+    vga::write_str("\x1b[1K");
+    check!("ESC[1K erases to the cursor", vga::read_row(6)[..3] == *b"   " && vga::cursor() == (6, 2));
+    vga::write_str("\x1b[2J");
+    check!("ESC[2J blanks the screen, cursor kept",
+        vga::read_row(3)[11..15] == *b"    " && vga::cursor() == (6, 2));
+
+    // A prompt ending in the last column: "ab" wraps onto the next row
+    vga::write_str("\x1b[10;79H>");
+    vga::set_backspace_limit(true);
+    vga::write_str("ab");
+    vga::backspace();
+    vga::backspace();
+    check!("backspace at column 0 erases the end of the row above",
+        vga::read_row(10)[0] == b' ' && vga::read_row(9)[79] == b' ' && vga::cursor() == (9, 79));
+    vga::backspace();
+    check!("backspace stops at the limit", vga::read_row(9)[78] == b'>' && vga::cursor() == (9, 79));
+    vga::set_backspace_limit(false);
+    vga::backspace();
+    check!("backspace goes on once the limit is lifted", vga::read_row(9)[78] == b' ');
+
+    // A prompt on the bottom row, scrolled up a line by a wrapping command
+    vga::write_str("\x1b[25;1H$ ");
+    vga::set_backspace_limit(true);
+    for _ in 0..SCREEN_COLUMNS {
+        vga::write_str("x");
+    }
+    for _ in 0..2 * SCREEN_COLUMNS {
+        vga::backspace();
+    }
+    check!("limit follows the prompt when the screen scrolls",
+        vga::cursor() == (23, 2) && vga::read_row(23)[..3] == *b"$  " && vga::read_row(24)[0] == b' ');
+    vga::set_backspace_limit(false);
+
+    vga::clear_screen();
+    test_log!("=== ANSI Escape Sequence Test Suite ===");
+    for (desc, ok) in results {
+        if ok {
+            test_log!("[PASS] {}", desc); pass += 1;
+        } else {
+            test_log!("[FAIL] {}", desc); fail += 1;
+        }
+    }
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// Feed `bytes` and return what the parser made of the last one.
+fn last_action(parser: &mut AnsiParser, bytes: &[u8]) -> AnsiAction {
+    let mut action = AnsiAction::Pending;
+    for &b in bytes {
+        action = parser.feed(b);
+    }
+    action
+}
@@ -1,28 +1,95 @@
 use crate::{print, println};
+use crate::fs::error::FsError;
+use crate::fs::inode::FileType;
 use alloc::vec;
+use alloc::vec::Vec;
 
-/// cat <file> — read file contents via VFS.
+/// Bytes read from the VFS per step.
+const CHUNK: usize = 512;
+
+/// cat <file>... — print files via the VFS, on any mount. Files are
+/// streamed in chunks, so their size is not limited by the heap. Without a
+/// file, copies a redirected stdin (`cat < file`, `ls | cat`).
 pub fn run(args: &str) {
-    let filename = args.trim();
-    if filename.is_empty() {
-        println!("cat: missing filename");
+    let files: Vec<&str> = args.split_whitespace().collect();
+    if files.is_empty() {
+        if crate::shell::redirect::stdin_redirected() {
+            cat_stdin();
+        } else {
+            println!("cat: missing filename");
+        }
         return;
     }
 
+    for filename in files {
+        if let Err(e) = cat_file(filename) {
+            println!("cat: {}: {}", filename, e);
+        }
+    }
+}
+
+fn cat_file(filename: &str) -> Result<(), FsError> {
     let path = crate::shell::state::resolve_path(filename);
-    let vfs = crate::fs::VFS.lock();
-
-    // Read up to 4 KiB
-    let mut buf = vec![0u8; 4096];
-    match vfs.read_file(&path, 0, &mut buf) {
-        Ok(n) => {
-            if let Ok(text) = core::str::from_utf8(&buf[..n]) {
-                print!("{}", text);
-                if !text.ends_with('\n') { println!(); }
-            } else {
-                println!("cat: {}: Binary file ({} bytes)", filename, n);
-            }
-        },
-        Err(e) => println!("cat: {}: {}", filename, e),
+    let inode = crate::fs::VFS.lock().lookup(&path)?;
+    if inode.file_type == FileType::Directory {
+        return Err(FsError::IsADirectory);
+    }
+
+    let mut buf = [0u8; CHUNK];
+    // Bytes of a UTF-8 sequence split by the chunk boundary, kept for the next chunk
+    let mut pending: Vec<u8> = Vec::new();
+    let mut offset = 0;
+    let mut last = b'\n';
+
+    loop {
+        let n = crate::fs::VFS.lock().read_file(&path, offset, &mut buf)?;
+        if n == 0 {
+            break;
+        }
+        // Text is all we print; judge by the start of the file
+        if offset == 0 && looks_binary(&buf[..n]) {
+            println!("cat: {}: Binary file ({} bytes)", filename, inode.size);
+            return Ok(());
+        }
+        offset += n;
+        last = buf[n - 1];
+
+        pending.extend_from_slice(&buf[..n]);
+        let valid = match core::str::from_utf8(&pending) {
+            Ok(_) => pending.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => pending.len(),
+        };
+        print!("{}", alloc::string::String::from_utf8_lossy(&pending[..valid]));
+        pending.drain(..valid);
+    }
+
+    if !pending.is_empty() {
+        print!("{}", alloc::string::String::from_utf8_lossy(&pending));
+    }
+    if last != b'\n' {
+        println!();
+    }
+    Ok(())
+}
+
+/// NUL bytes or invalid UTF-8 (other than a sequence cut off at the end).
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes.contains(&0) || core::str::from_utf8(bytes).is_err_and(|e| e.error_len().is_some())
+}
+
+/// Print stdin until end of file.
+fn cat_stdin() {
+    let mut buf = vec![0u8; 512];
+    loop {
+        let n = crate::syscalls::sys_read_fd(0, &mut buf);
+        if crate::syscalls::errno::is_err(n) {
+            println!("cat: stdin: read failed (errno {})", n.wrapping_neg());
+            break;
+        }
+        if n == 0 {
+            break;
+        }
+        print!("{}", alloc::string::String::from_utf8_lossy(&buf[..n as usize]));
     }
 }
@@ -0,0 +1,76 @@
+use crate::println;
+use crate::fs::inode::MODE_MASK;
+
+/// chmod <mode> <file>... — change permission bits via the VFS. The mode is
+/// octal (`644`) or symbolic clauses such as `u+x`, `go-w`, `a=r` or `-w`,
+/// separated by commas. On FAT32 only the write bit is kept.
+pub fn run(args: &str) {
+    let parts: alloc::vec::Vec<&str> = args.split_whitespace().collect();
+    if parts.len() < 2 {
+        println!("Usage: chmod <mode> <file>...");
+        return;
+    }
+    let spec = parts[0];
+
+    for name in &parts[1..] {
+        let path = crate::shell::state::resolve_path(name);
+        let mut vfs = crate::fs::VFS.lock();
+        let current = match vfs.mode(&path) {
+            Ok(mode) => mode,
+            Err(e) => {
+                println!("chmod: {}: {}", name, e);
+                continue;
+            }
+        };
+        let Some(mode) = parse_mode(spec, current) else {
+            println!("chmod: invalid mode '{}'", spec);
+            return;
+        };
+        if let Err(e) = vfs.chmod(&path, mode) {
+            println!("chmod: {}: {}", name, e);
+        }
+    }
+}
+
+/// The new mode `spec` gives a file whose mode is `current`.
+fn parse_mode(spec: &str, current: u16) -> Option<u16> {
+    if spec.bytes().all(|b| b.is_ascii_digit()) {
+        return u16::from_str_radix(spec, 8).ok().filter(|&m| m <= MODE_MASK);
+    }
+
+    let mut mode = current;
+    for clause in spec.split(',') {
+        let op_at = clause.find(['+', '-', '='])?;
+        let (who, rest) = clause.split_at(op_at);
+        let mut who_mask = 0;
+        for c in who.chars() {
+            who_mask |= match c {
+                'u' => 0o700,
+                'g' => 0o070,
+                'o' => 0o007,
+                'a' => 0o777,
+                _ => return None,
+            };
+        }
+        if who_mask == 0 {
+            who_mask = 0o777;
+        }
+
+        let mut perms = 0;
+        for c in rest[1..].chars() {
+            perms |= match c {
+                'r' => 0o444,
+                'w' => 0o222,
+                'x' => 0o111,
+                _ => return None,
+            };
+        }
+        let bits = perms & who_mask;
+        match rest.as_bytes()[0] {
+            b'+' => mode |= bits,
+            b'-' => mode &= !bits,
+            _ => mode = (mode & !who_mask) | bits,
+        }
+    }
+    Some(mode)
+}
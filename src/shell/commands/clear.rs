@@ -1,3 +1,3 @@
 pub fn run(_args: &str) {
-    crate::vga::WRITER.lock().clear_screen();
+    crate::vga::clear_screen();
 }
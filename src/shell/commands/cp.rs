@@ -1,7 +1,12 @@
 use crate::println;
-use alloc::vec;
+use crate::fs::error::FsError;
+use crate::fs::inode::FileType;
 
-/// cp <src> <dst> — copy a file via VFS.
+/// Bytes copied per step.
+const CHUNK: usize = 512;
+
+/// cp <src> <dst> — copy a file via the VFS, across mounts if need be.
+/// The file is streamed in chunks, so its size is not limited by the heap.
 pub fn run(args: &str) {
     let parts: alloc::vec::Vec<&str> = args.trim().split_whitespace().collect();
     if parts.len() < 2 {
@@ -10,22 +15,46 @@ pub fn run(args: &str) {
     }
 
     let src = crate::shell::state::resolve_path(parts[0]);
-    let dst = crate::shell::state::resolve_path(parts[1]);
-
-    let vfs = crate::fs::VFS.lock();
-    let mut buf = vec![0u8; 4096];
-    let n = match vfs.read_file(&src, 0, &mut buf) {
-        Ok(n) => n,
-        Err(e) => { println!("cp: {}: {}", parts[0], e); return; }
-    };
-    drop(vfs);
-
-    let mut vfs = crate::fs::VFS.lock();
-    if !vfs.exists(&dst) {
-        let _ = vfs.create(&dst);
+    let dst = crate::shell::state::resolve_dest(&src, parts[1]);
+    if src == dst {
+        println!("cp: {} and {} are the same file", parts[0], parts[1]);
+        return;
+    }
+
+    match copy(&src, &dst) {
+        Ok(n) => println!("Copied {} -> {} ({} bytes)", parts[0], parts[1], n),
+        Err((name, e)) => println!("cp: {}: {}", if name == Side::Source { parts[0] } else { parts[1] }, e),
+    }
+}
+
+#[derive(PartialEq)]
+enum Side {
+    Source,
+    Dest,
+}
+
+/// Copy `src` over `dst`, creating or truncating it. Returns the bytes
+/// copied, or the error with the side it happened on.
+fn copy(src: &str, dst: &str) -> Result<usize, (Side, FsError)> {
+    let inode = crate::fs::VFS.lock().lookup(src).map_err(|e| (Side::Source, e))?;
+    if inode.file_type == FileType::Directory {
+        return Err((Side::Source, FsError::IsADirectory));
     }
-    match vfs.write_file(&dst, &buf[..n]) {
-        Ok(_) => println!("Copied {} -> {}", parts[0], parts[1]),
-        Err(e) => println!("cp: write error: {}", e),
+
+    {
+        let mut vfs = crate::fs::VFS.lock();
+        let prepared = if vfs.exists(dst) { vfs.truncate(dst, 0) } else { vfs.create(dst).map(|_| ()) };
+        prepared.map_err(|e| (Side::Dest, e))?;
+    }
+
+    let mut buf = [0u8; CHUNK];
+    let mut offset = 0;
+    loop {
+        let n = crate::fs::VFS.lock().read_file(src, offset, &mut buf).map_err(|e| (Side::Source, e))?;
+        if n == 0 {
+            return Ok(offset);
+        }
+        crate::fs::VFS.lock().write_at(dst, offset, &buf[..n]).map_err(|e| (Side::Dest, e))?;
+        offset += n;
     }
 }
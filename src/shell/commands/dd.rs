@@ -0,0 +1,114 @@
+use crate::println;
+use crate::drivers::ata::PRIMARY_ATA;
+use alloc::vec;
+
+/// Most sectors `dd` moves in one invocation (8 KiB).
+const MAX_DD_SECTORS: usize = 16;
+
+/// dd read <lba> [count] | dd write <lba> <file> [-f] — raw sector access on
+/// the primary ATA disk, bypassing the filesystem.
+pub fn run(args: &str) {
+    let parts: alloc::vec::Vec<&str> = args.split_whitespace().collect();
+    match parts.as_slice() {
+        ["read", lba] => read(lba, "1"),
+        ["read", lba, count] => read(lba, count),
+        ["write", lba, file] => write(lba, file, false),
+        ["write", lba, file, "-f"] => write(lba, file, true),
+        _ => {
+            println!("dd: usage: dd read <lba> [count]");
+            println!("          dd write <lba> <file> [-f]");
+        }
+    }
+}
+
+fn parse_lba(arg: &str) -> Option<u64> {
+    let lba = match arg.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+        None => arg.parse().ok()?,
+    };
+    // LBA48 addressing
+    if lba >= crate::drivers::ata::pio::LBA48_LIMIT { None } else { Some(lba) }
+}
+
+fn read(lba_arg: &str, count_arg: &str) {
+    let lba = match parse_lba(lba_arg) {
+        Some(l) => l,
+        None => { println!("dd: invalid lba: {}", lba_arg); return; }
+    };
+    let count: usize = match count_arg.parse() {
+        Ok(c) if c >= 1 && c <= MAX_DD_SECTORS => c,
+        _ => { println!("dd: count must be 1..{}", MAX_DD_SECTORS); return; }
+    };
+
+    let mut buf = vec![0u8; count * 512];
+    {
+        let ata = PRIMARY_ATA.lock();
+        if !ata.detected {
+            println!("dd: no disk detected");
+            return;
+        }
+        if let Err(e) = ata.read_sectors(lba, &mut buf) {
+            println!("dd: read error at lba {}: {}", lba, e);
+            return;
+        }
+    }
+
+    for (i, sector) in buf.chunks(512).enumerate() {
+        println!("-- LBA {} --", lba + i as u64);
+        for row in (0..512).step_by(16) {
+            let mut hex = alloc::string::String::new();
+            let mut ascii = alloc::string::String::new();
+            for &byte in &sector[row..row + 16] {
+                hex.push_str(&alloc::format!("{:02x} ", byte));
+                ascii.push(if (0x20..=0x7e).contains(&byte) { byte as char } else { '.' });
+            }
+            println!("  {:03x}  {}|{}|", row, hex, ascii);
+        }
+    }
+}
+
+fn write(lba_arg: &str, file: &str, force: bool) {
+    let lba = match parse_lba(lba_arg) {
+        Some(l) => l,
+        None => { println!("dd: invalid lba: {}", lba_arg); return; }
+    };
+
+    let path = crate::shell::state::resolve_path(file);
+    let mut data = vec![0u8; MAX_DD_SECTORS * 512 + 1];
+    let n = match crate::fs::VFS.lock().read_file(&path, 0, &mut data) {
+        Ok(n) => n,
+        Err(e) => { println!("dd: {}: {}", file, e); return; }
+    };
+    if n == 0 {
+        println!("dd: {}: file is empty", file);
+        return;
+    }
+    if n > MAX_DD_SECTORS * 512 {
+        println!("dd: {}: larger than {} sectors", file, MAX_DD_SECTORS);
+        return;
+    }
+
+    if lba == 0 {
+        println!("dd: WARNING: LBA 0 is the boot sector / FAT32 BPB.");
+        println!("dd: WARNING: overwriting it can make the disk unmountable.");
+        if !force {
+            println!("dd: refusing without -f");
+            return;
+        }
+    }
+
+    // Pad the tail of the last sector with zeros
+    let sectors = n.div_ceil(512);
+    data.truncate(sectors * 512);
+    for b in &mut data[n..] { *b = 0; }
+
+    let ata = PRIMARY_ATA.lock();
+    if !ata.detected {
+        println!("dd: no disk detected");
+        return;
+    }
+    match ata.write_sectors(lba, &data) {
+        Ok(()) => println!("dd: wrote {} bytes ({} sectors) at lba {}", n, sectors, lba),
+        Err(e) => println!("dd: write error at lba {}: {}", lba, e),
+    }
+}
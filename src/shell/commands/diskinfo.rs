@@ -0,0 +1,31 @@
+use crate::println;
+
+/// diskinfo — show the FAT32 volume name, OEM string and capacity.
+pub fn run(_args: &str) {
+    let fs = match crate::fs::fat32() {
+        Some(fs) => fs,
+        None => {
+            println!("diskinfo: no FAT32 volume mounted");
+            return;
+        }
+    };
+
+    let info = match fs.volume_info() {
+        Ok(i) => i,
+        Err(e) => {
+            println!("diskinfo: {}", e);
+            return;
+        }
+    };
+
+    let cluster_bytes = info.bytes_per_sector as u64 * info.sectors_per_cluster as u64;
+    let total_kib = info.total_clusters as u64 * cluster_bytes / 1024;
+    let free_kib = info.free_clusters as u64 * cluster_bytes / 1024;
+
+    println!("Volume:    /disk (fat32)");
+    println!("Label:     {}", info.label.as_deref().unwrap_or("(none)"));
+    println!("OEM name:  {}", if info.oem_name.is_empty() { "(none)" } else { &info.oem_name });
+    println!("Geometry:  {} B/sector, {} sectors/cluster, {} FATs", info.bytes_per_sector, info.sectors_per_cluster, info.num_fats);
+    println!("Sectors:   {}", info.total_sectors);
+    println!("Size:      {} KiB total, {} KiB used, {} KiB free", total_kib, total_kib - free_kib, free_kib);
+}
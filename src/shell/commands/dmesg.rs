@@ -0,0 +1,17 @@
+use crate::klog::Level;
+use crate::println;
+
+/// dmesg [-w|-e] — print the whole kernel log ring; `-w` keeps warnings and
+/// errors, `-e` errors only.
+pub fn run(args: &str) {
+    let min_level = match args.trim() {
+        "" => Level::Info,
+        "-w" => Level::Warn,
+        "-e" => Level::Error,
+        _ => {
+            println!("Usage: dmesg [-w|-e]");
+            return;
+        }
+    };
+    super::log::print(usize::MAX, min_level);
+}
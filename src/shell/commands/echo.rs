@@ -1,5 +1,36 @@
+use alloc::string::String;
 use crate::println;
 
+/// echo [-e] <text> — print text. With -e, backslash escapes are expanded:
+/// \n, \\ and \e (also \033 or \x1b) for ANSI sequences such as
+/// `echo -e \e[31mred\e[0m`.
 pub fn run(args: &str) {
-    println!("{}", args);
+    match args.strip_prefix("-e") {
+        Some(rest) if rest.is_empty() || rest.starts_with(' ') => println!("{}", unescape(rest.trim_start())),
+        _ => println!("{}", args),
+    }
+}
+
+/// Expand the escapes `echo -e` understands; unknown ones are kept as typed.
+fn unescape(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(pos) = rest.find('\\') {
+        out.push_str(&rest[..pos]);
+        let escape = &rest[pos + 1..];
+        let (expanded, len) = if escape.starts_with("033") || escape.starts_with("x1b") {
+            ("\x1b", 3)
+        } else {
+            match escape.chars().next() {
+                Some('e') => ("\x1b", 1),
+                Some('n') => ("\n", 1),
+                Some('\\') => ("\\", 1),
+                _ => ("\\", 0),
+            }
+        };
+        out.push_str(expanded);
+        rest = &escape[len..];
+    }
+    out.push_str(rest);
+    out
 }
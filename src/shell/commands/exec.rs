@@ -1,27 +1,18 @@
 use crate::println;
 
-/// exec — load and execute an ELF64 binary from disk.
+/// exec — run an ELF64 binary from disk in the foreground and report its exit code.
 pub fn run(args: &str) {
-    let path = args.trim();
+    let mut parts = args.trim().splitn(2, ' ');
+    let path = parts.next().unwrap_or("");
+    let rest = parts.next().unwrap_or("");
     if path.is_empty() {
-        println!("Usage: exec <path>");
+        println!("Usage: exec <path> [args...]");
         return;
     }
 
-    println!("[EXEC] Loading {}...", path);
-    crate::log_info!("[EXEC] Loading {}...", path);
+    crate::log_info!("[EXEC] Running {}...", path);
 
-    match crate::loader::elf::load(path) {
-        Ok(task_id) => {
-            println!("[EXEC] Loaded '{}' as task {}", path, task_id);
-            crate::log_info!("[EXEC] Loaded '{}' as task {}", path, task_id);
-            
-            // Automatically switch to the newly loaded program
-            crate::scheduler::yield_now();
-        }
-        Err(e) => {
-            println!("[EXEC] Failed: {}", e);
-            crate::log_info!("[EXEC] Failed: {}", e);
-        }
-    }
+    let code = crate::shell::run_external(path, rest);
+    println!("[EXEC] '{}' exited with status {}", path, code);
+    crate::log_info!("[EXEC] '{}' exited with status {}", path, code);
 }
@@ -0,0 +1,127 @@
+use crate::shell::commands::testutil::test_log;
+
+/// Tiny user program: echoes argv[1] and exits with argc (tests/test_elf/test_argv.S).
+static TEST_ARGV_ELF: &[u8] = include_bytes!("../../../tests/test_elf/test_argv.elf");
+
+/// Tiny user program that executes int3 (tests/test_elf/test_trap.S).
+static TEST_TRAP_ELF: &[u8] = include_bytes!("../../../tests/test_elf/test_trap.elf");
+
+/// Tiny user program that writes to address 0 (tests/test_elf/test_segv.S).
+static TEST_SEGV_ELF: &[u8] = include_bytes!("../../../tests/test_elf/test_segv.elf");
+
+/// Tiny user program that touches chosen pages of its image (tests/test_elf/test_lazy.S).
+static TEST_LAZY_ELF: &[u8] = include_bytes!("../../../tests/test_elf/test_lazy.elf");
+
+const TEST_PATH: &str = "/tmp/exectest.elf";
+const TRAP_PATH: &str = "/tmp/exectrap.elf";
+const SEGV_PATH: &str = "/tmp/execsegv.elf";
+const LAZY_PATH: &str = "/tmp/execlazy.elf";
+
+/// exectest — end-to-end test of `run_external`: argv passing, console
+/// output from the child, exit status propagation, fatal user faults and
+/// loading program pages on demand.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== Exec Integration Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    // Setup: drop the embedded ELFs into the tmpfs
+    for (path, elf) in [(TEST_PATH, TEST_ARGV_ELF), (TRAP_PATH, TEST_TRAP_ELF), (SEGV_PATH, TEST_SEGV_ELF), (LAZY_PATH, TEST_LAZY_ELF)] {
+        let mut vfs = crate::fs::VFS.lock();
+        let _ = vfs.unlink(path);
+        let written = vfs.create(path).and_then(|_| vfs.write_file(path, elf));
+        match written {
+            Ok(n) if n == elf.len() => {},
+            Ok(n) => { test_log!("[FAIL] setup: short write ({} bytes)", n); return; },
+            Err(e) => { test_log!("[FAIL] setup: {}", e); return; },
+        }
+    }
+
+    // Test 1: no arguments -> argc == 1
+    match crate::shell::run_external(TEST_PATH, "") {
+        1 => { test_log!("[PASS] exit status == argc (1)"); pass += 1; },
+        code => { test_log!("[FAIL] expected exit 1, got {}", code); fail += 1; },
+    }
+
+    // Test 2: arguments reach the child; it echoes argv[1] above this line
+    match crate::shell::run_external(TEST_PATH, "hello-argv two three") {
+        4 => { test_log!("[PASS] argv passed (argc 4, echoed argv[1])"); pass += 1; },
+        code => { test_log!("[FAIL] expected exit 4, got {}", code); fail += 1; },
+    }
+
+    // Test 3: exec of a missing binary fails in the forked child, which
+    // exits with the exec failure code
+    match crate::shell::run_external("/tmp/exectest_missing.elf", "") {
+        crate::shell::EXEC_FAILED => { test_log!("[PASS] missing binary -> {}", crate::shell::EXEC_FAILED); pass += 1; },
+        code => { test_log!("[FAIL] expected exit {}, got {}", crate::shell::EXEC_FAILED, code); fail += 1; },
+    }
+
+    // Test 4: a user-mode int3 kills only the child, with SIGTRAP
+    match crate::shell::run_external(TRAP_PATH, "") {
+        crate::interrupts::idt::SIGTRAP_EXIT_STATUS => {
+            test_log!("[PASS] user breakpoint -> SIGTRAP ({})", crate::interrupts::idt::SIGTRAP_EXIT_STATUS); pass += 1;
+        },
+        code => { test_log!("[FAIL] expected exit {}, got {}", crate::interrupts::idt::SIGTRAP_EXIT_STATUS, code); fail += 1; },
+    }
+
+    // Test 5: a user-mode null pointer write kills only the child, with SIGSEGV
+    match crate::shell::run_external(SEGV_PATH, "") {
+        crate::interrupts::idt::SIGSEGV_EXIT_STATUS => {
+            test_log!("[PASS] user page fault -> SIGSEGV ({}), shell survived", crate::interrupts::idt::SIGSEGV_EXIT_STATUS); pass += 1;
+        },
+        code => { test_log!("[FAIL] expected exit {}, got {}", crate::interrupts::idt::SIGSEGV_EXIT_STATUS, code); fail += 1; },
+    }
+
+    // Test 6: only the pages a program touches are loaded, with the file's bytes
+    let before = crate::memory::demand::pages_loaded();
+    let code = crate::shell::run_external(LAZY_PATH, "");
+    let loaded = crate::memory::demand::pages_loaded() - before;
+    if code == 7 && loaded == 1 {
+        test_log!("[PASS] untouched image pages not loaded (1 of 8)"); pass += 1;
+    } else {
+        test_log!("[FAIL] expected exit 7 with 1 page loaded, got exit {} with {}", code, loaded); fail += 1;
+    }
+
+    // Test 7: .data pages hold the file contents, .bss pages are zeroed and writable
+    let before = crate::memory::demand::pages_loaded();
+    let code = crate::shell::run_external(LAZY_PATH, "touch");
+    let loaded = crate::memory::demand::pages_loaded() - before;
+    if code == 43 && loaded == 3 {
+        test_log!("[PASS] .data and .bss pages loaded on first touch (3 of 8)"); pass += 1;
+    } else {
+        test_log!("[FAIL] expected exit 43 with 3 pages loaded, got exit {} with {}", code, loaded); fail += 1;
+    }
+
+    // Test 8: syscalls writing into pages not loaded yet load them, rather
+    // than deadlocking (pipe) or faulting in the kernel (read)
+    match crate::shell::run_external(LAZY_PATH, "pipe read") {
+        42 => { test_log!("[PASS] pipe() and read() into unloaded .bss pages"); pass += 1; },
+        code => { test_log!("[FAIL] expected exit 42, got {}", code); fail += 1; },
+    }
+
+    // Test 9: the shell reaped its children (no lingering zombies)
+    {
+        let sched = crate::scheduler::SCHEDULER.lock();
+        let zombies = sched.ready_queue.iter()
+            .filter(|p| p.state == crate::scheduler::ProcessState::Zombie
+                && (p.name == "exectest.elf" || p.name == "exectrap.elf" || p.name == "execsegv.elf" || p.name == "execlazy.elf"))
+            .count();
+        if zombies == 0 {
+            test_log!("[PASS] children reaped"); pass += 1;
+        } else {
+            test_log!("[FAIL] {} zombie(s) left behind", zombies); fail += 1;
+        }
+    }
+
+    let _ = crate::fs::VFS.lock().unlink(TEST_PATH);
+    let _ = crate::fs::VFS.lock().unlink(TRAP_PATH);
+    let _ = crate::fs::VFS.lock().unlink(SEGV_PATH);
+    let _ = crate::fs::VFS.lock().unlink(LAZY_PATH);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
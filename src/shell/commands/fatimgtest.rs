@@ -0,0 +1,93 @@
+use alloc::format;
+use alloc::vec::Vec;
+use lazy_static::lazy_static;
+use crate::drivers::block::MemBlockDevice;
+use crate::fs::fat32::{format, Fat32Fs};
+use crate::fs::mount::FileSystem;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// Size of the test image: 512 KiB, one sector per cluster, so a few KiB
+/// of data already spans many clusters.
+const IMAGE_SECTORS: u64 = 1024;
+/// Files created in one directory: more than its first cluster holds.
+const DIR_FILES: usize = 40;
+
+lazy_static! {
+    /// Allocated on first use and reformatted by every run.
+    static ref IMAGE: MemBlockDevice = MemBlockDevice::new(512, IMAGE_SECTORS);
+}
+
+/// fatimgtest — the FAT32 driver on a freshly formatted in-memory image, no
+/// disk needed: multi-cluster writes and appends (cluster allocation), a
+/// directory outgrowing its first cluster, the read-only attribute, freeing
+/// on unlink, a clean fsck, and the same files seen by a second mount after
+/// sync.
+pub fn run(_args: &str) {
+    test_log!("=== FAT32 Image Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    let fs = match format::format(&*IMAGE, 1, "TESTIMG").and_then(|_| Fat32Fs::init(&*IMAGE)) {
+        Ok(fs) => fs,
+        Err(e) => {
+            test_log!("[FAIL] format and mount the image: {}", e);
+            return;
+        }
+    };
+    let free = || fs.volume_info().map_or(0, |info| info.free_clusters);
+    let info = fs.volume_info();
+    check!(pass, fail, "formatted volume mounts with its label",
+        info.as_ref().is_ok_and(|i| i.label.as_deref() == Some("TESTIMG")));
+    let total = info.map_or(0, |i| i.total_clusters);
+    check!(pass, fail, "only the root directory's cluster in use", total > 0 && free() == total - 1);
+    check!(pass, fail, "fresh volume passes fsck", fs.fsck().is_ok_and(|r| r.is_clean()));
+
+    // Cluster allocation: 5000 bytes take 10 clusters, an append 2 more
+    let data: Vec<u8> = (0..6000).map(|i| (i % 251) as u8).collect();
+    let before = free();
+    let written = fs.create("/big.bin").and_then(|_| fs.write("/big.bin", 0, &data[..5000]));
+    check!(pass, fail, "multi-cluster write", written == Ok(5000) && free() == before - 10);
+    let appended = fs.write("/big.bin", 5000, &data[5000..]);
+    check!(pass, fail, "append extends the chain", appended == Ok(1000) && free() == before - 12);
+    let mut back = alloc::vec![0u8; 6000];
+    check!(pass, fail, "multi-cluster file reads back intact", fs.read("/big.bin", 0, &mut back) == Ok(6000) && back == data);
+
+    // Directory growth: '.', '..' and 14 files fill the first cluster
+    let made = fs.mkdir("/dir").is_ok() && (0..DIR_FILES).all(|i| {
+        let path = format!("/dir/f{:02}.txt", i);
+        fs.create(&path).and_then(|_| fs.write(&path, 0, path.as_bytes())) == Ok(path.len())
+    });
+    check!(pass, fail, "directory grows past its first cluster", made);
+    let listed = fs.readdir("/dir").map_or(0, |e| e.iter().filter(|e| !e.name.starts_with('.')).count());
+    check!(pass, fail, "every entry listed", listed == DIR_FILES);
+    let mut buf = [0u8; 16];
+    check!(pass, fail, "files in every cluster of the directory readable", (0..DIR_FILES).all(|i| {
+        let path = format!("/dir/f{:02}.txt", i);
+        fs.read(&path, 0, &mut buf).is_ok_and(|n| &buf[..n] == path.as_bytes())
+    }));
+
+    // Permissions: the write bit is kept as the read-only attribute
+    let ro = fs.set_mode("/dir/f00.txt", 0o444).is_ok() && fs.get_mode("/dir/f00.txt") == Ok(0o444);
+    check!(pass, fail, "chmod 444 sets the read-only attribute", ro);
+    let rw = fs.set_mode("/dir/f00.txt", 0o600).is_ok() && fs.get_mode("/dir/f00.txt") == Ok(0o644);
+    check!(pass, fail, "chmod 600 clears it; other bits read back as 644", rw);
+
+    let before = free();
+    check!(pass, fail, "unlink frees the whole chain", fs.unlink("/big.bin").is_ok() && free() == before + 12);
+    check!(pass, fail, "volume consistent afterwards", fs.fsck().is_ok_and(|r| r.is_clean()));
+
+    // Through the image itself, not the first mount's cache
+    let synced = fs.sync();
+    drop(fs);
+    let again = Fat32Fs::init(&*IMAGE);
+    let last = format!("/dir/f{:02}.txt", DIR_FILES - 1);
+    check!(pass, fail, "second mount after sync sees the files", synced.is_ok() && again.is_ok_and(|fs| {
+        fs.read(&last, 0, &mut buf) == Ok(last.len()) && fs.lookup("/big.bin").is_err()
+    }));
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
@@ -0,0 +1,469 @@
+use alloc::collections::BTreeMap;
+use lazy_static::lazy_static;
+use spin::Mutex;
+use crate::drivers::block::{BlockDevice, BlockError, BlockResult};
+use crate::fs::error::{FsError, FsResult};
+use crate::drivers::rtc::{self, DateTime};
+use crate::fs::fat32::fat32::{
+    check_boot_sector, dos_datetime, dos_timestamp, encode_83_name, fat_chain, lfn_checksum, lfn_entries,
+    sector_reads, sector_writes, short_name_for, LongNameBuilder,
+};
+use crate::fs::fat32::{format, Fat32Fs};
+use crate::fs::mount::FileSystem;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// Sparse image for the large-file read: 48 MiB, 4 KiB clusters.
+const SPARSE_SECTORS: u64 = 96 * 1024;
+const SPARSE_CLUSTER_SECTORS: u8 = 8;
+/// More than the 16 MiB heap, so it can only be read from an offset.
+const BIG_FILE_SIZE: usize = 20 * 1024 * 1024;
+/// The big file's chain starts right after the root directory's cluster.
+const BIG_FILE_CLUSTER: u32 = 3;
+
+lazy_static! {
+    /// Reformatted by every run; only the sectors written are kept.
+    static ref SPARSE_IMAGE: SparseDisk = SparseDisk::new(SPARSE_SECTORS);
+}
+
+/// fattest — FAT32 8.3 short-name encoding, VFAT long-name parsing, BPB
+/// validation, cluster chain validation and DOS timestamp test suite, plus write-amplification,
+/// offset-read, timestamp, rename and long name checks on /disk when a volume
+/// is mounted, and a 20 MiB file read at high offsets on a sparse image.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== FAT32 Short Name / BPB Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    // Exact 8.3 names are kept (uppercased, space-padded)
+    check!(pass, fail, "exact 8.3 name", encode_83_name("abcdefgh.txt") == Some(*b"ABCDEFGHTXT"));
+    check!(pass, fail, "name without extension", encode_83_name("readme") == Some(*b"README     "));
+    check!(pass, fail, "dot entries", encode_83_name("..") == Some(*b"..         "));
+
+    // Not valid short names
+    check!(pass, fail, "9-char base rejected", encode_83_name("abcdefghi.txt").is_none());
+    check!(pass, fail, "multiple dots rejected", encode_83_name("archive.tar.gz").is_none());
+    check!(pass, fail, "leading dot rejected", encode_83_name(".profile").is_none());
+    check!(pass, fail, "illegal '+' rejected", encode_83_name("a+b.txt").is_none());
+    check!(pass, fail, "illegal '[' rejected", encode_83_name("x[1]").is_none());
+    check!(pass, fail, "control char rejected", encode_83_name("bad\x07.txt").is_none());
+    check!(pass, fail, "reserved CON rejected", encode_83_name("con").is_none());
+    check!(pass, fail, "reserved LPT1.TXT rejected", encode_83_name("lpt1.txt").is_none());
+
+    // Short names chosen for new entries
+    check!(pass, fail, "short name used as-is", short_name_for("notes.txt", &[]) == Ok(*b"NOTES   TXT"));
+    check!(pass, fail, "duplicate short name -> AlreadyExists",
+        short_name_for("notes.txt", &[*b"NOTES   TXT"]) == Err(FsError::AlreadyExists));
+    check!(pass, fail, "illegal char -> InvalidPath", short_name_for("a;b.txt", &[]) == Err(FsError::InvalidPath));
+    check!(pass, fail, "reserved name -> InvalidPath", short_name_for("nul.log", &[]) == Err(FsError::InvalidPath));
+    check!(pass, fail, "long name mangled to ~1", short_name_for("longfilename.text", &[]) == Ok(*b"LONGFI~1TEX"));
+    check!(pass, fail, "colliding alias gets ~2",
+        short_name_for("longfileother.text", &[*b"LONGFI~1TEX"]) == Ok(*b"LONGFI~2TEX"));
+    check!(pass, fail, "multiple dots mangled", short_name_for("archive.tar.gz", &[]) == Ok(*b"ARCHIV~1GZ "));
+
+    let mut taken = alloc::vec::Vec::new();
+    for n in 1..=9u8 {
+        let mut alias = *b"LONGFI~0TEX";
+        alias[7] = b'0' + n;
+        taken.push(alias);
+    }
+    check!(pass, fail, "~10 shortens the base", short_name_for("longfilename.text", &taken) == Ok(*b"LONGF~10TEX"));
+
+    // VFAT long names: LFN entries are assembled in front of their short entry
+    const ALIAS: [u8; 11] = *b"LONGFI~1TXT";
+    check!(pass, fail, "LFN checksum of LONGFI~1.TXT", lfn_checksum(&ALIAS) == 0xD4);
+    let mut lfn = LongNameBuilder::new();
+    let long = lfn_name(&mut lfn, &lfn_entries("longfilename.txt", &ALIAS).unwrap_or_default(), &ALIAS);
+    check!(pass, fail, "two-entry long name assembled", long.as_deref() == Some("longfilename.txt"));
+    check!(pass, fail, "builder starts over after a short entry", lfn.finish(&ALIAS).is_none());
+    let exact = lfn_entries("thirteen.char", &ALIAS).unwrap_or_default();
+    check!(pass, fail, "13-char name without terminator", lfn_name(&mut lfn, &exact, &ALIAS).as_deref() == Some("thirteen.char"));
+    check!(pass, fail, "checksum mismatch -> no long name",
+        lfn_name(&mut lfn, &lfn_entries("longfilename.txt", &ALIAS).unwrap_or_default(), b"OTHER   TXT").is_none());
+    let mut swapped = lfn_entries("longfilename.txt", &ALIAS).unwrap_or_default();
+    swapped.swap(0, 1);
+    check!(pass, fail, "out-of-order entries -> no long name", lfn_name(&mut lfn, &swapped, &ALIAS).is_none());
+    let mut no_last = lfn_entries("longfilename.txt", &ALIAS).unwrap_or_default();
+    no_last[0][0] &= !0x40;
+    check!(pass, fail, "missing last flag -> no long name", lfn_name(&mut lfn, &no_last, &ALIAS).is_none());
+    let mut gap = lfn_entries("a name long enough for three entries", &ALIAS).unwrap_or_default();
+    gap.remove(1);
+    check!(pass, fail, "missing middle entry -> no long name", lfn_name(&mut lfn, &gap, &ALIAS).is_none());
+    let longest = "n".repeat(255);
+    check!(pass, fail, "255-char name fits in 20 entries", lfn_entries(&longest, &ALIAS).map(|e| e.len()) == Ok(20));
+    check!(pass, fail, "256-char name -> InvalidPath", short_name_for(&"n".repeat(256), &[]) == Err(FsError::InvalidPath));
+
+    // Crafted boot sectors: only sane geometry may be mounted
+    check!(pass, fail, "valid BPB accepted", check_boot_sector(&boot_sector(|_| {})) == Ok(()));
+    check!(pass, fail, "zero bytes/sector rejected",
+        check_boot_sector(&boot_sector(|s| s[11..13].copy_from_slice(&0u16.to_le_bytes()))) == Err(FsError::InvalidPath));
+    check!(pass, fail, "4096 bytes/sector rejected",
+        check_boot_sector(&boot_sector(|s| s[11..13].copy_from_slice(&4096u16.to_le_bytes()))) == Err(FsError::InvalidPath));
+    check!(pass, fail, "zero sectors/cluster rejected", check_boot_sector(&boot_sector(|s| s[13] = 0)) == Err(FsError::InvalidPath));
+    check!(pass, fail, "3 sectors/cluster rejected", check_boot_sector(&boot_sector(|s| s[13] = 3)) == Err(FsError::InvalidPath));
+    check!(pass, fail, "zero FATs rejected", check_boot_sector(&boot_sector(|s| s[16] = 0)) == Err(FsError::InvalidPath));
+    check!(pass, fail, "data start beyond volume rejected",
+        check_boot_sector(&boot_sector(|s| s[32..36].copy_from_slice(&200u32.to_le_bytes()))) == Err(FsError::InvalidPath));
+    check!(pass, fail, "huge FAT size does not overflow",
+        check_boot_sector(&boot_sector(|s| s[36..40].copy_from_slice(&u32::MAX.to_le_bytes()))) == Err(FsError::InvalidPath));
+    check!(pass, fail, "FAT too small for volume rejected",
+        check_boot_sector(&boot_sector(|s| s[36..40].copy_from_slice(&16u32.to_le_bytes()))) == Err(FsError::InvalidPath));
+    check!(pass, fail, "root cluster 0 rejected",
+        check_boot_sector(&boot_sector(|s| s[44..48].copy_from_slice(&0u32.to_le_bytes()))) == Err(FsError::InvalidPath));
+    check!(pass, fail, "root cluster past end rejected",
+        check_boot_sector(&boot_sector(|s| s[44..48].copy_from_slice(&0x0FFF_FFF0u32.to_le_bytes()))) == Err(FsError::InvalidPath));
+    // 256 GiB, past what LBA28 reaches: sectors are addressed with u64 LBAs
+    check!(pass, fail, "volume beyond LBA28 accepted",
+        check_boot_sector(&boot_sector(|s| {
+            s[32..36].copy_from_slice(&(1u32 << 29).to_le_bytes());
+            s[36..40].copy_from_slice(&(1u32 << 19).to_le_bytes());
+        })) == Ok(()));
+    check!(pass, fail, "more clusters than FAT32 numbers rejected",
+        check_boot_sector(&boot_sector(|s| {
+            s[13] = 1;
+            s[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+            s[36..40].copy_from_slice(&(1u32 << 25).to_le_bytes());
+        })) == Err(FsError::InvalidPath));
+    check!(pass, fail, "missing 0x55AA rejected", check_boot_sector(&boot_sector(|s| s[511] = 0)) == Err(FsError::InvalidPath));
+
+    // Crafted FATs: chain readers refuse cycles, bad clusters and stray links
+    // rather than looping. Clusters 2-9 of an 8-cluster volume.
+    const EOC: u32 = 0x0FFF_FFFF;
+    let fat = [0x0FFF_FFF8, EOC, 3, 4, EOC, 5, 7, 6, 0x0FFF_FFF7, 42];
+    check!(pass, fail, "sound chain followed to its end", fat_chain(&fat, 2) == Ok(alloc::vec![2, 3, 4]));
+    check!(pass, fail, "chain into itself -> IoError", fat_chain(&fat, 5) == Err(FsError::IoError));
+    check!(pass, fail, "two-cluster cycle -> IoError", fat_chain(&fat, 6) == Err(FsError::IoError));
+    check!(pass, fail, "bad-cluster marker -> IoError", fat_chain(&fat, 8) == Err(FsError::IoError));
+    check!(pass, fail, "link past the data area -> IoError", fat_chain(&fat, 9) == Err(FsError::IoError));
+    check!(pass, fail, "start outside the data area -> IoError", fat_chain(&fat, 1) == Err(FsError::IoError));
+    let mut free_link = fat;
+    free_link[3] = 0;
+    check!(pass, fail, "link to a free cluster -> IoError", fat_chain(&free_link, 2) == Err(FsError::IoError));
+
+    // DOS timestamps: years counted from 1980, seconds stored halved, the odd
+    // second only in the creation tenth field
+    let dt = DateTime { year: 2026, month: 10, day: 16, hour: 21, minute: 45, second: 31 };
+    let (date, time, tenth) = dos_timestamp(&dt);
+    check!(pass, fail, "DOS date packs year-1980, month, day", date == (46 << 9) | (10 << 5) | 16);
+    check!(pass, fail, "DOS time packs hour, minute, second/2", time == (21 << 11) | (45 << 5) | 15);
+    check!(pass, fail, "odd second kept in the tenth field", tenth == 100 && dos_datetime(date, time, tenth) == dt);
+    check!(pass, fail, "time field has 2-second granularity", dos_datetime(date, time, 0).second == 30);
+    check!(pass, fail, "dates before 1980 clamped to 1980-01-01",
+        dos_timestamp(&DateTime { year: 1975, ..dt }).0 == (1 << 5) | 1);
+
+    // Multi-cluster write on the real volume (16 clusters on the boot image):
+    // FAT updates for the whole chain are coalesced, so sector writes stay
+    // close to the data sectors themselves, counted once synced to disk
+    match crate::fs::fat32().map(|fs| fs.volume_info()) {
+        Some(Ok(info)) => {
+            const PATH: &str = "/disk/batch.tmp";
+            const SIZE: usize = 8 * 1024;
+            let data: alloc::vec::Vec<u8> = (0..SIZE).map(|i| (i % 251) as u8).collect();
+            let mut vfs = crate::fs::VFS.lock();
+            let _ = vfs.unlink(PATH);
+
+            let before = sector_writes();
+            let written = vfs.create(PATH).and_then(|_| vfs.write_file(PATH, &data));
+            let synced = vfs.sync_all();
+            let writes = sector_writes() - before;
+
+            let spc = info.sectors_per_cluster as u64;
+            let data_sectors = (SIZE as u64).div_ceil(512).div_ceil(spc) * spc;
+            // create() zeroes its first cluster; beyond that only the directory
+            // entry (create + size update) and a few FAT sectors per copy
+            let budget = data_sectors + spc + 4 * info.num_fats as u64 + 4;
+            test_log!("  8 KiB write: {} sector writes ({} data, budget {})", writes, data_sectors, budget);
+            check!(pass, fail, "8 KiB file written", written == Ok(SIZE));
+            check!(pass, fail, "FAT writes coalesced", synced.is_ok() && writes <= budget);
+
+            // The sector cache: a second lookup reads nothing from disk, and
+            // a rewrite stays in memory until sync writes it back
+            let _ = vfs.exists(PATH);
+            let before = sector_reads();
+            let found = vfs.exists(PATH);
+            check!(pass, fail, "repeated lookup served from the sector cache", found && sector_reads() == before);
+            let before = sector_writes();
+            let rewritten = vfs.write_file(PATH, &data);
+            let cached_writes = sector_writes() - before;
+            let synced = vfs.sync_all();
+            let synced_writes = sector_writes() - before - cached_writes;
+            test_log!("  rewrite: {} sector writes before sync, {} on sync", cached_writes, synced_writes);
+            check!(pass, fail, "sync writes back the cached sectors",
+                rewritten == Ok(SIZE) && synced.is_ok() && synced_writes > 0);
+            let before = sector_writes();
+            check!(pass, fail, "nothing left dirty after sync", vfs.sync_all().is_ok() && sector_writes() == before);
+
+            let mut back = alloc::vec![0u8; SIZE];
+            check!(pass, fail, "8 KiB read back intact",
+                vfs.read_file(PATH, 0, &mut back) == Ok(SIZE) && back == data);
+
+            // Stamped from the RTC; allow for the 2-second granularity and the time the write took
+            let now = rtc::read_datetime().unix_time();
+            match crate::fs::fat32().map(|fs| fs.times("/batch.tmp")) {
+                Some(Ok(times)) => {
+                    test_log!("  created {}, modified {}", times.created, times.modified);
+                    let recent = |t: DateTime| t.unix_time() <= now && t.unix_time() + 5 >= now;
+                    check!(pass, fail, "creation time set on create", recent(times.created));
+                    check!(pass, fail, "write time set on write", recent(times.modified));
+                    check!(pass, fail, "access date set", times.accessed.unix_time() / 86400 == now / 86400);
+                }
+                _ => { test_log!("[FAIL] timestamps of the written file unreadable"); fail += 1; }
+            }
+
+            // A short read near the end walks the FAT links but only reads the
+            // sectors it copies: same cost as at the start, give or take a FAT
+            // sector. (The 16 MiB boot image can't hold a file past the old
+            // whole-chain read limit; the sparse image below checks the size.)
+            let mut head = [0u8; 100];
+            let mut tail = [0u8; 100];
+            let before = sector_reads();
+            let head_ok = vfs.read_file(PATH, 0, &mut head) == Ok(100);
+            let head_reads = sector_reads() - before;
+            let before = sector_reads();
+            let tail_ok = vfs.read_file(PATH, SIZE - 200, &mut tail) == Ok(100);
+            let tail_reads = sector_reads() - before;
+            test_log!("  100 B reads: {} sectors at offset 0, {} near the end", head_reads, tail_reads);
+            check!(pass, fail, "read at high offset returns the right bytes",
+                head_ok && tail_ok && head[..] == data[..100] && tail[..] == data[SIZE - 200..SIZE - 100]);
+            check!(pass, fail, "read at high offset skips the data before it", tail_reads <= head_reads + 2);
+            let mut past_end = [0u8; 100];
+            check!(pass, fail, "read straddling EOF is short", vfs.read_file(PATH, SIZE - 10, &mut past_end) == Ok(10));
+
+            // Rename: same directory, into a subdirectory (directory and its
+            // contents), over an existing file, and not below itself
+            let _ = vfs.mkdir("/disk/ren_dir");
+            let _ = vfs.create("/disk/ren_dir/inner.txt");
+            let _ = vfs.write_file("/disk/ren_dir/inner.txt", b"inner");
+            let _ = vfs.mkdir("/disk/ren_dst");
+            let mut small = [0u8; 16];
+            check!(pass, fail, "rename within a directory", vfs.rename(PATH, "/disk/batch2.tmp").is_ok()
+                && !vfs.exists(PATH) && vfs.read_file("/disk/batch2.tmp", 0, &mut back) == Ok(SIZE) && back == data);
+            check!(pass, fail, "rename directory with children",
+                vfs.rename("/disk/ren_dir", "/disk/ren_dst/moved").is_ok()
+                    && vfs.read_file("/disk/ren_dst/moved/inner.txt", 0, &mut small) == Ok(5)
+                    && !vfs.exists("/disk/ren_dir"));
+            // Found by walking up through the moved directory's updated ".."
+            check!(pass, fail, "rename directory below itself -> InvalidPath",
+                vfs.rename("/disk/ren_dst", "/disk/ren_dst/moved/loop") == Err(FsError::InvalidPath));
+            let _ = vfs.rename("/disk/batch2.tmp", PATH);
+            check!(pass, fail, "rename over an existing file replaces it",
+                vfs.rename("/disk/ren_dst/moved/inner.txt", PATH).is_ok()
+                    && vfs.lookup(PATH).map(|i| i.size) == Ok(5));
+            let _ = vfs.unlink("/disk/ren_dst/moved");
+            let _ = vfs.unlink("/disk/ren_dst");
+            check!(pass, fail, "volume consistent after renames",
+                crate::fs::fat32().is_some_and(|fs| fs.fsck().is_ok_and(|r| r.is_clean())));
+
+            check!(pass, fail, "temp file removed", vfs.unlink(PATH).is_ok());
+
+            // Put on the image by `make run`, through a host VFAT driver
+            const LONG: &str = "/disk/longfilename.txt";
+            let listed = vfs.readdir("/disk")
+                .map(|es| es.iter().any(|e| e.name == "longfilename.txt"))
+                .unwrap_or(false);
+            check!(pass, fail, "readdir lists longfilename.txt", listed);
+            check!(pass, fail, "lookup by long name", vfs.lookup(LONG).is_ok());
+            check!(pass, fail, "long name lookup ignores case", vfs.lookup("/disk/LongFileName.TXT").is_ok());
+            check!(pass, fail, "8.3 alias still resolves", vfs.lookup("/disk/longfi~1.txt").is_ok());
+            let mut text = [0u8; 64];
+            let n = vfs.read_file(LONG, 0, &mut text).unwrap_or(0);
+            check!(pass, fail, "read file by long name", text[..n].starts_with(b"This file has a VFAT long name."));
+            check!(pass, fail, "create over a long name -> AlreadyExists",
+                vfs.create("/disk/LONGFILENAME.TXT").err() == Some(FsError::AlreadyExists));
+
+            // Names that aren't 8.3 get LFN entries, so they open by the name given
+            const NEW_LONG: &str = "/disk/a new long name.text";
+            let created = vfs.create(NEW_LONG).is_ok() && vfs.write_file(NEW_LONG, b"long").is_ok();
+            check!(pass, fail, "create a long name", created);
+            let n = vfs.read_file(NEW_LONG, 0, &mut text).unwrap_or(0);
+            check!(pass, fail, "open it by its long name", &text[..n] == b"long");
+            check!(pass, fail, "readdir shows the long name", vfs.readdir("/disk")
+                .is_ok_and(|es| es.iter().any(|e| e.name == "a new long name.text")));
+            check!(pass, fail, "its ~1 alias resolves too", vfs.lookup("/disk/anewlo~1.tex").is_ok());
+            check!(pass, fail, "mkdir a long name, create inside it",
+                vfs.mkdir("/disk/long directory name").is_ok()
+                    && vfs.create("/disk/long directory name/inside a long dir.txt").is_ok()
+                    && vfs.lookup("/disk/long directory name/inside a long dir.txt").is_ok());
+            check!(pass, fail, "rename to a long name keeps it",
+                vfs.rename(NEW_LONG, "/disk/long directory name/renamed long name.txt").is_ok()
+                    && vfs.lookup("/disk/long directory name/renamed long name.txt").is_ok());
+            let _ = vfs.unlink("/disk/long directory name/renamed long name.txt");
+            let _ = vfs.unlink("/disk/long directory name/inside a long dir.txt");
+            check!(pass, fail, "long name directory removed", vfs.unlink("/disk/long directory name").is_ok());
+            check!(pass, fail, "volume consistent after long names",
+                crate::fs::fat32().is_some_and(|fs| fs.fsck().is_ok_and(|r| r.is_clean())));
+        }
+        _ => { test_log!("  (no FAT32 volume mounted, skipping disk I/O tests)"); }
+    }
+
+    // A 20 MiB file, larger than the heap: reading the whole chain to get at
+    // an offset could never serve it, reading from the offset only touches
+    // the FAT and the sectors copied
+    match big_file_volume() {
+        Ok((fs, file_start)) => {
+            const PATH: &str = "/BIG.BIN";
+            check!(pass, fail, "20 MiB file listed with its size", fs.lookup(PATH).map(|i| i.size) == Ok(BIG_FILE_SIZE));
+            let expected = |offset: usize, len: usize| (0..len).map(move |i| SparseDisk::pattern(file_start + offset + i));
+
+            let mut buf = [0u8; 100];
+            let before = sector_reads();
+            let tail = fs.read(PATH, BIG_FILE_SIZE - 100, &mut buf);
+            let tail_reads = sector_reads() - before;
+            test_log!("  100 B read at 20 MiB - 100: {} sectors", tail_reads);
+            check!(pass, fail, "read at 20 MiB - 100 returns the right bytes",
+                tail == Ok(100) && buf.iter().copied().eq(expected(BIG_FILE_SIZE - 100, 100)));
+            // The root directory's cluster, the FAT sectors holding the chain
+            // and the one data sector, give or take a sector or two; not the
+            // 40960 data sectors before it
+            let chain_fat_sectors = (BIG_FILE_SIZE / (SPARSE_CLUSTER_SECTORS as usize * 512) * 4).div_ceil(512) as u64 + 1;
+            check!(pass, fail, "high-offset read skips the data before it",
+                tail_reads <= SPARSE_CLUSTER_SECTORS as u64 + chain_fat_sectors + 1 + 2);
+            let across = fs.read(PATH, 16 * 1024 * 1024 - 50, &mut buf);
+            check!(pass, fail, "read across the old 16 MiB limit",
+                across == Ok(100) && buf.iter().copied().eq(expected(16 * 1024 * 1024 - 50, 100)));
+            check!(pass, fail, "read straddling the 20 MiB EOF is short", fs.read(PATH, BIG_FILE_SIZE - 10, &mut buf) == Ok(10));
+        }
+        Err(e) => { test_log!("[FAIL] lay out a 20 MiB file on the sparse image: {}", e); fail += 1; }
+    }
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// A well-formed 64 MiB FAT32 boot sector (8 sectors/cluster, 2 FATs of 128
+/// sectors, root at cluster 2), with `patch` applied on top.
+fn boot_sector(patch: impl FnOnce(&mut [u8; 512])) -> [u8; 512] {
+    let mut s = [0u8; 512];
+    s[3..11].copy_from_slice(b"ATOMICOS");
+    s[11..13].copy_from_slice(&512u16.to_le_bytes());
+    s[13] = 8;
+    s[14..16].copy_from_slice(&32u16.to_le_bytes());
+    s[16] = 2;
+    s[32..36].copy_from_slice(&131072u32.to_le_bytes());
+    s[36..40].copy_from_slice(&128u32.to_le_bytes());
+    s[44..48].copy_from_slice(&2u32.to_le_bytes());
+    s[510] = 0x55;
+    s[511] = 0xAA;
+    patch(&mut s);
+    s
+}
+
+/// Feed `entries` to `lfn` and return the name it gives the short entry `alias`.
+fn lfn_name(lfn: &mut LongNameBuilder, entries: &[[u8; 32]], alias: &[u8; 11]) -> Option<alloc::string::String> {
+    for e in entries {
+        lfn.push(e);
+    }
+    lfn.finish(alias)
+}
+
+/// A disk holding only the sectors written to it. Sectors never written read
+/// as `pattern` of their byte position, so a file far larger than the heap
+/// can be laid out over them without its contents being held anywhere.
+struct SparseDisk {
+    sectors: u64,
+    written: Mutex<BTreeMap<u64, [u8; 512]>>,
+}
+
+impl SparseDisk {
+    fn new(sectors: u64) -> Self {
+        SparseDisk { sectors, written: Mutex::new(BTreeMap::new()) }
+    }
+
+    /// Contents of never-written byte `pos`: period 251, so every offset
+    /// within a sector and cluster reads differently.
+    fn pattern(pos: usize) -> u8 {
+        (pos % 251) as u8
+    }
+}
+
+impl BlockDevice for SparseDisk {
+    fn block_size(&self) -> usize {
+        512
+    }
+
+    fn block_count(&self) -> u64 {
+        self.sectors
+    }
+
+    fn read_block(&self, lba: u64, buf: &mut [u8]) -> BlockResult<()> {
+        if lba >= self.sectors || buf.len() != 512 {
+            return Err(BlockError::OutOfRange);
+        }
+        match self.written.lock().get(&lba) {
+            Some(sector) => buf.copy_from_slice(sector),
+            None => {
+                for (i, b) in buf.iter_mut().enumerate() {
+                    *b = Self::pattern(lba as usize * 512 + i);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_block(&self, lba: u64, buf: &[u8]) -> BlockResult<()> {
+        let sector: [u8; 512] = buf.try_into().map_err(|_| BlockError::OutOfRange)?;
+        if lba >= self.sectors {
+            return Err(BlockError::OutOfRange);
+        }
+        self.written.lock().insert(lba, sector);
+        Ok(())
+    }
+}
+
+/// Format the sparse image and lay out /BIG.BIN by hand: `BIG_FILE_SIZE`
+/// bytes in consecutive clusters from `BIG_FILE_CLUSTER`, linked in every
+/// FAT copy, with its entry first in the root directory. Its data is the
+/// never-written sectors' pattern, so only the FAT and root sectors are
+/// stored. Returns the image mounted and the disk byte the file starts at.
+fn big_file_volume() -> FsResult<(Fat32Fs, usize)> {
+    let disk: &'static SparseDisk = &SPARSE_IMAGE;
+    disk.written.lock().clear();
+    format::format(disk, SPARSE_CLUSTER_SECTORS, "BIGFILE")?;
+
+    let read = |lba: u64| {
+        let mut sector = [0u8; 512];
+        disk.read_block(lba, &mut sector).map(|_| sector).map_err(|_| FsError::IoError)
+    };
+    let write = |lba: u64, sector: &[u8; 512]| disk.write_block(lba, sector).map_err(|_| FsError::IoError);
+    let boot = read(0)?;
+    let reserved = u16::from_le_bytes([boot[14], boot[15]]) as u64;
+    let num_fats = boot[16] as u64;
+    let fat_size = u32::from_le_bytes([boot[36], boot[37], boot[38], boot[39]]) as u64;
+    let root_sector = reserved + num_fats * fat_size;
+
+    let clusters = BIG_FILE_SIZE.div_ceil(SPARSE_CLUSTER_SECTORS as usize * 512) as u32;
+    let last = BIG_FILE_CLUSTER + clusters - 1;
+    for copy in 0..num_fats {
+        let fat_start = reserved + copy * fat_size;
+        let mut index = u64::MAX;
+        let mut sector = [0u8; 512];
+        for cluster in BIG_FILE_CLUSTER..=last {
+            let at = cluster as u64 * 4;
+            if at / 512 != index {
+                if index != u64::MAX {
+                    write(fat_start + index, &sector)?;
+                }
+                index = at / 512;
+                sector = read(fat_start + index)?;
+            }
+            let next: u32 = if cluster == last { 0x0FFF_FFFF } else { cluster + 1 };
+            let off = (at % 512) as usize;
+            sector[off..off + 4].copy_from_slice(&next.to_le_bytes());
+        }
+        write(fat_start + index, &sector)?;
+    }
+
+    let mut root = read(root_sector)?;
+    root[0..11].copy_from_slice(b"BIG     BIN");
+    root[11] = 0x20; // archive
+    root[20..22].copy_from_slice(&((BIG_FILE_CLUSTER >> 16) as u16).to_le_bytes());
+    root[26..28].copy_from_slice(&(BIG_FILE_CLUSTER as u16).to_le_bytes());
+    root[28..32].copy_from_slice(&(BIG_FILE_SIZE as u32).to_le_bytes());
+    write(root_sector, &root)?;
+
+    let file_sector = root_sector + (BIG_FILE_CLUSTER as u64 - 2) * SPARSE_CLUSTER_SECTORS as u64;
+    Ok((Fat32Fs::init(disk)?, file_sector as usize * 512))
+}
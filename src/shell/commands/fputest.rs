@@ -0,0 +1,214 @@
+use core::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use crate::scheduler::fpu::DEFAULT_MXCSR;
+use crate::shell::commands::uptime::TICKS;
+use crate::shell::commands::testutil::test_log;
+
+/// User program that holds values in XMM0-15 and MXCSR across syscalls
+/// and a fork (tests/test_elf/test_fpu.S).
+static TEST_FPU_ELF: &[u8] = include_bytes!("../../../tests/test_elf/test_fpu.elf");
+
+const FPU_PATH: &str = "/tmp/fputest.elf";
+
+/// Rounds each worker runs; every round spans at least one timer tick.
+const ROUNDS: u32 = 5;
+/// Terms of the harmonic series each worker sums per round.
+const HARMONIC_TERMS: u32 = 2000;
+/// MXCSR with rounding control set to round-toward-zero.
+const MXCSR_ROUND_TO_ZERO: u32 = DEFAULT_MXCSR | (0b11 << 13);
+
+static DONE: AtomicUsize = AtomicUsize::new(0);
+static XMM_ERRORS: AtomicU32 = AtomicU32::new(0);
+static MXCSR_ERRORS: AtomicU32 = AtomicU32::new(0);
+static MATH_ERRORS: AtomicU32 = AtomicU32::new(0);
+static EXPECTED_NEAREST: AtomicU64 = AtomicU64::new(0);
+static EXPECTED_ZERO: AtomicU64 = AtomicU64::new(0);
+
+/// fputest — two tasks do floating-point work across preemptions with
+/// different XMM contents and MXCSR rounding modes. If the context switch
+/// did not save FPU/SSE state, each would see the other's registers.
+/// Then a user program checks its own registers survive syscalls, which
+/// run kernel code that may use SSE.
+pub fn run(_args: &str) {
+    test_log!("=== FPU/SSE Context Switch Test ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    // Reference results, computed with preemption off so nothing can interfere
+    let (nearest, zero) = x86_64::instructions::interrupts::without_interrupts(|| {
+        let nearest = harmonic();
+        set_mxcsr(MXCSR_ROUND_TO_ZERO);
+        let zero = harmonic();
+        set_mxcsr(DEFAULT_MXCSR);
+        (nearest, zero)
+    });
+    EXPECTED_NEAREST.store(nearest.to_bits(), Ordering::Relaxed);
+    EXPECTED_ZERO.store(zero.to_bits(), Ordering::Relaxed);
+
+    DONE.store(0, Ordering::Relaxed);
+    XMM_ERRORS.store(0, Ordering::Relaxed);
+    MXCSR_ERRORS.store(0, Ordering::Relaxed);
+    MATH_ERRORS.store(0, Ordering::Relaxed);
+
+    crate::scheduler::spawn(task_nearest, "fpu_a");
+    crate::scheduler::spawn(task_zero, "fpu_b");
+    while DONE.load(Ordering::Acquire) < 2 {
+        crate::scheduler::yield_now();
+    }
+
+    // Test 1: the two rounding modes really give different answers
+    if nearest != zero {
+        test_log!("[PASS] rounding mode changes the result"); pass += 1;
+    } else {
+        test_log!("[FAIL] rounding modes agree, MXCSR check is meaningless"); fail += 1;
+    }
+
+    // Test 2: XMM0-15 survive being switched out
+    match XMM_ERRORS.load(Ordering::Relaxed) {
+        0 => { test_log!("[PASS] XMM registers preserved across switches"); pass += 1; }
+        n => { test_log!("[FAIL] {} round(s) saw clobbered XMM registers", n); fail += 1; }
+    }
+
+    // Test 3: each task keeps its own MXCSR
+    match MXCSR_ERRORS.load(Ordering::Relaxed) {
+        0 => { test_log!("[PASS] MXCSR is per task"); pass += 1; }
+        n => { test_log!("[FAIL] {} round(s) saw another task's MXCSR", n); fail += 1; }
+    }
+
+    // Test 4: concurrent floating-point math matches the reference results
+    match MATH_ERRORS.load(Ordering::Relaxed) {
+        0 => { test_log!("[PASS] concurrent harmonic sums correct"); pass += 1; }
+        n => { test_log!("[FAIL] {} harmonic sum(s) wrong", n); fail += 1; }
+    }
+
+    // Test 5: the shell's own state came through the switches unchanged
+    if get_mxcsr() == DEFAULT_MXCSR && harmonic() == nearest {
+        test_log!("[PASS] shell FPU state intact"); pass += 1;
+    } else {
+        test_log!("[FAIL] shell FPU state changed"); fail += 1;
+    }
+
+    // Test 6: a user program's XMM registers and MXCSR survive int 0x80
+    let written = {
+        let mut vfs = crate::fs::VFS.lock();
+        let _ = vfs.unlink(FPU_PATH);
+        vfs.create(FPU_PATH).and_then(|_| vfs.write_file(FPU_PATH, TEST_FPU_ELF))
+    };
+    match written {
+        Ok(n) if n == TEST_FPU_ELF.len() => match crate::shell::run_external(FPU_PATH, "") {
+            0 => { test_log!("[PASS] user FPU state held across syscalls and fork"); pass += 1; }
+            17 => { test_log!("[FAIL] user MXCSR changed by a syscall"); fail += 1; }
+            code @ 1..=16 => { test_log!("[FAIL] user XMM{} changed by a syscall", code - 1); fail += 1; }
+            code => { test_log!("[FAIL] user FPU program exited {}", code); fail += 1; }
+        },
+        Ok(n) => { test_log!("[FAIL] setup: short write ({} bytes)", n); fail += 1; }
+        Err(e) => { test_log!("[FAIL] setup: {}", e); fail += 1; }
+    }
+    let _ = crate::fs::VFS.lock().unlink(FPU_PATH);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+fn task_nearest() {
+    worker(0x0123_4567_89AB_CDEF, DEFAULT_MXCSR, &EXPECTED_NEAREST);
+}
+
+fn task_zero() {
+    worker(0xFEDC_BA98_7654_3210, MXCSR_ROUND_TO_ZERO, &EXPECTED_ZERO);
+}
+
+fn worker(seed: u64, mxcsr: u32, expected: &AtomicU64) {
+    set_mxcsr(mxcsr);
+    for round in 0..ROUNDS {
+        let mut pattern = [0u64; 32];
+        for (i, word) in pattern.iter_mut().enumerate() {
+            *word = seed.rotate_left(i as u32) ^ (round as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        }
+        if hold_xmm_across_tick(&pattern) != pattern {
+            XMM_ERRORS.fetch_add(1, Ordering::Relaxed);
+        }
+        if get_mxcsr() != mxcsr {
+            MXCSR_ERRORS.fetch_add(1, Ordering::Relaxed);
+        }
+        if harmonic().to_bits() != expected.load(Ordering::Relaxed) {
+            MATH_ERRORS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    DONE.fetch_add(1, Ordering::Release);
+    crate::scheduler::exit_current(0);
+}
+
+/// Load `pattern` into XMM0-15, spin until the next timer tick (which may
+/// switch to another task), then read the registers back.
+fn hold_xmm_across_tick(pattern: &[u64; 32]) -> [u64; 32] {
+    let mut out = [0u64; 32];
+    let start = TICKS.load(Ordering::Relaxed);
+    unsafe {
+        core::arch::asm!(
+            "movdqu xmm0, [{src} + 0]",
+            "movdqu xmm1, [{src} + 16]",
+            "movdqu xmm2, [{src} + 32]",
+            "movdqu xmm3, [{src} + 48]",
+            "movdqu xmm4, [{src} + 64]",
+            "movdqu xmm5, [{src} + 80]",
+            "movdqu xmm6, [{src} + 96]",
+            "movdqu xmm7, [{src} + 112]",
+            "movdqu xmm8, [{src} + 128]",
+            "movdqu xmm9, [{src} + 144]",
+            "movdqu xmm10, [{src} + 160]",
+            "movdqu xmm11, [{src} + 176]",
+            "movdqu xmm12, [{src} + 192]",
+            "movdqu xmm13, [{src} + 208]",
+            "movdqu xmm14, [{src} + 224]",
+            "movdqu xmm15, [{src} + 240]",
+            "2:",
+            "cmp [{ticks}], {start}",
+            "je 2b",
+            "movdqu [{dst} + 0], xmm0",
+            "movdqu [{dst} + 16], xmm1",
+            "movdqu [{dst} + 32], xmm2",
+            "movdqu [{dst} + 48], xmm3",
+            "movdqu [{dst} + 64], xmm4",
+            "movdqu [{dst} + 80], xmm5",
+            "movdqu [{dst} + 96], xmm6",
+            "movdqu [{dst} + 112], xmm7",
+            "movdqu [{dst} + 128], xmm8",
+            "movdqu [{dst} + 144], xmm9",
+            "movdqu [{dst} + 160], xmm10",
+            "movdqu [{dst} + 176], xmm11",
+            "movdqu [{dst} + 192], xmm12",
+            "movdqu [{dst} + 208], xmm13",
+            "movdqu [{dst} + 224], xmm14",
+            "movdqu [{dst} + 240], xmm15",
+            src = in(reg) pattern.as_ptr(),
+            dst = in(reg) out.as_mut_ptr(),
+            ticks = in(reg) TICKS.as_ptr(),
+            start = in(reg) start,
+            out("xmm0") _, out("xmm1") _, out("xmm2") _, out("xmm3") _, out("xmm4") _, out("xmm5") _, out("xmm6") _, out("xmm7") _, out("xmm8") _, out("xmm9") _, out("xmm10") _, out("xmm11") _, out("xmm12") _, out("xmm13") _, out("xmm14") _, out("xmm15") _,
+            options(nostack),
+        );
+    }
+    out
+}
+
+/// Sum of 1/i for i in 1..=HARMONIC_TERMS, rounded per the current MXCSR.
+fn harmonic() -> f64 {
+    let mut sum = 0.0f64;
+    for i in 1..=HARMONIC_TERMS {
+        sum += 1.0 / core::hint::black_box(i as f64);
+    }
+    sum
+}
+
+fn get_mxcsr() -> u32 {
+    let mut mxcsr = 0u32;
+    unsafe { core::arch::asm!("stmxcsr [{}]", in(reg) &mut mxcsr, options(nostack, preserves_flags)); }
+    mxcsr
+}
+
+fn set_mxcsr(mxcsr: u32) {
+    unsafe { core::arch::asm!("ldmxcsr [{}]", in(reg) &mxcsr, options(nostack, readonly)); }
+}
@@ -0,0 +1,172 @@
+use x86_64::structures::paging::{FrameAllocator, FrameDeallocator};
+use crate::memory::FRAME_ALLOCATOR;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// User program that sleeps, then forks (tests/test_elf/test_forkfail.S).
+static TEST_FORKFAIL_ELF: &[u8] = include_bytes!("../../../tests/test_elf/test_forkfail.elf");
+
+const FORKFAIL_PATH: &str = "/tmp/forkfail.elf";
+/// Allocations a fork may make before it is assumed never to succeed.
+const MAX_FAIL_POINTS: usize = 64;
+/// Frames put on the free list before each fork, more than any failing
+/// fork takes, so it only ever runs on recycled frames.
+const SEED_FRAMES: usize = 16;
+
+/// Fork/exit benchmark on the FAT32 disk (userland/fork_bench).
+const FORK_PROGRAM: &str = "/disk/fbench.elf";
+/// Times to run it; each run forks and reaps 32 children.
+const FORK_RUNS: usize = 3;
+
+/// frametest — physical frame recycling. Checks the free list directly,
+/// makes a fork fail at each of its frame allocations in turn, then runs a
+/// fork/exit-heavy program repeatedly: every frame its processes used
+/// (pages and page tables) must come back.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== Frame Allocator Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    // Results are printed once the lock is dropped: it is held with
+    // interrupts off, and printing may have to wait for the serial port
+    let (counted, freed, next, scrubbed, listed, lifo, restored) = {
+        let mut frames = FRAME_ALLOCATOR.lock();
+        let used = frames.used_frames();
+
+        let Some(a) = frames.allocate_frame() else {
+            drop(frames);
+            test_log!("[FAIL] no physical frame available");
+            return;
+        };
+        let counted = frames.used_frames() == used + 1;
+        // Physical memory is identity-mapped
+        let bytes = a.start_address().as_u64() as *mut u8;
+        unsafe {
+            core::ptr::write_bytes(bytes, 0xA5, 4096);
+            frames.deallocate_frame(a);
+        }
+        let freed = frames.used_frames() == used;
+        let next = frames.allocate_frame() == Some(a);
+        // Recycled frames may end up in user space: nothing of the last
+        // owner's data, nor the free-list links, may be left
+        let scrubbed = next && unsafe { core::slice::from_raw_parts(bytes, 4096) }.iter().all(|&b| b == 0);
+
+        // Several at once come back most recently freed first
+        let (b, c) = (frames.allocate_frame(), frames.allocate_frame());
+        let (listed, lifo) = if let (Some(b), Some(c)) = (b, c) {
+            unsafe {
+                frames.deallocate_frame(a);
+                frames.deallocate_frame(b);
+                frames.deallocate_frame(c);
+            }
+            let listed = frames.recycled_frames() >= 3;
+            let again = [frames.allocate_frame(), frames.allocate_frame(), frames.allocate_frame()];
+            for f in again.into_iter().flatten() {
+                unsafe { frames.deallocate_frame(f); }
+            }
+            (Some(listed), again == [Some(c), Some(b), Some(a)])
+        } else {
+            (None, false)
+        };
+        (counted, freed, next, scrubbed, listed, lifo, frames.used_frames() == used)
+    };
+    check!(pass, fail, "allocation counted", counted);
+    check!(pass, fail, "free returns the frame", freed);
+    check!(pass, fail, "freed frame handed out next", next);
+    check!(pass, fail, "recycled frame comes back zeroed", scrubbed);
+    if let Some(listed) = listed {
+        check!(pass, fail, "three frames on the free list", listed);
+        check!(pass, fail, "reused in LIFO order", lifo);
+    } else {
+        test_log!("[FAIL] could not allocate three frames"); fail += 1;
+    }
+    check!(pass, fail, "usage back where it started", restored);
+
+    // A fork that fails partway, on frames taken from the free list, gives
+    // them all back. Failing allocation n is tried for n = 0, 1, ... until
+    // the fork gets through
+    let written = {
+        let mut vfs = crate::fs::VFS.lock();
+        let _ = vfs.unlink(FORKFAIL_PATH);
+        vfs.create(FORKFAIL_PATH).and_then(|_| vfs.write_file(FORKFAIL_PATH, TEST_FORKFAIL_ELF))
+    };
+    if matches!(written, Ok(n) if n == TEST_FORKFAIL_ELF.len()) {
+        // A first run that forks normally, so one-time growth (heap, stack
+        // slots) does not count against the failing runs
+        let warm_up = fork_with_failure(None);
+        let before = FRAME_ALLOCATOR.lock().free_frames();
+        let mut failed_points = 0;
+        let mut succeeded = false;
+        let mut leaked = 0;
+        for n in 0..MAX_FAIL_POINTS {
+            match fork_with_failure(Some(n)) {
+                Some(1) => failed_points += 1,
+                Some(2) => { succeeded = true; break; }
+                code => { test_log!("  failing allocation {}: unexpected exit {:?}", n, code); break; }
+            }
+            leaked = before.saturating_sub(FRAME_ALLOCATOR.lock().free_frames());
+            if leaked != 0 {
+                test_log!("  failing allocation {}: {} frame(s) not returned", n, leaked);
+                break;
+            }
+        }
+        test_log!("fork failed at {} allocation point(s)", failed_points);
+        check!(pass, fail, "warm-up fork succeeded", warm_up == Some(2));
+        check!(pass, fail, "fork fails cleanly at its early allocations", failed_points >= 3);
+        check!(pass, fail, "fork succeeds once allocations stop failing", succeeded);
+        check!(pass, fail, "failed forks returned all their frames", leaked == 0);
+    } else {
+        test_log!("[FAIL] setup: could not write {}", FORKFAIL_PATH); fail += 1;
+    }
+    let _ = crate::fs::VFS.lock().unlink(FORKFAIL_PATH);
+
+    if crate::fs::VFS.lock().exists(FORK_PROGRAM) {
+        let before = FRAME_ALLOCATOR.lock().used_frames();
+        let mut statuses_ok = true;
+        let mut leaked = 0;
+        for _ in 0..FORK_RUNS {
+            statuses_ok &= crate::shell::run_external(FORK_PROGRAM, "") == 0;
+            leaked = FRAME_ALLOCATOR.lock().used_frames().saturating_sub(before);
+        }
+        check!(pass, fail, "fork/exit runs completed", statuses_ok);
+        test_log!("frames still in use after {} runs: {} more than before", FORK_RUNS, leaked);
+        check!(pass, fail, "exited processes returned all their frames", leaked == 0);
+    } else {
+        test_log!("  ({} not found, skipping fork/exit test)", FORK_PROGRAM);
+    }
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// Run the fork test program. While it sleeps, put `SEED_FRAMES` frames on
+/// the free list and arm the frame allocator so that allocation `fail_at`
+/// (counting from then) fails. Returns its exit code: 1 if the fork
+/// failed, 2 if it succeeded.
+fn fork_with_failure(fail_at: Option<usize>) -> Option<u64> {
+    let shell_pid = crate::scheduler::SCHEDULER.lock()
+        .current.as_ref().map_or(crate::scheduler::ProcessId(0), |p| p.pid);
+    let pid = crate::loader::elf::spawn(FORKFAIL_PATH, &[FORKFAIL_PATH], &[], Some(shell_pid)).ok()?;
+
+    // Long enough for it to load its pages and start its 200 ms sleep
+    crate::scheduler::sleep_ticks(crate::interrupts::pit::ms_to_ticks(50));
+    {
+        let mut frames = FRAME_ALLOCATOR.lock();
+        let seeds: [_; SEED_FRAMES] = core::array::from_fn(|_| frames.allocate_frame());
+        for frame in seeds.into_iter().flatten() {
+            unsafe { frames.deallocate_frame(frame); }
+        }
+        frames.fail_after(fail_at);
+    }
+
+    let status = crate::scheduler::sys_wait(pid.0, 0);
+    // In case the fork made fewer allocations than that
+    FRAME_ALLOCATOR.lock().fail_after(None);
+    match status {
+        Ok(Some((_, status))) if crate::syscalls::wait::exited(status) => Some(crate::syscalls::wait::exit_code(status)),
+        _ => None,
+    }
+}
@@ -0,0 +1,14 @@
+use crate::println;
+
+/// free — physical memory and kernel heap usage, in KiB.
+pub fn run(_args: &str) {
+    let mem = crate::memory::stats();
+
+    println!("{:8}{:>12}{:>12}{:>12}", "", "total", "used", "free");
+    println!("{:8}{:>12}{:>12}{:>12}", "Mem:",
+        mem.total_bytes / 1024, mem.used_bytes() / 1024, mem.free_bytes() / 1024);
+    println!("{:8}{:>12}{:>12}{:>12}", "Heap:",
+        mem.heap_mapped / 1024, mem.heap_used / 1024, (mem.heap_mapped - mem.heap_used) / 1024);
+    println!("Heap since boot: {} KiB allocated, {} KiB freed; {} frames handed out",
+        mem.heap_allocated / 1024, mem.heap_freed / 1024, mem.frames_handed_out);
+}
@@ -0,0 +1,40 @@
+use crate::println;
+
+/// fsck — read-only consistency check of the FAT32 volume at /disk.
+pub fn run(_args: &str) {
+    let fs = match crate::fs::fat32() {
+        Some(fs) => fs,
+        None => {
+            println!("fsck: no FAT32 volume mounted");
+            return;
+        }
+    };
+
+    println!("fsck: checking /disk...");
+    let report = match fs.fsck() {
+        Ok(r) => r,
+        Err(e) => {
+            println!("fsck: {}", e);
+            return;
+        }
+    };
+
+    for msg in &report.messages {
+        println!("  {}", msg);
+    }
+
+    println!("  {} FAT copies, {} clusters ({} in use)", report.num_fats, report.total_clusters, report.used_clusters);
+    println!("  {} directories, {} files", report.directories, report.files);
+    println!("  FAT mismatches:   {}", report.fat_mismatches);
+    println!("  Chain loops:      {}", report.loops);
+    println!("  Bad links:        {}", report.bad_links);
+    println!("  Cross-links:      {}", report.cross_links);
+    println!("  Size mismatches:  {}", report.size_mismatches);
+    println!("  Orphan clusters:  {}", report.orphaned_clusters);
+
+    if report.is_clean() {
+        println!("fsck: volume is clean");
+    } else {
+        println!("fsck: volume has errors");
+    }
+}
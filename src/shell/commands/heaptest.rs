@@ -0,0 +1,120 @@
+use alloc::alloc::{alloc, dealloc, Layout};
+use alloc::vec::Vec;
+use crate::allocator::{heap_fragments, heap_stats};
+use crate::shell::commands::testutil::{check, test_log};
+
+/// Allocate/free rounds in each stress pass.
+const STRESS_ROUNDS: usize = 5000;
+/// Vecs kept alive at once during the stress passes.
+const LIVE_SLOTS: usize = 64;
+/// Largest Vec the stress passes allocate.
+const MAX_VEC: usize = 4096;
+/// A single allocation bigger than the heap mapped at boot.
+const BIG_ALLOC: usize = 256 * 1024;
+
+/// heaptest — kernel heap allocator. Checks alignment, that freed blocks
+/// are merged back together, that the heap grows past its boot size, and
+/// that thousands of allocations of mixed sizes leave usage where it
+/// started without the mapped heap growing from one pass to the next.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== Heap Allocator Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    let (used_before, mapped_before) = heap_stats();
+    test_log!("heap: {} bytes used, {} KiB mapped", used_before, mapped_before / 1024);
+
+    // Alignment requests from Layout are honoured and cost nothing once freed
+    let mut aligned = true;
+    for align in [1, 8, 16, 64, 512, 4096] {
+        let layout = Layout::from_size_align(24, align).unwrap();
+        let ptr = unsafe { alloc(layout) };
+        aligned &= !ptr.is_null() && ptr as usize % align == 0;
+        if !ptr.is_null() {
+            unsafe { dealloc(ptr, layout); }
+        }
+    }
+    check!(pass, fail, "allocations aligned as requested", aligned);
+    check!(pass, fail, "aligned allocations fully freed", heap_stats().0 == used_before);
+
+    // Three neighbours freed middle-first: the gaps merge into one block again
+    let (blocks_before, _) = heap_fragments();
+    {
+        let a: Vec<u8> = Vec::with_capacity(256);
+        let b: Vec<u8> = Vec::with_capacity(256);
+        let c: Vec<u8> = Vec::with_capacity(256);
+        drop(b);
+        drop(a);
+        drop(c);
+    }
+    check!(pass, fail, "freed neighbours coalesced", heap_fragments().0 <= blocks_before);
+
+    // Bigger than the whole boot heap: only works if the heap grows
+    {
+        let big: Vec<u8> = alloc::vec![0xA5; BIG_ALLOC];
+        check!(pass, fail, "allocation larger than the boot heap", big.iter().all(|&b| b == 0xA5));
+        check!(pass, fail, "heap grew past its boot size", heap_stats().1 > crate::allocator::HEAP_SIZE);
+    }
+    check!(pass, fail, "large allocation freed", heap_stats().0 == used_before);
+
+    let allocated_before = crate::memory::stats().heap_allocated;
+    let (intact, peak) = stress_pass();
+    check!(pass, fail, "stress pass 1: contents intact", intact);
+    let stats = crate::memory::stats();
+    check!(pass, fail, "stress pass 1 counted in memory::stats", stats.heap_allocated - allocated_before >= (peak - used_before) as u64);
+    check!(pass, fail, "heap allocated - freed matches usage", stats.heap_allocated - stats.heap_freed == stats.heap_used as u64);
+    let after_first = heap_stats();
+    check!(pass, fail, "stress pass 1: usage back to baseline", after_first.0 == used_before);
+
+    let (intact, _) = stress_pass();
+    check!(pass, fail, "stress pass 2: contents intact", intact);
+    let after_second = heap_stats();
+    check!(pass, fail, "stress pass 2: usage back to baseline", after_second.0 == used_before);
+    test_log!("peak {} KiB used, {} KiB mapped after pass 1, {} KiB after pass 2",
+        peak / 1024, after_first.1 / 1024, after_second.1 / 1024);
+    check!(pass, fail, "freed memory reused: heap did not grow in pass 2", after_second.1 == after_first.1);
+
+    let (blocks, largest) = heap_fragments();
+    test_log!("free list: {} block(s), largest {} KiB", blocks, largest / 1024);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// Allocate and free `STRESS_ROUNDS` Vecs of pseudo-random sizes, up to
+/// `LIVE_SLOTS` alive at a time, each filled with a pattern that is checked
+/// before it is freed. Returns whether every pattern survived and the peak
+/// heap usage seen.
+fn stress_pass() -> (bool, usize) {
+    let mut slots: Vec<Option<Vec<u8>>> = (0..LIVE_SLOTS).map(|_| None).collect();
+    let mut seed: u32 = 0x2545_F491;
+    let mut intact = true;
+    let mut peak = 0;
+
+    for round in 0..STRESS_ROUNDS {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        let slot = (seed >> 8) as usize % LIVE_SLOTS;
+        if let Some(v) = slots[slot].take() {
+            intact &= v.iter().all(|&b| b == v.len() as u8);
+        }
+        // Mostly small, with the odd large one to split and merge big blocks
+        let len = match (seed >> 20) % 8 {
+            0 => 1 + (seed as usize >> 3) % MAX_VEC,
+            _ => 1 + (seed as usize >> 3) % 128,
+        };
+        slots[slot] = Some(alloc::vec![len as u8; len]);
+        if round % 64 == 0 {
+            peak = peak.max(heap_stats().0);
+        }
+    }
+
+    for v in slots.iter_mut().filter_map(Option::take) {
+        intact &= v.iter().all(|&b| b == v.len() as u8);
+    }
+    drop(slots);
+    (intact, peak)
+}
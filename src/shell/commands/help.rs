@@ -3,27 +3,78 @@ use crate::println;
 pub fn run(_args: &str) {
     println!("AtomicOS Shell - Available commands:");
     println!("");
-    println!("  echo <text>       Print text to terminal");
-    println!("  ls [dir]          List files in directory");
+    println!("  echo [-e] <text>  Print text to terminal (-e: \\n, \\e escapes)");
+    println!("  ls [-la] [dir]    List files (-l long, -a dotfiles)");
     println!("  cat <file>        Show file contents");
     println!("  clear             Clear the screen");
-    println!("  cd [dir]          Change directory");
+    println!("  cd [dir|-|~]      Change directory");
     println!("  help              Show this help message");
     println!("  date              Show current date/time (RTC)");
     println!("  whoami            Show current user");
+    println!("  su/login [user]   Switch current user");
+    println!("  hostname [name]   Show or set the hostname");
     println!("  pwd               Show working directory");
     println!("  uptime            Show time since boot");
     println!("  version           Show kernel version");
     println!("  neofetch          Show system info with logo");
+    println!("  cmd >f, >>f, <f   Redirect output (overwrite/append) or input");
+    println!("  cmd1 | cmd2       Pipe one command's output into the next");
     println!("");
     println!("  ps                List active processes");
-    println!("  kill <pid>        Terminate a process");
-    println!("  mkdir <name>      Create a directory");
+    println!("  top               Live process monitor (q to quit)");
+    println!("  free              Show physical memory and heap usage");
+    println!("  meminfo           Show heap, frame and slab cache usage");
+    println!("  kill [-9] <pid>   Send SIGTERM (or SIGKILL) to a process");
+    println!("  sleep <secs>      Block the shell, letting tasks run");
+    println!("  yield             Let the next ready task run");
+    println!("  mkdir [-p] <dir>  Create a directory (-p: and its parents)");
+    println!("  touch <file>      Create a file or update its time");
     println!("  rm <path>         Remove a file or directory");
     println!("  cp <src> <dst>    Copy a file");
-    println!("  mv <src> <dst>    Move/rename a file");
+    println!("  mv <src> <dst>    Move/rename a file or directory");
+    println!("  ln -s <tgt> <lnk> Create a symbolic link");
+    println!("  readlink <path>   Show a symbolic link's target");
+    println!("  chmod <mode> <f>  Change a file's permission bits");
     println!("  catbin <addr>     Hex dump memory at address");
+    println!("  sum <file>        Print a file's CRC-32 and size");
     println!("  objdump           Inspect kernel ELF info");
+    println!("  regs              Show control registers and CPU state");
     println!("  shellscript <..>  Run commands separated by ;");
-    println!("  log [n]           Show last n kernel log entries");
+    println!("  exec <elf> [args] Run a program and show its exit status");
+    println!("  exectest          Run the exec/argv integration tests");
+    println!("  log [n] [level]   Show last n kernel log entries (/dev/kmsg)");
+    println!("  dmesg [-w|-e]     Print the kernel log (-w warnings, -e errors)");
+    println!("  fsck              Check the FAT32 volume for errors");
+    println!("  fattest           Run the FAT32 name, BPB and disk I/O tests");
+    println!("  fatimgtest        Run the FAT32 tests on an in-memory image");
+    println!("  isotest           Run the ISO9660 driver tests (image + /cdrom)");
+    println!("  ttytest           Run the blocking console read tests");
+    println!("  preempttest       Run the timer preemption test");
+    println!("  priotest          Run the scheduler priority tests (levels, aging)");
+    println!("  jointest          Run the task join tests (ended, running, detached)");
+    println!("  spsctest          Run the interrupt-to-task ring buffer stress test");
+    println!("  stacktest         Run the kernel stack guard page tests (overflow, reuse)");
+    println!("  redirtest         Run the shell redirection tests (>, >>, <)");
+    println!("  pipetest          Run the shell pipeline tests (pipes between stages)");
+    println!("  frametest         Run the frame recycling tests (free list, failed fork, fork/exit)");
+    println!("  heaptest          Run the heap allocator tests (alignment, growth, stress)");
+    println!("  slabtest          Run the slab cache tests (recycling, heap vs slab cost)");
+    println!("  rtctest           Run the RTC tests (decoding, clock advancing)");
+    println!("  pittest           Run the PIT tests (rounding, limits, measured rate)");
+    println!("  mousetest         Run the mouse cursor tests (movement, clicks, scrolling)");
+    println!("  scrolltest        Run the VGA scrollback tests");
+    println!("  ansitest          Run the ANSI escape sequence tests");
+    println!("  locktest          Run the lock priority-inheritance test");
+    println!("  fputest           Run the FPU/SSE context switch and syscall test");
+    println!("  diskinfo          Show FAT32 volume label and usage");
+    println!("  lsblk             List ATA/ATAPI devices on both buses");
+    println!("  sync              Flush filesystems to disk");
+    println!("  dd read|write ..  Raw sector dump / write (ATA)");
+    println!("  umount [path]     Unmount a filesystem / list mounts");
+    println!("  mount [-r] [path] Re-attach a boot filesystem / list mounts");
+    println!("  remount [-r|-w] p Make a mount read-only / read-write");
+    println!("  reboot, poweroff  Flush disks, then reset / power off");
+    println!("  shutdown [-r|-h]  Unmount, then power off / reboot / halt");
+    println!("  panic [mode]      Crash the kernel; mode: locked, assert");
+    println!("  watchdog [..]     Show/tune hung-task watchdog limits");
 }
@@ -0,0 +1,19 @@
+use crate::println;
+
+/// hostname [name] — show or change the system hostname (/etc/hostname).
+pub fn run(args: &str) {
+    let name = args.trim();
+    if name.is_empty() {
+        println!("{}", crate::system_info::hostname());
+        return;
+    }
+
+    if name.contains(char::is_whitespace) || name.contains('/') {
+        println!("hostname: invalid hostname: {}", name);
+        return;
+    }
+
+    if let Err(e) = crate::system_info::set_hostname(name) {
+        println!("hostname: {}", e);
+    }
+}
@@ -0,0 +1,93 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+use spin::Mutex;
+use crate::scheduler::{self, join::{self, JoinError}, ProcessId};
+use crate::syscalls::wait;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// How long the slow task runs, so a joiner is sure to find it alive.
+const SLOW_TICKS: u64 = 5;
+
+/// Task the helper joins.
+static TARGET: AtomicU64 = AtomicU64::new(0);
+/// What the helper's `join` returned.
+static HELPER_RESULT: Mutex<Option<Result<u64, JoinError>>> = Mutex::new(None);
+
+/// jointest — joining kernel tasks: a task that already ended, one still
+/// running (claimed by a helper task first, so the shell's join is refused),
+/// a task that is not joinable, joining oneself, and detaching.
+pub fn run(_args: &str) {
+    test_log!("=== Task Join Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    // Ended before anyone joined: the zombie waits for the join
+    let quick = join::spawn_joinable(task_exit_3, "join_quick");
+    scheduler::sleep_ticks(3);
+    check!(pass, fail, "joinable task kept as a zombie", is_zombie(quick));
+    let res = join::join(quick);
+    check!(pass, fail, "join of an ended task returns its status",
+        res.is_ok_and(|s| wait::exited(s) && wait::exit_code(s) == 3));
+    check!(pass, fail, "joined task reaped", join::join(quick) == Err(JoinError::NoSuchTask));
+
+    // Still running, and claimed by the helper before the shell gets to it
+    *HELPER_RESULT.lock() = None;
+    let slow = join::spawn_joinable(task_slow, "join_slow");
+    TARGET.store(slow.0, Ordering::Relaxed);
+    scheduler::spawn(task_helper, "join_helper");
+    scheduler::sleep_ticks(1);
+    check!(pass, fail, "second joiner refused", join::join(slow) == Err(JoinError::AlreadyJoined));
+    let mut result = None;
+    for _ in 0..SLOW_TICKS * 4 {
+        result = *HELPER_RESULT.lock();
+        if result.is_some() {
+            break;
+        }
+        scheduler::sleep_ticks(1);
+    }
+    check!(pass, fail, "join blocks until the task exits",
+        result.is_some_and(|r| r.is_ok_and(|s| wait::exit_code(s) == 7)));
+    check!(pass, fail, "only the first joiner reaped it", join::join(slow) == Err(JoinError::NoSuchTask));
+
+    let plain = scheduler::spawn(task_exit_3, "join_plain");
+    check!(pass, fail, "task from spawn is not joinable", join::join(plain) == Err(JoinError::NotJoinable));
+    check!(pass, fail, "joining oneself refused", join::join(scheduler::current_pid()) == Err(JoinError::Deadlock));
+    check!(pass, fail, "nonexistent task", join::join(ProcessId(u64::MAX)) == Err(JoinError::NoSuchTask));
+
+    // Detached after it ended: init reaps it like any spawned task
+    let detached = join::spawn_joinable(task_exit_3, "join_detached");
+    scheduler::sleep_ticks(3);
+    check!(pass, fail, "detach accepted", join::detach(detached).is_ok());
+    scheduler::sleep_ticks(3);
+    check!(pass, fail, "detached zombie reaped by init", !exists(detached));
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+fn is_zombie(pid: ProcessId) -> bool {
+    scheduler::SCHEDULER.lock().ready_queue.iter()
+        .any(|p| p.pid == pid && p.state == scheduler::ProcessState::Zombie)
+}
+
+fn exists(pid: ProcessId) -> bool {
+    scheduler::SCHEDULER.lock().ready_queue.iter().any(|p| p.pid == pid)
+}
+
+fn task_exit_3() {
+    scheduler::exit_current(3);
+}
+
+fn task_slow() {
+    scheduler::sleep_ticks(SLOW_TICKS);
+    scheduler::exit_current(7);
+}
+
+/// Join `TARGET` and record what happened.
+fn task_helper() {
+    let res = join::join(ProcessId(TARGET.load(Ordering::Relaxed)));
+    *HELPER_RESULT.lock() = Some(res);
+    scheduler::exit_current(0);
+}
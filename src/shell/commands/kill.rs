@@ -1,29 +1,39 @@
 use crate::println;
+use crate::scheduler::signal::{SIGKILL, SIGTERM};
 
-/// kill <pid> — terminate a task via the scheduler.
+/// kill [-9|-15|-KILL|-TERM] <pid> — send a signal (SIGTERM by default) to
+/// a user process. It is acted on when the process next returns to user mode.
 pub fn run(args: &str) {
-    let pid_str = args.trim();
-    if pid_str.is_empty() {
-        println!("kill: usage: kill <pid>");
+    let parts: alloc::vec::Vec<&str> = args.split_whitespace().collect();
+    let (sig, pid_str) = match parts.as_slice() {
+        [pid] => (Some(SIGTERM), *pid),
+        [flag, pid] if flag.starts_with('-') => (parse_signal(&flag[1..]), *pid),
+        _ => {
+            println!("kill: usage: kill [-9|-15|-KILL|-TERM] <pid>");
+            return;
+        }
+    };
+    let Some(sig) = sig else {
+        println!("kill: unknown signal: {}", parts[0]);
         return;
-    }
+    };
 
     let pid: u64 = match pid_str.parse() {
         Ok(v) => v,
         Err(_) => { println!("kill: invalid pid: {}", pid_str); return; }
     };
 
-    if pid == 0 {
-        println!("kill: cannot kill kernel (pid 0)");
-        return;
+    match crate::syscalls::sys_kill(pid, sig) {
+        Ok(()) => println!("Sent signal {} to pid {}", sig, pid),
+        Err(e) => println!("kill: ({}): {}", pid, e),
     }
+}
 
-    // Remove task from scheduler ready queue
-    let mut sched = crate::scheduler::SCHEDULER.lock();
-    if let Some(pos) = sched.ready_queue.iter().position(|t| t.pid.0 == pid) {
-        let task = sched.ready_queue.remove(pos).unwrap();
-        println!("Terminated task '{}' (pid {})", task.name, pid);
-    } else {
-        println!("kill: no such process: {}", pid);
+/// A signal given by number or name, with or without the SIG prefix.
+fn parse_signal(name: &str) -> Option<u32> {
+    match name.trim_start_matches("SIG") {
+        "9" | "KILL" => Some(SIGKILL),
+        "15" | "TERM" => Some(SIGTERM),
+        _ => None,
     }
 }
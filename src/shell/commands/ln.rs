@@ -0,0 +1,25 @@
+use crate::println;
+
+/// ln -s <target> <link> — create a symbolic link via VFS.
+pub fn run(args: &str) {
+    let parts: alloc::vec::Vec<&str> = args.split_whitespace().collect();
+    let (target, link) = match parts.as_slice() {
+        ["-s", target, link] => (*target, *link),
+        [_, _] => {
+            println!("ln: hard links are not supported, use ln -s");
+            return;
+        }
+        _ => {
+            println!("Usage: ln -s <target> <link>");
+            return;
+        }
+    };
+
+    // The target is stored verbatim; only the link location is resolved
+    let full = crate::shell::state::resolve_path(link);
+    let mut vfs = crate::fs::VFS.lock();
+    match vfs.symlink(target, &full) {
+        Ok(_) => println!("{} -> {}", link, target),
+        Err(e) => println!("ln: {}: {}", link, e),
+    }
+}
@@ -0,0 +1,127 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+use crate::scheduler::lock::InheritMutex;
+use crate::shell::commands::uptime::TICKS;
+use crate::shell::commands::testutil::test_log;
+
+/// Quanta of CPU time the holder needs inside its critical section.
+const HOLD_QUANTA: u64 = 6;
+/// CPU-bound tasks competing with the holder for the CPU.
+const HOGS: usize = 3;
+
+static TEST_LOCK: InheritMutex<u64> = InheritMutex::new(0);
+static HOLDER_READY: AtomicBool = AtomicBool::new(false);
+static STOP_HOGS: AtomicBool = AtomicBool::new(false);
+
+/// locktest — priority-inversion scenario for `InheritMutex`.
+/// A holder task is preempted mid critical section behind several CPU hogs
+/// while the shell waits for the lock. The wait is timed once with a plain
+/// spin (try_lock loop) and once with `lock()`, which donates the waiter's
+/// time to the holder; the latter must finish sooner.
+pub fn run(_args: &str) {
+    test_log!("=== Lock Inheritance Test ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    *TEST_LOCK.lock() = 0;
+    STOP_HOGS.store(false, Ordering::Relaxed);
+    for _ in 0..HOGS {
+        crate::scheduler::spawn(task_hog, "hog");
+    }
+
+    let (spin_ticks, spin_owner_ok) = contend(false);
+    let (inherit_ticks, inherit_owner_ok) = contend(true);
+    test_log!("plain spin waited {} ticks, inheritance waited {} ticks", spin_ticks, inherit_ticks);
+
+    // Test 1: the holder is the recorded owner while it is descheduled
+    if spin_owner_ok && inherit_owner_ok {
+        test_log!("[PASS] holder recorded as lock owner"); pass += 1;
+    } else {
+        test_log!("[FAIL] lock owner is not the holder task"); fail += 1;
+    }
+
+    // Test 2: the holder got through both critical sections
+    if *TEST_LOCK.lock() == 2 * HOLD_QUANTA {
+        test_log!("[PASS] both critical sections completed"); pass += 1;
+    } else {
+        test_log!("[FAIL] holder did not finish its critical sections"); fail += 1;
+    }
+
+    // Test 3: donating to the holder beats spinning behind the hogs
+    if inherit_ticks < spin_ticks {
+        test_log!("[PASS] holder ran on the waiter's time"); pass += 1;
+    } else {
+        test_log!("[FAIL] no speedup from inheritance"); fail += 1;
+    }
+
+    // Test 4: releasing clears the owner
+    if TEST_LOCK.owner().is_none() {
+        test_log!("[PASS] owner cleared on release"); pass += 1;
+    } else {
+        test_log!("[FAIL] owner still set after release"); fail += 1;
+    }
+
+    STOP_HOGS.store(true, Ordering::Relaxed);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// Spawn a holder, wait until it owns `TEST_LOCK`, then time how many ticks
+/// pass before the shell gets the lock. Also reports whether the holder was
+/// recorded as the owner.
+fn contend(inherit: bool) -> (u64, bool) {
+    HOLDER_READY.store(false, Ordering::Relaxed);
+    let holder = crate::scheduler::spawn(task_holder, "holder");
+    while !HOLDER_READY.load(Ordering::Acquire) {
+        crate::scheduler::yield_now();
+    }
+    let owner_ok = TEST_LOCK.owner() == Some(holder);
+
+    let start = TICKS.load(Ordering::Relaxed);
+    if inherit {
+        drop(TEST_LOCK.lock());
+    } else {
+        loop {
+            if let Some(guard) = TEST_LOCK.try_lock() {
+                drop(guard);
+                break;
+            }
+            core::hint::spin_loop();
+        }
+    }
+    (TICKS.load(Ordering::Relaxed) - start, owner_ok)
+}
+
+/// Holds `TEST_LOCK` until it has been given HOLD_QUANTA timer ticks of CPU.
+/// It only sees the tick counter change while it is running, so the count
+/// tracks CPU time received.
+fn task_holder() {
+    let mut guard = TEST_LOCK.lock();
+    HOLDER_READY.store(true, Ordering::Release);
+
+    let mut seen = TICKS.load(Ordering::Relaxed);
+    let mut quanta = 0;
+    while quanta < HOLD_QUANTA {
+        let now = TICKS.load(Ordering::Relaxed);
+        if now != seen {
+            seen = now;
+            quanta += 1;
+            *guard += 1;
+        }
+        core::hint::spin_loop();
+    }
+
+    drop(guard);
+    crate::scheduler::exit_current(0);
+}
+
+/// Burns CPU without ever yielding voluntarily, like a compute-bound task.
+fn task_hog() {
+    while !STOP_HOGS.load(Ordering::Relaxed) {
+        core::hint::spin_loop();
+    }
+    crate::scheduler::exit_current(0);
+}
@@ -1,19 +1,47 @@
 use crate::println;
-use super::super::state;
+use crate::klog::{self, Level};
 
-/// log — display the kernel command log buffer.
+/// log [n] [info|warn|error] — show the last n kernel log entries,
+/// optionally only those at or above a level.
 pub fn run(args: &str) {
-    let klog = state::KLOG.lock();
+    let mut count = usize::MAX;
+    let mut min_level = Level::Info;
+    for arg in args.split_whitespace() {
+        if let Ok(n) = arg.parse::<usize>() {
+            count = n;
+        } else if let Some(level) = Level::parse(arg) {
+            min_level = level;
+        } else {
+            println!("Usage: log [n] [info|warn|error]");
+            return;
+        }
+    }
+    print(count, min_level);
+}
 
-    if klog.entries.is_empty() {
+/// Print the last `count` entries at or above `min_level`, oldest first.
+pub fn print(count: usize, min_level: Level) {
+    // Walk backwards to find the first of the last `count` matching entries
+    let (first, next) = klog::seq_range();
+    let mut start = next;
+    let mut matched = 0;
+    while start > first && matched < count {
+        start -= 1;
+        if klog::get(start).is_some_and(|e| e.level >= min_level) {
+            matched += 1;
+        }
+    }
+
+    if matched == 0 {
         println!("(no log entries)");
         return;
     }
 
-    let count = args.trim().parse::<usize>().unwrap_or(klog.entries.len());
-    let start = if klog.entries.len() > count { klog.entries.len() - count } else { 0 };
-
-    for entry in &klog.entries[start..] {
-        println!("  {}", entry);
+    for seq in start..next {
+        if let Some(entry) = klog::get(seq) {
+            if entry.level >= min_level {
+                println!("  {}", entry);
+            }
+        }
     }
 }
@@ -1,30 +1,129 @@
-use crate::println;
+use crate::{print, println};
+use crate::fs::dentry::DirEntry;
+use crate::fs::error::FsError;
 use crate::fs::inode::FileType;
+use crate::vga::{self, Color};
 
-/// ls [dir] — list entries using the VFS.
+/// ls [-l] [-a] [dir] — list entries using the VFS, sorted by name.
+/// -l: long listing (type and permissions, size, modification time where
+/// the filesystem records one, name); -a: include dotfiles.
 pub fn run(args: &str) {
-    let target = args.trim();
+    let mut long = false;
+    let mut all = false;
+    let mut target = "";
+    for arg in args.split_whitespace() {
+        match arg.strip_prefix('-') {
+            Some(flags) if !flags.is_empty() => {
+                for flag in flags.chars() {
+                    match flag {
+                        'l' => long = true,
+                        'a' => all = true,
+                        _ => {
+                            println!("ls: unknown option '-{}'", flag);
+                            println!("Usage: ls [-l] [-a] [dir]");
+                            return;
+                        }
+                    }
+                }
+            }
+            _ => target = arg,
+        }
+    }
+
     let dir = if target.is_empty() {
-        crate::shell::state::CWD.lock().clone()
+        crate::shell::state::cwd()
     } else {
         crate::shell::state::resolve_path(target)
     };
 
     let vfs = crate::fs::VFS.lock();
+    if let Ok(inode) = vfs.lookup(&dir) {
+        if inode.file_type != FileType::Directory {
+            println!("ls: {}: {}", dir, FsError::NotADirectory);
+            return;
+        }
+    }
     match vfs.readdir(&dir) {
         Ok(entries) => {
+            let mut entries: alloc::vec::Vec<_> = entries.into_iter()
+                .filter(|e| all || !e.name.starts_with('.'))
+                .collect();
             if entries.is_empty() {
                 println!("(empty)");
-            } else {
-                for e in entries {
-                    if e.inode.file_type == FileType::Directory {
-                        println!("  {}/", e.name);
-                    } else {
-                        println!("  {}  ({}B)", e.name, e.inode.size);
+                return;
+            }
+            entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+            let width = entries.iter().map(|e| digits(e.inode.size)).max().unwrap_or(1);
+            for e in &entries {
+                let path = alloc::format!("{}/{}", dir.trim_end_matches('/'), e.name);
+                let link_target = if e.inode.file_type == FileType::Symlink {
+                    Some(vfs.readlink(&path).unwrap_or_default())
+                } else {
+                    None
+                };
+
+                if long {
+                    let kind = match e.inode.file_type {
+                        FileType::Directory => 'd',
+                        FileType::Symlink => 'l',
+                        FileType::File => '-',
+                    };
+                    print!("  {}{}  {:>width$}  ", kind, permissions(e.inode.mode), e.inode.size, width = width);
+                    // Blank where the filesystem keeps no times (RAMFS, ISO9660)
+                    match vfs.modified(&path) {
+                        Ok(t) => print!("{:04}-{:02}-{:02} {:02}:{:02}  ", t.year, t.month, t.day, t.hour, t.minute),
+                        Err(_) => print!("{:18}", ""),
                     }
+                } else {
+                    print!("  ");
+                }
+
+                print_name(e);
+                match (link_target, long) {
+                    (Some(t), _) => println!(" -> {}", t),
+                    (None, false) if e.inode.file_type == FileType::File => println!("  ({}B)", e.inode.size),
+                    (None, _) => println!(),
                 }
             }
         },
         Err(e) => println!("ls: {}: {}", dir, e),
     }
 }
+
+/// Print an entry's name, colored by type; directories get a trailing '/'.
+fn print_name(e: &DirEntry) {
+    match e.inode.file_type {
+        FileType::Directory => {
+            vga::set_color(Color::LightBlue, Color::Black);
+            print!("{}/", e.name);
+            vga::reset_color();
+        }
+        FileType::Symlink => {
+            vga::set_color(Color::LightCyan, Color::Black);
+            print!("{}", e.name);
+            vga::reset_color();
+        }
+        FileType::File => print!("{}", e.name),
+    }
+}
+
+/// Permission bits as `rwxr-xr-x`.
+fn permissions(mode: u16) -> alloc::string::String {
+    (0..9).map(|i| {
+        if mode & (0o400 >> i) == 0 {
+            '-'
+        } else {
+            ['r', 'w', 'x'][i % 3]
+        }
+    }).collect()
+}
+
+fn digits(mut n: usize) -> usize {
+    let mut d = 1;
+    while n >= 10 {
+        n /= 10;
+        d += 1;
+    }
+    d
+}
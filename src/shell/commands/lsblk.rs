@@ -0,0 +1,28 @@
+use crate::println;
+use crate::drivers::ata::pio::{AtapiInfo, DeviceClass};
+
+/// lsblk — list the ATA bus positions and what was detected on each.
+pub fn run(_args: &str) {
+    println!("  NAME  CLASS  DETAILS");
+    for (name, dev) in crate::drivers::ata::devices() {
+        let dev = dev.lock();
+        match (dev.class, &dev.atapi) {
+            (DeviceClass::Atapi, Some(info)) => {
+                let kind = if info.device_type == AtapiInfo::TYPE_CDROM { "CD/DVD" } else { "packet device" };
+                println!("  {:4}  {:5}  {}{}, {}", name, dev.class, kind,
+                    if info.removable { " (removable)" } else { "" },
+                    if info.model.is_empty() { "unnamed" } else { &info.model });
+            }
+            (DeviceClass::Ata, _) => {
+                let mounted = if name == "hda" && crate::fs::fat32().is_some() { ", FAT32 at /disk" } else { "" };
+                match &dev.disk {
+                    Some(disk) => println!("  {:4}  {:5}  disk, {} MiB, {}{}", name, dev.class,
+                        disk.capacity() / (1024 * 1024),
+                        if disk.model.is_empty() { "unnamed" } else { &disk.model }, mounted),
+                    None => println!("  {:4}  {:5}  disk{}", name, dev.class, mounted),
+                }
+            }
+            _ => println!("  {:4}  {:5}  -", name, dev.class),
+        }
+    }
+}
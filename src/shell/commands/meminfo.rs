@@ -0,0 +1,25 @@
+use crate::println;
+
+/// meminfo — kernel heap, physical frames, and per-cache slab counters.
+pub fn run(_args: &str) {
+    let (heap_used, heap_total) = crate::allocator::heap_stats();
+    let (frames_used, frames_total, frames_recycled) = {
+        let frames = crate::memory::FRAME_ALLOCATOR.lock();
+        (frames.used_frames(), frames.total_frames(), frames.recycled_frames())
+    };
+
+    println!("Heap:    {} / {} KiB used ({} KiB max)", heap_used / 1024, heap_total / 1024, crate::allocator::HEAP_MAX_SIZE / 1024);
+    println!("Frames:  {} / {} used ({} KiB free, {} recycled)",
+        frames_used, frames_total, (frames_total - frames_used) * 4, frames_recycled);
+
+    let caches = crate::allocator::slab::slab_stats();
+    if caches.is_empty() {
+        println!("Slabs:   (no caches in use)");
+        return;
+    }
+    println!();
+    println!("  CACHE           OBJSIZE  SLABS  IN USE   FREE");
+    for c in &caches {
+        println!("  {:14}  {:>7}  {:>5}  {:>6}  {:>5}", c.name, c.object_size, c.slabs, c.in_use, c.free);
+    }
+}
@@ -1,17 +1,63 @@
 use crate::println;
+use crate::fs::error::FsError;
+use crate::fs::inode::FileType;
 
-/// mkdir <path> — create a directory via VFS.
+/// mkdir [-p] <path>... — create directories via VFS. With -p, missing
+/// parent directories are created in turn, and directories that already
+/// exist are not an error.
 pub fn run(args: &str) {
-    let path = args.trim();
-    if path.is_empty() {
+    let mut parents = false;
+    let mut paths = alloc::vec::Vec::new();
+    for arg in args.split_whitespace() {
+        match arg {
+            "-p" => parents = true,
+            _ if arg.starts_with('-') => {
+                println!("mkdir: unknown option '{}'", arg);
+                println!("Usage: mkdir [-p] <path>...");
+                return;
+            }
+            _ => paths.push(arg),
+        }
+    }
+    if paths.is_empty() {
         println!("mkdir: missing operand");
         return;
     }
 
-    let full = crate::shell::state::resolve_path(path);
+    for path in paths {
+        let full = crate::shell::state::resolve_path(path);
+        if parents {
+            make_parents(&full);
+            continue;
+        }
+        let mut vfs = crate::fs::VFS.lock();
+        match vfs.mkdir(&full) {
+            Ok(_) => println!("Created directory: {}", path),
+            Err(e) => println!("mkdir: {}: {}", path, e),
+        }
+    }
+}
+
+/// Create each missing component of absolute path `full`, stopping at the
+/// first one that can't be created.
+fn make_parents(full: &str) {
     let mut vfs = crate::fs::VFS.lock();
-    match vfs.mkdir(&full) {
-        Ok(_) => println!("Created directory: {}", path),
-        Err(e) => println!("mkdir: {}: {}", path, e),
+    let mut prefix = alloc::string::String::new();
+    for component in full.split('/').filter(|c| !c.is_empty()) {
+        prefix.push('/');
+        prefix.push_str(component);
+        let made = match vfs.lookup(&prefix) {
+            Ok(inode) if inode.file_type == FileType::Directory => continue,
+            Ok(_) => Err(FsError::NotADirectory),
+            Err(FsError::NotFound) => vfs.mkdir(&prefix).map(|_| ()),
+            Err(e) => Err(e),
+        };
+        match made {
+            Ok(()) => println!("Created directory: {}", prefix),
+            Err(e) => {
+                println!("mkdir: {}: {}", prefix, e);
+                return;
+            }
+        }
     }
 }
@@ -20,6 +20,7 @@ pub mod catbin;
 pub mod objdump;
 pub mod shellscript;
 pub mod log;
+pub mod dmesg;
 pub mod spawn;
 pub mod yield_cmd;
 pub mod touch;
@@ -27,3 +28,47 @@ pub mod vfstest;
 pub mod write;
 pub mod atatest;
 pub mod exec;
+pub mod watchdog;
+pub mod ln;
+pub mod chmod;
+pub mod readlink;
+pub mod hostname;
+pub mod su;
+pub mod fsck;
+pub mod diskinfo;
+pub mod panic;
+pub mod exectest;
+pub mod testutil;
+pub mod sleep;
+pub mod fattest;
+pub mod sync;
+pub mod umount;
+pub mod mount;
+pub mod reboot;
+pub mod dd;
+pub mod locktest;
+pub mod regs;
+pub mod sum;
+pub mod fputest;
+pub mod top;
+pub mod free;
+pub mod meminfo;
+pub mod lsblk;
+pub mod isotest;
+pub mod ttytest;
+pub mod preempttest;
+pub mod frametest;
+pub mod scrolltest;
+pub mod ansitest;
+pub mod heaptest;
+pub mod slabtest;
+pub mod rtctest;
+pub mod fatimgtest;
+pub mod pittest;
+pub mod mousetest;
+pub mod pipetest;
+pub mod priotest;
+pub mod jointest;
+pub mod spsctest;
+pub mod stacktest;
+pub mod redirtest;
@@ -0,0 +1,71 @@
+use crate::println;
+
+/// mount [-r] [path] — list mounts, or re-attach one of the boot filesystems
+/// (/tmp, /dev, /disk, /cdrom) after `umount`. -r mounts it read-only.
+pub fn run(args: &str) {
+    let (read_only, target) = match parse(args) {
+        Some((flag, target)) if flag != Some(false) => (flag == Some(true), target),
+        _ => {
+            println!("Usage: mount [-r] [path]");
+            return;
+        }
+    };
+
+    if target.is_empty() {
+        for m in crate::fs::VFS.lock().mounts().iter().rev() {
+            println!("{} on {} ({})", m.fs_name, m.path, if m.read_only { "ro" } else { "rw" });
+        }
+        return;
+    }
+
+    let path = crate::shell::state::resolve_path(target);
+    let mut vfs = crate::fs::VFS.lock();
+    if vfs.mounts().iter().any(|m| m.path == path) {
+        println!("mount: {}: already mounted (use remount)", path);
+        return;
+    }
+    match crate::fs::standard_filesystem(&path) {
+        Some(fs) => vfs.mount_with(&path, fs, read_only),
+        None => println!("mount: {}: no filesystem for this mount point", path),
+    }
+}
+
+/// remount [-r|-w] <path> — make a mount read-only (-r) or writable (-w).
+/// Without a flag the current setting is toggled.
+pub fn remount(args: &str) {
+    let (flag, target) = match parse(args) {
+        Some((flag, target)) if !target.is_empty() => (flag, target),
+        _ => {
+            println!("Usage: remount [-r|-w] <path>");
+            return;
+        }
+    };
+
+    let path = crate::shell::state::resolve_path(target);
+    let mut vfs = crate::fs::VFS.lock();
+    let Some(current) = vfs.mounts().into_iter().find(|m| m.path == path).map(|m| m.read_only) else {
+        println!("remount: {}: not mounted", path);
+        return;
+    };
+    let read_only = flag.unwrap_or(!current);
+    match vfs.remount(&path, read_only) {
+        Ok(()) => println!("{} is now {}", path, if read_only { "read-only" } else { "read-write" }),
+        Err(e) => println!("remount: {}: {}", path, e),
+    }
+}
+
+/// Split `[-r|-w] [path]` into (Some(true) for -r / Some(false) for -w, path).
+/// None on an unknown option or extra arguments.
+fn parse(args: &str) -> Option<(Option<bool>, &str)> {
+    let mut flag = None;
+    let mut target = "";
+    for arg in args.split_whitespace() {
+        match arg {
+            "-r" => flag = Some(true),
+            "-w" => flag = Some(false),
+            _ if arg.starts_with('-') || !target.is_empty() => return None,
+            _ => target = arg,
+        }
+    }
+    Some((flag, target))
+}
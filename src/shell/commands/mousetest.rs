@@ -0,0 +1,100 @@
+use crate::drivers::mouse::{cursor, MouseEvent, MouseState, MOUSE_BUFFER};
+use crate::vga;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// Lines printed to scroll the whole screen under the cursor.
+const SCREEN_ROWS: usize = 25;
+
+/// mousetest — text-mode mouse cursor. Feeds synthetic mouse events through
+/// the driver's queue and checks clamping to the screen, cell movement, the
+/// inverted cell under the cursor, click reporting, and that scrolling text
+/// carries no trace of the cursor along. Also decodes 3-byte and 4-byte
+/// (scroll wheel) PS/2 packets.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== Mouse Cursor Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    // Top left, whatever the starting point: far more than a screen's worth
+    for _ in 0..8 {
+        feed(-255, -255, false);
+    }
+    check!(pass, fail, "clamped at the top left", cursor::position() == (0, 0));
+    check!(pass, fail, "cursor drawn after movement", vga::mouse_position() == Some((0, 0)));
+
+    for _ in 0..8 {
+        feed(255, 255, false);
+    }
+    check!(pass, fail, "clamped at the bottom right", cursor::position() == (24, 79));
+
+    // Back to the corner, then 10 columns (8 counts each) and 5 rows (16 each)
+    for _ in 0..8 {
+        feed(-255, -255, false);
+    }
+    feed(80, 80, false);
+    check!(pass, fail, "moved by whole cells", cursor::position() == (5, 10));
+    feed(7, 15, false);
+    check!(pass, fail, "partial cell movement accumulates", cursor::position() == (5, 10));
+    feed(1, 1, false);
+    check!(pass, fail, "accumulated movement crosses a cell", cursor::position() == (6, 11));
+
+    let (row, col) = cursor::position();
+    let text = vga::attribute_at(row, col);
+    let shown = vga::shown_attribute_at(row, col);
+    check!(pass, fail, "cell under the cursor inverted", shown == ((text & 0x07) << 4 | text >> 4));
+
+    feed(0, 0, true);
+    feed(0, 0, true);
+    feed(0, 0, false);
+    check!(pass, fail, "click reported at the cursor cell",
+        cursor::last_click() == Some(cursor::Click { button: cursor::Button::Left, row, col }));
+
+    // Scroll the screen under the cursor: only its current cell is inverted
+    for i in 0..SCREEN_ROWS {
+        crate::println!("mousetest: scrolling line {}", i);
+    }
+    let clean_above = vga::shown_attribute_at(row - 1, col) == vga::attribute_at(row - 1, col);
+    let text = vga::attribute_at(row, col);
+    let still_drawn = vga::shown_attribute_at(row, col) == ((text & 0x07) << 4 | text >> 4);
+    check!(pass, fail, "scrolling leaves no stale inverted cell", clean_above);
+    check!(pass, fail, "cursor redrawn over the scrolled text", still_drawn);
+
+    cursor::hide();
+    let text = vga::attribute_at(row, col);
+    check!(pass, fail, "hidden cursor restores the cell", vga::shown_attribute_at(row, col) == text);
+    check!(pass, fail, "nothing drawn once hidden", vga::mouse_position().is_none());
+
+    // Packet decoding, on a private state so the real mouse is unaffected
+    let mut state = MouseState::new();
+    let event = feed_packet(&mut state, &[0x09, 5, 0]);
+    check!(pass, fail, "3-byte packet: left button, x = 5, no wheel",
+        event.is_some_and(|e| e.left_button && e.x_movement == 5 && e.y_movement == 0 && e.z_movement == 0));
+
+    state.set_wheel(true);
+    check!(pass, fail, "wheel mode waits for the 4th byte", feed_packet(&mut state, &[0x18, 0xFB, 0x02]).is_none());
+    let event = state.process_byte(0x0F);
+    check!(pass, fail, "4-byte packet: x = -5, y = -2, z = -1",
+        event.is_some_and(|e| e.x_movement == -5 && e.y_movement == -2 && e.z_movement == -1));
+    let mut z = |byte| feed_packet(&mut state, &[0x08, 0, 0, byte]).map(|e| e.z_movement);
+    check!(pass, fail, "z sign-extended from 4 bits", z(0x01) == Some(1) && z(0x08) == Some(-8) && z(0x07) == Some(7));
+    check!(pass, fail, "z ignores the upper nibble", z(0xF7) == Some(7));
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// Queue one mouse packet and apply it at once, as the timer tick would.
+fn feed(dx: i16, dy: i16, left: bool) {
+    let event = MouseEvent { left_button: left, right_button: false, middle_button: false, x_movement: dx, y_movement: dy, z_movement: 0 };
+    let _ = MOUSE_BUFFER.push(event);
+    cursor::pump();
+}
+
+/// Feed raw packet bytes to `state`; the event completed by the last byte, if any.
+fn feed_packet(state: &mut MouseState, bytes: &[u8]) -> Option<MouseEvent> {
+    bytes.iter().fold(None, |_, &b| state.process_byte(b))
+}
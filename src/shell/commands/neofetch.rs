@@ -19,14 +19,23 @@ const LOGO: &str = r#"
 "#;
 
 pub fn run(_args: &str) {
-    println!("        AtomicOS x86_64");
+    use crate::system_info as info;
+
+    let secs = info::uptime_secs();
+    let mem = crate::memory::stats();
+
+    println!("        {}@{}", info::current_user(), info::hostname());
     println!("  ========================");
     println!("{}", LOGO);
-    println!("  OS:       AtomicOS 0.2.0");
-    println!("  Arch:     x86_64");
+    println!("  OS:       {} {}", info::OS_NAME, info::VERSION);
+    println!("  Arch:     {}", info::ARCH);
     println!("  Kernel:   Rust (no_std)");
+    println!("  Uptime:   {}h {}m {}s", secs / 3600, (secs % 3600) / 60, secs % 60);
     println!("  Shell:    AtomicTTY v2");
-    println!("  Memory:   Heap (Bump Alloc)");
+    println!("  Tasks:    {}", crate::scheduler::list_tasks().len());
+    println!("  Memory:   {} / {} MiB", mem.used_bytes() / (1024 * 1024), mem.total_bytes / (1024 * 1024));
+    println!("  Heap:     {} / {} KiB", mem.heap_used / 1024, mem.heap_mapped / 1024);
+    println!("  Frames:   {} / {}", mem.frames_used, mem.frames_total);
     println!("  Drivers:  PS/2 KB + Mouse");
     println!("  Display:  VGA Text 80x25");
 }
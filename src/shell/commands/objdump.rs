@@ -1,24 +1,37 @@
 use crate::println;
+use crate::memory::layout;
 
-/// objdump — display info about the running kernel ELF binary.
+/// objdump — display the section layout and key symbols of the running kernel.
 pub fn run(_args: &str) {
     println!("kernel.bin: file format elf64-x86-64");
     println!("");
     println!("Sections:");
     println!("  Idx  Name          Size       VMA");
-    println!("    0  .boot         00001000   0000000000100000");
-    println!("    1  .text         00010000   0000000000101000");
-    println!("    2  .rodata       00004000   0000000000111000");
-    println!("    3  .data         00002000   0000000000115000");
-    println!("    4  .bss          00008000   0000000000117000");
+    for (idx, section) in layout::sections().iter().enumerate() {
+        println!("  {:>3}  {:<12}  {:08x}   {:016x}", idx, section.name, section.size(), section.start);
+    }
+
+    let image = layout::kernel_image();
+    println!("");
+    println!("Image: {:016x}-{:016x} ({} KiB)", image.start, image.end, image.size() / 1024);
+
     println!("");
     println!("SYMBOL TABLE (excerpt):");
-    println!("  0000000000100000  _start");
-    println!("  0000000000101000  vga::init");
-    println!("  0000000000101200  serial::init");
-    println!("  0000000000102000  interrupts::init");
-    println!("  0000000000103000  memory::init");
-    println!("  0000000000104000  shell::exec_command");
+    let symbols: [(&str, u64); 6] = [
+        ("_start",              crate::_start as *const () as u64),
+        ("vga::init",           crate::vga::init as *const () as u64),
+        ("serial::init",        crate::serial::init as *const () as u64),
+        ("interrupts::init",    crate::interrupts::init as *const () as u64),
+        ("memory::init",        crate::memory::init as *const () as u64),
+        ("shell::exec_command", crate::shell::exec_command as *const () as u64),
+    ];
+    for (name, addr) in symbols.iter() {
+        println!("  {:016x}  {}", addr, name);
+    }
+
     println!("");
-    println!("(simulated — will read real ELF headers with loader)");
+    println!("Memory regions:");
+    for region in [layout::boot_stack(), layout::heap(), layout::task_stacks()].iter() {
+        println!("  {:<6} {:016x}-{:016x} ({} KiB)", region.name, region.start, region.end, region.size() / 1024);
+    }
 }
@@ -0,0 +1,22 @@
+use crate::println;
+
+/// panic [locked|assert] — deliberately panic the kernel to test the panic path.
+/// With `locked`, the VGA and serial locks are held while panicking, which
+/// would deadlock a handler that used println!/log_error!. With `assert`, a
+/// `kassert!` fails while the scheduler lock is held.
+pub fn run(args: &str) {
+    match args.trim() {
+        "" => panic!("panic command invoked from shell"),
+        "locked" => {
+            let _vga = crate::vga::WRITER.lock();
+            let _serial = crate::serial::SERIAL1.lock();
+            panic!("panic command invoked while holding WRITER and SERIAL1");
+        }
+        "assert" => {
+            let sched = crate::scheduler::SCHEDULER.lock();
+            let tasks = sched.ready_queue.len();
+            crate::kassert!(tasks == usize::MAX, "panic command: {} ready tasks is not usize::MAX", tasks);
+        }
+        _ => println!("Usage: panic [locked|assert]"),
+    }
+}
@@ -0,0 +1,187 @@
+use alloc::sync::Arc;
+use spin::Mutex;
+use crate::fs::fd::FileType;
+use crate::fs::pipe::{Pipe, PipeError};
+use crate::scheduler::{self, join, signal, ProcessState};
+use crate::shell::exec_command;
+use crate::syscalls::wait;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// Tiny user program that reads one byte from stdin (tests/test_elf/test_stdin.S).
+static TEST_STDIN_ELF: &[u8] = include_bytes!("../../../tests/test_elf/test_stdin.elf");
+const STDIN_PATH: &str = "/tmp/pipewait.elf";
+
+/// Round trips between the shell and the echo task.
+const ROUNDS: usize = 200;
+/// Pipes shared with the wake-up test's tasks, indexed by the constants below.
+static PIPES: Mutex<Option<[Arc<Pipe>; 3]>> = Mutex::new(None);
+const PING: usize = 0;
+const PONG: usize = 1;
+const IDLE: usize = 2;
+
+const OUT: &str = "/tmp/pipe_out.txt";
+const BIG: &str = "/tmp/pipe_big.txt";
+/// Bigger than a pipe buffer holds, so the writer has to wait for the reader.
+const BIG_LINES: usize = 64;
+const BIG_LINE: &[u8; 64] = b"0123456789abcdefghijklmnopqrstuvwxyz0123456789abcdefghijklmnopq\n";
+
+/// pipetest — shell pipelines. Chains builtins through real pipes, with
+/// two and three stages and more data than one pipe buffer, and checks that
+/// an unknown command stops the pipeline and the shell gets its terminal
+/// descriptors and no stray children back. Then pipe wake-ups: a transfer
+/// wakes only the tasks blocked on that pipe, and a process killed while
+/// blocked leaves the pipe's wait list.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== Shell Pipeline Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    exec_command(&alloc::format!("echo hello | cat > {}", OUT));
+    check!(pass, fail, "echo | cat", contents(OUT) == "hello\n");
+
+    exec_command(&alloc::format!("echo three stages | cat | cat > {}", OUT));
+    check!(pass, fail, "echo | cat | cat", contents(OUT) == "three stages\n");
+
+    exec_command(&alloc::format!("ls / | cat > {}", OUT));
+    check!(pass, fail, "ls | cat lists the root", contents(OUT).contains("README.md"));
+
+    {
+        let mut vfs = crate::fs::VFS.lock();
+        let _ = vfs.create(BIG);
+        for i in 0..BIG_LINES {
+            let _ = vfs.write_at(BIG, i * BIG_LINE.len(), BIG_LINE);
+        }
+    }
+    exec_command(&alloc::format!("cat {} | cat > {}", BIG, OUT));
+    let size = crate::fs::VFS.lock().lookup(OUT).map_or(0, |inode| inode.size);
+    check!(pass, fail, "more than a pipe buffer goes through", size == BIG_LINES * BIG_LINE.len());
+
+    exec_command(&alloc::format!("echo lost | no_such_command > {}", OUT));
+    check!(pass, fail, "unknown stage: nothing runs", contents(OUT).is_empty());
+
+    let console = |fd| crate::scheduler::current_fd(fd)
+        .is_some_and(|f| matches!(f.lock().file_type, FileType::Console));
+    check!(pass, fail, "stdin and stdout restored", console(0) && console(1));
+    let children = crate::scheduler::SCHEDULER.lock().current.as_ref().map_or(0, |p| p.children.len());
+    check!(pass, fail, "all stages reaped", children == 0);
+
+    let mut vfs = crate::fs::VFS.lock();
+    let _ = vfs.unlink(OUT);
+    let _ = vfs.unlink(BIG);
+    drop(vfs);
+
+    // Ping-pong with a task while another sits blocked on an idle pipe
+    *PIPES.lock() = Some([open_pipe(), open_pipe(), open_pipe()]);
+    let bystander = join::spawn_joinable(task_bystander, "pipe_bystander");
+    let echo = join::spawn_joinable(task_echo, "pipe_echo");
+    let blocked = wait_blocked(bystander);
+    let before = switches(bystander);
+    let (ping, pong) = (pipe(PING), pipe(PONG));
+    let mut byte = [0u8; 1];
+    let echoed = (0..ROUNDS).all(|i| {
+        ping.write(&[i as u8]) == Ok(1) && pong.read(&mut byte) == Ok(1) && byte[0] == i as u8
+    });
+    let echo_switches = switches(echo);
+    ping.close_writer();
+    let echo_done = join::join(echo).is_ok_and(|s| wait::exited(s) && wait::exit_code(s) == 0);
+    let woken = switches(bystander) - before;
+    test_log!("{} round trips: {} switches to the echo task, {} to the bystander",
+        ROUNDS, echo_switches, woken);
+    check!(pass, fail, "ping-pong through two pipes", echoed && echo_done);
+    check!(pass, fail, "task blocked on another pipe never woken", blocked && woken == 0);
+    pipe(IDLE).close_writer();
+    check!(pass, fail, "closing the writer wakes the reader with EOF",
+        join::join(bystander).is_ok_and(|s| wait::exited(s) && wait::exit_code(s) == 0));
+    *PIPES.lock() = None;
+
+    // A process killed while blocked reading its stdin pipe
+    let (read_end, write_end) = crate::fs::fd::File::new_pipe();
+    let waiting = match &write_end.lock().file_type {
+        FileType::PipeWrite(p) => Some(p.clone()),
+        _ => None,
+    };
+    let child = {
+        let mut vfs = crate::fs::VFS.lock();
+        let _ = vfs.unlink(STDIN_PATH);
+        let _ = vfs.create(STDIN_PATH).and_then(|_| vfs.write_file(STDIN_PATH, TEST_STDIN_ELF));
+        drop(vfs);
+        // The child inherits the shell's descriptors
+        let saved = scheduler::replace_current_fd(0, Some(read_end));
+        let child = crate::loader::elf::spawn(STDIN_PATH, &[STDIN_PATH], &[], Some(scheduler::current_pid()));
+        scheduler::replace_current_fd(0, saved);
+        child
+    };
+    match (child, waiting) {
+        (Ok(pid), Some(pipe)) => {
+            let queued = wait_blocked(pid) && pipe.waiting_readers() == 1;
+            let killed = signal::send(pid, signal::SIGKILL).is_ok()
+                && scheduler::sys_wait(pid.0, 0).is_ok_and(|r| r.is_some_and(|(_, s)| {
+                    wait::signaled(s) && wait::term_signal(s) == signal::SIGKILL
+                }));
+            check!(pass, fail, "killed while blocked on a pipe", queued && killed);
+            check!(pass, fail, "killed reader left the wait list and closed its end",
+                pipe.waiting_readers() == 0 && pipe.write(b"x") == Err(PipeError::Broken));
+        }
+        (Err(e), _) => { test_log!("[FAIL] spawn {}: {}", STDIN_PATH, e); fail += 1; }
+        (_, None) => { test_log!("[FAIL] new_pipe gave no write end"); fail += 1; }
+    }
+    drop(write_end);
+    let _ = crate::fs::VFS.lock().unlink(STDIN_PATH);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// A pipe with one read and one write end, driven directly.
+fn open_pipe() -> Arc<Pipe> {
+    let pipe = Pipe::new();
+    pipe.lock().add_reader();
+    pipe.lock().add_writer();
+    pipe
+}
+
+fn pipe(index: usize) -> Arc<Pipe> {
+    PIPES.lock().as_ref().map(|p| p[index].clone()).expect("pipetest pipes not set up")
+}
+
+/// Let other tasks run until `pid` is Blocked (false if it never is).
+fn wait_blocked(pid: scheduler::ProcessId) -> bool {
+    (0..100).any(|_| {
+        scheduler::yield_now();
+        scheduler::task_snapshot().iter().any(|t| t.pid == pid.0 && t.state == ProcessState::Blocked)
+    })
+}
+
+/// Times the scheduler has switched to `pid` so far.
+fn switches(pid: scheduler::ProcessId) -> u64 {
+    scheduler::task_snapshot().iter().find(|t| t.pid == pid.0).map_or(0, |t| t.switches)
+}
+
+/// Blocks on the idle pipe until its writer closes, then exits 0 on EOF.
+fn task_bystander() {
+    let eof = pipe(IDLE).read(&mut [0u8; 1]) == Ok(0);
+    scheduler::exit_current(if eof { 0 } else { 1 });
+}
+
+/// Sends every byte from ping back on pong, until ping's writer closes.
+fn task_echo() {
+    let (ping, pong) = (pipe(PING), pipe(PONG));
+    let mut byte = [0u8; 1];
+    while ping.read(&mut byte) == Ok(1) {
+        if pong.write(&byte) != Ok(1) {
+            scheduler::exit_current(1);
+        }
+    }
+    scheduler::exit_current(0);
+}
+
+/// Whole contents of a small text file, or "" if it can't be read.
+fn contents(path: &str) -> alloc::string::String {
+    let mut buf = [0u8; 256];
+    let n = crate::fs::VFS.lock().read_file(path, 0, &mut buf).unwrap_or(0);
+    alloc::string::String::from_utf8_lossy(&buf[..n]).into_owned()
+}
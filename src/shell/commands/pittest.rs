@@ -0,0 +1,56 @@
+use core::sync::atomic::Ordering;
+use crate::drivers::rtc;
+use crate::interrupts::pit::{self, DEFAULT_HZ, MAX_HZ, MIN_HZ};
+use crate::shell::commands::uptime::TICKS;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// pittest — timer programming: divisor rounding, the rate limits and
+/// millisecond conversion, then the ticks counted over one RTC second at
+/// the default rate and at 1000 Hz, which must be within 10% of the rate.
+/// The default rate is restored afterwards.
+pub fn run(_args: &str) {
+    test_log!("=== PIT Timer Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    // 1193182 / 1000 = 1193.18: divisor 1193, ticks of 999.847 us
+    check!(pass, fail, "1000 Hz rounds to the nearest divisor",
+        pit::set_timer_hz(1000) == 1000 && pit::nanos_per_tick() == 999_847);
+    check!(pass, fail, "rate below the minimum clamped", pit::set_timer_hz(1) == pit::set_timer_hz(MIN_HZ));
+    check!(pass, fail, "rate above the maximum clamped", pit::set_timer_hz(u32::MAX) == pit::set_timer_hz(MAX_HZ));
+
+    let hz = pit::set_timer_hz(DEFAULT_HZ);
+    check!(pass, fail, "default rate set", hz == DEFAULT_HZ);
+    check!(pass, fail, "1 s is a rate's worth of ticks", pit::ms_to_ticks(1000) == DEFAULT_HZ as u64);
+    check!(pass, fail, "milliseconds round up to whole ticks", pit::ms_to_ticks(1) == 1 && pit::ms_to_ticks(0) == 0);
+
+    for rate in [DEFAULT_HZ, 1000] {
+        let hz = pit::set_timer_hz(rate);
+        let ticks = ticks_per_rtc_second();
+        test_log!("  {} Hz: {} ticks in one RTC second", hz, ticks);
+        check!(pass, fail, "measured rate matches the programmed one",
+            ticks * 10 >= hz as u64 * 9 && ticks * 10 <= hz as u64 * 11);
+    }
+    pit::set_timer_hz(DEFAULT_HZ);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// Busy-wait from one RTC second boundary to the next, counting ticks.
+fn ticks_per_rtc_second() -> u64 {
+    wait_for_next_second();
+    let start = TICKS.load(Ordering::Relaxed);
+    wait_for_next_second();
+    TICKS.load(Ordering::Relaxed) - start
+}
+
+fn wait_for_next_second() {
+    let now = rtc::unix_time();
+    while rtc::unix_time() == now {
+        core::hint::spin_loop();
+    }
+}
@@ -0,0 +1,76 @@
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use crate::scheduler::QUANTUM_TICKS;
+use crate::shell::commands::uptime::TICKS;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// How long the spinners are left running: four rounds of a quantum each
+/// for them and the shell.
+const RUN_TICKS: u64 = QUANTUM_TICKS * 3 * 4;
+
+static SPIN_A: AtomicU64 = AtomicU64::new(0);
+static SPIN_B: AtomicU64 = AtomicU64::new(0);
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// preempttest — timer preemption test. Two tasks spin without ever
+/// yielding; both must make progress, and so must the shell.
+pub fn run(_args: &str) {
+    test_log!("=== Preemption Test (quantum {} ticks) ===", QUANTUM_TICKS);
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    SPIN_A.store(0, Ordering::Relaxed);
+    SPIN_B.store(0, Ordering::Relaxed);
+    STOP.store(false, Ordering::Relaxed);
+    crate::scheduler::spawn(task_spin_a, "spin_a");
+    crate::scheduler::spawn(task_spin_b, "spin_b");
+
+    // Busy-wait too: the shell gets the CPU back only by preemption. On one
+    // CPU the counters can only move while the shell is switched out, so
+    // each time the shell sees them change it has been preempted and resumed
+    let start = TICKS.load(Ordering::Relaxed);
+    let mut seen = (0, 0);
+    let mut resumed = 0u32;
+    while TICKS.load(Ordering::Relaxed) - start < RUN_TICKS {
+        let now = (SPIN_A.load(Ordering::Relaxed), SPIN_B.load(Ordering::Relaxed));
+        if now != seen {
+            resumed += 1;
+            seen = now;
+        }
+        core::hint::spin_loop();
+    }
+    let (a, b) = (SPIN_A.load(Ordering::Relaxed), SPIN_B.load(Ordering::Relaxed));
+    test_log!("spin_a: {} iterations, spin_b: {} iterations, shell resumed {} times", a, b, resumed);
+
+    check!(pass, fail, "first spinner ran", a > 0);
+    check!(pass, fail, "second spinner ran", b > 0);
+    check!(pass, fail, "shell ran alongside the spinners", resumed >= 2);
+
+    // Both keep going while the shell sleeps
+    crate::scheduler::sleep_ticks(QUANTUM_TICKS * 2);
+    check!(pass, fail, "spinners still progressing",
+        SPIN_A.load(Ordering::Relaxed) > a && SPIN_B.load(Ordering::Relaxed) > b);
+
+    STOP.store(true, Ordering::Relaxed);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+fn task_spin_a() {
+    spin(&SPIN_A);
+}
+
+fn task_spin_b() {
+    spin(&SPIN_B);
+}
+
+/// Count as fast as possible until told to stop, never yielding.
+fn spin(counter: &AtomicU64) {
+    while !STOP.load(Ordering::Relaxed) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+    crate::scheduler::exit_current(0);
+}
@@ -0,0 +1,69 @@
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use crate::scheduler::{self, PRIORITY_LOW, PRIORITY_NORMAL};
+use crate::shell::commands::testutil::{check, test_log};
+
+/// How long the spinners are left running while the shell sleeps.
+const RUN_TICKS: u64 = 20;
+
+static SPIN_LOW: AtomicU64 = AtomicU64::new(0);
+static SPIN_NORMAL: AtomicU64 = AtomicU64::new(0);
+static STOP: AtomicBool = AtomicBool::new(false);
+
+/// priotest — scheduler priorities. A low-priority task alone still runs;
+/// next to a normal-priority spinner it gets less CPU, but aging keeps it
+/// from starving. The shell itself runs at high priority throughout.
+pub fn run(_args: &str) {
+    test_log!("=== Scheduler Priority Test (aging after {} picks) ===", scheduler::AGING_PICKS);
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    SPIN_LOW.store(0, Ordering::Relaxed);
+    SPIN_NORMAL.store(0, Ordering::Relaxed);
+    STOP.store(false, Ordering::Relaxed);
+
+    // Only a low-priority task is runnable while the shell sleeps
+    scheduler::spawn_with_priority(task_spin_low, "spin_low", PRIORITY_LOW);
+    scheduler::sleep_ticks(RUN_TICKS / 2);
+    let alone = SPIN_LOW.load(Ordering::Relaxed);
+    check!(pass, fail, "low-priority task runs when nothing else can", alone > 0);
+
+    // Now next to a normal-priority spinner
+    scheduler::spawn_with_priority(task_spin_normal, "spin_normal", PRIORITY_NORMAL);
+    scheduler::sleep_ticks(RUN_TICKS);
+    let low = SPIN_LOW.load(Ordering::Relaxed) - alone;
+    let normal = SPIN_NORMAL.load(Ordering::Relaxed);
+    test_log!("spin_low: {} iterations, spin_normal: {} iterations", low, normal);
+    check!(pass, fail, "normal priority got more CPU than low", normal > low);
+    check!(pass, fail, "aging kept the low-priority task running", low > 0);
+
+    // The spinners never yield; the shell still gets the CPU right back
+    let start = crate::shell::commands::uptime::TICKS.load(Ordering::Relaxed);
+    scheduler::sleep_ticks(1);
+    let waited = crate::shell::commands::uptime::TICKS.load(Ordering::Relaxed) - start;
+    test_log!("1-tick sleep next to two spinners took {} ticks", waited);
+    check!(pass, fail, "high-priority shell woke within a quantum", waited <= 1 + scheduler::QUANTUM_TICKS);
+
+    STOP.store(true, Ordering::Relaxed);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+fn task_spin_low() {
+    spin(&SPIN_LOW);
+}
+
+fn task_spin_normal() {
+    spin(&SPIN_NORMAL);
+}
+
+/// Count as fast as possible until told to stop, never yielding.
+fn spin(counter: &AtomicU64) {
+    while !STOP.load(Ordering::Relaxed) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+    crate::scheduler::exit_current(0);
+}
@@ -1,11 +1,39 @@
 use crate::println;
+use crate::scheduler::{ProcessState, TaskInfo, PRIORITY_HIGH, PRIORITY_LOW};
 
-/// ps — list active tasks from the real scheduler.
+/// ps — list every process from the real scheduler, by PID: its parent,
+/// state, priority and open descriptors. The process running `ps` shows
+/// as "running"; others that are runnable show as "ready".
 pub fn run(_args: &str) {
-    let tasks = crate::scheduler::list_tasks();
-    println!("  PID  STATE      NAME");
-    println!("  ---  ---------  ----");
-    for (pid, name, state) in &tasks {
-        println!("  {:>3}  {:9}  {}", pid, state, name);
+    let mut tasks = crate::scheduler::task_snapshot();
+    tasks.sort_by_key(|t| t.pid);
+
+    println!("  PID  PPID  STATE    PRI     FDS  NAME");
+    for task in &tasks {
+        let ppid = match task.parent_pid {
+            Some(pid) => alloc::format!("{}", pid),
+            None => "-".into(),
+        };
+        println!("  {:>3}  {:>4}  {:7}  {:6}  {:>3}  {}",
+            task.pid, ppid, state_name(task), priority_name(task.priority), task.open_fds, task.name);
+    }
+}
+
+fn state_name(task: &TaskInfo) -> &'static str {
+    if task.running {
+        return "running";
+    }
+    match task.state {
+        ProcessState::Ready | ProcessState::Running => "ready",
+        ProcessState::Blocked => "blocked",
+        ProcessState::Zombie => "zombie",
+    }
+}
+
+fn priority_name(priority: u8) -> &'static str {
+    match priority {
+        PRIORITY_LOW => "low",
+        PRIORITY_HIGH => "high",
+        _ => "normal",
     }
 }
@@ -1,6 +1,6 @@
 use crate::println;
 
+/// pwd — print the shell process's working directory.
 pub fn run(_args: &str) {
-    let cwd = crate::shell::state::CWD.lock().clone();
-    println!("{}", cwd);
+    println!("{}", crate::shell::state::cwd());
 }
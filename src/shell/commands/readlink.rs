@@ -0,0 +1,17 @@
+use crate::println;
+
+/// readlink <path> — print the target of a symbolic link.
+pub fn run(args: &str) {
+    let path = args.trim();
+    if path.is_empty() {
+        println!("readlink: missing operand");
+        return;
+    }
+
+    let full = crate::shell::state::resolve_path(path);
+    let vfs = crate::fs::VFS.lock();
+    match vfs.readlink(&full) {
+        Ok(target) => println!("{}", target),
+        Err(e) => println!("readlink: {}: {}", path, e),
+    }
+}
@@ -0,0 +1,20 @@
+/// reboot — flush filesystems and reset the machine.
+pub fn run(_args: &str) {
+    crate::power::reboot();
+}
+
+/// poweroff — flush filesystems and turn the machine off.
+pub fn poweroff(_args: &str) {
+    crate::power::poweroff();
+}
+
+/// shutdown [-r|-h] — flush filesystems, then power off (default), reboot
+/// (`-r`) or halt with the machine left on (`-h`).
+pub fn shutdown(args: &str) {
+    match args.trim() {
+        "" => crate::power::poweroff(),
+        "-r" => crate::power::reboot(),
+        "-h" => crate::power::shutdown_halt(),
+        other => crate::println!("shutdown: unknown option '{}' (use -r or -h)", other),
+    }
+}
@@ -0,0 +1,59 @@
+use crate::fs::fd::FileType;
+use crate::shell::exec_command;
+use crate::shell::commands::testutil::{check, test_log};
+
+const OUT: &str = "/tmp/redir_out.txt";
+const COPY: &str = "/tmp/redir_copy.txt";
+
+/// redirtest — shell redirection. Runs command lines with `>`, `>>` and `<`
+/// and checks the files they produce, that a target which can't be opened
+/// stops the command, and that the terminal descriptors come back after.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== Shell Redirection Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    check!(pass, fail, "parse: plain line untouched", crate::shell::redirect::parse("echo hi").ok() == Some(None));
+    let parsed = crate::shell::redirect::parse("cat <in.txt >> out.txt").ok().flatten();
+    check!(pass, fail, "parse: attached and detached targets", parsed.is_some_and(|(line, r)| line == "cat"
+        && r.stdin.as_deref() == Some("in.txt") && r.stdout.as_deref() == Some("out.txt") && r.append));
+    check!(pass, fail, "parse: missing target rejected", crate::shell::redirect::parse("echo hi >").is_err());
+
+    exec_command(&alloc::format!("echo first > {}", OUT));
+    check!(pass, fail, "> writes the output to a file", contents(OUT) == "first\n");
+    exec_command(&alloc::format!("echo second >{}", OUT));
+    check!(pass, fail, "> truncates an existing file", contents(OUT) == "second\n");
+    exec_command(&alloc::format!("echo third >> {}", OUT));
+    check!(pass, fail, ">> appends", contents(OUT) == "second\nthird\n");
+
+    exec_command(&alloc::format!("cat < {} > {}", OUT, COPY));
+    check!(pass, fail, "< feeds a file as stdin", contents(COPY) == "second\nthird\n");
+
+    exec_command("echo lost > /tmp/no_such_dir/out.txt");
+    check!(pass, fail, "unopenable target: nothing created", !crate::fs::VFS.lock().exists("/tmp/no_such_dir/out.txt"));
+    exec_command(&alloc::format!("cat < /tmp/no_such_file > {}", COPY));
+    check!(pass, fail, "missing input: command not run", contents(COPY) == "second\nthird\n");
+
+    let console = |fd| crate::scheduler::current_fd(fd)
+        .is_some_and(|f| matches!(f.lock().file_type, FileType::Console));
+    check!(pass, fail, "stdin and stdout restored", console(0) && console(1));
+
+    let mut vfs = crate::fs::VFS.lock();
+    let _ = vfs.unlink(OUT);
+    let _ = vfs.unlink(COPY);
+    drop(vfs);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// Whole contents of a small text file, or "" if it can't be read.
+fn contents(path: &str) -> alloc::string::String {
+    let mut buf = [0u8; 64];
+    let n = crate::fs::VFS.lock().read_file(path, 0, &mut buf).unwrap_or(0);
+    alloc::string::String::from_utf8_lossy(&buf[..n]).into_owned()
+}
@@ -0,0 +1,46 @@
+use crate::println;
+use x86_64::registers::control::{Cr0, Cr2, Cr3, Cr4};
+use x86_64::registers::model_specific::Efer;
+use x86_64::registers::rflags;
+use x86_64::registers::segmentation::{Segment, CS, SS};
+use x86_64::instructions::tables::{sgdt, sidt};
+
+/// regs — dump control registers, EFER, stack/flags, descriptor tables and
+/// the current task's saved context. Read-only.
+pub fn run(_args: &str) {
+    let rsp: u64;
+    unsafe { core::arch::asm!("mov {}, rsp", out(reg) rsp, options(nomem, nostack, preserves_flags)); }
+
+    let (p4, cr3_flags) = Cr3::read();
+
+    println!("Control registers:");
+    println!("  CR0    = {:#018x}  {:?}", Cr0::read_raw(), Cr0::read());
+    println!("  CR2    = {:#018x}  (last page-fault address)", Cr2::read().as_u64());
+    println!("  CR3    = {:#018x}  {:?}", p4.start_address().as_u64(), cr3_flags);
+    println!("  CR4    = {:#018x}  {:?}", Cr4::read_raw(), Cr4::read());
+    println!("  EFER   = {:#018x}  {:?}", Efer::read_raw(), Efer::read());
+
+    println!("Execution state:");
+    println!("  RSP    = {:#018x}", rsp);
+    println!("  RFLAGS = {:#018x}  {:?}", rflags::read_raw(), rflags::read());
+    println!("  CS     = {:#06x}  SS = {:#06x}", CS::get_reg().0, SS::get_reg().0);
+
+    let gdt = sgdt();
+    let idt = sidt();
+    println!("Descriptor tables:");
+    println!("  GDT    base={:#018x} limit={:#06x}", gdt.base.as_u64(), gdt.limit);
+    println!("  IDT    base={:#018x} limit={:#06x}", idt.base.as_u64(), idt.limit);
+
+    let sched = crate::scheduler::SCHEDULER.lock();
+    match sched.current.as_ref() {
+        Some(p) => {
+            let ctx = &p.context;
+            println!("Saved context of PID {} ('{}'), as of its last switch-out:", p.pid.0, p.name);
+            println!("  RIP={:#018x} RSP={:#018x} RBP={:#018x}", ctx.rip, ctx.rsp, ctx.rbp);
+            println!("  RBX={:#018x} R12={:#018x} R13={:#018x}", ctx.rbx, ctx.r12, ctx.r13);
+            println!("  R14={:#018x} R15={:#018x}", ctx.r14, ctx.r15);
+            println!("  page table = {:#018x}", p.page_table);
+        }
+        None => println!("No current process."),
+    }
+}
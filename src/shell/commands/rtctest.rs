@@ -0,0 +1,49 @@
+use crate::drivers::rtc::{self, RawTime, STATUS_B_24_HOUR, STATUS_B_BINARY};
+use crate::shell::commands::testutil::{check, test_log};
+
+/// Time to wait between the two clock reads.
+const WAIT_MS: u64 = 1100;
+
+/// rtctest — CMOS real-time clock. Checks register decoding (BCD, binary,
+/// 12-hour mode, century) on fixed values, then reads the clock twice a
+/// second apart and checks that it advanced.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== RTC Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    // 2026-10-16 21:45:30, as BCD with 24-hour time
+    let bcd = RawTime { second: 0x30, minute: 0x45, hour: 0x21, day: 0x16, month: 0x10, year: 0x26, century: 0x20 };
+    let dt = bcd.decode(STATUS_B_24_HOUR);
+    check!(pass, fail, "BCD decoded",
+        (dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second) == (2026, 10, 16, 21, 45, 30));
+    check!(pass, fail, "unix time of a known date", dt.unix_time() == 1_792_187_130);
+
+    let binary = RawTime { second: 30, minute: 45, hour: 21, day: 16, month: 10, year: 26, century: 20 };
+    check!(pass, fail, "binary mode decoded", binary.decode(STATUS_B_24_HOUR | STATUS_B_BINARY) == dt);
+
+    let twelve = |hour: u8| RawTime { hour, ..bcd }.decode(0).hour;
+    check!(pass, fail, "12-hour: 9 PM is 21", twelve(0x80 | 0x09) == 21);
+    check!(pass, fail, "12-hour: 12 AM is midnight", twelve(0x12) == 0);
+    check!(pass, fail, "12-hour: 12 PM is noon", twelve(0x80 | 0x12) == 12);
+    check!(pass, fail, "12-hour: 11 AM is 11", twelve(0x11) == 11);
+    check!(pass, fail, "missing century register assumes 20xx", RawTime { century: 0xFF, ..bcd }.decode(STATUS_B_24_HOUR).year == 2026);
+    check!(pass, fail, "century register honoured", RawTime { century: 0x19, year: 0x99, ..bcd }.decode(STATUS_B_24_HOUR).year == 1999);
+
+    let first = rtc::read_datetime();
+    test_log!("now:   {} UTC", first);
+    check!(pass, fail, "fields in range", first.month >= 1 && first.month <= 12 && first.day >= 1 && first.day <= 31
+        && first.hour < 24 && first.minute < 60 && first.second < 60);
+    crate::scheduler::sleep_ticks(crate::interrupts::pit::ms_to_ticks(WAIT_MS));
+    let second = rtc::read_datetime();
+    test_log!("later: {} UTC", second);
+    let elapsed = second.unix_time().saturating_sub(first.unix_time());
+    check!(pass, fail, "clock advanced by about a second", elapsed == 1 || elapsed == 2);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
@@ -0,0 +1,68 @@
+use core::fmt::Write;
+use crate::vga;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// Lines printed to make sure the screen has scrolled.
+const LINES: usize = 30;
+const MARKER: &str = "scrolltest line ";
+
+/// scrolltest — VGA scrollback: PageUp/PageDown scrolling, cursor restore,
+/// snapping back to the bottom on new output, and many lines written at once
+/// reaching the screen in a single flush. The screen is sampled
+/// before any result is printed, since printing moves it.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== VGA Scrollback Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    // One print of many lines: they scroll in the shadow and are flushed once
+    let mut batch = alloc::string::String::new();
+    for n in 0..LINES {
+        let _ = writeln!(batch, "{}{}", MARKER, n);
+    }
+    crate::print!("{}", batch);
+    let batch_last = marker_number(&vga::read_row(vga::cursor().0 - 1));
+
+    for n in 0..LINES {
+        crate::println!("{}{}", MARKER, n);
+    }
+    let live_top = vga::read_row(0);
+    let live_cursor = vga::cursor();
+
+    vga::scroll_up(5);
+    let back_top = vga::read_row(0);
+    vga::scroll_down(5);
+    let restored_top = vga::read_row(0);
+    let restored_cursor = vga::cursor();
+
+    vga::scroll_up(vga::SCROLLBACK_LINES * 2);
+    vga::scroll_down(1);
+    vga::scroll_up(1);
+    crate::print!("!");
+    let snapped_top = vga::read_row(0);
+    let snapped_cursor = vga::cursor();
+    crate::println!();
+
+    let top_line = marker_number(&live_top);
+    check!(pass, fail, "a batched write shows its last line", batch_last == Some(LINES - 1));
+    check!(pass, fail, "screen scrolled during the test", top_line.is_some_and(|n| n >= 5));
+    check!(pass, fail, "scroll_up(5) shows the line 5 above the top",
+        top_line.is_some_and(|n| marker_number(&back_top) == Some(n - 5)));
+    check!(pass, fail, "scroll_down(5) restores the live screen", restored_top == live_top);
+    check!(pass, fail, "cursor restored after scrolling back down", restored_cursor == live_cursor);
+    check!(pass, fail, "output while scrolled snaps to the bottom", snapped_top == live_top);
+    check!(pass, fail, "snapped output lands at the cursor", snapped_cursor == (live_cursor.0, live_cursor.1 + 1));
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// N from a row reading "scrolltest line N".
+fn marker_number(row: &[u8]) -> Option<usize> {
+    let text = core::str::from_utf8(row).ok()?.trim_end();
+    text.strip_prefix(MARKER)?.parse().ok()
+}
@@ -0,0 +1,114 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::hint::black_box;
+use crate::allocator::slab::{slab_stats, SlabCache, SlabStats};
+use crate::scheduler::fpu::FpuState;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// A cache of its own, so the test's objects don't mix with the scheduler's.
+static TEST_CACHE: SlabCache<FpuState> = SlabCache::new("slabtest");
+
+/// Allocate/free pairs timed for each allocator.
+const BENCH_ROUNDS: u64 = 2000;
+/// Heap blocks of mixed sizes left live during the benchmark, as on a
+/// system that has been running for a while.
+const CLUTTER: usize = 128;
+
+/// Fork/exit benchmark on the FAT32 disk (userland/fork_bench).
+const FORK_PROGRAM: &str = "/disk/fbench.elf";
+/// Runs after the warm-up; each forks and reaps 32 children.
+const FORK_RUNS: usize = 2;
+
+/// slabtest — `SlabCache` recycling, and what it bought fork. Frees must
+/// hand slots back out without new slabs, the FPU area alloc/free pair that
+/// every fork and exit does must cost less from the cache than from the
+/// heap it used to come from, and fork_bench must not grow the FPU cache
+/// once warm.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== Slab Cache Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    // Freed slots go back on the free list and are handed out first
+    let first = TEST_CACHE.alloc(FpuState::new());
+    let addr = &*first as *const FpuState as usize;
+    check!(pass, fail, "objects keep their alignment", addr % core::mem::align_of::<FpuState>() == 0);
+    drop(first);
+    let again = TEST_CACHE.alloc(FpuState::new());
+    check!(pass, fail, "freed slot handed out again", &*again as *const FpuState as usize == addr);
+    drop(again);
+
+    let before = cache_stats("slabtest");
+    let per_slab = SlabCache::<FpuState>::objects_per_slab();
+    let live: Vec<_> = (0..before.free + 1).map(|_| TEST_CACHE.alloc(FpuState::new())).collect();
+    let grown = cache_stats("slabtest");
+    check!(pass, fail, "a full cache grows by one slab",
+        grown.slabs == before.slabs + 1 && grown.free == per_slab - 1);
+    drop(live);
+    for _ in 0..BENCH_ROUNDS {
+        drop(black_box(TEST_CACHE.alloc(FpuState::new())));
+    }
+    let after = cache_stats("slabtest");
+    check!(pass, fail, "recycled objects need no new slabs", after.slabs == grown.slabs);
+    check!(pass, fail, "every object back on the free list", after.in_use == 0 && after.free == after.slabs * per_slab);
+
+    // Before and after: FPU areas were boxed on the heap until the cache
+    let clutter = fragment_heap();
+    let heap = average_cycles(|| drop(black_box(Box::new(FpuState::new()))));
+    let slab = average_cycles(|| drop(black_box(TEST_CACHE.alloc(FpuState::new()))));
+    drop(clutter);
+    test_log!("FPU area alloc+free: heap {} cycles, slab {} cycles", heap, slab);
+    check!(pass, fail, "slab alloc+free cheaper than the heap", slab < heap);
+
+    // fork_bench forks and reaps: with its FPU areas recycled, the cache
+    // stops growing after the first run
+    if crate::fs::VFS.lock().exists(FORK_PROGRAM) {
+        let mut statuses_ok = crate::shell::run_external(FORK_PROGRAM, "") == 0;
+        let warm = cache_stats("fpu_state");
+        for _ in 0..FORK_RUNS {
+            statuses_ok &= crate::shell::run_external(FORK_PROGRAM, "") == 0;
+        }
+        check!(pass, fail, "fork benchmark runs completed", statuses_ok);
+        check!(pass, fail, "forks reuse FPU areas: fpu_state cache did not grow",
+            cache_stats("fpu_state").slabs == warm.slabs);
+    } else {
+        test_log!("  ({} not found, skipping fork benchmark)", FORK_PROGRAM);
+    }
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// Counters of the cache called `name`; all zero before its first slab.
+fn cache_stats(name: &'static str) -> SlabStats {
+    slab_stats().into_iter().find(|c| c.name == name)
+        .unwrap_or(SlabStats { name, object_size: 0, slabs: 0, in_use: 0, free: 0 })
+}
+
+/// Leave `CLUTTER` small blocks live with holes between them, so the heap's
+/// free list is as long as on a busy system. Dropping the result frees them.
+fn fragment_heap() -> Vec<Vec<u8>> {
+    let mut blocks: Vec<Vec<u8>> = (0..CLUTTER * 2).map(|i| Vec::with_capacity(16 + i % 7 * 24)).collect();
+    let mut kept = Vec::with_capacity(CLUTTER);
+    for (i, block) in blocks.drain(..).enumerate() {
+        if i % 2 == 0 {
+            kept.push(block);
+        }
+    }
+    kept
+}
+
+/// Average TSC cycles of `f` over `BENCH_ROUNDS` calls.
+fn average_cycles(mut f: impl FnMut()) -> u64 {
+    // SAFETY: RDTSC has no side effects
+    let start = unsafe { core::arch::x86_64::_rdtsc() };
+    for _ in 0..BENCH_ROUNDS {
+        f();
+    }
+    let end = unsafe { core::arch::x86_64::_rdtsc() };
+    (end - start) / BENCH_ROUNDS
+}
@@ -0,0 +1,33 @@
+use crate::println;
+use crate::scheduler::signal;
+
+/// Longest accepted sleep, in seconds.
+const MAX_SLEEP_SECS: u64 = 3600;
+
+/// sleep <seconds> — block the shell while background tasks keep running.
+/// Ctrl-C ends the sleep early.
+pub fn run(args: &str) {
+    let arg = args.trim();
+    if arg.is_empty() {
+        println!("sleep: usage: sleep <seconds>");
+        return;
+    }
+
+    // Rejects negative numbers and garbage alike
+    let secs: u64 = match arg.parse() {
+        Ok(v) => v,
+        Err(_) => { println!("sleep: invalid time interval: {}", arg); return; }
+    };
+    if secs > MAX_SLEEP_SECS {
+        println!("sleep: {} seconds is too long (max {})", secs, MAX_SLEEP_SECS);
+        return;
+    }
+
+    // Ctrl-C posts SIGINT to the foreground task, which cuts the sleep short
+    signal::set_foreground(Some(crate::scheduler::current_pid()));
+    crate::scheduler::sleep_ticks(crate::interrupts::pit::ms_to_ticks(secs * 1000));
+    signal::set_foreground(None);
+    if signal::take_interrupt() {
+        println!("^C");
+    }
+}
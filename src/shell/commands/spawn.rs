@@ -5,7 +5,7 @@ pub fn run(args: &str) {
     let name = args.trim();
     if name.is_empty() {
         println!("spawn: usage: spawn <task_name>");
-        println!("  Available demo tasks: counter, ticker, hello");
+        println!("  Available demo tasks: counter, ticker, hello, spinner");
         return;
     }
 
@@ -22,6 +22,10 @@ pub fn run(args: &str) {
             let id = crate::syscalls::sys_spawn(task_hello, "hello");
             println!("Spawned 'hello' as task {}", id);
         },
+        "spinner" => {
+            let id = crate::syscalls::sys_spawn(task_spinner, "spinner");
+            println!("Spawned 'spinner' as task {} (stop it with kill {})", id, id);
+        },
         _ => println!("spawn: unknown task '{}'", name),
     }
 }
@@ -30,9 +34,8 @@ pub fn run(args: &str) {
 fn task_counter() {
     for i in 1..=5 {
         crate::println!("[counter] tick {}", i);
-        // Small busy-wait to make output visible
+        // Busy-wait without yielding; the timer preempts us
         for _ in 0..500_000 { core::hint::spin_loop(); }
-        crate::scheduler::yield_now();
     }
     crate::println!("[counter] done!");
     crate::scheduler::exit_current(0);
@@ -43,7 +46,6 @@ fn task_ticker() {
     for _ in 0..3 {
         crate::println!("[ticker] *");
         for _ in 0..300_000 { core::hint::spin_loop(); }
-        crate::scheduler::yield_now();
     }
     crate::println!("[ticker] finished.");
     crate::scheduler::exit_current(0);
@@ -54,3 +56,9 @@ fn task_hello() {
     crate::println!("[hello] Hello from a background task!");
     crate::scheduler::exit_current(0);
 }
+
+/// Demo task: spins forever without yielding. The shell stays responsive
+/// because the timer preempts it.
+fn task_spinner() {
+    loop { core::hint::spin_loop(); }
+}
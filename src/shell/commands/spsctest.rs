@@ -0,0 +1,93 @@
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use x86_64::instructions::interrupts;
+use crate::scheduler;
+use crate::util::spsc::SpscRing;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// Items sent through the ring; many times its size, so slots are reused.
+const ITEMS: u64 = 20_000;
+/// Items pushed per simulated interrupt.
+const BURST: u64 = 5;
+
+static RING: SpscRing<u64, 16> = SpscRing::new(0);
+/// Items the consumer took, and how many were not the one expected next.
+static RECEIVED: AtomicU64 = AtomicU64::new(0);
+static OUT_OF_ORDER: AtomicU64 = AtomicU64::new(0);
+static DONE: AtomicBool = AtomicBool::new(false);
+
+/// spsctest — the interrupt-to-task ring buffer behind the keyboard and mouse
+/// queues. The shell pushes a counting sequence in bursts with interrupts
+/// disabled, as an interrupt handler would, while a task pops it and yields
+/// whenever the ring runs dry. Every item must arrive once, in order.
+pub fn run(_args: &str) {
+    test_log!("=== SPSC Ring Buffer Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    check!(pass, fail, "empty ring pops nothing", RING.is_empty() && RING.pop().is_none());
+    let mut filled = 0;
+    while RING.push(filled).is_ok() {
+        filled += 1;
+    }
+    check!(pass, fail, "full ring gives the item back", filled == 15 && RING.push(99) == Err(99));
+    let drained: u64 = core::iter::from_fn(|| RING.pop()).zip(0..).filter(|&(v, i)| v == i).count() as u64;
+    check!(pass, fail, "items come out in order", drained == filled && RING.is_empty());
+
+    RECEIVED.store(0, Ordering::Relaxed);
+    OUT_OF_ORDER.store(0, Ordering::Relaxed);
+    DONE.store(false, Ordering::Relaxed);
+    scheduler::spawn(consumer_task, "spsc_consumer");
+
+    let mut next = 0;
+    let mut full = 0u64;
+    while next < ITEMS {
+        // One simulated interrupt: a burst of pushes the consumer can't interleave with
+        let burst_end = (next + BURST).min(ITEMS);
+        interrupts::without_interrupts(|| {
+            while next < burst_end && RING.push(next).is_ok() {
+                next += 1;
+            }
+        });
+        if next < burst_end {
+            full += 1;
+            scheduler::yield_now();
+        }
+    }
+    for _ in 0..100 {
+        if DONE.load(Ordering::Acquire) {
+            break;
+        }
+        scheduler::sleep_ticks(1);
+    }
+
+    let received = RECEIVED.load(Ordering::Relaxed);
+    test_log!("{} items sent, ring full {} times, {} received", ITEMS, full, received);
+    check!(pass, fail, "consumer received every item", DONE.load(Ordering::Acquire) && received == ITEMS);
+    check!(pass, fail, "no item lost, duplicated or reordered", OUT_OF_ORDER.load(Ordering::Relaxed) == 0);
+    check!(pass, fail, "ring empty afterwards", RING.is_empty());
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// Pop the sequence 0, 1, 2, ... counting anything unexpected.
+fn consumer_task() {
+    let mut expected = 0;
+    while expected < ITEMS {
+        match RING.pop() {
+            Some(item) => {
+                if item != expected {
+                    OUT_OF_ORDER.fetch_add(1, Ordering::Relaxed);
+                }
+                expected = item + 1;
+                RECEIVED.fetch_add(1, Ordering::Relaxed);
+            }
+            None => scheduler::yield_now(),
+        }
+    }
+    DONE.store(true, Ordering::Release);
+    scheduler::exit_current(0);
+}
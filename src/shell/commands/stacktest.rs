@@ -0,0 +1,79 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+use crate::scheduler::{self, join, signal, ProcessId};
+use crate::syscalls::wait;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// Lowest stack address the recursing task reached.
+static LOWEST: AtomicU64 = AtomicU64::new(u64::MAX);
+/// Stack the bounded recursion should use, in bytes.
+const BOUNDED_DEPTH_BYTES: u64 = 12 * 1024;
+
+/// stacktest — kernel task stacks and their guard pages. A task recursing
+/// without bound must be ended with SIGSEGV once it reaches the guard page,
+/// after using all of its 16 KiB, while one that stays within its stack
+/// runs normally. The overflowed stack is then handed out again.
+pub fn run(_args: &str) {
+    test_log!("=== Kernel Stack Guard Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    let bounded = join::spawn_joinable(task_bounded, "stack_bounded");
+    let res = join::join(bounded);
+    check!(pass, fail, "task using 12 KiB of stack runs normally",
+        res.is_ok_and(|s| wait::exited(s) && wait::exit_code(s) == 0));
+
+    LOWEST.store(u64::MAX, Ordering::Relaxed);
+    let deep = join::spawn_joinable(task_unbounded, "stack_overflow");
+    let top = stack_top(deep);
+    let res = join::join(deep);
+    check!(pass, fail, "overflowing task ended with SIGSEGV",
+        res.is_ok_and(|s| wait::signaled(s) && wait::term_signal(s) == signal::SIGSEGV));
+    let used = top.map(|top| top - LOWEST.load(Ordering::Relaxed));
+    test_log!("stack used before the fault: {:?} bytes", used);
+    check!(pass, fail, "whole 16 KiB usable before the guard page", used.is_some_and(|u| u > 15 * 1024));
+    check!(pass, fail, "the fault was on the guard page",
+        scheduler::stack::is_guard_page(LOWEST.load(Ordering::Relaxed) - 4096));
+
+    let again = join::spawn_joinable(task_bounded, "stack_reuse");
+    check!(pass, fail, "overflowed stack handed out again", top.is_some() && stack_top(again) == top);
+    check!(pass, fail, "reused stack works", join::join(again).is_ok_and(|s| wait::exited(s)));
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+fn stack_top(pid: ProcessId) -> Option<u64> {
+    scheduler::SCHEDULER.lock().ready_queue.iter()
+        .find(|p| p.pid == pid)
+        .and_then(|p| p.kernel_stack_top())
+}
+
+/// Recurse until `limit` bytes below the first frame.
+#[inline(never)]
+fn recurse(start: u64, limit: u64) -> u64 {
+    let mut frame = [0u8; 128];
+    let here = core::hint::black_box(&mut frame).as_ptr() as u64;
+    LOWEST.fetch_min(here, Ordering::Relaxed);
+    if start - here >= limit {
+        return 0;
+    }
+    frame[0] = 1;
+    recurse(start, limit) + core::hint::black_box(&frame)[0] as u64
+}
+
+fn task_bounded() {
+    let marker = 0u8;
+    let start = core::hint::black_box(&marker) as *const u8 as u64;
+    recurse(start, BOUNDED_DEPTH_BYTES);
+    scheduler::exit_current(0);
+}
+
+fn task_unbounded() {
+    let marker = 0u8;
+    let start = core::hint::black_box(&marker) as *const u8 as u64;
+    recurse(start, u64::MAX);
+    scheduler::exit_current(0);
+}
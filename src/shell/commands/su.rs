@@ -0,0 +1,16 @@
+use crate::println;
+
+/// su [user] / login <user> — switch the shell's current user.
+/// Stub: there are no accounts or passwords yet, any name is accepted.
+pub fn run(args: &str) {
+    let user = args.trim();
+    let user = if user.is_empty() { "root" } else { user };
+
+    if user.contains(char::is_whitespace) {
+        println!("su: invalid user name: {}", user);
+        return;
+    }
+
+    crate::system_info::set_user(user);
+    crate::shell::state::log_cmd(&alloc::format!("su: now logged in as {}", user));
+}
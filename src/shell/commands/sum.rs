@@ -0,0 +1,32 @@
+use crate::println;
+use crate::util::crc32::Crc32;
+
+/// Bytes read from the VFS per step.
+const CHUNK: usize = 512;
+
+/// sum <path> — print the CRC-32 and byte count of a file.
+/// The file is streamed in chunks, so its size is not limited by the heap.
+pub fn run(args: &str) {
+    let filename = args.trim();
+    if filename.is_empty() {
+        println!("sum: missing filename");
+        return;
+    }
+
+    let path = crate::shell::state::resolve_path(filename);
+    let mut crc = Crc32::new();
+    let mut buf = [0u8; CHUNK];
+    let mut total = 0usize;
+
+    loop {
+        let n = match crate::fs::VFS.lock().read_file(&path, total, &mut buf) {
+            Ok(n) => n,
+            Err(e) => { println!("sum: {}: {}", filename, e); return; }
+        };
+        if n == 0 { break; }
+        crc.update(&buf[..n]);
+        total += n;
+    }
+
+    println!("{:08x}  {}  {}", crc.finish(), total, filename);
+}
@@ -0,0 +1,8 @@
+use crate::println;
+
+/// sync — flush all mounted filesystems to disk.
+pub fn run(_args: &str) {
+    if let Err(e) = crate::fs::sync_all() {
+        println!("sync: {}", e);
+    }
+}
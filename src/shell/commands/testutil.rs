@@ -0,0 +1,25 @@
+//! Macros shared by the `*test` commands.
+
+/// Print to both VGA (println) and serial (log_info), so results show on
+/// screen and in the serial log.
+macro_rules! test_log {
+    ($($arg:tt)*) => {
+        crate::println!($($arg)*);
+        crate::log_info!($($arg)*);
+    }
+}
+pub(crate) use test_log;
+
+/// Log `[PASS] desc` or `[FAIL] desc` depending on `cond` and bump the
+/// matching counter. The counters are passed in by name since the macro
+/// can't see the caller's locals otherwise.
+macro_rules! check {
+    ($pass:ident, $fail:ident, $desc:expr, $cond:expr) => {
+        if $cond {
+            $crate::shell::commands::testutil::test_log!("[PASS] {}", $desc); $pass += 1;
+        } else {
+            $crate::shell::commands::testutil::test_log!("[FAIL] {}", $desc); $fail += 1;
+        }
+    }
+}
+pub(crate) use check;
@@ -0,0 +1,59 @@
+use alloc::collections::BTreeMap;
+use crate::println;
+use crate::drivers::keyboard::{self, scancodes::KeyCode};
+use crate::shell::commands::uptime::TICKS;
+use core::sync::atomic::Ordering;
+
+/// Time between refreshes.
+const REFRESH_MS: u64 = 1000;
+
+/// top — live process monitor. Redraws a table of tasks sorted by CPU
+/// use over the last interval every second until 'q' is pressed.
+pub fn run(_args: &str) {
+    // CPU ticks per PID at the previous refresh; tasks that appeared since
+    // count from zero, tasks that exited simply drop out of the table
+    let mut prev: BTreeMap<u64, u64> = BTreeMap::new();
+    let mut prev_now = TICKS.load(Ordering::Relaxed);
+
+    loop {
+        let now = TICKS.load(Ordering::Relaxed);
+        let interval = (now - prev_now).max(1);
+
+        let mut rows: alloc::vec::Vec<_> = crate::scheduler::list_tasks()
+            .into_iter()
+            .map(|(pid, name, state, cpu)| {
+                let recent = cpu.saturating_sub(prev.get(&pid).copied().unwrap_or(0));
+                (pid, name, state, cpu, recent)
+            })
+            .collect();
+        rows.sort_by(|a, b| b.4.cmp(&a.4).then(b.3.cmp(&a.3)).then(a.0.cmp(&b.0)));
+
+        // PID 0 is the shell, which also owns the idle halt loop
+        let busy: u64 = rows.iter().filter(|r| r.0 != 0).map(|r| r.4).sum();
+
+        crate::vga::clear_screen();
+        println!("top - up {}s, {} tasks, CPU {}% busy        (q to quit)",
+            crate::system_info::uptime_secs(), rows.len(), percent(busy, interval));
+        println!();
+        println!("  PID  STATE      %CPU    TICKS  NAME");
+        for (pid, name, state, cpu, recent) in &rows {
+            println!("  {:>3}  {:9}  {:>4}  {:>7}  {}", pid, state, percent(*recent, interval), cpu, name);
+        }
+
+        prev = rows.iter().map(|r| (r.0, r.3)).collect();
+        prev_now = now;
+
+        // Sleep one tick at a time so 'q' is noticed promptly
+        for _ in 0..crate::interrupts::pit::ms_to_ticks(REFRESH_MS) {
+            crate::scheduler::sleep_ticks(1);
+            if let Some(KeyCode::Char('q')) = keyboard::try_read_char() {
+                crate::vga::clear_screen();
+                return;
+            }
+        }
+    }
+}
+
+fn percent(part: u64, whole: u64) -> u64 {
+    (part * 100 / whole).min(100)
+}
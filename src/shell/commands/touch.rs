@@ -1,17 +1,31 @@
 use crate::println;
+use crate::fs::error::FsError;
+use crate::fs::inode::FileType;
 
-/// touch <path> — create an empty file via VFS.
+/// touch <path>... — create empty files via VFS. An existing file keeps its
+/// contents and gets its modification time set to now, on filesystems that
+/// record one.
 pub fn run(args: &str) {
-    let path = args.trim();
-    if path.is_empty() {
+    let paths: alloc::vec::Vec<&str> = args.split_whitespace().collect();
+    if paths.is_empty() {
         println!("touch: missing file operand");
         return;
     }
 
-    let full = crate::shell::state::resolve_path(path);
-    let mut vfs = crate::fs::VFS.lock();
-    match vfs.create(&full) {
-        Ok(_) => println!("Created: {}", path),
-        Err(e) => println!("touch: {}: {}", path, e),
+    for path in paths {
+        let full = crate::shell::state::resolve_path(path);
+        let mut vfs = crate::fs::VFS.lock();
+        let result = match vfs.lookup(&full) {
+            Ok(inode) if inode.file_type == FileType::Directory => Err(FsError::IsADirectory),
+            Ok(_) => match vfs.touch(&full) {
+                Err(FsError::NotSupported) => Ok(()),
+                other => other,
+            },
+            Err(FsError::NotFound) => vfs.create(&full).map(|_| println!("Created: {}", path)),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            println!("touch: {}: {}", path, e);
+        }
     }
 }
@@ -0,0 +1,197 @@
+use spin::Mutex;
+use crate::drivers::keyboard::{self, scancodes::KeyCode};
+use crate::drivers::tty::console;
+use crate::interrupts::pit;
+use crate::serial::SerialDecoder;
+use alloc::vec::Vec;
+use crate::scheduler::{self, signal, ProcessId, ProcessState};
+use crate::shell::commands::uptime::TICKS;
+use core::sync::atomic::Ordering;
+use crate::shell::commands::testutil::{check, test_log};
+
+/// What the reader task got: (first read, second read, bytes).
+static RESULT: Mutex<Option<(usize, usize, [u8; 8])>> = Mutex::new(None);
+/// Key returned to the `read_char` task.
+static KEY: Mutex<Option<KeyCode>> = Mutex::new(None);
+/// What the sleeping task saw: (ticks slept, SIGINT posted).
+static SLEPT: Mutex<Option<(u64, bool)>> = Mutex::new(None);
+
+/// Scancode of the 'a' key (make code, set 1).
+const SCANCODE_A: u8 = 0x1E;
+
+/// ttytest — blocking console reads. A kernel task reads a line while this
+/// command plays the keyboard interrupt, feeding keys through `console::offer`.
+/// Also covers Ctrl-D, a task sleeping in `keyboard::read_char`, and Ctrl-C
+/// interrupting the foreground task.
+/// Output goes to both VGA (println) and serial (log_info).
+pub fn run(_args: &str) {
+    test_log!("=== Console Read Test Suite ===");
+
+    let mut pass = 0u32;
+    let mut fail = 0u32;
+
+    *RESULT.lock() = None;
+    let reader = scheduler::spawn(reader_task, "tty_reader");
+    scheduler::sleep_ticks(3);
+
+    let blocked = scheduler::SCHEDULER.lock().ready_queue.iter()
+        .any(|p| p.pid == reader && p.state == ProcessState::Blocked);
+    check!(pass, fail, "reader blocks until a line is typed", blocked && RESULT.lock().is_none());
+
+    // "hx<BS>i<Enter>", as the keyboard IRQ would deliver it
+    let keys = [KeyCode::Char('h'), KeyCode::Char('x'), KeyCode::Backspace, KeyCode::Char('i'), KeyCode::Enter];
+    let taken = x86_64::instructions::interrupts::without_interrupts(|| {
+        keys.iter().all(|&k| console::offer(k))
+    });
+    check!(pass, fail, "keys go to the reader, not the shell", taken);
+
+    let mut result = None;
+    for _ in 0..pit::ms_to_ticks(1000) {
+        result = *RESULT.lock();
+        if result.is_some() {
+            break;
+        }
+        scheduler::sleep_ticks(1);
+    }
+    check!(pass, fail, "reader woken by Enter", result.is_some());
+    if let Some((first, second, bytes)) = result {
+        check!(pass, fail, "backspace edits the line", &bytes[..first + second] == b"hi\n");
+        check!(pass, fail, "line longer than the buffer split over two reads", first == 2 && second == 1);
+    }
+    check!(pass, fail, "keys return to the shell after the read", !console::offer(KeyCode::Char('z')));
+
+    // Ctrl-D ends "ab" without a newline, then reads as EOF on an empty line
+    *RESULT.lock() = None;
+    let reader = scheduler::spawn(eof_task, "tty_eof");
+    wait_blocked(reader);
+    let taken = x86_64::instructions::interrupts::without_interrupts(|| {
+        console::offer(KeyCode::Char('a')) && console::offer(KeyCode::Char('b')) && console::offer(KeyCode::Ctrl('d'))
+    });
+    wait_blocked(reader);
+    let eof_taken = x86_64::instructions::interrupts::without_interrupts(|| console::offer(KeyCode::Ctrl('d')));
+    let result = wait_result();
+    check!(pass, fail, "Ctrl-D keys go to the reader", taken && eof_taken);
+    check!(pass, fail, "Ctrl-D completes a line without a newline",
+        matches!(result, Some((2, _, bytes)) if &bytes[..2] == b"ab"));
+    check!(pass, fail, "Ctrl-D on an empty line reads 0 (EOF)", matches!(result, Some((_, 0, _))));
+
+    // read_char sleeps instead of polling, and a key interrupt wakes it
+    while keyboard::try_read_char().is_some() {}
+    *KEY.lock() = None;
+    let reader = scheduler::spawn(key_task, "tty_key");
+    let blocked = wait_blocked(reader);
+    check!(pass, fail, "read_char blocks on an empty buffer", blocked && KEY.lock().is_none());
+    x86_64::instructions::interrupts::without_interrupts(|| keyboard::push_scancode(SCANCODE_A));
+    let mut key = None;
+    for _ in 0..pit::ms_to_ticks(1000) {
+        key = *KEY.lock();
+        if key.is_some() {
+            break;
+        }
+        scheduler::sleep_ticks(1);
+    }
+    check!(pass, fail, "key interrupt wakes read_char", key == Some(KeyCode::Char('a')));
+
+    // Ctrl-C cuts the foreground task's sleep short with a SIGINT
+    *SLEPT.lock() = None;
+    let sleeper = scheduler::spawn(sleep_task, "tty_sleep");
+    wait_blocked(sleeper);
+    signal::set_foreground(Some(sleeper));
+    x86_64::instructions::interrupts::without_interrupts(|| keyboard::push_key(KeyCode::Ctrl('c')));
+    let mut slept = None;
+    for _ in 0..pit::ms_to_ticks(1000) {
+        slept = *SLEPT.lock();
+        if slept.is_some() {
+            break;
+        }
+        scheduler::sleep_ticks(1);
+    }
+    signal::set_foreground(None);
+    check!(pass, fail, "Ctrl-C wakes the foreground task early",
+        matches!(slept, Some((ticks, true)) if ticks < pit::ms_to_ticks(1000)));
+    check!(pass, fail, "Ctrl-C goes to the foreground task, not the shell", keyboard::try_read_char().is_none());
+    x86_64::instructions::interrupts::without_interrupts(|| keyboard::push_key(KeyCode::Ctrl('c')));
+    check!(pass, fail, "Ctrl-C with nothing in the foreground is an ordinary key",
+        keyboard::try_read_char() == Some(KeyCode::Ctrl('c')));
+
+    // Serial console input, as a terminal emulator sends it
+    let decode = |bytes: &[u8]| {
+        let mut decoder = SerialDecoder::new();
+        bytes.iter().filter_map(|&b| decoder.feed(b)).collect::<Vec<_>>()
+    };
+    check!(pass, fail, "serial: printable bytes are characters",
+        decode(b"ls -l") == [KeyCode::Char('l'), KeyCode::Char('s'), KeyCode::Char(' '), KeyCode::Char('-'), KeyCode::Char('l')]);
+    check!(pass, fail, "serial: CR, LF and CR LF are each one Enter",
+        decode(b"\r\n\ra\n") == [KeyCode::Enter, KeyCode::Enter, KeyCode::Char('a'), KeyCode::Enter]);
+    check!(pass, fail, "serial: DEL and BS are Backspace", decode(b"\x7f\x08") == [KeyCode::Backspace, KeyCode::Backspace]);
+    check!(pass, fail, "serial: C0 controls are Ctrl keys", decode(b"\x03\x04") == [KeyCode::Ctrl('c'), KeyCode::Ctrl('d')]);
+    check!(pass, fail, "serial: arrow and page escape sequences",
+        decode(b"\x1b[A\x1b[D\x1b[5~\x1b[6~") == [KeyCode::ArrowUp, KeyCode::ArrowLeft, KeyCode::PageUp, KeyCode::PageDown]);
+    check!(pass, fail, "serial: F1 as sent by xterm", decode(b"\x1bOP") == [KeyCode::F(1)]);
+    check!(pass, fail, "serial: unknown sequences dropped whole", decode(b"\x1b[2~\x1b[1;5Hx") == [KeyCode::Char('x')]);
+
+    test_log!("=== Results: {}/{} passed ===", pass, pass + fail);
+    if fail > 0 {
+        test_log!("{} test(s) FAILED.", fail);
+    }
+}
+
+/// Wait up to a second for `pid` to block; true if it did.
+fn wait_blocked(pid: ProcessId) -> bool {
+    for _ in 0..pit::ms_to_ticks(1000) {
+        scheduler::sleep_ticks(1);
+        let blocked = scheduler::SCHEDULER.lock().ready_queue.iter()
+            .any(|p| p.pid == pid && p.state == ProcessState::Blocked);
+        if blocked {
+            return true;
+        }
+    }
+    false
+}
+
+/// Wait up to a second for a reader task to post its result.
+fn wait_result() -> Option<(usize, usize, [u8; 8])> {
+    for _ in 0..pit::ms_to_ticks(1000) {
+        let result = *RESULT.lock();
+        if result.is_some() {
+            return result;
+        }
+        scheduler::sleep_ticks(1);
+    }
+    None
+}
+
+/// Reads "ab" (ended by Ctrl-D), then an immediate Ctrl-D.
+fn eof_task() {
+    let mut bytes = [0u8; 8];
+    let first = console::read_line(&mut bytes);
+    let second = console::read_line(&mut bytes[first..]);
+    *RESULT.lock() = Some((first, second, bytes));
+    scheduler::exit_current(0);
+}
+
+/// Waits for one key from the shell's keyboard buffer.
+fn key_task() {
+    let key = keyboard::read_char();
+    *KEY.lock() = Some(key);
+    scheduler::exit_current(0);
+}
+
+/// Sleeps for ten seconds unless interrupted, then reports how long it slept.
+fn sleep_task() {
+    let start = TICKS.load(Ordering::Relaxed);
+    scheduler::sleep_ticks(pit::ms_to_ticks(10_000));
+    let slept = TICKS.load(Ordering::Relaxed).wrapping_sub(start);
+    *SLEPT.lock() = Some((slept, signal::take_interrupt()));
+    scheduler::exit_current(0);
+}
+
+/// Reads "hi\n" through a 2-byte buffer first, so the newline is left for
+/// a second read that must return without blocking.
+fn reader_task() {
+    let mut bytes = [0u8; 8];
+    let first = console::read_line(&mut bytes[..2]);
+    let second = console::read_line(&mut bytes[first..]);
+    *RESULT.lock() = Some((first, second, bytes));
+    scheduler::exit_current(0);
+}
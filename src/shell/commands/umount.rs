@@ -0,0 +1,17 @@
+use crate::println;
+
+/// umount <path> — flush and detach a mounted filesystem. Without arguments, list mounts.
+pub fn run(args: &str) {
+    let target = args.trim();
+    if target.is_empty() {
+        for m in crate::fs::VFS.lock().mounts().iter().rev() {
+            println!("{} on {} ({})", m.fs_name, m.path, if m.read_only { "ro" } else { "rw" });
+        }
+        return;
+    }
+
+    let path = crate::shell::state::resolve_path(target);
+    if let Err(e) = crate::fs::VFS.lock().unmount(&path) {
+        println!("umount: {}: {}", path, e);
+    }
+}
@@ -1,21 +1,31 @@
 use crate::println;
 use core::sync::atomic::{AtomicU64, Ordering};
+use crate::interrupts::pit;
 
 /// Global tick counter incremented by the PIT timer interrupt handler.
 pub static TICKS: AtomicU64 = AtomicU64::new(0);
 
-/// Called by the timer interrupt handler every tick (~18.2 Hz default PIT).
+/// Time since boot, advanced by each tick's length at the rate it fired at,
+/// so it stays right across `pit::set_timer_hz` calls.
+static NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// Called by the timer interrupt handler every tick.
 pub fn tick() {
     TICKS.fetch_add(1, Ordering::Relaxed);
+    NANOS.fetch_add(pit::nanos_per_tick(), Ordering::Relaxed);
+}
+
+/// Nanoseconds since the timer started, in steps of one tick.
+pub fn nanos_since_boot() -> u64 {
+    NANOS.load(Ordering::Relaxed)
 }
 
 pub fn run(_args: &str) {
     let ticks = TICKS.load(Ordering::Relaxed);
-    // Default PIT fires ~18.2 times per second
-    let total_secs = ticks / 18;
+    let total_secs = nanos_since_boot() / 1_000_000_000;
     let hours = total_secs / 3600;
     let mins = (total_secs % 3600) / 60;
     let secs = total_secs % 60;
 
-    println!("up {:02}:{:02}:{:02} ({} ticks)", hours, mins, secs, ticks);
+    println!("up {:02}:{:02}:{:02} ({} ticks at {} Hz)", hours, mins, secs, ticks, pit::timer_hz());
 }
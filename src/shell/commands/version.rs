@@ -1,8 +1,9 @@
 use crate::println;
+use crate::system_info as info;
 
 pub fn run(_args: &str) {
-    println!("AtomicOS v0.2.0 (x86_64)");
-    println!("Kernel:  Rust no_std + alloc");
-    println!("Boot:    Multiboot2 / GRUB");
-    println!("Build:   GNU Toolchain (nasm + ld)");
+    println!("{} v{} ({})", info::OS_NAME, info::VERSION, info::ARCH);
+    println!("Kernel:  {}", info::KERNEL);
+    println!("Boot:    {}", info::BOOT);
+    println!("Build:   {}", info::TOOLCHAIN);
 }
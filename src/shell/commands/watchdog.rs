@@ -0,0 +1,30 @@
+use crate::println;
+use core::sync::atomic::Ordering;
+use crate::scheduler::watchdog::{WARN_QUANTA, KILL_QUANTA};
+
+/// watchdog [warn <n> | kill <n>] — show or tune the hung-task watchdog limits.
+pub fn run(args: &str) {
+    let parts: alloc::vec::Vec<&str> = args.split_whitespace().collect();
+
+    match parts.as_slice() {
+        [] => {
+            let warn = WARN_QUANTA.load(Ordering::Relaxed);
+            let kill = KILL_QUANTA.load(Ordering::Relaxed);
+            println!("watchdog: warn after {} quanta{}", warn, if warn == 0 { " (disabled)" } else { "" });
+            println!("watchdog: kill after {} quanta{}", kill, if kill == 0 { " (disabled)" } else { "" });
+        }
+        [which @ ("warn" | "kill"), value] => {
+            let n: u64 = match value.parse() {
+                Ok(v) => v,
+                Err(_) => { println!("watchdog: invalid number: {}", value); return; }
+            };
+            if *which == "warn" {
+                WARN_QUANTA.store(n, Ordering::Relaxed);
+            } else {
+                KILL_QUANTA.store(n, Ordering::Relaxed);
+            }
+            println!("watchdog: {} limit set to {} quanta", which, n);
+        }
+        _ => println!("Usage: watchdog [warn <quanta> | kill <quanta>]  (0 disables)"),
+    }
+}
@@ -1,5 +1,6 @@
 use crate::println;
 
+/// whoami — print the logged-in user.
 pub fn run(_args: &str) {
-    println!("root");
+    println!("{}", crate::system_info::current_user());
 }
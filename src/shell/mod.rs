@@ -1,50 +1,248 @@
 pub mod commands;
+pub mod pipeline;
+pub mod redirect;
 pub mod state;
 
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 use crate::println;
+use crate::scheduler::TrapFrame;
+use crate::scheduler::fpu::FpuState;
 
-/// Parse input line into command + arguments, then dispatch.
+/// Exit code reported when an external program could not be loaded
+/// (missing file, bad ELF, ...), matching the usual shell convention.
+pub const EXEC_FAILED: u64 = 127;
+
+/// Run an ELF program in the foreground and return its exit code.
+///
+/// The shell forks (`sys_fork`), the child replaces itself with the program
+/// and `argv = [path, args...]` (`sys_exec`), and the shell blocks in
+/// `sys_wait` until it exits. The child inherits the shell's fds, so its
+/// output lands on the terminal unless redirected, and is the foreground
+/// task Ctrl-C interrupts. A program that can't be loaded makes the child
+/// exit with `EXEC_FAILED`; so does a failed fork, here in the shell.
+pub fn run_external(path: &str, args: &str) -> u64 {
+    let Some(pid) = spawn_external(path, args) else { return EXEC_FAILED };
+
+    crate::scheduler::signal::set_foreground(Some(pid));
+    let result = crate::scheduler::sys_wait(pid.0, 0);
+    crate::scheduler::signal::set_foreground(None);
+    match result {
+        Ok(Some((_, status))) => crate::syscalls::wait::shell_code(status),
+        _ => EXEC_FAILED,
+    }
+}
+
+/// What a forked child execs.
+struct ExecRequest {
+    path: String,
+    argv: Vec<String>,
+    envp: Vec<String>,
+}
+
+/// The stack the syscall entry would have built for a `sys_fork` from user
+/// mode: the FXSAVE area right below the `TrapFrame` (see `saved_fpu`).
+#[repr(C)]
+struct ForkFrame {
+    fpu: FpuState,
+    frame: TrapFrame,
+}
+
+/// RFLAGS with only IF and the always-one bit 1 set.
+const RFLAGS_IF: u64 = 0x202;
+
+/// Fork the shell into a child that execs `path`, and return the child's
+/// PID without waiting for it. None if the fork failed.
+///
+/// The shell runs as PID 0 in Ring 0 and did not come in through int 0x80,
+/// so it builds the frame `sys_fork` copies itself: the child comes out of
+/// the fork in `exec_child`, in Ring 0 on its own kernel stack, with the
+/// request in RDI.
+fn spawn_external(path: &str, args: &str) -> Option<crate::scheduler::ProcessId> {
+    let mut argv = vec![String::from(path)];
+    argv.extend(args.split_whitespace().map(String::from));
+    let request = Box::into_raw(Box::new(ExecRequest {
+        path: state::resolve_path(path),
+        argv,
+        envp: state::environment(),
+    }));
+
+    let selectors = &crate::interrupts::gdt::GDT.1;
+    let fork = ForkFrame {
+        fpu: FpuState::new(),
+        frame: TrapFrame {
+            rdi: request as u64,
+            rip: exec_child as *const () as u64,
+            cs: selectors.kernel_code.0 as u64,
+            rflags: RFLAGS_IF,
+            ss: selectors.kernel_data.0 as u64,
+            // The rest starts zeroed; `sys_fork` gives the child its stack
+            // SAFETY: every field is a plain integer
+            ..unsafe { core::mem::zeroed() }
+        },
+    };
+
+    // As in a syscall: nothing else runs while the child is built
+    let pid = x86_64::instructions::interrupts::without_interrupts(|| {
+        crate::scheduler::sys_fork(&fork.frame)
+    });
+    if pid == u64::MAX {
+        // SAFETY: no child was created to take it
+        drop(unsafe { Box::from_raw(request) });
+        println!("{}: fork failed", path);
+        return None;
+    }
+    Some(crate::scheduler::ProcessId(pid))
+}
+
+/// Where a child forked by `spawn_external` starts: exec the program, or
+/// report why it could not and exit with `EXEC_FAILED`.
+extern "C" fn exec_child(request: *mut ExecRequest) -> ! {
+    // SAFETY: `spawn_external` leaked the request for this child alone
+    let ExecRequest { path, argv, envp } = *unsafe { Box::from_raw(request) };
+    if let Err((e, path)) = crate::scheduler::exec_owned(path, argv, envp) {
+        println!("{}: {}", path, e);
+    }
+    crate::scheduler::exit_current(EXEC_FAILED);
+    unreachable!();
+}
+
+/// True if `cmd` names an executable on disk rather than a builtin.
+fn is_external(cmd: &str) -> bool {
+    (cmd.contains('/') || cmd.ends_with(".elf"))
+        && crate::fs::VFS.lock().exists(&state::resolve_path(cmd))
+}
+
+/// Parse input line into command + arguments, then dispatch, with any
+/// `<`, `>` and `>>` redirections in place for the command's duration.
 pub fn exec_command(input: &str) {
     let trimmed = input.trim();
     if trimmed.is_empty() {
         return;
     }
 
-    // Split by whitespace: first token = command, rest = args
-    let parts: alloc::vec::Vec<&str> = trimmed.splitn(2, ' ').collect();
-    let cmd = parts[0];
-    let args = if parts.len() > 1 { parts[1] } else { "" };
-
-    match cmd {
-        "echo"        => commands::echo::run(args),
-        "ls"          => commands::ls::run(args),
-        "cat"         => commands::cat::run(args),
-        "clear"       => commands::clear::run(args),
-        "help"        => commands::help::run(args),
-        "date"        => commands::date::run(args),
-        "whoami"      => commands::whoami::run(args),
-        "pwd"         => commands::pwd::run(args),
-        "uptime"      => commands::uptime::run(args),
-        "version"     => commands::version::run(args),
-        "neofetch"    => commands::neofetch::run(args),
-        "cd"          => commands::cd::run(args),
-        "ps"          => commands::ps::run(args),
-        "kill"        => commands::kill::run(args),
-        "mkdir"       => commands::mkdir::run(args),
-        "rm"          => commands::rm::run(args),
-        "cp"          => commands::cp::run(args),
-        "mv"          => commands::mv::run(args),
-        "catbin"      => commands::catbin::run(args),
-        "objdump"     => commands::objdump::run(args),
-        "shellscript" => commands::shellscript::run(args),
-        "log"         => commands::log::run(args),
-        "spawn"       => commands::spawn::run(args),
-        "yield"       => commands::yield_cmd::run(args),
-        "touch"       => commands::touch::run(args),
-        "vfstest"     => commands::vfstest::run(args),
-        "write"       => commands::write::run(args),
-        "atatest"     => commands::atatest::run(args),
-        "exec"        => commands::exec::run(args),
-        _             => println!("{}: command not found", cmd),
+    match redirect::parse(trimmed) {
+        Ok(None) => dispatch(trimmed),
+        Ok(Some((line, redirects))) => redirect::run(&redirects, || dispatch(&line)),
+        Err(msg) => println!("sh: {}", msg),
+    }
+}
+
+/// Run one command line, free of redirections.
+fn dispatch(line: &str) {
+    if line.contains('|') {
+        pipeline::run(line);
+        return;
+    }
+    let (cmd, args) = split_command(line);
+    if cmd.is_empty() {
+        return;
+    }
+
+    if let Some(run) = builtin(cmd) {
+        run(args);
+    } else if is_external(cmd) {
+        let code = run_external(cmd, args);
+        if code != 0 {
+            println!("[exit {}]", code);
+        }
+    } else {
+        println!("{}: command not found", cmd);
+    }
+}
+
+/// Split a command line at the first space: (command, arguments).
+fn split_command(line: &str) -> (&str, &str) {
+    let line = line.trim();
+    match line.split_once(' ') {
+        Some((cmd, args)) => (cmd, args),
+        None => (line, ""),
     }
 }
+
+/// The builtin named `cmd`, if there is one.
+fn builtin(cmd: &str) -> Option<fn(&str)> {
+    let run: fn(&str) = match cmd {
+        "echo"        => commands::echo::run,
+        "ls"          => commands::ls::run,
+        "cat"         => commands::cat::run,
+        "clear"       => commands::clear::run,
+        "help"        => commands::help::run,
+        "date"        => commands::date::run,
+        "whoami"      => commands::whoami::run,
+        "pwd"         => commands::pwd::run,
+        "uptime"      => commands::uptime::run,
+        "version"     => commands::version::run,
+        "neofetch"    => commands::neofetch::run,
+        "cd"          => commands::cd::run,
+        "ps"          => commands::ps::run,
+        "kill"        => commands::kill::run,
+        "mkdir"       => commands::mkdir::run,
+        "rm"          => commands::rm::run,
+        "cp"          => commands::cp::run,
+        "mv"          => commands::mv::run,
+        "catbin"      => commands::catbin::run,
+        "objdump"     => commands::objdump::run,
+        "shellscript" => commands::shellscript::run,
+        "log"         => commands::log::run,
+        "dmesg"       => commands::dmesg::run,
+        "spawn"       => commands::spawn::run,
+        "yield"       => commands::yield_cmd::run,
+        "sleep"       => commands::sleep::run,
+        "touch"       => commands::touch::run,
+        "vfstest"     => commands::vfstest::run,
+        "write"       => commands::write::run,
+        "atatest"     => commands::atatest::run,
+        "exec"        => commands::exec::run,
+        "watchdog"    => commands::watchdog::run,
+        "ln"          => commands::ln::run,
+        "chmod"       => commands::chmod::run,
+        "readlink"    => commands::readlink::run,
+        "hostname"    => commands::hostname::run,
+        "su"          => commands::su::run,
+        "login"       => commands::su::run,
+        "fsck"        => commands::fsck::run,
+        "diskinfo"    => commands::diskinfo::run,
+        "panic"       => commands::panic::run,
+        "exectest"    => commands::exectest::run,
+        "fattest"     => commands::fattest::run,
+        "fatimgtest"  => commands::fatimgtest::run,
+        "sync"        => commands::sync::run,
+        "umount"      => commands::umount::run,
+        "mount"       => commands::mount::run,
+        "remount"     => commands::mount::remount,
+        "reboot"      => commands::reboot::run,
+        "poweroff"    => commands::reboot::poweroff,
+        "shutdown"    => commands::reboot::shutdown,
+        "dd"          => commands::dd::run,
+        "locktest"    => commands::locktest::run,
+        "regs"        => commands::regs::run,
+        "sum"         => commands::sum::run,
+        "fputest"     => commands::fputest::run,
+        "top"         => commands::top::run,
+        "free"        => commands::free::run,
+        "meminfo"     => commands::meminfo::run,
+        "lsblk"       => commands::lsblk::run,
+        "isotest"     => commands::isotest::run,
+        "ttytest"     => commands::ttytest::run,
+        "preempttest" => commands::preempttest::run,
+        "priotest"    => commands::priotest::run,
+        "jointest"    => commands::jointest::run,
+        "spsctest"    => commands::spsctest::run,
+        "stacktest"   => commands::stacktest::run,
+        "redirtest"   => commands::redirtest::run,
+        "pipetest"    => commands::pipetest::run,
+        "frametest"   => commands::frametest::run,
+        "heaptest"    => commands::heaptest::run,
+        "slabtest"    => commands::slabtest::run,
+        "rtctest"     => commands::rtctest::run,
+        "pittest"     => commands::pittest::run,
+        "mousetest"   => commands::mousetest::run,
+        "scrolltest"  => commands::scrolltest::run,
+        "ansitest"    => commands::ansitest::run,
+        _             => return None,
+    };
+    Some(run)
+}
@@ -0,0 +1,117 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use spin::Mutex;
+use x86_64::instructions::interrupts;
+use crate::fs::fd::File;
+use crate::println;
+use crate::scheduler::{self, ProcessId};
+
+const STDIN: usize = 0;
+const STDOUT: usize = 1;
+
+/// Command lines of builtin stages whose task has not picked them up yet.
+static PENDING: Mutex<Vec<(ProcessId, String)>> = Mutex::new(Vec::new());
+
+/// Run `a | b | c`. Every stage is a child process of the shell: programs
+/// are forked off and exec'd, builtins run in a kernel task. Between two
+/// stages the shell creates a pipe and puts its ends over its own stdout and
+/// stdin while spawning the writer and the reader, which inherit them (the
+/// dup2 of fork+dup2+exec). The shell then drops its copies, so each end is
+/// only held by the one stage using it and EOF reaches the reader once the
+/// writer exits. Waits for all stages; nothing runs if a command is not found.
+pub fn run(line: &str) {
+    let stages: Vec<&str> = line.split('|').map(str::trim).collect();
+    for stage in &stages {
+        let (cmd, _) = super::split_command(stage);
+        if cmd.is_empty() {
+            println!("sh: syntax error: empty command in pipeline");
+            return;
+        }
+        if super::builtin(cmd).is_none() && !super::is_external(cmd) {
+            println!("{}: command not found", cmd);
+            return;
+        }
+    }
+
+    let mut pids = Vec::new();
+    let mut stdin = None;
+    for (i, stage) in stages.iter().enumerate() {
+        let (next_stdin, stdout) = if i + 1 < stages.len() {
+            let (read_end, write_end) = File::new_pipe();
+            (Some(read_end), Some(write_end))
+        } else {
+            (None, None)
+        };
+
+        let saved_stdin = stdin.take().map(|f| scheduler::replace_current_fd(STDIN, Some(f)));
+        let saved_stdout = stdout.map(|f| scheduler::replace_current_fd(STDOUT, Some(f)));
+        let pid = spawn_stage(stage);
+        // Putting the shell's own descriptors back drops its copies of the ends
+        if let Some(old) = saved_stdout {
+            scheduler::replace_current_fd(STDOUT, old);
+        }
+        if let Some(old) = saved_stdin {
+            scheduler::replace_current_fd(STDIN, old);
+        }
+
+        pids.extend(pid);
+        stdin = next_stdin;
+    }
+
+    let mut status = 0;
+    for pid in pids {
+        status = match scheduler::sys_wait(pid.0, 0) {
+            Ok(Some((_, status))) => crate::syscalls::wait::shell_code(status),
+            _ => super::EXEC_FAILED,
+        };
+    }
+    if status != 0 {
+        println!("[exit {}]", status);
+    }
+}
+
+/// Start one stage with the shell's current stdin and stdout.
+fn spawn_stage(stage: &str) -> Option<ProcessId> {
+    let (cmd, args) = super::split_command(stage);
+    if super::builtin(cmd).is_none() {
+        return super::spawn_external(cmd, args);
+    }
+    // The task must not look for its command line before it is queued
+    Some(interrupts::without_interrupts(|| {
+        let pid = scheduler::spawn_child(builtin_stage, cmd);
+        PENDING.lock().push((pid, String::from(stage)));
+        pid
+    }))
+}
+
+/// Body of a builtin stage's task. Builtins print to the screen, so the
+/// output is captured and written to the task's stdout when it is done.
+fn builtin_stage() {
+    let pid = scheduler::current_pid();
+    let line = {
+        let mut pending = PENDING.lock();
+        pending.iter().position(|(p, _)| *p == pid).map(|i| pending.swap_remove(i).1)
+    };
+
+    if let Some(line) = line {
+        let (cmd, args) = super::split_command(&line);
+        if let Some(run) = super::builtin(cmd) {
+            crate::vga::start_capture(pid.0);
+            run(args);
+            let output = crate::vga::take_capture(pid.0).unwrap_or_default();
+            write_stdout(output.as_bytes());
+        }
+    }
+    scheduler::exit_current(0);
+}
+
+/// Write all of `data` to stdout, giving up if the reader has gone away.
+fn write_stdout(mut data: &[u8]) {
+    while !data.is_empty() {
+        let n = crate::syscalls::sys_write_fd(STDOUT, data);
+        if crate::syscalls::errno::is_err(n) || n == 0 {
+            return;
+        }
+        data = &data[n as usize..];
+    }
+}
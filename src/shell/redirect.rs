@@ -0,0 +1,128 @@
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use spin::Mutex;
+use crate::fs::fd::{File, O_APPEND, O_CREAT, O_RDONLY, O_TRUNC, O_WRONLY};
+use crate::println;
+
+/// Descriptors replaced while a redirected command runs.
+const STDIN: usize = 0;
+const STDOUT: usize = 1;
+
+/// The `<`, `>` and `>>` targets of a command line, as typed.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Redirects {
+    pub stdin: Option<String>,
+    pub stdout: Option<String>,
+    /// `>>`: append to `stdout` rather than truncating it.
+    pub append: bool,
+}
+
+/// Split the redirections off a command line. Operators may stand alone
+/// (`cat a > b`) or be attached to their target (`cat a >b`); the last one of
+/// each direction wins. Returns `None` for a line without any, otherwise the
+/// remaining words and the targets.
+pub fn parse(line: &str) -> Result<Option<(String, Redirects)>, &'static str> {
+    if !line.contains(['<', '>']) {
+        return Ok(None);
+    }
+
+    let mut redirects = Redirects::default();
+    let mut words: Vec<&str> = Vec::new();
+    let mut tokens = line.split_whitespace();
+    while let Some(token) = tokens.next() {
+        let (op, rest) = if let Some(rest) = token.strip_prefix(">>") {
+            (">>", rest)
+        } else if let Some(rest) = token.strip_prefix('>') {
+            (">", rest)
+        } else if let Some(rest) = token.strip_prefix('<') {
+            ("<", rest)
+        } else {
+            words.push(token);
+            continue;
+        };
+
+        let target = if rest.is_empty() { tokens.next() } else { Some(rest) };
+        let target = match target {
+            Some(t) if !t.starts_with(['<', '>']) => String::from(t),
+            _ => return Err("syntax error: redirection without a file name"),
+        };
+        if op == "<" {
+            redirects.stdin = Some(target);
+        } else {
+            redirects.append = op == ">>";
+            redirects.stdout = Some(target);
+        }
+    }
+
+    Ok(Some((words.join(" "), redirects)))
+}
+
+/// Run `command` with the shell's stdin and stdout pointed at the redirect
+/// targets, then put the terminal back. Builtins print straight to the
+/// screen, so their output is captured and written to the target afterwards;
+/// programs the shell starts inherit the descriptors and write to it directly.
+/// If a target can't be opened, the command does not run.
+pub fn run(redirects: &Redirects, command: impl FnOnce()) {
+    let stdin = match &redirects.stdin {
+        Some(path) => match open(path, O_RDONLY) {
+            Some(file) => Some(file),
+            None => return,
+        },
+        None => None,
+    };
+    let stdout = match &redirects.stdout {
+        Some(path) => {
+            let flags = O_WRONLY | O_CREAT | if redirects.append { O_APPEND } else { O_TRUNC };
+            match open(path, flags) {
+                Some(file) => Some(file),
+                None => return,
+            }
+        }
+        None => None,
+    };
+
+    let saved_stdin = stdin.map(|file| crate::scheduler::replace_current_fd(STDIN, Some(file)));
+    let saved_stdout = stdout.clone().map(|file| crate::scheduler::replace_current_fd(STDOUT, Some(file)));
+    let pid = crate::scheduler::current_pid().0;
+    if stdout.is_some() {
+        crate::vga::start_capture(pid);
+    }
+
+    command();
+
+    if let Some(file) = stdout {
+        let text = crate::vga::take_capture(pid).unwrap_or_default();
+        if !text.is_empty() {
+            if let Err(e) = file.lock().write(text.as_bytes()) {
+                let path = redirects.stdout.as_deref().unwrap_or("");
+                println!("sh: {}: {}", path, e);
+            }
+        }
+    }
+    if let Some(old) = saved_stdout {
+        crate::scheduler::replace_current_fd(STDOUT, old);
+    }
+    if let Some(old) = saved_stdin {
+        crate::scheduler::replace_current_fd(STDIN, old);
+    }
+}
+
+/// True if the running command's stdin is a file or pipe rather than the
+/// terminal.
+pub fn stdin_redirected() -> bool {
+    crate::scheduler::current_fd(STDIN)
+        .is_some_and(|file| !matches!(file.lock().file_type, crate::fs::fd::FileType::Console))
+}
+
+/// Open a redirect target relative to the shell's cwd, reporting failures
+/// on the terminal.
+fn open(path: &str, flags: u64) -> Option<Arc<Mutex<File>>> {
+    match File::open(&super::state::resolve_path(path), flags) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            println!("sh: {}: {}", path, e);
+            None
+        }
+    }
+}
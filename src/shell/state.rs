@@ -1,70 +1,65 @@
 use alloc::string::String;
 use alloc::format;
-use alloc::vec::Vec;
 use spin::Mutex;
 use lazy_static::lazy_static;
 
-/// Kernel log ring buffer.
-pub struct KernelLog {
-    pub entries: Vec<String>,
+lazy_static! {
+    /// Previous working directory, for `cd -`.
+    pub static ref OLDPWD: Mutex<Option<String>> = Mutex::new(None);
 }
 
-impl KernelLog {
-    pub fn new() -> Self {
-        KernelLog { entries: Vec::new() }
-    }
+/// Home directory used for `cd` with no arguments and `~` expansion.
+pub const HOME: &str = "/";
 
-    pub fn push(&mut self, msg: String) {
-        if self.entries.len() >= 64 {
-            self.entries.remove(0);
-        }
-        self.entries.push(msg);
-    }
+/// The shell's working directory: the cwd of the shell process itself,
+/// which programs it runs inherit.
+pub fn cwd() -> String {
+    crate::scheduler::current_cwd()
 }
 
-lazy_static! {
-    pub static ref KLOG: Mutex<KernelLog> = Mutex::new(KernelLog::new());
-    pub static ref CWD: Mutex<String> = Mutex::new(String::from("/"));
+/// Change the working directory, remembering the old one in `OLDPWD`.
+pub fn set_cwd(path: String) {
+    let old = cwd();
+    crate::scheduler::set_current_cwd(path);
+    *OLDPWD.lock() = Some(old);
+}
+
+/// Environment handed to the programs the shell starts, as `NAME=value`.
+pub fn environment() -> alloc::vec::Vec<String> {
+    alloc::vec![
+        format!("HOME={}", HOME),
+        format!("PWD={}", cwd()),
+        format!("USER={}", crate::system_info::current_user()),
+        format!("HOSTNAME={}", crate::system_info::hostname()),
+    ]
 }
 
 /// Resolve a path relative to the current working directory.
-/// Handles absolute paths, relative paths, `.` and `..`.
+/// Handles absolute paths, relative paths, `~`, `.` and `..` (clamped at `/`).
 pub fn resolve_path(input: &str) -> String {
-    let cwd = CWD.lock().clone();
-    let raw = if input.starts_with('/') {
-        String::from(input)
-    } else {
-        if cwd == "/" {
-            format!("/{}", input)
-        } else {
-            format!("{}/{}", cwd, input)
-        }
-    };
-
-    // Normalize: split by '/', handle . and ..
-    let mut parts: Vec<&str> = Vec::new();
-    for segment in raw.split('/') {
-        match segment {
-            "" | "." => {},
-            ".." => { parts.pop(); },
-            s => parts.push(s),
-        }
+    if input == "~" || input.starts_with("~/") {
+        return crate::fs::absolute_path(HOME, input[1..].trim_start_matches('/'));
     }
+    crate::fs::absolute_path(&cwd(), input)
+}
 
-    if parts.is_empty() {
-        String::from("/")
-    } else {
-        let mut result = String::new();
-        for p in parts {
-            result.push('/');
-            result.push_str(p);
+/// Resolve the destination of a copy/move: like `resolve_path`, but a
+/// destination that is an existing directory receives the source's file name.
+pub fn resolve_dest(src: &str, dst: &str) -> String {
+    let target = resolve_path(dst);
+    if crate::fs::VFS.lock().is_dir(&target) {
+        let name = src.rsplit('/').next().unwrap_or(src);
+        if target == "/" {
+            format!("/{}", name)
+        } else {
+            format!("{}/{}", target, name)
         }
-        result
+    } else {
+        target
     }
 }
 
-/// Helper: log a command execution to the kernel log buffer.
+/// Helper: record a shell event in the kernel log.
 pub fn log_cmd(msg: &str) {
-    let ticks = crate::shell::commands::uptime::TICKS.load(core::sync::atomic::Ordering::Relaxed);
-    KLOG.lock().push(format!("[{}] {}", ticks, msg));
+    crate::log_info!("shell: {}", msg);
 }
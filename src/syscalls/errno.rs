@@ -0,0 +1,113 @@
+// Syscall error numbers.
+//
+// Convention: a syscall that fails returns `-(errno)` reinterpreted as `u64`,
+// so every error has the high bit set and lies in `[-4095, -1]`. Successful
+// calls return small non-negative values (byte counts, fds, PIDs, 0).
+// User code checks `(ret as i64) < 0` and negates to recover the errno.
+// The values match Linux so existing tooling reads them naturally.
+
+use crate::fs::error::FsError;
+use crate::fs::pipe::PipeError;
+use crate::loader::elf::ExecError;
+use crate::scheduler::signal::SignalError;
+use crate::scheduler::WaitError;
+
+pub const EPERM: u64   = 1;
+pub const ENOENT: u64  = 2;
+pub const ESRCH: u64   = 3;
+pub const EINTR: u64   = 4;
+pub const EIO: u64     = 5;
+pub const E2BIG: u64   = 7;
+pub const ENOEXEC: u64 = 8;
+pub const EBADF: u64   = 9;
+pub const ECHILD: u64  = 10;
+pub const EAGAIN: u64  = 11;
+pub const ENOMEM: u64  = 12;
+pub const EACCES: u64  = 13;
+pub const EFAULT: u64  = 14;
+pub const EEXIST: u64  = 17;
+pub const EXDEV: u64   = 18;
+pub const ENODEV: u64  = 19;
+pub const ENOTDIR: u64 = 20;
+pub const EISDIR: u64  = 21;
+pub const EINVAL: u64  = 22;
+pub const EMFILE: u64  = 24;
+pub const ENOSPC: u64  = 28;
+pub const ESPIPE: u64  = 29;
+pub const EROFS: u64   = 30;
+pub const EPIPE: u64   = 32;
+pub const ERANGE: u64  = 34;
+pub const ENAMETOOLONG: u64 = 36;
+pub const ENOSYS: u64  = 38;
+pub const ELOOP: u64   = 40;
+pub const ENOTSUP: u64 = 95;
+
+/// Largest errno value; anything in `[-MAX_ERRNO, -1]` is an error return.
+pub const MAX_ERRNO: u64 = 4095;
+
+/// Encode an errno as a syscall return value.
+pub const fn err(errno: u64) -> u64 {
+    (errno as i64).wrapping_neg() as u64
+}
+
+/// Returns true if a syscall return value encodes an error.
+pub const fn is_err(ret: u64) -> bool {
+    ret > u64::MAX - MAX_ERRNO
+}
+
+/// Map a filesystem error onto the matching errno.
+pub fn from_fs_error(e: &FsError) -> u64 {
+    match e {
+        FsError::NotFound      => ENOENT,
+        FsError::AlreadyExists => EEXIST,
+        FsError::NotADirectory => ENOTDIR,
+        FsError::IsADirectory  => EISDIR,
+        FsError::InvalidPath   => EINVAL,
+        FsError::IoError       => EIO,
+        FsError::NoSpace       => ENOSPC,
+        FsError::NotMounted    => ENODEV,
+        FsError::NotSupported  => ENOTSUP,
+        FsError::TooManyLinks  => ELOOP,
+        FsError::ReadOnly      => EROFS,
+        FsError::CrossDevice   => EXDEV,
+        FsError::PermissionDenied => EACCES,
+    }
+}
+
+/// Map a blocking pipe transfer failure onto the matching errno.
+pub fn from_pipe_error(e: &PipeError) -> u64 {
+    match e {
+        PipeError::Broken      => EPIPE,
+        PipeError::Interrupted => EINTR,
+    }
+}
+
+/// Map a `kill` failure onto the matching errno.
+pub fn from_signal_error(e: &SignalError) -> u64 {
+    match e {
+        SignalError::InvalidSignal => EINVAL,
+        SignalError::NoSuchProcess => ESRCH,
+        SignalError::NotPermitted  => EPERM,
+    }
+}
+
+/// Map a `wait` failure onto the matching errno.
+pub fn from_wait_error(e: &WaitError) -> u64 {
+    match e {
+        WaitError::NoChild     => ECHILD,
+        WaitError::Interrupted => EINTR,
+    }
+}
+
+/// Map an ELF loader error onto the matching errno.
+pub fn from_exec_error(e: &ExecError) -> u64 {
+    match e {
+        ExecError::FileNotFound    => ENOENT,
+        ExecError::InvalidFormat
+        | ExecError::UnsupportedArch
+        | ExecError::UnsupportedType => ENOEXEC,
+        ExecError::MemoryError     => ENOMEM,
+        ExecError::ReadError       => EIO,
+        ExecError::ArgListTooLong  => E2BIG,
+    }
+}
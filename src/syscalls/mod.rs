@@ -1,4 +1,11 @@
+pub mod errno;
+pub mod poll;
+pub mod stat;
+pub mod usercopy;
+pub mod wait;
+
 use crate::scheduler;
+use errno::err;
 
 /// Syscall numbers (passed in RAX from userland).
 pub const SYS_EXIT:  u64 = 0;
@@ -17,15 +24,79 @@ pub const SYS_DUP:   u64 = 10;
 pub const SYS_DUP2:  u64 = 11;
 pub const SYS_PIPE:  u64 = 12;
 pub const SYS_BRK:   u64 = 13;
+pub const SYS_GETDENTS: u64 = 14;
+pub const SYS_MMAP:  u64 = 15;
+pub const SYS_LSEEK: u64 = 16;
+pub const SYS_CHDIR: u64 = 17;
+pub const SYS_GETCWD: u64 = 18;
+pub const SYS_STAT: u64  = 19;
+pub const SYS_FSTAT: u64 = 20;
+pub const SYS_POLL: u64  = 21;
+pub const SYS_FTRUNCATE: u64 = 77;
+pub const SYS_CLOCK_GETTIME: u64 = 28;
+pub const SYS_GETSYSCALLS: u64 = 29;
+pub const SYS_NANOSLEEP: u64 = 35;
+pub const SYS_KILL: u64 = 62;
+pub const SYS_REBOOT: u64 = 169;
+
+/// Every syscall number `dispatch` handles, as reported by SYS_GETSYSCALLS.
+/// Keep in sync with the match in `dispatch`.
+const IMPLEMENTED: &[u64] = &[
+    SYS_EXIT, SYS_WRITE, SYS_YIELD, SYS_GETPID, SYS_FORK, SYS_EXEC, SYS_WAIT,
+    SYS_OPEN, SYS_CLOSE, SYS_READ, SYS_DUP, SYS_DUP2, SYS_PIPE, SYS_BRK,
+    SYS_GETDENTS, SYS_MMAP, SYS_LSEEK, SYS_CHDIR, SYS_GETCWD, SYS_STAT, SYS_FSTAT, SYS_POLL,
+    SYS_CLOCK_GETTIME, SYS_GETSYSCALLS,
+    SYS_NANOSLEEP, SYS_KILL, SYS_FTRUNCATE, SYS_REBOOT,
+];
+
+/// Bytes in the SYS_GETSYSCALLS bitmap (bit n set = syscall n exists).
+const SYSCALL_BITMAP_BYTES: usize = (highest_syscall() as usize + 1).div_ceil(8);
+
+const fn highest_syscall() -> u64 {
+    let mut max = 0;
+    let mut i = 0;
+    while i < IMPLEMENTED.len() {
+        if IMPLEMENTED[i] > max {
+            max = IMPLEMENTED[i];
+        }
+        i += 1;
+    }
+    max
+}
+
+/// Minimum time between "unknown syscall" warnings.
+const UNKNOWN_WARN_INTERVAL_MS: u64 = 1000;
+
+/// SYS_CLOCK_GETTIME clock ids (Linux values).
+pub const CLOCK_REALTIME: u64  = 0;
+pub const CLOCK_MONOTONIC: u64 = 1;
+
+/// SYS_MMAP flags (Linux values). Every mapping is anonymous; exactly one of
+/// MAP_SHARED / MAP_PRIVATE must be given.
+pub const MAP_SHARED: u64    = 0x01;
+pub const MAP_PRIVATE: u64   = 0x02;
+pub const MAP_ANONYMOUS: u64 = 0x20;
+
+/// SYS_REBOOT magic (Linux's first magic value), required so a stray call
+/// can't take the machine down, and commands (Linux values).
+pub const REBOOT_MAGIC: u64 = 0xFEE1_DEAD;
+pub const REBOOT_CMD_RESTART: u64   = 0x0123_4567;
+pub const REBOOT_CMD_HALT: u64      = 0xCDEF_0123;
+pub const REBOOT_CMD_POWER_OFF: u64 = 0x4321_FEDC;
 
 /// Central syscall dispatcher — called from the int 0x80 handler.
 /// Arguments come from registers: rax=number, rdi=arg0, rsi=arg1, rdx=arg2.
-/// Returns result in rax.
-pub extern "C" fn dispatch(number: u64, arg0: u64, arg1: u64, arg2: u64) -> u64 {
+/// `frame` points at the caller's saved user registers on this kernel stack.
+/// Returns result in rax: a non-negative value on success, `-(errno)` on failure
+/// (see `errno` for the convention).
+pub extern "C" fn dispatch(number: u64, arg0: u64, arg1: u64, arg2: u64, frame: *const scheduler::TrapFrame) -> u64 {
     // Enable interrupts so that system calls can be preempted by hardware timers!
     // Since int 0x80 goes through an Interrupt Gate, the CPU automatically masks IF=0. 
     x86_64::instructions::interrupts::enable();
     
+    // Any syscall counts as forward progress for the hung-task watchdog
+    scheduler::watchdog::pet();
+    
     match number {
         SYS_EXIT => {
             let exit_code = arg0;
@@ -37,137 +108,22 @@ pub extern "C" fn dispatch(number: u64, arg0: u64, arg1: u64, arg2: u64) -> u64
             let ptr = arg1 as *mut u8;
             let len = arg2 as usize;
             
-            if fd >= 64 || len == 0 || len > 1024 * 1024 { return u64::MAX; }
+            if fd >= 64 { return err(errno::EBADF); }
+            if len == 0 || len > 1024 * 1024 { return err(errno::EINVAL); }
             let slice = unsafe { core::slice::from_raw_parts_mut(ptr, len) };
             
-            let mut sched = scheduler::SCHEDULER.lock();
-            let current = sched.current.as_mut().unwrap();
-            
-            // Re-borrow the Arc to drop the scheduler lock early!
-            let file_arc = match current.fd_table[fd].clone() {
-                Some(f) => f,
-                None => return u64::MAX,
-            };
-            
-            drop(sched); // Critical: Unlock scheduler before blocking OS ops!
-            
-            let mut file = file_arc.lock();
-            if !file.readable { return u64::MAX; }
-            
-            use crate::fs::fd::FileType;
-            match &mut file.file_type {
-                FileType::Console => {
-                    // For now, Console Read is a simplified generic mock because Phase 5.4 
-                    // doesn't focus on TTY line disciplines. 
-                    slice[0] = b'\n';
-                    1
-                }
-                FileType::Regular => {
-                    // FAT32 Mock read for Phase 5.4 - Just return 0 (EOF) for now as we test Pipes
-                    0
-                }
-                FileType::PipeRead(pipe_inner) => {
-                    // Read from pipe lock
-                    let mut inner = pipe_inner.lock();
-                    loop {
-                        if !inner.is_empty() {
-                            let read_bytes = inner.read(slice);
-                            // Wake up any writers waiting for space!
-                            scheduler::wake_all_blocked(); 
-                            return read_bytes as u64;
-                        }
-                        
-                        if inner.active_writers() == 0 {
-                            return 0; // EOF
-                        }
-                        
-                        // Wait for writers to push data!
-                        drop(inner);
-                        drop(file);
-                        
-                        // Block current process and Yield!
-                        let mut sched = scheduler::SCHEDULER.lock();
-                        sched.current.as_mut().unwrap().state = scheduler::ProcessState::Blocked;
-                        drop(sched);
-                        scheduler::yield_now();
-                        
-                        // Re-acquire locks after waking up to try reading again
-                        file = file_arc.lock();
-                        // Refetch inner reference after lock manipulation
-                        match &file.file_type {
-                            FileType::PipeRead(p) => inner = p.lock(),
-                            _ => return u64::MAX,
-                        }
-                    }
-                }
-                _ => u64::MAX,
-            }
+            sys_read_fd(fd, slice)
         }
         SYS_WRITE => {
             let fd = arg0 as usize;
             let ptr = arg1 as *const u8;
             let len = arg2 as usize;
             
-            if fd >= 64 || len == 0 || len > 1024 * 1024 { return u64::MAX; }
+            if fd >= 64 { return err(errno::EBADF); }
+            if len == 0 || len > 1024 * 1024 { return err(errno::EINVAL); }
             let slice = unsafe { core::slice::from_raw_parts(ptr, len) };
             
-            let mut sched = scheduler::SCHEDULER.lock();
-            let current = sched.current.as_mut().unwrap();
-            
-            let file_arc = match current.fd_table[fd].clone() {
-                Some(f) => f,
-                None => return u64::MAX,
-            };
-            
-            drop(sched); // Yield scheduler lock
-            
-            use crate::fs::fd::FileType;
-            let mut file = file_arc.lock();
-            if !file.writable { return u64::MAX; }
-            
-            match &mut file.file_type {
-                FileType::Console => {
-                    if let Ok(s) = core::str::from_utf8(slice) {
-                        print_no_newline(s);
-                    }
-                    len as u64
-                }
-                FileType::Regular => {
-                    // FAT32 Mock write for Phase 5.4
-                    len as u64
-                }
-                FileType::PipeWrite(pipe_inner) => {
-                    let mut inner = pipe_inner.lock();
-                    loop {
-                        if !inner.is_full() {
-                            let written = inner.write(slice);
-                            // Wake up any readers waiting for data!
-                            scheduler::wake_all_blocked();
-                            return written as u64;
-                        }
-                        
-                        if inner.active_readers() == 0 {
-                            return u64::MAX; // Broken pipe
-                        }
-                        
-                        // Wait for readers to pull data!
-                        drop(inner);
-                        drop(file);
-                        
-                        let mut sched = scheduler::SCHEDULER.lock();
-                        sched.current.as_mut().unwrap().state = scheduler::ProcessState::Blocked;
-                        drop(sched);
-                        scheduler::yield_now();
-                        
-                        file = file_arc.lock();
-                        match &file.file_type {
-                            FileType::PipeWrite(p) => inner = p.lock(),
-                            _ => return u64::MAX,
-                        }
-                    }
-                }
-                _ => u64::MAX,
-            }
+            sys_write_fd(fd, slice)
         }
         SYS_YIELD => {
             scheduler::yield_now();
@@ -178,180 +134,560 @@ pub extern "C" fn dispatch(number: u64, arg0: u64, arg1: u64, arg2: u64) -> u64
             sched.current.as_ref().map_or(0, |t| t.pid.0)
         }
         SYS_FORK => {
-            scheduler::sys_fork()
+            match scheduler::sys_fork(frame) {
+                u64::MAX => err(errno::ENOMEM),
+                pid => pid,
+            }
         }
         SYS_EXEC => {
-            let ptr = arg0 as *const u8;
-            let len = arg1 as usize;
-            if len > 4096 { return u64::MAX; }
-            let slice = unsafe { core::slice::from_raw_parts(ptr, len) };
-            if let Ok(path) = core::str::from_utf8(slice) {
-                if let Err(e) = scheduler::sys_exec(path) {
-                    crate::log_error!("sys_exec failed: {}", e);
-                    u64::MAX
-                } else {
-                    unreachable!()
-                }
+            let path = match user_path(arg0, arg1) {
+                Ok(p) => p,
+                Err(e) => return err(e),
+            };
+            // arg2: optional NULL-terminated `char**` argv (0 = just the path)
+            // r10: optional NULL-terminated `char**` envp (0 = empty environment)
+            let envp_ptr = unsafe { (*frame).r10 };
+            let mut arg_bytes = 0;
+            let (argv, envp) = match user_argv(arg2, &mut arg_bytes)
+                .and_then(|argv| Ok((argv, user_argv(envp_ptr, &mut arg_bytes)?)))
+            {
+                Ok(v) => v,
+                Err(e) => return err(e),
+            };
+            let argv_refs: alloc::vec::Vec<&str> = argv.iter().map(|a| a.as_str()).collect();
+            let envp_refs: alloc::vec::Vec<&str> = envp.iter().map(|e| e.as_str()).collect();
+            if let Err(e) = scheduler::sys_exec(&path, &argv_refs, &envp_refs) {
+                crate::log_error!("sys_exec failed: {}", e);
+                err(errno::from_exec_error(&e))
             } else {
-                u64::MAX
+                unreachable!()
             }
         }
         SYS_WAIT => {
-            let target_pid = arg0;
-            scheduler::sys_wait(target_pid)
+            // arg0 = PID (-1 for any child), arg1 = WNOHANG, arg2 = where to
+            // store the wait status (0 if not wanted). Returns the child's PID,
+            // or 0 under WNOHANG if it has not exited yet.
+            match scheduler::sys_wait(arg0, arg1) {
+                Ok(Some((pid, status))) => {
+                    if arg2 != 0 {
+                        if let Err(e) = usercopy::copy_to_user(arg2, &status.to_ne_bytes()) {
+                            return err(e);
+                        }
+                    }
+                    pid.0
+                }
+                Ok(None) => 0,
+                Err(e) => err(errno::from_wait_error(&e)),
+            }
+        }
+        SYS_KILL => {
+            // arg0 = target PID, arg1 = signal number (0 = existence check)
+            let Ok(sig) = u32::try_from(arg1) else { return err(errno::EINVAL); };
+            match scheduler::signal::send(scheduler::ProcessId(arg0), sig) {
+                Ok(()) => 0,
+                Err(e) => err(errno::from_signal_error(&e)),
+            }
         }
         SYS_OPEN => {
-            let ptr = arg0 as *const u8;
-            let len = arg1 as usize;
-            if len > 4096 { return u64::MAX; }
-            let slice = unsafe { core::slice::from_raw_parts(ptr, len) };
-            let path = core::str::from_utf8(slice).unwrap_or("");
-            
-            // FIXME: This is a simplfied VFS pass-through focusing only on FAT32 for Phase 5.4 requirements
-            // A real VFS open would return an Inode handle. Here we just assume it's valid if length > 0
-            if path.len() == 0 { return u64::MAX; }
+            // arg0/arg1 = path, arg2 = O_* flags
+            let path = match user_path(arg0, arg1) {
+                Ok(p) => p,
+                Err(e) => return err(e),
+            };
+            if path.is_empty() { return err(errno::ENOENT); }
+            // Relative paths start at the caller's working directory
+            let path = crate::fs::absolute_path(&scheduler::current_cwd(), &path);
+            let path = path.as_str();
+            
+            // Find a free FD first: with a full table, O_CREAT and O_TRUNC
+            // must not have touched the file
+            let fd_idx = {
+                let sched = scheduler::SCHEDULER.lock();
+                let current = sched.current.as_ref().unwrap();
+                match (0..64).find(|&i| current.fd_table[i].is_none()) {
+                    Some(i) => i,
+                    None => return err(errno::EMFILE), // Table Full
+                }
+            };
             
-            use alloc::sync::Arc;
-            use spin::Mutex;
-            use crate::fs::fd::File;
+            // Resolve through the VFS without the scheduler lock. Only this
+            // task changes its own table, so the slot stays free meanwhile.
+            let file = match crate::fs::fd::File::open(path, arg2) {
+                Ok(file) => file,
+                Err(e) => {
+                    crate::log_warn!("sys_open: {}: {}", path, e);
+                    return err(errno::from_fs_error(&e));
+                }
+            };
             
             let mut sched = scheduler::SCHEDULER.lock();
             let current = sched.current.as_mut().unwrap();
-            
-            // Find free FD
-            let mut fd = None;
-            for i in 0..64 {
-                if current.fd_table[i].is_none() {
-                    fd = Some(i);
-                    break;
+            current.fd_table[fd_idx] = Some(file);
+            fd_idx as u64
+        }
+        SYS_MMAP => {
+            let len = arg0;
+            let flags = arg1;
+            if len == 0 || len > 64 * 1024 * 1024 { return err(errno::EINVAL); }
+            if flags & !(MAP_SHARED | MAP_PRIVATE | MAP_ANONYMOUS) != 0 { return err(errno::EINVAL); }
+            let shared = match flags & (MAP_SHARED | MAP_PRIVATE) {
+                MAP_SHARED => true,
+                MAP_PRIVATE => false,
+                _ => return err(errno::EINVAL),
+            };
+            match scheduler::sys_mmap(len, shared) {
+                Some(addr) => addr,
+                None => err(errno::ENOMEM),
+            }
+        }
+        SYS_CLOCK_GETTIME => {
+            // arg0 = clock id, arg1 = user pointer to `{ secs: u64, nsecs: u64 }`
+            let (secs, nsecs) = match arg0 {
+                // Whole ticks since boot: nsecs moves in steps of one tick
+                // period (10 ms at the default PIT rate), never in between.
+                CLOCK_MONOTONIC => {
+                    let nanos = crate::shell::commands::uptime::nanos_since_boot();
+                    (nanos / 1_000_000_000, nanos % 1_000_000_000)
+                }
+                // The RTC only counts whole seconds
+                CLOCK_REALTIME => (crate::drivers::rtc::unix_time(), 0),
+                _ => return err(errno::EINVAL),
+            };
+            let mut ts = [0u8; 16];
+            ts[0..8].copy_from_slice(&secs.to_ne_bytes());
+            ts[8..16].copy_from_slice(&nsecs.to_ne_bytes());
+            match usercopy::copy_to_user(arg1, &ts) {
+                Ok(()) => 0,
+                Err(e) => err(e),
+            }
+        }
+        SYS_LSEEK => {
+            // arg0 = fd, arg1 = offset (signed), arg2 = whence
+            use crate::fs::fd::{SeekError, SeekFrom};
+            let fd = arg0 as usize;
+            if fd >= 64 { return err(errno::EBADF); }
+            let Some(pos) = SeekFrom::from_whence(arg1 as i64, arg2) else {
+                return err(errno::EINVAL);
+            };
+
+            let file_arc = {
+                let sched = scheduler::SCHEDULER.lock();
+                match sched.current.as_ref().and_then(|p| p.fd_table[fd].clone()) {
+                    Some(f) => f,
+                    None => return err(errno::EBADF),
                 }
+            };
+            // dup'd descriptors share this File, and with it the offset
+            let result = file_arc.lock().seek(pos);
+            match result {
+                Ok(offset) => offset,
+                Err(SeekError::NotSeekable) => err(errno::ESPIPE),
+                Err(SeekError::InvalidOffset) => err(errno::EINVAL),
+                Err(SeekError::Fs(e)) => err(errno::from_fs_error(&e)),
+            }
+        }
+        SYS_CHDIR => {
+            // arg0 = path pointer, arg1 = length; relative to the current cwd
+            let path = match user_path(arg0, arg1) {
+                Ok(p) => p,
+                Err(e) => return err(e),
+            };
+            if path.is_empty() { return err(errno::ENOENT); }
+            let target = crate::fs::absolute_path(&scheduler::current_cwd(), &path);
+            match crate::fs::VFS.lock().lookup(&target) {
+                Ok(inode) if inode.file_type == crate::fs::inode::FileType::Directory => {}
+                Ok(_) => return err(errno::ENOTDIR),
+                Err(e) => return err(errno::from_fs_error(&e)),
+            }
+            scheduler::set_current_cwd(target);
+            0
+        }
+        SYS_GETCWD => {
+            // arg0 = user buffer, arg1 = its size. Returns the length
+            // written, including the terminating NUL.
+            let mut cwd = scheduler::current_cwd().into_bytes();
+            cwd.push(0);
+            if (arg1 as usize) < cwd.len() { return err(errno::ERANGE); }
+            match usercopy::copy_to_user(arg0, &cwd) {
+                Ok(()) => cwd.len() as u64,
+                Err(e) => err(e),
+            }
+        }
+        SYS_NANOSLEEP => {
+            // arg0 = user pointer to `{ secs: u64, nsecs: u64 }`. Only a
+            // signal cuts a sleep short, and that ends the process on the way
+            // out, so there is no remaining time to report.
+            let mut ts = [0u8; 16];
+            if let Err(e) = usercopy::copy_from_user(&mut ts, arg0) {
+                return err(e);
+            }
+            let secs = u64::from_ne_bytes(ts[0..8].try_into().unwrap());
+            let nsecs = u64::from_ne_bytes(ts[8..16].try_into().unwrap());
+            if nsecs >= 1_000_000_000 {
+                return err(errno::EINVAL);
             }
+            // Round up to whole ticks: never wake before the requested time
+            let nanos = secs as u128 * 1_000_000_000 + nsecs as u128;
+            let ticks = nanos.div_ceil(crate::interrupts::pit::nanos_per_tick() as u128);
+            scheduler::sleep_ticks(ticks.min(u64::MAX as u128) as u64);
+            0
+        }
+        SYS_STAT => {
+            // arg0/arg1 = path, arg2 = user pointer to a `stat::Stat`
+            let path = match user_path(arg0, arg1) {
+                Ok(p) => p,
+                Err(e) => return err(e),
+            };
+            if path.is_empty() { return err(errno::ENOENT); }
+            let path = crate::fs::absolute_path(&scheduler::current_cwd(), &path);
             
-            if let Some(fd_idx) = fd {
-                current.fd_table[fd_idx] = Some(File::new_regular(path, true, true));
-                fd_idx as u64
-            } else {
-                u64::MAX // Table Full
+            let inode = match crate::fs::VFS.lock().lookup(&path) {
+                Ok(inode) => inode,
+                Err(e) => return err(errno::from_fs_error(&e)),
+            };
+            match usercopy::copy_to_user(arg2, &inode_stat(&inode).to_bytes()) {
+                Ok(()) => 0,
+                Err(e) => err(e),
+            }
+        }
+        SYS_FSTAT => {
+            // arg0 = fd, arg1 = user pointer to a `stat::Stat`
+            let fd = arg0 as usize;
+            if fd >= 64 { return err(errno::EBADF); }
+            let file_arc = match scheduler::current_fd(fd) {
+                Some(f) => f,
+                None => return err(errno::EBADF),
+            };
+            
+            use crate::fs::fd::FileType;
+            let file = file_arc.lock();
+            let st = match file.file_type {
+                FileType::Regular | FileType::Directory => match crate::fs::VFS.lock().lookup(&file.path) {
+                    Ok(inode) => inode_stat(&inode),
+                    Err(e) => return err(errno::from_fs_error(&e)),
+                },
+                FileType::Console => stat::Stat { file_type: stat::DT_CHR, ..Default::default() },
+                FileType::PipeRead(_) | FileType::PipeWrite(_) => stat::Stat { file_type: stat::DT_FIFO, ..Default::default() },
+            };
+            drop(file);
+            match usercopy::copy_to_user(arg1, &st.to_bytes()) {
+                Ok(()) => 0,
+                Err(e) => err(e),
+            }
+        }
+        SYS_FTRUNCATE => {
+            // arg0 = fd, arg1 = new length. The offset is left where it is.
+            let fd = arg0 as usize;
+            if fd >= 64 { return err(errno::EBADF); }
+            let file_arc = match scheduler::current_fd(fd) {
+                Some(f) => f,
+                None => return err(errno::EBADF),
+            };
+
+            let file = file_arc.lock();
+            if !matches!(file.file_type, crate::fs::fd::FileType::Regular) || !file.writable {
+                return err(errno::EINVAL);
+            }
+            match crate::fs::VFS.lock().truncate(&file.path, arg1 as usize) {
+                Ok(()) => 0,
+                Err(e) => err(errno::from_fs_error(&e)),
+            }
+        }
+        SYS_POLL => {
+            // arg0 = user array of `poll::PollFd`, arg1 = entry count,
+            // arg2 = timeout in ticks (0 checks once, negative waits for good)
+            use poll::PollFd;
+            let count = arg1 as usize;
+            if count > poll::POLL_MAX_FDS { return err(errno::EINVAL); }
+            let mut raw = alloc::vec![0u8; count * PollFd::SIZE];
+            if let Err(e) = usercopy::copy_from_user(&mut raw, arg0) {
+                return err(e);
+            }
+            let mut fds: alloc::vec::Vec<PollFd> = raw.chunks_exact(PollFd::SIZE)
+                .map(|b| PollFd::from_bytes(b.try_into().unwrap()))
+                .collect();
+
+            let ready = sys_poll(&mut fds, arg2 as i64);
+            if errno::is_err(ready) { return ready; }
+            for (bytes, pfd) in raw.chunks_exact_mut(PollFd::SIZE).zip(&fds) {
+                bytes.copy_from_slice(&pfd.to_bytes());
+            }
+            match usercopy::copy_to_user(arg0, &raw) {
+                Ok(()) => ready,
+                Err(e) => err(e),
             }
         }
+        SYS_GETDENTS => {
+            let fd = arg0 as usize;
+            let ptr = arg1 as *mut u8;
+            let len = arg2 as usize;
+            
+            if fd >= 64 { return err(errno::EBADF); }
+            if len == 0 || len > 1024 * 1024 { return err(errno::EINVAL); }
+            let buf = unsafe { core::slice::from_raw_parts_mut(ptr, len) };
+            
+            let sched = scheduler::SCHEDULER.lock();
+            let file_arc = match sched.current.as_ref().unwrap().fd_table[fd].clone() {
+                Some(f) => f,
+                None => return err(errno::EBADF),
+            };
+            drop(sched);
+            
+            let mut file = file_arc.lock();
+            match file.file_type {
+                crate::fs::fd::FileType::Directory => {}
+                _ => return err(errno::ENOTDIR),
+            }
+            
+            let entries = match crate::fs::VFS.lock().readdir(&file.path) {
+                Ok(e) => e,
+                Err(e) => return err(errno::from_fs_error(&e)),
+            };
+            
+            let (written, consumed) = fill_dirents(&entries[(file.offset as usize).min(entries.len())..], buf);
+            if written == 0 && consumed == 0 && (file.offset as usize) < entries.len() {
+                return err(errno::EINVAL); // Buffer too small for the next entry
+            }
+            file.offset += consumed as u64;
+            written as u64
+        }
         SYS_CLOSE => {
             let fd = arg0 as usize;
-            if fd >= 64 { return u64::MAX; }
+            if fd >= 64 { return err(errno::EBADF); }
             let mut sched = scheduler::SCHEDULER.lock();
             let current = sched.current.as_mut().unwrap();
+            let file = current.fd_table[fd].take();
+            drop(sched);
             
-            // Drop Reference
-            current.fd_table[fd] = None;
+            // Drop Reference (outside the scheduler lock: closing a pipe end wakes its other side)
+            if file.is_none() { return err(errno::EBADF); }
             0
         }
         SYS_DUP => {
             let old_fd = arg0 as usize;
-            if old_fd >= 64 { return u64::MAX; }
+            if old_fd >= 64 { return err(errno::EBADF); }
             
             let mut sched = scheduler::SCHEDULER.lock();
             let current = sched.current.as_mut().unwrap();
             
             // Get Arc pointing to original file
-            if let Some(file_arc) = current.fd_table[old_fd].clone() {
-                // Find next free FD
-                for i in 0..64 {
-                    if current.fd_table[i].is_none() {
-                        current.fd_table[i] = Some(file_arc); // Increments Arc Rc!
-                        return i as u64;
-                    }
+            let file_arc = match current.fd_table[old_fd].clone() {
+                Some(f) => f,
+                None => return err(errno::EBADF),
+            };
+            // Find next free FD
+            for i in 0..64 {
+                if current.fd_table[i].is_none() {
+                    current.fd_table[i] = Some(file_arc); // Increments Arc Rc!
+                    return i as u64;
                 }
             }
-            u64::MAX // Table full or invalid old_fd
+            err(errno::EMFILE) // Table full
         }
         SYS_DUP2 => {
             let old_fd = arg0 as usize;
             let new_fd = arg1 as usize;
-            if old_fd >= 64 || new_fd >= 64 { return u64::MAX; }
+            if old_fd >= 64 || new_fd >= 64 { return err(errno::EBADF); }
             if old_fd == new_fd { return new_fd as u64; } // No-op
             
             let mut sched = scheduler::SCHEDULER.lock();
             let current = sched.current.as_mut().unwrap();
             
             if let Some(file_arc) = current.fd_table[old_fd].clone() {
-                // If there's an existing file in new_fd, this assignment safely drops its Arc
-                current.fd_table[new_fd] = Some(file_arc);
+                // An existing file in new_fd is closed once the scheduler lock is released
+                let _closed = core::mem::replace(&mut current.fd_table[new_fd], Some(file_arc));
+                drop(sched);
                 return new_fd as u64;
             }
-            u64::MAX // Invalid old_fd
+            err(errno::EBADF) // Invalid old_fd
         }
         SYS_BRK => {
             let addr = arg0;
             scheduler::sys_brk(addr)
         }
         SYS_PIPE => {
-            let fds_ptr = arg0 as *mut [u32; 2]; // Pass pointer to [u32; 2] from user
-            let mut sched = scheduler::SCHEDULER.lock();
-            let current = sched.current.as_mut().unwrap();
-            
-            // Find two available FDs
-            let mut fd0 = None;
-            let mut fd1 = None;
-            for i in 0..64 {
-                if current.fd_table[i].is_none() {
-                    if fd0.is_none() { fd0 = Some(i); continue; }
-                    if fd1.is_none() { fd1 = Some(i); break; }
-                }
-            }
+            let fds_addr = arg0; // User pointer to [u32; 2]
+            
+            // Find two available FDs; nothing is claimed unless both exist
+            let free_fds = {
+                let sched = scheduler::SCHEDULER.lock();
+                let current = sched.current.as_ref().unwrap();
+                let mut free = (0..64).filter(|&i| current.fd_table[i].is_none());
+                (free.next(), free.next())
+            };
+            let (fd_read, fd_write) = match free_fds {
+                (Some(r), Some(w)) => (r, w),
+                _ => return err(errno::EMFILE), // Table full
+            };
             
-            if fd0.is_none() || fd1.is_none() {
-                return u64::MAX; // Table full
+            // Report the fds first, without the scheduler lock: the page may
+            // still have to be loaded, which takes it. Only this task changes
+            // its own table, so the two slots stay free meanwhile.
+            let mut fds = [0u8; 8];
+            fds[0..4].copy_from_slice(&(fd_read as u32).to_ne_bytes());
+            fds[4..8].copy_from_slice(&(fd_write as u32).to_ne_bytes());
+            if let Err(e) = usercopy::copy_to_user(fds_addr, &fds) {
+                return err(e);
             }
             
-            let fd_read = fd0.unwrap();
-            let fd_write = fd1.unwrap();
-            
-            use alloc::sync::Arc;
-            use spin::Mutex;
-            use crate::fs::fd::{File, FileType};
-            
-            let inner = crate::fs::pipe::PipeInner::new();
-            
-            // Pipe initially has 1 reader and 1 writer
-            inner.lock().add_reader();
-            inner.lock().add_writer();
-            
-            let read_file = Arc::new(Mutex::new(File {
-                file_type: FileType::PipeRead(inner.clone()),
-                path: alloc::string::String::from("pipe"),
-                offset: 0,
-                readable: true,
-                writable: false,
-            }));
-            
-            let write_file = Arc::new(Mutex::new(File {
-                file_type: FileType::PipeWrite(inner),
-                path: alloc::string::String::from("pipe"),
-                offset: 0,
-                readable: false,
-                writable: true,
-            }));
-            
+            let (read_file, write_file) = crate::fs::fd::File::new_pipe();
+            let mut sched = scheduler::SCHEDULER.lock();
+            let current = sched.current.as_mut().unwrap();
             current.fd_table[fd_read] = Some(read_file);
             current.fd_table[fd_write] = Some(write_file);
             
-            unsafe {
-                (*fds_ptr)[0] = fd_read as u32;
-                (*fds_ptr)[1] = fd_write as u32;
-            }
-            
             0
         }
+        SYS_REBOOT => {
+            // arg0 = REBOOT_MAGIC, arg1 = command. Filesystems are flushed and
+            // unmounted first; only returns on a bad argument.
+            if arg0 != REBOOT_MAGIC {
+                return err(errno::EINVAL);
+            }
+            match arg1 {
+                REBOOT_CMD_RESTART => crate::power::reboot(),
+                REBOOT_CMD_HALT => crate::power::shutdown_halt(),
+                REBOOT_CMD_POWER_OFF => crate::power::poweroff(),
+                _ => err(errno::EINVAL),
+            }
+        }
+        SYS_GETSYSCALLS => {
+            // arg0 = user buffer, arg1 = its length in bytes. The bitmap is
+            // truncated to fit; the return value is its full size.
+            let mut bitmap = [0u8; SYSCALL_BITMAP_BYTES];
+            for &n in IMPLEMENTED {
+                bitmap[n as usize / 8] |= 1 << (n % 8);
+            }
+            let len = (arg1 as usize).min(SYSCALL_BITMAP_BYTES);
+            match usercopy::copy_to_user(arg0, &bitmap[..len]) {
+                Ok(()) => SYSCALL_BITMAP_BYTES as u64,
+                Err(e) => err(e),
+            }
+        }
         _ => {
-            crate::log_warn!("syscall: unknown number {}", number);
-            u64::MAX // error
+            warn_unknown_syscall(number);
+            err(errno::ENOSYS)
         }
     }
 }
 
+/// Log an unknown syscall number with the caller's PID, at most once per
+/// UNKNOWN_WARN_INTERVAL_MS; calls in between are only counted so a
+/// program spinning on a bad syscall cannot flood the log.
+fn warn_unknown_syscall(number: u64) {
+    use core::sync::atomic::{AtomicU64, Ordering};
+    static NEXT_WARN_TICK: AtomicU64 = AtomicU64::new(0);
+    static SUPPRESSED: AtomicU64 = AtomicU64::new(0);
+
+    let now = crate::shell::commands::uptime::TICKS.load(Ordering::Relaxed);
+    let next = NEXT_WARN_TICK.load(Ordering::Relaxed);
+    if now < next || NEXT_WARN_TICK
+        .compare_exchange(next, now + crate::interrupts::pit::ms_to_ticks(UNKNOWN_WARN_INTERVAL_MS),
+            Ordering::Relaxed, Ordering::Relaxed)
+        .is_err()
+    {
+        SUPPRESSED.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+
+    let pid = scheduler::current_pid().0;
+    match SUPPRESSED.swap(0, Ordering::Relaxed) {
+        0 => { crate::log_warn!("syscall: unknown number {} from PID {} (ENOSYS)", number, pid); }
+        n => { crate::log_warn!("syscall: unknown number {} from PID {} (ENOSYS, {} more suppressed)", number, pid, n); }
+    }
+}
+
+/// Directory entry types reported in `d_type`.
+pub use stat::{DT_DIR, DT_LNK, DT_REG};
+
+/// The `stat` record for an inode.
+fn inode_stat(inode: &crate::fs::inode::Inode) -> stat::Stat {
+    stat::Stat {
+        ino: inode.id,
+        size: inode.size as u64,
+        file_type: dt_type(inode.file_type),
+        ..Default::default()
+    }
+}
+
+/// The `DT_*` code for an inode type.
+fn dt_type(file_type: crate::fs::inode::FileType) -> u8 {
+    match file_type {
+        crate::fs::inode::FileType::Directory => DT_DIR,
+        crate::fs::inode::FileType::File => DT_REG,
+        crate::fs::inode::FileType::Symlink => DT_LNK,
+    }
+}
+
+/// Pack directory entries into `buf` as `linux_dirent64`-style records:
+/// `d_ino: u64, d_reclen: u16, d_type: u8, d_name: [u8]` (NUL-terminated),
+/// each padded to 8 bytes. Returns (bytes written, entries consumed).
+fn fill_dirents(entries: &[crate::fs::dentry::DirEntry], buf: &mut [u8]) -> (usize, usize) {
+    let mut pos = 0;
+    let mut count = 0;
+    for entry in entries {
+        let name = entry.name.as_bytes();
+        let reclen = (8 + 2 + 1 + name.len() + 1 + 7) & !7;
+        if pos + reclen > buf.len() { break; }
+        
+        let rec = &mut buf[pos..pos + reclen];
+        rec[0..8].copy_from_slice(&entry.inode.id.to_le_bytes());
+        rec[8..10].copy_from_slice(&(reclen as u16).to_le_bytes());
+        rec[10] = dt_type(entry.inode.file_type);
+        rec[11..11 + name.len()].copy_from_slice(name);
+        for b in &mut rec[11 + name.len()..] { *b = 0; }
+        
+        pos += reclen;
+        count += 1;
+    }
+    (pos, count)
+}
+
+/// Longest path (in bytes) accepted by SYS_OPEN and SYS_EXEC.
+pub const PATH_MAX: usize = 4096;
+
+/// Maximum number of argv (and of envp) entries accepted by SYS_EXEC.
+const MAX_ARGS: usize = 64;
+
+/// Copy a `(ptr, len)` path argument out of user memory.
+/// ENAMETOOLONG beyond PATH_MAX, EFAULT if unmapped, EINVAL if not UTF-8.
+fn user_path(ptr: u64, len: u64) -> Result<alloc::string::String, u64> {
+    if len as usize > PATH_MAX { return Err(errno::ENAMETOOLONG); }
+    let mut bytes = alloc::vec![0u8; len as usize];
+    if len != 0 {
+        usercopy::copy_from_user(&mut bytes, ptr)?;
+    }
+    alloc::string::String::from_utf8(bytes).map_err(|_| errno::EINVAL)
+}
+
+/// Copy a NULL-terminated user `char**` and its strings into the kernel.
+/// `total` carries the bytes used so far across argv and envp, held to
+/// `loader::elf::MAX_ARG_BYTES`, the space reserved for them on the new user
+/// stack; anything larger is E2BIG.
+fn user_argv(argv: u64, total: &mut usize) -> Result<alloc::vec::Vec<alloc::string::String>, u64> {
+    let mut out = alloc::vec::Vec::new();
+    if argv == 0 { return Ok(out); }
+
+    // Same accounting as the loader: each string plus its NUL, plus the
+    // pointer array with its terminating NULL
+    *total += 8;
+    loop {
+        let arg = usercopy::read_u64_from_user(argv + 8 * out.len() as u64)?;
+        if arg == 0 { break; }
+        if out.len() == MAX_ARGS { return Err(errno::E2BIG); }
+
+        let room = crate::loader::elf::MAX_ARG_BYTES.saturating_sub(*total + 8 + 1);
+        let bytes = usercopy::copy_cstr_from_user(arg, room)?;
+        *total += 8 + bytes.len() + 1;
+        out.push(alloc::string::String::from_utf8(bytes).map_err(|_| errno::EINVAL)?);
+    }
+    Ok(out)
+}
+
 /// Print without trailing newline.
 fn print_no_newline(s: &str) {
     use core::fmt::Write;
-    let _ = crate::vga::WRITER.lock().write_str(s);
+    crate::vga::write_str(s);
     let _ = crate::serial::SERIAL1.lock().write_str(s);
 }
 
@@ -362,6 +698,172 @@ pub fn sys_write(msg: &str) {
     crate::println!("{}", msg);
 }
 
+/// sys_read_fd: read from descriptor `fd` of the running process into
+/// `buf`, blocking like SYS_READ. Returns the byte count or `err(errno)`.
+pub fn sys_read_fd(fd: usize, slice: &mut [u8]) -> u64 {
+    if fd >= 64 { return err(errno::EBADF); }
+    let mut sched = scheduler::SCHEDULER.lock();
+    let current = sched.current.as_mut().unwrap();
+    
+    // Re-borrow the Arc to drop the scheduler lock early!
+    let file_arc = match current.fd_table[fd].clone() {
+        Some(f) => f,
+        None => return err(errno::EBADF),
+    };
+    
+    drop(sched); // Critical: Unlock scheduler before blocking OS ops!
+    
+    let mut file = file_arc.lock();
+    if !file.readable { return err(errno::EBADF); }
+    
+    use crate::fs::fd::FileType;
+    if let FileType::Console = file.file_type {
+        // Blocks until Enter: don't hold the descriptor meanwhile
+        drop(file);
+        return crate::drivers::tty::console::read_line(slice) as u64;
+    }
+    match &mut file.file_type {
+        FileType::Directory => {
+            // Directories are enumerated via SYS_GETDENTS
+            err(errno::EISDIR)
+        }
+        FileType::Regular => match file.read(slice) {
+            Ok(n) => n as u64,
+            Err(e) => err(errno::from_fs_error(&e)),
+        },
+        FileType::PipeRead(pipe) => {
+            // Blocks until data or EOF: don't hold the descriptor meanwhile
+            let pipe = pipe.clone();
+            drop(file);
+            match pipe.read(slice) {
+                Ok(n) => n as u64,
+                Err(e) => err(errno::from_pipe_error(&e)),
+            }
+        }
+        _ => err(errno::EBADF),
+    }
+}
+
+/// sys_write_fd: write `data` to descriptor `fd` of the running process,
+/// blocking like SYS_WRITE. Returns the byte count or `err(errno)`.
+pub fn sys_write_fd(fd: usize, slice: &[u8]) -> u64 {
+    if fd >= 64 { return err(errno::EBADF); }
+    let mut sched = scheduler::SCHEDULER.lock();
+    let current = sched.current.as_mut().unwrap();
+    
+    let file_arc = match current.fd_table[fd].clone() {
+        Some(f) => f,
+        None => return err(errno::EBADF),
+    };
+    
+    drop(sched); // Yield scheduler lock
+    
+    use crate::fs::fd::FileType;
+    let mut file = file_arc.lock();
+    if let FileType::Directory = file.file_type { return err(errno::EISDIR); }
+    if !file.writable { return err(errno::EBADF); }
+    
+    match &mut file.file_type {
+        FileType::Console => {
+            if let Ok(s) = core::str::from_utf8(slice) {
+                print_no_newline(s);
+            }
+            slice.len() as u64
+        }
+        FileType::Regular => match file.write(slice) {
+            Ok(n) => n as u64,
+            Err(e) => err(errno::from_fs_error(&e)),
+        },
+        FileType::PipeWrite(pipe) => {
+            let pipe = pipe.clone();
+            drop(file);
+            match pipe.write(slice) {
+                Ok(n) => n as u64,
+                Err(e) => err(errno::from_pipe_error(&e)),
+            }
+        }
+        _ => err(errno::EBADF),
+    }
+}
+
+/// sys_poll: wait until an entry of `fds` is ready or `timeout` ticks have
+/// passed (0 checks once, negative waits indefinitely), filling in every
+/// `revents`. Returns how many entries have events, 0 on timeout, or
+/// `err(EINTR)` if a signal arrives first.
+pub fn sys_poll(fds: &mut [poll::PollFd], timeout: i64) -> u64 {
+    use core::sync::atomic::Ordering;
+    let timer = &crate::shell::commands::uptime::TICKS;
+    let start = timer.load(Ordering::Relaxed);
+    loop {
+        let mut ready = 0;
+        for pfd in fds.iter_mut() {
+            pfd.revents = poll_revents(pfd);
+            if pfd.revents != 0 {
+                ready += 1;
+            }
+        }
+        if ready > 0 || timeout == 0 {
+            return ready;
+        }
+        let now = timer.load(Ordering::Relaxed);
+        if timeout > 0 && now.wrapping_sub(start) >= timeout as u64 {
+            return 0;
+        }
+        if scheduler::signal::pending() {
+            return err(errno::EINTR);
+        }
+        // Pipes only wake tasks blocked reading or writing them, keystrokes
+        // wake no one, and a change between the checks above and blocking
+        // would be missed: look again on the next tick at the latest
+        scheduler::block_until(now.wrapping_add(1));
+    }
+}
+
+/// The events `pfd` would report right now. Never blocks.
+fn poll_revents(pfd: &poll::PollFd) -> i16 {
+    use crate::fs::fd::FileType;
+    use poll::{POLLERR, POLLHUP, POLLIN, POLLNVAL, POLLOUT};
+    if pfd.fd < 0 {
+        return 0;
+    }
+    let Some(file_arc) = scheduler::current_fd(pfd.fd as usize) else {
+        return POLLNVAL;
+    };
+    let file = file_arc.lock();
+    let ready = match &file.file_type {
+        FileType::PipeRead(pipe) => {
+            let pipe = pipe.lock();
+            // With every writer gone a read returns at once (0 at the end)
+            if pipe.active_writers() == 0 {
+                POLLIN | POLLHUP
+            } else if pipe.is_empty() {
+                0
+            } else {
+                POLLIN
+            }
+        }
+        FileType::PipeWrite(pipe) => {
+            let pipe = pipe.lock();
+            if pipe.active_readers() == 0 {
+                POLLERR
+            } else if pipe.is_full() {
+                0
+            } else {
+                POLLOUT
+            }
+        }
+        FileType::Console => {
+            let input = if file.readable && crate::drivers::tty::console::input_ready() { POLLIN } else { 0 };
+            input | if file.writable { POLLOUT } else { 0 }
+        }
+        // Files and directories never block
+        FileType::Regular | FileType::Directory => {
+            (if file.readable { POLLIN } else { 0 }) | (if file.writable { POLLOUT } else { 0 })
+        }
+    };
+    ready & (pfd.events | POLLERR | POLLHUP)
+}
+
 /// sys_yield: cooperatively yield the CPU.
 pub fn sys_yield() {
     scheduler::yield_now();
@@ -379,6 +881,11 @@ pub fn sys_spawn(entry: fn(), name: &str) -> u64 {
     id.0
 }
 
+/// sys_kill: post signal `sig` to process `pid` (kernel-side).
+pub fn sys_kill(pid: u64, sig: u32) -> Result<(), scheduler::signal::SignalError> {
+    scheduler::signal::send(scheduler::ProcessId(pid), sig)
+}
+
 /// sys_getpid: return current task ID.
 pub fn sys_getpid() -> u64 {
     let sched = scheduler::SCHEDULER.lock();
@@ -0,0 +1,54 @@
+//! `poll` ABI shared by the kernel and userland: atomiclibc includes this
+//! file as its `poll` module, so both sides agree on the layout. Keep it
+//! free of kernel dependencies.
+
+/// Event bits, as in `PollFd::events` and `PollFd::revents` (Linux values).
+/// Data can be read without blocking (for a pipe, also at end of file).
+pub const POLLIN: i16 = 0x01;
+/// Data can be written without blocking.
+pub const POLLOUT: i16 = 0x04;
+/// The read end of a written pipe is gone; writing would fail with EPIPE.
+pub const POLLERR: i16 = 0x08;
+/// The write end of a read pipe is gone.
+pub const POLLHUP: i16 = 0x10;
+/// `fd` is not an open descriptor.
+pub const POLLNVAL: i16 = 0x20;
+
+/// Most entries one SYS_POLL call accepts: one per descriptor slot.
+pub const POLL_MAX_FDS: usize = 64;
+
+/// One descriptor watched by SYS_POLL.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PollFd {
+    /// Descriptor to watch; negative entries are skipped.
+    pub fd: i32,
+    /// Events of interest (`POLLIN`, `POLLOUT`).
+    pub events: i16,
+    /// Filled in by the kernel: the requested events that are ready, plus
+    /// `POLLERR`, `POLLHUP` and `POLLNVAL` whether requested or not.
+    pub revents: i16,
+}
+
+impl PollFd {
+    /// Size of the struct as copied to and from userspace.
+    pub const SIZE: usize = core::mem::size_of::<PollFd>();
+
+    /// The struct's bytes in its `repr(C)` layout.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..4].copy_from_slice(&self.fd.to_ne_bytes());
+        bytes[4..6].copy_from_slice(&self.events.to_ne_bytes());
+        bytes[6..8].copy_from_slice(&self.revents.to_ne_bytes());
+        bytes
+    }
+
+    /// Read a struct back from its `repr(C)` bytes.
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        PollFd {
+            fd: i32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            events: i16::from_ne_bytes([bytes[4], bytes[5]]),
+            revents: i16::from_ne_bytes([bytes[6], bytes[7]]),
+        }
+    }
+}
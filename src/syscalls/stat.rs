@@ -0,0 +1,38 @@
+//! `stat`/`fstat` ABI shared by the kernel and userland: atomiclibc includes
+//! this file as its `stat` module, so both sides agree on the layout. Keep
+//! it free of kernel dependencies.
+
+/// File type codes, as in `Stat::file_type` and the `d_type` of getdents
+/// records (Linux `DT_*` values).
+pub const DT_FIFO: u8 = 1;
+pub const DT_CHR: u8 = 2;
+pub const DT_DIR: u8 = 4;
+pub const DT_REG: u8 = 8;
+pub const DT_LNK: u8 = 10;
+
+/// What SYS_STAT and SYS_FSTAT report about a file.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stat {
+    /// Inode number, unique within its filesystem.
+    pub ino: u64,
+    /// Size in bytes (0 for directories, the console and pipes).
+    pub size: u64,
+    /// One of the `DT_*` codes.
+    pub file_type: u8,
+    pub _reserved: [u8; 7],
+}
+
+impl Stat {
+    /// Size of the struct as copied to userspace.
+    pub const SIZE: usize = core::mem::size_of::<Stat>();
+
+    /// The struct's bytes in its `repr(C)` layout.
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        bytes[0..8].copy_from_slice(&self.ino.to_ne_bytes());
+        bytes[8..16].copy_from_slice(&self.size.to_ne_bytes());
+        bytes[16] = self.file_type;
+        bytes
+    }
+}
@@ -0,0 +1,67 @@
+// Checked access to user memory.
+//
+// A syscall must never dereference a user pointer before making sure the
+// range is mapped and reachable from Ring 3 in the caller's address space:
+// a bad pointer would otherwise fault inside the kernel. These helpers
+// validate the whole range first and return EFAULT instead.
+
+use alloc::vec::Vec;
+use super::errno;
+use crate::memory::paging::user_range_accessible;
+
+const PAGE_SIZE: u64 = 4096;
+
+/// Copy `src` into user memory at `dst`.
+/// Fails with EFAULT unless every destination byte is mapped user-writable.
+pub fn copy_to_user(dst: u64, src: &[u8]) -> Result<(), u64> {
+    if dst == 0 || !user_range_accessible(dst, src.len() as u64, true) {
+        return Err(errno::EFAULT);
+    }
+    unsafe { core::ptr::copy_nonoverlapping(src.as_ptr(), dst as *mut u8, src.len()); }
+    Ok(())
+}
+
+/// Copy user memory at `src` into `dst`.
+/// Fails with EFAULT unless every source byte is mapped user-readable.
+pub fn copy_from_user(dst: &mut [u8], src: u64) -> Result<(), u64> {
+    if src == 0 || !user_range_accessible(src, dst.len() as u64, false) {
+        return Err(errno::EFAULT);
+    }
+    unsafe { core::ptr::copy_nonoverlapping(src as *const u8, dst.as_mut_ptr(), dst.len()); }
+    Ok(())
+}
+
+/// Read one native-endian u64 (e.g. a pointer in a user `char**`).
+pub fn read_u64_from_user(src: u64) -> Result<u64, u64> {
+    let mut bytes = [0u8; 8];
+    copy_from_user(&mut bytes, src)?;
+    Ok(u64::from_ne_bytes(bytes))
+}
+
+/// Copy a NUL-terminated user string of at most `max` bytes (excluding
+/// the NUL). Each page is validated before it is scanned, so the string may
+/// end right at the edge of the mapping. Fails with EFAULT on an unmapped
+/// byte and E2BIG if no NUL appears within `max` bytes.
+pub fn copy_cstr_from_user(src: u64, max: usize) -> Result<Vec<u8>, u64> {
+    if src == 0 {
+        return Err(errno::EFAULT);
+    }
+    let mut out = Vec::new();
+    let mut addr = src;
+    loop {
+        let chunk = (PAGE_SIZE - addr % PAGE_SIZE).min((max + 1 - out.len()) as u64);
+        if !user_range_accessible(addr, chunk, false) {
+            return Err(errno::EFAULT);
+        }
+        let bytes = unsafe { core::slice::from_raw_parts(addr as *const u8, chunk as usize) };
+        if let Some(nul) = bytes.iter().position(|&b| b == 0) {
+            out.extend_from_slice(&bytes[..nul]);
+            return Ok(out);
+        }
+        out.extend_from_slice(bytes);
+        if out.len() > max {
+            return Err(errno::E2BIG);
+        }
+        addr += chunk;
+    }
+}
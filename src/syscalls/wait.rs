@@ -0,0 +1,49 @@
+//! `wait` ABI shared by the kernel and userland: atomiclibc includes this
+//! file as its `wait` module, so both sides encode and decode exit statuses
+//! the same way. Keep it free of kernel dependencies.
+//!
+//! A status holds the exit code in bits 8-15 and, for a task ended by a
+//! signal, the signal number in bits 0-6 (as POSIX `wait` reports them).
+
+/// SYS_WAIT option: return 0 at once instead of blocking if no matching
+/// child has exited yet.
+pub const WNOHANG: u64 = 1;
+
+/// Status of a task that exited with `code` (only the low 8 bits are kept).
+pub const fn exited_status(code: u64) -> u64 {
+    (code & 0xFF) << 8
+}
+
+/// Status of a task ended by signal `sig`.
+pub const fn signaled_status(sig: u32) -> u64 {
+    sig as u64 & 0x7F
+}
+
+/// True if the task exited on its own (WIFEXITED).
+pub const fn exited(status: u64) -> bool {
+    status & 0x7F == 0
+}
+
+/// Exit code of a task that exited on its own (WEXITSTATUS).
+pub const fn exit_code(status: u64) -> u64 {
+    (status >> 8) & 0xFF
+}
+
+/// True if the task was ended by a signal (WIFSIGNALED).
+pub const fn signaled(status: u64) -> bool {
+    status & 0x7F != 0
+}
+
+/// Signal that ended the task (WTERMSIG).
+pub const fn term_signal(status: u64) -> u32 {
+    (status & 0x7F) as u32
+}
+
+/// The status as shells print it: the exit code, or 128 + the signal number.
+pub const fn shell_code(status: u64) -> u64 {
+    if signaled(status) {
+        128 + term_signal(status) as u64
+    } else {
+        exit_code(status)
+    }
+}
@@ -0,0 +1,60 @@
+use alloc::string::String;
+use spin::Mutex;
+use lazy_static::lazy_static;
+
+/// Kernel identity, shared by `version`, `neofetch`, `hostname` and the prompt.
+pub const OS_NAME: &str = "AtomicOS";
+pub const VERSION: &str = "0.2.0";
+pub const ARCH: &str = "x86_64";
+pub const KERNEL: &str = "Rust no_std + alloc";
+pub const BOOT: &str = "Multiboot2 / GRUB";
+pub const TOOLCHAIN: &str = "GNU Toolchain (nasm + ld)";
+
+/// Where the hostname is persisted (seeded by `fs::init`).
+pub const HOSTNAME_PATH: &str = "/etc/hostname";
+const DEFAULT_HOSTNAME: &str = "atomicos";
+
+lazy_static! {
+    /// User currently logged in to the shell (changed by `login`/`su`).
+    static ref CURRENT_USER: Mutex<String> = Mutex::new(String::from("root"));
+}
+
+/// Name of the logged-in user.
+pub fn current_user() -> String {
+    CURRENT_USER.lock().clone()
+}
+
+/// Switch the logged-in user. There is no authentication yet.
+pub fn set_user(name: &str) {
+    *CURRENT_USER.lock() = String::from(name);
+}
+
+/// Hostname read from `/etc/hostname`, falling back to the default.
+pub fn hostname() -> String {
+    let mut buf = [0u8; 64];
+    let vfs = crate::fs::VFS.lock();
+    let name = match vfs.read_file(HOSTNAME_PATH, 0, &mut buf) {
+        Ok(n) => core::str::from_utf8(&buf[..n]).unwrap_or("").trim(),
+        Err(_) => "",
+    };
+    if name.is_empty() {
+        String::from(DEFAULT_HOSTNAME)
+    } else {
+        String::from(name)
+    }
+}
+
+/// Replace the contents of `/etc/hostname`.
+pub fn set_hostname(name: &str) -> crate::fs::error::FsResult<()> {
+    let mut vfs = crate::fs::VFS.lock();
+    // Writes do not truncate, so recreate the file to drop the old name
+    let _ = vfs.unlink(HOSTNAME_PATH);
+    vfs.create(HOSTNAME_PATH)?;
+    vfs.write_file(HOSTNAME_PATH, alloc::format!("{}\n", name).as_bytes())?;
+    Ok(())
+}
+
+/// Seconds since boot, derived from the PIT tick counter.
+pub fn uptime_secs() -> u64 {
+    crate::shell::commands::uptime::nanos_since_boot() / 1_000_000_000
+}
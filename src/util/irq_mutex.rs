@@ -0,0 +1,76 @@
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use x86_64::instructions::interrupts;
+
+/// A spin mutex held with interrupts disabled.
+///
+/// The timer preempts kernel code, so a plain `spin::Mutex` may be left
+/// held by a task that is switched out, and anything that waits for the
+/// lock while holding the scheduler (or from an interrupt handler) never
+/// sees it released. With interrupts off while the lock is held, the holder
+/// always runs until it unlocks, and a lock found busy from an exception
+/// handler belongs to the code it interrupted.
+pub struct IrqMutex<T> {
+    inner: spin::Mutex<T>,
+}
+
+pub struct IrqMutexGuard<'a, T> {
+    guard: ManuallyDrop<spin::MutexGuard<'a, T>>,
+    /// Whether interrupts were enabled before the lock was taken.
+    enable: bool,
+}
+
+impl<T> IrqMutex<T> {
+    pub const fn new(value: T) -> Self {
+        IrqMutex { inner: spin::Mutex::new(value) }
+    }
+
+    /// Disable interrupts, then spin until the lock is free.
+    pub fn lock(&self) -> IrqMutexGuard<'_, T> {
+        let enable = interrupts::are_enabled();
+        interrupts::disable();
+        IrqMutexGuard { guard: ManuallyDrop::new(self.inner.lock()), enable }
+    }
+
+    /// Acquire the lock only if it is free right now.
+    pub fn try_lock(&self) -> Option<IrqMutexGuard<'_, T>> {
+        let enable = interrupts::are_enabled();
+        interrupts::disable();
+        match self.inner.try_lock() {
+            Some(guard) => Some(IrqMutexGuard { guard: ManuallyDrop::new(guard), enable }),
+            None => {
+                if enable {
+                    interrupts::enable();
+                }
+                None
+            }
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.inner.is_locked()
+    }
+}
+
+impl<T> Deref for IrqMutexGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for IrqMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for IrqMutexGuard<'_, T> {
+    fn drop(&mut self) {
+        // Unlocked before interrupts come back, so no tick finds it held
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+        if self.enable {
+            interrupts::enable();
+        }
+    }
+}
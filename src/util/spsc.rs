@@ -0,0 +1,85 @@
+// Single-producer single-consumer ring buffer for handing items from an
+// interrupt handler to the tasks that consume them, without a lock the
+// handler could find held.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use x86_64::instructions::interrupts;
+
+/// Fixed-size ring of `N - 1` usable slots of `Copy` items.
+///
+/// The producer owns the slot at `head` until it publishes it by advancing
+/// `head` with a Release store; the consumer's Acquire load of `head` then
+/// guarantees it sees the slot contents written before that store. The same
+/// pairing on `tail` hands a slot back to the producer only once the
+/// consumer has finished copying it out, so a slot being reused is never
+/// read half-written.
+///
+/// `push` and `pop` each run with interrupts disabled. On this single CPU
+/// that keeps a push from an interrupt handler from landing in the middle of
+/// a push or pop made by a task (a test feeding events, or two tasks reading
+/// the keyboard), so each side behaves as a single producer and consumer.
+pub struct SpscRing<T: Copy, const N: usize> {
+    slots: UnsafeCell<[T; N]>,
+    /// Next slot the producer writes.
+    head: AtomicUsize,
+    /// Next slot the consumer reads; equal to `head` when empty.
+    tail: AtomicUsize,
+}
+
+// SAFETY: slots are only accessed through `push` and `pop`, which never touch
+// the same slot at once (see the type's documentation).
+unsafe impl<T: Copy + Send, const N: usize> Sync for SpscRing<T, N> {}
+
+impl<T: Copy, const N: usize> SpscRing<T, N> {
+    /// An empty ring; `fill` is only there to initialize the slots.
+    pub const fn new(fill: T) -> Self {
+        Self {
+            slots: UnsafeCell::new([fill; N]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Append `item`, or give it back if the ring is full.
+    pub fn push(&self, item: T) -> Result<(), T> {
+        interrupts::without_interrupts(|| {
+            // Only the producer stores `head`, so this load can be Relaxed
+            let head = self.head.load(Ordering::Relaxed);
+            let next_head = (head + 1) % N;
+            // Acquire: the consumer is done with the slot it handed back
+            if next_head == self.tail.load(Ordering::Acquire) {
+                return Err(item);
+            }
+
+            // SAFETY: the slot at `head` is not visible to the consumer until
+            // the Release store below, and `head < N`
+            unsafe { (self.slots.get() as *mut T).add(head).write(item) };
+            self.head.store(next_head, Ordering::Release);
+            Ok(())
+        })
+    }
+
+    /// Remove the oldest item, if any.
+    pub fn pop(&self) -> Option<T> {
+        interrupts::without_interrupts(|| {
+            // Only the consumer stores `tail`
+            let tail = self.tail.load(Ordering::Relaxed);
+            // Acquire: pairs with the producer's Release, making the slot
+            // contents visible before they are read
+            if tail == self.head.load(Ordering::Acquire) {
+                return None;
+            }
+
+            // SAFETY: the producer published this slot and won't reuse it
+            // until the Release store below hands it back; `tail < N`
+            let item = unsafe { (self.slots.get() as *const T).add(tail).read() };
+            self.tail.store((tail + 1) % N, Ordering::Release);
+            Some(item)
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tail.load(Ordering::Acquire) == self.head.load(Ordering::Acquire)
+    }
+}
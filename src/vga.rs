@@ -30,7 +30,7 @@ pub enum Color {
 struct ColorCode(u8);
 
 impl ColorCode {
-    fn new(foreground: Color, background: Color) -> ColorCode {
+    const fn new(foreground: Color, background: Color) -> ColorCode {
         ColorCode((background as u8) << 4 | (foreground as u8))
     }
 }
@@ -44,6 +44,11 @@ struct ScreenChar {
 
 const BUFFER_HEIGHT: usize = 25;
 const BUFFER_WIDTH: usize = 80;
+/// Lines that scrolled off the top and can be brought back with `scroll_up`.
+pub const SCROLLBACK_LINES: usize = 500;
+
+/// One bit per screen row, as in `Writer::dirty`.
+const ALL_ROWS: u32 = (1 << BUFFER_HEIGHT) - 1;
 
 #[repr(transparent)]
 struct Buffer {
@@ -51,13 +56,232 @@ struct Buffer {
 }
 
 pub struct Writer {
+    row_position: usize,
     column_position: usize,
     color_code: ColorCode,
     buffer: &'static mut Buffer,
+    /// The live screen. Output is drawn here and copied to `buffer` by `flush`.
+    shadow: [Line; BUFFER_HEIGHT],
+    /// Rows of `shadow` changed since the last flush, bit `n` for row `n`.
+    dirty: u32,
+    /// Lines the view is scrolled back from the bottom; 0 shows the live screen.
+    view_offset: usize,
+    /// Escape sequence in progress, kept across `write_string` calls.
+    ansi: AnsiParser,
+    /// SGR 1: foreground colors 30-37 are drawn in their bright variants.
+    bold: bool,
+    /// The mouse cursor, if drawn. Hidden around every change to the screen
+    /// (see `with_writer`), so it never scrolls along with the text.
+    mouse: Option<MouseCursor>,
+    /// Lines scrolled off the top since boot. Added to a row, it gives a
+    /// position that stays put while the screen scrolls.
+    scrolled: usize,
+    /// Position, as (scrolled + row, column), that `backspace` won't erase
+    /// past: the end of the shell prompt.
+    backspace_limit: Option<(usize, usize)>,
+}
+
+/// A cell drawn with inverted colors to show the mouse position.
+#[derive(Debug, Clone, Copy)]
+struct MouseCursor {
+    row: usize,
+    col: usize,
+    /// The cell as it was before it was inverted.
+    saved: ScreenChar,
+}
+
+/// `ch` with foreground and background swapped. The background only has
+/// three bits (the fourth is blink), so a bright foreground loses its intensity.
+fn inverted(ch: ScreenChar) -> ScreenChar {
+    let attr = ch.color_code.0;
+    ScreenChar {
+        ascii_character: ch.ascii_character,
+        color_code: ColorCode((attr & 0x07) << 4 | attr >> 4),
+    }
+}
+
+/// White on black, used at boot and restored by `clear_screen`.
+const DEFAULT_COLOR: (Color, Color) = (Color::White, Color::Black);
+
+type Line = [ScreenChar; BUFFER_WIDTH];
+
+const BLANK_LINE: Line = [ScreenChar {
+    ascii_character: b' ',
+    color_code: ColorCode::new(DEFAULT_COLOR.0, DEFAULT_COLOR.1),
+}; BUFFER_WIDTH];
+
+/// Lines scrolled off the top of the screen, oldest overwritten first.
+/// Static rather than in `Writer`: at 80 KB it would not fit on the boot
+/// stack while `WRITER` is being built. Only locked with `WRITER` held.
+struct Scrollback {
+    lines: [Line; SCROLLBACK_LINES],
+    /// Lines ever pushed; line `n` lives at `n % SCROLLBACK_LINES` until overwritten.
+    pushed: usize,
+}
+
+impl Scrollback {
+    /// Lines currently held.
+    fn len(&self) -> usize {
+        self.pushed.min(SCROLLBACK_LINES)
+    }
+
+    fn push(&mut self, line: Line) {
+        self.lines[self.pushed % SCROLLBACK_LINES] = line;
+        self.pushed += 1;
+    }
+
+    /// Line `i` of the scrollback followed by the `live` screen, oldest first.
+    fn line<'a>(&'a self, i: usize, live: &'a [Line; BUFFER_HEIGHT]) -> &'a Line {
+        let held = self.len();
+        if i < held {
+            &self.lines[(self.pushed - held + i) % SCROLLBACK_LINES]
+        } else {
+            &live[i - held]
+        }
+    }
+}
+
+static SCROLLBACK: Mutex<Scrollback> = Mutex::new(Scrollback {
+    lines: [BLANK_LINE; SCROLLBACK_LINES],
+    pushed: 0,
+});
+
+// ══════════════════════════════════════════════════════════════
+//  ANSI escape sequences
+// ══════════════════════════════════════════════════════════════
+
+const ESC: u8 = 0x1b;
+/// Parameters kept per CSI sequence; later ones are parsed but dropped.
+const CSI_MAX_PARAMS: usize = 8;
+
+/// ANSI color number (30-37 minus 30) to VGA palette color.
+const ANSI_COLORS: [Color; 8] = [
+    Color::Black, Color::Red, Color::Green, Color::Brown,
+    Color::Blue, Color::Magenta, Color::Cyan, Color::LightGray,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnsiState {
+    /// Plain text.
+    Ground,
+    /// After ESC.
+    Escape,
+    /// After `ESC [`, collecting parameters until the final byte.
+    Csi,
+}
+
+/// A complete `ESC [ params final` sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Csi {
+    pub final_byte: u8,
+    params: [u16; CSI_MAX_PARAMS],
+    len: usize,
+    /// A private marker such as `?` was present (`ESC [ ? 25 l`).
+    pub private: bool,
+}
+
+impl Csi {
+    /// Parameter `i`, or `default` if it is missing or 0.
+    pub fn param(&self, i: usize, default: u16) -> u16 {
+        match self.params.get(i) {
+            Some(&p) if i < self.len && p != 0 => p,
+            _ => default,
+        }
+    }
+
+    /// The parameters given, missing ones as 0.
+    pub fn params(&self) -> &[u16] {
+        &self.params[..self.len]
+    }
+}
+
+/// VT100-style escape sequence recognizer. Bytes are fed one at a time, so a
+/// sequence split across writes is picked up where it left off. Anything
+/// that isn't a CSI sequence is dropped along with its ESC.
+#[derive(Debug, Clone, Copy)]
+pub struct AnsiParser {
+    state: AnsiState,
+    csi: Csi,
+}
+
+/// What `AnsiParser::feed` made of a byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiAction {
+    /// Ordinary output.
+    Print(u8),
+    /// Part of an escape sequence; nothing to do yet.
+    Pending,
+    /// A finished CSI sequence.
+    Csi(Csi),
+}
+
+impl AnsiParser {
+    pub const fn new() -> Self {
+        AnsiParser {
+            state: AnsiState::Ground,
+            csi: Csi { final_byte: 0, params: [0; CSI_MAX_PARAMS], len: 0, private: false },
+        }
+    }
+
+    pub fn feed(&mut self, byte: u8) -> AnsiAction {
+        match self.state {
+            AnsiState::Ground if byte == ESC => {
+                self.state = AnsiState::Escape;
+                AnsiAction::Pending
+            }
+            AnsiState::Ground => AnsiAction::Print(byte),
+            AnsiState::Escape => {
+                self.state = match byte {
+                    b'[' => {
+                        self.csi = AnsiParser::new().csi;
+                        AnsiState::Csi
+                    }
+                    ESC => AnsiState::Escape,
+                    // Two-byte sequences (ESC c, ESC 7, ...) aren't supported
+                    _ => AnsiState::Ground,
+                };
+                AnsiAction::Pending
+            }
+            AnsiState::Csi => match byte {
+                b'0'..=b'9' => {
+                    if self.csi.len == 0 {
+                        self.csi.len = 1;
+                    }
+                    if let Some(p) = self.csi.params.get_mut(self.csi.len - 1) {
+                        *p = p.saturating_mul(10).saturating_add((byte - b'0') as u16);
+                    }
+                    AnsiAction::Pending
+                }
+                b';' => {
+                    // "ESC [ ; 5 H": the empty first parameter counts too
+                    self.csi.len = (self.csi.len.max(1) + 1).min(CSI_MAX_PARAMS + 1);
+                    AnsiAction::Pending
+                }
+                b'<'..=b'?' => {
+                    self.csi.private = true;
+                    AnsiAction::Pending
+                }
+                // Intermediate bytes: none of the sequences handled use them
+                0x20..=0x2F => AnsiAction::Pending,
+                0x40..=0x7E => {
+                    self.state = AnsiState::Ground;
+                    self.csi.final_byte = byte;
+                    self.csi.len = self.csi.len.min(CSI_MAX_PARAMS);
+                    AnsiAction::Csi(self.csi)
+                }
+                // A control character or stray byte aborts the sequence
+                _ => {
+                    self.state = AnsiState::Ground;
+                    AnsiAction::Pending
+                }
+            },
+        }
+    }
 }
 
 impl Writer {
     pub fn write_byte(&mut self, byte: u8) {
+        self.snap_to_bottom();
         match byte {
             b'\n' => self.new_line(),
             byte => {
@@ -65,11 +289,11 @@ impl Writer {
                     self.new_line();
                 }
 
-                let row = BUFFER_HEIGHT - 1;
+                let row = self.row_position;
                 let col = self.column_position;
 
                 let color_code = self.color_code;
-                self.buffer.chars[row][col].write(ScreenChar {
+                self.put(row, col, ScreenChar {
                     ascii_character: byte,
                     color_code,
                 });
@@ -79,33 +303,133 @@ impl Writer {
     }
 
     fn new_line(&mut self) {
-        for row in 1..BUFFER_HEIGHT {
+        if self.row_position + 1 < BUFFER_HEIGHT {
+            self.row_position += 1;
+        } else {
+            self.scroll();
+        }
+        self.column_position = 0;
+    }
+
+    /// Move every line up by one, saving the top line in the scrollback,
+    /// and blank the bottom line. Only the shadow moves; the screen catches
+    /// up on the next flush, however many lines scrolled meanwhile.
+    fn scroll(&mut self) {
+        SCROLLBACK.lock().push(self.shadow[0]);
+        self.scrolled += 1;
+        self.shadow.copy_within(1.., 0);
+        self.dirty = ALL_ROWS;
+        self.clear_row(BUFFER_HEIGHT - 1);
+    }
+
+    /// Draw `ch` at (row, col) of the shadow.
+    fn put(&mut self, row: usize, col: usize, ch: ScreenChar) {
+        self.shadow[row][col] = ch;
+        self.dirty |= 1 << row;
+    }
+
+    /// Copy the rows changed since the last flush to the screen and move
+    /// the hardware cursor to the output position. While the view is
+    /// scrolled back the rows wait until it returns to the bottom.
+    pub fn flush(&mut self) {
+        if self.view_offset == 0 {
+            for row in 0..BUFFER_HEIGHT {
+                if self.dirty & 1 << row != 0 {
+                    for col in 0..BUFFER_WIDTH {
+                        self.buffer.chars[row][col].write(self.shadow[row][col]);
+                    }
+                }
+            }
+            self.dirty = 0;
+        }
+        self.update_cursor();
+    }
+
+    /// Show `lines` older lines from the scrollback, as far as it goes.
+    /// The hardware cursor is hidden until the view is back at the bottom.
+    pub fn scroll_up(&mut self, lines: usize) {
+        let history = SCROLLBACK.lock();
+        let offset = (self.view_offset + lines).min(history.len());
+        if offset == self.view_offset {
+            return;
+        }
+        self.view_offset = offset;
+        self.paint_view(&history);
+    }
+
+    /// Move the view `lines` lines back towards the live screen.
+    pub fn scroll_down(&mut self, lines: usize) {
+        if self.view_offset == 0 {
+            return;
+        }
+        let history = SCROLLBACK.lock();
+        self.view_offset = self.view_offset.saturating_sub(lines);
+        self.paint_view(&history);
+    }
+
+    /// Return to the live screen if the view is scrolled back; output
+    /// always lands there.
+    fn snap_to_bottom(&mut self) {
+        if self.view_offset != 0 {
+            self.scroll_down(self.view_offset);
+        }
+    }
+
+    /// Redraw the screen `view_offset` lines back. At offset 0 this puts the
+    /// live screen back; the next flush puts the cursor where output left it.
+    fn paint_view(&mut self, history: &Scrollback) {
+        let top = history.len() - self.view_offset;
+        for row in 0..BUFFER_HEIGHT {
+            let line = *history.line(top + row, &self.shadow);
             for col in 0..BUFFER_WIDTH {
-                let character = self.buffer.chars[row][col].read();
-                self.buffer.chars[row - 1][col].write(character);
+                self.buffer.chars[row][col].write(line[col]);
             }
         }
-        self.clear_row(BUFFER_HEIGHT - 1);
-        self.column_position = 0;
+        if self.view_offset == 0 {
+            self.dirty = 0;
+        }
     }
 
+    /// Erase the character before the cursor. At column 0 this is the last
+    /// cell of the row above, where a long line wrapped. Stops at the
+    /// backspace limit, if one is set.
     pub fn backspace(&mut self) {
-        if self.column_position > 0 {
-            self.column_position -= 1;
-        } else {
-            // Se estivermos na coluna 0 de uma linha, não voltamos para a linha de cima (simplicidade).
-            // Em uma implementação mais complexa, poderíamos tracking the previous line length.
+        self.snap_to_bottom();
+        if self.backspace_limit.is_some_and(|limit| self.absolute_position() <= limit) {
             return;
         }
-
-        let row = BUFFER_HEIGHT - 1;
-        let col = self.column_position;
+        let (row, col) = match (self.row_position, self.column_position) {
+            (row, col) if col > 0 => (row, col - 1),
+            (row, _) if row > 0 => (row - 1, BUFFER_WIDTH - 1),
+            // The line above scrolled off the screen
+            _ => return,
+        };
+        self.row_position = row;
+        self.column_position = col;
 
         let blank = ScreenChar {
             ascii_character: b' ',
             color_code: self.color_code,
         };
-        self.buffer.chars[row][col].write(blank);
+        self.put(row, col, blank);
+    }
+
+    /// The output position as (scrolled + row, column). A full row, where
+    /// output would wrap on the next character, counts as the start of the
+    /// next one.
+    fn absolute_position(&self) -> (usize, usize) {
+        let row = self.scrolled + self.row_position;
+        if self.column_position >= BUFFER_WIDTH {
+            (row + 1, 0)
+        } else {
+            (row, self.column_position)
+        }
+    }
+
+    /// Keep `backspace` from erasing anything before the current output
+    /// position, even once the screen has scrolled; `false` lifts the limit.
+    pub fn set_backspace_limit(&mut self, enabled: bool) {
+        self.backspace_limit = enabled.then(|| self.absolute_position());
     }
 
     fn clear_row(&mut self, row: usize) {
@@ -113,25 +437,178 @@ impl Writer {
             ascii_character: b' ',
             color_code: self.color_code,
         };
-        for col in 0..BUFFER_WIDTH {
-            self.buffer.chars[row][col].write(blank);
-        }
+        self.shadow[row] = [blank; BUFFER_WIDTH];
+        self.dirty |= 1 << row;
     }
 
+    /// Blank the whole screen in the default colors and home the cursor.
     pub fn clear_screen(&mut self) {
+        self.snap_to_bottom();
+        self.reset_color();
         for row in 0..BUFFER_HEIGHT {
             self.clear_row(row);
         }
-        self.column_position = 0;
+        self.set_cursor(0, 0);
+    }
+
+    /// Color used for subsequent output.
+    pub fn set_color(&mut self, foreground: Color, background: Color) {
+        self.color_code = ColorCode::new(foreground, background);
+    }
+
+    pub fn reset_color(&mut self) {
+        self.bold = false;
+        self.set_color(DEFAULT_COLOR.0, DEFAULT_COLOR.1);
+    }
+
+    /// Move the output position, clamped to the screen. The hardware cursor
+    /// follows on the next flush.
+    pub fn set_cursor(&mut self, row: usize, col: usize) {
+        self.snap_to_bottom();
+        self.row_position = row.min(BUFFER_HEIGHT - 1);
+        self.column_position = col.min(BUFFER_WIDTH - 1);
+    }
+
+    /// Current output position as (row, column).
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row_position, self.column_position)
+    }
+
+    /// Point the blinking hardware cursor at the output position
+    /// (CRTC registers 0x0E/0x0F: cursor location high/low byte). While
+    /// scrolled back it is parked past the last cell, which hides it.
+    fn update_cursor(&self) {
+        use x86_64::instructions::port::Port;
+        let pos = if self.view_offset != 0 {
+            (BUFFER_HEIGHT * BUFFER_WIDTH) as u16
+        } else {
+            (self.row_position * BUFFER_WIDTH + self.column_position.min(BUFFER_WIDTH - 1)) as u16
+        };
+        let mut index: Port<u8> = Port::new(0x3D4);
+        let mut data: Port<u8> = Port::new(0x3D5);
+        unsafe {
+            index.write(0x0F);
+            data.write(pos as u8);
+            index.write(0x0E);
+            data.write((pos >> 8) as u8);
+        }
+    }
+
+    /// Draw the mouse cursor at (row, col), clamped to the screen, moving it
+    /// if it is shown elsewhere.
+    fn show_mouse(&mut self, row: usize, col: usize) {
+        self.hide_mouse();
+        let (row, col) = (row.min(BUFFER_HEIGHT - 1), col.min(BUFFER_WIDTH - 1));
+        let saved = self.buffer.chars[row][col].read();
+        self.buffer.chars[row][col].write(inverted(saved));
+        self.mouse = Some(MouseCursor { row, col, saved });
+    }
+
+    /// Put back the cell under the mouse cursor. If something was written
+    /// there since the cursor was drawn, the new content stays.
+    fn hide_mouse(&mut self) {
+        if let Some(m) = self.mouse.take() {
+            let cell = &mut self.buffer.chars[m.row][m.col];
+            if cell.read() == inverted(m.saved) {
+                cell.write(m.saved);
+            }
+        }
     }
 
     pub fn write_string(&mut self, s: &str) {
         for byte in s.bytes() {
-            match byte {
-                0x20..=0x7e | b'\n' => self.write_byte(byte),
-                _ => self.write_byte(0xfe),
+            match self.ansi.feed(byte) {
+                AnsiAction::Print(byte @ (0x20..=0x7e | b'\n')) => self.write_byte(byte),
+                AnsiAction::Print(_) => self.write_byte(0xfe),
+                AnsiAction::Pending => {}
+                AnsiAction::Csi(csi) => self.apply_csi(&csi),
+            }
+        }
+    }
+
+    /// Carry out a CSI sequence: SGR colors, cursor movement and erasing.
+    /// Other sequences are ignored.
+    fn apply_csi(&mut self, csi: &Csi) {
+        if csi.private {
+            return;
+        }
+        self.snap_to_bottom();
+        let n = csi.param(0, 1) as usize;
+        let (row, col) = (self.row_position, self.column_position.min(BUFFER_WIDTH - 1));
+        match csi.final_byte {
+            b'm' => self.select_graphic_rendition(csi.params()),
+            b'H' | b'f' => {
+                let row = csi.param(0, 1) as usize - 1;
+                let col = csi.param(1, 1) as usize - 1;
+                self.set_cursor(row, col);
+            }
+            b'A' => self.set_cursor(row.saturating_sub(n), col),
+            b'B' => self.set_cursor(row + n, col),
+            b'C' => self.set_cursor(row, col + n),
+            b'D' => self.set_cursor(row, col.saturating_sub(n)),
+            b'J' => {
+                let cursor = row * BUFFER_WIDTH + col;
+                match csi.param(0, 0) {
+                    0 => self.clear_cells(cursor, BUFFER_HEIGHT * BUFFER_WIDTH),
+                    1 => self.clear_cells(0, cursor + 1),
+                    2 => self.clear_cells(0, BUFFER_HEIGHT * BUFFER_WIDTH),
+                    _ => {}
+                }
+            }
+            b'K' => {
+                let start = row * BUFFER_WIDTH;
+                match csi.param(0, 0) {
+                    0 => self.clear_cells(start + col, start + BUFFER_WIDTH),
+                    1 => self.clear_cells(start, start + col + 1),
+                    2 => self.clear_cells(start, start + BUFFER_WIDTH),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// SGR: 0 reset, 1/22 bold on/off, 30-37/39 foreground, 40-47/49 background.
+    /// No parameters means reset.
+    fn select_graphic_rendition(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            self.reset_color();
+            return;
+        }
+        let mut fg = self.color_code.0 & 0x0F;
+        let mut bg = self.color_code.0 >> 4;
+        for &p in params {
+            match p {
+                0 => {
+                    self.bold = false;
+                    fg = DEFAULT_COLOR.0 as u8;
+                    bg = DEFAULT_COLOR.1 as u8;
+                }
+                1 => {
+                    self.bold = true;
+                    fg |= 0x08;
+                }
+                22 => {
+                    self.bold = false;
+                    fg &= 0x07;
+                }
+                30..=37 => fg = ANSI_COLORS[(p - 30) as usize] as u8 | if self.bold { 0x08 } else { 0 },
+                39 => fg = DEFAULT_COLOR.0 as u8,
+                40..=47 => bg = ANSI_COLORS[(p - 40) as usize] as u8,
+                49 => bg = DEFAULT_COLOR.1 as u8,
+                _ => {}
             }
         }
+        self.color_code = ColorCode(bg << 4 | fg);
+    }
+
+    /// Blank screen cells `from..to`, counted row by row from the top left,
+    /// in the current colors. The cursor doesn't move.
+    fn clear_cells(&mut self, from: usize, to: usize) {
+        let blank = ScreenChar { ascii_character: b' ', color_code: self.color_code };
+        for cell in from..to.min(BUFFER_HEIGHT * BUFFER_WIDTH) {
+            self.put(cell / BUFFER_WIDTH, cell % BUFFER_WIDTH, blank);
+        }
     }
 }
 
@@ -144,9 +621,18 @@ impl fmt::Write for Writer {
 
 lazy_static! {
     pub static ref WRITER: Mutex<Writer> = Mutex::new(Writer {
+        row_position: 0,
         column_position: 0,
-        color_code: ColorCode::new(Color::White, Color::Black),
+        color_code: ColorCode::new(DEFAULT_COLOR.0, DEFAULT_COLOR.1),
         buffer: unsafe { &mut *(0xb8000 as *mut Buffer) },
+        shadow: [BLANK_LINE; BUFFER_HEIGHT],
+        dirty: ALL_ROWS,
+        view_offset: 0,
+        ansi: AnsiParser::new(),
+        bold: false,
+        mouse: None,
+        scrolled: 0,
+        backspace_limit: None,
     });
 }
 
@@ -161,17 +647,266 @@ macro_rules! println {
     ($($arg:tt)*) => ($crate::print!("{}\n", format_args!($($arg)*)));
 }
 
+/// Output of one task collected instead of drawn (see `start_capture`).
+struct Capture {
+    pid: u64,
+    text: alloc::string::String,
+}
+
+static CAPTURES: Mutex<alloc::vec::Vec<Capture>> = Mutex::new(alloc::vec::Vec::new());
+
 #[doc(hidden)]
 pub fn _print(args: fmt::Arguments) {
     use core::fmt::Write;
-    use x86_64::instructions::interrupts;
-    
+
+    // Interrupt and exception handlers always print to the screen
+    if x86_64::instructions::interrupts::are_enabled() {
+        let captured = x86_64::instructions::interrupts::without_interrupts(|| {
+            let pid = crate::scheduler::current_pid().0;
+            let mut captures = CAPTURES.lock();
+            match captures.iter_mut().find(|c| c.pid == pid) {
+                Some(c) => c.text.write_fmt(args).is_ok(),
+                None => false,
+            }
+        });
+        if captured {
+            return;
+        }
+    }
+
     // We disable interrupts when locking the writer to avoid deadlock in exception handlers
-    interrupts::without_interrupts(|| {
-        WRITER.lock().write_fmt(args).unwrap();
+    with_writer(|w| w.write_fmt(args).unwrap());
+    crate::serial::console_print(args);
+}
+
+/// Collect everything task `pid` prints from now on instead of drawing it,
+/// until `take_capture`. The shell uses this to send builtin output to
+/// redirect targets and pipes.
+pub fn start_capture(pid: u64) {
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        let mut captures = CAPTURES.lock();
+        captures.retain(|c| c.pid != pid);
+        captures.push(Capture { pid, text: alloc::string::String::new() });
     });
 }
 
+/// Stop capturing the output of task `pid` and return what it printed meanwhile.
+pub fn take_capture(pid: u64) -> Option<alloc::string::String> {
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        let mut captures = CAPTURES.lock();
+        let i = captures.iter().position(|c| c.pid == pid)?;
+        Some(captures.swap_remove(i).text)
+    })
+}
+
+/// Run `f` on the locked writer with interrupts disabled, like `_print`.
+/// All screen manipulation outside this module goes through the helpers below.
+/// The mouse cursor is taken off the screen while `f` runs, so `f` sees and
+/// changes only the text, and is drawn again afterwards. What `f` drew is
+/// flushed before the lock is released, so one print is one screen update
+/// however many lines it holds.
+fn with_writer<R>(f: impl FnOnce(&mut Writer) -> R) -> R {
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        let mut writer = WRITER.lock();
+        let mouse = writer.mouse;
+        writer.hide_mouse();
+        let result = f(&mut writer);
+        writer.flush();
+        if let Some(m) = mouse {
+            writer.show_mouse(m.row, m.col);
+        }
+        result
+    })
+}
+
+/// Clear the screen, restore the default colors and home the cursor.
+pub fn clear_screen() {
+    with_writer(|w| w.clear_screen());
+}
+
+/// Erase the character before the cursor, wrapping back to the end of the
+/// row above from column 0.
+pub fn backspace() {
+    with_writer(|w| w.backspace());
+}
+
+/// Stop `backspace` at the current output position (the end of the prompt),
+/// or lift that limit with `false`.
+pub fn set_backspace_limit(enabled: bool) {
+    with_writer(|w| w.set_backspace_limit(enabled));
+}
+
+/// Change the color of subsequent output.
+pub fn set_color(foreground: Color, background: Color) {
+    with_writer(|w| w.set_color(foreground, background));
+}
+
+/// Go back to the default white-on-black output color.
+pub fn reset_color() {
+    with_writer(|w| w.reset_color());
+}
+
+/// Move the output position to (row, col).
+pub fn set_cursor(row: usize, col: usize) {
+    with_writer(|w| w.set_cursor(row, col));
+}
+
+/// Scroll the view `lines` lines back into the scrollback.
+pub fn scroll_up(lines: usize) {
+    with_writer(|w| w.scroll_up(lines));
+}
+
+/// Scroll the view `lines` lines towards the live screen.
+pub fn scroll_down(lines: usize) {
+    with_writer(|w| w.scroll_down(lines));
+}
+
+/// Current output position as (row, column).
+pub fn cursor() -> (usize, usize) {
+    with_writer(|w| w.cursor())
+}
+
+/// The characters shown on screen row `row` (which is clamped to the screen).
+pub fn read_row(row: usize) -> [u8; BUFFER_WIDTH] {
+    with_writer(|w| {
+        let mut text = [0u8; BUFFER_WIDTH];
+        for (col, byte) in text.iter_mut().enumerate() {
+            *byte = w.buffer.chars[row.min(BUFFER_HEIGHT - 1)][col].read().ascii_character;
+        }
+        text
+    })
+}
+
+/// The VGA attribute byte (background << 4 | foreground) at (row, col).
+pub fn attribute_at(row: usize, col: usize) -> u8 {
+    with_writer(|w| w.buffer.chars[row.min(BUFFER_HEIGHT - 1)][col.min(BUFFER_WIDTH - 1)].read().color_code.0)
+}
+
+/// Draw the mouse cursor at (row, col) unless it is already there. Called
+/// from the timer interrupt, so it gives up if the screen is busy; returns
+/// whether the cursor is now at (row, col).
+pub fn try_show_mouse(row: usize, col: usize) -> bool {
+    let Some(mut writer) = WRITER.try_lock() else { return false };
+    if writer.mouse.map(|m| (m.row, m.col)) != Some((row, col)) {
+        writer.show_mouse(row, col);
+    }
+    true
+}
+
+/// Remove the mouse cursor from the screen.
+pub fn hide_mouse() {
+    x86_64::instructions::interrupts::without_interrupts(|| WRITER.lock().hide_mouse());
+}
+
+/// Where the mouse cursor is drawn, as (row, column).
+pub fn mouse_position() -> Option<(usize, usize)> {
+    with_writer(|w| w.mouse.map(|m| (m.row, m.col)))
+}
+
+/// The attribute byte actually on screen at (row, col), mouse cursor included.
+/// `attribute_at` gives the attribute of the text underneath.
+pub fn shown_attribute_at(row: usize, col: usize) -> u8 {
+    x86_64::instructions::interrupts::without_interrupts(|| {
+        WRITER.lock().buffer.chars[row.min(BUFFER_HEIGHT - 1)][col.min(BUFFER_WIDTH - 1)].read().color_code.0
+    })
+}
+
+/// Write raw text without formatting.
+pub fn write_str(s: &str) {
+    with_writer(|w| w.write_string(s));
+}
+
+/// Start from a blank screen (GRUB leaves its own text behind).
 pub fn init() {
-    let _ = WRITER.lock();
+    clear_screen();
+}
+
+/// Lock-free writer used only on the panic path.
+///
+/// `WRITER` may be held by whoever panicked (or by the code a fault interrupted),
+/// so this writes straight to the text buffer without touching the mutex. It
+/// starts from a cleared screen so the message is readable regardless of what
+/// was displayed before.
+pub struct EmergencyWriter {
+    row: usize,
+    column: usize,
+    color_code: ColorCode,
+}
+
+impl EmergencyWriter {
+    /// Clear the screen to white-on-red and start writing at the top-left.
+    ///
+    /// # Safety
+    /// Must only be used once nothing else will touch the VGA buffer again
+    /// (interrupts disabled, no return to normal operation).
+    pub unsafe fn take_over() -> Self {
+        let mut writer = EmergencyWriter {
+            row: 0,
+            column: 0,
+            color_code: ColorCode::new(Color::White, Color::Red),
+        };
+        for row in 0..BUFFER_HEIGHT {
+            writer.clear_row(row);
+        }
+        writer
+    }
+
+    fn buffer() -> *mut ScreenChar {
+        0xb8000 as *mut ScreenChar
+    }
+
+    fn put(&self, row: usize, col: usize, ch: ScreenChar) {
+        unsafe { core::ptr::write_volatile(Self::buffer().add(row * BUFFER_WIDTH + col), ch) };
+    }
+
+    fn get(&self, row: usize, col: usize) -> ScreenChar {
+        unsafe { core::ptr::read_volatile(Self::buffer().add(row * BUFFER_WIDTH + col)) }
+    }
+
+    fn clear_row(&mut self, row: usize) {
+        let blank = ScreenChar { ascii_character: b' ', color_code: self.color_code };
+        for col in 0..BUFFER_WIDTH {
+            self.put(row, col, blank);
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.column = 0;
+        if self.row + 1 < BUFFER_HEIGHT {
+            self.row += 1;
+            return;
+        }
+        // Screen full: scroll up. The newest lines stay visible and the start
+        // of a long report scrolls off; the serial copy keeps all of it
+        for row in 1..BUFFER_HEIGHT {
+            for col in 0..BUFFER_WIDTH {
+                let ch = self.get(row, col);
+                self.put(row - 1, col, ch);
+            }
+        }
+        self.clear_row(BUFFER_HEIGHT - 1);
+    }
+
+    pub fn write_byte(&mut self, byte: u8) {
+        match byte {
+            b'\n' => self.new_line(),
+            byte => {
+                if self.column >= BUFFER_WIDTH {
+                    self.new_line();
+                }
+                let byte = match byte { 0x20..=0x7e => byte, _ => 0xfe };
+                self.put(self.row, self.column, ScreenChar { ascii_character: byte, color_code: self.color_code });
+                self.column += 1;
+            }
+        }
+    }
+}
+
+impl fmt::Write for EmergencyWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            self.write_byte(byte);
+        }
+        Ok(())
+    }
 }
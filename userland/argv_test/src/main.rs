@@ -0,0 +1,40 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate atomiclibc;
+
+use atomiclibc::string::strlen;
+
+/// Prints its own argv and environment, as laid out on the initial stack.
+/// Try `args.elf one two three`.
+#[no_mangle]
+pub extern "C" fn main(argc: isize, argv: *const *const u8) -> isize {
+    printf!("argc = %d\n", argc);
+    for i in 0..argc as usize {
+        let arg = unsafe { cstr(*argv.add(i)) };
+        printf!("argv[%d] = \"%s\"\n", i, arg);
+    }
+    if unsafe { !(*argv.add(argc as usize)).is_null() } {
+        printf!("argv is not NULL-terminated!\n");
+        return 1;
+    }
+
+    let envp = atomiclibc::unistd::environ();
+    let mut i = 0;
+    while !envp.is_null() && unsafe { !(*envp.add(i)).is_null() } {
+        printf!("envp[%d] = \"%s\"\n", i, unsafe { cstr(*envp.add(i)) });
+        i += 1;
+    }
+    match atomiclibc::unistd::getenv("HOME") {
+        Some(home) => { printf!("getenv(\"HOME\") = \"%s\"\n", home); }
+        None => { printf!("getenv(\"HOME\") = NULL\n"); }
+    }
+    0
+}
+
+/// View a NUL-terminated string from the initial stack.
+unsafe fn cstr(s: *const u8) -> &'static str {
+    let bytes = core::slice::from_raw_parts(s, strlen(s));
+    core::str::from_utf8(bytes).unwrap_or("?")
+}
@@ -1,17 +1,32 @@
 use crate::unistd;
 
-// The entry point expected by our ELF linker script.
-// It sets up basic arguments (argc=0, argv=null) and calls `main`.
-#[no_mangle]
-pub extern "C" fn _start() -> ! {
+// The entry point expected by our ELF linker script. The kernel starts us
+// with RSP pointing at argc, followed by the NULL-terminated argv and envp
+// arrays (System V AMD64). RSP is 16-byte aligned there, so a plain `call`
+// leaves the stack just as a C function expects it.
+core::arch::global_asm!(
+    ".globl _start",
+    "_start:",
+    "    xor rbp, rbp",
+    "    mov rdi, [rsp]",
+    "    lea rsi, [rsp + 8]",
+    "    lea rdx, [rsi + rdi * 8 + 8]",
+    "    call {start_main}",
+    "    ud2",
+    start_main = sym start_main,
+);
+
+/// Record the environment, run `main` and exit with its return value.
+extern "C" fn start_main(argc: isize, argv: *const *const u8, envp: *const *const u8) -> ! {
     extern "C" {
         fn main(argc: isize, argv: *const *const u8) -> isize;
     }
 
+    unistd::set_environ(envp);
+
     // Call the user's main function
-    let ret = unsafe { main(0, core::ptr::null()) };
+    let ret = unsafe { main(argc, argv) };
 
     // Exit the process cleanly
     unistd::exit(ret as i32);
-    loop {}
 }
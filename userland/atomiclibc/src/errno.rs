@@ -0,0 +1,79 @@
+// Error numbers returned by the kernel (mirrors src/syscalls/errno.rs).
+//
+// A failing syscall returns `-(errno)` in rax. The `unistd` wrappers pass this
+// through as a negative `isize`, so callers test `ret < 0` and use `-ret` as the
+// errno. Non-negative values are successful results.
+
+pub const EPERM: isize   = 1;
+pub const ENOENT: isize  = 2;
+pub const ESRCH: isize   = 3;
+pub const EINTR: isize   = 4;
+pub const EIO: isize     = 5;
+pub const E2BIG: isize   = 7;
+pub const ENOEXEC: isize = 8;
+pub const EBADF: isize   = 9;
+pub const ECHILD: isize  = 10;
+pub const EAGAIN: isize  = 11;
+pub const ENOMEM: isize  = 12;
+pub const EACCES: isize  = 13;
+pub const EFAULT: isize  = 14;
+pub const EEXIST: isize  = 17;
+pub const ENODEV: isize  = 19;
+pub const ENOTDIR: isize = 20;
+pub const EISDIR: isize  = 21;
+pub const EINVAL: isize  = 22;
+pub const EMFILE: isize  = 24;
+pub const ENOSPC: isize  = 28;
+pub const ESPIPE: isize  = 29;
+pub const EPIPE: isize   = 32;
+pub const ERANGE: isize  = 34;
+pub const ENAMETOOLONG: isize = 36;
+pub const ENOSYS: isize  = 38;
+pub const ELOOP: isize   = 40;
+pub const ENOTSUP: isize = 95;
+
+/// Largest errno value; raw returns in `[-MAX_ERRNO, -1]` are errors.
+pub const MAX_ERRNO: isize = 4095;
+
+/// Split a wrapper's return value into `Ok(value)` or `Err(errno)`.
+pub fn check(ret: isize) -> Result<usize, isize> {
+    if ret < 0 && ret >= -MAX_ERRNO {
+        Err(-ret)
+    } else {
+        Ok(ret as usize)
+    }
+}
+
+/// Short description of an errno, for diagnostics.
+pub fn strerror(errno: isize) -> &'static str {
+    match errno {
+        EPERM   => "Operation not permitted",
+        ENOENT  => "No such file or directory",
+        ESRCH   => "No such process",
+        EINTR   => "Interrupted system call",
+        EIO     => "I/O error",
+        E2BIG   => "Argument list too long",
+        ENOEXEC => "Exec format error",
+        EBADF   => "Bad file descriptor",
+        ECHILD  => "No child processes",
+        EAGAIN  => "Resource temporarily unavailable",
+        ENOMEM  => "Out of memory",
+        EACCES  => "Permission denied",
+        EFAULT  => "Bad address",
+        EEXIST  => "File exists",
+        ENODEV  => "No such device",
+        ENOTDIR => "Not a directory",
+        EISDIR  => "Is a directory",
+        EINVAL  => "Invalid argument",
+        EMFILE  => "Too many open files",
+        ENOSPC  => "No space left on device",
+        ESPIPE  => "Illegal seek",
+        EPIPE   => "Broken pipe",
+        ERANGE  => "Result too large",
+        ENAMETOOLONG => "File name too long",
+        ENOSYS  => "Function not implemented",
+        ELOOP   => "Too many levels of symbolic links",
+        ENOTSUP => "Operation not supported",
+        _       => "Unknown error",
+    }
+}
@@ -2,10 +2,20 @@
 
 pub mod syscall;
 pub mod unistd;
+pub mod errno;
 pub mod stdio;
 pub mod string;
 pub mod malloc;
 pub mod crt0;
+/// The kernel's `stat` ABI, shared verbatim.
+#[path = "../../../src/syscalls/stat.rs"]
+pub mod stat;
+/// The kernel's `poll` ABI, shared verbatim.
+#[path = "../../../src/syscalls/poll.rs"]
+pub mod poll;
+/// The kernel's `wait` status encoding, shared verbatim.
+#[path = "../../../src/syscalls/wait.rs"]
+pub mod wait;
 
 use core::panic::PanicInfo;
 
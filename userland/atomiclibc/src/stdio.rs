@@ -10,6 +10,22 @@ pub fn putchar(c: u8) {
     unistd::write(1, &buf);
 }
 
+/// Read one line from stdin into `buf`, blocking until Enter. The kernel
+/// echoes and handles Backspace. Returns the length including the trailing
+/// '\n' (a line longer than `buf` arrives over several calls), or -errno.
+pub fn getline(buf: &mut [u8]) -> isize {
+    unistd::read(0, buf)
+}
+
+/// Read one byte from stdin; blocks for a whole line the first time.
+pub fn getchar() -> Option<u8> {
+    let mut c = [0u8; 1];
+    match unistd::read(0, &mut c) {
+        1 => Some(c[0]),
+        _ => None,
+    }
+}
+
 // A minimal `print!` macro equivalent mechanism for `printf`.
 // It takes a string with custom formatting: %s (string), %d (int), %x (hex).
 pub fn printf(format: &str, args: &[PrintfArg]) {
@@ -54,3 +54,21 @@ pub unsafe fn syscall3(n: u64, a1: u64, a2: u64, a3: u64) -> u64 {
     );
     ret
 }
+
+/// The 4th argument travels in R10 (RCX is clobbered by `syscall` on Linux;
+/// AtomicOS keeps the same register for compatibility).
+#[inline(always)]
+pub unsafe fn syscall4(n: u64, a1: u64, a2: u64, a3: u64, a4: u64) -> u64 {
+    let ret: u64;
+    asm!(
+        "int 0x80",
+        in("rax") n,
+        in("rdi") a1,
+        in("rsi") a2,
+        in("rdx") a3,
+        in("r10") a4,
+        out("rax") ret,
+        options(nostack, preserves_flags)
+    );
+    ret
+}
@@ -1,4 +1,9 @@
 use crate::syscall::*;
+use crate::poll::PollFd;
+use crate::stat::Stat;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+// All wrappers returning `isize` report failure as `-(errno)`; see `errno::check`.
 
 pub const SYS_EXIT:  u64 = 0;
 pub const SYS_WRITE: u64 = 1;
@@ -15,9 +20,83 @@ pub const SYS_READ:  u64 = 9;
 pub const SYS_DUP:   u64 = 10;
 pub const SYS_DUP2:  u64 = 11;
 pub const SYS_PIPE:  u64 = 12;
+pub const SYS_LSEEK: u64 = 16;
+
+/// `whence` values for `lseek`.
+pub const SEEK_SET: u64 = 0;
+pub const SEEK_CUR: u64 = 1;
+pub const SEEK_END: u64 = 2;
+
+/// `open` access modes and flags.
+pub const O_RDONLY: u64 = 0;
+pub const O_WRONLY: u64 = 1;
+pub const O_RDWR: u64   = 2;
+pub const O_CREAT: u64  = 0x40;
+pub const O_TRUNC: u64  = 0x200;
+pub const O_APPEND: u64 = 0x400;
 
 // Memory Syscalls
 pub const SYS_BRK:   u64 = 13;
+pub const SYS_MMAP:  u64 = 15;
+
+/// `mmap` flags. Mappings are always anonymous and zero-filled.
+pub const MAP_SHARED: u64    = 0x01;
+pub const MAP_PRIVATE: u64   = 0x02;
+pub const MAP_ANONYMOUS: u64 = 0x20;
+
+// Directory Syscalls
+pub const SYS_GETDENTS: u64 = 14;
+pub const SYS_CHDIR: u64 = 17;
+pub const SYS_GETCWD: u64 = 18;
+
+// File Metadata Syscalls
+pub const SYS_STAT: u64  = 19;
+pub const SYS_FSTAT: u64 = 20;
+pub const SYS_FTRUNCATE: u64 = 77;
+
+// Multiplexing Syscalls
+pub const SYS_POLL: u64 = 21;
+
+// Time Syscalls
+pub const SYS_CLOCK_GETTIME: u64 = 28;
+pub const SYS_NANOSLEEP: u64 = 35;
+
+// Signal Syscalls
+pub const SYS_KILL: u64 = 62;
+
+/// Signals `kill` can send. Neither can be caught: the target exits with
+/// status 128 + the signal number.
+pub const SIGKILL: u64 = 9;
+pub const SIGTERM: u64 = 15;
+
+// Power Syscalls
+pub const SYS_REBOOT: u64 = 169;
+
+/// `reboot` commands. Filesystems are flushed and unmounted first.
+pub const REBOOT_CMD_RESTART: u64   = 0x0123_4567;
+pub const REBOOT_CMD_HALT: u64      = 0xCDEF_0123;
+pub const REBOOT_CMD_POWER_OFF: u64 = 0x4321_FEDC;
+const REBOOT_MAGIC: u64 = 0xFEE1_DEAD;
+
+// Introspection Syscalls
+pub const SYS_GETSYSCALLS: u64 = 29;
+
+/// Clock ids for `clock_gettime`.
+pub const CLOCK_REALTIME: u64  = 0;
+pub const CLOCK_MONOTONIC: u64 = 1;
+
+/// Seconds plus nanoseconds, as filled in by `clock_gettime`.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct Timespec {
+    pub secs: u64,
+    pub nsecs: u64,
+}
+
+/// `d_type` values reported by `getdents`.
+pub const DT_DIR: u8 = 4;
+pub const DT_REG: u8 = 8;
+pub const DT_LNK: u8 = 10;
 
 pub fn exit(status: i32) -> ! {
     unsafe { syscall1(SYS_EXIT, status as u64) };
@@ -38,9 +117,22 @@ pub fn read(fd: usize, buf: &mut [u8]) -> isize {
     }
 }
 
-pub fn open(path: &str) -> isize {
+/// Opens `path` with one of `O_RDONLY`/`O_WRONLY`/`O_RDWR`, optionally or'ed
+/// with `O_CREAT`, `O_TRUNC` and `O_APPEND`. `-ENOENT` if the file is missing
+/// and `O_CREAT` is not given; `-EISDIR` when writing to a directory.
+pub fn open(path: &str, flags: u64) -> isize {
+    unsafe {
+        let res = syscall3(SYS_OPEN, path.as_ptr() as u64, path.len() as u64, flags);
+        res as isize
+    }
+}
+
+/// Moves the offset of `fd` and returns the new offset. Seeking past the
+/// end is allowed; a later write leaves a zero-filled gap. `-ESPIPE` on
+/// pipes and the console.
+pub fn lseek(fd: usize, offset: i64, whence: u64) -> isize {
     unsafe {
-        let res = syscall2(SYS_OPEN, path.as_ptr() as u64, path.len() as u64);
+        let res = syscall3(SYS_LSEEK, fd as u64, offset as u64, whence);
         res as isize
     }
 }
@@ -61,14 +153,75 @@ pub fn fork() -> isize {
 
 pub fn exec(path: &str) -> isize {
     unsafe {
-        let res = syscall2(SYS_EXEC, path.as_ptr() as u64, path.len() as u64);
+        let res = syscall3(SYS_EXEC, path.as_ptr() as u64, path.len() as u64, 0);
+        res as isize
+    }
+}
+
+/// Like `exec`, but passes `argv`: a NULL-terminated array of NUL-terminated
+/// strings. The new program inherits this one's environment.
+pub fn execv(path: &str, argv: *const *const u8) -> isize {
+    execve(path, argv, environ())
+}
+
+/// Like `execv`, with `envp` (NULL-terminated `NAME=value` strings, or null
+/// for none) as the new program's environment.
+pub fn execve(path: &str, argv: *const *const u8, envp: *const *const u8) -> isize {
+    unsafe {
+        let res = syscall4(SYS_EXEC, path.as_ptr() as u64, path.len() as u64, argv as u64, envp as u64);
         res as isize
     }
 }
 
+/// The process environment, as handed over by the kernel at startup.
+static ENVIRON: AtomicPtr<*const u8> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Called by `_start` with the envp array from the initial stack.
+pub fn set_environ(envp: *const *const u8) {
+    ENVIRON.store(envp as *mut *const u8, Ordering::Relaxed);
+}
+
+/// The NULL-terminated `NAME=value` array this process was started with
+/// (null if it has none).
+pub fn environ() -> *const *const u8 {
+    ENVIRON.load(Ordering::Relaxed)
+}
+
+/// Look up `name` in the environment.
+pub fn getenv(name: &str) -> Option<&'static str> {
+    let mut entry = environ();
+    if entry.is_null() {
+        return None;
+    }
+    unsafe {
+        while !(*entry).is_null() {
+            let bytes = core::slice::from_raw_parts(*entry, crate::string::strlen(*entry));
+            if let Some(value) = bytes.strip_prefix(name.as_bytes()).and_then(|rest| rest.strip_prefix(b"=")) {
+                return core::str::from_utf8(value).ok();
+            }
+            entry = entry.add(1);
+        }
+    }
+    None
+}
+
+/// Wait for child `pid` (-1 for any) to exit and return its wait status;
+/// decode it with the `wait` module.
 pub fn wait(pid: isize) -> isize {
+    let mut status = 0;
+    let res = waitpid(pid, &mut status, 0);
+    if res < 0 {
+        return res;
+    }
+    status as isize
+}
+
+/// Reap child `pid` (-1 for any), storing its wait status in `status`.
+/// Returns the child's PID, or 0 if `options` has `wait::WNOHANG` and it
+/// has not exited yet.
+pub fn waitpid(pid: isize, status: &mut u64, options: u64) -> isize {
     unsafe {
-        let res = syscall1(SYS_WAIT, pid as u64);
+        let res = syscall3(SYS_WAIT, pid as u64, options, status as *mut u64 as u64);
         res as isize
     }
 }
@@ -109,3 +262,144 @@ pub fn brk(addr: *mut u8) -> *mut u8 {
         res as *mut u8
     }
 }
+
+/// Maps `len` bytes of anonymous memory. With `MAP_SHARED` the region stays
+/// shared with children forked afterwards; `MAP_PRIVATE` gives each its own copy.
+/// Returns the address, or `-(errno)` cast to a pointer on failure.
+pub fn mmap(len: usize, flags: u64) -> *mut u8 {
+    unsafe {
+        let res = syscall2(SYS_MMAP, len as u64, flags);
+        res as *mut u8
+    }
+}
+
+/// Reads `clock` into `ts`. CLOCK_MONOTONIC counts from boot and only advances
+/// once per timer tick; CLOCK_REALTIME is Unix time with whole-second precision.
+pub fn clock_gettime(clock: u64, ts: &mut Timespec) -> isize {
+    unsafe {
+        let res = syscall2(SYS_CLOCK_GETTIME, clock, ts as *mut Timespec as u64);
+        res as isize
+    }
+}
+
+/// Sleeps for at least `req`, rounded up to whole timer ticks (10 ms at the
+/// default rate).
+/// The CPU goes to other processes meanwhile. Returns 0, or `-EINVAL` if
+/// `req.nsecs` is a second or more.
+pub fn nanosleep(req: &Timespec) -> isize {
+    unsafe {
+        let res = syscall1(SYS_NANOSLEEP, req as *const Timespec as u64);
+        res as isize
+    }
+}
+
+/// Sleeps for at least `ms` milliseconds.
+pub fn sleep_ms(ms: u64) -> isize {
+    nanosleep(&Timespec { secs: ms / 1000, nsecs: (ms % 1000) * 1_000_000 })
+}
+
+/// Reads directory records from a directory fd into `buf`.
+/// Each record is `d_ino: u64, d_reclen: u16, d_type: u8, d_name` (NUL-terminated).
+/// Returns the number of bytes filled, 0 at end of directory.
+pub fn getdents(fd: usize, buf: &mut [u8]) -> isize {
+    unsafe {
+        let res = syscall3(SYS_GETDENTS, fd as u64, buf.as_mut_ptr() as u64, buf.len() as u64);
+        res as isize
+    }
+}
+
+/// Changes the working directory. Relative paths, and those passed to
+/// `open` afterwards, start from the current one. `-ENOTDIR` if `path`
+/// isn't a directory.
+pub fn chdir(path: &str) -> isize {
+    unsafe {
+        let res = syscall2(SYS_CHDIR, path.as_ptr() as u64, path.len() as u64);
+        res as isize
+    }
+}
+
+/// Copies the absolute working directory, NUL-terminated, into `buf`.
+/// Returns the bytes written including the NUL, or `-ERANGE` if `buf` is too small.
+pub fn getcwd(buf: &mut [u8]) -> isize {
+    unsafe {
+        let res = syscall2(SYS_GETCWD, buf.as_mut_ptr() as u64, buf.len() as u64);
+        res as isize
+    }
+}
+
+/// Fills `st` with the inode number, type (`stat::DT_*`) and size of `path`,
+/// following symlinks. `-ENOENT` if it doesn't exist, `-ENOTDIR` if a
+/// directory in the path is a file.
+pub fn stat(path: &str, st: &mut Stat) -> isize {
+    unsafe {
+        let res = syscall3(SYS_STAT, path.as_ptr() as u64, path.len() as u64, st as *mut Stat as u64);
+        res as isize
+    }
+}
+
+/// Like `stat`, for an open descriptor. The console reports `DT_CHR` and
+/// pipes `DT_FIFO`.
+pub fn fstat(fd: usize, st: &mut Stat) -> isize {
+    unsafe {
+        let res = syscall2(SYS_FSTAT, fd as u64, st as *mut Stat as u64);
+        res as isize
+    }
+}
+
+/// Cuts the file open on `fd` down to `len` bytes, or extends it with zeros.
+/// `-EINVAL` unless `fd` is a regular file opened for writing.
+pub fn ftruncate(fd: usize, len: u64) -> isize {
+    unsafe {
+        let res = syscall2(SYS_FTRUNCATE, fd as u64, len);
+        res as isize
+    }
+}
+
+/// Waits until one of `fds` is ready (`poll::POLLIN`/`POLLOUT`) or
+/// `timeout` ticks pass: 0 only checks, negative waits indefinitely. Fills
+/// in every `revents` and returns how many entries have events, 0 on
+/// timeout. Closed descriptors report `POLLNVAL`.
+pub fn poll(fds: &mut [PollFd], timeout: i64) -> isize {
+    unsafe {
+        let res = syscall3(SYS_POLL, fds.as_mut_ptr() as u64, fds.len() as u64, timeout as u64);
+        res as isize
+    }
+}
+
+/// Sends `sig` to process `pid`; 0 only checks that it exists. `-ESRCH` if
+/// there is no such process, `-EPERM` for kernel tasks and init.
+pub fn kill(pid: isize, sig: u64) -> isize {
+    unsafe {
+        let res = syscall2(SYS_KILL, pid as u64, sig);
+        res as isize
+    }
+}
+
+/// Restarts, halts or powers off the machine (`REBOOT_CMD_*`). Returns
+/// `-EINVAL` for an unknown command; otherwise never returns.
+pub fn reboot(cmd: u64) -> isize {
+    unsafe {
+        let res = syscall2(SYS_REBOOT, REBOOT_MAGIC, cmd);
+        res as isize
+    }
+}
+
+/// Fills `buf` with the kernel's syscall bitmap (bit n set = syscall n exists),
+/// truncated to fit. Returns the bitmap's full size in bytes, or `-(errno)`.
+pub fn getsyscalls(buf: &mut [u8]) -> isize {
+    unsafe {
+        let res = syscall2(SYS_GETSYSCALLS, buf.as_mut_ptr() as u64, buf.len() as u64);
+        res as isize
+    }
+}
+
+/// Returns true if the running kernel implements syscall `n`.
+/// Kernels that predate SYS_GETSYSCALLS report nothing, so this is false.
+pub fn has_syscall(n: u64) -> bool {
+    let mut bitmap = [0u8; 32];
+    if getsyscalls(&mut bitmap) < 0 {
+        return false;
+    }
+    let byte = (n / 8) as usize;
+    byte < bitmap.len() && bitmap[byte] & (1 << (n % 8)) != 0
+}
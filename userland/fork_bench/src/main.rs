@@ -0,0 +1,65 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate atomiclibc;
+
+/// Fork/wait rounds to time. Kernel stacks and FPU areas of reaped children
+/// come back out of the scheduler's slab caches, so every round after the
+/// first should reuse them instead of growing the heap.
+const ROUNDS: u64 = 32;
+
+fn rdtsc() -> u64 {
+    let lo: u32;
+    let hi: u32;
+    unsafe { core::arch::asm!("rdtsc", out("eax") lo, out("edx") hi, options(nomem, nostack)); }
+    ((hi as u64) << 32) | lo as u64
+}
+
+/// Cycles from fork() until the exited child has been reaped.
+fn fork_wait_once() -> Option<u64> {
+    let start = rdtsc();
+    let pid = atomiclibc::unistd::fork();
+    if pid == 0 {
+        atomiclibc::unistd::exit(0);
+    }
+    if pid < 0 {
+        return None;
+    }
+    atomiclibc::unistd::wait(pid);
+    Some(rdtsc() - start)
+}
+
+#[no_mangle]
+pub extern "C" fn main(_argc: isize, _argv: *const *const u8) -> isize {
+    printf!("Timing %d fork+wait rounds...\n", ROUNDS);
+
+    // The first round may have to carve fresh slabs
+    let first = match fork_wait_once() {
+        Some(c) => c,
+        None => {
+            printf!("Fork failed in round 1!\n");
+            return -1;
+        }
+    };
+
+    let mut total = 0u64;
+    let mut best = u64::MAX;
+    for round in 1..ROUNDS {
+        match fork_wait_once() {
+            Some(c) => {
+                total += c;
+                best = best.min(c);
+            }
+            None => {
+                printf!("Fork failed in round %d!\n", round + 1);
+                return -1;
+            }
+        }
+    }
+
+    printf!("first fork:     %d cycles\n", first);
+    printf!("recycled forks: %d cycles avg, %d best\n", total / (ROUNDS - 1), best);
+    printf!("`slabtest` runs this and checks the fpu_state cache does not grow.\n");
+    0
+}
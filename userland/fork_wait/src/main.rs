@@ -24,7 +24,7 @@ pub extern "C" fn main(_argc: isize, _argv: *const *const u8) -> isize {
         // Parent
         printf!("I am the parent! Waiting for child %d to finish...\n", pid);
         let status = atomiclibc::unistd::wait(pid);
-        printf!("Child finished with status: %d\n", status);
+        printf!("Child finished with exit code: %d\n", atomiclibc::wait::exit_code(status as u64) as isize);
         0
     } else {
         printf!("Fork failed!\n");
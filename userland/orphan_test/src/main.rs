@@ -0,0 +1,27 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate atomiclibc;
+
+/// Parent exits without waiting; the orphaned child must be adopted and
+/// reaped by init (PID 1). Check with `ps` afterwards: no zombie should remain.
+#[no_mangle]
+pub extern "C" fn main(_argc: isize, _argv: *const *const u8) -> isize {
+    let pid = atomiclibc::unistd::fork();
+
+    if pid == 0 {
+        // Child: outlive the parent, then exit
+        for _ in 0..50 {
+            atomiclibc::unistd::yield_now();
+        }
+        printf!("Orphan child %d exiting, init should reap me.\n", atomiclibc::unistd::getpid());
+        atomiclibc::unistd::exit(7);
+    } else if pid > 0 {
+        printf!("Parent exiting without waiting for child %d.\n", pid);
+        0
+    } else {
+        printf!("Fork failed!\n");
+        -1
+    }
+}
@@ -8,6 +8,17 @@ extern crate atomiclibc;
 pub extern "C" fn main(_argc: isize, _argv: *const *const u8) -> isize {
     printf!("Starting pipe test...\n");
 
+    // Bad destination pointers must fail with EFAULT without using up fds:
+    // NULL, and a kernel address that is mapped but not user-accessible
+    for &bad in &[0u64, 0x100000] {
+        let res = unsafe { atomiclibc::syscall::syscall1(atomiclibc::unistd::SYS_PIPE, bad) } as isize;
+        if res != -atomiclibc::errno::EFAULT {
+            printf!("pipe(%x) returned %d, expected -EFAULT\n", bad, res);
+            return -1;
+        }
+    }
+    printf!("pipe(bad pointer) -> EFAULT: PASS\n");
+
     let mut fds = [0u32; 2];
     if atomiclibc::unistd::pipe(&mut fds) < 0 {
         printf!("Pipe failed!\n");
@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate atomiclibc;
+
+use atomiclibc::stdio::getline;
+use atomiclibc::unistd::write;
+
+/// Echo lines typed at the console back with their length, until "quit".
+/// Exercises the blocking console read: the kernel does the echo and
+/// Backspace editing, and each read returns one line with its '\n'.
+#[no_mangle]
+pub extern "C" fn main(_argc: isize, _argv: *const *const u8) -> isize {
+    let mut buf = [0u8; 128];
+    printf!("Type lines to echo back; 'quit' exits.\n");
+
+    loop {
+        write(1, b"> ");
+        let n = getline(&mut buf);
+        if n <= 0 {
+            printf!("read failed: %d\n", n);
+            return 1;
+        }
+        let line = &buf[..n as usize];
+        if line == b"quit\n" {
+            return 0;
+        }
+        printf!("%d bytes: ", n);
+        write(1, line);
+    }
+}
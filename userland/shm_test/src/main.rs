@@ -0,0 +1,50 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate atomiclibc;
+
+use atomiclibc::unistd::{self, MAP_ANONYMOUS, MAP_PRIVATE, MAP_SHARED};
+
+/// A MAP_SHARED page written by the child must be visible to the parent after
+/// `wait`, while a MAP_PRIVATE page keeps the parent's value.
+#[no_mangle]
+pub extern "C" fn main(_argc: isize, _argv: *const *const u8) -> isize {
+    let shared = unistd::mmap(4096, MAP_SHARED | MAP_ANONYMOUS) as *mut u64;
+    let private = unistd::mmap(4096, MAP_PRIVATE | MAP_ANONYMOUS) as *mut u64;
+    if (shared as isize) < 0 || (private as isize) < 0 {
+        printf!("mmap failed!\n");
+        return -1;
+    }
+
+    unsafe {
+        shared.write_volatile(1);
+        private.write_volatile(1);
+    }
+
+    let pid = unistd::fork();
+
+    if pid == 0 {
+        // Child: both pages start with the parent's contents
+        unsafe {
+            let ok = shared.read_volatile() == 1 && private.read_volatile() == 1;
+            shared.write_volatile(0xC0FFEE);
+            private.write_volatile(0xBAD);
+            unistd::exit(if ok { 0 } else { 1 });
+        }
+    } else if pid > 0 {
+        let status = unistd::wait(pid);
+        let (s, p) = unsafe { (shared.read_volatile(), private.read_volatile()) };
+
+        if status == 0 && s == 0xC0FFEE && p == 1 {
+            printf!("shm_test: PASS (shared=%x private=%x)\n", s, p);
+            0
+        } else {
+            printf!("shm_test: FAIL (child=%d shared=%x private=%x)\n", status, s, p);
+            1
+        }
+    } else {
+        printf!("Fork failed!\n");
+        -1
+    }
+}
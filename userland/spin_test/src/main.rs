@@ -0,0 +1,16 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate atomiclibc;
+
+/// Deliberately hangs without yielding or making syscalls.
+/// The kernel watchdog should warn about, then terminate, this process
+/// while the shell stays responsive.
+#[no_mangle]
+pub extern "C" fn main(_argc: isize, _argv: *const *const u8) -> isize {
+    printf!("Spinning forever (PID %d)...\n", atomiclibc::unistd::getpid());
+    loop {
+        core::hint::spin_loop();
+    }
+}
@@ -0,0 +1,535 @@
+#![no_std]
+#![no_main]
+
+#[macro_use]
+extern crate atomiclibc;
+
+use atomiclibc::errno::{E2BIG, EBADF, ECHILD, EFAULT, EINVAL, EISDIR, EMFILE, ENAMETOOLONG, ENOENT, ENOMEM, ENOTDIR, EPERM, ERANGE, ESRCH};
+use atomiclibc::poll::{PollFd, POLLHUP, POLLIN, POLLNVAL, POLLOUT};
+use atomiclibc::stat::{Stat, DT_CHR, DT_DIR, DT_REG};
+use atomiclibc::syscall::{syscall0, syscall2, syscall3};
+use atomiclibc::wait::{self, WNOHANG};
+use atomiclibc::unistd::{self, Timespec, CLOCK_MONOTONIC, MAP_ANONYMOUS, MAP_PRIVATE, O_APPEND, O_CREAT, O_RDONLY,
+    O_RDWR, O_TRUNC, O_WRONLY, SEEK_SET, SIGKILL, SIGTERM,
+    SYS_CLOCK_GETTIME, SYS_EXEC, SYS_GETSYSCALLS, SYS_NANOSLEEP, SYS_OPEN, SYS_STAT, SYS_WRITE};
+
+/// Length of the README the kernel seeds the root filesystem with.
+const README_SIZE: u64 = 50;
+/// A number no kernel version assigns.
+const BOGUS_SYSCALL: u64 = 999;
+/// Kernel PATH_MAX and argv limit (half the 16 KiB user stack).
+const PATH_MAX: u64 = 4096;
+const MAX_ARG_BYTES: usize = 8192;
+
+/// An argument one byte too long to ever fit in the argv area.
+static mut BIG_ARG: [u8; MAX_ARG_BYTES + 1] = [b'a'; MAX_ARG_BYTES + 1];
+
+#[no_mangle]
+pub extern "C" fn main(_argc: isize, _argv: *const *const u8) -> isize {
+    printf!("Starting syscall probe test...\n");
+
+    // Unknown numbers fail with ENOSYS, not a generic -1
+    let res = unsafe { syscall0(BOGUS_SYSCALL) } as isize;
+    if res != -atomiclibc::errno::ENOSYS {
+        printf!("syscall(%d) returned %d, expected -ENOSYS\n", BOGUS_SYSCALL, res);
+        return -1;
+    }
+    printf!("unknown syscall -> ENOSYS: PASS\n");
+
+    // Spamming them must not hang or crash (the kernel log is rate-limited)
+    for _ in 0..1000 {
+        unsafe { syscall0(BOGUS_SYSCALL) };
+    }
+    printf!("1000 unknown syscalls survived: PASS\n");
+
+    // The bitmap reports implemented syscalls and nothing else
+    if !unistd::has_syscall(SYS_WRITE) || !unistd::has_syscall(SYS_CLOCK_GETTIME)
+        || !unistd::has_syscall(SYS_GETSYSCALLS) || !unistd::has_syscall(SYS_NANOSLEEP) {
+        printf!("bitmap is missing an implemented syscall\n");
+        return -1;
+    }
+    if unistd::has_syscall(BOGUS_SYSCALL) || unistd::has_syscall(SYS_GETSYSCALLS + 1) {
+        printf!("bitmap reports a syscall that does not exist\n");
+        return -1;
+    }
+    printf!("syscall bitmap: PASS\n");
+
+    // A short buffer is filled as far as it goes and the full size is returned
+    let mut one = [0u8; 1];
+    let full = unistd::getsyscalls(&mut one);
+    if full < 1 || one[0] & 1 == 0 {
+        printf!("getsyscalls(1-byte buffer) returned %d\n", full);
+        return -1;
+    }
+    printf!("bitmap is %d bytes, truncation: PASS\n", full);
+
+    // Paths and argv are copied in with checks, not read through raw slices
+    let path = "/disk/hello.elf";
+    let res = unsafe { syscall2(SYS_OPEN, path.as_ptr() as u64, PATH_MAX + 1) } as isize;
+    if res != -ENAMETOOLONG {
+        printf!("open(len PATH_MAX+1) returned %d, expected -ENAMETOOLONG\n", res);
+        return -1;
+    }
+    let res = unsafe { syscall2(SYS_OPEN, 0x100000, 8) } as isize;
+    if res != -EFAULT {
+        printf!("open(kernel pointer) returned %d, expected -EFAULT\n", res);
+        return -1;
+    }
+    printf!("open path checks: PASS\n");
+
+    unsafe { BIG_ARG[MAX_ARG_BYTES] = 0; }
+    let argv = [path.as_ptr(), unsafe { core::ptr::addr_of!(BIG_ARG) as *const u8 }, core::ptr::null()];
+    let res = unsafe { syscall3(SYS_EXEC, path.as_ptr() as u64, path.len() as u64, argv.as_ptr() as u64) } as isize;
+    if res != -E2BIG {
+        printf!("exec(oversized argv) returned %d, expected -E2BIG\n", res);
+        return -1;
+    }
+    let bad_argv = [path.as_ptr(), 0x100000 as *const u8, core::ptr::null()];
+    let res = unsafe { syscall3(SYS_EXEC, path.as_ptr() as u64, path.len() as u64, bad_argv.as_ptr() as u64) } as isize;
+    if res != -EFAULT {
+        printf!("exec(kernel pointer in argv) returned %d, expected -EFAULT\n", res);
+        return -1;
+    }
+    printf!("exec argv checks: PASS\n");
+
+    // Sleeps last at least as long as asked; zero just yields
+    let mut before = Timespec::default();
+    let mut after = Timespec::default();
+    unistd::clock_gettime(CLOCK_MONOTONIC, &mut before);
+    let res = unistd::sleep_ms(100);
+    unistd::clock_gettime(CLOCK_MONOTONIC, &mut after);
+    let elapsed_ms = (after.secs * 1000 + after.nsecs / 1_000_000) - (before.secs * 1000 + before.nsecs / 1_000_000);
+    if res != 0 || elapsed_ms < 100 {
+        printf!("sleep_ms(100) returned %d after %d ms\n", res, elapsed_ms);
+        return -1;
+    }
+    if unistd::sleep_ms(0) != 0 {
+        printf!("sleep_ms(0) failed\n");
+        return -1;
+    }
+    let res = unistd::nanosleep(&Timespec { secs: 0, nsecs: 1_000_000_000 });
+    if res != -EINVAL {
+        printf!("nanosleep(nsecs = 1e9) returned %d, expected -EINVAL\n", res);
+        return -1;
+    }
+    printf!("nanosleep: PASS (100 ms took %d ms)\n", elapsed_ms);
+
+    // Working directory: relative opens start there, children inherit it
+    let mut buf = [0u8; 64];
+    if unistd::chdir("/disk") != 0 || !cwd_is(&mut buf, "/disk") {
+        printf!("chdir(/disk) did not change the cwd\n");
+        return -1;
+    }
+    let fd = unistd::open("hello.elf", O_RDONLY);
+    if fd < 0 {
+        printf!("open(hello.elf) relative to /disk returned %d\n", fd);
+        return -1;
+    }
+    unistd::close(fd as usize);
+    let pid = unistd::fork();
+    if pid == 0 {
+        unistd::exit(if cwd_is(&mut buf, "/disk") { 0 } else { 1 });
+    }
+    if pid < 0 || unistd::wait(pid) != 0 {
+        printf!("forked child did not inherit the cwd\n");
+        return -1;
+    }
+    if unistd::chdir("./../disk/..") != 0 || !cwd_is(&mut buf, "/") {
+        printf!("chdir(./../disk/..) did not normalize to /\n");
+        return -1;
+    }
+    let res = unistd::chdir("/README.md");
+    if res != -ENOTDIR || !cwd_is(&mut buf, "/") {
+        printf!("chdir(regular file) returned %d, expected -ENOTDIR\n", res);
+        return -1;
+    }
+    let res = unistd::chdir("/no/such/dir");
+    if res != -ENOENT {
+        printf!("chdir(missing) returned %d, expected -ENOENT\n", res);
+        return -1;
+    }
+    let res = unistd::getcwd(&mut buf[..1]);
+    if res != -ERANGE {
+        printf!("getcwd(1-byte buffer) returned %d, expected -ERANGE\n", res);
+        return -1;
+    }
+    printf!("chdir/getcwd: PASS\n");
+
+    // Open flags: access modes, O_CREAT, O_TRUNC and O_APPEND
+    let file = "/tmp/open_flags.txt";
+    let res = unistd::open("/tmp/no_such_file", O_RDONLY);
+    if res != -ENOENT {
+        printf!("open(missing, O_RDONLY) returned %d, expected -ENOENT\n", res);
+        return -1;
+    }
+    let res = unistd::open("/tmp/no_such_file", O_WRONLY | O_TRUNC);
+    if res != -ENOENT {
+        printf!("open(missing, O_WRONLY|O_TRUNC) returned %d, expected -ENOENT\n", res);
+        return -1;
+    }
+    let fd = unistd::open(file, O_WRONLY | O_CREAT | O_TRUNC);
+    if fd < 0 || unistd::write(fd as usize, b"hello") != 5 {
+        printf!("open(O_WRONLY|O_CREAT|O_TRUNC) then write failed: %d\n", fd);
+        return -1;
+    }
+    let res = unistd::read(fd as usize, &mut buf);
+    unistd::close(fd as usize);
+    if res != -EBADF {
+        printf!("read on an O_WRONLY fd returned %d, expected -EBADF\n", res);
+        return -1;
+    }
+    let fd = unistd::open(file, O_RDONLY);
+    let res = unistd::write(fd as usize, b"x");
+    let n = unistd::read(fd as usize, &mut buf);
+    unistd::close(fd as usize);
+    if fd < 0 || res != -EBADF || n != 5 || &buf[..5] != b"hello" {
+        printf!("O_RDONLY: fd %d, write returned %d, read %d bytes\n", fd, res, n);
+        return -1;
+    }
+    // Appends land at the end even after seeking back
+    let fd = unistd::open(file, O_WRONLY | O_APPEND);
+    unistd::lseek(fd as usize, 0, SEEK_SET);
+    let res = unistd::write(fd as usize, b" world");
+    unistd::close(fd as usize);
+    if fd < 0 || res != 6 {
+        printf!("O_APPEND write failed: fd %d, wrote %d\n", fd, res);
+        return -1;
+    }
+    // O_CREAT on an existing file keeps its contents; O_RDWR reads and writes
+    let fd = unistd::open(file, O_RDWR | O_CREAT);
+    let res = unistd::write(fd as usize, b"J");
+    unistd::lseek(fd as usize, 0, SEEK_SET);
+    let n = unistd::read(fd as usize, &mut buf);
+    unistd::close(fd as usize);
+    if fd < 0 || res != 1 || n != 11 || &buf[..11] != b"Jello world" {
+        printf!("O_RDWR|O_CREAT: fd %d, wrote %d, read %d bytes\n", fd, res, n);
+        return -1;
+    }
+    let fd = unistd::open(file, O_RDWR | O_TRUNC);
+    let n = unistd::read(fd as usize, &mut buf);
+    unistd::close(fd as usize);
+    if fd < 0 || n != 0 {
+        printf!("O_TRUNC left %d bytes (fd %d)\n", n, fd);
+        return -1;
+    }
+    // With the fd table full, O_CREAT and O_TRUNC must not touch anything
+    let fd = unistd::open(file, O_WRONLY);
+    let res = unistd::write(fd as usize, b"kept");
+    unistd::close(fd as usize);
+    if fd < 0 || res != 4 {
+        printf!("rewriting open_flags.txt failed: fd %d, wrote %d\n", fd, res);
+        return -1;
+    }
+    let mut held = [-1isize; 64];
+    let mut count = 0;
+    while count < held.len() {
+        let fd = unistd::open(file, O_RDONLY);
+        if fd < 0 {
+            break;
+        }
+        held[count] = fd;
+        count += 1;
+    }
+    let trunc = unistd::open(file, O_WRONLY | O_TRUNC);
+    let created = unistd::open("/tmp/emfile_new.txt", O_WRONLY | O_CREAT);
+    for &fd in &held[..count] {
+        unistd::close(fd as usize);
+    }
+    let fd = unistd::open(file, O_RDONLY);
+    let n = unistd::read(fd as usize, &mut buf);
+    unistd::close(fd as usize);
+    let missing = unistd::open("/tmp/emfile_new.txt", O_RDONLY);
+    if trunc != -EMFILE || created != -EMFILE || n != 4 || missing != -ENOENT {
+        printf!("full fd table: O_TRUNC %d, O_CREAT %d, %d bytes left, new file %d\n", trunc, created, n, missing);
+        return -1;
+    }
+    let res = unistd::open(file, 3);
+    if res != -EINVAL {
+        printf!("open(access mode 3) returned %d, expected -EINVAL\n", res);
+        return -1;
+    }
+    let res = unistd::open("/tmp", O_WRONLY);
+    if res != -EISDIR {
+        printf!("open(directory, O_WRONLY) returned %d, expected -EISDIR\n", res);
+        return -1;
+    }
+    printf!("open flags: PASS\n");
+
+    // File metadata: the seeded README, directories, descriptors and errors
+    let mut st = Stat::default();
+    if unistd::stat("/README.md", &mut st) != 0 || st.file_type != DT_REG || st.size != README_SIZE {
+        printf!("stat(/README.md): type %d, size %d, expected %d\n", st.file_type as isize, st.size as isize, README_SIZE as isize);
+        return -1;
+    }
+    let readme_ino = st.ino;
+    let fd = unistd::open("/README.md", O_RDONLY);
+    let mut fst = Stat::default();
+    let res = unistd::fstat(fd as usize, &mut fst);
+    unistd::close(fd as usize);
+    if fd < 0 || res != 0 || fst != st {
+        printf!("fstat(/README.md) returned %d and disagrees with stat\n", res);
+        return -1;
+    }
+    if unistd::stat("/tmp", &mut st) != 0 || st.file_type != DT_DIR || st.ino == readme_ino {
+        printf!("stat(/tmp) did not report a directory\n");
+        return -1;
+    }
+    if unistd::fstat(1, &mut st) != 0 || st.file_type != DT_CHR {
+        printf!("fstat(stdout) did not report the console\n");
+        return -1;
+    }
+    let res = unistd::stat("/no/such/file", &mut st);
+    if res != -ENOENT {
+        printf!("stat(missing) returned %d, expected -ENOENT\n", res);
+        return -1;
+    }
+    let res = unistd::stat("/README.md/child", &mut st);
+    if res != -ENOTDIR {
+        printf!("stat(file/child) returned %d, expected -ENOTDIR\n", res);
+        return -1;
+    }
+    let res = unsafe { syscall3(SYS_STAT, path.as_ptr() as u64, path.len() as u64, 0x100000) } as isize;
+    if res != -EFAULT {
+        printf!("stat(kernel pointer) returned %d, expected -EFAULT\n", res);
+        return -1;
+    }
+    let res = unistd::fstat(63, &mut st);
+    if res != -EBADF {
+        printf!("fstat(closed fd) returned %d, expected -EBADF\n", res);
+        return -1;
+    }
+    printf!("stat/fstat: PASS\n");
+
+    // ftruncate shrinks, zero-fills when growing, and needs a writable file
+    let file = "/tmp/truncate.txt";
+    let fd = unistd::open(file, O_RDWR | O_CREAT | O_TRUNC);
+    let res = unistd::write(fd as usize, b"truncate me");
+    if fd < 0 || res != 11 || unistd::ftruncate(fd as usize, 5) != 0
+        || unistd::fstat(fd as usize, &mut st) != 0 || st.size != 5 {
+        printf!("ftruncate(5): fd %d, size %d\n", fd, st.size as isize);
+        return -1;
+    }
+    let res = unistd::ftruncate(fd as usize, 8);
+    unistd::lseek(fd as usize, 0, SEEK_SET);
+    let n = unistd::read(fd as usize, &mut buf);
+    unistd::close(fd as usize);
+    if res != 0 || n != 8 || &buf[..8] != b"trunc\0\0\0" {
+        printf!("ftruncate(8) returned %d, read back %d bytes\n", res, n);
+        return -1;
+    }
+    let fd = unistd::open(file, O_RDONLY);
+    let res = unistd::ftruncate(fd as usize, 0);
+    unistd::close(fd as usize);
+    if res != -EINVAL {
+        printf!("ftruncate on an O_RDONLY fd returned %d, expected -EINVAL\n", res);
+        return -1;
+    }
+    let res = unistd::ftruncate(63, 0);
+    if res != -EBADF {
+        printf!("ftruncate(closed fd) returned %d, expected -EBADF\n", res);
+        return -1;
+    }
+    printf!("ftruncate: PASS\n");
+
+    // Anonymous memory: page-aligned, zeroed, writable, and sizes round up to pages
+    let page = unistd::mmap(1, MAP_PRIVATE | MAP_ANONYMOUS);
+    let next = unistd::mmap(4096, MAP_PRIVATE | MAP_ANONYMOUS);
+    if (page as isize) < 0 || (next as isize) < 0 || page as usize % 4096 != 0 || next as usize != page as usize + 4096 {
+        printf!("mmap returned %x then %x\n", page as usize, next as usize);
+        return -1;
+    }
+    let words = page as *mut u64;
+    unsafe {
+        if words.read_volatile() != 0 || words.add(511).read_volatile() != 0 {
+            printf!("mmap page is not zeroed\n");
+            return -1;
+        }
+        words.write_volatile(0x1234_5678);
+        words.add(511).write_volatile(0x9ABC);
+    }
+    let pid = unistd::fork();
+    if pid == 0 {
+        let ok = unsafe { words.read_volatile() == 0x1234_5678 && words.add(511).read_volatile() == 0x9ABC };
+        unistd::exit(if ok { 0 } else { 1 });
+    }
+    if pid < 0 || unistd::wait(pid) != 0 {
+        printf!("forked child did not inherit the mmap page\n");
+        return -1;
+    }
+    let res = unistd::mmap(0, MAP_PRIVATE | MAP_ANONYMOUS) as isize;
+    if res != -EINVAL {
+        printf!("mmap(0) returned %d, expected -EINVAL\n", res);
+        return -1;
+    }
+    // Exhausting physical memory fails cleanly; the child's pages go back on exit
+    let pid = unistd::fork();
+    if pid == 0 {
+        loop {
+            let res = unistd::mmap(32 * 1024 * 1024, MAP_PRIVATE | MAP_ANONYMOUS) as isize;
+            if res < 0 {
+                unistd::exit(if res == -ENOMEM { 0 } else { 1 });
+            }
+        }
+    }
+    if pid < 0 || unistd::wait(pid) != 0 {
+        printf!("mmap until out of memory did not fail with -ENOMEM\n");
+        return -1;
+    }
+    printf!("mmap: PASS\n");
+
+    // SIGTERM reaches a child spinning in user mode and one making syscalls
+    let spinner = unistd::fork();
+    if spinner == 0 {
+        loop { core::hint::spin_loop(); }
+    }
+    if !killed_by(spinner, SIGTERM) {
+        return -1;
+    }
+    let sleeper = unistd::fork();
+    if sleeper == 0 {
+        loop { unistd::sleep_ms(10); }
+    }
+    if !killed_by(sleeper, SIGTERM) {
+        return -1;
+    }
+    // SIGKILL wakes a child blocked reading an empty pipe
+    let mut fds = [0u32; 2];
+    if unistd::pipe(&mut fds) != 0 {
+        printf!("pipe failed\n");
+        return -1;
+    }
+    let reader = unistd::fork();
+    if reader == 0 {
+        let mut byte = [0u8; 1];
+        unistd::read(fds[0] as usize, &mut byte);
+        unistd::exit(0);
+    }
+    let blocked_ok = killed_by(reader, SIGKILL);
+    unistd::close(fds[0] as usize);
+    unistd::close(fds[1] as usize);
+    if !blocked_ok {
+        return -1;
+    }
+    let res = unistd::kill(99999, SIGTERM);
+    if res != -ESRCH {
+        printf!("kill(nonexistent pid) returned %d, expected -ESRCH\n", res);
+        return -1;
+    }
+    let res = unistd::kill(1, SIGKILL);
+    if res != -EPERM {
+        printf!("kill(init) returned %d, expected -EPERM\n", res);
+        return -1;
+    }
+    let res = unistd::kill(unistd::getpid() as isize, 7);
+    if res != -EINVAL {
+        printf!("kill(self, 7) returned %d, expected -EINVAL\n", res);
+        return -1;
+    }
+    if unistd::kill(unistd::getpid() as isize, 0) != 0 {
+        printf!("kill(self, 0) failed\n");
+        return -1;
+    }
+    printf!("kill/signals: PASS\n");
+
+    // poll: pipe ends report readiness, closed descriptors POLLNVAL, and a
+    // blocked poll wakes when a child writes
+    if unistd::pipe(&mut fds) != 0 {
+        printf!("pipe failed\n");
+        return -1;
+    }
+    let mut set = [
+        PollFd { fd: fds[0] as i32, events: POLLIN, revents: 0 },
+        PollFd { fd: fds[1] as i32, events: POLLOUT, revents: 0 },
+        PollFd { fd: 63, events: POLLIN, revents: 0 },
+        PollFd { fd: -1, events: POLLIN, revents: 0 },
+    ];
+    let res = unistd::poll(&mut set, 0);
+    if res != 2 || set[0].revents != 0 || set[1].revents != POLLOUT || set[2].revents != POLLNVAL || set[3].revents != 0 {
+        printf!("poll(empty pipe) returned %d, revents %d %d %d\n", res,
+            set[0].revents as isize, set[1].revents as isize, set[2].revents as isize);
+        return -1;
+    }
+    let res = unistd::poll(&mut set[..1], 2);
+    if res != 0 {
+        printf!("poll(empty pipe, 2 ticks) returned %d, expected a timeout\n", res);
+        return -1;
+    }
+    let writer = unistd::fork();
+    if writer == 0 {
+        unistd::sleep_ms(100);
+        unistd::write(fds[1] as usize, b"x");
+        unistd::exit(0);
+    }
+    let res = unistd::poll(&mut set[..1], -1);
+    unistd::wait(writer);
+    if writer < 0 || res != 1 || set[0].revents != POLLIN {
+        printf!("poll(pipe, no timeout) returned %d, revents %d\n", res, set[0].revents as isize);
+        return -1;
+    }
+    unistd::close(fds[1] as usize);
+    let res = unistd::poll(&mut set[..1], 0);
+    unistd::close(fds[0] as usize);
+    if res != 1 || set[0].revents != POLLIN | POLLHUP {
+        printf!("poll(pipe without writers) returned %d, revents %d\n", res, set[0].revents as isize);
+        return -1;
+    }
+    printf!("poll: PASS\n");
+
+    // waitpid: WNOHANG before the child is done, then its exit code decoded
+    let pid = unistd::fork();
+    if pid == 0 {
+        unistd::sleep_ms(100);
+        unistd::exit(42);
+    }
+    let mut status = 0;
+    let res = unistd::waitpid(pid, &mut status, WNOHANG);
+    if pid < 0 || res != 0 {
+        printf!("waitpid(running child, WNOHANG) returned %d, expected 0\n", res);
+        return -1;
+    }
+    let res = unistd::waitpid(pid, &mut status, 0);
+    if res != pid || !wait::exited(status) || wait::exit_code(status) != 42 {
+        printf!("waitpid returned %d with status %x, expected exit code 42\n", res, status as isize);
+        return -1;
+    }
+    let res = unistd::waitpid(pid, &mut status, WNOHANG);
+    if res != -ECHILD {
+        printf!("waitpid(reaped child) returned %d, expected -ECHILD\n", res);
+        return -1;
+    }
+    printf!("waitpid: PASS\n");
+
+    // reboot: bad magic or command is refused, and the machine stays up
+    let res = unistd::reboot(0);
+    let bad_magic = unsafe { syscall2(unistd::SYS_REBOOT, 0, unistd::REBOOT_CMD_POWER_OFF) } as isize;
+    if res != -EINVAL || bad_magic != -EINVAL {
+        printf!("reboot(bad command) returned %d, bad magic %d, expected -EINVAL\n", res, bad_magic);
+        return -1;
+    }
+    printf!("reboot argument checks: PASS\n");
+
+    printf!("Syscall probe test completed.\n");
+    0
+}
+
+/// True if getcwd succeeds and returns exactly `expected`.
+fn cwd_is(buf: &mut [u8], expected: &str) -> bool {
+    let n = unistd::getcwd(buf);
+    n > 0 && &buf[..n as usize - 1] == expected.as_bytes() && buf[n as usize - 1] == 0
+}
+
+/// Let `pid` get going, send it `sig` and check it reports being ended by `sig`.
+fn killed_by(pid: isize, sig: u64) -> bool {
+    if pid < 0 {
+        printf!("fork failed: %d\n", pid);
+        return false;
+    }
+    unistd::sleep_ms(100);
+    let res = unistd::kill(pid, sig);
+    let status = unistd::wait(pid);
+    if res != 0 || status < 0 || !wait::signaled(status as u64) || wait::term_signal(status as u64) as u64 != sig {
+        printf!("signal %d: kill returned %d, child exit status %d\n", sig, res, status);
+        return false;
+    }
+    true
+}